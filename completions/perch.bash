@@ -0,0 +1,113 @@
+# bash completion for perch
+# Install: perch completions bash > ~/.local/share/bash-completion/completions/perch
+
+_perch() {
+    local cur prev words cword
+    _init_completion 2>/dev/null || {
+        cur="${COMP_WORDS[COMP_CWORD]}"
+        prev="${COMP_WORDS[COMP_CWORD-1]}"
+        words=("${COMP_WORDS[@]}")
+        cword=$COMP_CWORD
+    }
+
+    local commands="auth post schedule timeline accounts export follow unfollow mute unmute block unblock config logout completions demo help version"
+    local networks="mastodon bluesky"
+
+    if [[ $cword -eq 1 ]]; then
+        COMPREPLY=($(compgen -W "$commands --help --version --demo --config-dir --profile" -- "$cur"))
+        return
+    fi
+
+    case "$prev" in
+        --to|-t)
+            COMPREPLY=($(compgen -W "$networks mastodon,bluesky" -- "$cur"))
+            return
+            ;;
+        --media|-m|--image)
+            COMPREPLY=($(compgen -f -- "$cur"))
+            return
+            ;;
+        --repeat|-r)
+            COMPREPLY=($(compgen -W "daily weekly" -- "$cur"))
+            return
+            ;;
+        --format|-f)
+            COMPREPLY=($(compgen -W "json csv" -- "$cur"))
+            return
+            ;;
+        --network|-n)
+            COMPREPLY=($(compgen -W "$networks" -- "$cur"))
+            return
+            ;;
+        --out|-o)
+            COMPREPLY=($(compgen -f -- "$cur"))
+            return
+            ;;
+        --snapshot|--config-dir)
+            COMPREPLY=($(compgen -d -- "$cur"))
+            return
+            ;;
+        --schedule|-s|--at|--cw|--alt|--as|--text|--interval|-i|--limit|-l|--theme|--profile)
+            return
+            ;;
+    esac
+
+    case "${words[1]}" in
+        auth)
+            if [[ $cword -eq 2 ]]; then
+                COMPREPLY=($(compgen -W "$networks" -- "$cur"))
+            else
+                COMPREPLY=($(compgen -W "--oob" -- "$cur"))
+            fi
+            ;;
+        post)
+            COMPREPLY=($(compgen -W "--to -t --schedule -s --at --media -m --cw --alt --as --repeat -r --server-side --thread" -- "$cur"))
+            ;;
+        schedule|scheduled)
+            if [[ $cword -eq 2 ]]; then
+                COMPREPLY=($(compgen -W "list edit cancel retry run daemon" -- "$cur"))
+                return
+            fi
+            case "${words[2]}" in
+                edit) COMPREPLY=($(compgen -W "--text --to -t --schedule -s" -- "$cur")) ;;
+                daemon|watch) COMPREPLY=($(compgen -W "--interval -i --once" -- "$cur")) ;;
+            esac
+            ;;
+        timeline|tl)
+            COMPREPLY=($(compgen -W "$networks --limit -l" -- "$cur"))
+            ;;
+        accounts)
+            if [[ $cword -eq 2 ]]; then
+                COMPREPLY=($(compgen -W "list export import" -- "$cur"))
+            elif [[ ${words[2]} == export && $cur == -* ]]; then
+                COMPREPLY=($(compgen -W "--with-credentials" -- "$cur"))
+            else
+                COMPREPLY=($(compgen -f -- "$cur"))
+            fi
+            ;;
+        export)
+            COMPREPLY=($(compgen -W "--format -f --network -n --out -o" -- "$cur"))
+            ;;
+        follow|unfollow|mute|unmute|block|unblock)
+            [[ $cword -gt 2 ]] && COMPREPLY=($(compgen -W "--as" -- "$cur"))
+            ;;
+        config)
+            if [[ $cword -eq 2 ]]; then
+                COMPREPLY=($(compgen -W "path show get set" -- "$cur"))
+            elif [[ $cword -eq 3 && ( ${words[2]} == get || ${words[2]} == set ) ]]; then
+                COMPREPLY=($(compgen -W "theme color post_limit refresh_interval_secs default_post_networks streaming show_avatars layout timeline_width mouse image_cache_max_mb cache_max_posts timezone absolute_times animate_images account_colors missing_alt_text check_for_updates log_file proxy root_certificate" -- "$cur"))
+            fi
+            ;;
+        logout)
+            [[ $cword -eq 2 ]] && COMPREPLY=($(compgen -W "$networks --all" -- "$cur"))
+            ;;
+        completions)
+            [[ $cword -eq 2 ]] && COMPREPLY=($(compgen -W "bash zsh fish powershell" -- "$cur"))
+            ;;
+        demo|--demo)
+            COMPREPLY=($(compgen -W "--theme --snapshot" -- "$cur"))
+            ;;
+    esac
+}
+
+complete -F _perch perch
@@ -0,0 +1,97 @@
+# fish completion for perch
+# Install: perch completions fish > ~/.config/fish/completions/perch.fish
+
+set -l commands auth post schedule timeline accounts export follow unfollow mute unmute block unblock config logout completions demo help version
+set -l networks mastodon bluesky
+
+complete -c perch -f
+
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a auth -d 'Authenticate with a network'
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a post -d 'Post to networks'
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a schedule -d 'Manage scheduled posts'
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a timeline -d 'Show timeline'
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a accounts -d 'Manage configured accounts'
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a export -d 'Export cached posts'
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a follow -d 'Follow an account'
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a unfollow -d 'Unfollow an account'
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a mute -d 'Hide posts from an account'
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a unmute -d 'Unmute an account'
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a block -d 'Block an account'
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a unblock -d 'Unblock an account'
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a config -d 'View or change settings'
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a logout -d 'Remove an account'
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a completions -d 'Print a shell completion script'
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a demo -d 'Launch the TUI with sample data'
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a help -d 'Show help'
+complete -c perch -n "not __fish_seen_subcommand_from $commands" -a version -d 'Show version'
+
+# Global options
+complete -c perch -l config-dir -x -a "(__fish_complete_directories)" -d 'Keep config and data in this directory'
+complete -c perch -l profile -x -d 'Use a separate named setup'
+
+# auth and logout
+complete -c perch -n "__fish_seen_subcommand_from auth" -a "$networks"
+complete -c perch -n "__fish_seen_subcommand_from auth" -l oob -d 'Paste the authorization code instead'
+complete -c perch -n "__fish_seen_subcommand_from logout" -a "$networks"
+complete -c perch -n "__fish_seen_subcommand_from logout" -l all -s a -d 'Remove every account'
+
+# post
+complete -c perch -n "__fish_seen_subcommand_from post" -s t -l to -x -a "$networks mastodon,bluesky" -d 'Comma-separated networks'
+complete -c perch -n "__fish_seen_subcommand_from post" -s s -l schedule -x -d 'Schedule post for later'
+complete -c perch -n "__fish_seen_subcommand_from post" -l at -x -d 'Schedule post for later'
+complete -c perch -n "__fish_seen_subcommand_from post" -s m -l media -r -F -d 'Attach an image'
+complete -c perch -n "__fish_seen_subcommand_from post" -l alt -x -d 'Alt text for the preceding image'
+complete -c perch -n "__fish_seen_subcommand_from post" -l cw -x -d 'Content warning'
+complete -c perch -n "__fish_seen_subcommand_from post" -l as -x -d 'Post from this account'
+complete -c perch -n "__fish_seen_subcommand_from post" -s r -l repeat -x -a "daily weekly" -d 'Repeat a scheduled post'
+complete -c perch -n "__fish_seen_subcommand_from post" -l server-side -d 'Have the Mastodon server publish a scheduled post'
+complete -c perch -n "__fish_seen_subcommand_from post" -l thread -d 'Post as a thread where it is too long'
+
+# schedule
+set -l schedule_commands list edit cancel retry run daemon
+complete -c perch -n "__fish_seen_subcommand_from schedule; and not __fish_seen_subcommand_from $schedule_commands" -a list -d 'List pending and failed scheduled posts'
+complete -c perch -n "__fish_seen_subcommand_from schedule; and not __fish_seen_subcommand_from $schedule_commands" -a edit -d 'Change a pending scheduled post'
+complete -c perch -n "__fish_seen_subcommand_from schedule; and not __fish_seen_subcommand_from $schedule_commands" -a cancel -d 'Cancel a scheduled post'
+complete -c perch -n "__fish_seen_subcommand_from schedule; and not __fish_seen_subcommand_from $schedule_commands" -a retry -d 'Try a failed scheduled post again'
+complete -c perch -n "__fish_seen_subcommand_from schedule; and not __fish_seen_subcommand_from $schedule_commands" -a run -d 'Process due scheduled posts'
+complete -c perch -n "__fish_seen_subcommand_from schedule; and not __fish_seen_subcommand_from $schedule_commands" -a daemon -d 'Run continuously, posting as posts come due'
+complete -c perch -n "__fish_seen_subcommand_from edit" -l text -x -d 'New content'
+complete -c perch -n "__fish_seen_subcommand_from edit" -s t -l to -x -a "$networks mastodon,bluesky" -d 'New networks'
+complete -c perch -n "__fish_seen_subcommand_from edit" -s s -l schedule -x -d 'New time'
+complete -c perch -n "__fish_seen_subcommand_from daemon" -s i -l interval -x -d 'Longest wait between checks, in seconds'
+complete -c perch -n "__fish_seen_subcommand_from daemon" -l once -d 'Post what is due now, then exit'
+
+# timeline
+complete -c perch -n "__fish_seen_subcommand_from timeline" -a "$networks"
+complete -c perch -n "__fish_seen_subcommand_from timeline" -s l -l limit -x -d 'Number of posts'
+
+# accounts
+set -l accounts_commands list export import
+complete -c perch -n "__fish_seen_subcommand_from accounts; and not __fish_seen_subcommand_from $accounts_commands" -a list -d 'List configured accounts'
+complete -c perch -n "__fish_seen_subcommand_from accounts; and not __fish_seen_subcommand_from $accounts_commands" -a export -d 'Write accounts to a file for another machine'
+complete -c perch -n "__fish_seen_subcommand_from accounts; and not __fish_seen_subcommand_from $accounts_commands" -a import -d 'Add the accounts in an export file'
+complete -c perch -n "__fish_seen_subcommand_from accounts; and __fish_seen_subcommand_from export import" -F
+complete -c perch -n "__fish_seen_subcommand_from accounts; and __fish_seen_subcommand_from export" -l with-credentials -d 'Include logins, encrypted with a passphrase'
+
+# export
+complete -c perch -n "__fish_seen_subcommand_from export; and not __fish_seen_subcommand_from accounts" -s f -l format -x -a "json csv" -d 'Export format'
+complete -c perch -n "__fish_seen_subcommand_from export; and not __fish_seen_subcommand_from accounts" -s n -l network -x -a "$networks" -d 'Only this network'
+complete -c perch -n "__fish_seen_subcommand_from export; and not __fish_seen_subcommand_from accounts" -s o -l out -r -F -d 'Write to a file'
+
+# follow, mute and block
+complete -c perch -n "__fish_seen_subcommand_from follow unfollow mute unmute block unblock" -l as -x -d 'Act from this account'
+
+# config
+set -l config_commands path show get set
+complete -c perch -n "__fish_seen_subcommand_from config; and not __fish_seen_subcommand_from $config_commands" -a path -d 'Print the config file location'
+complete -c perch -n "__fish_seen_subcommand_from config; and not __fish_seen_subcommand_from $config_commands" -a show -d 'Print the current settings'
+complete -c perch -n "__fish_seen_subcommand_from config; and not __fish_seen_subcommand_from $config_commands" -a get -d 'Print one setting'
+complete -c perch -n "__fish_seen_subcommand_from config; and not __fish_seen_subcommand_from $config_commands" -a set -d 'Change one setting'
+complete -c perch -n "__fish_seen_subcommand_from config; and __fish_seen_subcommand_from get set" -a "theme color post_limit refresh_interval_secs default_post_networks streaming show_avatars layout timeline_width mouse image_cache_max_mb cache_max_posts timezone absolute_times animate_images account_colors missing_alt_text check_for_updates log_file proxy root_certificate"
+
+# completions
+complete -c perch -n "__fish_seen_subcommand_from completions" -a "bash zsh fish powershell"
+
+# demo
+complete -c perch -n "__fish_seen_subcommand_from demo" -l theme -x -d 'Theme to show it in'
+complete -c perch -n "__fish_seen_subcommand_from demo" -l snapshot -x -a "(__fish_complete_directories)" -d 'Write each screen to a directory instead'
@@ -0,0 +1,74 @@
+# PowerShell completion for perch
+# Install: perch completions powershell >> $PROFILE
+
+Register-ArgumentCompleter -Native -CommandName perch -ScriptBlock {
+    param($wordToComplete, $commandAst, $cursorPosition)
+
+    $words = @($commandAst.CommandElements | ForEach-Object { $_.ToString() })
+    # The word being completed is not in the list yet if it's empty
+    if ($wordToComplete -eq '') { $words += '' }
+    $position = $words.Count - 1
+    $previous = if ($position -ge 1) { $words[$position - 1] } else { '' }
+
+    $networks = @('mastodon', 'bluesky')
+    $candidates = @()
+
+    if ($position -eq 1) {
+        $candidates = @('auth', 'post', 'schedule', 'timeline', 'accounts', 'export', 'follow', 'unfollow',
+            'mute', 'unmute', 'block', 'unblock', 'config', 'logout', 'completions', 'demo', 'help', 'version', '--help', '--version', '--config-dir', '--profile')
+    }
+    elseif ($previous -in @('--to', '-t')) {
+        $candidates = $networks + @('mastodon,bluesky')
+    }
+    elseif ($previous -in @('--repeat', '-r')) {
+        $candidates = @('daily', 'weekly')
+    }
+    elseif ($previous -in @('--format', '-f')) {
+        $candidates = @('json', 'csv')
+    }
+    elseif ($previous -in @('--network', '-n')) {
+        $candidates = $networks
+    }
+    else {
+        switch ($words[1]) {
+            'auth' { if ($position -eq 2) { $candidates = $networks } else { $candidates = @('--oob') } }
+            'post' {
+                $candidates = @('--to', '-t', '--schedule', '-s', '--at', '--media', '-m',
+                    '--cw', '--alt', '--as', '--repeat', '-r', '--server-side', '--thread')
+            }
+            { $_ -in @('schedule', 'scheduled') } {
+                if ($position -eq 2) {
+                    $candidates = @('list', 'edit', 'cancel', 'retry', 'run', 'daemon')
+                }
+                elseif ($words[2] -eq 'edit') {
+                    $candidates = @('--text', '--to', '-t', '--schedule', '-s')
+                }
+                elseif ($words[2] -in @('daemon', 'watch')) {
+                    $candidates = @('--interval', '-i', '--once')
+                }
+            }
+            { $_ -in @('timeline', 'tl') } { $candidates = $networks + @('--limit', '-l') }
+            'accounts' {
+                if ($position -eq 2) { $candidates = @('list', 'export', 'import') }
+                elseif ($words[2] -eq 'export') { $candidates = @('--with-credentials') }
+            }
+            'export' { $candidates = @('--format', '-f', '--network', '-n', '--out', '-o') }
+            { $_ -in @('follow', 'unfollow', 'mute', 'unmute', 'block', 'unblock') } { if ($position -ge 3) { $candidates = @('--as') } }
+            'config' {
+                if ($position -eq 2) { $candidates = @('path', 'show', 'get', 'set') }
+                elseif ($position -eq 3 -and $words[2] -in @('get', 'set')) {
+                    $candidates = @('theme', 'color', 'post_limit', 'refresh_interval_secs', 'default_post_networks', 'streaming', 'show_avatars', 'layout', 'timeline_width', 'mouse', 'image_cache_max_mb', 'cache_max_posts', 'timezone', 'absolute_times', 'animate_images', 'account_colors', 'missing_alt_text', 'check_for_updates', 'log_file', 'proxy', 'root_certificate')
+                }
+            }
+            'logout' { if ($position -eq 2) { $candidates = $networks + @('--all') } }
+            'completions' {
+                if ($position -eq 2) { $candidates = @('bash', 'zsh', 'fish', 'powershell') }
+            }
+            { $_ -in @('demo', '--demo') } { $candidates = @('--theme', '--snapshot') }
+        }
+    }
+
+    $candidates | Where-Object { $_ -like "$wordToComplete*" } | ForEach-Object {
+        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)
+    }
+}
@@ -1,31 +1,69 @@
 //! Bluesky (AT Protocol) API client
 
+mod facets;
+mod identity;
+mod jetstream;
+
 use anyhow::{Context, Result, bail};
 use chrono::{DateTime, Utc};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-
-use crate::models::{Account, MediaAttachment, MediaType, Network, Post};
-
-use super::SocialApi;
-
-/// Build a reqwest client with a proper User-Agent header.
-fn http_client() -> Client {
-    Client::builder()
-        .user_agent(format!("Perch/{}", env!("CARGO_PKG_VERSION")))
-        .build()
-        .expect("Failed to build HTTP client")
-}
+use std::collections::HashSet;
+use std::sync::RwLock;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::models::{
+    Account, FollowState, LinkCard, MediaAttachment, MediaType, Network, Notification,
+    NotificationKind, Post, Profile,
+};
+
+use super::streaming::{PostSink, STREAM_IDLE_TIMEOUT, stream_client};
+use super::websocket::WebSocket;
+use super::{
+    MAX_MEDIA_ATTACHMENTS, MediaUpload, PostContext, PostOptions, SearchPage, SocialApi,
+    TimelinePage, UPLOAD_TIMEOUT, check_media_count, http_client,
+};
+use facets::{Facet, FacetTarget, Feature};
+pub use identity::resolve_pds;
+use jetstream::OptionsUpdate;
 
 /// Default PDS URL for Bluesky
 pub const DEFAULT_PDS_URL: &str = "https://bsky.social";
 
+/// Maximum image size accepted by the `app.bsky.embed.images` lexicon
+const MAX_IMAGE_BYTES: usize = 1_000_000;
+
+/// Maximum number of URIs `app.bsky.feed.getPosts` accepts per call
+const GET_POSTS_BATCH: usize = 25;
+
+/// How long to collect streamed posts before fetching them, which also gives
+/// the `AppView` time to index them
+const STREAM_HYDRATE_DELAY: Duration = Duration::from_secs(3);
+
+/// Persistable Bluesky session tokens
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    /// DID of the logged-in account
+    pub did: String,
+    /// Short-lived access token
+    pub access_jwt: String,
+    /// Long-lived token used to obtain a new access token
+    pub refresh_jwt: String,
+}
+
 /// Bluesky API client
 pub struct BlueskyClient {
     client: Client,
     pds_url: String,
-    access_jwt: String,
+    session: RwLock<Session>,
     did: String,
+    /// App password, used to log in again if the refresh token is also expired
+    password: Option<String>,
+    /// Handle used for re-login
+    handle: Option<String>,
+    /// Account whose stored session is updated after a refresh
+    account: Option<Box<Account>>,
 }
 
 impl BlueskyClient {
@@ -36,133 +74,210 @@ impl BlueskyClient {
 
     /// Login to Bluesky with a custom PDS URL
     pub async fn login_with_pds(handle: &str, app_password: &str, pds_url: &str) -> Result<Self> {
+        Self::login_with_auth_factor(handle, app_password, pds_url, None).await
+    }
+
+    /// Login with the sign-in code Bluesky emails to accounts with two-factor
+    /// authentication, after a login failed with
+    /// [`LoginError::AuthFactorRequired`]
+    pub async fn login_with_auth_factor(
+        handle: &str,
+        app_password: &str,
+        pds_url: &str,
+        auth_factor_token: Option<&str>,
+    ) -> Result<Self> {
         let client = http_client();
         let pds_url = pds_url.trim_end_matches('/').to_string();
 
-        let url = format!("{pds_url}/xrpc/com.atproto.server.createSession");
-
-        let request = CreateSessionRequest {
-            identifier: handle.to_string(),
-            password: app_password.to_string(),
-        };
-
-        let response = client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to login to Bluesky")?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            bail!("Bluesky login failed: {error_text}");
-        }
-
-        let session: CreateSessionResponse = response
-            .json()
-            .await
-            .context("Failed to parse login response")?;
+        let session =
+            create_session(&client, &pds_url, handle, app_password, auth_factor_token).await?;
 
         Ok(Self {
             client,
             pds_url,
-            access_jwt: session.access_jwt,
-            did: session.did,
+            did: session.did.clone(),
+            session: RwLock::new(session),
+            password: Some(app_password.to_string()),
+            handle: Some(handle.to_string()),
+            account: None,
         })
     }
 
     /// Create a new client with existing credentials
     pub fn new(pds_url: &str, access_jwt: &str, did: &str) -> Self {
+        Self::from_session(
+            pds_url,
+            Session {
+                did: did.to_string(),
+                access_jwt: access_jwt.to_string(),
+                refresh_jwt: String::new(),
+            },
+        )
+    }
+
+    /// Create a client from a previously stored session
+    pub fn from_session(pds_url: &str, session: Session) -> Self {
         Self {
             client: http_client(),
-            pds_url: pds_url.to_string(),
-            access_jwt: access_jwt.to_string(),
-            did: did.to_string(),
+            pds_url: pds_url.trim_end_matches('/').to_string(),
+            did: session.did.clone(),
+            session: RwLock::new(session),
+            password: None,
+            handle: None,
+            account: None,
         }
     }
-}
 
-impl SocialApi for BlueskyClient {
-    async fn timeline(&self, limit: usize) -> Result<Vec<Post>> {
-        let url = format!(
-            "{}/xrpc/app.bsky.feed.getTimeline?limit={}",
-            self.pds_url, limit
-        );
+    /// Allow falling back to a fresh login when the session can't be refreshed
+    pub fn with_password(mut self, handle: &str, app_password: &str) -> Self {
+        self.handle = Some(handle.to_string());
+        self.password = Some(app_password.to_string());
+        self
+    }
 
-        let response = self
-            .client
+    /// Persist refreshed sessions for this account
+    pub fn with_account(mut self, account: Account) -> Self {
+        self.account = Some(Box::new(account));
+        self
+    }
+
+    /// Get a copy of the current session tokens
+    pub fn session(&self) -> Session {
+        self.session
+            .read()
+            .expect("Bluesky session lock poisoned")
+            .clone()
+    }
+
+    /// Reply to `parent`, a post of the thread `root` starts
+    pub async fn reply_in_thread(&self, content: &str, parent: &Post, root: &Post) -> Result<Post> {
+        let reply = ReplyRef {
+            parent: StrongRef::of(parent)?,
+            root: StrongRef::of(root)?,
+        };
+        let mut created = self.create_post(content, None, Some(reply)).await?;
+        created.reply_to_id = Some(parent.network_id.clone());
+        Ok(created)
+    }
+
+    /// What the PDS says about itself, if it answers
+    async fn describe_server(&self) -> Option<DescribeServerResponse> {
+        let url = format!("{}/xrpc/com.atproto.server.describeServer", self.pds_url);
+        self.client
             .get(&url)
-            .header("Authorization", format!("Bearer {}", self.access_jwt))
             .send()
             .await
-            .context("Failed to fetch timeline")?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            bail!("Failed to fetch timeline: {error_text}");
-        }
-
-        let timeline: GetTimelineResponse = response
+            .ok()?
+            .error_for_status()
+            .ok()?
             .json()
             .await
-            .context("Failed to parse timeline response")?;
-
-        Ok(timeline
-            .feed
-            .into_iter()
-            .map(FeedViewPost::into_post)
-            .collect())
+            .ok()
     }
 
-    async fn get_context(&self, post: &Post) -> Result<Vec<Post>> {
-        let uri = post.uri.as_ref().context("Post missing URI for context")?;
+    /// Obtain a new access token using the refresh token.
+    ///
+    /// Falls back to logging in again if the refresh token was rejected and an
+    /// app password is available. The new session is persisted when the client
+    /// is bound to an account.
+    pub async fn refresh_session(&self) -> Result<()> {
+        let refresh_jwt = self.session().refresh_jwt;
+        let url = format!("{}/xrpc/com.atproto.server.refreshSession", self.pds_url);
+
+        let refreshed = if refresh_jwt.is_empty() {
+            None
+        } else {
+            let response = self
+                .client
+                .post(&url)
+                .header("Authorization", format!("Bearer {refresh_jwt}"))
+                .send()
+                .await
+                .context("Failed to refresh session")?;
+
+            if response.status().is_success() {
+                let session: CreateSessionResponse = response
+                    .json()
+                    .await
+                    .context("Failed to parse refresh response")?;
+                Some(session.into_session())
+            } else {
+                let error_text = response.text().await.unwrap_or_default();
+                tracing::warn!("Bluesky session refresh failed: {error_text}");
+                None
+            }
+        };
 
-        let url = format!(
-            "{}/xrpc/app.bsky.feed.getPostThread?uri={}&depth=10",
-            self.pds_url,
-            urlencoding::encode(uri)
-        );
+        let session = match (refreshed, &self.handle, &self.password) {
+            (Some(session), _, _) => session,
+            (None, Some(handle), Some(password)) => {
+                create_session(&self.client, &self.pds_url, handle, password, None).await?
+            }
+            (None, _, _) => bail!("Bluesky session expired, please log in again"),
+        };
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.access_jwt))
-            .send()
-            .await
-            .context("Failed to fetch thread")?;
+        if let Some(account) = &self.account
+            && let Err(e) = crate::auth::store_session(account, &serde_json::to_string(&session)?)
+        {
+            tracing::warn!("Failed to persist Bluesky session: {e}");
+        }
 
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            bail!("Failed to fetch thread: {error_text}");
+        *self.session.write().expect("Bluesky session lock poisoned") = session;
+
+        Ok(())
+    }
+
+    /// Upload an image blob and return the blob reference for embedding
+    async fn upload_blob(&self, media: MediaUpload) -> Result<serde_json::Value> {
+        if media.bytes.len() > MAX_IMAGE_BYTES {
+            bail!(
+                "{} is too large ({} KB); Bluesky images must be under {} KB",
+                media.file_name,
+                media.bytes.len() / 1000,
+                MAX_IMAGE_BYTES / 1000
+            );
         }
 
-        let text = response.text().await.context("Failed to read response")?;
+        let url = format!("{}/xrpc/com.atproto.repo.uploadBlob", self.pds_url);
+        let error_context = format!("Failed to upload {}", media.file_name);
 
-        let thread_response: GetThreadResponse =
-            serde_json::from_str(&text).with_context(|| {
-                format!(
-                    "Failed to parse thread response: {}",
-                    &text[..text.len().min(500)]
-                )
-            })?;
+        let response = self
+            .send_authed(&error_context, || {
+                self.client
+                    .post(&url)
+                    .header("Content-Type", &media.mime_type)
+                    .body(media.bytes.clone())
+                    .timeout(UPLOAD_TIMEOUT)
+            })
+            .await?;
 
-        // Collect all replies recursively
-        let mut replies = Vec::new();
-        thread_response.thread.collect_replies(&mut replies);
+        let uploaded: UploadBlobResponse = response
+            .json()
+            .await
+            .context("Failed to parse upload response")?;
 
-        Ok(replies)
+        Ok(uploaded.blob)
     }
 
-    async fn post(&self, content: &str) -> Result<Post> {
+    /// Create a post record with an optional embed (images or a quoted post)
+    async fn create_post(
+        &self,
+        content: &str,
+        embed: Option<RecordEmbed>,
+        reply: Option<ReplyRef>,
+    ) -> Result<Post> {
         let url = format!("{}/xrpc/com.atproto.repo.createRecord", self.pds_url);
 
         let now = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
 
         let record = PostRecord {
             text: content.to_string(),
-            created_at: now.clone(),
+            created_at: now,
             r#type: "app.bsky.feed.post".to_string(),
-            reply: None,
+            reply,
+            embed,
+            facets: self.build_facets(content).await,
+            raw_facets: None,
         };
 
         let request = CreateRecordRequest {
@@ -172,18 +287,8 @@ impl SocialApi for BlueskyClient {
         };
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.access_jwt))
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to post")?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            bail!("Failed to post: {error_text}");
-        }
+            .send_authed("Failed to post", || self.client.post(&url).json(&request))
+            .await?;
 
         let result: CreateRecordResponse = response
             .json()
@@ -205,6 +310,7 @@ impl SocialApi for BlueskyClient {
             author_avatar: None,
             content: content.to_string(),
             content_raw: None,
+            spoiler_text: None,
             created_at: Utc::now(),
             url: None,
             is_repost: false,
@@ -214,88 +320,442 @@ impl SocialApi for BlueskyClient {
             reply_count: 0,
             liked: false,
             reposted: false,
+            bookmarked: false,
             reply_to_id: None,
             media: Vec::new(),
             cid: Some(result.cid),
             uri: Some(result.uri),
+            quote: None,
+            link_card: None,
+            owner_account_id: None,
         })
     }
 
-    async fn reply(&self, content: &str, reply_to_id: &str) -> Result<Post> {
-        // For Bluesky replies, we need the parent post's URI and CID
-        // For now, we'll construct the URI from the reply_to_id
-        // Full implementation would need to fetch the parent post first
+    /// Mark mentions, links and hashtags in post text. Mentions of handles
+    /// that don't resolve are left as plain text.
+    async fn build_facets(&self, text: &str) -> Vec<Facet> {
+        let mut facets = Vec::new();
+        for (range, target) in facets::detect(text) {
+            let feature = match target {
+                FacetTarget::Mention(handle) => match self.resolve_handle(&handle).await {
+                    Ok(did) => Feature::Mention { did },
+                    Err(e) => {
+                        tracing::debug!("Not linking @{handle}: {e}");
+                        continue;
+                    }
+                },
+                FacetTarget::Link(uri) => Feature::Link { uri },
+                FacetTarget::Tag(tag) => Feature::Tag { tag },
+            };
+            facets.push(Facet::new(range, feature));
+        }
+        facets
+    }
 
-        let url = format!("{}/xrpc/com.atproto.repo.createRecord", self.pds_url);
-        let now = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+    /// Look up the DID for a handle
+    async fn resolve_handle(&self, handle: &str) -> Result<String> {
+        let url = format!(
+            "{}/xrpc/com.atproto.identity.resolveHandle?handle={}",
+            self.pds_url,
+            urlencoding::encode(handle)
+        );
 
-        // The reply_to_id for Bluesky should be the post rkey
-        // We need to construct the full URI: at://did/app.bsky.feed.post/rkey
-        // For simplicity, just post without reply threading for now
-        // TODO: Implement proper reply threading by fetching parent post
+        let response = self
+            .send_authed("Failed to resolve handle", || self.client.get(&url))
+            .await?;
 
-        let record = PostRecord {
-            text: content.to_string(),
-            created_at: now.clone(),
-            r#type: "app.bsky.feed.post".to_string(),
-            reply: None, // TODO: Add reply refs when implementing proper threading
-        };
+        let resolved: ResolveHandleResponse = response
+            .json()
+            .await
+            .context("Failed to parse resolveHandle response")?;
+        Ok(resolved.did)
+    }
 
-        let request = CreateRecordRequest {
-            repo: self.did.clone(),
-            collection: "app.bsky.feed.post".to_string(),
-            record,
-        };
+    /// Fetch full post views for a set of `at://` URIs
+    async fn get_posts(&self, uris: &[String]) -> Result<Vec<Post>> {
+        let mut posts = Vec::new();
+
+        for batch in uris.chunks(GET_POSTS_BATCH) {
+            let query: Vec<String> = batch
+                .iter()
+                .map(|uri| format!("uris={}", urlencoding::encode(uri)))
+                .collect();
+            let url = format!(
+                "{}/xrpc/app.bsky.feed.getPosts?{}",
+                self.pds_url,
+                query.join("&")
+            );
+
+            let response = self
+                .send_authed("Failed to fetch posts", || self.client.get(&url))
+                .await?;
+
+            let result: GetPostsResponse = response
+                .json()
+                .await
+                .context("Failed to parse posts response")?;
+
+            posts.extend(result.posts.into_iter().map(post_view_to_post));
+        }
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.access_jwt))
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to post reply")?;
+        Ok(posts)
+    }
+
+    /// DIDs of the accounts this one follows (up to what Jetstream can filter on)
+    async fn follows(&self) -> Result<Vec<String>> {
+        let mut dids = Vec::new();
+        let mut cursor = None;
+
+        loop {
+            let page_cursor = cursor
+                .map(|c: String| format!("&cursor={}", urlencoding::encode(&c)))
+                .unwrap_or_default();
+            let url = format!(
+                "{}/xrpc/app.bsky.graph.getFollows?actor={}&limit=100{page_cursor}",
+                self.pds_url,
+                urlencoding::encode(&self.did)
+            );
+
+            let response = self
+                .send_authed("Failed to fetch follows", || self.client.get(&url))
+                .await?;
+            let page: GetFollowsResponse = response
+                .json()
+                .await
+                .context("Failed to parse follows response")?;
+
+            dids.extend(page.follows.into_iter().map(|f| f.did));
+            cursor = page.cursor.filter(|c| !c.is_empty());
+            if cursor.is_none() || dids.len() >= jetstream::MAX_WANTED_DIDS {
+                return Ok(dids);
+            }
+        }
+    }
+
+    /// Stream new posts by followed accounts into `sink` until the connection
+    /// drops
+    pub async fn stream_home(&self, sink: &PostSink) -> Result<()> {
+        let mut dids = self.follows().await?;
+        dids.truncate(jetstream::MAX_WANTED_DIDS - 1);
+        dids.push(self.did.clone());
+        let followed: HashSet<String> = dids.iter().cloned().collect();
+
+        let mut socket = WebSocket::connect(&stream_client(), jetstream::JETSTREAM_URL).await?;
+        let options = serde_json::to_string(&OptionsUpdate::posts_from(&dids))?;
+        socket.send_text(&options).await?;
+
+        // Jetstream only has record references; full posts are fetched in batches
+        let mut pending = Vec::new();
+        let mut hydrate_at: Option<Instant> = None;
+        let mut idle_at = Instant::now() + STREAM_IDLE_TIMEOUT;
+
+        loop {
+            let deadline = hydrate_at.map_or(idle_at, |at| at.min(idle_at));
+            match tokio::time::timeout_at(deadline, socket.next_text()).await {
+                Ok(message) => {
+                    let Some(message) = message? else {
+                        return Ok(());
+                    };
+                    idle_at = Instant::now() + STREAM_IDLE_TIMEOUT;
+                    if let Some(uri) = jetstream::new_post_uri(&message, &followed) {
+                        pending.push(uri);
+                        hydrate_at.get_or_insert_with(|| Instant::now() + STREAM_HYDRATE_DELAY);
+                    }
+                }
+                Err(_) if hydrate_at.is_some_and(|at| at <= Instant::now()) => {
+                    hydrate_at = None;
+                    match self.get_posts(&std::mem::take(&mut pending)).await {
+                        Ok(posts) if posts.is_empty() => {}
+                        Ok(posts) => {
+                            if sink.send(posts).await.is_err() {
+                                // Nobody is listening any more
+                                return Ok(());
+                            }
+                        }
+                        Err(e) => tracing::warn!("Failed to fetch streamed posts: {e}"),
+                    }
+                }
+                Err(_) if socket.awaiting_pong() => bail!("Jetstream stopped responding"),
+                Err(_) => {
+                    // Quiet timelines are normal; check the connection is still up
+                    socket.ping().await?;
+                    idle_at = Instant::now() + STREAM_IDLE_TIMEOUT;
+                }
+            }
+        }
+    }
+
+    /// Send an authenticated request.
+    ///
+    /// If the access token has expired, the session is refreshed and the
+    /// request is retried once. Non-success responses become errors prefixed
+    /// with `error_context`.
+    async fn send_authed<F>(&self, error_context: &str, build: F) -> Result<reqwest::Response>
+    where
+        F: Fn() -> reqwest::RequestBuilder,
+    {
+        let mut refreshed = false;
+
+        loop {
+            let access_jwt = self.session().access_jwt;
+            let response = build()
+                .header("Authorization", format!("Bearer {access_jwt}"))
+                .send()
+                .await
+                .context(error_context.to_string())?;
+
+            if response.status().is_success() {
+                return Ok(response);
+            }
 
-        if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            bail!("Failed to post reply: {error_text}");
+
+            if !refreshed && is_expired_token(&error_text) {
+                tracing::debug!("Bluesky access token expired, refreshing session");
+                self.refresh_session().await?;
+                refreshed = true;
+                continue;
+            }
+
+            bail!("{error_context}: {error_text}");
         }
+    }
+}
 
-        let result: CreateRecordResponse = response
+/// Why Bluesky refused a login
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoginError {
+    /// The account has two-factor authentication and Bluesky has emailed a
+    /// sign-in code; logging in again with the code will work
+    AuthFactorRequired,
+    /// The emailed sign-in code was wrong or has expired
+    InvalidAuthFactor,
+    /// Wrong handle or password
+    InvalidCredentials {
+        /// The password has the `xxxx-xxxx-xxxx-xxxx` shape of an app password
+        app_password: bool,
+    },
+    /// The account has been taken down by its host
+    AccountTakedown,
+    /// Any other error, with the server's message
+    Other(String),
+}
+
+impl LoginError {
+    /// Read the error `createSession` returned for a login with `password`
+    fn from_response(body: &str, password: &str) -> Self {
+        let Ok(error) = serde_json::from_str::<XrpcError>(body) else {
+            return Self::Other(body.trim().to_string());
+        };
+        match error.error.as_str() {
+            "AuthFactorTokenRequired" => Self::AuthFactorRequired,
+            "InvalidToken" | "ExpiredToken" => Self::InvalidAuthFactor,
+            "AuthenticationRequired" => Self::InvalidCredentials {
+                app_password: is_app_password(password),
+            },
+            "AccountTakedown" => Self::AccountTakedown,
+            _ => Self::Other(error.message.unwrap_or(error.error)),
+        }
+    }
+}
+
+impl std::fmt::Display for LoginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AuthFactorRequired => write!(
+                f,
+                "Bluesky emailed a sign-in code for this account. Log in again with the code, \
+                 or use an app password, which doesn't need one"
+            ),
+            Self::InvalidAuthFactor => {
+                write!(f, "The sign-in code is wrong or has expired")
+            }
+            Self::InvalidCredentials { app_password: true } => write!(
+                f,
+                "Wrong handle or app password. App passwords can be revoked, so check it \
+                 still exists at https://bsky.app/settings/app-passwords"
+            ),
+            Self::InvalidCredentials {
+                app_password: false,
+            } => write!(
+                f,
+                "Wrong handle or password. Perch logs in with an app password \
+                 (xxxx-xxxx-xxxx-xxxx); create one at https://bsky.app/settings/app-passwords"
+            ),
+            Self::AccountTakedown => write!(f, "This Bluesky account has been taken down"),
+            Self::Other(message) => write!(f, "Bluesky login failed: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for LoginError {}
+
+/// Whether a password has the shape of a Bluesky app password
+fn is_app_password(password: &str) -> bool {
+    let groups: Vec<&str> = password.split('-').collect();
+    groups.len() == 4
+        && groups
+            .iter()
+            .all(|g| g.len() == 4 && g.bytes().all(|b| b.is_ascii_alphanumeric()))
+}
+
+/// Create a new session with a handle and app password, and the emailed
+/// sign-in code if the account asks for one
+async fn create_session(
+    client: &Client,
+    pds_url: &str,
+    handle: &str,
+    app_password: &str,
+    auth_factor_token: Option<&str>,
+) -> Result<Session> {
+    let url = format!("{pds_url}/xrpc/com.atproto.server.createSession");
+
+    let request = CreateSessionRequest {
+        identifier: handle.to_string(),
+        password: app_password.to_string(),
+        auth_factor_token: auth_factor_token.map(str::to_string),
+    };
+
+    let response = client
+        .post(&url)
+        .json(&request)
+        .send()
+        .await
+        .context("Failed to login to Bluesky")?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(LoginError::from_response(&error_text, app_password).into());
+    }
+
+    let session: CreateSessionResponse = response
+        .json()
+        .await
+        .context("Failed to parse login response")?;
+
+    Ok(session.into_session())
+}
+
+/// Check whether an XRPC error body reports an expired access token
+fn is_expired_token(error_text: &str) -> bool {
+    serde_json::from_str::<XrpcError>(error_text).is_ok_and(|e| e.error == "ExpiredToken")
+}
+
+impl SocialApi for BlueskyClient {
+    async fn timeline(&self, limit: usize) -> Result<Vec<Post>> {
+        Ok(self.timeline_paged(limit, None).await?.posts)
+    }
+
+    async fn timeline_paged(&self, limit: usize, cursor: Option<String>) -> Result<TimelinePage> {
+        let cursor = cursor
+            .map(|c| format!("&cursor={}", urlencoding::encode(&c)))
+            .unwrap_or_default();
+        let url = format!(
+            "{}/xrpc/app.bsky.feed.getTimeline?limit={}{}",
+            self.pds_url, limit, cursor
+        );
+
+        let response = self
+            .send_authed("Failed to fetch timeline", || self.client.get(&url))
+            .await?;
+
+        let timeline: GetTimelineResponse = response
             .json()
             .await
-            .context("Failed to parse reply response")?;
+            .context("Failed to parse timeline response")?;
 
-        Ok(Post {
-            id: uuid::Uuid::new_v4(),
-            network_id: result
-                .uri
-                .split('/')
-                .next_back()
-                .unwrap_or(&result.uri)
-                .to_string(),
-            network: Network::Bluesky,
-            author_handle: self.did.clone(),
-            author_name: String::new(),
-            author_avatar: None,
-            content: content.to_string(),
-            content_raw: None,
-            created_at: Utc::now(),
-            url: None,
-            is_repost: false,
-            repost_author: None,
-            like_count: 0,
-            repost_count: 0,
-            reply_count: 0,
-            liked: false,
-            reposted: false,
-            reply_to_id: Some(reply_to_id.to_string()),
-            media: Vec::new(),
-            cid: Some(result.cid),
-            uri: Some(result.uri),
+        Ok(TimelinePage {
+            posts: timeline
+                .feed
+                .into_iter()
+                .map(FeedViewPost::into_post)
+                .collect(),
+            next_cursor: timeline.cursor,
         })
     }
 
+    async fn get_context(&self, post: &Post) -> Result<PostContext> {
+        let uri = post.uri.as_ref().context("Post missing URI for context")?;
+
+        let url = format!(
+            "{}/xrpc/app.bsky.feed.getPostThread?uri={}&depth=10",
+            self.pds_url,
+            urlencoding::encode(uri)
+        );
+
+        let response = self
+            .send_authed("Failed to fetch thread", || self.client.get(&url))
+            .await?;
+
+        let text = response.text().await.context("Failed to read response")?;
+
+        let thread_response: GetThreadResponse =
+            serde_json::from_str(&text).with_context(|| {
+                format!(
+                    "Failed to parse thread response: {}",
+                    &text[..text.len().min(500)]
+                )
+            })?;
+
+        // Collect all replies recursively
+        let mut replies = Vec::new();
+        thread_response.thread.collect_replies(&mut replies);
+
+        Ok(PostContext {
+            ancestors: thread_response.thread.collect_ancestors(),
+            descendants: replies,
+        })
+    }
+
+    async fn post(&self, content: &str, options: &PostOptions) -> Result<Post> {
+        self.post_with_media(content, Vec::new(), options).await
+    }
+
+    async fn post_with_media(
+        &self,
+        content: &str,
+        media: Vec<MediaUpload>,
+        _options: &PostOptions,
+    ) -> Result<Post> {
+        check_media_count(&media)?;
+
+        let mut images = Vec::with_capacity(media.len());
+        for upload in media {
+            let alt = upload.alt_text.clone().unwrap_or_default();
+            let image = self.upload_blob(upload).await?;
+            images.push(RecordEmbedImage { image, alt });
+        }
+
+        let embed = (!images.is_empty()).then(|| RecordEmbed::images(images));
+        self.create_post(content, embed, None).await
+    }
+
+    async fn reply(
+        &self,
+        content: &str,
+        reply_to_id: &str,
+        _options: &PostOptions,
+    ) -> Result<Post> {
+        // The reply_to_id is only the parent's rkey, but reply refs need its
+        // full URI and CID, so for now this posts without reply threading
+        // TODO: Implement proper reply threading by fetching parent post
+        let mut created = self.create_post(content, None, None).await?;
+        created.reply_to_id = Some(reply_to_id.to_string());
+        Ok(created)
+    }
+
+    async fn quote(&self, post: &Post, content: &str, _options: &PostOptions) -> Result<Post> {
+        let (Some(uri), Some(cid)) = (&post.uri, &post.cid) else {
+            anyhow::bail!("The quoted post has no URI or CID");
+        };
+
+        let mut created = self
+            .create_post(content, Some(RecordEmbed::quote(uri, cid)), None)
+            .await?;
+        created.quote = Some(Box::new(post.clone()));
+        Ok(created)
+    }
+
     async fn like(&self, post: &Post) -> Result<()> {
         let cid = post.cid.as_ref().context("Post missing CID for like")?;
         let uri = post.uri.as_ref().context("Post missing URI for like")?;
@@ -319,19 +779,10 @@ impl SocialApi for BlueskyClient {
             record,
         };
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.access_jwt))
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to like post")?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            bail!("Failed to like post: {error_text}");
-        }
+        self.send_authed("Failed to like post", || {
+            self.client.post(&url).json(&request)
+        })
+        .await?;
 
         Ok(())
     }
@@ -347,17 +798,8 @@ impl SocialApi for BlueskyClient {
         );
 
         let response = self
-            .client
-            .get(&list_url)
-            .header("Authorization", format!("Bearer {}", self.access_jwt))
-            .send()
-            .await
-            .context("Failed to get likes")?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            bail!("Failed to get likes: {error_text}");
-        }
+            .send_authed("Failed to get likes", || self.client.get(&list_url))
+            .await?;
 
         #[derive(Debug, Deserialize)]
         #[allow(dead_code)]
@@ -382,17 +824,10 @@ impl SocialApi for BlueskyClient {
         );
 
         let response = self
-            .client
-            .get(&records_url)
-            .header("Authorization", format!("Bearer {}", self.access_jwt))
-            .send()
-            .await
-            .context("Failed to list like records")?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            bail!("Failed to list like records: {error_text}");
-        }
+            .send_authed("Failed to list like records", || {
+                self.client.get(&records_url)
+            })
+            .await?;
 
         #[derive(Debug, Deserialize)]
         struct ListRecordsResponse {
@@ -449,19 +884,10 @@ impl SocialApi for BlueskyClient {
             rkey: rkey.to_string(),
         };
 
-        let response = self
-            .client
-            .post(&delete_url)
-            .header("Authorization", format!("Bearer {}", self.access_jwt))
-            .json(&delete_request)
-            .send()
-            .await
-            .context("Failed to delete like")?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            bail!("Failed to unlike: {error_text}");
-        }
+        self.send_authed("Failed to unlike", || {
+            self.client.post(&delete_url).json(&delete_request)
+        })
+        .await?;
 
         Ok(())
     }
@@ -489,24 +915,13 @@ impl SocialApi for BlueskyClient {
             record,
         };
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.access_jwt))
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to repost")?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            bail!("Failed to repost: {error_text}");
-        }
+        self.send_authed("Failed to repost", || self.client.post(&url).json(&request))
+            .await?;
 
         Ok(())
     }
 
-    async fn unrepost(&self, post: &Post) -> Result<()> {
+    async fn unrepost(&self, post: &Post) -> Result<bool> {
         let uri = post.uri.as_ref().context("Post missing URI for unrepost")?;
 
         // Find the repost record in the actor's repo
@@ -516,17 +931,10 @@ impl SocialApi for BlueskyClient {
         );
 
         let response = self
-            .client
-            .get(&records_url)
-            .header("Authorization", format!("Bearer {}", self.access_jwt))
-            .send()
-            .await
-            .context("Failed to list repost records")?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            bail!("Failed to list repost records: {error_text}");
-        }
+            .send_authed("Failed to list repost records", || {
+                self.client.get(&records_url)
+            })
+            .await?;
 
         #[derive(Debug, Deserialize)]
         struct ListRecordsResponse {
@@ -557,7 +965,7 @@ impl SocialApi for BlueskyClient {
 
         let Some(record) = repost_record else {
             // Already unreposted or not found
-            return Ok(());
+            return Ok(false);
         };
 
         // Extract rkey from the record URI
@@ -583,23 +991,83 @@ impl SocialApi for BlueskyClient {
             rkey: rkey.to_string(),
         };
 
-        let response = self
-            .client
-            .post(&delete_url)
-            .header("Authorization", format!("Bearer {}", self.access_jwt))
-            .json(&delete_request)
-            .send()
-            .await
-            .context("Failed to delete repost")?;
+        self.send_authed("Failed to unrepost", || {
+            self.client.post(&delete_url).json(&delete_request)
+        })
+        .await?;
 
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            bail!("Failed to unrepost: {error_text}");
+        Ok(true)
+    }
+
+    async fn bookmark(&self, _post: &Post) -> Result<()> {
+        bail!("Bookmarks are not supported on Bluesky")
+    }
+
+    async fn unbookmark(&self, _post: &Post) -> Result<()> {
+        bail!("Bookmarks are not supported on Bluesky")
+    }
+
+    async fn delete(&self, post: &Post) -> Result<()> {
+        let uri = post.uri.as_ref().context("Post missing URI for delete")?;
+        // at://did/app.bsky.feed.post/rkey
+        let rkey = uri.rsplit('/').next().context("Invalid post URI")?;
+
+        let delete_url = format!("{}/xrpc/com.atproto.repo.deleteRecord", self.pds_url);
+
+        #[derive(Debug, Serialize)]
+        struct DeleteRequest {
+            repo: String,
+            collection: String,
+            rkey: String,
         }
 
+        let delete_request = DeleteRequest {
+            repo: self.did.clone(),
+            collection: "app.bsky.feed.post".to_string(),
+            rkey: rkey.to_string(),
+        };
+
+        self.send_authed("Failed to delete post", || {
+            self.client.post(&delete_url).json(&delete_request)
+        })
+        .await?;
+
         Ok(())
     }
 
+    async fn edit(&self, _post_id: &str, _content: &str) -> Result<Post> {
+        bail!("Bluesky doesn't support editing posts")
+    }
+
+    async fn search(&self, query: &str, limit: usize) -> Result<SearchPage> {
+        // searchPosts allows at most 100 results per page
+        let url = format!(
+            "{}/xrpc/app.bsky.feed.searchPosts?q={}&limit={}",
+            self.pds_url,
+            urlencoding::encode(query),
+            limit.min(100)
+        );
+
+        let response = self
+            .send_authed("Search failed", || self.client.get(&url))
+            .await?;
+
+        let result: GetPostsResponse = response
+            .json()
+            .await
+            .context("Failed to parse search response")?;
+
+        Ok(SearchPage {
+            posts: result.posts.into_iter().map(post_view_to_post).collect(),
+            notice: None,
+        })
+    }
+
+    async fn hashtag_timeline(&self, tag: &str, limit: usize) -> Result<Vec<Post>> {
+        // There is no tag feed; a `#tag` search matches the tag facet
+        Ok(self.search(&format!("#{tag}"), limit).await?.posts)
+    }
+
     async fn verify_credentials(&self) -> Result<Account> {
         let url = format!(
             "{}/xrpc/app.bsky.actor.getProfile?actor={}",
@@ -607,31 +1075,273 @@ impl SocialApi for BlueskyClient {
         );
 
         let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.access_jwt))
-            .send()
-            .await
-            .context("Failed to get profile")?;
+            .send_authed("Failed to get profile", || self.client.get(&url))
+            .await?;
+
+        let profile: ProfileResponse = response
+            .json()
+            .await
+            .context("Failed to parse profile response")?;
+        let server = self.describe_server().await;
+
+        Ok(Account {
+            id: uuid::Uuid::new_v4(),
+            network: Network::Bluesky,
+            display_name: profile
+                .display_name
+                .unwrap_or_else(|| profile.handle.clone()),
+            handle: profile.handle,
+            server: self.pds_url.clone(),
+            is_default: true, // First account of this network is default
+            avatar_url: profile.avatar,
+            created_at: Utc::now(),
+            last_used_at: None,
+            max_post_chars: None,
+            server_version: None,
+            server_name: server.map(|s| s.did).filter(|did| !did.is_empty()),
+            // Set by the protocol rather than the server
+            max_media_attachments: Some(MAX_MEDIA_ATTACHMENTS),
+            max_image_bytes: Some(MAX_IMAGE_BYTES),
+        })
+    }
+
+    async fn profile(&self, handle: &str) -> Result<Profile> {
+        let url = format!(
+            "{}/xrpc/app.bsky.actor.getProfile?actor={}",
+            self.pds_url,
+            urlencoding::encode(handle.trim_start_matches('@'))
+        );
+
+        let response = self
+            .send_authed("Failed to get profile", || self.client.get(&url))
+            .await?;
+
+        let profile: ProfileResponse = response
+            .json()
+            .await
+            .context("Failed to parse profile response")?;
+        Ok(profile.into_profile())
+    }
+
+    async fn author_feed(
+        &self,
+        profile: &Profile,
+        limit: usize,
+        cursor: Option<String>,
+    ) -> Result<TimelinePage> {
+        let cursor = cursor
+            .map(|c| format!("&cursor={}", urlencoding::encode(&c)))
+            .unwrap_or_default();
+        let url = format!(
+            "{}/xrpc/app.bsky.feed.getAuthorFeed?actor={}&limit={}{}",
+            self.pds_url,
+            urlencoding::encode(&profile.network_id),
+            limit,
+            cursor
+        );
+
+        let response = self
+            .send_authed("Failed to fetch posts", || self.client.get(&url))
+            .await?;
+
+        // Same shape as the home timeline
+        let feed: GetTimelineResponse = response
+            .json()
+            .await
+            .context("Failed to parse author feed response")?;
+
+        Ok(TimelinePage {
+            posts: feed.feed.into_iter().map(FeedViewPost::into_post).collect(),
+            next_cursor: feed.cursor,
+        })
+    }
+
+    async fn follow(&self, profile: &Profile) -> Result<Profile> {
+        let url = format!("{}/xrpc/com.atproto.repo.createRecord", self.pds_url);
+
+        let request = CreateRecordRequest {
+            repo: self.did.clone(),
+            collection: "app.bsky.graph.follow".to_string(),
+            record: GraphRecord {
+                subject: profile.network_id.clone(),
+                created_at: Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+                r#type: "app.bsky.graph.follow".to_string(),
+            },
+        };
+
+        let response = self
+            .send_authed("Failed to follow", || self.client.post(&url).json(&request))
+            .await?;
+        let created: CreateRecordResponse = response
+            .json()
+            .await
+            .context("Failed to parse follow response")?;
+
+        // Bluesky has no follow approval
+        Ok(Profile {
+            follow_state: FollowState::Following,
+            follow_uri: Some(created.uri),
+            ..profile.clone()
+        })
+    }
+
+    async fn unfollow(&self, profile: &Profile) -> Result<Profile> {
+        let uri = profile
+            .follow_uri
+            .as_ref()
+            .with_context(|| format!("Not following @{}", profile.handle))?;
+        // at://did/app.bsky.graph.follow/rkey
+        let rkey = uri.rsplit('/').next().context("Invalid follow URI")?;
+
+        let delete_url = format!("{}/xrpc/com.atproto.repo.deleteRecord", self.pds_url);
+
+        #[derive(Debug, Serialize)]
+        struct DeleteRequest {
+            repo: String,
+            collection: String,
+            rkey: String,
+        }
+
+        let delete_request = DeleteRequest {
+            repo: self.did.clone(),
+            collection: "app.bsky.graph.follow".to_string(),
+            rkey: rkey.to_string(),
+        };
+
+        self.send_authed("Failed to unfollow", || {
+            self.client.post(&delete_url).json(&delete_request)
+        })
+        .await?;
+
+        Ok(Profile {
+            follow_state: FollowState::NotFollowing,
+            follow_uri: None,
+            ..profile.clone()
+        })
+    }
+
+    async fn mute(&self, profile: &Profile) -> Result<()> {
+        let url = format!("{}/xrpc/app.bsky.graph.muteActor", self.pds_url);
+        let body = serde_json::json!({ "actor": profile.network_id });
+
+        self.send_authed("Failed to mute", || self.client.post(&url).json(&body))
+            .await?;
+        Ok(())
+    }
+
+    async fn unmute(&self, profile: &Profile) -> Result<()> {
+        let url = format!("{}/xrpc/app.bsky.graph.unmuteActor", self.pds_url);
+        let body = serde_json::json!({ "actor": profile.network_id });
+
+        self.send_authed("Failed to unmute", || self.client.post(&url).json(&body))
+            .await?;
+        Ok(())
+    }
+
+    async fn block(&self, profile: &Profile) -> Result<()> {
+        let url = format!("{}/xrpc/com.atproto.repo.createRecord", self.pds_url);
+
+        let request = CreateRecordRequest {
+            repo: self.did.clone(),
+            collection: "app.bsky.graph.block".to_string(),
+            record: GraphRecord {
+                subject: profile.network_id.clone(),
+                created_at: Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+                r#type: "app.bsky.graph.block".to_string(),
+            },
+        };
+
+        self.send_authed("Failed to block", || self.client.post(&url).json(&request))
+            .await?;
+        Ok(())
+    }
+
+    async fn unblock(&self, profile: &Profile) -> Result<()> {
+        let uri = profile
+            .block_uri
+            .as_ref()
+            .with_context(|| format!("Not blocking @{}", profile.handle))?;
+        // at://did/app.bsky.graph.block/rkey
+        let rkey = uri.rsplit('/').next().context("Invalid block URI")?;
+
+        let delete_url = format!("{}/xrpc/com.atproto.repo.deleteRecord", self.pds_url);
+
+        #[derive(Debug, Serialize)]
+        struct DeleteRequest {
+            repo: String,
+            collection: String,
+            rkey: String,
+        }
+
+        let delete_request = DeleteRequest {
+            repo: self.did.clone(),
+            collection: "app.bsky.graph.block".to_string(),
+            rkey: rkey.to_string(),
+        };
+
+        self.send_authed("Failed to unblock", || {
+            self.client.post(&delete_url).json(&delete_request)
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn notifications(&self, limit: usize) -> Result<Vec<Notification>> {
+        let url = format!(
+            "{}/xrpc/app.bsky.notification.listNotifications?limit={}",
+            self.pds_url, limit
+        );
+
+        let response = self
+            .send_authed("Failed to fetch notifications", || self.client.get(&url))
+            .await?;
 
-        let profile: ProfileResponse = response
+        let result: ListNotificationsResponse = response
             .json()
             .await
-            .context("Failed to parse profile response")?;
+            .context("Failed to parse notifications response")?;
+
+        // Notifications only carry URIs; hydrate the posts they refer to in bulk
+        let mut uris: Vec<String> = result
+            .notifications
+            .iter()
+            .filter_map(BskyNotification::subject_uri)
+            .map(String::from)
+            .collect();
+        uris.sort();
+        uris.dedup();
+
+        let posts = match self.get_posts(&uris).await {
+            Ok(posts) => posts,
+            Err(e) => {
+                tracing::warn!("Failed to load notification posts: {e}");
+                Vec::new()
+            }
+        };
 
-        Ok(Account {
-            id: uuid::Uuid::new_v4(),
-            network: Network::Bluesky,
-            display_name: profile
-                .display_name
-                .unwrap_or_else(|| profile.handle.clone()),
-            handle: profile.handle,
-            server: self.pds_url.clone(),
-            is_default: true, // First account of this network is default
-            avatar_url: profile.avatar,
-            created_at: Utc::now(),
-            last_used_at: None,
+        Ok(result
+            .notifications
+            .into_iter()
+            .map(|n| {
+                let post = n
+                    .subject_uri()
+                    .and_then(|uri| posts.iter().find(|p| p.uri.as_deref() == Some(uri)))
+                    .cloned();
+                n.into_notification(post)
+            })
+            .collect())
+    }
+
+    async fn mark_notifications_read(&self) -> Result<()> {
+        let url = format!("{}/xrpc/app.bsky.notification.updateSeen", self.pds_url);
+        let body = serde_json::json!({ "seenAt": Utc::now().to_rfc3339() });
+
+        self.send_authed("Failed to mark notifications as seen", || {
+            self.client.post(&url).json(&body)
         })
+        .await?;
+
+        Ok(())
     }
 }
 
@@ -641,24 +1351,117 @@ impl SocialApi for BlueskyClient {
 struct CreateSessionRequest {
     identifier: String,
     password: String,
+    #[serde(rename = "authFactorToken", skip_serializing_if = "Option::is_none")]
+    auth_factor_token: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct CreateSessionResponse {
     #[serde(rename = "accessJwt")]
     access_jwt: String,
+    #[serde(rename = "refreshJwt")]
+    refresh_jwt: String,
     did: String,
     #[allow(dead_code)]
     handle: String,
 }
 
+impl CreateSessionResponse {
+    fn into_session(self) -> Session {
+        Session {
+            did: self.did,
+            access_jwt: self.access_jwt,
+            refresh_jwt: self.refresh_jwt,
+        }
+    }
+}
+
+/// Error body returned by XRPC endpoints
+#[derive(Debug, Deserialize)]
+struct XrpcError {
+    error: String,
+    message: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct GetTimelineResponse {
     feed: Vec<FeedViewPost>,
-    #[allow(dead_code)]
     cursor: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct GetFollowsResponse {
+    follows: Vec<ActorRef>,
+    cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetPostsResponse {
+    posts: Vec<PostView>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListNotificationsResponse {
+    notifications: Vec<BskyNotification>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BskyNotification {
+    uri: String,
+    author: Author,
+    reason: String,
+    #[serde(rename = "reasonSubject")]
+    reason_subject: Option<String>,
+    #[serde(rename = "isRead", default)]
+    is_read: bool,
+    #[serde(rename = "indexedAt")]
+    indexed_at: String,
+}
+
+impl BskyNotification {
+    /// URI of the post this notification is about, if any.
+    ///
+    /// Replies, mentions and quotes are posts themselves; likes and reposts
+    /// point at your post through `reasonSubject`.
+    fn subject_uri(&self) -> Option<&str> {
+        match self.reason.as_str() {
+            "reply" | "mention" | "quote" => Some(&self.uri),
+            "like" | "repost" => self.reason_subject.as_deref(),
+            _ => None,
+        }
+    }
+
+    fn into_notification(self, post: Option<Post>) -> Notification {
+        let kind = match self.reason.as_str() {
+            "mention" => NotificationKind::Mention,
+            "reply" => NotificationKind::Reply,
+            "like" => NotificationKind::Like,
+            "repost" => NotificationKind::Repost,
+            "follow" => NotificationKind::Follow,
+            "quote" => NotificationKind::Quote,
+            _ => NotificationKind::Other,
+        };
+
+        let created_at = DateTime::parse_from_rfc3339(&self.indexed_at)
+            .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc));
+
+        Notification {
+            id: self.uri,
+            network: Network::Bluesky,
+            kind,
+            actor_name: self
+                .author
+                .display_name
+                .unwrap_or_else(|| self.author.handle.clone()),
+            actor_handle: self.author.handle,
+            actor_avatar: self.author.avatar,
+            post,
+            created_at,
+            is_read: self.is_read,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct FeedViewPost {
     post: PostView,
@@ -675,7 +1478,6 @@ struct RepostReason {
 
 #[derive(Debug, Deserialize)]
 struct ActorRef {
-    #[allow(dead_code)]
     did: String,
     handle: String,
     #[serde(rename = "displayName")]
@@ -722,6 +1524,68 @@ struct PostRecord {
     r#type: String,
     /// Reply reference (parent and root)
     reply: Option<ReplyRef>,
+    /// Attached images or quoted post. Only written: fetched posts are read
+    /// from the hydrated `embed` view instead
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    embed: Option<RecordEmbed>,
+    /// Mentions, links and hashtags (only written, like `embed`)
+    #[serde(default, skip_deserializing, skip_serializing_if = "Vec::is_empty")]
+    facets: Vec<Facet>,
+    /// Facets of a fetched post, kept as JSON for `Post::content_raw`
+    #[serde(rename = "facets", default, skip_serializing)]
+    raw_facets: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResolveHandleResponse {
+    did: String,
+}
+
+/// Embed as stored in a post record (as opposed to the hydrated view)
+#[derive(Debug, Serialize, Clone)]
+struct RecordEmbed {
+    #[serde(rename = "$type")]
+    r#type: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    images: Vec<RecordEmbedImage>,
+    /// Quoted post (`app.bsky.embed.record`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    record: Option<StrongRef>,
+}
+
+impl RecordEmbed {
+    /// Embed uploaded images
+    fn images(images: Vec<RecordEmbedImage>) -> Self {
+        Self {
+            r#type: "app.bsky.embed.images".to_string(),
+            images,
+            record: None,
+        }
+    }
+
+    /// Embed a quoted post
+    fn quote(uri: &str, cid: &str) -> Self {
+        Self {
+            r#type: "app.bsky.embed.record".to_string(),
+            images: Vec::new(),
+            record: Some(StrongRef {
+                uri: uri.to_string(),
+                cid: cid.to_string(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct RecordEmbedImage {
+    /// Blob reference returned by `uploadBlob`
+    image: serde_json::Value,
+    alt: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct UploadBlobResponse {
+    blob: serde_json::Value,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -734,16 +1598,142 @@ struct ReplyRef {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 struct StrongRef {
     uri: String,
-    #[allow(dead_code)]
     cid: String,
 }
 
+impl StrongRef {
+    /// Reference to a post fetched or created here
+    fn of(post: &Post) -> Result<Self> {
+        let (Some(uri), Some(cid)) = (&post.uri, &post.cid) else {
+            anyhow::bail!("Post has no URI or CID to reply to");
+        };
+        Ok(Self {
+            uri: uri.clone(),
+            cid: cid.clone(),
+        })
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 struct Embed {
     #[allow(dead_code)]
     #[serde(rename = "$type")]
-    embed_type: String,
+    r#type: String,
     images: Option<Vec<EmbedImage>>,
+    /// Link card (`app.bsky.embed.external#view`)
+    #[serde(default)]
+    external: Option<EmbedExternal>,
+    /// Quoted record view (`app.bsky.embed.record#view`, or wrapped in
+    /// `recordWithMedia#view`)
+    #[serde(default)]
+    record: Option<serde_json::Value>,
+    /// Images or link card next to a quote (`recordWithMedia#view`)
+    #[serde(default)]
+    media: Option<Box<Embed>>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+struct EmbedExternal {
+    uri: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    description: String,
+    thumb: Option<String>,
+}
+
+/// What an embed shows, split into the matching `Post` fields
+#[derive(Debug, Default)]
+struct EmbedParts {
+    media: Vec<MediaAttachment>,
+    quote: Option<Box<Post>>,
+    link_card: Option<LinkCard>,
+}
+
+impl Embed {
+    /// Images, link card and quoted post shown by this embed
+    fn into_parts(self) -> EmbedParts {
+        // `recordWithMedia` keeps the images or link card in a nested embed
+        let mut parts = self.media.map(|m| m.into_parts()).unwrap_or_default();
+
+        if let Some(images) = self.images {
+            parts.media = images
+                .into_iter()
+                .map(|img| MediaAttachment {
+                    url: img.fullsize,
+                    preview_url: Some(img.thumb),
+                    media_type: MediaType::Image,
+                    alt_text: img.alt,
+                    // Bluesky doesn't send blurhashes
+                    blurhash: None,
+                })
+                .collect();
+        }
+        if let Some(external) = self.external {
+            parts.link_card = Some(LinkCard {
+                uri: external.uri,
+                title: external.title,
+                description: external.description,
+                thumb: external.thumb,
+            });
+        }
+        if let Some(quote) = self.record.as_ref().and_then(quoted_post) {
+            parts.quote = Some(Box::new(quote));
+        }
+        parts
+    }
+}
+
+/// A quoted post as shown in a record embed view
+#[derive(Debug, Deserialize)]
+struct QuotedRecordView {
+    uri: String,
+    cid: String,
+    author: Author,
+    value: QuotedRecordValue,
+    #[serde(rename = "indexedAt")]
+    indexed_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuotedRecordValue {
+    #[serde(default)]
+    text: String,
+    #[serde(rename = "createdAt")]
+    created_at: Option<String>,
+}
+
+/// The post inside a record embed view, if it is one that can be shown (not
+/// a feed or list, and not deleted or blocked)
+fn quoted_post(record: &serde_json::Value) -> Option<Post> {
+    // `recordWithMedia` views nest the record view one level deeper
+    let view = if record.get("uri").is_some() {
+        record
+    } else {
+        record.get("record")?
+    };
+    let view: QuotedRecordView = serde_json::from_value(view.clone()).ok()?;
+
+    let rkey = view.uri.split('/').next_back().unwrap_or(&view.uri);
+    let mut post = Post::new(Network::Bluesky, rkey);
+    post.url = Some(format!(
+        "https://bsky.app/profile/{}/post/{rkey}",
+        view.author.handle
+    ));
+    post.created_at = view
+        .value
+        .created_at
+        .as_deref()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .or_else(|| DateTime::parse_from_rfc3339(&view.indexed_at).ok())
+        .map_or_else(Utc::now, |dt| dt.with_timezone(&Utc));
+    post.author_handle = view.author.handle;
+    post.author_name = view.author.display_name.unwrap_or_default();
+    post.author_avatar = view.author.avatar;
+    post.content = view.value.text;
+    post.cid = Some(view.cid);
+    post.uri = Some(view.uri);
+    Some(post)
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -784,18 +1774,89 @@ struct RepostRecord {
     r#type: String,
 }
 
+/// A follow or block record
+#[derive(Debug, Serialize)]
+struct GraphRecord {
+    /// DID of the followed or blocked account
+    subject: String,
+    #[serde(rename = "createdAt")]
+    created_at: String,
+    #[serde(rename = "$type")]
+    r#type: String,
+}
+
 #[derive(Debug, Serialize)]
 struct RecordRef {
     uri: String,
     cid: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct DescribeServerResponse {
+    #[serde(default)]
+    did: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct ProfileResponse {
+    #[serde(default)]
+    did: String,
     handle: String,
     #[serde(rename = "displayName")]
     display_name: Option<String>,
     avatar: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(rename = "followersCount", default)]
+    followers_count: u64,
+    #[serde(rename = "followsCount", default)]
+    follows_count: u64,
+    #[serde(rename = "postsCount", default)]
+    posts_count: u64,
+    /// The signed-in account's relationship to this one
+    #[serde(default)]
+    viewer: Option<ProfileViewer>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct ProfileViewer {
+    /// URI of the signed-in account's follow record, if it follows
+    #[serde(default)]
+    following: Option<String>,
+    #[serde(default)]
+    muted: bool,
+    /// URI of the signed-in account's block record, if it blocks
+    #[serde(default)]
+    blocking: Option<String>,
+}
+
+impl ProfileResponse {
+    fn into_profile(self) -> Profile {
+        let viewer = self.viewer.unwrap_or_default();
+        let follow_uri = viewer.following;
+        Profile {
+            network: Network::Bluesky,
+            url: Some(format!("https://bsky.app/profile/{}", self.handle)),
+            network_id: self.did,
+            handle: self.handle,
+            display_name: self.display_name.unwrap_or_default(),
+            bio: self.description.unwrap_or_default(),
+            avatar_url: self.avatar,
+            followers_count: self.followers_count,
+            following_count: self.follows_count,
+            posts_count: self.posts_count,
+            locked: false,
+            follow_state: if follow_uri.is_some() {
+                FollowState::Following
+            } else {
+                FollowState::NotFollowing
+            },
+            follow_uri,
+            muted: viewer.muted,
+            blocked: viewer.blocking.is_some(),
+            block_uri: viewer.blocking,
+        }
+    }
 }
 
 /// Viewer state for a post (whether current user liked/reposted)
@@ -821,10 +1882,12 @@ struct ThreadViewPost {
     thread_type: Option<String>,
     post: PostView,
     #[serde(default)]
+    parent: Option<Box<ThreadReply>>,
+    #[serde(default)]
     replies: Vec<ThreadReply>,
 }
 
-/// A reply in a thread (can be nested)
+/// A parent or reply in a thread (can be nested)
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 enum ThreadReply {
@@ -834,6 +1897,18 @@ enum ThreadReply {
 }
 
 impl ThreadViewPost {
+    /// Collect the parent chain, thread root first
+    fn collect_ancestors(&self) -> Vec<Post> {
+        let mut ancestors = Vec::new();
+        let mut parent = self.parent.as_deref();
+        while let Some(ThreadReply::Post(thread_post)) = parent {
+            ancestors.push(post_view_to_post(thread_post.post.clone()));
+            parent = thread_post.parent.as_deref();
+        }
+        ancestors.reverse();
+        ancestors
+    }
+
     /// Recursively collect all replies as flat list
     fn collect_replies(&self, result: &mut Vec<Post>) {
         for reply in &self.replies {
@@ -853,21 +1928,7 @@ fn post_view_to_post(post_view: PostView) -> Post {
         .or_else(|_| DateTime::parse_from_rfc3339(&post_view.indexed_at))
         .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc));
 
-    let media = post_view
-        .embed
-        .and_then(|e| e.images)
-        .map(|images| {
-            images
-                .into_iter()
-                .map(|img| MediaAttachment {
-                    url: img.fullsize,
-                    preview_url: Some(img.thumb),
-                    media_type: MediaType::Image,
-                    alt_text: img.alt,
-                })
-                .collect()
-        })
-        .unwrap_or_default();
+    let embed = post_view.embed.map(Embed::into_parts).unwrap_or_default();
 
     // Build URL from URI
     let url = format!(
@@ -894,8 +1955,9 @@ fn post_view_to_post(post_view: PostView) -> Post {
         author_handle: post_view.author.handle,
         author_name: post_view.author.display_name.unwrap_or_default(),
         author_avatar: post_view.author.avatar,
+        content_raw: post_view.record.raw_facets.map(|f| f.to_string()),
         content: post_view.record.text,
-        content_raw: None,
+        spoiler_text: None,
         created_at,
         url: Some(url),
         is_repost: false,
@@ -905,10 +1967,14 @@ fn post_view_to_post(post_view: PostView) -> Post {
         reply_count: post_view.reply_count,
         liked,
         reposted,
+        bookmarked: false,
         reply_to_id: post_view.record.reply.map(|r| r.parent.uri),
-        media,
+        media: embed.media,
         cid: Some(post_view.cid),
         uri: Some(post_view.uri),
+        quote: embed.quote,
+        link_card: embed.link_card,
+        owner_account_id: None,
     }
 }
 
@@ -937,22 +2003,7 @@ impl FeedViewPost {
             (false, None)
         };
 
-        let media = self
-            .post
-            .embed
-            .and_then(|e| e.images)
-            .map(|images| {
-                images
-                    .into_iter()
-                    .map(|img| MediaAttachment {
-                        url: img.fullsize,
-                        preview_url: Some(img.thumb),
-                        media_type: MediaType::Image,
-                        alt_text: img.alt,
-                    })
-                    .collect()
-            })
-            .unwrap_or_default();
+        let embed = self.post.embed.map(Embed::into_parts).unwrap_or_default();
 
         // Build URL from URI
         let url = format!(
@@ -981,8 +2032,9 @@ impl FeedViewPost {
             author_handle: self.post.author.handle,
             author_name: self.post.author.display_name.unwrap_or_default(),
             author_avatar: self.post.author.avatar,
+            content_raw: self.post.record.raw_facets.map(|f| f.to_string()),
             content: self.post.record.text,
-            content_raw: None,
+            spoiler_text: None,
             created_at,
             url: Some(url),
             is_repost,
@@ -992,10 +2044,360 @@ impl FeedViewPost {
             reply_count: self.post.reply_count,
             liked,
             reposted,
+            bookmarked: false,
             reply_to_id: None,
-            media,
+            media: embed.media,
             cid: Some(self.post.cid),
             uri: Some(self.post.uri),
+            quote: embed.quote,
+            link_card: embed.link_card,
+            owner_account_id: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_server::{MockServer, Route};
+
+    /// The paths of the requests `server` received, in order
+    fn paths(server: &MockServer) -> Vec<String> {
+        server
+            .requests()
+            .iter()
+            .map(|request| request.path().to_string())
+            .collect()
+    }
+
+    /// Minimal `threadViewPost` JSON for a post with the given rkey and parent
+    fn thread_json(rkey: &str, parent: Option<String>) -> String {
+        let parent = parent
+            .map(|p| format!(r#","parent":{p}"#))
+            .unwrap_or_default();
+        format!(
+            r#"{{"$type":"app.bsky.feed.defs#threadViewPost","post":{{"uri":"at://did:plc:a/app.bsky.feed.post/{rkey}","cid":"c{rkey}","author":{{"did":"did:plc:a","handle":"a.bsky.social"}},"record":{{"text":"{rkey}","createdAt":"2026-01-01T00:00:00Z","$type":"app.bsky.feed.post"}},"indexedAt":"2026-01-01T00:00:00Z"}}{parent}}}"#
+        )
+    }
+
+    #[test]
+    fn test_profile_follow_state_from_viewer() {
+        let followed: ProfileResponse = serde_json::from_str(
+            r#"{"did": "did:plc:a", "handle": "a.bsky.social",
+                "viewer": {"following": "at://did:plc:me/app.bsky.graph.follow/1"}}"#,
+        )
+        .unwrap();
+        let profile = followed.into_profile();
+        assert_eq!(profile.follow_state, FollowState::Following);
+        assert_eq!(
+            profile.follow_uri.as_deref(),
+            Some("at://did:plc:me/app.bsky.graph.follow/1")
+        );
+
+        let stranger: ProfileResponse =
+            serde_json::from_str(r#"{"did": "did:plc:b", "handle": "b.bsky.social"}"#).unwrap();
+        assert_eq!(
+            stranger.into_profile().follow_state,
+            FollowState::NotFollowing
+        );
+    }
+
+    #[test]
+    fn test_thread_ancestors_are_root_first() {
+        let root = thread_json("root", None);
+        let middle = thread_json("middle", Some(root));
+        let leaf = thread_json("leaf", Some(middle));
+
+        let thread: ThreadViewPost = serde_json::from_str(&leaf).unwrap();
+        let ancestors: Vec<_> = thread
+            .collect_ancestors()
+            .into_iter()
+            .map(|p| p.content)
+            .collect();
+
+        assert_eq!(ancestors, ["root", "middle"]);
+    }
+
+    #[test]
+    fn test_quote_embed_serialization() {
+        let record = PostRecord {
+            text: "So true".to_string(),
+            created_at: "2026-01-01T00:00:00.000Z".to_string(),
+            r#type: "app.bsky.feed.post".to_string(),
+            reply: None,
+            embed: Some(RecordEmbed::quote(
+                "at://did:plc:a/app.bsky.feed.post/abc",
+                "bafyquoted",
+            )),
+            facets: Vec::new(),
+            raw_facets: None,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&record).unwrap(),
+            serde_json::json!({
+                "text": "So true",
+                "createdAt": "2026-01-01T00:00:00.000Z",
+                "$type": "app.bsky.feed.post",
+                "reply": null,
+                "embed": {
+                    "$type": "app.bsky.embed.record",
+                    "record": {
+                        "uri": "at://did:plc:a/app.bsky.feed.post/abc",
+                        "cid": "bafyquoted"
+                    }
+                }
+            })
+        );
+    }
+
+    /// Hydrated embed views as returned in `app.bsky.feed.defs#postView`
+    const IMAGES_VIEW: &str = r#"{"$type":"app.bsky.embed.images#view","images":[{"thumb":"https://cdn/t.jpg","fullsize":"https://cdn/f.jpg","alt":"A cat"}]}"#;
+    const EXTERNAL_VIEW: &str = r#"{"$type":"app.bsky.embed.external#view","external":{"uri":"https://example.com/article","title":"An article","description":"What it says","thumb":"https://cdn/card.jpg"}}"#;
+    const RECORD_VIEW: &str = r#"{"$type":"app.bsky.embed.record#view","record":{"$type":"app.bsky.embed.record#viewRecord","uri":"at://did:plc:a/app.bsky.feed.post/abc","cid":"bafyquoted","author":{"did":"did:plc:a","handle":"a.bsky.social","displayName":"A"},"value":{"$type":"app.bsky.feed.post","text":"Original","createdAt":"2026-01-01T00:00:00Z"},"indexedAt":"2026-01-01T00:00:00Z"}}"#;
+    const NOT_FOUND_VIEW: &str = r#"{"$type":"app.bsky.embed.record#view","record":{"$type":"app.bsky.embed.record#viewNotFound","uri":"at://x","notFound":true}}"#;
+
+    fn embed_parts(json: &str) -> EmbedParts {
+        serde_json::from_str::<Embed>(json).unwrap().into_parts()
+    }
+
+    /// A `recordWithMedia` view around `RECORD_VIEW` and the given media view
+    fn record_with_media(media: &str) -> String {
+        let record: serde_json::Value = serde_json::from_str(RECORD_VIEW).unwrap();
+        serde_json::json!({
+            "$type": "app.bsky.embed.recordWithMedia#view",
+            "record": record,
+            "media": serde_json::from_str::<serde_json::Value>(media).unwrap(),
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_embed_images() {
+        let parts = embed_parts(IMAGES_VIEW);
+        assert_eq!(parts.media.len(), 1);
+        assert_eq!(parts.media[0].alt_text.as_deref(), Some("A cat"));
+        assert!(parts.quote.is_none() && parts.link_card.is_none());
+    }
+
+    #[test]
+    fn test_embed_external_card() {
+        let parts = embed_parts(EXTERNAL_VIEW);
+        assert_eq!(
+            parts.link_card,
+            Some(LinkCard {
+                uri: "https://example.com/article".to_string(),
+                title: "An article".to_string(),
+                description: "What it says".to_string(),
+                thumb: Some("https://cdn/card.jpg".to_string()),
+            })
+        );
+        assert!(parts.media.is_empty() && parts.quote.is_none());
+    }
+
+    #[test]
+    fn test_embed_quoted_record() {
+        let quote = embed_parts(RECORD_VIEW).quote.unwrap();
+        assert_eq!(quote.content, "Original");
+        assert_eq!(quote.network_id, "abc");
+        assert_eq!(quote.author_name, "A");
+
+        // Deleted (or blocked) posts aren't shown
+        assert!(embed_parts(NOT_FOUND_VIEW).quote.is_none());
+    }
+
+    #[test]
+    fn test_embed_record_with_media() {
+        let parts = embed_parts(&record_with_media(IMAGES_VIEW));
+        assert_eq!(parts.media.len(), 1);
+        assert_eq!(parts.quote.unwrap().content, "Original");
+
+        let parts = embed_parts(&record_with_media(EXTERNAL_VIEW));
+        assert_eq!(parts.link_card.unwrap().title, "An article");
+        assert_eq!(parts.quote.unwrap().content, "Original");
+    }
+
+    #[test]
+    fn test_thread_ancestors_stop_at_missing_parent() {
+        let not_found =
+            r#"{"$type":"app.bsky.feed.defs#notFoundPost","uri":"at://x","notFound":true}"#;
+        let middle = thread_json("middle", Some(not_found.to_string()));
+        let leaf = thread_json("leaf", Some(middle));
+
+        let thread: ThreadViewPost = serde_json::from_str(&leaf).unwrap();
+        let ancestors = thread.collect_ancestors();
+
+        assert_eq!(ancestors.len(), 1);
+        assert_eq!(ancestors[0].content, "middle");
+    }
+
+    #[test]
+    fn test_is_expired_token() {
+        assert!(is_expired_token(
+            r#"{"error":"ExpiredToken","message":"Token has expired"}"#
+        ));
+        assert!(!is_expired_token(r#"{"error":"InvalidRequest"}"#));
+        assert!(!is_expired_token("not json"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_credentials_describes_server() {
+        let server = MockServer::start([
+            Route::new(
+                "POST",
+                "/xrpc/com.atproto.server.createSession",
+                200,
+                r#"{"accessJwt":"access","refreshJwt":"refresh","did":"did:plc:test","handle":"test.bsky.social"}"#,
+            ),
+            Route::new(
+                "GET",
+                "/xrpc/app.bsky.actor.getProfile",
+                200,
+                r#"{"did":"did:plc:test","handle":"test.bsky.social","displayName":"Test"}"#,
+            ),
+            Route::new(
+                "GET",
+                "/xrpc/com.atproto.server.describeServer",
+                200,
+                r#"{"did":"did:web:pds.example.com","availableUserDomains":[".example.com"]}"#,
+            ),
+        ])
+        .await;
+
+        let client = BlueskyClient::login_with_pds("test.bsky.social", "hunter2", server.url())
+            .await
+            .unwrap();
+        let account = client.verify_credentials().await.unwrap();
+        assert_eq!(
+            account.server_name.as_deref(),
+            Some("did:web:pds.example.com")
+        );
+        assert_eq!(account.max_media_attachments, Some(4));
+        assert_eq!(account.max_image_bytes, Some(1_000_000));
+        assert_eq!(paths(&server)[2], "/xrpc/com.atproto.server.describeServer");
+    }
+
+    #[tokio::test]
+    async fn test_login_asks_for_auth_factor() {
+        let server = MockServer::start([
+            Route::new(
+                "POST",
+                "/xrpc/com.atproto.server.createSession",
+                401,
+                r#"{"error":"AuthFactorTokenRequired","message":"A sign in code has been sent to your email address"}"#,
+            ),
+            Route::new(
+                "POST",
+                "/xrpc/com.atproto.server.createSession",
+                200,
+                r#"{"accessJwt":"access","refreshJwt":"refresh","did":"did:plc:test","handle":"test.bsky.social"}"#,
+            ),
+        ])
+        .await;
+        let url = server.url();
+
+        let Err(error) = BlueskyClient::login_with_pds("test.bsky.social", "hunter2", url).await
+        else {
+            panic!("login without the code should fail");
+        };
+        assert_eq!(
+            error.downcast_ref::<LoginError>(),
+            Some(&LoginError::AuthFactorRequired)
+        );
+
+        let client = BlueskyClient::login_with_auth_factor(
+            "test.bsky.social",
+            "hunter2",
+            url,
+            Some("AB-12"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(client.session().did, "did:plc:test");
+    }
+
+    #[test]
+    fn test_login_errors() {
+        let invalid =
+            r#"{"error":"AuthenticationRequired","message":"Invalid identifier or password"}"#;
+        assert_eq!(
+            LoginError::from_response(invalid, "abcd-efgh-1234-5678"),
+            LoginError::InvalidCredentials { app_password: true }
+        );
+        // Probably the account password rather than an app password
+        assert_eq!(
+            LoginError::from_response(invalid, "correct horse battery staple"),
+            LoginError::InvalidCredentials {
+                app_password: false
+            }
+        );
+        assert_eq!(
+            LoginError::from_response(
+                r#"{"error":"InvalidToken","message":"Token is invalid"}"#,
+                ""
+            ),
+            LoginError::InvalidAuthFactor
+        );
+        assert_eq!(
+            LoginError::from_response(r#"{"error":"RateLimitExceeded","message":"Slow down"}"#, ""),
+            LoginError::Other("Slow down".to_string())
+        );
+        assert_eq!(
+            LoginError::from_response("Bad Gateway", ""),
+            LoginError::Other("Bad Gateway".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_refreshes_and_retries() {
+        let server = MockServer::start([
+            Route::new(
+                "GET",
+                "/xrpc/app.bsky.feed.getTimeline",
+                400,
+                r#"{"error":"ExpiredToken","message":"Token has expired"}"#,
+            ),
+            Route::new(
+                "POST",
+                "/xrpc/com.atproto.server.refreshSession",
+                200,
+                r#"{"accessJwt":"new-access","refreshJwt":"new-refresh","did":"did:plc:test","handle":"test.bsky.social"}"#,
+            ),
+            Route::new(
+                "GET",
+                "/xrpc/app.bsky.feed.getTimeline",
+                200,
+                r#"{"feed":[]}"#,
+            ),
+        ])
+        .await;
+
+        let client = BlueskyClient::from_session(
+            server.url(),
+            Session {
+                did: "did:plc:test".to_string(),
+                access_jwt: "old-access".to_string(),
+                refresh_jwt: "old-refresh".to_string(),
+            },
+        );
+
+        let posts = client.timeline(10).await.unwrap();
+        assert!(posts.is_empty());
+
+        assert_eq!(
+            paths(&server),
+            [
+                "/xrpc/app.bsky.feed.getTimeline",
+                "/xrpc/com.atproto.server.refreshSession",
+                "/xrpc/app.bsky.feed.getTimeline",
+            ]
+        );
+        // The retry went out with the new token
+        let retry = &server.requests()[2];
+        assert_eq!(retry.header("authorization"), Some("Bearer new-access"));
+
+        let session = client.session();
+        assert_eq!(session.access_jwt, "new-access");
+        assert_eq!(session.refresh_jwt, "new-refresh");
+    }
+}
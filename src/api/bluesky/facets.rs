@@ -0,0 +1,178 @@
+//! Rich text facets (mentions, links and hashtags) for Bluesky posts
+//!
+//! Facets point into the post text by UTF-8 byte offsets, not character
+//! offsets, so anything before them that isn't ASCII (emoji, accents) must be
+//! counted in bytes.
+
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use regex_lite::Regex;
+use serde::Serialize;
+
+use crate::models::{find_links, split_hashtags};
+
+/// Something in the text that becomes a facet, before mentions are resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FacetTarget {
+    /// `@handle` (without the `@`), to be resolved to a DID
+    Mention(String),
+    /// `http(s)://` URL
+    Link(String),
+    /// `#hashtag` (without the `#`)
+    Tag(String),
+}
+
+/// A facet as stored in a post record (`app.bsky.richtext.facet`)
+#[derive(Debug, Clone, Serialize)]
+pub struct Facet {
+    index: ByteSlice,
+    features: Vec<Feature>,
+}
+
+impl Facet {
+    /// Facet for one feature over a byte range of the text
+    pub fn new(range: Range<usize>, feature: Feature) -> Self {
+        Self {
+            index: ByteSlice {
+                byte_start: range.start,
+                byte_end: range.end,
+            },
+            features: vec![feature],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ByteSlice {
+    #[serde(rename = "byteStart")]
+    byte_start: usize,
+    #[serde(rename = "byteEnd")]
+    byte_end: usize,
+}
+
+/// What a facet links to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "$type")]
+pub enum Feature {
+    #[serde(rename = "app.bsky.richtext.facet#mention")]
+    Mention { did: String },
+    #[serde(rename = "app.bsky.richtext.facet#link")]
+    Link { uri: String },
+    #[serde(rename = "app.bsky.richtext.facet#tag")]
+    Tag { tag: String },
+}
+
+fn mention_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?:^|[\s(])(@[a-zA-Z0-9.-]+)").expect("valid regex"))
+}
+
+/// Find mentions, links and hashtags in post text, with their byte ranges,
+/// in order of appearance
+pub fn detect(text: &str) -> Vec<(Range<usize>, FacetTarget)> {
+    let mut found = Vec::new();
+
+    for range in find_links(text) {
+        let link = text[range.clone()].to_string();
+        found.push((range, FacetTarget::Link(link)));
+    }
+
+    for m in mention_regex().captures_iter(text).filter_map(|c| c.get(1)) {
+        let mention = m.as_str().trim_end_matches(['.', '-']);
+        let handle = &mention[1..];
+        // Handles are domain names
+        if !handle.contains('.') {
+            continue;
+        }
+        found.push((
+            m.start()..m.start() + mention.len(),
+            FacetTarget::Mention(handle.to_string()),
+        ));
+    }
+
+    let mut offset = 0;
+    for (segment, is_tag) in split_hashtags(text) {
+        if is_tag {
+            found.push((
+                offset..offset + segment.len(),
+                FacetTarget::Tag(segment[1..].to_string()),
+            ));
+        }
+        offset += segment.len();
+    }
+
+    // Facets can't overlap; earlier ones (links first) win
+    let mut facets: Vec<(Range<usize>, FacetTarget)> = Vec::new();
+    for (range, target) in found {
+        if !facets
+            .iter()
+            .any(|(r, _)| r.start < range.end && range.start < r.end)
+        {
+            facets.push((range, target));
+        }
+    }
+    facets.sort_by_key(|(range, _)| range.start);
+    facets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_uses_byte_offsets() {
+        let text = "👋🏽 Hé @alice.bsky.social, see https://example.com/a. #rust 🦀 #日本";
+        let facets = detect(text);
+
+        let spans: Vec<&str> = facets.iter().map(|(r, _)| &text[r.clone()]).collect();
+        assert_eq!(
+            spans,
+            [
+                "@alice.bsky.social",
+                "https://example.com/a",
+                "#rust",
+                "#日本"
+            ]
+        );
+        assert_eq!(facets[0].0.start, "👋🏽 Hé ".len());
+        assert_eq!(
+            facets.iter().map(|(_, t)| t.clone()).collect::<Vec<_>>(),
+            [
+                FacetTarget::Mention("alice.bsky.social".to_string()),
+                FacetTarget::Link("https://example.com/a".to_string()),
+                FacetTarget::Tag("rust".to_string()),
+                FacetTarget::Tag("日本".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_skips_lookalikes() {
+        // Emails, bare names, numbers and fragments inside links
+        let text = "mail me@example.com or @bob, #1 (https://example.com/#top)";
+        let facets = detect(text);
+
+        assert_eq!(facets.len(), 1);
+        assert_eq!(&text[facets[0].0.clone()], "https://example.com/#top");
+    }
+
+    #[test]
+    fn test_facet_serialization() {
+        let facet = Facet::new(
+            3..9,
+            Feature::Mention {
+                did: "did:plc:abc".to_string(),
+            },
+        );
+        assert_eq!(
+            serde_json::to_value(&facet).unwrap(),
+            serde_json::json!({
+                "index": { "byteStart": 3, "byteEnd": 9 },
+                "features": [
+                    { "$type": "app.bsky.richtext.facet#mention", "did": "did:plc:abc" }
+                ]
+            })
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! Finding the PDS that hosts an account
+//!
+//! A handle resolves to a DID, and the DID document names the account's
+//! Personal Data Server as its `#atproto_pds` service. `did:plc` documents
+//! come from the PLC directory; `did:web` ones are served by the domain
+//! itself at `/.well-known/did.json`.
+
+use anyhow::{Context, Result, bail};
+use reqwest::Client;
+use serde::Deserialize;
+
+use super::{DEFAULT_PDS_URL, ResolveHandleResponse, http_client};
+
+/// Where `did:plc` documents are published
+const PLC_DIRECTORY_URL: &str = "https://plc.directory";
+
+/// A DID document, as far as finding the PDS needs
+#[derive(Debug, Deserialize)]
+struct DidDocument {
+    id: String,
+    #[serde(default)]
+    service: Vec<DidService>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DidService {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(rename = "serviceEndpoint")]
+    endpoint: serde_json::Value,
+}
+
+/// Find the URL of the PDS hosting `handle` (or a DID)
+pub async fn resolve_pds(handle: &str) -> Result<String> {
+    let client = http_client();
+    let handle = handle.trim().trim_start_matches('@');
+    let did = if handle.starts_with("did:") {
+        handle.to_string()
+    } else {
+        resolve_handle(&client, handle).await?
+    };
+
+    let document: DidDocument = client
+        .get(did_document_url(&did)?)
+        .send()
+        .await
+        .context("Failed to fetch DID document")?
+        .error_for_status()
+        .context("Failed to fetch DID document")?
+        .json()
+        .await
+        .context("Failed to parse DID document")?;
+
+    pds_endpoint(&document).with_context(|| format!("{did} doesn't name a PDS"))
+}
+
+/// Look up the DID for a handle with the default PDS, which resolves handles
+/// hosted anywhere
+async fn resolve_handle(client: &Client, handle: &str) -> Result<String> {
+    let url = format!(
+        "{DEFAULT_PDS_URL}/xrpc/com.atproto.identity.resolveHandle?handle={}",
+        urlencoding::encode(handle)
+    );
+    let resolved: ResolveHandleResponse = client
+        .get(&url)
+        .send()
+        .await
+        .context("Failed to resolve handle")?
+        .error_for_status()
+        .with_context(|| format!("No Bluesky account found for @{handle}"))?
+        .json()
+        .await
+        .context("Failed to parse resolveHandle response")?;
+    Ok(resolved.did)
+}
+
+/// Where the DID document for `did` is published
+fn did_document_url(did: &str) -> Result<String> {
+    if did.starts_with("did:plc:") {
+        return Ok(format!("{PLC_DIRECTORY_URL}/{did}"));
+    }
+    let Some(id) = did.strip_prefix("did:web:") else {
+        bail!("Unsupported DID method: {did}");
+    };
+    // `did:web:example.com:user:alice` lives at example.com/user/alice/did.json
+    let mut parts = id.split(':');
+    let host = urlencoding::decode(parts.next().unwrap_or_default())?;
+    let path: Vec<&str> = parts.collect();
+    Ok(if path.is_empty() {
+        format!("https://{host}/.well-known/did.json")
+    } else {
+        format!("https://{host}/{}/did.json", path.join("/"))
+    })
+}
+
+/// The `#atproto_pds` service endpoint of a DID document
+fn pds_endpoint(document: &DidDocument) -> Option<String> {
+    document
+        .service
+        .iter()
+        .find(|s| {
+            s.kind == "AtprotoPersonalDataServer"
+                && (s.id == "#atproto_pds" || s.id == format!("{}#atproto_pds", document.id))
+        })?
+        .endpoint
+        .as_str()
+        .filter(|url| url.starts_with("https://") || url.starts_with("http://"))
+        .map(|url| url.trim_end_matches('/').to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_did_plc_document() {
+        assert_eq!(
+            did_document_url("did:plc:ewvi7nxzyoun6zhxrhs64oiz").unwrap(),
+            "https://plc.directory/did:plc:ewvi7nxzyoun6zhxrhs64oiz"
+        );
+
+        let document: DidDocument = serde_json::from_str(
+            r##"{
+                "@context": ["https://www.w3.org/ns/did/v1"],
+                "id": "did:plc:ewvi7nxzyoun6zhxrhs64oiz",
+                "alsoKnownAs": ["at://atproto.com"],
+                "verificationMethod": [],
+                "service": [{
+                    "id": "#atproto_pds",
+                    "type": "AtprotoPersonalDataServer",
+                    "serviceEndpoint": "https://enoki.us-east.host.bsky.network/"
+                }]
+            }"##,
+        )
+        .unwrap();
+        assert_eq!(
+            pds_endpoint(&document).as_deref(),
+            Some("https://enoki.us-east.host.bsky.network")
+        );
+    }
+
+    #[test]
+    fn test_did_web_document() {
+        assert_eq!(
+            did_document_url("did:web:pds.example.com").unwrap(),
+            "https://pds.example.com/.well-known/did.json"
+        );
+        assert_eq!(
+            did_document_url("did:web:example.com%3A8443:user:alice").unwrap(),
+            "https://example.com:8443/user/alice/did.json"
+        );
+        assert!(did_document_url("did:key:z6Mk").is_err());
+
+        let document: DidDocument = serde_json::from_str(
+            r#"{
+                "id": "did:web:pds.example.com",
+                "service": [
+                    {"id": "did:web:pds.example.com#bsky_notif", "type": "BskyNotificationService",
+                     "serviceEndpoint": "https://notify.example.com"},
+                    {"id": "did:web:pds.example.com#atproto_pds", "type": "AtprotoPersonalDataServer",
+                     "serviceEndpoint": "https://pds.example.com"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            pds_endpoint(&document).as_deref(),
+            Some("https://pds.example.com")
+        );
+
+        let document: DidDocument =
+            serde_json::from_str(r#"{"id": "did:web:example.com"}"#).unwrap();
+        assert_eq!(pds_endpoint(&document), None);
+    }
+}
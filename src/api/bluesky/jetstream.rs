@@ -0,0 +1,156 @@
+//! Jetstream, Bluesky's JSON firehose, filtered to followed accounts
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+/// Public Jetstream instance, as `https://` for the WebSocket handshake.
+/// `requireHello` holds events back until the filter has been sent.
+pub const JETSTREAM_URL: &str =
+    "https://jetstream2.us-east.bsky.network/subscribe?requireHello=true";
+
+/// Most DIDs Jetstream will filter on
+pub const MAX_WANTED_DIDS: usize = 10_000;
+
+const POST_COLLECTION: &str = "app.bsky.feed.post";
+
+/// Message that sets which records Jetstream sends
+#[derive(Debug, Serialize)]
+pub struct OptionsUpdate<'a> {
+    #[serde(rename = "type")]
+    r#type: &'static str,
+    payload: Options<'a>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Options<'a> {
+    wanted_collections: [&'static str; 1],
+    wanted_dids: &'a [String],
+}
+
+impl<'a> OptionsUpdate<'a> {
+    /// Only send posts created by these accounts
+    pub const fn posts_from(dids: &'a [String]) -> Self {
+        Self {
+            r#type: "options_update",
+            payload: Options {
+                wanted_collections: [POST_COLLECTION],
+                wanted_dids: dids,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Event {
+    did: String,
+    kind: String,
+    commit: Option<Commit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Commit {
+    operation: String,
+    collection: String,
+    rkey: String,
+    record: Option<Record>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Record {
+    reply: Option<Reply>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Reply {
+    parent: Parent,
+}
+
+#[derive(Debug, Deserialize)]
+struct Parent {
+    uri: String,
+}
+
+/// The `at://` URI of a post created in a Jetstream message.
+///
+/// Like the following feed, replies only count when they answer an account
+/// in `followed`.
+pub fn new_post_uri(message: &str, followed: &HashSet<String>) -> Option<String> {
+    let event: Event = serde_json::from_str(message).ok()?;
+    let commit = event.commit.filter(|c| {
+        event.kind == "commit" && c.operation == "create" && c.collection == POST_COLLECTION
+    })?;
+
+    if let Some(reply) = commit.record.and_then(|r| r.reply) {
+        let parent_did = reply.parent.uri.strip_prefix("at://")?.split('/').next()?;
+        if !followed.contains(parent_did) {
+            return None;
+        }
+    }
+
+    Some(format!(
+        "at://{}/{POST_COLLECTION}/{}",
+        event.did, commit.rkey
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_event(did: &str, record: &str) -> String {
+        format!(
+            r#"{{"did":"{did}","time_us":1,"kind":"commit","commit":{{"rev":"r","operation":"create","collection":"app.bsky.feed.post","rkey":"3abc","record":{record},"cid":"c"}}}}"#
+        )
+    }
+
+    #[test]
+    fn test_new_post_uri() {
+        let followed = HashSet::from(["did:plc:alice".to_string()]);
+        let message = create_event("did:plc:alice", r#"{"text":"hi"}"#);
+        assert_eq!(
+            new_post_uri(&message, &followed).as_deref(),
+            Some("at://did:plc:alice/app.bsky.feed.post/3abc")
+        );
+
+        let delete = message.replace("\"create\"", "\"delete\"");
+        assert_eq!(new_post_uri(&delete, &followed), None);
+        assert_eq!(
+            new_post_uri(r#"{"did":"x","kind":"identity"}"#, &followed),
+            None
+        );
+    }
+
+    #[test]
+    fn test_new_post_uri_skips_replies_to_strangers() {
+        let followed = HashSet::from(["did:plc:alice".to_string(), "did:plc:bob".to_string()]);
+        let reply = |parent: &str| {
+            create_event(
+                "did:plc:alice",
+                &format!(
+                    r#"{{"text":"hi","reply":{{"parent":{{"uri":"at://{parent}/app.bsky.feed.post/1","cid":"c"}},"root":{{"uri":"at://{parent}/app.bsky.feed.post/1","cid":"c"}}}}}}"#
+                ),
+            )
+        };
+
+        assert!(new_post_uri(&reply("did:plc:bob"), &followed).is_some());
+        assert_eq!(new_post_uri(&reply("did:plc:stranger"), &followed), None);
+    }
+
+    #[test]
+    fn test_options_update_serialization() {
+        let dids = vec!["did:plc:alice".to_string()];
+        let json = serde_json::to_value(OptionsUpdate::posts_from(&dids)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "options_update",
+                "payload": {
+                    "wantedCollections": ["app.bsky.feed.post"],
+                    "wantedDids": ["did:plc:alice"],
+                },
+            })
+        );
+    }
+}
@@ -1,29 +1,39 @@
 //! Mastodon API client
 
+mod html;
+pub mod rate_limit;
+
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use reqwest::Client;
+use reqwest::multipart::{Form, Part};
+use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
 
-use crate::models::{Account, MediaAttachment, MediaType, Network, Post};
+use crate::filters::{FilterAction, FilterRule};
+use crate::models::{
+    Account, FollowState, MediaAttachment, MediaType, Network, Notification, NotificationKind,
+    Post, Profile, ServerSoftware,
+};
 
-use super::SocialApi;
+use html::html_to_text;
+use rate_limit::{RateLimit, SendLimited};
 
-/// Build a reqwest client with a proper User-Agent header.
-/// `GoToSocial` (and potentially other `ActivityPub` servers) reject requests
-/// without a User-Agent with HTTP 418 "I'm a teapot".
-fn http_client() -> Client {
-    Client::builder()
-        .user_agent(format!("Perch/{}", env!("CARGO_PKG_VERSION")))
-        .build()
-        .expect("Failed to build HTTP client")
-}
+use super::streaming::{PostSink, STREAM_IDLE_TIMEOUT, stream_client, take_sse_events};
+use super::{
+    MediaUpload, PostContext, PostOptions, SearchPage, SocialApi, TimelineKind, TimelinePage,
+    UPLOAD_TIMEOUT, check_media_count, http_client,
+};
+
+/// Mastodon only schedules posts at least this far ahead
+pub const MIN_SCHEDULE_LEAD: chrono::TimeDelta = chrono::TimeDelta::minutes(5);
 
 /// Mastodon API client
 pub struct MastodonClient {
     client: Client,
     instance: String,
     access_token: String,
+    /// What the instance runs, if known, for the quirks of compatible servers
+    software: Option<ServerSoftware>,
 }
 
 impl MastodonClient {
@@ -33,25 +43,61 @@ impl MastodonClient {
             client: http_client(),
             instance: instance.trim_end_matches('/').to_string(),
             access_token: access_token.to_string(),
+            software: None,
         }
     }
 
+    /// Set the software the instance is known to run
+    #[must_use]
+    pub const fn with_software(mut self, software: Option<ServerSoftware>) -> Self {
+        self.software = software;
+        self
+    }
+
+    /// Send requests through `client` instead of the shared one
+    #[must_use]
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// The rate limit this instance last reported
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        rate_limit::current(&self.instance)
+    }
+
     /// Build API URL
     fn api_url(&self, endpoint: &str) -> String {
         format!("{}/api/v1{}", self.instance, endpoint)
     }
-}
 
-impl SocialApi for MastodonClient {
-    async fn timeline(&self, limit: usize) -> Result<Vec<Post>> {
-        let url = self.api_url(&format!("/timelines/home?limit={limit}"));
+    /// Get a page of the home, local or federated timeline, starting after
+    /// `cursor`
+    pub async fn timeline_kind(
+        &self,
+        kind: TimelineKind,
+        limit: usize,
+        cursor: Option<String>,
+    ) -> Result<TimelinePage> {
+        let endpoint = match kind {
+            TimelineKind::Home => "/timelines/home?",
+            TimelineKind::Local => "/timelines/public?local=true&",
+            TimelineKind::Federated => "/timelines/public?",
+        };
+        let max_id = cursor
+            .map(|id| format!("&max_id={}", urlencoding::encode(&id)))
+            .unwrap_or_default();
+        let url = self.api_url(&format!("{endpoint}limit={limit}{max_id}"));
 
         let response = self
             .client
             .get(&url)
             .header("Authorization", format!("Bearer {}", self.access_token))
-            .send()
+            .send_limited(&self.instance)
             .await
+            .context("Failed to fetch timeline")?
+            // Instances can keep public timelines to themselves
+            .error_for_status()
             .context("Failed to fetch timeline")?;
 
         let statuses: Vec<MastodonStatus> = response
@@ -59,25 +105,427 @@ impl SocialApi for MastodonClient {
             .await
             .context("Failed to parse timeline response")?;
 
+        // Use the wrapper status ID, not the reblogged one, so boosts page correctly
+        let next_cursor = statuses.last().map(|s| s.id.clone());
+
+        Ok(TimelinePage {
+            posts: statuses
+                .into_iter()
+                .map(MastodonStatus::into_post)
+                .collect(),
+            next_cursor,
+        })
+    }
+
+    /// Stream new home timeline posts into `sink` until the connection drops
+    pub async fn stream_home(&self, sink: &PostSink) -> Result<()> {
+        let url = format!("{}/api/v1/streaming/user", self.streaming_base().await);
+
+        let mut response = stream_client()
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to connect to the streaming API")?
+            .error_for_status()
+            .context("Failed to connect to the streaming API")?;
+
+        let mut buffer = Vec::new();
+        loop {
+            // The server sends a heartbeat comment every few seconds
+            let chunk = tokio::time::timeout(STREAM_IDLE_TIMEOUT, response.chunk())
+                .await
+                .context("Streaming API stopped responding")?
+                .context("Streaming connection failed")?;
+            let Some(chunk) = chunk else {
+                return Ok(());
+            };
+            buffer.extend_from_slice(&chunk);
+
+            let posts: Vec<Post> = take_sse_events(&mut buffer)
+                .into_iter()
+                .filter(|event| event.event == "update")
+                .filter_map(
+                    |event| match serde_json::from_str::<MastodonStatus>(&event.data) {
+                        Ok(status) => Some(status.into_post()),
+                        Err(e) => {
+                            tracing::debug!("Skipping unparseable streamed status: {e}");
+                            None
+                        }
+                    },
+                )
+                .collect();
+
+            if !posts.is_empty() && sink.send(posts).await.is_err() {
+                // Nobody is listening any more
+                return Ok(());
+            }
+        }
+    }
+
+    /// Instance metadata, if the server shares it
+    async fn instance_info(&self) -> Option<InstanceResponse> {
+        self.client
+            .get(self.api_url("/instance"))
+            .send_limited(&self.instance)
+            .await
+            .ok()?
+            .error_for_status()
+            .ok()?
+            .json()
+            .await
+            .ok()
+    }
+
+    /// Longest post the instance accepts, in characters, if it says
+    pub async fn max_post_chars(&self) -> Option<usize> {
+        self.instance_info().await?.max_post_chars()
+    }
+
+    /// Have the instance publish a status at `scheduled_at`, even while
+    /// Perch isn't running. Returns the scheduled status ID.
+    pub async fn schedule_status(
+        &self,
+        content: &str,
+        options: &PostOptions,
+        scheduled_at: DateTime<Utc>,
+    ) -> Result<String> {
+        if scheduled_at < Utc::now() + MIN_SCHEDULE_LEAD {
+            anyhow::bail!(
+                "Mastodon only schedules posts at least {} minutes ahead",
+                MIN_SCHEDULE_LEAD.num_minutes()
+            );
+        }
+
+        let request = PostStatusRequest {
+            scheduled_at: Some(scheduled_at.to_rfc3339()),
+            ..PostStatusRequest::new(content, options)
+        };
+
+        let response = self
+            .client
+            .post(self.api_url("/statuses"))
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .json(&request)
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to schedule status")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Mastodon error {status}: {body}");
+        }
+
+        let scheduled: MastodonScheduledStatus = response
+            .json()
+            .await
+            .context("Failed to parse scheduled status response")?;
+
+        Ok(scheduled.id)
+    }
+
+    /// Cancel a status scheduled with [`Self::schedule_status`]
+    pub async fn cancel_scheduled_status(&self, id: &str) -> Result<()> {
+        let url = self.api_url(&format!("/scheduled_statuses/{}", urlencoding::encode(id)));
+
+        self.client
+            .delete(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to cancel scheduled status")?
+            .error_for_status()
+            .context("Failed to cancel scheduled status")?;
+
+        Ok(())
+    }
+
+    /// Keyword filters set up on the account for timelines, as client-side
+    /// filter rules (expired ones are left out)
+    pub async fn filters(&self) -> Result<Vec<FilterRule>> {
+        // Pleroma and Akkoma only have the older filters API
+        if matches!(
+            self.software,
+            Some(ServerSoftware::Pleroma | ServerSoftware::Akkoma)
+        ) {
+            return self.filters_v1().await;
+        }
+
+        let response = self
+            .client
+            .get(self.api_v2_url("/filters"))
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to fetch filters")?;
+        if self.software.is_none() && response.status() == StatusCode::NOT_FOUND {
+            return self.filters_v1().await;
+        }
+        let filters: Vec<MastodonFilter> = response
+            .error_for_status()
+            .context("Failed to fetch filters")?
+            .json()
+            .await
+            .context("Failed to parse filters response")?;
+
+        Ok(filters
+            .into_iter()
+            .filter(MastodonFilter::applies_to_timelines)
+            .flat_map(MastodonFilter::into_rules)
+            .collect())
+    }
+
+    /// Filters from `/api/v1/filters`, one keyword each
+    async fn filters_v1(&self) -> Result<Vec<FilterRule>> {
+        let filters: Vec<MastodonFilterV1> = self
+            .client
+            .get(self.api_url("/filters"))
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to fetch filters")?
+            .error_for_status()
+            .context("Failed to fetch filters")?
+            .json()
+            .await
+            .context("Failed to parse filters response")?;
+
+        Ok(filters
+            .into_iter()
+            .map(MastodonFilter::from)
+            .filter(MastodonFilter::applies_to_timelines)
+            .flat_map(MastodonFilter::into_rules)
+            .collect())
+    }
+
+    /// Base URL of the streaming server, which instances may host separately
+    async fn streaming_base(&self) -> String {
+        self.instance_info()
+            .await
+            .and_then(|i| i.urls.streaming_api)
+            .map(|url| {
+                url.replacen("wss://", "https://", 1)
+                    .replacen("ws://", "http://", 1)
+                    .trim_end_matches('/')
+                    .to_string()
+            })
+            .filter(|url| url.starts_with("http"))
+            .unwrap_or_else(|| self.instance.clone())
+    }
+
+    /// Build a v2 API URL
+    fn api_v2_url(&self, endpoint: &str) -> String {
+        format!("{}/api/v2{}", self.instance, endpoint)
+    }
+
+    /// Run a v2 search, optionally limited to one result type
+    async fn search_v2(
+        &self,
+        query: &str,
+        kind: Option<&str>,
+        limit: usize,
+    ) -> Result<MastodonSearchResponse> {
+        let kind = kind.map(|k| format!("&type={k}")).unwrap_or_default();
+        // `resolve` looks up remote accounts and post URLs via WebFinger
+        let url = self.api_v2_url(&format!(
+            "/search?q={}&limit={limit}&resolve=true{kind}",
+            urlencoding::encode(query)
+        ));
+
+        self.client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send_limited(&self.instance)
+            .await
+            .context("Search failed")?
+            .error_for_status()
+            .context("Search failed")?
+            .json()
+            .await
+            .context("Failed to parse search response")
+    }
+
+    /// Fetch a list of statuses from a v1 endpoint
+    async fn fetch_statuses(&self, endpoint: &str) -> Result<Vec<Post>> {
+        let statuses: Vec<MastodonStatus> = self
+            .client
+            .get(self.api_url(endpoint))
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to fetch posts")?
+            .error_for_status()
+            .context("Failed to fetch posts")?
+            .json()
+            .await
+            .context("Failed to parse posts response")?;
+
         Ok(statuses
             .into_iter()
             .map(MastodonStatus::into_post)
             .collect())
     }
 
-    async fn get_context(&self, post: &Post) -> Result<Vec<Post>> {
+    /// How the signed-in account relates to `account_id` (follows, mutes, blocks)
+    async fn relationship(&self, account_id: &str) -> Result<MastodonRelationship> {
+        let url = self.api_url(&format!(
+            "/accounts/relationships?id[]={}",
+            urlencoding::encode(account_id)
+        ));
+
+        let relationships: Vec<MastodonRelationship> = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to fetch relationship")?
+            .error_for_status()
+            .context("Failed to fetch relationship")?
+            .json()
+            .await
+            .context("Failed to parse relationship response")?;
+
+        Ok(relationships.into_iter().next().unwrap_or_default())
+    }
+
+    /// Follow, mute or block an account, or undo it (`action`, e.g.
+    /// `unfollow`), returning the relationship the server reports back
+    async fn change_relationship(
+        &self,
+        profile: &Profile,
+        action: &str,
+    ) -> Result<MastodonRelationship> {
+        let url = self.api_url(&format!(
+            "/accounts/{}/{action}",
+            urlencoding::encode(&profile.network_id)
+        ));
+
+        self.client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send_limited(&self.instance)
+            .await
+            .with_context(|| format!("Failed to {action} @{}", profile.handle))?
+            .error_for_status()
+            .with_context(|| format!("Failed to {action} @{}", profile.handle))?
+            .json()
+            .await
+            .context("Failed to parse relationship response")
+    }
+
+    /// Follow or unfollow (`action`) an account, returning the profile with
+    /// the follow state the server reports back
+    async fn change_follow(&self, profile: &Profile, action: &str) -> Result<Profile> {
+        let relationship = self.change_relationship(profile, action).await?;
+        Ok(Profile {
+            follow_state: relationship.follow_state(),
+            ..profile.clone()
+        })
+    }
+
+    /// Fetch raw notifications, newest first
+    async fn fetch_notifications(&self, limit: usize) -> Result<Vec<MastodonNotification>> {
+        let url = self.api_url(&format!("/notifications?limit={limit}"));
+
+        let response = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to fetch notifications")?;
+
+        response
+            .json()
+            .await
+            .context("Failed to parse notifications response")
+    }
+
+    /// Get the ID of the last notification marked as read, if the server has one
+    async fn notifications_marker(&self) -> Result<Option<String>> {
+        let url = self.api_url("/markers?timeline[]=notifications");
+
+        let response = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to fetch read markers")?;
+
+        let markers: MarkersResponse = response
+            .json()
+            .await
+            .context("Failed to parse read markers")?;
+
+        Ok(markers.notifications.map(|m| m.last_read_id))
+    }
+
+    /// Upload a media file and return its attachment ID
+    async fn upload_media(&self, media: MediaUpload) -> Result<String> {
+        let url = format!("{}/api/v2/media", self.instance);
+        let file_name = media.file_name.clone();
+
+        let part = Part::bytes(media.bytes)
+            .file_name(file_name.clone())
+            .mime_str(&media.mime_type)
+            .context("Invalid media type")?;
+        let mut form = Form::new().part("file", part);
+        if let Some(alt) = media.alt_text {
+            form = form.text("description", alt);
+        }
+
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .multipart(form)
+            .timeout(UPLOAD_TIMEOUT)
+            .send_limited(&self.instance)
+            .await
+            .with_context(|| format!("Failed to upload {file_name}"))?;
+
+        let status = response.status();
+        if status == StatusCode::PAYLOAD_TOO_LARGE {
+            anyhow::bail!("{file_name} exceeds the server's size limit");
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Failed to upload {file_name}: Mastodon error {status}: {body}");
+        }
+
+        let attachment: MastodonMediaUpload = response
+            .json()
+            .await
+            .context("Failed to parse media upload response")?;
+
+        Ok(attachment.id)
+    }
+}
+
+impl SocialApi for MastodonClient {
+    async fn timeline(&self, limit: usize) -> Result<Vec<Post>> {
+        Ok(self.timeline_paged(limit, None).await?.posts)
+    }
+
+    async fn timeline_paged(&self, limit: usize, cursor: Option<String>) -> Result<TimelinePage> {
+        self.timeline_kind(TimelineKind::Home, limit, cursor).await
+    }
+
+    async fn get_context(&self, post: &Post) -> Result<PostContext> {
         let url = self.api_url(&format!("/statuses/{}/context", post.network_id));
 
         let response = self
             .client
             .get(&url)
             .header("Authorization", format!("Bearer {}", self.access_token))
-            .send()
+            .send_limited(&self.instance)
             .await
             .context("Failed to fetch context")?;
 
         #[derive(Deserialize)]
-        #[allow(dead_code)]
         struct ContextResponse {
             ancestors: Vec<MastodonStatus>,
             descendants: Vec<MastodonStatus>,
@@ -88,21 +536,42 @@ impl SocialApi for MastodonClient {
             .await
             .context("Failed to parse context response")?;
 
-        // Return descendants (replies) only
-        Ok(context
-            .descendants
-            .into_iter()
-            .map(MastodonStatus::into_post)
-            .collect())
+        Ok(PostContext {
+            ancestors: context
+                .ancestors
+                .into_iter()
+                .map(MastodonStatus::into_post)
+                .collect(),
+            descendants: context
+                .descendants
+                .into_iter()
+                .map(MastodonStatus::into_post)
+                .collect(),
+        })
     }
 
-    async fn post(&self, content: &str) -> Result<Post> {
+    async fn post(&self, content: &str, options: &PostOptions) -> Result<Post> {
+        self.post_with_media(content, Vec::new(), options).await
+    }
+
+    async fn post_with_media(
+        &self,
+        content: &str,
+        media: Vec<MediaUpload>,
+        options: &PostOptions,
+    ) -> Result<Post> {
+        check_media_count(&media)?;
+
+        let mut media_ids = Vec::with_capacity(media.len());
+        for upload in media {
+            media_ids.push(self.upload_media(upload).await?);
+        }
+
         let url = self.api_url("/statuses");
 
         let request = PostStatusRequest {
-            status: content.to_string(),
-            visibility: Some("public".to_string()),
-            ..Default::default()
+            media_ids,
+            ..PostStatusRequest::new(content, options)
         };
 
         let response = self
@@ -110,7 +579,7 @@ impl SocialApi for MastodonClient {
             .post(&url)
             .header("Authorization", format!("Bearer {}", self.access_token))
             .json(&request)
-            .send()
+            .send_limited(&self.instance)
             .await
             .context("Failed to post status")?;
 
@@ -128,14 +597,12 @@ impl SocialApi for MastodonClient {
         Ok(status.into_post())
     }
 
-    async fn reply(&self, content: &str, reply_to_id: &str) -> Result<Post> {
+    async fn reply(&self, content: &str, reply_to_id: &str, options: &PostOptions) -> Result<Post> {
         let url = self.api_url("/statuses");
 
         let request = PostStatusRequest {
-            status: content.to_string(),
-            visibility: Some("public".to_string()),
             in_reply_to_id: Some(reply_to_id.to_string()),
-            ..Default::default()
+            ..PostStatusRequest::new(content, options)
         };
 
         let response = self
@@ -143,7 +610,7 @@ impl SocialApi for MastodonClient {
             .post(&url)
             .header("Authorization", format!("Bearer {}", self.access_token))
             .json(&request)
-            .send()
+            .send_limited(&self.instance)
             .await
             .context("Failed to post reply")?;
 
@@ -161,13 +628,22 @@ impl SocialApi for MastodonClient {
         Ok(status.into_post())
     }
 
+    async fn quote(&self, post: &Post, content: &str, options: &PostOptions) -> Result<Post> {
+        // No native quotes: link the quoted post instead
+        let url = post
+            .url
+            .as_deref()
+            .context("The quoted post has no URL to link")?;
+        self.post(&format!("{url}\n\n{content}"), options).await
+    }
+
     async fn like(&self, post: &Post) -> Result<()> {
         let url = self.api_url(&format!("/statuses/{}/favourite", post.network_id));
 
         self.client
             .post(&url)
             .header("Authorization", format!("Bearer {}", self.access_token))
-            .send()
+            .send_limited(&self.instance)
             .await
             .context("Failed to like post")?;
 
@@ -180,7 +656,7 @@ impl SocialApi for MastodonClient {
         self.client
             .post(&url)
             .header("Authorization", format!("Bearer {}", self.access_token))
-            .send()
+            .send_limited(&self.instance)
             .await
             .context("Failed to unlike post")?;
 
@@ -193,97 +669,694 @@ impl SocialApi for MastodonClient {
         self.client
             .post(&url)
             .header("Authorization", format!("Bearer {}", self.access_token))
-            .send()
+            .send_limited(&self.instance)
             .await
             .context("Failed to repost")?;
 
         Ok(())
     }
 
-    async fn unrepost(&self, post: &Post) -> Result<()> {
-        let url = self.api_url(&format!("/statuses/{}/unreblog", post.network_id));
+    async fn unrepost(&self, post: &Post) -> Result<bool> {
+        let url = self.api_url(&format!("/statuses/{}/unreblog", post.network_id));
+
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to unrepost")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Mastodon error {status}: {body}");
+        }
+
+        Ok(true)
+    }
+
+    async fn bookmark(&self, post: &Post) -> Result<()> {
+        let url = self.api_url(&format!("/statuses/{}/bookmark", post.network_id));
+
+        self.client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to bookmark post")?
+            .error_for_status()
+            .context("Failed to bookmark post")?;
+
+        Ok(())
+    }
+
+    async fn unbookmark(&self, post: &Post) -> Result<()> {
+        let url = self.api_url(&format!("/statuses/{}/unbookmark", post.network_id));
+
+        self.client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to remove bookmark")?
+            .error_for_status()
+            .context("Failed to remove bookmark")?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, post: &Post) -> Result<()> {
+        let url = self.api_url(&format!("/statuses/{}", post.network_id));
+
+        self.client
+            .delete(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to delete post")?
+            .error_for_status()
+            .context("Failed to delete post")?;
+
+        Ok(())
+    }
+
+    async fn edit(&self, post_id: &str, content: &str) -> Result<Post> {
+        let url = self.api_url(&format!("/statuses/{post_id}"));
+
+        // Only the text is sent, so media and content warning stay as they are
+        let request = PostStatusRequest {
+            status: content.to_string(),
+            ..PostStatusRequest::default()
+        };
+
+        let response = self
+            .client
+            .put(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .json(&request)
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to edit status")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Mastodon error {status}: {body}");
+        }
+
+        let status: MastodonStatus = response
+            .json()
+            .await
+            .context("Failed to parse edit response")?;
+
+        Ok(status.into_post())
+    }
+
+    async fn search(&self, query: &str, limit: usize) -> Result<SearchPage> {
+        let found = self.search_v2(query, Some("statuses"), limit).await?;
+        if !found.statuses.is_empty() {
+            return Ok(SearchPage {
+                posts: found
+                    .statuses
+                    .into_iter()
+                    .map(MastodonStatus::into_post)
+                    .collect(),
+                notice: None,
+            });
+        }
+
+        // Without full-text search an instance only matches posts you have
+        // interacted with, so fall back to a matching hashtag or account
+        let found = self.search_v2(query, None, 5).await?;
+        let term = query.trim().trim_start_matches(['#', '@']);
+        let tag = found
+            .hashtags
+            .iter()
+            .find(|t| t.name.eq_ignore_ascii_case(term))
+            .or_else(|| found.hashtags.first());
+        let prefer_account = query.trim().starts_with('@') || tag.is_none();
+
+        if prefer_account && let Some(account) = found.accounts.first() {
+            let posts = self
+                .fetch_statuses(&format!("/accounts/{}/statuses?limit={limit}", account.id))
+                .await?;
+            return Ok(SearchPage {
+                posts,
+                notice: Some(format!(
+                    "No post text matched (full-text search may be off on this server), showing posts by @{}",
+                    account.acct
+                )),
+            });
+        }
+
+        if let Some(tag) = tag {
+            let posts = self.hashtag_timeline(&tag.name, limit).await?;
+            return Ok(SearchPage {
+                posts,
+                notice: Some(format!(
+                    "No post text matched (full-text search may be off on this server), showing #{}",
+                    tag.name
+                )),
+            });
+        }
+
+        Ok(SearchPage {
+            posts: Vec::new(),
+            notice: Some(
+                "No posts, accounts or hashtags matched (full-text search may be off on this server)"
+                    .to_string(),
+            ),
+        })
+    }
+
+    async fn hashtag_timeline(&self, tag: &str, limit: usize) -> Result<Vec<Post>> {
+        self.fetch_statuses(&format!(
+            "/timelines/tag/{}?limit={limit}",
+            urlencoding::encode(tag)
+        ))
+        .await
+    }
+
+    async fn verify_credentials(&self) -> Result<Account> {
+        let url = self.api_url("/accounts/verify_credentials");
+
+        let response = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to verify credentials")?;
+
+        let account: MastodonAccount = response
+            .json()
+            .await
+            .context("Failed to parse account response")?;
+        let instance = self.instance_info().await;
+
+        Ok(Account {
+            id: uuid::Uuid::new_v4(),
+            network: Network::Mastodon,
+            display_name: account.name(),
+            handle: account.username,
+            server: self.instance.clone(),
+            is_default: true, // First account of this network is default
+            avatar_url: Some(account.avatar),
+            created_at: Utc::now(),
+            last_used_at: None,
+            max_post_chars: instance.as_ref().and_then(InstanceResponse::max_post_chars),
+            max_media_attachments: instance
+                .as_ref()
+                .and_then(|i| i.configuration.statuses.max_media_attachments),
+            max_image_bytes: instance
+                .as_ref()
+                .and_then(InstanceResponse::max_image_bytes),
+            server_name: instance
+                .as_ref()
+                .map(|i| i.title.clone())
+                .filter(|t| !t.is_empty()),
+            server_version: instance.map(|i| i.version).filter(|v| !v.is_empty()),
+        })
+    }
+
+    async fn profile(&self, handle: &str) -> Result<Profile> {
+        let acct = handle.trim_start_matches('@');
+        let url = self.api_url(&format!(
+            "/accounts/lookup?acct={}",
+            urlencoding::encode(acct)
+        ));
+
+        let response = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to look up account")?;
+
+        // The instance may not know a remote account yet; a resolving search fetches it
+        let account: MastodonAccount = if response.status() == StatusCode::NOT_FOUND {
+            self.search_v2(acct, Some("accounts"), 1)
+                .await?
+                .accounts
+                .into_iter()
+                .next()
+                .with_context(|| format!("No account found for @{acct}"))?
+        } else {
+            response
+                .error_for_status()
+                .context("Failed to look up account")?
+                .json()
+                .await
+                .context("Failed to parse account response")?
+        };
+
+        let mut profile = account.into_profile();
+        // Counts and bio are still worth showing without the follow state
+        match self.relationship(&profile.network_id).await {
+            Ok(relationship) => {
+                profile.follow_state = relationship.follow_state();
+                profile.muted = relationship.muting;
+                profile.blocked = relationship.blocking;
+            }
+            Err(e) => tracing::debug!("Failed to fetch follow state: {e}"),
+        }
+        Ok(profile)
+    }
+
+    async fn author_feed(
+        &self,
+        profile: &Profile,
+        limit: usize,
+        cursor: Option<String>,
+    ) -> Result<TimelinePage> {
+        let max_id = cursor
+            .map(|id| format!("&max_id={}", urlencoding::encode(&id)))
+            .unwrap_or_default();
+        let url = self.api_url(&format!(
+            "/accounts/{}/statuses?limit={limit}{max_id}",
+            urlencoding::encode(&profile.network_id)
+        ));
+
+        let statuses: Vec<MastodonStatus> = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to fetch posts")?
+            .error_for_status()
+            .context("Failed to fetch posts")?
+            .json()
+            .await
+            .context("Failed to parse posts response")?;
+
+        // Page by the boost's own ID, as the home timeline does
+        let next_cursor = statuses.last().map(|s| s.id.clone());
+
+        Ok(TimelinePage {
+            posts: statuses
+                .into_iter()
+                .map(MastodonStatus::into_post)
+                .collect(),
+            next_cursor,
+        })
+    }
+
+    async fn follow(&self, profile: &Profile) -> Result<Profile> {
+        self.change_follow(profile, "follow").await
+    }
+
+    async fn unfollow(&self, profile: &Profile) -> Result<Profile> {
+        // Also withdraws a pending follow request
+        self.change_follow(profile, "unfollow").await
+    }
+
+    async fn mute(&self, profile: &Profile) -> Result<()> {
+        self.change_relationship(profile, "mute").await.map(|_| ())
+    }
+
+    async fn unmute(&self, profile: &Profile) -> Result<()> {
+        self.change_relationship(profile, "unmute")
+            .await
+            .map(|_| ())
+    }
+
+    async fn block(&self, profile: &Profile) -> Result<()> {
+        self.change_relationship(profile, "block").await.map(|_| ())
+    }
+
+    async fn unblock(&self, profile: &Profile) -> Result<()> {
+        self.change_relationship(profile, "unblock")
+            .await
+            .map(|_| ())
+    }
+
+    async fn notifications(&self, limit: usize) -> Result<Vec<Notification>> {
+        let notifications = self.fetch_notifications(limit).await?;
+
+        // Without a marker we can't tell what's new, so don't claim anything is
+        let last_read_id = self.notifications_marker().await.ok().flatten();
+
+        Ok(notifications
+            .into_iter()
+            .map(|n| {
+                let is_read = last_read_id
+                    .as_deref()
+                    .is_none_or(|last| !is_newer_id(&n.id, last));
+                n.into_notification(is_read)
+            })
+            .collect())
+    }
+
+    async fn mark_notifications_read(&self) -> Result<()> {
+        let Some(latest) = self.fetch_notifications(1).await?.into_iter().next() else {
+            return Ok(());
+        };
+
+        let url = self.api_url("/markers");
+        let body = serde_json::json!({ "notifications": { "last_read_id": latest.id } });
+
+        self.client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .json(&body)
+            .send_limited(&self.instance)
+            .await
+            .context("Failed to update read marker")?
+            .error_for_status()
+            .context("Failed to update read marker")?;
+
+        Ok(())
+    }
+}
+
+/// Whether Mastodon ID `id` is newer than `than`.
+///
+/// IDs are numeric strings that can exceed `u64` on some servers, so compare
+/// by length first and then lexically.
+fn is_newer_id(id: &str, than: &str) -> bool {
+    (id.len(), id) > (than.len(), than)
+}
+
+// ==================== API Types ====================
+
+#[derive(Debug, Serialize, Default)]
+struct PostStatusRequest {
+    status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    visibility: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    in_reply_to_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sensitive: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spoiler_text: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    media_ids: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scheduled_at: Option<String>,
+}
+
+impl PostStatusRequest {
+    /// Build a status request from compose options
+    fn new(content: &str, options: &PostOptions) -> Self {
+        let spoiler_text = options
+            .spoiler_text
+            .as_ref()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+
+        Self {
+            status: content.to_string(),
+            visibility: Some(options.visibility.as_api_str().to_string()),
+            sensitive: spoiler_text.is_some().then_some(true),
+            spoiler_text,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MastodonMediaUpload {
+    id: String,
+}
+
+/// What `POST /statuses` returns instead of a status when `scheduled_at` is set
+#[derive(Debug, Deserialize)]
+struct MastodonScheduledStatus {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MastodonStatus {
+    id: String,
+    created_at: String,
+    // Compatible servers leave out or null some of what Mastodon always sends
+    #[serde(default, deserialize_with = "null_as_default")]
+    content: String,
+    url: Option<String>,
+    account: MastodonAccount,
+    reblog: Option<Box<Self>>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    favourites_count: u32,
+    #[serde(default, deserialize_with = "null_as_default")]
+    reblogs_count: u32,
+    #[serde(default, deserialize_with = "null_as_default")]
+    replies_count: u32,
+    favourited: Option<bool>,
+    reblogged: Option<bool>,
+    bookmarked: Option<bool>,
+    in_reply_to_id: Option<String>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    media_attachments: Vec<MastodonMedia>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    spoiler_text: String,
+    /// Custom emoji used in the content and CW
+    #[serde(default)]
+    emojis: Vec<MastodonEmoji>,
+}
+
+/// A server's custom emoji, written as `:shortcode:` in text
+#[derive(Debug, Deserialize)]
+struct MastodonEmoji {
+    shortcode: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MastodonAccount {
+    id: String,
+    username: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    display_name: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    avatar: String,
+    #[serde(default)]
+    acct: String,
+    /// Bio (HTML)
+    #[serde(default)]
+    note: String,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    followers_count: u64,
+    #[serde(default)]
+    following_count: u64,
+    #[serde(default)]
+    statuses_count: u64,
+    /// Whether follow requests need approval
+    #[serde(default)]
+    locked: bool,
+    /// Custom emoji used in the display name and bio
+    #[serde(default)]
+    emojis: Vec<MastodonEmoji>,
+}
+
+/// A filter from `/api/v2/filters`
+#[derive(Debug, Deserialize)]
+struct MastodonFilter {
+    /// Where it applies: home, notifications, public, thread, account
+    #[serde(default)]
+    context: Vec<String>,
+    /// `warn`, `hide` or `blur` (media only)
+    #[serde(default)]
+    filter_action: String,
+    expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    keywords: Vec<MastodonFilterKeyword>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MastodonFilterKeyword {
+    keyword: String,
+    #[serde(default)]
+    whole_word: bool,
+}
+
+/// A filter from `/api/v1/filters`, all Pleroma and Akkoma have
+#[derive(Debug, Deserialize)]
+struct MastodonFilterV1 {
+    phrase: String,
+    #[serde(default)]
+    context: Vec<String>,
+    #[serde(default)]
+    whole_word: bool,
+    /// Drop matching posts rather than warn
+    #[serde(default)]
+    irreversible: bool,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<MastodonFilterV1> for MastodonFilter {
+    fn from(filter: MastodonFilterV1) -> Self {
+        Self {
+            context: filter.context,
+            filter_action: if filter.irreversible { "hide" } else { "warn" }.to_string(),
+            expires_at: filter.expires_at,
+            keywords: vec![MastodonFilterKeyword {
+                keyword: filter.phrase,
+                whole_word: filter.whole_word,
+            }],
+        }
+    }
+}
+
+impl MastodonFilter {
+    fn applies_to_timelines(&self) -> bool {
+        self.context.iter().any(|c| c == "home" || c == "public")
+            && self.expires_at.is_none_or(|at| at > Utc::now())
+    }
+
+    fn into_rules(self) -> Vec<FilterRule> {
+        let action = if self.filter_action == "hide" {
+            FilterAction::Hide
+        } else {
+            FilterAction::Warn
+        };
+        self.keywords
+            .into_iter()
+            .map(|k| {
+                let pattern = if k.whole_word {
+                    format!(r"/\b{}\b/", regex_lite::escape(&k.keyword))
+                } else {
+                    k.keyword
+                };
+                FilterRule::keyword(pattern, action)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct MastodonRelationship {
+    #[serde(default)]
+    following: bool,
+    /// A follow request is waiting for approval
+    #[serde(default)]
+    requested: bool,
+    #[serde(default)]
+    muting: bool,
+    #[serde(default)]
+    blocking: bool,
+}
+
+impl MastodonRelationship {
+    const fn follow_state(&self) -> FollowState {
+        if self.following {
+            FollowState::Following
+        } else if self.requested {
+            FollowState::Requested
+        } else {
+            FollowState::NotFollowing
+        }
+    }
+}
+
+impl MastodonAccount {
+    /// Display name with custom emoji replaced
+    fn name(&self) -> String {
+        replace_emoji(&self.display_name, &self.emojis)
+    }
+
+    fn into_profile(self) -> Profile {
+        let display_name = self.name();
+        let bio = replace_emoji(&html_to_text(&self.note), &self.emojis);
+        Profile {
+            network: Network::Mastodon,
+            network_id: self.id,
+            handle: if self.acct.is_empty() {
+                self.username
+            } else {
+                self.acct
+            },
+            display_name,
+            bio,
+            avatar_url: Some(self.avatar).filter(|a| !a.is_empty()),
+            url: self.url,
+            followers_count: self.followers_count,
+            following_count: self.following_count,
+            posts_count: self.statuses_count,
+            locked: self.locked,
+            follow_state: FollowState::default(),
+            follow_uri: None,
+            muted: false,
+            blocked: false,
+            block_uri: None,
+        }
+    }
+}
 
-        self.client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.access_token))
-            .send()
-            .await
-            .context("Failed to unrepost")?;
+#[derive(Debug, Deserialize)]
+struct InstanceResponse {
+    /// Mastodon version, with the real software named by compatible servers
+    #[serde(default)]
+    version: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    urls: InstanceUrls,
+    #[serde(default)]
+    configuration: InstanceConfiguration,
+    max_toot_chars: Option<usize>,
+    upload_limit: Option<usize>,
+}
 
-        Ok(())
+impl InstanceResponse {
+    fn max_post_chars(&self) -> Option<usize> {
+        self.configuration
+            .statuses
+            .max_characters
+            // Pleroma and Akkoma
+            .or(self.max_toot_chars)
     }
 
-    async fn verify_credentials(&self) -> Result<Account> {
-        let url = self.api_url("/accounts/verify_credentials");
-
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.access_token))
-            .send()
-            .await
-            .context("Failed to verify credentials")?;
+    fn max_image_bytes(&self) -> Option<usize> {
+        self.configuration
+            .media_attachments
+            .image_size_limit
+            // Pleroma and Akkoma
+            .or(self.upload_limit)
+    }
+}
 
-        let account: MastodonAccount = response
-            .json()
-            .await
-            .context("Failed to parse account response")?;
+#[derive(Debug, Default, Deserialize)]
+struct InstanceUrls {
+    streaming_api: Option<String>,
+}
 
-        Ok(Account {
-            id: uuid::Uuid::new_v4(),
-            network: Network::Mastodon,
-            display_name: account.display_name,
-            handle: account.username,
-            server: self.instance.clone(),
-            is_default: true, // First account of this network is default
-            avatar_url: Some(account.avatar),
-            created_at: Utc::now(),
-            last_used_at: None,
-        })
-    }
+#[derive(Debug, Default, Deserialize)]
+struct InstanceConfiguration {
+    #[serde(default)]
+    statuses: StatusesConfiguration,
+    #[serde(default)]
+    media_attachments: MediaConfiguration,
 }
 
-// ==================== API Types ====================
+#[derive(Debug, Default, Deserialize)]
+struct StatusesConfiguration {
+    max_characters: Option<usize>,
+    max_media_attachments: Option<usize>,
+}
 
-#[derive(Debug, Serialize, Default)]
-struct PostStatusRequest {
-    status: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    visibility: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    in_reply_to_id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    sensitive: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    spoiler_text: Option<String>,
+#[derive(Debug, Default, Deserialize)]
+struct MediaConfiguration {
+    image_size_limit: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
-struct MastodonStatus {
-    id: String,
-    created_at: String,
-    content: String,
-    url: Option<String>,
-    account: MastodonAccount,
-    reblog: Option<Box<Self>>,
-    favourites_count: u32,
-    reblogs_count: u32,
-    replies_count: u32,
-    favourited: Option<bool>,
-    reblogged: Option<bool>,
-    in_reply_to_id: Option<String>,
-    media_attachments: Vec<MastodonMedia>,
+struct MastodonSearchResponse {
+    #[serde(default)]
+    accounts: Vec<MastodonAccount>,
+    #[serde(default)]
+    statuses: Vec<MastodonStatus>,
+    #[serde(default)]
+    hashtags: Vec<MastodonTag>,
 }
 
 #[derive(Debug, Deserialize)]
-struct MastodonAccount {
-    #[allow(dead_code)]
-    id: String,
-    username: String,
-    display_name: String,
-    avatar: String,
-    #[serde(default)]
-    acct: String,
+struct MastodonTag {
+    name: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -293,6 +1366,60 @@ struct MastodonMedia {
     #[serde(rename = "type")]
     media_type: String,
     description: Option<String>,
+    blurhash: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MastodonNotification {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    created_at: String,
+    account: MastodonAccount,
+    status: Option<MastodonStatus>,
+}
+
+impl MastodonNotification {
+    fn into_notification(self, is_read: bool) -> Notification {
+        let actor_name = self.account.name();
+        let kind = match self.kind.as_str() {
+            "mention" => NotificationKind::Mention,
+            "favourite" => NotificationKind::Like,
+            "reblog" => NotificationKind::Repost,
+            "follow" | "follow_request" => NotificationKind::Follow,
+            "quote" => NotificationKind::Quote,
+            _ => NotificationKind::Other,
+        };
+
+        let created_at = DateTime::parse_from_rfc3339(&self.created_at)
+            .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc));
+
+        Notification {
+            id: self.id,
+            network: Network::Mastodon,
+            kind,
+            actor_handle: if self.account.acct.is_empty() {
+                self.account.username
+            } else {
+                self.account.acct
+            },
+            actor_name,
+            actor_avatar: Some(self.account.avatar),
+            post: self.status.map(MastodonStatus::into_post),
+            created_at,
+            is_read,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MarkersResponse {
+    notifications: Option<Marker>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Marker {
+    last_read_id: String,
 }
 
 impl MastodonStatus {
@@ -301,23 +1428,13 @@ impl MastodonStatus {
         if let Some(reblog) = self.reblog {
             let mut post = reblog.into_post();
             post.is_repost = true;
-            post.repost_author = Some(self.account.display_name);
+            post.repost_author = Some(self.account.name());
             return post;
         }
 
-        // Strip HTML from content
-        let content = html_escape::decode_html_entities(&self.content)
-            .to_string()
-            .replace("<br>", "\n")
-            .replace("<br/>", "\n")
-            .replace("<br />", "\n")
-            .replace("</p><p>", "\n\n");
-
-        // Simple HTML tag removal
-        let content = regex_lite::Regex::new(r"<[^>]+>")
-            .map(|re| re.replace_all(&content, "").to_string())
-            .unwrap_or(content);
-
+        let content = replace_emoji(&html_to_text(&self.content), &self.emojis);
+        let spoiler_text = replace_emoji(&self.spoiler_text, &self.emojis);
+        let author_name = self.account.name();
         let created_at = DateTime::parse_from_rfc3339(&self.created_at)
             .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc));
 
@@ -330,10 +1447,11 @@ impl MastodonStatus {
             } else {
                 self.account.acct
             },
-            author_name: self.account.display_name,
+            author_name,
             author_avatar: Some(self.account.avatar),
             content,
             content_raw: Some(self.content),
+            spoiler_text: Some(spoiler_text).filter(|s| !s.is_empty()),
             created_at,
             url: self.url,
             is_repost: false,
@@ -343,6 +1461,7 @@ impl MastodonStatus {
             reply_count: self.replies_count,
             liked: self.favourited.unwrap_or(false),
             reposted: self.reblogged.unwrap_or(false),
+            bookmarked: self.bookmarked.unwrap_or(false),
             reply_to_id: self.in_reply_to_id,
             media: self
                 .media_attachments
@@ -358,24 +1477,52 @@ impl MastodonStatus {
                         _ => MediaType::Unknown,
                     },
                     alt_text: m.description,
+                    blurhash: m.blurhash,
                 })
                 .collect(),
             cid: None,
             uri: None,
+            quote: None,
+            link_card: None,
+            owner_account_id: None,
+        }
+    }
+}
+
+/// Read `null` as the type's default, for fields compatible servers send as
+/// `null` where Mastodon doesn't
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Show custom emoji shortcodes (`:blobcat:`) that the server knows about as
+/// `[blobcat]`, since the terminal can't draw their images. Other text
+/// between colons is left alone.
+fn replace_emoji(text: &str, emojis: &[MastodonEmoji]) -> String {
+    let mut text = text.to_string();
+    for emoji in emojis {
+        let shortcode = format!(":{}:", emoji.shortcode);
+        if text.contains(&shortcode) {
+            text = text.replace(&shortcode, &format!("[{}]", emoji.shortcode));
         }
     }
+    text
 }
 
 /// OAuth authentication flow for Mastodon
 pub mod oauth {
-    use super::{Client, Context, Deserialize, Result};
+    use super::{Client, Context, Deserialize, Result, http_client};
 
-    fn http_client() -> Client {
-        Client::builder()
-            .user_agent(format!("Perch/{}", env!("CARGO_PKG_VERSION")))
-            .build()
-            .expect("Failed to build HTTP client")
-    }
+    mod callback;
+
+    pub use callback::{CallbackServer, LOOPBACK_REDIRECT_URI};
+
+    /// Redirect URI that has the instance show the code to paste instead
+    pub const OOB_REDIRECT_URI: &str = "urn:ietf:wg:oauth:2.0:oob";
 
     /// Registered OAuth application credentials
     #[derive(Debug, Deserialize)]
@@ -395,17 +1542,25 @@ pub mod oauth {
         pub token_type: String,
     }
 
+    /// Error body of a failed OAuth request
+    #[derive(Debug, Deserialize)]
+    struct OAuthError {
+        error: String,
+    }
+
     /// Register an OAuth application with an instance
     ///
     /// Uses JSON request body for compatibility with `GoToSocial` and other
-    /// Mastodon-compatible `ActivityPub` servers.
-    pub async fn register_app(instance: &str) -> Result<OAuthApp> {
-        let client = http_client();
+    /// Mastodon-compatible `ActivityPub` servers. Both the pasted-code and
+    /// localhost redirects are registered, so either works with the app.
+    /// Uses the shared HTTP client unless one is given.
+    pub async fn register_app(instance: &str, client: Option<&Client>) -> Result<OAuthApp> {
+        let client = client.cloned().unwrap_or_else(http_client);
         let url = format!("{}/api/v1/apps", instance.trim_end_matches('/'));
 
         let params = serde_json::json!({
             "client_name": "Perch",
-            "redirect_uris": "urn:ietf:wg:oauth:2.0:oob",
+            "redirect_uris": format!("{OOB_REDIRECT_URI}\n{LOOPBACK_REDIRECT_URI}"),
             "scopes": "read write follow",
             "website": "https://github.com/ricardodantas/perch"
         });
@@ -440,31 +1595,118 @@ pub mod oauth {
         })
     }
 
-    /// Get the authorization URL for the user to visit
-    pub fn get_auth_url(instance: &str, client_id: &str) -> String {
+    /// Check that a stored app registration is still known to the instance
+    ///
+    /// Asks for an app-only token with the client credentials. Instances
+    /// answer `invalid_client` once the app was deleted or its secret
+    /// rotated, and then it has to be registered again. Any other failure
+    /// can't tell us either way, so the registration is assumed to still be
+    /// good.
+    pub async fn app_is_registered(
+        instance: &str,
+        app: &OAuthApp,
+        client: Option<&Client>,
+    ) -> Result<bool> {
+        let client = client.cloned().unwrap_or_else(http_client);
+        let url = format!("{}/oauth/token", instance.trim_end_matches('/'));
+
+        let params = [
+            ("grant_type", "client_credentials"),
+            ("client_id", app.client_id.as_str()),
+            ("client_secret", app.client_secret.as_str()),
+            ("redirect_uri", OOB_REDIRECT_URI),
+            ("scope", "read"),
+        ];
+
+        let response = client
+            .post(&url)
+            .form(&params)
+            .send()
+            .await
+            .context("Failed to check app registration")?;
+        if response.status().is_success() {
+            return Ok(true);
+        }
+        let error: Option<OAuthError> = response.json().await.ok();
+        Ok(error.is_none_or(|e| e.error != "invalid_client"))
+    }
+
+    /// Revoke an access token, ending the session it belongs to
+    ///
+    /// Uses the shared HTTP client unless one is given.
+    pub async fn revoke_token(
+        instance: &str,
+        app: &OAuthApp,
+        token: &str,
+        client: Option<&Client>,
+    ) -> Result<()> {
+        let client = client.cloned().unwrap_or_else(http_client);
+        let url = format!("{}/oauth/revoke", instance.trim_end_matches('/'));
+
+        let params = [
+            ("client_id", app.client_id.as_str()),
+            ("client_secret", app.client_secret.as_str()),
+            ("token", token),
+        ];
+
+        let response = client
+            .post(&url)
+            .form(&params)
+            .send()
+            .await
+            .context("Failed to revoke token")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!(
+                "Token revocation failed (HTTP {}): {}",
+                status.as_u16(),
+                body
+            );
+        }
+        Ok(())
+    }
+
+    /// Get the authorization URL for the user to visit, which comes back to
+    /// `redirect_uri` (with `state`, if given)
+    pub fn get_auth_url(
+        instance: &str,
+        client_id: &str,
+        redirect_uri: &str,
+        state: Option<&str>,
+    ) -> String {
+        let state = state
+            .map(|state| format!("&state={}", urlencoding::encode(state)))
+            .unwrap_or_default();
         format!(
-            "{}/oauth/authorize?client_id={}&redirect_uri={}&response_type=code&scope=read+write+follow",
+            "{}/oauth/authorize?client_id={}&redirect_uri={}&response_type=code&scope=read+write+follow{state}",
             instance.trim_end_matches('/'),
             client_id,
-            urlencoding::encode("urn:ietf:wg:oauth:2.0:oob")
+            urlencoding::encode(redirect_uri)
         )
     }
 
     /// Exchange authorization code for access token
+    ///
+    /// `redirect_uri` has to be the one the code was requested with. Uses the
+    /// shared HTTP client unless one is given.
     pub async fn get_token(
         instance: &str,
         client_id: &str,
         client_secret: &str,
         code: &str,
+        redirect_uri: &str,
+        client: Option<&Client>,
     ) -> Result<OAuthToken> {
-        let client = http_client();
+        let client = client.cloned().unwrap_or_else(http_client);
         let url = format!("{}/oauth/token", instance.trim_end_matches('/'));
 
         let params = [
             ("grant_type", "authorization_code"),
             ("client_id", client_id),
             ("client_secret", client_secret),
-            ("redirect_uri", "urn:ietf:wg:oauth:2.0:oob"),
+            ("redirect_uri", redirect_uri),
             ("code", code),
             ("scope", "read write follow"),
         ];
@@ -490,3 +1732,643 @@ pub mod oauth {
             .with_context(|| format!("Failed to parse token response: {body}"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_server::{MockServer, Route as MockRoute};
+
+    #[test]
+    fn test_status_request_without_spoiler() {
+        let request = PostStatusRequest::new("Hello", &PostOptions::default());
+        let json = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(json["status"], "Hello");
+        assert!(json.get("spoiler_text").is_none());
+        assert!(json.get("sensitive").is_none());
+
+        // Blank CW counts as no CW
+        let options = PostOptions {
+            spoiler_text: Some("   ".to_string()),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(PostStatusRequest::new("Hello", &options)).unwrap();
+        assert!(json.get("spoiler_text").is_none());
+    }
+
+    #[test]
+    fn test_status_request_with_spoiler() {
+        let options = PostOptions {
+            spoiler_text: Some("Movie spoilers".to_string()),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(PostStatusRequest::new("The end", &options)).unwrap();
+
+        assert_eq!(json["spoiler_text"], "Movie spoilers");
+        assert_eq!(json["sensitive"], true);
+        assert!(json.get("scheduled_at").is_none());
+    }
+
+    #[test]
+    fn test_status_request_scheduled() {
+        let at = DateTime::parse_from_rfc3339("2030-01-14T09:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let request = PostStatusRequest {
+            scheduled_at: Some(at.to_rfc3339()),
+            ..PostStatusRequest::new("Later", &PostOptions::default())
+        };
+        let json = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(json["scheduled_at"], "2030-01-14T09:00:00+00:00");
+        assert_eq!(json["visibility"], "public");
+    }
+
+    #[test]
+    fn test_filters_into_rules() {
+        let filters: Vec<MastodonFilter> = serde_json::from_str(
+            r#"[
+                {"context": ["home"], "filter_action": "hide",
+                 "keywords": [{"keyword": "cat", "whole_word": true}, {"keyword": "dog"}]},
+                {"context": ["notifications"], "filter_action": "hide",
+                 "keywords": [{"keyword": "ping"}]},
+                {"context": ["public"], "filter_action": "warn",
+                 "expires_at": "2000-01-01T00:00:00Z", "keywords": [{"keyword": "old"}]}
+            ]"#,
+        )
+        .unwrap();
+        let rules: Vec<_> = filters
+            .into_iter()
+            .filter(MastodonFilter::applies_to_timelines)
+            .flat_map(MastodonFilter::into_rules)
+            .collect();
+
+        assert_eq!(
+            rules,
+            [
+                FilterRule::keyword(r"/\bcat\b/", FilterAction::Hide),
+                FilterRule::keyword("dog", FilterAction::Hide),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_v1_filters() {
+        let filters: Vec<MastodonFilterV1> = serde_json::from_str(
+            r#"[
+                {"id": "1", "phrase": "cat", "context": ["home"], "whole_word": true,
+                 "irreversible": true, "expires_at": null},
+                {"id": "2", "phrase": "dog", "context": ["public", "thread"]},
+                {"id": "3", "phrase": "ping", "context": ["notifications"]}
+            ]"#,
+        )
+        .unwrap();
+        let rules: Vec<_> = filters
+            .into_iter()
+            .map(MastodonFilter::from)
+            .filter(MastodonFilter::applies_to_timelines)
+            .flat_map(MastodonFilter::into_rules)
+            .collect();
+
+        assert_eq!(
+            rules,
+            [
+                FilterRule::keyword(r"/\bcat\b/", FilterAction::Hide),
+                FilterRule::keyword("dog", FilterAction::Warn),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pleroma_timeline() {
+        let statuses: Vec<MastodonStatus> =
+            serde_json::from_str(include_str!("mastodon/testdata/pleroma_timeline.json")).unwrap();
+        let posts: Vec<Post> = statuses
+            .into_iter()
+            .map(MastodonStatus::into_post)
+            .collect();
+
+        assert_eq!(posts[0].network_id, "AfK2Nx9cC1Yb8v4Bpo");
+        assert_eq!(posts[0].author_handle, "lain");
+        assert_eq!(posts[0].author_name, "lain [blobcat]");
+        assert_eq!(
+            posts[0].content,
+            "Testing the #pleroma timeline\nsecond line"
+        );
+        assert!(posts[0].liked);
+        assert_eq!(posts[0].media.len(), 1);
+        assert_eq!(posts[0].media[0].alt_text, None);
+
+        // A boost of a remote post
+        assert!(posts[1].is_repost);
+        assert_eq!(posts[1].repost_author.as_deref(), Some("lain [blobcat]"));
+        assert_eq!(posts[1].author_handle, "bob@mastodon.example");
+        assert_eq!(posts[1].spoiler_text.as_deref(), Some("food"));
+        assert_eq!(posts[1].reply_count, 1);
+    }
+
+    #[test]
+    fn test_status_with_missing_fields() {
+        let status: MastodonStatus = serde_json::from_str(
+            r#"{
+                "id": "1",
+                "created_at": "2024-03-01T09:15:02Z",
+                "content": null,
+                "account": {"id": "2", "username": "eve", "display_name": null},
+                "replies_count": null,
+                "spoiler_text": null
+            }"#,
+        )
+        .unwrap();
+        let post = status.into_post();
+
+        assert_eq!(post.author_handle, "eve");
+        assert_eq!(post.content, "");
+        assert_eq!(post.like_count, 0);
+        assert!(post.media.is_empty());
+        assert!(post.spoiler_text.is_none());
+    }
+
+    #[test]
+    fn test_server_software() {
+        let software = |version| ServerSoftware::from_version(version);
+        assert_eq!(software("4.2.8"), ServerSoftware::Mastodon);
+        assert_eq!(
+            software("2.7.2 (compatible; Pleroma 2.6.2)"),
+            ServerSoftware::Pleroma
+        );
+        assert_eq!(
+            software("2.7.2 (compatible; Akkoma 3.13.2)"),
+            ServerSoftware::Akkoma
+        );
+    }
+
+    #[test]
+    fn test_pleroma_instance_limits() {
+        let instance: InstanceResponse = serde_json::from_str(
+            r#"{
+                "title": "Pleroma/Akkoma",
+                "version": "2.7.2 (compatible; Akkoma 3.13.2)",
+                "max_toot_chars": 5000,
+                "upload_limit": 16000000
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(instance.max_post_chars(), Some(5000));
+        assert_eq!(instance.max_image_bytes(), Some(16_000_000));
+    }
+
+    #[test]
+    fn test_account_into_profile() {
+        let account: MastodonAccount = serde_json::from_str(
+            r#"{
+                "id": "42",
+                "username": "alice",
+                "acct": "alice@example.social",
+                "display_name": "",
+                "avatar": "https://example.social/a.png",
+                "note": "<p>Birds &amp; bees</p><p>Second line</p>",
+                "followers_count": 10,
+                "following_count": 3,
+                "statuses_count": 7
+            }"#,
+        )
+        .unwrap();
+        let profile = account.into_profile();
+
+        assert_eq!(profile.network_id, "42");
+        assert_eq!(profile.handle, "alice@example.social");
+        assert_eq!(profile.name(), "alice@example.social");
+        assert_eq!(profile.bio, "Birds & bees\n\nSecond line");
+        assert_eq!(
+            (
+                profile.followers_count,
+                profile.following_count,
+                profile.posts_count
+            ),
+            (10, 3, 7)
+        );
+        assert!(!profile.locked);
+    }
+
+    #[test]
+    fn test_status_custom_emoji() {
+        let status: MastodonStatus = serde_json::from_str(
+            r#"{
+                "id": "1",
+                "created_at": "2026-01-01T12:00:00Z",
+                "content": "<p>Hello :blobcat: at 10:30:00 :unknown:</p>",
+                "spoiler_text": ":blobcat: spoilers",
+                "url": null,
+                "account": {
+                    "id": "42",
+                    "username": "alice",
+                    "display_name": "Alice :verified:",
+                    "avatar": "",
+                    "emojis": [{"shortcode": "verified", "url": "https://example.social/v.png"}]
+                },
+                "reblog": null,
+                "favourites_count": 0,
+                "reblogs_count": 0,
+                "replies_count": 0,
+                "in_reply_to_id": null,
+                "media_attachments": [],
+                "emojis": [{"shortcode": "blobcat", "url": "https://example.social/b.png"}]
+            }"#,
+        )
+        .unwrap();
+        let post = status.into_post();
+
+        assert_eq!(post.content, "Hello [blobcat] at 10:30:00 :unknown:");
+        assert_eq!(post.spoiler_text.as_deref(), Some("[blobcat] spoilers"));
+        assert_eq!(post.author_name, "Alice [verified]");
+        // The original HTML is kept as it came
+        assert_eq!(
+            post.content_raw.as_deref(),
+            Some("<p>Hello :blobcat: at 10:30:00 :unknown:</p>")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_requests_wait_for_reset() {
+        let reset = (Utc::now() + chrono::TimeDelta::minutes(5)).to_rfc3339();
+        let (server, client) = mock_client([MockRoute::new(
+            "GET",
+            "/api/v1/timelines/home",
+            429,
+            r#"{"error":"Too many requests"}"#,
+        )
+        .header("X-RateLimit-Limit", "300")
+        .header("X-RateLimit-Remaining", "0")
+        .header("X-RateLimit-Reset", reset)])
+        .await;
+        let instance = server.url();
+
+        for _ in 0..2 {
+            let error = client
+                .timeline_kind(TimelineKind::Home, 20, None)
+                .await
+                .unwrap_err();
+            let limited = error.downcast_ref::<rate_limit::RateLimited>().unwrap();
+            assert!((299..=300).contains(&limited.retry_in_secs()));
+        }
+
+        // The second request waited instead of reaching the server
+        assert_eq!(server.requests().len(), 1);
+
+        let limit = client.rate_limit().unwrap();
+        assert_eq!((limit.limit, limit.remaining), (Some(300), 0));
+        assert!(rate_limit::paused_until([instance]).is_some());
+        assert!(rate_limit::paused_until(["https://mastodon.social"]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_app_is_registered() {
+        let server = MockServer::start([
+            MockRoute::new(
+                "POST",
+                "/oauth/token",
+                200,
+                r#"{"access_token":"app-token","token_type":"Bearer","scope":"read"}"#,
+            ),
+            MockRoute::new(
+                "POST",
+                "/oauth/token",
+                401,
+                r#"{"error":"invalid_client","error_description":"Client authentication failed due to unknown client, no client authentication included, or unsupported authentication method."}"#,
+            ),
+            MockRoute::new(
+                "POST",
+                "/oauth/token",
+                503,
+                "<html>Down for maintenance</html>",
+            ),
+        ])
+        .await;
+        let instance = server.url();
+
+        let app = oauth::OAuthApp {
+            client_id: "id".to_string(),
+            client_secret: "secret".to_string(),
+        };
+        let registered = || oauth::app_is_registered(instance, &app, None);
+        assert!(registered().await.unwrap());
+        // Deleted on the server
+        assert!(!registered().await.unwrap());
+        // Unrelated failures keep the stored app
+        assert!(registered().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_revoke_token() {
+        let server = MockServer::start([
+            MockRoute::new("POST", "/oauth/revoke", 200, "{}"),
+            MockRoute::new(
+                "POST",
+                "/oauth/revoke",
+                403,
+                r#"{"error":"unauthorized_client","error_description":"You are not authorized to revoke this token"}"#,
+            ),
+        ])
+        .await;
+        let instance = server.url();
+
+        let app = oauth::OAuthApp {
+            client_id: "id".to_string(),
+            client_secret: "secret".to_string(),
+        };
+        oauth::revoke_token(instance, &app, "user-token", None)
+            .await
+            .unwrap();
+        let error = oauth::revoke_token(instance, &app, "user-token", None)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("HTTP 403"));
+
+        let requests = server.requests_to("/oauth/revoke");
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            requests[0].body,
+            "client_id=id&client_secret=secret&token=user-token"
+        );
+    }
+
+    /// A minimal status, as Mastodon sends it
+    fn status_json(id: &str, acct: &str, content: &str, in_reply_to_id: Option<&str>) -> String {
+        serde_json::json!({
+            "id": id,
+            "created_at": "2024-11-12T18:04:33.000Z",
+            "in_reply_to_id": in_reply_to_id,
+            "spoiler_text": "",
+            "visibility": "public",
+            "url": format!("https://mastodon.social/@{acct}/{id}"),
+            "replies_count": 0,
+            "reblogs_count": 0,
+            "favourites_count": 0,
+            "content": content,
+            "reblog": null,
+            "account": {
+                "id": "1",
+                "username": acct.split('@').next().unwrap(),
+                "acct": acct,
+                "display_name": "",
+                "avatar": "https://files.mastodon.social/avatar.png",
+            },
+            "media_attachments": [],
+            "emojis": [],
+        })
+        .to_string()
+    }
+
+    async fn mock_client(
+        routes: impl IntoIterator<Item = impl Into<MockRoute>>,
+    ) -> (MockServer, MastodonClient) {
+        let server = MockServer::start(routes).await;
+        let client = MastodonClient::new(server.url(), "token").with_client(Client::new());
+        (server, client)
+    }
+
+    #[tokio::test]
+    async fn test_home_timeline() {
+        let (server, client) = mock_client(vec![(
+            "GET",
+            "/api/v1/timelines/home",
+            200,
+            include_str!("mastodon/testdata/home_timeline.json").to_string(),
+        )])
+        .await;
+
+        let page = client
+            .timeline_paged(20, Some("113472500000000000".to_string()))
+            .await
+            .unwrap();
+        let request = &server.requests()[0];
+        assert_eq!(
+            request.target,
+            "/api/v1/timelines/home?limit=20&max_id=113472500000000000"
+        );
+        assert_eq!(request.header("authorization"), Some("Bearer token"));
+
+        let post = &page.posts[0];
+        assert_eq!(post.network, Network::Mastodon);
+        assert_eq!(post.network_id, "113472365438491247");
+        assert_eq!(post.author_handle, "alice");
+        assert_eq!(post.author_name, "Alice");
+        assert_eq!(
+            post.content,
+            "Spotted a #heron by the river & took a photo.\n\nThanks for the tip @bob\nit was worth it"
+        );
+        assert!(
+            post.content_raw
+                .as_deref()
+                .unwrap()
+                .starts_with("<p>Spotted")
+        );
+        assert_eq!(post.spoiler_text.as_deref(), Some("Birds"));
+        assert_eq!(post.created_at.to_rfc3339(), "2024-11-12T18:04:33+00:00");
+        assert_eq!(post.reply_to_id.as_deref(), Some("113472300000000000"));
+        assert_eq!(
+            (post.reply_count, post.repost_count, post.like_count),
+            (2, 5, 12)
+        );
+        assert!(post.liked && post.bookmarked && !post.reposted);
+        assert!(!post.is_repost);
+        assert_eq!(post.media.len(), 1);
+        assert_eq!(post.media[0].media_type, MediaType::Image);
+        assert_eq!(
+            post.media[0].alt_text.as_deref(),
+            Some("A grey heron standing in shallow water")
+        );
+
+        // A boost shows the boosted post, crediting who boosted it
+        let boost = &page.posts[1];
+        assert!(boost.is_repost);
+        assert_eq!(boost.repost_author.as_deref(), Some("Bob"));
+        assert_eq!(boost.network_id, "113472100000000000");
+        assert_eq!(boost.author_handle, "carol@fosstodon.org");
+        assert_eq!(boost.author_name, "");
+        assert_eq!(boost.content, "Released v2.0 of my terminal #rust tool");
+        assert!(boost.reposted);
+        assert_eq!(boost.like_count, 88);
+
+        // Paging goes on from the boost itself, not the boosted post
+        assert_eq!(page.next_cursor.as_deref(), Some("113472400000000001"));
+    }
+
+    #[tokio::test]
+    async fn test_context() {
+        let context = format!(
+            r#"{{"ancestors":[{}],"descendants":[{},{}]}}"#,
+            status_json("100", "alice", "<p>Which bird is this?</p>", None),
+            status_json("102", "bob@hachyderm.io", "<p>A heron</p>", Some("101")),
+            status_json("103", "alice", "<p>Thanks!</p>", Some("102")),
+        );
+        let (_server, client) =
+            mock_client(vec![("GET", "/api/v1/statuses/101/context", 200, context)]).await;
+
+        let post = Post::new(Network::Mastodon, "101");
+        let context = client.get_context(&post).await.unwrap();
+        assert_eq!(context.ancestors.len(), 1);
+        assert_eq!(context.ancestors[0].content, "Which bird is this?");
+        let replies: Vec<_> = context
+            .descendants
+            .iter()
+            .map(|p| (p.network_id.as_str(), p.reply_to_id.as_deref()))
+            .collect();
+        assert_eq!(replies, [("102", Some("101")), ("103", Some("102"))]);
+        assert_eq!(context.descendants[0].author_handle, "bob@hachyderm.io");
+    }
+
+    #[tokio::test]
+    async fn test_post_and_reply_bodies() {
+        let (server, client) = mock_client(vec![(
+            "POST",
+            "/api/v1/statuses",
+            200,
+            status_json("200", "me", "<p>Hello</p>", None),
+        )])
+        .await;
+
+        let post = client.post("Hello", &PostOptions::default()).await.unwrap();
+        assert_eq!(post.network_id, "200");
+        assert_eq!(post.content, "Hello");
+
+        let options = PostOptions {
+            visibility: crate::models::Visibility::Unlisted,
+            spoiler_text: Some("Birds".to_string()),
+            ..Default::default()
+        };
+        client.reply("Nice heron", "101", &options).await.unwrap();
+
+        let requests = server.requests_to("/api/v1/statuses");
+        assert_eq!(
+            requests[0].json(),
+            serde_json::json!({"status": "Hello", "visibility": "public"})
+        );
+        assert_eq!(
+            requests[1].json(),
+            serde_json::json!({
+                "status": "Nice heron",
+                "visibility": "unlisted",
+                "in_reply_to_id": "101",
+                "sensitive": true,
+                "spoiler_text": "Birds",
+            })
+        );
+        assert_eq!(requests[1].header("content-type"), Some("application/json"));
+    }
+
+    #[tokio::test]
+    async fn test_continue_thread() {
+        let (server, client) = mock_client(vec![(
+            "POST",
+            "/api/v1/statuses",
+            200,
+            status_json("201", "me", "<p>Part</p>", Some("200")),
+        )])
+        .await;
+
+        let first = Post::new(Network::Mastodon, "200");
+        let parts = ["Second 2/3".to_string(), "Third 3/3".to_string()];
+        let posted = crate::api::Client::Mastodon(client)
+            .continue_thread(&first, &parts, &PostOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(posted.len(), 2);
+
+        // Each part replies to the one before
+        let bodies: Vec<_> = server
+            .requests_to("/api/v1/statuses")
+            .iter()
+            .map(|r| {
+                let body = r.json();
+                (body["status"].clone(), body["in_reply_to_id"].clone())
+            })
+            .collect();
+        assert_eq!(
+            bodies,
+            [
+                (serde_json::json!("Second 2/3"), serde_json::json!("200")),
+                (serde_json::json!("Third 3/3"), serde_json::json!("201")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_credentials() {
+        let account = r#"{
+            "id": "109301",
+            "username": "alice",
+            "acct": "alice",
+            "display_name": "",
+            "avatar": "https://files.mastodon.social/accounts/avatars/alice.png",
+            "note": "<p>Birds and code</p>",
+            "followers_count": 310,
+            "following_count": 172,
+            "statuses_count": 1204
+        }"#;
+        let instance = r#"{
+            "uri": "mastodon.social",
+            "title": "Mastodon",
+            "version": "4.3.1",
+            "urls": {"streaming_api": "wss://streaming.mastodon.social"},
+            "configuration": {
+                "statuses": {"max_characters": 500, "max_media_attachments": 4},
+                "media_attachments": {"image_size_limit": 16777216}
+            }
+        }"#;
+        let (server, client) = mock_client(vec![
+            (
+                "GET",
+                "/api/v1/accounts/verify_credentials",
+                200,
+                account.to_string(),
+            ),
+            ("GET", "/api/v1/instance", 200, instance.to_string()),
+        ])
+        .await;
+
+        let account = client.verify_credentials().await.unwrap();
+        assert_eq!(account.network, Network::Mastodon);
+        assert_eq!(account.handle, "alice");
+        assert_eq!(account.display_name, "");
+        assert_eq!(account.server, server.url());
+        assert_eq!(account.max_post_chars, Some(500));
+        assert_eq!(account.server_version.as_deref(), Some("4.3.1"));
+        assert_eq!(account.server_name.as_deref(), Some("Mastodon"));
+        assert_eq!(account.max_media_attachments, Some(4));
+        assert_eq!(account.max_image_bytes, Some(16_777_216));
+        assert_eq!(account.software_label().as_deref(), Some("Mastodon 4.3.1"));
+        assert_eq!(
+            server.requests_to("/api/v1/accounts/verify_credentials")[0].header("authorization"),
+            Some("Bearer token")
+        );
+    }
+
+    #[test]
+    fn test_relationship_follow_state() {
+        let state = |json: &str| {
+            serde_json::from_str::<MastodonRelationship>(json)
+                .unwrap()
+                .follow_state()
+        };
+        assert_eq!(
+            state(r#"{"following": true, "requested": false}"#),
+            FollowState::Following
+        );
+        // Locked accounts approve follows by hand
+        assert_eq!(
+            state(r#"{"following": false, "requested": true}"#),
+            FollowState::Requested
+        );
+        assert_eq!(state("{}"), FollowState::NotFollowing);
+    }
+
+    #[test]
+    fn test_is_newer_id() {
+        assert!(is_newer_id("110", "99"));
+        assert!(is_newer_id("112000000000000002", "112000000000000001"));
+        assert!(!is_newer_id("99", "110"));
+        assert!(!is_newer_id("42", "42"));
+    }
+}
@@ -0,0 +1,302 @@
+//! Turning Mastodon's post and bio HTML into plain text
+//!
+//! Statuses come as a small subset of HTML (paragraphs, line breaks, links,
+//! lists and quotes), but other `ActivityPub` servers send more, so this walks
+//! the tags rather than pattern-matching a few of them. Paragraph and line
+//! breaks are kept, link text is kept, list items get bullets and quotes get
+//! a `> ` prefix.
+
+/// Strip HTML from post content or a bio, keeping line breaks
+pub fn html_to_text(html: &str) -> String {
+    let mut writer = TextWriter::default();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        writer.text(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        match parse_tag(rest) {
+            Some((tag, len)) => {
+                writer.tag(&tag);
+                rest = &rest[len..];
+            }
+            None => {
+                // A stray `<`, not a tag
+                writer.text("<");
+                rest = &rest[1..];
+            }
+        }
+    }
+    writer.text(rest);
+
+    writer.finish()
+}
+
+/// An opening or closing tag
+struct Tag {
+    name: String,
+    closing: bool,
+}
+
+/// Parse the tag at the start of `html`, returning it and its length in bytes
+fn parse_tag(html: &str) -> Option<(Tag, usize)> {
+    let inner = html.strip_prefix('<')?;
+    let (closing, inner) = inner
+        .strip_prefix('/')
+        .map_or((false, inner), |inner| (true, inner));
+    if !inner.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    // Find the closing `>`, skipping any inside quoted attribute values
+    let mut quote = None;
+    let end = inner.char_indices().find_map(|(i, c)| {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+        None
+    })?;
+
+    let name = inner
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let len = html.len() - inner.len() + end + 1;
+    Some((Tag { name, closing }, len))
+}
+
+/// Builds the text, tracking where lines start and what they're nested in
+#[derive(Default)]
+struct TextWriter {
+    out: String,
+    /// Line breaks waiting for the next text, so trailing ones are dropped
+    pending_breaks: usize,
+    /// How many blockquotes deep we are
+    quote_depth: usize,
+    /// Open lists: the next number for `<ol>`, `None` for `<ul>`
+    lists: Vec<Option<u32>>,
+    /// Inside `<pre>`, where whitespace is kept as it is
+    preformatted: usize,
+    /// Inside `<script>` or `<style>`, whose text isn't shown
+    hidden: usize,
+    /// Quote depth of the last line written, for the blank lines after it
+    written_depth: usize,
+}
+
+impl TextWriter {
+    fn tag(&mut self, tag: &Tag) {
+        match (tag.name.as_str(), tag.closing) {
+            ("br", _) => self.pending_breaks += 1,
+            ("p" | "div" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6", _) => self.paragraph(),
+            ("blockquote", false) => {
+                self.paragraph();
+                self.quote_depth += 1;
+            }
+            ("blockquote", true) => {
+                self.paragraph();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+            }
+            ("ul" | "ol", false) => {
+                if self.lists.is_empty() {
+                    self.paragraph();
+                } else {
+                    self.line_break();
+                }
+                self.lists.push((tag.name == "ol").then_some(1));
+            }
+            ("ul" | "ol", true) => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.paragraph();
+                }
+            }
+            ("li", false) => {
+                self.line_break();
+                let depth = self.lists.len().saturating_sub(1);
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                self.write(&format!("{}{marker}", "  ".repeat(depth)));
+            }
+            ("li", true) => self.line_break(),
+            ("pre", false) => {
+                self.paragraph();
+                self.preformatted += 1;
+            }
+            ("pre", true) => {
+                self.preformatted = self.preformatted.saturating_sub(1);
+                self.paragraph();
+            }
+            ("script" | "style", false) => self.hidden += 1,
+            ("script" | "style", true) => self.hidden = self.hidden.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    /// Start a new paragraph (a blank line) before the next text
+    fn paragraph(&mut self) {
+        self.pending_breaks = self.pending_breaks.max(2);
+    }
+
+    /// Start a new line before the next text
+    fn line_break(&mut self) {
+        self.pending_breaks = self.pending_breaks.max(1);
+    }
+
+    /// Add text from between tags, collapsing whitespace and decoding entities
+    fn text(&mut self, raw: &str) {
+        if raw.is_empty() || self.hidden > 0 {
+            return;
+        }
+        if self.preformatted > 0 {
+            self.write(&html_escape::decode_html_entities(raw));
+            return;
+        }
+
+        // Collapse the raw whitespace before decoding, so `&nbsp;` survives
+        let mut collapsed = String::with_capacity(raw.len());
+        for word in raw.split_ascii_whitespace() {
+            if !collapsed.is_empty() {
+                collapsed.push(' ');
+            }
+            collapsed.push_str(word);
+        }
+        let leading_space = raw.starts_with(|c: char| c.is_ascii_whitespace());
+        let trailing_space =
+            !collapsed.is_empty() && raw.ends_with(|c: char| c.is_ascii_whitespace());
+
+        if leading_space && !self.at_line_start() && !self.out.ends_with(' ') {
+            self.write(" ");
+        }
+        if !collapsed.is_empty() {
+            self.write(&html_escape::decode_html_entities(&collapsed));
+        }
+        if trailing_space {
+            self.write(" ");
+        }
+    }
+
+    /// Whether the next text starts a line
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n') || self.pending_breaks > 0
+    }
+
+    /// Write text, adding any pending line breaks and the quote prefix first
+    fn write(&mut self, text: &str) {
+        if self.pending_breaks > 0 && !self.out.is_empty() {
+            let prefix = "> ".repeat(self.quote_depth.min(self.written_depth));
+            for i in 0..self.pending_breaks {
+                if i > 0 {
+                    // Blank lines inside a quote keep the quote marker
+                    self.out.push_str(prefix.trim_end());
+                }
+                self.out.push('\n');
+            }
+        }
+        if self.pending_breaks > 0 || self.out.is_empty() || self.out.ends_with('\n') {
+            self.out.push_str(&"> ".repeat(self.quote_depth));
+        }
+        self.pending_breaks = 0;
+        self.written_depth = self.quote_depth;
+
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.out.push('\n');
+                self.out.push_str(&"> ".repeat(self.quote_depth));
+            }
+            self.out.push_str(line);
+        }
+    }
+
+    fn finish(self) -> String {
+        let lines: Vec<&str> = self.out.lines().map(str::trim_end).collect();
+        lines.join("\n").trim().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paragraphs_and_line_breaks() {
+        assert_eq!(
+            html_to_text(
+                "<p>First line<br>second line</p><p>New paragraph<br/><br />after a gap</p>"
+            ),
+            "First line\nsecond line\n\nNew paragraph\n\nafter a gap"
+        );
+        assert_eq!(
+            html_to_text("<p>  lots   of\n  space </p>"),
+            "lots of space"
+        );
+    }
+
+    #[test]
+    fn test_links_and_nested_tags() {
+        let html = r#"<p>Read <a href="https://example.com/post?a=1&amp;b=2" rel="nofollow noopener" target="_blank"><span class="invisible">https://</span><span class="ellipsis">example.com/post</span><span class="invisible">?a=1&amp;b=2</span></a> by <span class="h-card"><a href="https://mastodon.social/@alice" class="u-url mention">@<span>alice</span></a></span> <a href="https://mastodon.social/tags/rust" class="mention hashtag" rel="tag">#<span>Rust</span></a></p>"#;
+        assert_eq!(
+            html_to_text(html),
+            "Read https://example.com/post?a=1&b=2 by @alice #Rust"
+        );
+
+        // `>` inside an attribute doesn't end the tag
+        assert_eq!(html_to_text(r#"<a title="a > b">link</a>"#), "link");
+    }
+
+    #[test]
+    fn test_entities() {
+        assert_eq!(
+            html_to_text(
+                "<p>Tom &amp; Jerry &lt;3 &quot;quotes&quot; &#39;apostrophes&#39; caf&eacute; &#x1F600;</p>"
+            ),
+            "Tom & Jerry <3 \"quotes\" 'apostrophes' café 😀"
+        );
+        // Escaped tags are text, not markup
+        assert_eq!(html_to_text("<p>&lt;br&gt; is a tag</p>"), "<br> is a tag");
+        // A bare `<` isn't a tag either
+        assert_eq!(html_to_text("1 < 2 and 3 > 2"), "1 < 2 and 3 > 2");
+    }
+
+    #[test]
+    fn test_lists() {
+        assert_eq!(
+            html_to_text(
+                "<p>Shopping:</p><ul><li>eggs</li><li>milk<ul><li>oat</li></ul></li></ul><ol><li>one</li><li>two</li></ol><p>done</p>"
+            ),
+            "Shopping:\n\n• eggs\n• milk\n  • oat\n\n1. one\n2. two\n\ndone"
+        );
+    }
+
+    #[test]
+    fn test_blockquotes() {
+        assert_eq!(
+            html_to_text(
+                "<p>They said:</p><blockquote><p>first</p><p>second<br>line</p></blockquote><p>and that was it</p>"
+            ),
+            "They said:\n\n> first\n>\n> second\n> line\n\nand that was it"
+        );
+    }
+
+    #[test]
+    fn test_preformatted_and_hidden() {
+        assert_eq!(
+            html_to_text(
+                "<p>Code:</p><pre><code>fn main() {\n    x &lt; y\n}</code></pre><script>alert(1)</script><!-- note -->"
+            ),
+            "Code:\n\nfn main() {\n    x < y\n}"
+        );
+    }
+}
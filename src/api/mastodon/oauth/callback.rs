@@ -0,0 +1,203 @@
+//! Catching the OAuth redirect on localhost
+//!
+//! Rather than showing a code to copy back into the terminal, the instance
+//! sends the browser to a listener on the loopback interface, which reads
+//! the code off that request. The port is fixed because Mastodon only
+//! accepts redirect URIs that exactly match a registered one.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use reqwest::Url;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Where the listener accepts the redirect
+const LOOPBACK_ADDR: &str = "127.0.0.1:47621";
+
+/// Redirect URI for the listener, registered with every app
+pub const LOOPBACK_REDIRECT_URI: &str = "http://127.0.0.1:47621/callback";
+
+/// How long to wait for the browser to come back
+const CALLBACK_TIMEOUT: Duration = Duration::from_mins(5);
+
+/// Page shown in the browser once it's been redirected back
+const DONE_PAGE: &str = "<!doctype html><title>Perch</title>\
+    <p>🐦 Authorized! You can close this tab and go back to the terminal.</p>";
+
+/// A listener waiting for the instance to redirect the browser back with an
+/// authorization code
+pub struct CallbackServer {
+    listener: TcpListener,
+    /// Random value the redirect has to carry back, so a request from
+    /// anywhere else can't slip in its own code
+    state: String,
+}
+
+impl CallbackServer {
+    /// Start listening on [`LOOPBACK_REDIRECT_URI`]'s port
+    pub async fn bind() -> Result<Self> {
+        Self::bind_to(LOOPBACK_ADDR).await
+    }
+
+    async fn bind_to(addr: &str) -> Result<Self> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Couldn't listen on {addr}"))?;
+        Ok(Self {
+            listener,
+            state: uuid::Uuid::new_v4().simple().to_string(),
+        })
+    }
+
+    /// The `state` to put in the authorization URL
+    pub fn state(&self) -> &str {
+        &self.state
+    }
+
+    /// Wait for the browser to be redirected back and return the
+    /// authorization code it brings
+    pub async fn wait_for_code(self) -> Result<String> {
+        tokio::time::timeout(CALLBACK_TIMEOUT, self.accept_code())
+            .await
+            .context(
+                "Timed out waiting for the browser to come back (run with --oob to paste the code instead)",
+            )?
+    }
+
+    async fn accept_code(&self) -> Result<String> {
+        loop {
+            let (mut socket, _) = self.listener.accept().await?;
+            let Some(target) = read_request_target(&mut socket).await else {
+                continue;
+            };
+            // Browsers also ask for things like /favicon.ico
+            let Some(result) = parse_callback(&target, &self.state) else {
+                respond(&mut socket, "404 Not Found", "").await;
+                continue;
+            };
+            match result {
+                Ok(code) => {
+                    respond(&mut socket, "200 OK", DONE_PAGE).await;
+                    return Ok(code);
+                }
+                Err(e) => {
+                    let page = format!(
+                        "<!doctype html><title>Perch</title><p>⚠ {}</p>",
+                        html_escape(&e.to_string())
+                    );
+                    respond(&mut socket, "400 Bad Request", &page).await;
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+/// Path and query of an HTTP request, from its request line
+async fn read_request_target(socket: &mut TcpStream) -> Option<String> {
+    let mut buf = vec![0u8; 8192];
+    let mut len = 0;
+    // The request line is all that's needed
+    while !buf[..len].contains(&b'\n') && len < buf.len() {
+        let n = socket.read(&mut buf[len..]).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        len += n;
+    }
+    let head = String::from_utf8_lossy(&buf[..len]);
+    let mut parts = head.lines().next()?.split_whitespace();
+    (parts.next()? == "GET").then_some(())?;
+    parts.next().map(str::to_string)
+}
+
+/// The code (or error) a redirect to `/callback` carries, or `None` for
+/// any other path
+fn parse_callback(target: &str, state: &str) -> Option<Result<String>> {
+    let url = Url::parse(&format!("http://localhost{target}")).ok()?;
+    (url.path() == "/callback").then(|| callback_code(&url, state))
+}
+
+fn callback_code(url: &Url, state: &str) -> Result<String> {
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+    if let Some(error) = param("error") {
+        let description = param("error_description").unwrap_or_default();
+        bail!("Authorization failed: {error} {description}");
+    }
+    if param("state").as_deref() != Some(state) {
+        bail!("Authorization redirect didn't come from this login attempt");
+    }
+    param("code")
+        .filter(|code| !code.is_empty())
+        .context("Authorization redirect had no code")
+}
+
+async fn respond(socket: &mut TcpStream, status: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = socket.write_all(response.as_bytes()).await;
+    let _ = socket.shutdown().await;
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_callback() {
+        let code = |target| parse_callback(target, "abc").map(|r| r.map_err(|e| e.to_string()));
+
+        assert_eq!(
+            code("/callback?code=q9Xf_2&state=abc"),
+            Some(Ok("q9Xf_2".to_string()))
+        );
+        assert!(code("/callback?code=q9Xf_2&state=other").unwrap().is_err());
+        assert!(code("/callback?state=abc").unwrap().is_err());
+        assert_eq!(
+            code(
+                "/callback?error=access_denied&error_description=The+resource+owner+denied+the+request."
+            ),
+            Some(Err(
+                "Authorization failed: access_denied The resource owner denied the request."
+                    .to_string()
+            ))
+        );
+        assert_eq!(code("/favicon.ico"), None);
+    }
+
+    #[tokio::test]
+    async fn test_waits_for_the_redirect() {
+        let server = CallbackServer::bind_to("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", server.listener.local_addr().unwrap());
+        let state = server.state().to_string();
+
+        let browser = tokio::spawn(async move {
+            let client = reqwest::Client::new();
+            let favicon = client.get(format!("{base}/favicon.ico")).send().await;
+            let callback = client
+                .get(format!("{base}/callback?code=the-code&state={state}"))
+                .send()
+                .await
+                .unwrap();
+            (favicon.unwrap().status(), callback.status())
+        });
+
+        assert_eq!(server.wait_for_code().await.unwrap(), "the-code");
+        let (favicon, callback) = browser.await.unwrap();
+        assert_eq!(favicon, reqwest::StatusCode::NOT_FOUND);
+        assert!(callback.is_success());
+    }
+}
@@ -0,0 +1,195 @@
+//! Mastodon rate limits, tracked per instance
+//!
+//! Instances report how many requests are left in the current window with
+//! `X-RateLimit-*` headers and answer `429 Too Many Requests` once they run
+//! out. Every client for an instance shares what it last reported, so once
+//! the limit is used up requests wait for the reset instead of being sent.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use chrono::{DateTime, TimeDelta, Utc};
+use reqwest::header::HeaderMap;
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+/// How long to wait after a `429` that doesn't say when to retry
+const DEFAULT_BACKOFF: TimeDelta = TimeDelta::seconds(60);
+
+/// Last rate limit reported by each instance
+static LIMITS: Mutex<BTreeMap<String, RateLimit>> = Mutex::new(BTreeMap::new());
+
+/// The rate limit an instance last reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Requests allowed per window, if the server said
+    pub limit: Option<u32>,
+    /// Requests left in the current window
+    pub remaining: u32,
+    /// When the window resets
+    pub reset: DateTime<Utc>,
+}
+
+impl RateLimit {
+    /// Read the `X-RateLimit-*` headers, if the server sent them
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| headers.get(name)?.to_str().ok().map(str::trim);
+        Some(Self {
+            limit: header("x-ratelimit-limit").and_then(|v| v.parse().ok()),
+            remaining: header("x-ratelimit-remaining")?.parse().ok()?,
+            reset: parse_reset(header("x-ratelimit-reset")?)?,
+        })
+    }
+
+    /// Whether requests have to wait for the reset
+    pub fn is_exhausted(&self, now: DateTime<Utc>) -> bool {
+        self.remaining == 0 && self.reset > now
+    }
+}
+
+/// Reset times are ISO 8601, though some servers send a Unix timestamp
+fn parse_reset(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok()
+        .or_else(|| DateTime::from_timestamp(value.parse().ok()?, 0))
+}
+
+/// A request was refused, or held back, until an instance's rate limit resets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimited {
+    /// Instance URL
+    pub instance: String,
+    /// When requests can be sent again
+    pub reset: DateTime<Utc>,
+}
+
+impl RateLimited {
+    /// Seconds until requests can be sent again (at least one)
+    pub fn retry_in_secs(&self) -> i64 {
+        (self.reset - Utc::now()).num_seconds().max(1)
+    }
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let host = self
+            .instance
+            .split_once("://")
+            .map_or(self.instance.as_str(), |(_, host)| host);
+        write!(
+            f,
+            "Rate limited by {host}, retry in {}s",
+            self.retry_in_secs()
+        )
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// The rate limit `instance` last reported
+pub fn current(instance: &str) -> Option<RateLimit> {
+    LIMITS.lock().ok()?.get(instance).copied()
+}
+
+/// When background requests can resume, if the limit of any of `instances`
+/// is used up
+pub fn paused_until<'a>(instances: impl IntoIterator<Item = &'a str>) -> Option<DateTime<Utc>> {
+    let now = Utc::now();
+    let limits = LIMITS.lock().ok()?;
+    instances
+        .into_iter()
+        .filter_map(|instance| limits.get(instance.trim_end_matches('/')))
+        .filter(|limit| limit.is_exhausted(now))
+        .map(|limit| limit.reset)
+        .max()
+}
+
+fn record(instance: &str, limit: RateLimit) {
+    if let Ok(mut limits) = LIMITS.lock() {
+        limits.insert(instance.to_string(), limit);
+    }
+}
+
+/// Sending requests to an instance while keeping track of its rate limit
+pub(crate) trait SendLimited {
+    /// Send the request unless `instance` has run out of requests, and note
+    /// the limit it reports. A `429` becomes a [`RateLimited`] error.
+    fn send_limited(self, instance: &str) -> impl Future<Output = Result<Response>> + Send;
+}
+
+impl SendLimited for RequestBuilder {
+    async fn send_limited(self, instance: &str) -> Result<Response> {
+        if let Some(limit) = current(instance).filter(|l| l.is_exhausted(Utc::now())) {
+            return Err(RateLimited {
+                instance: instance.to_string(),
+                reset: limit.reset,
+            }
+            .into());
+        }
+
+        let response = self.send().await?;
+        let reported = RateLimit::from_headers(response.headers());
+
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get("retry-after")
+                .and_then(|v| v.to_str().ok()?.trim().parse().ok())
+                .map_or(DEFAULT_BACKOFF, TimeDelta::seconds);
+            let reset = reported
+                .map(|l| l.reset)
+                .filter(|reset| *reset > Utc::now())
+                .unwrap_or_else(|| Utc::now() + retry_after);
+            record(
+                instance,
+                RateLimit {
+                    limit: reported.and_then(|l| l.limit),
+                    remaining: 0,
+                    reset,
+                },
+            );
+            return Err(RateLimited {
+                instance: instance.to_string(),
+                reset,
+            }
+            .into());
+        }
+
+        if let Some(limit) = reported {
+            record(instance, limit);
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_parse_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(RateLimit::from_headers(&headers), None);
+
+        headers.insert("X-RateLimit-Limit", HeaderValue::from_static("300"));
+        headers.insert("X-RateLimit-Remaining", HeaderValue::from_static("0"));
+        headers.insert(
+            "X-RateLimit-Reset",
+            HeaderValue::from_static("2030-01-15T12:05:00.000Z"),
+        );
+        let limit = RateLimit::from_headers(&headers).unwrap();
+        assert_eq!(limit.limit, Some(300));
+        assert_eq!(limit.remaining, 0);
+        assert_eq!(limit.reset.to_rfc3339(), "2030-01-15T12:05:00+00:00");
+        assert!(limit.is_exhausted(Utc::now()));
+        assert!(!limit.is_exhausted(limit.reset));
+
+        // Unix timestamps are understood too
+        headers.insert("X-RateLimit-Reset", HeaderValue::from_static("1894708800"));
+        let limit = RateLimit::from_headers(&headers).unwrap();
+        assert_eq!(limit.reset.to_rfc3339(), "2030-01-15T12:00:00+00:00");
+    }
+}
@@ -0,0 +1,211 @@
+//! A stand-in HTTP server for testing the API clients
+//!
+//! It answers each request by method and path with a canned response and
+//! keeps what it received, so tests can check both how responses are read
+//! and what the clients send. Several routes for the same method and path
+//! answer in turn, the last one for any requests after that.
+
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// A canned response, and the method and path (without the query) it
+/// answers
+#[derive(Debug, Clone)]
+pub struct Route {
+    method: &'static str,
+    path: &'static str,
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: String,
+}
+
+impl Route {
+    /// Answer `method` requests for `path` with `status` and `body`
+    pub fn new(
+        method: &'static str,
+        path: &'static str,
+        status: u16,
+        body: impl Into<String>,
+    ) -> Self {
+        Self {
+            method,
+            path,
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Send a header with the response too
+    pub fn header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+
+    fn response(&self) -> String {
+        let mut response = format!(
+            "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\n",
+            self.status
+        );
+        for (name, value) in &self.headers {
+            let _ = write!(response, "{name}: {value}\r\n");
+        }
+        let _ = write!(
+            response,
+            "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.body.len(),
+            self.body
+        );
+        response
+    }
+}
+
+impl From<(&'static str, &'static str, u16, String)> for Route {
+    fn from((method, path, status, body): (&'static str, &'static str, u16, String)) -> Self {
+        Self::new(method, path, status, body)
+    }
+}
+
+/// A request the server received
+#[derive(Debug, Clone)]
+pub struct Request {
+    /// e.g. `GET`
+    pub method: String,
+    /// Path and query
+    pub target: String,
+    /// Header names (lowercased) and values
+    pub headers: Vec<(String, String)>,
+    /// Body
+    pub body: String,
+}
+
+impl Request {
+    /// The path, without the query
+    pub fn path(&self) -> &str {
+        self.target.split('?').next().unwrap_or_default()
+    }
+
+    /// A header's value, by lowercase name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The body read as JSON
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).expect("request body isn't JSON")
+    }
+}
+
+/// A server on a free local port, answering until the test ends
+pub struct MockServer {
+    url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    /// Start answering `routes`; anything else gets a 404
+    pub async fn start(routes: impl IntoIterator<Item = impl Into<Route>>) -> Self {
+        let routes: Vec<Route> = routes.into_iter().map(Into::into).collect();
+        let not_found = Route::new("", "", 404, r#"{"error":"Record not found"}"#);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = Arc::clone(&requests);
+        tokio::spawn(async move {
+            let mut answered = vec![false; routes.len()];
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let Some(request) = read_request(&mut socket).await else {
+                    continue;
+                };
+                let matching: Vec<usize> = (0..routes.len())
+                    .filter(|&i| {
+                        routes[i].method == request.method && routes[i].path == request.path()
+                    })
+                    .collect();
+                let route = matching
+                    .iter()
+                    .find(|&&i| !answered[i])
+                    .or_else(|| matching.last())
+                    .map_or(&not_found, |&i| {
+                        answered[i] = true;
+                        &routes[i]
+                    });
+                received.lock().unwrap().push(request);
+                let _ = socket.write_all(route.response().as_bytes()).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+        Self { url, requests }
+    }
+
+    /// Base URL to point a client at
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Requests received so far, oldest first
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// The requests received for `path`
+    pub fn requests_to(&self, path: &str) -> Vec<Request> {
+        self.requests()
+            .into_iter()
+            .filter(|request| request.path() == path)
+            .collect()
+    }
+}
+
+/// Read one request, body included
+async fn read_request(socket: &mut TcpStream) -> Option<Request> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 8192];
+    let head_end = loop {
+        let n = socket.read(&mut buf).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        data.extend_from_slice(&buf[..n]);
+        if let Some(i) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break i + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&data[..head_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?.to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let length = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+    while data.len() < head_end + length {
+        let n = socket.read(&mut buf).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+    }
+    let body = String::from_utf8_lossy(&data[head_end..]).to_string();
+
+    Some(Request {
+        method,
+        target,
+        headers,
+        body,
+    })
+}
@@ -2,10 +2,280 @@
 
 pub mod bluesky;
 pub mod mastodon;
+pub mod streaming;
+mod websocket;
 
-use anyhow::Result;
+#[cfg(test)]
+pub(crate) mod mock_server;
 
-use crate::models::{Account, Network, Post};
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use reqwest::{Certificate, Client as HttpClient, ClientBuilder, NoProxy, Proxy};
+
+use crate::filters::FilterRule;
+use crate::models::{Account, Moderation, Network, Notification, Post, Profile, Visibility};
+
+/// Timeout for a single HTTP request
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Timeout for media uploads, which can take longer than regular API calls
+pub(crate) const UPLOAD_TIMEOUT: Duration = Duration::from_mins(2);
+
+/// Proxy and root certificates every HTTP client is built with
+static NETWORK_OPTIONS: OnceLock<NetworkOptions> = OnceLock::new();
+
+/// Connection settings from the config: a proxy to go through and extra
+/// root certificates to trust
+#[derive(Debug, Clone, Default)]
+pub struct NetworkOptions {
+    proxy: Option<(String, Proxy)>,
+    root_certificates: Vec<Certificate>,
+}
+
+impl NetworkOptions {
+    /// Check the proxy URL and load the certificates in a PEM file, failing
+    /// if either can't be used.
+    ///
+    /// Hosts listed in `NO_PROXY` skip the proxy. Without a proxy URL the
+    /// `HTTPS_PROXY`/`HTTP_PROXY` variables still apply.
+    pub fn new(proxy: Option<&str>, root_certificate: Option<&Path>) -> Result<Self> {
+        let proxy = proxy
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(|url| {
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    bail!("Proxy URL must start with http:// or https://: {url}");
+                }
+                let proxy = Proxy::all(url)
+                    .with_context(|| format!("Invalid proxy URL {url}"))?
+                    .no_proxy(NoProxy::from_env());
+                anyhow::Ok((url.to_string(), proxy))
+            })
+            .transpose()?;
+
+        let root_certificates = match root_certificate {
+            Some(path) => {
+                let pem = std::fs::read(path).with_context(|| {
+                    format!("Failed to read root certificate {}", path.display())
+                })?;
+                let certificates = Certificate::from_pem_bundle(&pem)
+                    .with_context(|| format!("Invalid root certificate {}", path.display()))?;
+                if certificates.is_empty() {
+                    bail!("No certificates found in {}", path.display());
+                }
+                certificates
+            }
+            None => Vec::new(),
+        };
+
+        let options = Self {
+            proxy,
+            root_certificates,
+        };
+        // Building a client parses the certificates, so bad ones fail here
+        // rather than on the first request
+        options
+            .apply(HttpClient::builder())
+            .build()
+            .context("Failed to set up connections with the proxy or root certificate")?;
+        Ok(options)
+    }
+
+    /// The proxy URL, if one is set
+    pub fn proxy_url(&self) -> Option<&str> {
+        self.proxy.as_ref().map(|(url, _)| url.as_str())
+    }
+
+    fn apply(&self, mut builder: ClientBuilder) -> ClientBuilder {
+        if let Some((_, proxy)) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        for certificate in &self.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+        builder
+    }
+}
+
+/// Use these connection settings for every HTTP client.
+///
+/// Call it once at startup, before the first request: clients are built on
+/// first use and keep the settings they were built with.
+pub fn init_network(options: NetworkOptions) {
+    let _ = NETWORK_OPTIONS.set(options);
+}
+
+/// The connection settings set at startup
+pub fn network_options() -> Option<&'static NetworkOptions> {
+    NETWORK_OPTIONS.get()
+}
+
+/// Start building an HTTP client with the proxy and root certificates set up
+pub(crate) fn client_builder() -> ClientBuilder {
+    let builder = HttpClient::builder().user_agent(format!("Perch/{}", env!("CARGO_PKG_VERSION")));
+    match network_options() {
+        Some(options) => options.apply(builder),
+        None => builder,
+    }
+}
+
+/// Get the shared HTTP client.
+///
+/// All API clients, OAuth calls and image downloads go through one
+/// connection pool so TLS sessions and keep-alive connections are reused.
+/// It sends a proper User-Agent header: `GoToSocial` (and potentially other
+/// `ActivityPub` servers) reject requests without one with HTTP 418
+/// "I'm a teapot".
+pub fn http_client() -> HttpClient {
+    static CLIENT: OnceLock<HttpClient> = OnceLock::new();
+
+    CLIENT
+        .get_or_init(|| {
+            client_builder()
+                .timeout(HTTP_TIMEOUT)
+                .build()
+                .expect("Failed to build HTTP client")
+        })
+        .clone()
+}
+
+/// Maximum number of images per post (same on Mastodon and Bluesky)
+pub const MAX_MEDIA_ATTACHMENTS: usize = 4;
+
+/// A media file to upload alongside a post
+#[derive(Debug, Clone)]
+pub struct MediaUpload {
+    /// File name, used for multipart uploads and error messages
+    pub file_name: String,
+    /// Raw file contents
+    pub bytes: Vec<u8>,
+    /// MIME type (e.g. `image/png`)
+    pub mime_type: String,
+    /// Alt text description
+    pub alt_text: Option<String>,
+}
+
+impl MediaUpload {
+    /// Read an image from disk, inferring the MIME type from its extension
+    pub fn from_path(path: &Path, alt_text: Option<String>) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase)
+            .unwrap_or_default();
+
+        let mime_type = match extension.as_str() {
+            "jpg" | "jpeg" => "image/jpeg",
+            "png" => "image/png",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            _ => bail!("Unsupported image type: {}", path.display()),
+        };
+
+        let bytes =
+            std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+        let file_name = path
+            .file_name()
+            .map_or_else(|| "image".to_string(), |n| n.to_string_lossy().to_string());
+
+        Ok(Self {
+            file_name,
+            bytes,
+            mime_type: mime_type.to_string(),
+            alt_text: alt_text.filter(|a| !a.trim().is_empty()),
+        })
+    }
+}
+
+/// One page of a timeline
+#[derive(Debug, Clone, Default)]
+pub struct TimelinePage {
+    /// Posts on this page (newest first)
+    pub posts: Vec<Post>,
+    /// Cursor for the next (older) page, if there is one
+    pub next_cursor: Option<String>,
+}
+
+/// Which timeline to fetch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimelineKind {
+    /// Posts from followed accounts
+    #[default]
+    Home,
+    /// Public posts from the account's own instance (Mastodon only)
+    Local,
+    /// Public posts from every instance the account's instance knows (Mastodon only)
+    Federated,
+}
+
+impl TimelineKind {
+    /// Cycle Home → Local → Federated
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Home => Self::Local,
+            Self::Local => Self::Federated,
+            Self::Federated => Self::Home,
+        }
+    }
+
+    /// Display name
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Home => "Home",
+            Self::Local => "Local",
+            Self::Federated => "Federated",
+        }
+    }
+}
+
+/// Posts found by a server-side search
+#[derive(Debug, Clone, Default)]
+pub struct SearchPage {
+    /// Matching posts
+    pub posts: Vec<Post>,
+    /// Why the results aren't a plain text match, e.g. when the server
+    /// can't search post text and related posts were shown instead
+    pub notice: Option<String>,
+}
+
+/// The conversation around a post
+#[derive(Debug, Clone, Default)]
+pub struct PostContext {
+    /// Posts this one replies to, oldest (thread root) first
+    pub ancestors: Vec<Post>,
+    /// Replies to this post, at any depth
+    pub descendants: Vec<Post>,
+}
+
+/// Per-post options chosen in compose
+#[derive(Debug, Clone, Default)]
+pub struct PostOptions {
+    /// Post visibility (ignored by Bluesky)
+    pub visibility: Visibility,
+    /// Content warning (ignored by Bluesky)
+    pub spoiler_text: Option<String>,
+    /// Split a post too long for an account into a thread (the clients
+    /// ignore it; see [`crate::models::split_thread`])
+    pub thread: bool,
+}
+
+/// Fail early if more images are attached than the networks allow
+pub(crate) fn check_media_count(media: &[MediaUpload]) -> Result<()> {
+    if media.len() > MAX_MEDIA_ATTACHMENTS {
+        bail!(
+            "Too many images: {} attached, at most {MAX_MEDIA_ATTACHMENTS} allowed",
+            media.len()
+        );
+    }
+    Ok(())
+}
 
 /// Unified API trait for social networks
 #[allow(async_fn_in_trait)]
@@ -13,14 +283,28 @@ pub trait SocialApi {
     /// Get the home timeline
     async fn timeline(&self, limit: usize) -> Result<Vec<Post>>;
 
-    /// Get replies/context for a post
-    async fn get_context(&self, post: &Post) -> Result<Vec<Post>>;
+    /// Get a page of the home timeline, starting after `cursor`
+    async fn timeline_paged(&self, limit: usize, cursor: Option<String>) -> Result<TimelinePage>;
+
+    /// Get the ancestors and replies of a post
+    async fn get_context(&self, post: &Post) -> Result<PostContext>;
 
     /// Post a new status
-    async fn post(&self, content: &str) -> Result<Post>;
+    async fn post(&self, content: &str, options: &PostOptions) -> Result<Post>;
+
+    /// Post a new status with attached media
+    async fn post_with_media(
+        &self,
+        content: &str,
+        media: Vec<MediaUpload>,
+        options: &PostOptions,
+    ) -> Result<Post>;
 
     /// Reply to a post
-    async fn reply(&self, content: &str, reply_to_id: &str) -> Result<Post>;
+    async fn reply(&self, content: &str, reply_to_id: &str, options: &PostOptions) -> Result<Post>;
+
+    /// Post with another post quoted (Mastodon links it, having no native quotes)
+    async fn quote(&self, post: &Post, content: &str, options: &PostOptions) -> Result<Post>;
 
     /// Like/favorite a post
     async fn like(&self, post: &Post) -> Result<()>;
@@ -32,10 +316,67 @@ pub trait SocialApi {
     async fn repost(&self, post: &Post) -> Result<()>;
 
     /// Unrepost/unboost a post
-    async fn unrepost(&self, post: &Post) -> Result<()>;
+    ///
+    /// Returns `false` if there was no repost to remove.
+    async fn unrepost(&self, post: &Post) -> Result<bool>;
+
+    /// Bookmark a post
+    async fn bookmark(&self, post: &Post) -> Result<()>;
+
+    /// Remove a bookmark
+    async fn unbookmark(&self, post: &Post) -> Result<()>;
+
+    /// Delete one of your own posts
+    async fn delete(&self, post: &Post) -> Result<()>;
+
+    /// Replace the text of one of your own posts, returning the updated post
+    async fn edit(&self, post_id: &str, content: &str) -> Result<Post>;
+
+    /// Search posts on the server
+    async fn search(&self, query: &str, limit: usize) -> Result<SearchPage>;
+
+    /// Get recent posts with a hashtag (`tag` without the `#`)
+    async fn hashtag_timeline(&self, tag: &str, limit: usize) -> Result<Vec<Post>>;
 
     /// Verify credentials and get account info
     async fn verify_credentials(&self) -> Result<Account>;
+
+    /// Look up an account's profile by handle
+    async fn profile(&self, handle: &str) -> Result<Profile>;
+
+    /// Get a page of an account's own posts and reposts, starting after `cursor`
+    async fn author_feed(
+        &self,
+        profile: &Profile,
+        limit: usize,
+        cursor: Option<String>,
+    ) -> Result<TimelinePage>;
+
+    /// Follow an account, returning the profile with its new follow state
+    /// ([`crate::models::FollowState::Requested`] when a locked account has to approve it)
+    async fn follow(&self, profile: &Profile) -> Result<Profile>;
+
+    /// Unfollow an account (or withdraw a follow request), returning the
+    /// profile with its new follow state
+    async fn unfollow(&self, profile: &Profile) -> Result<Profile>;
+
+    /// Mute an account, hiding its posts from the timeline
+    async fn mute(&self, profile: &Profile) -> Result<()>;
+
+    /// Unmute an account
+    async fn unmute(&self, profile: &Profile) -> Result<()>;
+
+    /// Block an account, hiding its posts and stopping it from interacting
+    async fn block(&self, profile: &Profile) -> Result<()>;
+
+    /// Unblock an account
+    async fn unblock(&self, profile: &Profile) -> Result<()>;
+
+    /// Get recent notifications (newest first), with read state from the server
+    async fn notifications(&self, limit: usize) -> Result<Vec<Notification>>;
+
+    /// Mark all notifications up to now as read on the server
+    async fn mark_notifications_read(&self) -> Result<()>;
 }
 
 /// Unified client that wraps network-specific implementations
@@ -55,8 +396,44 @@ impl Client {
         }
     }
 
+    /// Get a page of the home timeline, starting after `cursor`
+    pub async fn timeline_paged(
+        &self,
+        limit: usize,
+        cursor: Option<String>,
+    ) -> Result<TimelinePage> {
+        match self {
+            Self::Mastodon(c) => c.timeline_paged(limit, cursor).await,
+            Self::Bluesky(c) => c.timeline_paged(limit, cursor).await,
+        }
+    }
+
+    /// Get a page of a home or public timeline, starting after `cursor`
+    pub async fn timeline_kind(
+        &self,
+        kind: TimelineKind,
+        limit: usize,
+        cursor: Option<String>,
+    ) -> Result<TimelinePage> {
+        match self {
+            Self::Mastodon(c) => c.timeline_kind(kind, limit, cursor).await,
+            Self::Bluesky(_) if kind != TimelineKind::Home => {
+                bail!("Bluesky has no {} timeline", kind.name().to_lowercase())
+            }
+            Self::Bluesky(c) => c.timeline_paged(limit, cursor).await,
+        }
+    }
+
+    /// Stream new home timeline posts into `sink` until the connection drops
+    pub async fn stream_home(&self, sink: &streaming::PostSink) -> Result<()> {
+        match self {
+            Self::Mastodon(c) => c.stream_home(sink).await,
+            Self::Bluesky(c) => c.stream_home(sink).await,
+        }
+    }
+
     /// Get replies/context for a post
-    pub async fn get_context(&self, post: &Post) -> Result<Vec<Post>> {
+    pub async fn get_context(&self, post: &Post) -> Result<PostContext> {
         match self {
             Self::Mastodon(c) => c.get_context(post).await,
             Self::Bluesky(c) => c.get_context(post).await,
@@ -64,18 +441,66 @@ impl Client {
     }
 
     /// Post a new status
-    pub async fn post(&self, content: &str) -> Result<Post> {
+    pub async fn post(&self, content: &str, options: &PostOptions) -> Result<Post> {
+        match self {
+            Self::Mastodon(c) => c.post(content, options).await,
+            Self::Bluesky(c) => c.post(content, options).await,
+        }
+    }
+
+    /// Post a new status with attached media
+    pub async fn post_with_media(
+        &self,
+        content: &str,
+        media: Vec<MediaUpload>,
+        options: &PostOptions,
+    ) -> Result<Post> {
         match self {
-            Self::Mastodon(c) => c.post(content).await,
-            Self::Bluesky(c) => c.post(content).await,
+            Self::Mastodon(c) => c.post_with_media(content, media, options).await,
+            Self::Bluesky(c) => c.post_with_media(content, media, options).await,
         }
     }
 
     /// Reply to a post
-    pub async fn reply(&self, content: &str, reply_to_id: &str) -> Result<Post> {
+    pub async fn reply(
+        &self,
+        content: &str,
+        reply_to_id: &str,
+        options: &PostOptions,
+    ) -> Result<Post> {
+        match self {
+            Self::Mastodon(c) => c.reply(content, reply_to_id, options).await,
+            Self::Bluesky(c) => c.reply(content, reply_to_id, options).await,
+        }
+    }
+
+    /// Post `parts` as replies carrying on the thread `first` started, each
+    /// replying to the one before
+    pub async fn continue_thread(
+        &self,
+        first: &Post,
+        parts: &[String],
+        options: &PostOptions,
+    ) -> Result<Vec<Post>> {
+        let mut posted: Vec<Post> = Vec::with_capacity(parts.len());
+        for (i, part) in parts.iter().enumerate() {
+            let parent = posted.last().unwrap_or(first);
+            let result = match self {
+                Self::Mastodon(c) => c.reply(part, &parent.network_id, options).await,
+                Self::Bluesky(c) => c.reply_in_thread(part, parent, first).await,
+            };
+            let post = result
+                .with_context(|| format!("Part {} of {} of the thread", i + 2, parts.len() + 1))?;
+            posted.push(post);
+        }
+        Ok(posted)
+    }
+
+    /// Post with another post quoted
+    pub async fn quote(&self, post: &Post, content: &str, options: &PostOptions) -> Result<Post> {
         match self {
-            Self::Mastodon(c) => c.reply(content, reply_to_id).await,
-            Self::Bluesky(c) => c.reply(content, reply_to_id).await,
+            Self::Mastodon(c) => c.quote(post, content, options).await,
+            Self::Bluesky(c) => c.quote(post, content, options).await,
         }
     }
 
@@ -104,13 +529,63 @@ impl Client {
     }
 
     /// Unrepost/unboost a post
-    pub async fn unrepost(&self, post: &Post) -> Result<()> {
+    ///
+    /// Returns `false` if there was no repost to remove.
+    pub async fn unrepost(&self, post: &Post) -> Result<bool> {
         match self {
             Self::Mastodon(c) => c.unrepost(post).await,
             Self::Bluesky(c) => c.unrepost(post).await,
         }
     }
 
+    /// Bookmark a post
+    pub async fn bookmark(&self, post: &Post) -> Result<()> {
+        match self {
+            Self::Mastodon(c) => c.bookmark(post).await,
+            Self::Bluesky(c) => c.bookmark(post).await,
+        }
+    }
+
+    /// Remove a bookmark
+    pub async fn unbookmark(&self, post: &Post) -> Result<()> {
+        match self {
+            Self::Mastodon(c) => c.unbookmark(post).await,
+            Self::Bluesky(c) => c.unbookmark(post).await,
+        }
+    }
+
+    /// Delete one of your own posts
+    pub async fn delete(&self, post: &Post) -> Result<()> {
+        match self {
+            Self::Mastodon(c) => c.delete(post).await,
+            Self::Bluesky(c) => c.delete(post).await,
+        }
+    }
+
+    /// Replace the text of one of your own posts, returning the updated post
+    pub async fn edit(&self, post_id: &str, content: &str) -> Result<Post> {
+        match self {
+            Self::Mastodon(c) => c.edit(post_id, content).await,
+            Self::Bluesky(c) => c.edit(post_id, content).await,
+        }
+    }
+
+    /// Search posts on the server
+    pub async fn search(&self, query: &str, limit: usize) -> Result<SearchPage> {
+        match self {
+            Self::Mastodon(c) => c.search(query, limit).await,
+            Self::Bluesky(c) => c.search(query, limit).await,
+        }
+    }
+
+    /// Get recent posts with a hashtag
+    pub async fn hashtag_timeline(&self, tag: &str, limit: usize) -> Result<Vec<Post>> {
+        match self {
+            Self::Mastodon(c) => c.hashtag_timeline(tag, limit).await,
+            Self::Bluesky(c) => c.hashtag_timeline(tag, limit).await,
+        }
+    }
+
     /// Verify credentials and get account info
     pub async fn verify_credentials(&self) -> Result<Account> {
         match self {
@@ -118,25 +593,220 @@ impl Client {
             Self::Bluesky(c) => c.verify_credentials().await,
         }
     }
+
+    /// Look up an account's profile by handle
+    pub async fn profile(&self, handle: &str) -> Result<Profile> {
+        match self {
+            Self::Mastodon(c) => c.profile(handle).await,
+            Self::Bluesky(c) => c.profile(handle).await,
+        }
+    }
+
+    /// Get a page of an account's own posts and reposts, starting after `cursor`
+    pub async fn author_feed(
+        &self,
+        profile: &Profile,
+        limit: usize,
+        cursor: Option<String>,
+    ) -> Result<TimelinePage> {
+        match self {
+            Self::Mastodon(c) => c.author_feed(profile, limit, cursor).await,
+            Self::Bluesky(c) => c.author_feed(profile, limit, cursor).await,
+        }
+    }
+
+    /// Follow an account, returning the profile with its new follow state
+    pub async fn follow(&self, profile: &Profile) -> Result<Profile> {
+        match self {
+            Self::Mastodon(c) => c.follow(profile).await,
+            Self::Bluesky(c) => c.follow(profile).await,
+        }
+    }
+
+    /// Unfollow an account, returning the profile with its new follow state
+    pub async fn unfollow(&self, profile: &Profile) -> Result<Profile> {
+        match self {
+            Self::Mastodon(c) => c.unfollow(profile).await,
+            Self::Bluesky(c) => c.unfollow(profile).await,
+        }
+    }
+
+    /// Mute or block an account, or with `undo` lift it again
+    pub async fn moderate(
+        &self,
+        profile: &Profile,
+        moderation: Moderation,
+        undo: bool,
+    ) -> Result<()> {
+        match (self, moderation, undo) {
+            (Self::Mastodon(c), Moderation::Mute, false) => c.mute(profile).await,
+            (Self::Mastodon(c), Moderation::Mute, true) => c.unmute(profile).await,
+            (Self::Mastodon(c), Moderation::Block, false) => c.block(profile).await,
+            (Self::Mastodon(c), Moderation::Block, true) => c.unblock(profile).await,
+            (Self::Bluesky(c), Moderation::Mute, false) => c.mute(profile).await,
+            (Self::Bluesky(c), Moderation::Mute, true) => c.unmute(profile).await,
+            (Self::Bluesky(c), Moderation::Block, false) => c.block(profile).await,
+            (Self::Bluesky(c), Moderation::Block, true) => c.unblock(profile).await,
+        }
+    }
+
+    /// Have the server publish a post at `scheduled_at`, returning the ID it
+    /// was scheduled under (Mastodon only)
+    pub async fn schedule_post(
+        &self,
+        content: &str,
+        options: &PostOptions,
+        scheduled_at: DateTime<Utc>,
+    ) -> Result<String> {
+        match self {
+            Self::Mastodon(c) => c.schedule_status(content, options, scheduled_at).await,
+            Self::Bluesky(_) => bail!("Bluesky can't schedule posts on the server"),
+        }
+    }
+
+    /// Cancel a post scheduled with [`Self::schedule_post`]
+    pub async fn cancel_scheduled_post(&self, id: &str) -> Result<()> {
+        match self {
+            Self::Mastodon(c) => c.cancel_scheduled_status(id).await,
+            Self::Bluesky(_) => bail!("Bluesky can't schedule posts on the server"),
+        }
+    }
+
+    /// Filters set up on the account for timelines (Mastodon only; Bluesky
+    /// has none)
+    pub async fn server_filters(&self) -> Result<Vec<FilterRule>> {
+        match self {
+            Self::Mastodon(c) => c.filters().await,
+            Self::Bluesky(_) => Ok(Vec::new()),
+        }
+    }
+
+    /// Longest post the server accepts, in characters, if it says (Bluesky's
+    /// limit is fixed)
+    pub async fn max_post_chars(&self) -> Option<usize> {
+        match self {
+            Self::Mastodon(c) => c.max_post_chars().await,
+            Self::Bluesky(_) => None,
+        }
+    }
+
+    /// Get recent notifications
+    pub async fn notifications(&self, limit: usize) -> Result<Vec<Notification>> {
+        match self {
+            Self::Mastodon(c) => c.notifications(limit).await,
+            Self::Bluesky(c) => c.notifications(limit).await,
+        }
+    }
+
+    /// Mark all notifications as read
+    pub async fn mark_notifications_read(&self) -> Result<()> {
+        match self {
+            Self::Mastodon(c) => c.mark_notifications_read().await,
+            Self::Bluesky(c) => c.mark_notifications_read().await,
+        }
+    }
 }
 
+/// A client being set up for an account
+type Connecting<'a> = Pin<Box<dyn Future<Output = Result<Client>> + Send + 'a>>;
+
+/// How to get a client for a network's accounts
+struct Backend {
+    network: Network,
+    /// Build a client from an account and its stored token
+    connect: for<'a> fn(&'a Account, &'a str) -> Connecting<'a>,
+}
+
+/// Client constructors for every network (what each supports is in
+/// [`Network::capabilities`])
+static BACKENDS: &[Backend] = &[
+    Backend {
+        network: Network::Mastodon,
+        connect: |account, token| Box::pin(connect_mastodon(account, token)),
+    },
+    Backend {
+        network: Network::Bluesky,
+        connect: |account, token| Box::pin(connect_bluesky(account, token)),
+    },
+];
+
 /// Get the appropriate API client for an account
 pub async fn get_client(account: &Account, token: &str) -> Result<Client> {
-    match account.network {
-        Network::Mastodon => {
-            let client = mastodon::MastodonClient::new(&account.server, token);
-            Ok(Client::Mastodon(client))
-        }
-        Network::Bluesky => {
-            // For Bluesky, token is the app password, server is the PDS URL
-            let pds_url = if account.server.is_empty() {
-                bluesky::DEFAULT_PDS_URL
-            } else {
-                &account.server
-            };
-            let client =
-                bluesky::BlueskyClient::login_with_pds(&account.handle, token, pds_url).await?;
-            Ok(Client::Bluesky(client))
+    let backend = BACKENDS
+        .iter()
+        .find(|b| b.network == account.network)
+        .with_context(|| format!("{} accounts aren't supported", account.network))?;
+    (backend.connect)(account, token).await
+}
+
+/// End an account's session on its server, before its stored credentials
+/// are removed.
+///
+/// Only OAuth networks hand out a token that can be revoked; returns whether
+/// one was.
+pub async fn revoke_credentials(account: &Account) -> Result<bool> {
+    if !account.network.capabilities().oauth {
+        return Ok(false);
+    }
+    let Some(token) = crate::auth::get_credentials(account)? else {
+        return Ok(false);
+    };
+    let Some((client_id, client_secret)) = crate::auth::get_oauth_client(&account.server)? else {
+        bail!("No app registration stored for {}", account.server);
+    };
+    let app = mastodon::oauth::OAuthApp {
+        client_id,
+        client_secret,
+    };
+    mastodon::oauth::revoke_token(&account.server, &app, &token, None).await?;
+    Ok(true)
+}
+
+async fn connect_mastodon(account: &Account, token: &str) -> Result<Client> {
+    let client = mastodon::MastodonClient::new(&account.server, token)
+        .with_software(account.server_software());
+    Ok(Client::Mastodon(client))
+}
+
+async fn connect_bluesky(account: &Account, token: &str) -> Result<Client> {
+    // For Bluesky, token is the app password, server is the PDS URL
+    let resolved;
+    let pds_url = if account.server.is_empty() {
+        resolved = bluesky::resolve_pds(&account.handle).await;
+        resolved.as_deref().unwrap_or(bluesky::DEFAULT_PDS_URL)
+    } else {
+        &account.server
+    };
+    // Reuse a stored session if there is one; it is refreshed on demand
+    let stored = crate::auth::get_session(account)
+        .ok()
+        .flatten()
+        .and_then(|s| serde_json::from_str::<bluesky::Session>(&s).ok());
+
+    let client = if let Some(session) = stored {
+        bluesky::BlueskyClient::from_session(pds_url, session).with_password(&account.handle, token)
+    } else {
+        let client =
+            bluesky::BlueskyClient::login_with_pds(&account.handle, token, pds_url).await?;
+        if let Err(e) = serde_json::to_string(&client.session())
+            .map_err(anyhow::Error::from)
+            .and_then(|s| crate::auth::store_session(account, &s))
+        {
+            tracing::warn!("Failed to persist Bluesky session: {e}");
+        }
+        client
+    };
+    Ok(Client::Bluesky(client.with_account(account.clone())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_network_has_a_backend() {
+        for network in Network::all() {
+            assert!(BACKENDS.iter().any(|b| b.network == *network));
         }
     }
 }
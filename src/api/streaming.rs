@@ -0,0 +1,170 @@
+//! Shared pieces for streaming new posts (server-sent events and the HTTP
+//! client long-lived connections go through)
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use reqwest::Client as HttpClient;
+use tokio::sync::mpsc;
+
+use crate::models::Post;
+
+/// Where streams deliver new posts
+pub type PostSink = mpsc::Sender<Vec<Post>>;
+
+/// Give up on a stream that has sent nothing (not even a heartbeat) for this long
+pub const STREAM_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Timeout for opening a streaming connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Get the HTTP client for streaming connections.
+///
+/// Unlike [`super::http_client`] it has no overall request timeout, since
+/// streams stay open indefinitely, and it sticks to HTTP/1.1 so connections
+/// can be upgraded to web sockets.
+pub fn stream_client() -> HttpClient {
+    static CLIENT: OnceLock<HttpClient> = OnceLock::new();
+
+    CLIENT
+        .get_or_init(|| {
+            super::client_builder()
+                .connect_timeout(CONNECT_TIMEOUT)
+                .http1_only()
+                .build()
+                .expect("Failed to build streaming HTTP client")
+        })
+        .clone()
+}
+
+/// One server-sent event
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    /// Event type (`update`, `delete`, ...)
+    pub event: String,
+    /// Payload, with multiple `data:` lines joined by newlines
+    pub data: String,
+}
+
+/// Take every complete event out of `buffer`, leaving a partial one behind
+/// for the next chunk. Comment lines (heartbeats) are skipped.
+pub fn take_sse_events(buffer: &mut Vec<u8>) -> Vec<SseEvent> {
+    let mut events = Vec::new();
+
+    while let Some(end) = event_end(buffer) {
+        let block: Vec<u8> = buffer.drain(..end).collect();
+        let mut event = SseEvent::default();
+
+        for line in String::from_utf8_lossy(&block).split(['\r', '\n']) {
+            if line.is_empty() || line.starts_with(':') {
+                continue;
+            }
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => event.event = value.to_string(),
+                "data" => {
+                    if !event.data.is_empty() {
+                        event.data.push('\n');
+                    }
+                    event.data.push_str(value);
+                }
+                _ => {}
+            }
+        }
+
+        if !event.event.is_empty() || !event.data.is_empty() {
+            events.push(event);
+        }
+    }
+
+    events
+}
+
+/// Where the first event in `buffer` ends, just past the blank line closing
+/// it. Lines may end in `\r\n`, `\n` or `\r`, as SSE allows.
+fn event_end(buffer: &[u8]) -> Option<usize> {
+    let mut line_start = 0;
+    let mut i = 0;
+    while i < buffer.len() {
+        let terminator = match buffer[i] {
+            b'\n' => 1,
+            b'\r' => match buffer.get(i + 1) {
+                Some(b'\n') => 2,
+                Some(_) => 1,
+                // Could be the first half of a `\r\n`
+                None => return None,
+            },
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        i += terminator;
+        if i - terminator == line_start {
+            return Some(i);
+        }
+        line_start = i;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_sse_events_across_chunks() {
+        let mut buffer = b":thump\n\nevent: update\ndata: {\"id\":".to_vec();
+        assert!(take_sse_events(&mut buffer).is_empty());
+
+        buffer.extend_from_slice(b"\"1\"}\n\nevent: delete\ndata: 2\n\nevent: upd");
+        let events = take_sse_events(&mut buffer);
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    event: "update".to_string(),
+                    data: "{\"id\":\"1\"}".to_string(),
+                },
+                SseEvent {
+                    event: "delete".to_string(),
+                    data: "2".to_string(),
+                },
+            ]
+        );
+        // The unfinished event waits for the next chunk
+        assert_eq!(buffer, b"event: upd");
+    }
+
+    #[test]
+    fn test_take_sse_events_joins_data_lines() {
+        let mut buffer = b"data: a\ndata:b\r\n\n".to_vec();
+        let events = take_sse_events(&mut buffer);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "a\nb");
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_take_sse_events_with_crlf() {
+        let mut buffer =
+            b":thump\r\n\r\nevent: update\r\ndata: 1\r\n\r\nevent: delete\r\ndata: 2\r\n\r"
+                .to_vec();
+        let events = take_sse_events(&mut buffer);
+        assert_eq!(
+            events,
+            vec![SseEvent {
+                event: "update".to_string(),
+                data: "1".to_string(),
+            }]
+        );
+        // The last `\r` may still be followed by `\n`
+        assert_eq!(buffer, b"event: delete\r\ndata: 2\r\n\r");
+
+        buffer.extend_from_slice(b"\n");
+        let events = take_sse_events(&mut buffer);
+        assert_eq!(events[0].event, "delete");
+        assert!(buffer.is_empty());
+    }
+}
@@ -0,0 +1,348 @@
+//! Minimal WebSocket client (RFC 6455) on top of an upgraded HTTP connection
+//!
+//! Only what streaming needs: text messages in both directions, pings and
+//! close. Extensions such as compression are never negotiated.
+
+use anyhow::{Context, Result, bail};
+use base64::Engine;
+use reqwest::{Client, StatusCode};
+use sha1::{Digest, Sha1};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use uuid::Uuid;
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+/// Length markers for payloads needing a 16-bit or 64-bit extended length
+const LEN_U16: u8 = 0x7E;
+const LEN_U64: u8 = 0x7F;
+
+/// Refuse frames larger than this rather than buffering them
+const MAX_FRAME_LEN: u64 = 16 * 1024 * 1024;
+
+/// Appended to the handshake key before hashing it for `Sec-WebSocket-Accept`
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// A decoded frame
+#[derive(Debug, PartialEq, Eq)]
+struct Frame {
+    fin: bool,
+    opcode: u8,
+    payload: Vec<u8>,
+}
+
+/// A message being put back together from its frames
+#[derive(Debug, Default)]
+struct PartialMessage {
+    /// Opcode of the frame that started it, until its last frame
+    opcode: Option<u8>,
+    /// Text received so far
+    text: Vec<u8>,
+}
+
+impl PartialMessage {
+    /// Add a text, binary or continuation frame, returning the text once a
+    /// text message is complete. Binary messages are dropped.
+    fn push(&mut self, frame: Frame) -> Option<String> {
+        let opcode = if frame.opcode == OPCODE_CONTINUATION {
+            // A continuation with nothing to continue is ignored
+            self.opcode?
+        } else {
+            self.text.clear();
+            frame.opcode
+        };
+        if opcode == OPCODE_TEXT {
+            self.text.extend_from_slice(&frame.payload);
+        }
+        if !frame.fin {
+            self.opcode = Some(opcode);
+            return None;
+        }
+
+        self.opcode = None;
+        let text = std::mem::take(&mut self.text);
+        (opcode == OPCODE_TEXT).then(|| String::from_utf8_lossy(&text).into_owned())
+    }
+}
+
+/// An open WebSocket connection
+pub struct WebSocket {
+    io: reqwest::Upgraded,
+    /// Bytes read but not yet decoded
+    buffer: Vec<u8>,
+    /// A message split over several frames
+    partial: PartialMessage,
+    awaiting_pong: bool,
+}
+
+impl WebSocket {
+    /// Open a connection. `url` uses `https://` (or `http://`) rather than
+    /// `wss://`, since the handshake is a regular HTTP request.
+    pub async fn connect(client: &Client, url: &str) -> Result<Self> {
+        let key = base64::engine::general_purpose::STANDARD.encode(Uuid::new_v4().as_bytes());
+
+        let response = client
+            .get(url)
+            .header("Connection", "Upgrade")
+            .header("Upgrade", "websocket")
+            .header("Sec-WebSocket-Version", "13")
+            .header("Sec-WebSocket-Key", &key)
+            .send()
+            .await
+            .context("Failed to open WebSocket")?;
+
+        if response.status() != StatusCode::SWITCHING_PROTOCOLS {
+            bail!("WebSocket handshake failed: HTTP {}", response.status());
+        }
+        let accept = response
+            .headers()
+            .get("Sec-WebSocket-Accept")
+            .and_then(|value| value.to_str().ok());
+        if accept != Some(accept_key(&key).as_str()) {
+            bail!("WebSocket handshake failed: the server didn't accept the key");
+        }
+
+        let io = response
+            .upgrade()
+            .await
+            .context("Failed to upgrade to WebSocket")?;
+
+        Ok(Self {
+            io,
+            buffer: Vec::new(),
+            partial: PartialMessage::default(),
+            awaiting_pong: false,
+        })
+    }
+
+    /// Send a text message
+    pub async fn send_text(&mut self, text: &str) -> Result<()> {
+        self.send_frame(OPCODE_TEXT, text.as_bytes()).await
+    }
+
+    /// Send a ping to check the connection is alive. Any frame from the server
+    /// clears [`Self::awaiting_pong`].
+    pub async fn ping(&mut self) -> Result<()> {
+        self.send_frame(OPCODE_PING, b"").await?;
+        self.awaiting_pong = true;
+        Ok(())
+    }
+
+    /// Whether a ping is still unanswered
+    pub const fn awaiting_pong(&self) -> bool {
+        self.awaiting_pong
+    }
+
+    /// Wait for the next text message, answering pings along the way.
+    ///
+    /// Returns `None` once the server closes the connection.
+    pub async fn next_text(&mut self) -> Result<Option<String>> {
+        loop {
+            while let Some(frame) = decode_frame(&mut self.buffer)? {
+                self.awaiting_pong = false;
+                match frame.opcode {
+                    OPCODE_TEXT | OPCODE_BINARY | OPCODE_CONTINUATION => {
+                        if let Some(text) = self.partial.push(frame) {
+                            return Ok(Some(text));
+                        }
+                    }
+                    OPCODE_PING => self.send_frame(OPCODE_PONG, &frame.payload).await?,
+                    OPCODE_CLOSE => return Ok(None),
+                    // Pongs
+                    _ => {}
+                }
+            }
+
+            let mut chunk = [0u8; 8192];
+            let read = self
+                .io
+                .read(&mut chunk)
+                .await
+                .context("WebSocket read failed")?;
+            if read == 0 {
+                return Ok(None);
+            }
+            self.buffer.extend_from_slice(&chunk[..read]);
+        }
+    }
+
+    async fn send_frame(&mut self, opcode: u8, payload: &[u8]) -> Result<()> {
+        let mut mask = [0u8; 4];
+        mask.copy_from_slice(&Uuid::new_v4().as_bytes()[..4]);
+
+        self.io
+            .write_all(&encode_frame(opcode, payload, mask))
+            .await
+            .context("WebSocket write failed")?;
+        self.io.flush().await.context("WebSocket write failed")
+    }
+}
+
+/// The `Sec-WebSocket-Accept` value a server must answer `key` with
+fn accept_key(key: &str) -> String {
+    let hash = Sha1::digest(format!("{key}{ACCEPT_GUID}"));
+    base64::engine::general_purpose::STANDARD.encode(hash)
+}
+
+/// Encode a final frame. Frames sent by clients must be masked.
+fn encode_frame(opcode: u8, payload: &[u8], mask: [u8; 4]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 14);
+    frame.push(0x80 | opcode);
+
+    let len = payload.len();
+    if len < usize::from(LEN_U16) {
+        // Checked above, so this fits in the 7-bit length
+        #[allow(clippy::cast_possible_truncation)]
+        frame.push(0x80 | len as u8);
+    } else if let Ok(len) = u16::try_from(len) {
+        frame.push(0x80 | LEN_U16);
+        frame.extend_from_slice(&len.to_be_bytes());
+    } else {
+        frame.push(0x80 | LEN_U64);
+        frame.extend_from_slice(&(len as u64).to_be_bytes());
+    }
+
+    frame.extend_from_slice(&mask);
+    frame.extend(payload.iter().zip(mask.iter().cycle()).map(|(b, m)| b ^ m));
+    frame
+}
+
+/// Take the first complete frame out of `buffer`, if there is one
+fn decode_frame(buffer: &mut Vec<u8>) -> Result<Option<Frame>> {
+    let [first, second, ..] = buffer[..] else {
+        return Ok(None);
+    };
+    let fin = first & 0x80 != 0;
+    let opcode = first & 0x0F;
+    let masked = second & 0x80 != 0;
+
+    let (len, mut offset) = match second & 0x7F {
+        LEN_U16 => match buffer.get(2..4) {
+            Some(bytes) => (u64::from(u16::from_be_bytes([bytes[0], bytes[1]])), 4),
+            None => return Ok(None),
+        },
+        LEN_U64 => match buffer.get(2..10) {
+            Some(bytes) => (
+                u64::from_be_bytes(bytes.try_into().expect("slice is 8 bytes")),
+                10,
+            ),
+            None => return Ok(None),
+        },
+        len => (u64::from(len), 2),
+    };
+    if len > MAX_FRAME_LEN {
+        bail!("WebSocket frame too large ({len} bytes)");
+    }
+
+    let mask = if masked {
+        let Some(bytes) = buffer.get(offset..offset + 4) else {
+            return Ok(None);
+        };
+        offset += 4;
+        Some([bytes[0], bytes[1], bytes[2], bytes[3]])
+    } else {
+        None
+    };
+
+    // Bounded by MAX_FRAME_LEN
+    #[allow(clippy::cast_possible_truncation)]
+    let end = offset + len as usize;
+    if buffer.len() < end {
+        return Ok(None);
+    }
+
+    let mut payload: Vec<u8> = buffer.drain(..end).skip(offset).collect();
+    if let Some(mask) = mask {
+        for (byte, m) in payload.iter_mut().zip(mask.iter().cycle()) {
+            *byte ^= m;
+        }
+    }
+
+    Ok(Some(Frame {
+        fin,
+        opcode,
+        payload,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_unmasked_text_frame() {
+        let mut buffer = vec![0x81, 0x05];
+        assert_eq!(decode_frame(&mut buffer).unwrap(), None);
+
+        buffer.extend_from_slice(b"hello\x89");
+        let frame = decode_frame(&mut buffer).unwrap().unwrap();
+        assert_eq!(
+            frame,
+            Frame {
+                fin: true,
+                opcode: OPCODE_TEXT,
+                payload: b"hello".to_vec(),
+            }
+        );
+        // The start of the next frame stays buffered
+        assert_eq!(buffer, vec![0x89]);
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        for len in [0, 125, 126, 70_000] {
+            let payload = vec![b'x'; len];
+            let mut buffer = encode_frame(OPCODE_TEXT, &payload, [1, 2, 3, 4]);
+            assert_eq!(buffer[1] & 0x80, 0x80, "client frames are masked");
+
+            let frame = decode_frame(&mut buffer).unwrap().unwrap();
+            assert_eq!(frame.payload, payload);
+            assert!(buffer.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_accept_key() {
+        // RFC 6455 section 1.3
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn test_binary_continuations_are_dropped() {
+        let frame = |fin, opcode, payload: &[u8]| Frame {
+            fin,
+            opcode,
+            payload: payload.to_vec(),
+        };
+        let mut partial = PartialMessage::default();
+
+        assert_eq!(partial.push(frame(false, OPCODE_BINARY, b"\x00\x01")), None);
+        assert_eq!(
+            partial.push(frame(true, OPCODE_CONTINUATION, b"\x02")),
+            None
+        );
+
+        assert_eq!(partial.push(frame(false, OPCODE_TEXT, b"hel")), None);
+        assert_eq!(
+            partial.push(frame(true, OPCODE_CONTINUATION, b"lo")),
+            Some("hello".to_string())
+        );
+
+        // Nothing to continue
+        assert_eq!(partial.push(frame(true, OPCODE_CONTINUATION, b"x")), None);
+    }
+
+    #[test]
+    fn test_decode_rejects_huge_frame() {
+        let mut buffer = vec![0x81, LEN_U64];
+        buffer.extend_from_slice(&u64::MAX.to_be_bytes());
+        assert!(decode_frame(&mut buffer).is_err());
+    }
+}
@@ -3,30 +3,51 @@
 //! Uses channels to communicate between the sync TUI loop and async tasks.
 
 use anyhow::Result;
-use std::io::Write;
+use std::collections::HashMap;
 use tokio::sync::mpsc;
+use uuid::Uuid;
 
+use super::image_queue::{ImagePriority, ImageQueue, ImageRequest};
 use super::state::ReplyItem;
-use crate::api::get_client;
+use super::streaming::{StreamCommand, Streams};
+use crate::api::mastodon::rate_limit::RateLimited;
+use crate::api::{
+    MediaUpload, PostOptions, TimelineKind, TimelinePage, get_client, revoke_credentials,
+};
 use crate::auth;
-use crate::models::{Account, Network, Post};
-
-/// Log debug messages to `/tmp/perch_debug.log`
-fn log_debug(msg: &str) {
-    if let Ok(mut f) = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("/tmp/perch_debug.log")
-    {
-        let _ = writeln!(f, "[{}] {}", chrono::Local::now().format("%H:%M:%S"), msg);
-    }
-}
+use crate::filters::FilterRule;
+use crate::images::{CacheValidators, DiskCache};
+use crate::models::{Account, Moderation, Network, Notification, Post, Profile, split_thread};
+
+/// Number of notifications to fetch per account
+const NOTIFICATION_LIMIT: usize = 40;
+
+/// Number of posts to ask each server for when searching
+const SERVER_SEARCH_LIMIT: usize = 40;
+
+/// Number of posts per page in the profile view (Mastodon's maximum)
+const PROFILE_PAGE_SIZE: usize = 40;
 
 /// Commands sent from the TUI to the async worker
 #[derive(Debug, Clone)]
 pub enum AsyncCommand {
     /// Refresh timeline for given accounts
-    RefreshTimeline { accounts: Vec<Account> },
+    RefreshTimeline {
+        accounts: Vec<Account>,
+        kind: TimelineKind,
+        /// Automatic refresh, merged in without moving the selection
+        background: bool,
+    },
+    /// Fetch the next (older) page of the timeline for accounts with a cursor
+    LoadMore {
+        accounts: Vec<Account>,
+        cursors: HashMap<Uuid, String>,
+        kind: TimelineKind,
+    },
+    /// Refresh notifications for given accounts
+    RefreshNotifications { accounts: Vec<Account> },
+    /// Mark notifications as read on the server for given accounts
+    MarkNotificationsRead { accounts: Vec<Account> },
     /// Fetch replies/context for a post
     FetchContext { post: Post, account: Account },
     /// Like a post
@@ -37,33 +58,214 @@ pub enum AsyncCommand {
     Repost { post: Post, account: Account },
     /// Unrepost/unboost a post
     Unrepost { post: Post, account: Account },
+    /// Bookmark a post
+    Bookmark { post: Post, account: Account },
+    /// Remove a bookmark
+    Unbookmark { post: Post, account: Account },
+    /// Delete one of your own posts
+    Delete { post: Post, account: Account },
+    /// Replace the text of one of your own posts
+    Edit {
+        post: Post,
+        content: String,
+        account: Account,
+    },
+    /// Fetch recent posts with a hashtag for each account's network
+    HashtagTimeline { tag: String, accounts: Vec<Account> },
+    /// Fetch a profile and its first page of posts
+    FetchProfile { handle: String, account: Account },
+    /// Fetch an older page of a profile's posts
+    FetchProfilePosts {
+        /// Handle the profile view was opened for
+        handle: String,
+        profile: Box<Profile>,
+        account: Account,
+        cursor: String,
+    },
+    /// Follow (or with `follow` false, unfollow) a profile's account
+    Follow {
+        profile: Box<Profile>,
+        account: Account,
+        follow: bool,
+    },
+    /// Fetch the filters set up on each (Mastodon) account
+    FetchFilters { accounts: Vec<Account> },
+    /// Mute or block an account by handle (or with `undo`, lift it)
+    Moderate {
+        handle: String,
+        account: Account,
+        moderation: Moderation,
+        undo: bool,
+    },
+    /// Search posts on the server of each account
+    Search {
+        query: String,
+        accounts: Vec<Account>,
+    },
+    /// Check that an account's stored credentials still work
+    VerifyAccount { account: Account },
+    /// Sign an account out on its server, before it's removed here
+    RevokeAccount { account: Account },
+    /// Ask an account's server how long posts may be
+    FetchPostLimit { account: Account },
     /// Post to networks
     Post {
         content: String,
         accounts: Vec<Account>,
         reply_to: Option<Post>,
+        quote: Option<Box<Post>>,
+        media: Vec<MediaUpload>,
+        options: PostOptions,
     },
     /// Schedule a post for later
     SchedulePost {
         content: String,
+        /// Networks Perch posts to when the time comes
         networks: Vec<Network>,
         scheduled_for: chrono::DateTime<chrono::Utc>,
+        /// Mastodon account whose server publishes the post itself
+        server_account: Option<Account>,
+        options: PostOptions,
     },
-    /// Load an image from a URL
-    LoadImage { url: String },
+    /// Queue an image to load from a URL
+    LoadImage {
+        url: String,
+        /// Decode every frame if it's an animated GIF
+        animate: bool,
+        priority: ImagePriority,
+    },
+    /// Drop queued images that aren't among these URLs
+    RetainImages { urls: Vec<String> },
+    /// Start or stop streaming new posts
+    Stream(StreamCommand),
     /// Shutdown the worker
     Shutdown,
 }
 
+impl AsyncCommand {
+    /// What the status bar shows while this runs, for the commands that
+    /// keep the user waiting. Quick actions (likes, boosts) and work in the
+    /// background (automatic refreshes, streams) aren't shown.
+    pub const fn operation(&self) -> Option<Operation> {
+        match self {
+            Self::RefreshTimeline {
+                background: false, ..
+            }
+            | Self::LoadMore { .. }
+            | Self::HashtagTimeline { .. } => Some(Operation::Timeline),
+            Self::RefreshNotifications { .. } => Some(Operation::Notifications),
+            Self::FetchContext { .. } => Some(Operation::Replies),
+            Self::FetchProfile { .. } | Self::FetchProfilePosts { .. } => Some(Operation::Profile),
+            Self::Search { .. } => Some(Operation::Search),
+            Self::Post { .. } | Self::SchedulePost { .. } | Self::Edit { .. } => {
+                Some(Operation::Posting)
+            }
+            Self::VerifyAccount { .. } => Some(Operation::Verifying),
+            Self::RevokeAccount { .. } => Some(Operation::SigningOut),
+            _ => None,
+        }
+    }
+}
+
+/// Work in flight that the status bar spinner stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// Fetching a timeline or a page of it
+    Timeline,
+    /// Fetching notifications
+    Notifications,
+    /// Fetching a post's thread
+    Replies,
+    /// Fetching a profile or its posts
+    Profile,
+    /// Searching the servers
+    Search,
+    /// Sending, scheduling or editing a post
+    Posting,
+    /// Checking an account's credentials
+    Verifying,
+    /// Revoking an account's token before removing it
+    SigningOut,
+    /// Installing an update
+    Update,
+}
+
+impl Operation {
+    /// Short description for the status bar
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Timeline => "loading posts",
+            Self::Notifications => "loading notifications",
+            Self::Replies => "loading replies",
+            Self::Profile => "loading profile",
+            Self::Search => "searching",
+            Self::Posting => "posting",
+            Self::Verifying => "checking account",
+            Self::SigningOut => "signing out",
+            Self::Update => "updating",
+        }
+    }
+}
+
+/// A like or repost, or undoing one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostAction {
+    /// Like a post
+    Like,
+    /// Remove a like
+    Unlike,
+    /// Repost/boost a post
+    Repost,
+    /// Undo a repost
+    Unrepost,
+}
+
+impl PostAction {
+    /// The action that undoes this one
+    pub const fn inverse(self) -> Self {
+        match self {
+            Self::Like => Self::Unlike,
+            Self::Unlike => Self::Like,
+            Self::Repost => Self::Unrepost,
+            Self::Unrepost => Self::Repost,
+        }
+    }
+
+    /// Name for error messages
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Like => "Like",
+            Self::Unlike => "Unlike",
+            Self::Repost => "Repost",
+            Self::Unrepost => "Unrepost",
+        }
+    }
+}
+
 /// Results sent back from the async worker to the TUI
 #[derive(Debug)]
 pub enum AsyncResult {
     /// Timeline refreshed with new posts
-    TimelineRefreshed { posts: Vec<Post> },
+    TimelineRefreshed {
+        posts: Vec<Post>,
+        cursors: HashMap<Uuid, String>,
+        kind: TimelineKind,
+        background: bool,
+    },
+    /// Older posts fetched, to be appended to the timeline
+    MoreLoaded {
+        posts: Vec<Post>,
+        cursors: HashMap<Uuid, String>,
+        kind: TimelineKind,
+    },
+    /// No account's server could be reached
+    Offline { background: bool },
+    /// Notifications refreshed (newest first)
+    NotificationsRefreshed { notifications: Vec<Notification> },
     /// Context/replies fetched for a post
-    #[allow(dead_code)]
     ContextFetched {
         post_id: String,
+        ancestors: Vec<Post>,
         replies: Vec<ReplyItem>,
     },
     /// Post was liked
@@ -75,24 +277,101 @@ pub enum AsyncResult {
     /// Post was reposted
     #[allow(dead_code)]
     Reposted { post_id: String },
-    /// Post was unreposted
+    /// Post was bookmarked
+    Bookmarked { post_id: String },
+    /// Bookmark was removed
+    Unbookmarked { post_id: String },
+    /// Post was deleted
+    Deleted { network: Network, post_id: String },
+    /// Post was edited
+    Edited { post: Box<Post> },
+    /// Post was unreposted (`removed` is false if no repost existed)
     #[allow(dead_code)]
-    Unreposted { post_id: String },
+    Unreposted { post_id: String, removed: bool },
+    /// Hashtag feed fetched
+    HashtagLoaded { tag: String, posts: Vec<Post> },
+    /// Profile fetched, with its first page of posts
+    ProfileLoaded {
+        /// Handle the profile view was opened for
+        handle: String,
+        profile: Box<Profile>,
+        page: TimelinePage,
+    },
+    /// Older posts fetched for the profile view
+    ProfilePostsLoaded { handle: String, page: TimelinePage },
+    /// A like or repost (or undoing one) failed, so what was shown when the
+    /// key was pressed needs rolling back
+    ActionFailed {
+        network: Network,
+        post_id: String,
+        action: PostAction,
+        message: String,
+    },
+    /// Fetching a profile or its posts failed
+    ProfileFailed { handle: String, message: String },
+    /// Followed or unfollowed an account (with its new follow state)
+    FollowChanged { profile: Box<Profile> },
+    /// Filters set up on the server, from every account that has some
+    FiltersLoaded { rules: Vec<FilterRule> },
+    /// The server muted or blocked an account (or lifted it)
+    Moderated {
+        handle: String,
+        moderation: Moderation,
+        undo: bool,
+    },
+    /// Server search finished (`notices` explains fallbacks and failures)
+    SearchFinished {
+        query: String,
+        posts: Vec<Post>,
+        notices: Vec<String>,
+    },
+    /// Account credentials were accepted (with the profile they belong to)
+    AccountVerified { account_id: Uuid, profile: Account },
+    /// An account being removed was signed out on its server (or revoking
+    /// its token failed, with why)
+    AccountRevoked {
+        account: Account,
+        revoked: Result<bool, String>,
+    },
+    /// The server reported its post length limit
+    PostLimitFetched { account_id: Uuid, max_chars: usize },
     /// New post created
     Posted { posts: Vec<Post> },
     /// Post was scheduled
-    Scheduled { id: String, scheduled_for: String },
+    Scheduled {
+        /// Short ID of the locally scheduled post
+        id: Option<String>,
+        /// ID Mastodon scheduled the post under
+        server_id: Option<String>,
+        scheduled_for: chrono::DateTime<chrono::Utc>,
+    },
     /// Image loaded successfully
     ImageLoaded {
         url: String,
         image: image::DynamicImage,
+        /// All the frames, if it's an animated GIF
+        animation: Option<crate::images::Animation>,
     },
     /// Image loading failed
     ImageFailed { url: String, error: String },
+    /// An image was dropped from the queue before it loaded
+    ImageCancelled { url: String },
+    /// New home timeline posts arrived on a stream
+    StreamedPosts { posts: Vec<Post> },
+    /// An account's stream couldn't start, or dropped and is reconnecting
+    /// after `retry` (never, for `None`)
+    StreamFailed {
+        handle: String,
+        error: String,
+        retry: Option<std::time::Duration>,
+    },
     /// An error occurred
     Error { message: String },
     /// Status message (for progress updates)
     Status { message: String },
+    /// The worker is done with a command that had an [`Operation`],
+    /// whatever came of it
+    Finished { operation: Operation },
 }
 
 /// Channel handles for communicating with the async worker
@@ -104,50 +383,165 @@ pub struct AsyncHandle {
 }
 
 /// Spawn the async worker and return handles
-pub fn spawn_worker() -> AsyncHandle {
+///
+/// `image_cache` persists downloaded images across runs when available.
+pub fn spawn_worker(image_cache: Option<DiskCache>) -> AsyncHandle {
     let (cmd_tx, mut cmd_rx) = mpsc::channel::<AsyncCommand>(32);
     let (result_tx, result_rx) = mpsc::channel::<AsyncResult>(32);
 
     // Spawn the worker task
     tokio::spawn(async move {
+        let mut streams = Streams::default();
+        let images = ImageQueue::new(result_tx.clone(), image_cache);
+
         while let Some(cmd) = cmd_rx.recv().await {
+            let operation = cmd.operation();
             match cmd {
                 AsyncCommand::Shutdown => break,
-                AsyncCommand::RefreshTimeline { accounts } => {
-                    handle_refresh(&result_tx, accounts).await;
+                AsyncCommand::Stream(command) => streams.handle(command, &result_tx),
+                AsyncCommand::RefreshTimeline {
+                    accounts,
+                    kind,
+                    background,
+                } => {
+                    handle_refresh(&result_tx, accounts, kind, background).await;
+                }
+                AsyncCommand::LoadMore {
+                    accounts,
+                    cursors,
+                    kind,
+                } => {
+                    handle_load_more(&result_tx, accounts, cursors, kind).await;
+                }
+                AsyncCommand::RefreshNotifications { accounts } => {
+                    handle_refresh_notifications(&result_tx, accounts).await;
+                }
+                AsyncCommand::MarkNotificationsRead { accounts } => {
+                    handle_mark_notifications_read(accounts).await;
                 }
                 AsyncCommand::FetchContext { post, account } => {
                     handle_fetch_context(&result_tx, post, account).await;
                 }
                 AsyncCommand::Like { post, account } => {
-                    handle_like(&result_tx, post, account).await;
+                    handle_post_action(&result_tx, post, account, PostAction::Like).await;
                 }
                 AsyncCommand::Unlike { post, account } => {
-                    handle_unlike(&result_tx, post, account).await;
+                    handle_post_action(&result_tx, post, account, PostAction::Unlike).await;
                 }
                 AsyncCommand::Repost { post, account } => {
-                    handle_repost(&result_tx, post, account).await;
+                    handle_post_action(&result_tx, post, account, PostAction::Repost).await;
                 }
                 AsyncCommand::Unrepost { post, account } => {
-                    handle_unrepost(&result_tx, post, account).await;
+                    handle_post_action(&result_tx, post, account, PostAction::Unrepost).await;
+                }
+                AsyncCommand::Bookmark { post, account } => {
+                    handle_bookmark(&result_tx, post, account, true).await;
+                }
+                AsyncCommand::Unbookmark { post, account } => {
+                    handle_bookmark(&result_tx, post, account, false).await;
+                }
+                AsyncCommand::Delete { post, account } => {
+                    handle_delete(&result_tx, post, account).await;
+                }
+                AsyncCommand::Edit {
+                    post,
+                    content,
+                    account,
+                } => {
+                    handle_edit(&result_tx, post, content, account).await;
+                }
+                AsyncCommand::HashtagTimeline { tag, accounts } => {
+                    handle_hashtag_timeline(&result_tx, tag, accounts).await;
+                }
+                AsyncCommand::FetchProfile { handle, account } => {
+                    handle_fetch_profile(&result_tx, handle, account).await;
+                }
+                AsyncCommand::FetchProfilePosts {
+                    handle,
+                    profile,
+                    account,
+                    cursor,
+                } => {
+                    handle_fetch_profile_posts(&result_tx, handle, &profile, account, cursor).await;
+                }
+                AsyncCommand::Follow {
+                    profile,
+                    account,
+                    follow,
+                } => {
+                    handle_follow(&result_tx, &profile, account, follow).await;
+                }
+                AsyncCommand::FetchFilters { accounts } => {
+                    handle_fetch_filters(&result_tx, accounts).await;
+                }
+                AsyncCommand::Moderate {
+                    handle,
+                    account,
+                    moderation,
+                    undo,
+                } => {
+                    handle_moderate(&result_tx, handle, account, moderation, undo).await;
+                }
+                AsyncCommand::Search { query, accounts } => {
+                    handle_search(&result_tx, query, accounts).await;
+                }
+                AsyncCommand::VerifyAccount { account } => {
+                    handle_verify_account(&result_tx, account).await;
+                }
+                AsyncCommand::RevokeAccount { account } => {
+                    let revoked = revoke_credentials(&account)
+                        .await
+                        .map_err(|e| e.to_string());
+                    let _ = result_tx
+                        .send(AsyncResult::AccountRevoked { account, revoked })
+                        .await;
+                }
+                AsyncCommand::FetchPostLimit { account } => {
+                    handle_fetch_post_limit(&result_tx, account).await;
                 }
                 AsyncCommand::Post {
                     content,
                     accounts,
                     reply_to,
+                    quote,
+                    media,
+                    options,
                 } => {
-                    handle_post(&result_tx, content, accounts, reply_to).await;
+                    handle_post(
+                        &result_tx, content, accounts, reply_to, quote, media, options,
+                    )
+                    .await;
                 }
                 AsyncCommand::SchedulePost {
                     content,
                     networks,
                     scheduled_for,
+                    server_account,
+                    options,
                 } => {
-                    handle_schedule_post(&result_tx, content, networks, scheduled_for).await;
-                }
-                AsyncCommand::LoadImage { url } => {
-                    handle_load_image(&result_tx, url).await;
+                    handle_schedule_post(
+                        &result_tx,
+                        content,
+                        networks,
+                        scheduled_for,
+                        server_account,
+                        options,
+                    )
+                    .await;
                 }
+                AsyncCommand::LoadImage {
+                    url,
+                    animate,
+                    priority,
+                } => images.load(ImageRequest {
+                    url,
+                    animate,
+                    priority,
+                }),
+                AsyncCommand::RetainImages { urls } => images.retain(urls),
+            }
+            if let Some(operation) = operation {
+                let _ = result_tx.send(AsyncResult::Finished { operation }).await;
             }
         }
     });
@@ -155,24 +549,440 @@ pub fn spawn_worker() -> AsyncHandle {
     AsyncHandle { cmd_tx, result_rx }
 }
 
-async fn handle_refresh(result_tx: &mpsc::Sender<AsyncResult>, accounts: Vec<Account>) {
+async fn handle_refresh(
+    result_tx: &mpsc::Sender<AsyncResult>,
+    accounts: Vec<Account>,
+    kind: TimelineKind,
+    background: bool,
+) {
+    if !background {
+        let _ = result_tx
+            .send(AsyncResult::Status {
+                message: "Refreshing...".to_string(),
+            })
+            .await;
+    }
+
+    if accounts.is_empty() {
+        let _ = result_tx
+            .send(AsyncResult::Error {
+                message: "No accounts configured".to_string(),
+            })
+            .await;
+        return;
+    }
+
+    let TimelinesFetched {
+        posts: all_posts,
+        cursors,
+        errors,
+        offline,
+    } = fetch_timelines(&accounts, &HashMap::new(), kind).await;
+
+    if offline {
+        let _ = result_tx.send(AsyncResult::Offline { background }).await;
+    } else if all_posts.is_empty() && !errors.is_empty() {
+        let result = if background {
+            AsyncResult::Status {
+                message: format!("⚠ Auto-refresh failed: {}", errors.join("; ")),
+            }
+        } else {
+            AsyncResult::Error {
+                message: errors.join("; "),
+            }
+        };
+        let _ = result_tx.send(result).await;
+    } else {
+        let _ = result_tx
+            .send(AsyncResult::TimelineRefreshed {
+                posts: all_posts,
+                cursors,
+                kind,
+                background,
+            })
+            .await;
+
+        if !errors.is_empty() {
+            let _ = result_tx
+                .send(AsyncResult::Status {
+                    message: format!("Partial refresh: {}", errors.join("; ")),
+                })
+                .await;
+        }
+    }
+}
+
+async fn handle_load_more(
+    result_tx: &mpsc::Sender<AsyncResult>,
+    accounts: Vec<Account>,
+    cursors: HashMap<Uuid, String>,
+    kind: TimelineKind,
+) {
     let _ = result_tx
         .send(AsyncResult::Status {
-            message: "Refreshing...".to_string(),
+            message: "Loading older posts...".to_string(),
         })
         .await;
 
-    if accounts.is_empty() {
+    let TimelinesFetched {
+        posts,
+        cursors,
+        errors,
+        offline,
+    } = fetch_timelines(&accounts, &cursors, kind).await;
+
+    let _ = result_tx
+        .send(AsyncResult::MoreLoaded {
+            posts,
+            cursors,
+            kind,
+        })
+        .await;
+
+    if offline {
+        let _ = result_tx
+            .send(AsyncResult::Offline { background: false })
+            .await;
+    } else if !errors.is_empty() {
+        let _ = result_tx
+            .send(AsyncResult::Status {
+                message: format!("Failed to load older posts: {}", errors.join("; ")),
+            })
+            .await;
+    }
+}
+
+/// One timeline page from each account, merged
+struct TimelinesFetched {
+    /// Posts from every account, newest first
+    posts: Vec<Post>,
+    /// Next cursor per account; accounts without one have reached the end
+    cursors: HashMap<Uuid, String>,
+    /// Per-account errors
+    errors: Vec<String>,
+    /// None of the accounts' servers could be reached
+    offline: bool,
+}
+
+/// Fetch one timeline page per account, starting from each account's cursor
+async fn fetch_timelines(
+    accounts: &[Account],
+    cursors: &HashMap<Uuid, String>,
+    kind: TimelineKind,
+) -> TimelinesFetched {
+    let mut all_posts = Vec::new();
+    let mut next_cursors = HashMap::new();
+    let mut errors = Vec::new();
+    let mut unreachable = 0;
+
+    for account in accounts {
+        let token = match auth::get_credentials(account) {
+            Ok(Some(t)) => t,
+            Ok(None) => {
+                errors.push(format!("No credentials for @{}", account.handle));
+                continue;
+            }
+            Err(e) => {
+                errors.push(format!("Auth error for @{}: {}", account.handle, e));
+                continue;
+            }
+        };
+
+        let cursor = cursors.get(&account.id).cloned();
+        match fetch_timeline(account, &token, kind, cursor).await {
+            Ok(mut page) => {
+                Post::set_owner(&mut page.posts, account);
+                all_posts.extend(page.posts);
+                if let Some(cursor) = page.next_cursor {
+                    next_cursors.insert(account.id, cursor);
+                }
+            }
+            Err(e) => {
+                if is_connection_error(&e) {
+                    unreachable += 1;
+                }
+                errors.push(format!("@{}: {}", account.handle, error_message(&e)));
+                // Keep the old cursor so the page can be retried
+                if let Some(cursor) = cursors.get(&account.id) {
+                    next_cursors.insert(account.id, cursor.clone());
+                }
+            }
+        }
+    }
+
+    // Sort by timestamp (newest first)
+    all_posts.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+    // Two accounts may both see the same post, and several people boost it
+    Post::dedup(&mut all_posts);
+
+    TimelinesFetched {
+        posts: all_posts,
+        cursors: next_cursors,
+        errors,
+        offline: unreachable > 0 && unreachable == accounts.len(),
+    }
+}
+
+async fn fetch_timeline(
+    account: &Account,
+    token: &str,
+    kind: TimelineKind,
+    cursor: Option<String>,
+) -> Result<TimelinePage> {
+    let client = get_client(account, token).await?;
+    client.timeline_kind(kind, 50, cursor).await
+}
+
+async fn handle_hashtag_timeline(
+    result_tx: &mpsc::Sender<AsyncResult>,
+    tag: String,
+    accounts: Vec<Account>,
+) {
+    let mut posts = Vec::new();
+    let mut errors = Vec::new();
+
+    for account in &accounts {
+        let result = match auth::get_credentials(account) {
+            Ok(Some(token)) => match get_client(account, &token).await {
+                Ok(client) => client.hashtag_timeline(&tag, 50).await,
+                Err(e) => Err(e),
+            },
+            Ok(None) => Err(anyhow::anyhow!("No credentials")),
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(mut found) => {
+                Post::set_owner(&mut found, account);
+                posts.extend(found);
+            }
+            Err(e) => errors.push(format!("@{}: {e}", account.handle)),
+        }
+    }
+
+    if posts.is_empty() && !errors.is_empty() {
         let _ = result_tx
             .send(AsyncResult::Error {
-                message: "No accounts configured".to_string(),
+                message: errors.join("; "),
             })
             .await;
         return;
     }
 
-    let mut all_posts = Vec::new();
+    posts.sort_by_key(|p| std::cmp::Reverse(p.created_at));
+    let _ = result_tx
+        .send(AsyncResult::HashtagLoaded { tag, posts })
+        .await;
+
+    if !errors.is_empty() {
+        let _ = result_tx
+            .send(AsyncResult::Status {
+                message: format!("Partial refresh: {}", errors.join("; ")),
+            })
+            .await;
+    }
+}
+
+async fn handle_fetch_profile(
+    result_tx: &mpsc::Sender<AsyncResult>,
+    handle: String,
+    account: Account,
+) {
+    let result = match auth::get_credentials(&account) {
+        Ok(Some(token)) => fetch_profile(&account, &token, &handle).await,
+        Ok(None) => Err(anyhow::anyhow!("No credentials")),
+        Err(e) => Err(e),
+    };
+
+    let message = match result {
+        Ok((profile, mut page)) => {
+            Post::set_owner(&mut page.posts, &account);
+            AsyncResult::ProfileLoaded {
+                handle,
+                profile: Box::new(profile),
+                page,
+            }
+        }
+        Err(e) => AsyncResult::ProfileFailed {
+            message: format!("Couldn't load @{handle}: {e}"),
+            handle,
+        },
+    };
+    let _ = result_tx.send(message).await;
+}
+
+/// Look up a profile and its first page of posts
+async fn fetch_profile(
+    account: &Account,
+    token: &str,
+    handle: &str,
+) -> Result<(Profile, TimelinePage)> {
+    let client = get_client(account, token).await?;
+    let profile = client.profile(handle).await?;
+    let page = client
+        .author_feed(&profile, PROFILE_PAGE_SIZE, None)
+        .await?;
+    Ok((profile, page))
+}
+
+async fn handle_fetch_profile_posts(
+    result_tx: &mpsc::Sender<AsyncResult>,
+    handle: String,
+    profile: &Profile,
+    account: Account,
+    cursor: String,
+) {
+    let result = match auth::get_credentials(&account) {
+        Ok(Some(token)) => match get_client(&account, &token).await {
+            Ok(client) => {
+                client
+                    .author_feed(profile, PROFILE_PAGE_SIZE, Some(cursor))
+                    .await
+            }
+            Err(e) => Err(e),
+        },
+        Ok(None) => Err(anyhow::anyhow!("No credentials")),
+        Err(e) => Err(e),
+    };
+
+    let message = match result {
+        Ok(mut page) => {
+            Post::set_owner(&mut page.posts, &account);
+            AsyncResult::ProfilePostsLoaded { handle, page }
+        }
+        Err(e) => AsyncResult::ProfileFailed {
+            message: format!("Couldn't load older posts: {e}"),
+            handle,
+        },
+    };
+    let _ = result_tx.send(message).await;
+}
+
+async fn handle_follow(
+    result_tx: &mpsc::Sender<AsyncResult>,
+    profile: &Profile,
+    account: Account,
+    follow: bool,
+) {
+    let Some(client) = client_or_report(result_tx, &account).await else {
+        return;
+    };
+
+    let result = if follow {
+        client.follow(profile).await
+    } else {
+        client.unfollow(profile).await
+    };
+    let message = match result {
+        Ok(profile) => AsyncResult::FollowChanged {
+            profile: Box::new(profile),
+        },
+        Err(e) => AsyncResult::Error {
+            message: e.to_string(),
+        },
+    };
+    let _ = result_tx.send(message).await;
+}
+
+async fn handle_fetch_filters(result_tx: &mpsc::Sender<AsyncResult>, accounts: Vec<Account>) {
+    let mut rules = Vec::new();
+    for account in accounts {
+        let Some(client) = client_or_report(result_tx, &account).await else {
+            continue;
+        };
+        match client.server_filters().await {
+            Ok(found) => {
+                for rule in found {
+                    if !rules.contains(&rule) {
+                        rules.push(rule);
+                    }
+                }
+            }
+            // Filters are a nice-to-have; the timeline works without them
+            Err(e) => tracing::warn!("Failed to fetch filters for @{}: {e}", account.handle),
+        }
+    }
+    let _ = result_tx.send(AsyncResult::FiltersLoaded { rules }).await;
+}
+
+async fn handle_moderate(
+    result_tx: &mpsc::Sender<AsyncResult>,
+    handle: String,
+    account: Account,
+    moderation: Moderation,
+    undo: bool,
+) {
+    let Some(client) = client_or_report(result_tx, &account).await else {
+        return;
+    };
+
+    // Resolve the handle to an account ID or DID first
+    let result = match client.profile(&handle).await {
+        Ok(profile) => client.moderate(&profile, moderation, undo).await,
+        Err(e) => Err(e),
+    };
+    let message = match result {
+        Ok(()) => AsyncResult::Moderated {
+            handle,
+            moderation,
+            undo,
+        },
+        Err(e) => AsyncResult::Error {
+            message: format!("Failed to {} @{handle}: {e}", moderation.verb(undo)),
+        },
+    };
+    let _ = result_tx.send(message).await;
+}
+
+async fn handle_search(
+    result_tx: &mpsc::Sender<AsyncResult>,
+    query: String,
+    accounts: Vec<Account>,
+) {
+    let mut posts = Vec::new();
+    let mut notices = Vec::new();
+
+    for account in &accounts {
+        let result = match auth::get_credentials(account) {
+            Ok(Some(token)) => match get_client(account, &token).await {
+                Ok(client) => client.search(&query, SERVER_SEARCH_LIMIT).await,
+                Err(e) => Err(e),
+            },
+            Ok(None) => Err(anyhow::anyhow!("No credentials")),
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(mut page) => {
+                Post::set_owner(&mut page.posts, account);
+                posts.extend(page.posts);
+                if let Some(notice) = page.notice {
+                    notices.push(format!("{} {notice}", account.network.emoji()));
+                }
+            }
+            Err(e) => notices.push(format!("{} {e}", account.network.emoji())),
+        }
+    }
+
+    posts.sort_by_key(|p| std::cmp::Reverse(p.created_at));
+
+    let _ = result_tx
+        .send(AsyncResult::SearchFinished {
+            query,
+            posts,
+            notices,
+        })
+        .await;
+}
+
+async fn handle_refresh_notifications(
+    result_tx: &mpsc::Sender<AsyncResult>,
+    accounts: Vec<Account>,
+) {
+    let mut notifications = Vec::new();
     let mut errors = Vec::new();
+    let mut unreachable = 0;
 
     for account in &accounts {
         let token = match auth::get_credentials(account) {
@@ -187,50 +997,70 @@ async fn handle_refresh(result_tx: &mpsc::Sender<AsyncResult>, accounts: Vec<Acc
             }
         };
 
-        match fetch_timeline(account, &token).await {
-            Ok(posts) => {
-                all_posts.extend(posts);
-            }
+        let result = match get_client(account, &token).await {
+            Ok(client) => client.notifications(NOTIFICATION_LIMIT).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(items) => notifications.extend(items),
             Err(e) => {
+                if is_connection_error(&e) {
+                    unreachable += 1;
+                }
                 errors.push(format!("@{}: {}", account.handle, e));
             }
         }
     }
 
+    // Keep the notifications already shown rather than emptying the list
+    if unreachable > 0 && unreachable == accounts.len() {
+        let _ = result_tx
+            .send(AsyncResult::Offline { background: true })
+            .await;
+        return;
+    }
+
     // Sort by timestamp (newest first)
-    all_posts.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    notifications.sort_by_key(|n| std::cmp::Reverse(n.created_at));
+
+    let _ = result_tx
+        .send(AsyncResult::NotificationsRefreshed { notifications })
+        .await;
 
-    if all_posts.is_empty() && !errors.is_empty() {
+    if !errors.is_empty() {
         let _ = result_tx
-            .send(AsyncResult::Error {
-                message: errors.join("; "),
+            .send(AsyncResult::Status {
+                message: format!("Notifications: {}", errors.join("; ")),
             })
             .await;
-    } else {
-        let _ = result_tx
-            .send(AsyncResult::TimelineRefreshed { posts: all_posts })
-            .await;
-
-        if !errors.is_empty() {
-            let _ = result_tx
-                .send(AsyncResult::Status {
-                    message: format!("Partial refresh: {}", errors.join("; ")),
-                })
-                .await;
-        }
     }
 }
 
-async fn fetch_timeline(account: &Account, token: &str) -> Result<Vec<Post>> {
-    let client = get_client(account, token).await?;
-    client.timeline(50).await
+async fn handle_mark_notifications_read(accounts: Vec<Account>) {
+    for account in &accounts {
+        let Ok(Some(token)) = auth::get_credentials(account) else {
+            continue;
+        };
+        let result = match get_client(account, &token).await {
+            Ok(client) => client.mark_notifications_read().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::debug!(
+                "Failed to mark notifications read for @{}: {}",
+                account.handle,
+                e
+            );
+        }
+    }
 }
 
+#[tracing::instrument(skip_all, fields(post = %post.network_id))]
 async fn handle_fetch_context(result_tx: &mpsc::Sender<AsyncResult>, post: Post, account: Account) {
     let token = match auth::get_credentials(&account) {
         Ok(Some(t)) => t,
         _ => {
-            log_debug("No token for account");
+            tracing::debug!("No token for account");
             return;
         }
     };
@@ -238,38 +1068,35 @@ async fn handle_fetch_context(result_tx: &mpsc::Sender<AsyncResult>, post: Post,
     let client = match get_client(&account, &token).await {
         Ok(c) => c,
         Err(e) => {
-            log_debug(&format!("Failed to get client: {}", e));
+            tracing::debug!("Failed to get client: {}", e);
             return;
         }
     };
 
     match client.get_context(&post).await {
-        Ok(flat_replies) => {
-            log_debug(&format!(
-                "Got {} flat replies for {}",
-                flat_replies.len(),
-                post.network_id
-            ));
+        Ok(mut context) => {
+            Post::set_owner(&mut context.ancestors, &account);
+            Post::set_owner(&mut context.descendants, &account);
+            let flat_replies = context.descendants;
+            tracing::debug!("Got {} flat replies", flat_replies.len());
             if !flat_replies.is_empty() {
-                log_debug(&format!(
-                    "  First reply reply_to_id: {:?}",
-                    flat_replies[0].reply_to_id
-                ));
-                log_debug(&format!("  Post uri: {:?}", post.uri));
+                tracing::trace!("First reply reply_to_id: {:?}", flat_replies[0].reply_to_id);
+                tracing::trace!("Post uri: {:?}", post.uri);
             }
             // Build threaded reply list with depth
             let reply_items = build_reply_tree(&post, &flat_replies);
-            log_debug(&format!("  Built {} reply items", reply_items.len()));
+            tracing::debug!("Built {} reply items", reply_items.len());
 
             let _ = result_tx
                 .send(AsyncResult::ContextFetched {
                     post_id: post.network_id,
+                    ancestors: context.ancestors,
                     replies: reply_items,
                 })
                 .await;
         }
         Err(e) => {
-            log_debug(&format!("Failed to fetch context: {}", e));
+            tracing::debug!("Failed to fetch context: {}", e);
         }
     }
 }
@@ -310,163 +1137,191 @@ fn build_reply_tree(root_post: &Post, replies: &[Post]) -> Vec<ReplyItem> {
     result
 }
 
-async fn handle_like(result_tx: &mpsc::Sender<AsyncResult>, post: Post, account: Account) {
-    let token = match auth::get_credentials(&account) {
-        Ok(Some(t)) => t,
-        Ok(None) => {
-            let _ = result_tx
-                .send(AsyncResult::Error {
-                    message: "No credentials".to_string(),
-                })
-                .await;
-            return;
-        }
-        Err(e) => {
-            let _ = result_tx
-                .send(AsyncResult::Error {
-                    message: e.to_string(),
-                })
-                .await;
-            return;
-        }
-    };
+/// Describe a failed request, saying when to retry if it hit a rate limit
+/// (which the context added on the way up would otherwise hide)
+fn error_message(e: &anyhow::Error) -> String {
+    e.downcast_ref::<RateLimited>()
+        .map_or_else(|| e.to_string(), ToString::to_string)
+}
 
-    let client = match get_client(&account, &token).await {
-        Ok(c) => c,
-        Err(e) => {
-            let _ = result_tx
-                .send(AsyncResult::Error {
-                    message: e.to_string(),
-                })
-                .await;
-            return;
-        }
+/// Whether a request failed because the server couldn't be reached at all,
+/// rather than answering with an error
+fn is_connection_error(e: &anyhow::Error) -> bool {
+    e.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_connect() || e.is_timeout())
+    })
+}
+
+/// Get an API client for an account, reporting failures to the TUI
+async fn client_or_report(
+    result_tx: &mpsc::Sender<AsyncResult>,
+    account: &Account,
+) -> Option<crate::api::Client> {
+    let result = match auth::get_credentials(account) {
+        Ok(Some(token)) => get_client(account, &token).await,
+        Ok(None) => Err(anyhow::anyhow!("No credentials")),
+        Err(e) => Err(e),
     };
 
-    match client.like(&post).await {
-        Ok(()) => {
-            let _ = result_tx
-                .send(AsyncResult::Liked {
-                    post_id: post.network_id,
-                })
-                .await;
-        }
+    match result {
+        Ok(client) => Some(client),
         Err(e) => {
             let _ = result_tx
                 .send(AsyncResult::Error {
-                    message: format!("Like failed: {e}"),
+                    message: e.to_string(),
                 })
                 .await;
+            None
         }
     }
 }
 
-async fn handle_unlike(result_tx: &mpsc::Sender<AsyncResult>, post: Post, account: Account) {
-    let token = match auth::get_credentials(&account) {
-        Ok(Some(t)) => t,
-        _ => return,
+async fn handle_bookmark(
+    result_tx: &mpsc::Sender<AsyncResult>,
+    post: Post,
+    account: Account,
+    bookmark: bool,
+) {
+    let Some(client) = client_or_report(result_tx, &account).await else {
+        return;
     };
 
-    let client = match get_client(&account, &token).await {
-        Ok(c) => c,
-        Err(e) => {
-            let _ = result_tx
-                .send(AsyncResult::Error {
-                    message: e.to_string(),
-                })
-                .await;
-            return;
-        }
+    let result = if bookmark {
+        client.bookmark(&post).await
+    } else {
+        client.unbookmark(&post).await
     };
 
-    match client.unlike(&post).await {
-        Ok(()) => {
-            let _ = result_tx
-                .send(AsyncResult::Unliked {
-                    post_id: post.network_id,
-                })
-                .await;
-        }
-        Err(e) => {
-            let _ = result_tx
-                .send(AsyncResult::Error {
-                    message: format!("Unlike failed: {e}"),
-                })
-                .await;
-        }
-    }
+    let message = match result {
+        Ok(()) if bookmark => AsyncResult::Bookmarked {
+            post_id: post.network_id,
+        },
+        Ok(()) => AsyncResult::Unbookmarked {
+            post_id: post.network_id,
+        },
+        Err(e) => AsyncResult::Error {
+            message: format!("Bookmark failed: {e}"),
+        },
+    };
+    let _ = result_tx.send(message).await;
 }
 
-async fn handle_repost(result_tx: &mpsc::Sender<AsyncResult>, post: Post, account: Account) {
-    let token = match auth::get_credentials(&account) {
-        Ok(Some(t)) => t,
-        _ => return,
+async fn handle_delete(result_tx: &mpsc::Sender<AsyncResult>, post: Post, account: Account) {
+    let Some(client) = client_or_report(result_tx, &account).await else {
+        return;
     };
 
-    let client = match get_client(&account, &token).await {
-        Ok(c) => c,
-        Err(e) => {
-            let _ = result_tx
-                .send(AsyncResult::Error {
-                    message: e.to_string(),
-                })
-                .await;
-            return;
-        }
+    let message = match client.delete(&post).await {
+        Ok(()) => AsyncResult::Deleted {
+            network: post.network,
+            post_id: post.network_id,
+        },
+        Err(e) => AsyncResult::Error {
+            message: format!("Delete failed: {e}"),
+        },
     };
+    let _ = result_tx.send(message).await;
+}
 
-    match client.repost(&post).await {
-        Ok(()) => {
-            let _ = result_tx
-                .send(AsyncResult::Reposted {
-                    post_id: post.network_id,
-                })
-                .await;
-        }
-        Err(e) => {
-            let _ = result_tx
-                .send(AsyncResult::Error {
-                    message: format!("Repost failed: {e}"),
-                })
-                .await;
-        }
+async fn handle_verify_account(result_tx: &mpsc::Sender<AsyncResult>, account: Account) {
+    let Some(client) = client_or_report(result_tx, &account).await else {
+        return;
+    };
+
+    let message = match client.verify_credentials().await {
+        Ok(profile) => AsyncResult::AccountVerified {
+            account_id: account.id,
+            profile,
+        },
+        Err(e) => AsyncResult::Error {
+            message: format!("@{} credentials rejected: {e}", account.handle),
+        },
+    };
+    let _ = result_tx.send(message).await;
+}
+
+/// Look up the post length limit quietly; the default applies if it fails
+async fn handle_fetch_post_limit(result_tx: &mpsc::Sender<AsyncResult>, account: Account) {
+    let Ok(Some(token)) = auth::get_credentials(&account) else {
+        return;
+    };
+    let Ok(client) = get_client(&account, &token).await else {
+        return;
+    };
+
+    if let Some(max_chars) = client.max_post_chars().await {
+        let _ = result_tx
+            .send(AsyncResult::PostLimitFetched {
+                account_id: account.id,
+                max_chars,
+            })
+            .await;
     }
 }
 
-async fn handle_unrepost(result_tx: &mpsc::Sender<AsyncResult>, post: Post, account: Account) {
-    let token = match auth::get_credentials(&account) {
-        Ok(Some(t)) => t,
-        _ => return,
+async fn handle_edit(
+    result_tx: &mpsc::Sender<AsyncResult>,
+    post: Post,
+    content: String,
+    account: Account,
+) {
+    let Some(client) = client_or_report(result_tx, &account).await else {
+        return;
     };
 
-    let client = match get_client(&account, &token).await {
-        Ok(c) => c,
-        Err(e) => {
-            let _ = result_tx
-                .send(AsyncResult::Error {
-                    message: e.to_string(),
-                })
-                .await;
-            return;
+    let message = match client.edit(&post.network_id, &content).await {
+        Ok(mut edited) => {
+            Post::set_owner(std::slice::from_mut(&mut edited), &account);
+            AsyncResult::Edited {
+                post: Box::new(edited),
+            }
         }
+        Err(e) => AsyncResult::Error {
+            message: format!("Edit failed: {e}"),
+        },
     };
+    let _ = result_tx.send(message).await;
+}
 
-    match client.unrepost(&post).await {
-        Ok(()) => {
-            let _ = result_tx
-                .send(AsyncResult::Unreposted {
-                    post_id: post.network_id,
-                })
-                .await;
-        }
-        Err(e) => {
-            let _ = result_tx
-                .send(AsyncResult::Error {
-                    message: format!("Unrepost failed: {e}"),
-                })
-                .await;
-        }
-    }
+/// Like or repost a post (or undo it). Failures, including missing
+/// credentials, come back as `ActionFailed` so the TUI can roll back.
+async fn handle_post_action(
+    result_tx: &mpsc::Sender<AsyncResult>,
+    post: Post,
+    account: Account,
+    action: PostAction,
+) {
+    let client = match auth::get_credentials(&account) {
+        Ok(Some(token)) => get_client(&account, &token).await,
+        Ok(None) => Err(anyhow::anyhow!("No credentials")),
+        Err(e) => Err(e),
+    };
+    let result = match client {
+        Ok(client) => match action {
+            PostAction::Like => client.like(&post).await.map(|()| true),
+            PostAction::Unlike => client.unlike(&post).await.map(|()| true),
+            PostAction::Repost => client.repost(&post).await.map(|()| true),
+            PostAction::Unrepost => client.unrepost(&post).await,
+        },
+        Err(e) => Err(e),
+    };
+
+    let post_id = post.network_id;
+    let message = match (result, action) {
+        (Ok(_), PostAction::Like) => AsyncResult::Liked { post_id },
+        (Ok(_), PostAction::Unlike) => AsyncResult::Unliked { post_id },
+        (Ok(_), PostAction::Repost) => AsyncResult::Reposted { post_id },
+        (Ok(removed), PostAction::Unrepost) => AsyncResult::Unreposted { post_id, removed },
+        (Err(e), action) => AsyncResult::ActionFailed {
+            network: post.network,
+            post_id,
+            action,
+            message: format!("{} failed: {}", action.name(), error_message(&e)),
+        },
+    };
+    let _ = result_tx.send(message).await;
 }
 
 async fn handle_post(
@@ -474,9 +1329,14 @@ async fn handle_post(
     content: String,
     accounts: Vec<Account>,
     reply_to: Option<Post>,
+    quote: Option<Box<Post>>,
+    media: Vec<MediaUpload>,
+    options: PostOptions,
 ) {
     let action = if reply_to.is_some() {
         "Replying..."
+    } else if quote.is_some() {
+        "Quoting..."
     } else {
         "Posting..."
     };
@@ -520,15 +1380,39 @@ async fn handle_post(
             .filter(|p| p.network == account.network)
             .map(|p| p.network_id.clone());
 
+        let quoted = quote.as_deref().filter(|p| p.network == account.network);
+
+        // Too long for this account: the rest of the thread follows the
+        // first part
+        let parts = if options.thread {
+            split_thread(&content, account.post_char_limit())
+        } else {
+            vec![content.clone()]
+        };
+        let first = &parts[0];
+
         let result = if let Some(ref reply_id) = reply_id {
-            client.reply(&content, reply_id).await
+            client.reply(first, reply_id, &options).await
+        } else if let Some(quoted) = quoted {
+            client.quote(quoted, first, &options).await
+        } else if media.is_empty() {
+            client.post(first, &options).await
         } else {
-            client.post(&content).await
+            client.post_with_media(first, media.clone(), &options).await
         };
 
         match result {
             Ok(post) => {
-                posted.push(post);
+                let mut posts = vec![post];
+                match client
+                    .continue_thread(&posts[0], &parts[1..], &options)
+                    .await
+                {
+                    Ok(rest) => posts.extend(rest),
+                    Err(e) => errors.push(format!("{}: {e:#}", account.network.name())),
+                }
+                Post::set_owner(&mut posts, account);
+                posted.extend(posts);
             }
             Err(e) => {
                 errors.push(format!("{}: {}", account.network.name(), e));
@@ -542,6 +1426,8 @@ async fn handle_post(
 
     let success_msg = if reply_to.is_some() {
         "Replied successfully!"
+    } else if quote.is_some() {
+        "Quoted successfully!"
     } else {
         "Posted successfully!"
     };
@@ -565,6 +1451,8 @@ async fn handle_schedule_post(
     content: String,
     networks: Vec<Network>,
     scheduled_for: chrono::DateTime<chrono::Utc>,
+    server_account: Option<Account>,
+    options: PostOptions,
 ) {
     let _ = result_tx
         .send(AsyncResult::Status {
@@ -572,82 +1460,88 @@ async fn handle_schedule_post(
         })
         .await;
 
-    // Save to database
-    let db = match crate::Database::open() {
-        Ok(db) => db,
-        Err(e) => {
-            let _ = result_tx
-                .send(AsyncResult::Error {
-                    message: format!("Database error: {}", e),
-                })
-                .await;
-            return;
+    let server_id = match server_account {
+        Some(account) => {
+            match schedule_on_server(&account, &content, &options, scheduled_for).await {
+                Ok(id) => Some(id),
+                Err(e) => {
+                    let _ = result_tx
+                        .send(AsyncResult::Error {
+                            message: format!("Failed to schedule on {}: {}", account.server, e),
+                        })
+                        .await;
+                    return;
+                }
+            }
         }
+        None => None,
     };
 
-    let scheduled_post = crate::ScheduledPost::new(content, networks, scheduled_for);
-    let id = scheduled_post.id.to_string();
-    let scheduled_for_str = scheduled_post.scheduled_time_display();
-    let time_until = scheduled_post.time_until();
-
-    if let Err(e) = db.save_scheduled_post(&scheduled_post) {
-        let _ = result_tx
-            .send(AsyncResult::Error {
-                message: format!("Failed to schedule: {}", e),
-            })
-            .await;
-        return;
-    }
+    let id = if networks.is_empty() {
+        None
+    } else {
+        match save_scheduled_post(content, networks, scheduled_for) {
+            Ok(id) => Some(id),
+            Err(e) => {
+                let _ = result_tx
+                    .send(AsyncResult::Error {
+                        message: format!("Failed to schedule: {}", e),
+                    })
+                    .await;
+                return;
+            }
+        }
+    };
 
     let _ = result_tx
         .send(AsyncResult::Scheduled {
-            id: id[..8].to_string(),
-            scheduled_for: scheduled_for_str.clone(),
-        })
-        .await;
-
-    let _ = result_tx
-        .send(AsyncResult::Status {
-            message: format!("📅 Scheduled for {} (in {})", scheduled_for_str, time_until),
+            id,
+            server_id,
+            scheduled_for,
         })
         .await;
 }
 
-/// Handle image loading from URL
-async fn handle_load_image(result_tx: &mpsc::Sender<AsyncResult>, url: String) {
-    log_debug(&format!("Loading image: {}", url));
+/// Have a Mastodon account's server publish a post later, returning the ID
+/// it was scheduled under
+async fn schedule_on_server(
+    account: &Account,
+    content: &str,
+    options: &PostOptions,
+    scheduled_for: chrono::DateTime<chrono::Utc>,
+) -> Result<String> {
+    let token = auth::get_credentials(account)?
+        .ok_or_else(|| anyhow::anyhow!("No credentials for {}", account.handle))?;
+    let client = get_client(account, &token).await?;
+    client.schedule_post(content, options, scheduled_for).await
+}
 
-    // Download the image
-    let response = match reqwest::get(&url).await {
-        Ok(resp) => resp,
-        Err(e) => {
-            log_debug(&format!("Failed to fetch image: {}", e));
-            let _ = result_tx
-                .send(AsyncResult::ImageFailed {
-                    url,
-                    error: e.to_string(),
-                })
-                .await;
-            return;
-        }
-    };
+/// Save a post for `perch schedule run` to publish, returning its short ID
+fn save_scheduled_post(
+    content: String,
+    networks: Vec<Network>,
+    scheduled_for: chrono::DateTime<chrono::Utc>,
+) -> Result<String> {
+    let db = crate::Database::open()?;
+    let scheduled_post = crate::ScheduledPost::new(content, networks, scheduled_for);
+    db.save_scheduled_post(&scheduled_post)?;
+    Ok(scheduled_post.id.to_string()[..8].to_string())
+}
 
-    if !response.status().is_success() {
-        let status = response.status();
-        log_debug(&format!("Image fetch failed with status: {}", status));
-        let _ = result_tx
-            .send(AsyncResult::ImageFailed {
-                url,
-                error: format!("HTTP {}", status),
-            })
-            .await;
-        return;
-    }
+/// Handle image loading from URL
+#[tracing::instrument(skip_all, fields(%url))]
+pub(super) async fn handle_load_image(
+    result_tx: &mpsc::Sender<AsyncResult>,
+    url: String,
+    animate: bool,
+    disk_cache: Option<&DiskCache>,
+) {
+    tracing::debug!("Loading image");
 
-    let bytes = match response.bytes().await {
+    let bytes = match fetch_image_bytes(&url, disk_cache).await {
         Ok(b) => b,
         Err(e) => {
-            log_debug(&format!("Failed to read image bytes: {}", e));
+            tracing::debug!("Failed to fetch image: {}", e);
             let _ = result_tx
                 .send(AsyncResult::ImageFailed {
                     url,
@@ -662,7 +1556,7 @@ async fn handle_load_image(result_tx: &mpsc::Sender<AsyncResult>, url: String) {
     let image = match image::load_from_memory(&bytes) {
         Ok(img) => img,
         Err(e) => {
-            log_debug(&format!("Failed to decode image: {}", e));
+            tracing::debug!("Failed to decode image: {}", e);
             let _ = result_tx
                 .send(AsyncResult::ImageFailed {
                     url,
@@ -675,18 +1569,90 @@ async fn handle_load_image(result_tx: &mpsc::Sender<AsyncResult>, url: String) {
 
     // Optionally resize if too large
     let image = resize_if_needed(image);
+    let animation = animate
+        .then(|| crate::images::Animation::decode(&bytes))
+        .flatten();
 
-    log_debug(&format!(
+    tracing::debug!(
         "Image loaded successfully: {}x{}",
         image.width(),
         image.height()
-    ));
+    );
 
     let _ = result_tx
-        .send(AsyncResult::ImageLoaded { url, image })
+        .send(AsyncResult::ImageLoaded {
+            url,
+            image,
+            animation,
+        })
         .await;
 }
 
+/// Get image bytes from the disk cache, revalidating with the server when
+/// the cached entry has an `ETag` or `Last-Modified` to compare against.
+async fn fetch_image_bytes(url: &str, disk_cache: Option<&DiskCache>) -> Result<Vec<u8>> {
+    let cached = disk_cache.and_then(|cache| cache.get(url));
+
+    let mut request = crate::api::http_client().get(url);
+    if let Some((bytes, validators)) = &cached {
+        if validators.is_empty() {
+            tracing::trace!("Image served from disk cache");
+            return Ok(bytes.clone());
+        }
+        if let Some(etag) = &validators.etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = match request.send().await {
+        Ok(resp) => resp,
+        Err(e) => {
+            // Offline or server unreachable: a stale copy beats nothing
+            if let Some((bytes, _)) = cached {
+                tracing::debug!("Revalidation failed, using cached image: {}", e);
+                return Ok(bytes);
+            }
+            return Err(e.into());
+        }
+    };
+
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED
+        && let Some((bytes, _)) = cached
+    {
+        tracing::trace!("Image not modified, using disk cache");
+        return Ok(bytes);
+    }
+
+    if !response.status().is_success() {
+        anyhow::bail!("HTTP {}", response.status());
+    }
+
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v: &reqwest::header::HeaderValue| v.to_str().ok())
+            .map(String::from)
+    };
+    let validators = CacheValidators {
+        etag: header(reqwest::header::ETAG),
+        last_modified: header(reqwest::header::LAST_MODIFIED),
+    };
+
+    let bytes = response.bytes().await?.to_vec();
+
+    if let Some(cache) = disk_cache
+        && let Err(e) = cache.put(url, &bytes, &validators)
+    {
+        tracing::debug!("Failed to write image to disk cache: {}", e);
+    }
+
+    Ok(bytes)
+}
+
 /// Resize image if it's too large (to save memory and rendering time).
 fn resize_if_needed(image: image::DynamicImage) -> image::DynamicImage {
     const MAX_DIMENSION: u32 = 800;
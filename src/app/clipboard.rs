@@ -0,0 +1,37 @@
+//! Copying to the system clipboard
+//!
+//! Only with the `clipboard` feature. Without it, or when there's no
+//! clipboard to reach (as over SSH), callers show the text instead.
+
+use anyhow::Result;
+
+#[cfg(feature = "clipboard")]
+thread_local! {
+    /// Kept open because on X11 the copied text lives only as long as the
+    /// clipboard handle that set it
+    static CLIPBOARD: std::cell::RefCell<Option<arboard::Clipboard>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Copy `text` to the system clipboard
+#[cfg(feature = "clipboard")]
+pub fn copy(text: &str) -> Result<()> {
+    use anyhow::Context;
+
+    CLIPBOARD.with_borrow_mut(|clipboard| {
+        if clipboard.is_none() {
+            *clipboard = Some(arboard::Clipboard::new().context("No clipboard available")?);
+        }
+        clipboard
+            .as_mut()
+            .expect("clipboard was just opened")
+            .set_text(text)
+            .context("Failed to copy")
+    })
+}
+
+/// Copy `text` to the system clipboard
+#[cfg(not(feature = "clipboard"))]
+pub fn copy(_text: &str) -> Result<()> {
+    anyhow::bail!("Built without clipboard support")
+}
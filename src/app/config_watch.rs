@@ -0,0 +1,113 @@
+//! Noticing edits to the config file and custom themes while Perch runs
+//!
+//! Nothing is pushed to us: every [`CHECK_INTERVAL`] the modification times
+//! of the config file and of each file in the themes directory are compared
+//! with the last ones seen.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::config::Config;
+use crate::paths;
+
+/// How often to look for changes
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Modification times of the watched files, in path order
+type Stamp = Vec<(PathBuf, SystemTime)>;
+
+/// Watches the config file and themes directory for changes
+pub struct ConfigWatch {
+    config_path: PathBuf,
+    themes_dir: PathBuf,
+    stamp: Stamp,
+    next_check: Instant,
+}
+
+impl ConfigWatch {
+    /// Start watching the default config file and themes directory
+    pub fn new() -> Option<Self> {
+        Some(Self::watch(
+            Config::default_path().ok()?,
+            paths::themes_dir().ok()?,
+        ))
+    }
+
+    fn watch(config_path: PathBuf, themes_dir: PathBuf) -> Self {
+        let stamp = stamp(&config_path, &themes_dir);
+        Self {
+            config_path,
+            themes_dir,
+            stamp,
+            next_check: Instant::now() + CHECK_INTERVAL,
+        }
+    }
+
+    /// Whether any of the files changed, appeared or went away since the
+    /// last check. Checks at most once every [`CHECK_INTERVAL`].
+    pub fn changed(&mut self) -> bool {
+        let now = Instant::now();
+        if now < self.next_check {
+            return false;
+        }
+        self.next_check = now + CHECK_INTERVAL;
+
+        let stamp = stamp(&self.config_path, &self.themes_dir);
+        if stamp == self.stamp {
+            return false;
+        }
+        self.stamp = stamp;
+        true
+    }
+}
+
+fn stamp(config_path: &Path, themes_dir: &Path) -> Stamp {
+    let themes = std::fs::read_dir(themes_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| Some(entry.ok()?.path()));
+    let mut stamp: Stamp = std::iter::once(config_path.to_path_buf())
+        .chain(themes)
+        .filter_map(|path| {
+            let modified = std::fs::metadata(&path).ok()?.modified().ok()?;
+            Some((path, modified))
+        })
+        .collect();
+    stamp.sort();
+    stamp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("config.toml");
+        let themes_dir = dir.path().join("themes");
+        std::fs::write(&config_path, "theme = \"nord\"").unwrap();
+
+        let mut watch = ConfigWatch::watch(config_path.clone(), themes_dir.clone());
+        watch.next_check = Instant::now();
+        assert!(!watch.changed());
+        // Not checked again until the interval has passed
+        std::fs::create_dir(&themes_dir).unwrap();
+        std::fs::write(themes_dir.join("mine.toml"), "").unwrap();
+        assert!(!watch.changed());
+
+        watch.next_check = Instant::now();
+        assert!(watch.changed());
+
+        let file = std::fs::File::options()
+            .write(true)
+            .open(&config_path)
+            .unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5))
+            .unwrap();
+        watch.next_check = Instant::now();
+        assert!(watch.changed());
+        watch.next_check = Instant::now();
+        assert!(!watch.changed());
+    }
+}
@@ -0,0 +1,94 @@
+//! Compose in an external editor (`$EDITOR`)
+
+use anyhow::{Context, Result, bail};
+use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::prelude::*;
+use std::process::Command;
+
+use super::AppState;
+
+/// Editor command from `$EDITOR`, then `$VISUAL`, then the platform default
+fn editor_command() -> String {
+    ["EDITOR", "VISUAL"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|cmd| !cmd.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string())
+}
+
+/// Run the editor on a file and return what was saved
+fn run_editor(path: &std::path::Path) -> Result<String> {
+    let command = editor_command();
+    // Allow editors with arguments, e.g. `code --wait`
+    let mut parts = command.split_whitespace();
+    let program = parts.next().context("Editor command is empty")?;
+
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to launch editor '{program}'"))?;
+
+    if !status.success() {
+        match status.code() {
+            Some(code) => bail!("Editor exited with code {code}"),
+            None => bail!("Editor was terminated"),
+        }
+    }
+
+    std::fs::read_to_string(path).context("Failed to read edited post")
+}
+
+/// Suspend the TUI, edit the compose buffer in an external editor, and load
+/// the result back into compose.
+///
+/// The previous buffer is kept if the editor fails or leaves the file empty.
+pub fn edit_compose(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    state: &mut AppState,
+) -> Result<()> {
+    state.pending_editor = false;
+
+    let path = std::env::temp_dir().join(format!("perch-{}.txt", uuid::Uuid::new_v4()));
+    if let Err(e) = std::fs::write(&path, &state.compose_text) {
+        state.set_status(format!("⚠ Failed to write temp file: {e}"));
+        return Ok(());
+    }
+
+    disable_raw_mode()?;
+    if state.config.mouse {
+        execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    }
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    let edited = run_editor(&path);
+    let _ = std::fs::remove_file(&path);
+
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    if state.config.mouse {
+        execute!(terminal.backend_mut(), EnableMouseCapture)?;
+    }
+    terminal.clear()?;
+
+    match edited {
+        Ok(text) => {
+            // Editors add a trailing newline on save
+            let text = text.trim_end_matches(['\n', '\r']);
+            if text.trim().is_empty() {
+                state.set_status("⚠ Editor left the post empty, kept previous text");
+            } else {
+                state.set_compose_text(text.to_string());
+                state.set_status("✓ Loaded text from editor");
+            }
+        }
+        Err(e) => state.set_status(format!("⚠ {e}, kept previous text")),
+    }
+
+    Ok(())
+}
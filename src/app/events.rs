@@ -1,21 +1,20 @@
 //! Event handling
 
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Position;
 
-use super::async_ops::AsyncCommand;
-use super::state::{AppState, FocusedPanel, Mode, View};
-use crate::models::Network;
+use super::async_ops::{AsyncCommand, PostAction};
+use super::clipboard;
+use super::keymap::Action;
+use super::state::{AppState, Feed, FocusedPanel, Mode, PendingDelete, SearchScope, View};
+use super::text_cursor;
+use crate::api::{MAX_MEDIA_ATTACHMENTS, PostOptions};
+use crate::models::{Moderation, Network, Post};
 use crate::theme::Theme;
 
-/// Process pending update (called from main loop)
-pub fn process_pending_update(state: &mut AppState) {
-    if !state.pending_update {
-        return;
-    }
-    state.pending_update = false;
-
-    // Run the actual update
-    match crate::run_update(&state.package_manager) {
+/// Show how an update went, once it has finished in the background
+pub fn finish_update(state: &mut AppState, result: Result<(), String>) {
+    match result {
         Ok(()) => {
             state.update_status = Some("Update complete! Please restart perch.".to_string());
             state.update_available = None;
@@ -51,10 +50,42 @@ pub fn handle_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncCommand> {
         Mode::Search => {
             return handle_search_key(state, key);
         }
+        Mode::Thread => {
+            return handle_thread_key(state, key);
+        }
+        Mode::Profile => {
+            return handle_profile_key(state, key);
+        }
+        Mode::SearchResults => {
+            handle_search_results_key(state, key);
+            return None;
+        }
         Mode::UpdateConfirm => {
             handle_update_confirm_key(state, key);
             return None;
         }
+        Mode::ConfirmDelete => {
+            return handle_confirm_delete_key(state, key);
+        }
+        Mode::ConfirmModeration => {
+            return handle_confirm_moderation_key(state, key);
+        }
+        Mode::ConfirmQuit => {
+            handle_confirm_quit_key(state, key);
+            return None;
+        }
+        Mode::Drafts => {
+            handle_drafts_key(state, key);
+            return None;
+        }
+        Mode::Scheduled => {
+            handle_scheduled_key(state, key);
+            return None;
+        }
+        Mode::LinkPicker => {
+            handle_link_picker_key(state, key);
+            return None;
+        }
         Mode::Updating => {
             // No input during update
             return None;
@@ -62,49 +93,83 @@ pub fn handle_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncCommand> {
         Mode::Normal => {}
     }
 
+    // Ctrl+C always quits, whatever the keybindings say
+    if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+        state.request_quit();
+        return None;
+    }
+
     // Global shortcuts (work in normal mode)
-    match (key.modifiers, key.code) {
-        (KeyModifiers::CONTROL, KeyCode::Char('c')) | (_, KeyCode::Char('q')) => {
-            state.should_quit = true;
+    let action = state.keymap.action(key);
+    match action {
+        Some(Action::Quit) => {
+            state.request_quit();
             return None;
         }
-        (_, KeyCode::Char('?') | KeyCode::F(1)) => {
+        Some(Action::Help) => {
             state.mode = Mode::Help;
             return None;
         }
-        (_, KeyCode::Tab) => {
+        Some(Action::NextView) => {
+            let previous = state.view;
             state.next_view();
-            return None;
+            return view_changed(state, previous);
         }
-        (KeyModifiers::SHIFT, KeyCode::BackTab) => {
+        Some(Action::PrevView) => {
+            let previous = state.view;
             state.prev_view();
-            return None;
+            return view_changed(state, previous);
         }
         // Number keys for quick navigation
-        (_, KeyCode::Char('1')) => {
-            state.view = View::Timeline;
-            return None;
+        Some(Action::ViewTimeline) => {
+            let previous = std::mem::replace(&mut state.view, View::Timeline);
+            return view_changed(state, previous);
         }
-        (_, KeyCode::Char('2')) => {
-            state.view = View::Accounts;
-            return None;
+        Some(Action::ViewAccounts) => {
+            let previous = std::mem::replace(&mut state.view, View::Accounts);
+            return view_changed(state, previous);
+        }
+        Some(Action::ViewNotifications) => {
+            let previous = std::mem::replace(&mut state.view, View::Notifications);
+            return view_changed(state, previous);
         }
         // Theme picker
-        (_, KeyCode::Char('t')) => {
-            state.theme_picker_index = Theme::all()
-                .iter()
-                .position(|t| *t == state.theme.inner())
-                .unwrap_or(0);
+        Some(Action::Theme) => {
+            state.theme_picker_index = state.theme.index();
             state.mode = Mode::ThemePicker;
             return None;
         }
+        // Drafts picker
+        Some(Action::Drafts) => {
+            state.load_drafts();
+            if state.drafts.is_empty() {
+                state.set_status("No saved drafts");
+            } else {
+                state.mode = Mode::Drafts;
+            }
+            return None;
+        }
+        // Scheduled posts picker
+        Some(Action::Scheduled) => {
+            state.load_scheduled_posts();
+            if state.scheduled_posts.is_empty() {
+                state.set_status("No scheduled posts");
+            } else {
+                state.mode = Mode::Scheduled;
+            }
+            return None;
+        }
+        Some(Action::ToggleTimes) => {
+            state.toggle_absolute_times();
+            return None;
+        }
         // About dialog
-        (KeyModifiers::SHIFT, KeyCode::Char('A')) => {
+        Some(Action::About) => {
             state.mode = Mode::About;
             return None;
         }
         // Update (when available)
-        (_, KeyCode::Char('u' | 'U')) => {
+        Some(Action::Update) => {
             if state.update_available.is_some() {
                 state.mode = Mode::UpdateConfirm;
             }
@@ -115,25 +180,92 @@ pub fn handle_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncCommand> {
 
     // View-specific handling
     match state.view {
-        View::Timeline => handle_timeline_key(state, key),
-        View::Accounts => handle_accounts_key(state, key),
+        View::Timeline => handle_timeline_key(state, action?),
+        View::Accounts => handle_accounts_key(state, key, action),
+        View::Notifications => handle_notifications_key(state, key, action),
     }
 }
 
-fn handle_timeline_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncCommand> {
-    match (key.modifiers, key.code) {
+/// React to switching tabs: load notifications on first visit, and mark
+/// them read once the user leaves the notifications tab
+fn view_changed(state: &mut AppState, previous: View) -> Option<AsyncCommand> {
+    if previous == state.view {
+        return None;
+    }
+    if previous == View::Notifications {
+        return state.mark_notifications_read_command();
+    }
+    if state.view == View::Notifications && state.notifications.is_empty() {
+        return state.refresh_notifications_command();
+    }
+    None
+}
+
+/// Handle mouse events in the timeline view, returning an optional async command
+pub fn handle_mouse(state: &mut AppState, mouse: MouseEvent) -> Option<AsyncCommand> {
+    if state.mode != Mode::Normal || state.view != View::Timeline {
+        return None;
+    }
+
+    let position = Position::new(mouse.column, mouse.row);
+    let panel = if state.hit_regions.timeline.contains(position) {
+        FocusedPanel::Timeline
+    } else if state.hit_regions.detail.contains(position) {
+        FocusedPanel::Detail
+    } else {
+        return None;
+    };
+
+    match mouse.kind {
+        // The wheel moves the selection of the panel under the pointer
+        MouseEventKind::ScrollDown => {
+            state.focused_panel = panel;
+            handle_timeline_key(state, Action::NextPost)
+        }
+        MouseEventKind::ScrollUp => {
+            state.focused_panel = panel;
+            handle_timeline_key(state, Action::PrevPost)
+        }
+        MouseEventKind::Down(MouseButton::Left) => {
+            state.focused_panel = panel;
+            if let Some(url) = state.hit_regions.link_at(position) {
+                let _ = open::that(url);
+                state.set_status("✓ Opened in browser");
+                None
+            } else if let Some(index) = state.hit_regions.reply_at(position) {
+                state.selected_reply = Some(index);
+                None
+            } else if let Some(index) = state.hit_regions.post_at(position)
+                && index != state.selected_post
+            {
+                state.select_post(index);
+                None
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+fn handle_timeline_key(state: &mut AppState, action: Action) -> Option<AsyncCommand> {
+    match action {
         // Panel navigation (when in timeline view)
-        (_, KeyCode::Left | KeyCode::Char('h')) => {
+        Action::PanelLeft => {
             state.focused_panel = state.focused_panel.prev();
             None
         }
-        (_, KeyCode::Right | KeyCode::Char('l')) => {
+        Action::PanelRight => {
             state.focused_panel = state.focused_panel.next();
             None
         }
+        Action::Layout => {
+            state.cycle_layout();
+            None
+        }
 
         // Navigation within panel
-        (_, KeyCode::Char('j') | KeyCode::Down) => {
+        Action::NextPost => {
             match state.focused_panel {
                 FocusedPanel::Accounts => {
                     state.select_next_account();
@@ -162,18 +294,16 @@ fn handle_timeline_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncComma
                     None
                 }
                 FocusedPanel::Timeline => {
-                    state.select_next_post();
-                    // Fetch replies for newly selected post
-                    if let Some(post) = state.selected_post().cloned()
-                        && let Some(account) = find_account_for_post(state, &post)
-                    {
-                        return Some(AsyncCommand::FetchContext { post, account });
+                    // Already at the bottom: fetch the next page
+                    if state.selected_post + 1 >= state.posts.len() {
+                        return state.load_more_command();
                     }
+                    state.select_next_post();
                     None
                 }
             }
         }
-        (_, KeyCode::Char('k') | KeyCode::Up) => {
+        Action::PrevPost => {
             match state.focused_panel {
                 FocusedPanel::Accounts => {
                     state.select_prev_account();
@@ -203,37 +333,31 @@ fn handle_timeline_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncComma
                 }
                 FocusedPanel::Timeline => {
                     state.select_prev_post();
-                    // Fetch replies for newly selected post
-                    if let Some(post) = state.selected_post().cloned()
-                        && let Some(account) = find_account_for_post(state, &post)
-                    {
-                        return Some(AsyncCommand::FetchContext { post, account });
-                    }
                     None
                 }
             }
         }
 
         // Scroll detail panel (Shift+J/K or Ctrl+D/U)
-        (KeyModifiers::SHIFT, KeyCode::Char('J')) => {
+        Action::ScrollDown => {
             if state.focused_panel == FocusedPanel::Detail {
                 state.detail_scroll = state.detail_scroll.saturating_add(3);
             }
             None
         }
-        (KeyModifiers::SHIFT, KeyCode::Char('K')) => {
+        Action::ScrollUp => {
             if state.focused_panel == FocusedPanel::Detail {
                 state.detail_scroll = state.detail_scroll.saturating_sub(3);
             }
             None
         }
-        (KeyModifiers::CONTROL, KeyCode::Char('d')) => {
+        Action::PageDown => {
             if state.focused_panel == FocusedPanel::Detail {
                 state.detail_scroll = state.detail_scroll.saturating_add(10);
             }
             None
         }
-        (KeyModifiers::CONTROL, KeyCode::Char('u')) => {
+        Action::PageUp => {
             if state.focused_panel == FocusedPanel::Detail {
                 state.detail_scroll = state.detail_scroll.saturating_sub(10);
             }
@@ -241,39 +365,50 @@ fn handle_timeline_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncComma
         }
 
         // Jump to top/bottom
-        (_, KeyCode::Char('g')) => {
-            state.selected_post = 0;
+        Action::Top => {
+            state.select_post(0);
             None
         }
-        (KeyModifiers::SHIFT, KeyCode::Char('G')) => {
-            if !state.posts.is_empty() {
-                state.selected_post = state.posts.len() - 1;
+        Action::Newest => {
+            state.jump_to_newest();
+            None
+        }
+        Action::Bottom => {
+            if state.posts.is_empty() {
+                return None;
             }
+            if state.selected_post + 1 >= state.posts.len() {
+                // Already at the bottom: fetch the next page
+                return state.load_more_command();
+            }
+            state.select_post(state.posts.len() - 1);
             None
         }
 
         // Actions
-        (_, KeyCode::Char('n')) => {
+        Action::Compose => {
             state.open_compose();
             None
         }
-        (_, KeyCode::Char('/')) => {
+        Action::Search => {
             state.mode = Mode::Search;
             None
         }
-        (_, KeyCode::Char('b')) => {
+        Action::Refresh => {
             // Refresh timeline (b for "buffer refresh")
-            if !state.loading && !state.accounts.is_empty() {
-                state.loading = true;
-                state.set_status("Refreshing...");
-                Some(AsyncCommand::RefreshTimeline {
-                    accounts: state.accounts.clone(),
-                })
-            } else {
-                None
+            if let Feed::Hashtag(tag) = &state.feed {
+                if state.loading {
+                    return None;
+                }
+                let tag = tag.clone();
+                return state.hashtag_command(tag);
+            }
+            if state.loading {
+                return None;
             }
+            state.refresh_timeline_command()
         }
-        (KeyModifiers::SHIFT, KeyCode::Char('R')) => {
+        Action::Reply => {
             // Reply to selected post or reply
             let reply_target = if state.focused_panel == FocusedPanel::Detail {
                 // If a reply is selected, reply to that reply
@@ -293,7 +428,71 @@ fn handle_timeline_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncComma
             }
             None
         }
-        (_, KeyCode::Char('o')) => {
+        Action::Quote => {
+            // Quote the selected post
+            if let Some(post) = state.selected_post().cloned() {
+                if post.network.capabilities().quotes {
+                    state.open_quote(post);
+                } else {
+                    state.set_status(format!("⚠ {} doesn't support quote posts", post.network));
+                }
+            }
+            None
+        }
+        Action::ToggleCw => {
+            // Expand/collapse content warning of the selected post or reply
+            let target = if state.focused_panel == FocusedPanel::Detail {
+                state
+                    .selected_reply
+                    .and_then(|idx| state.current_replies.get(idx))
+                    .map(|r| r.post.clone())
+                    .or_else(|| state.selected_post().cloned())
+            } else {
+                state.selected_post().cloned()
+            };
+
+            if let Some(post) = target
+                && post.spoiler_text.is_some()
+            {
+                state.toggle_cw(&post);
+            }
+            None
+        }
+        Action::AltText => {
+            // Show the full alt text of the selected post's or reply's media
+            match focused_post(state) {
+                Some(post) if !post.media.is_empty() => state.toggle_alt_text(&post),
+                Some(_) => state.set_status("No media in this post"),
+                None => {}
+            }
+            None
+        }
+        Action::RetryImages => {
+            if let Some(post) = focused_post(state) {
+                match state.retry_failed_images(&post) {
+                    0 => state.set_status("No images failed to load in this post"),
+                    1 => state.set_status("Loading the image again..."),
+                    n => state.set_status(format!("Loading {n} images again...")),
+                }
+            }
+            None
+        }
+        Action::Profile => {
+            // Show the author of the selected post or reply
+            focused_post(state).and_then(|post| open_author_profile(state, &post))
+        }
+        Action::Mute | Action::Block => {
+            let moderation = if action == Action::Mute {
+                Moderation::Mute
+            } else {
+                Moderation::Block
+            };
+            if let Some(post) = focused_post(state) {
+                moderate_author(state, &post, moderation);
+            }
+            None
+        }
+        Action::Open => {
             // Open selected post in browser
             if let Some(post) = state.selected_post()
                 && let Some(url) = &post.url
@@ -303,30 +502,93 @@ fn handle_timeline_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncComma
             }
             None
         }
-        (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char('L')) => {
+        Action::Links => {
+            // Open a link from the selected post
+            if let Some(post) = state.selected_post().cloned() {
+                open_links(state, &post);
+            }
+            None
+        }
+        Action::CopyText | Action::CopyUrl => {
+            if let Some(post) = state.selected_post().cloned() {
+                copy_post(state, &post, action);
+            }
+            None
+        }
+        Action::Like => {
             // Like/favorite
             if let Some(post) = state.selected_post().cloned() {
                 if let Some(account) = find_account_for_post(state, &post) {
+                    // Shown right away, and rolled back if it fails
                     if post.liked {
-                        state.set_status("Unliking...");
+                        state.apply_post_action(post.network, &post.network_id, PostAction::Unlike);
                         return Some(AsyncCommand::Unlike { post, account });
                     }
-                    state.set_status("Liking...");
+                    state.apply_post_action(post.network, &post.network_id, PostAction::Like);
                     return Some(AsyncCommand::Like { post, account });
                 }
                 state.set_status("⚠ No matching account for this network");
             }
             None
         }
-        (_, KeyCode::Char('r')) => {
+        Action::Bookmark => {
+            // Bookmark (toggle, where the network has bookmarks)
+            if let Some(post) = state.selected_post().cloned() {
+                if !post.network.capabilities().bookmarks {
+                    state.set_status(format!("⚠ {} doesn't have bookmarks", post.network));
+                    return None;
+                }
+                if let Some(account) = find_account_for_post(state, &post) {
+                    if post.bookmarked {
+                        state.set_status("Removing bookmark...");
+                        return Some(AsyncCommand::Unbookmark { post, account });
+                    }
+                    state.set_status("Bookmarking...");
+                    return Some(AsyncCommand::Bookmark { post, account });
+                }
+                state.set_status("⚠ No matching account for this network");
+            }
+            None
+        }
+        Action::Edit => {
+            // Edit own post (where the network allows it)
+            if let Some(post) = state.selected_post().cloned() {
+                if !post.network.capabilities().edit {
+                    state.set_status(format!("⚠ {} doesn't support editing posts", post.network));
+                } else if state.own_account_for_post(&post).is_some() {
+                    state.open_edit(post);
+                } else {
+                    state.set_status("⚠ You can only edit your own posts");
+                }
+            }
+            None
+        }
+        Action::Delete => {
+            // Delete own post (after confirmation)
+            if let Some(post) = state.selected_post().cloned() {
+                if state.own_account_for_post(&post).is_some() {
+                    state.pending_delete = Some(PendingDelete::Post(Box::new(post)));
+                    state.mode = Mode::ConfirmDelete;
+                } else {
+                    state.set_status("⚠ You can only delete your own posts");
+                }
+            }
+            None
+        }
+        Action::Repost => {
             // Repost/boost (toggle)
             if let Some(post) = state.selected_post().cloned() {
                 if let Some(account) = find_account_for_post(state, &post) {
+                    // Shown right away, and rolled back if it fails
                     if post.reposted {
-                        state.set_status("Undoing repost...");
+                        state.apply_post_action(
+                            post.network,
+                            &post.network_id,
+                            PostAction::Unrepost,
+                        );
                         return Some(AsyncCommand::Unrepost { post, account });
                     }
-                    state.set_status("Reposting...");
+                    state.apply_post_action(post.network, &post.network_id, PostAction::Repost);
                     return Some(AsyncCommand::Repost { post, account });
                 }
                 state.set_status("⚠ No matching account for this network");
@@ -335,25 +597,65 @@ fn handle_timeline_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncComma
         }
 
         // Filter
-        (_, KeyCode::Char('f')) => {
+        Action::Filter => {
             state.cycle_filter();
             state.set_status(format!("Filter: {}", state.timeline_filter.name()));
             None
         }
+        // Home/local/federated timeline (Mastodon)
+        Action::TimelineKind => state.cycle_timeline_kind(),
+
+        // Highlight the next hashtag in the selected post
+        Action::Hashtag => {
+            match state.cycle_hashtag() {
+                Some(tag) => state.set_status(format!("#{tag} — Enter to open")),
+                None => state.set_status("No hashtags in this post"),
+            }
+            None
+        }
+
+        // Open the highlighted hashtag's feed
+        Action::Select if state.focused_panel != FocusedPanel::Accounts => {
+            // One panel at a time: bring up the post first
+            if state.single_column && state.focused_panel == FocusedPanel::Timeline {
+                state.focused_panel = FocusedPanel::Detail;
+                return None;
+            }
+            if let Some(tag) = state.selected_hashtag() {
+                return state.open_hashtag(tag);
+            }
+            // Replies were skipped for a post that seemed to have none
+            if state.replies_skipped {
+                return state.load_replies_command();
+            }
+            // Otherwise open the full conversation
 
-        // Enter detail view
-        (_, KeyCode::Enter) if state.focused_panel == FocusedPanel::Timeline => {
-            state.focused_panel = FocusedPanel::Detail;
-            // Fetch replies for the selected post
             if let Some(post) = state.selected_post().cloned()
                 && let Some(account) = find_account_for_post(state, &post)
             {
+                state.open_thread();
                 return Some(AsyncCommand::FetchContext { post, account });
             }
             None
         }
 
-        (_, KeyCode::Esc) => {
+        Action::Back => {
+            if state.selected_hashtag.take().is_some() {
+                state.clear_status();
+                return None;
+            }
+            if state.single_column && state.focused_panel == FocusedPanel::Detail {
+                state.focused_panel = FocusedPanel::Timeline;
+                return None;
+            }
+            if state.close_feed() {
+                let message = match &state.feed {
+                    Feed::Home => "Back to the home timeline".to_string(),
+                    Feed::Hashtag(tag) => format!("Back to #{tag}"),
+                };
+                state.set_status(message);
+                return None;
+            }
             state.clear_status();
             None
         }
@@ -362,26 +664,53 @@ fn handle_timeline_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncComma
     }
 }
 
-fn handle_accounts_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncCommand> {
-    match (key.modifiers, key.code) {
-        (_, KeyCode::Down | KeyCode::Char('j')) => {
+fn handle_accounts_key(
+    state: &mut AppState,
+    key: KeyEvent,
+    action: Option<Action>,
+) -> Option<AsyncCommand> {
+    match (action, key.code) {
+        (Some(Action::NextPost), _) => {
             state.select_next_account();
             None
         }
-        (_, KeyCode::Up | KeyCode::Char('k')) => {
+        (Some(Action::PrevPost), _) => {
             state.select_prev_account();
             None
         }
-        (_, KeyCode::Char('g')) => {
+        (Some(Action::Top), _) => {
             state.selected_account = 0;
             None
         }
-        (KeyModifiers::SHIFT, KeyCode::Char('G')) => {
+        (Some(Action::Bottom), _) => {
             if !state.accounts.is_empty() {
                 state.selected_account = state.accounts.len() - 1;
             }
             None
         }
+        (Some(Action::Delete), _) => {
+            // Remove account (after confirmation)
+            if let Some(account) = state.selected_account().cloned() {
+                state.pending_delete = Some(PendingDelete::Account(Box::new(account)));
+                state.mode = Mode::ConfirmDelete;
+            }
+            None
+        }
+        (Some(Action::Select), _) => {
+            // Switch to timeline view filtered by this account's network
+            if let Some(account) = state.accounts.get(state.selected_account) {
+                state.timeline_filter =
+                    crate::app::state::TimelineFilter::for_network(account.network);
+                state.view = crate::app::state::View::Timeline;
+                state.focused_panel = FocusedPanel::Timeline;
+                state.set_status(format!("Viewing {} timeline", account.network.emoji()));
+            }
+            None
+        }
+        (Some(Action::Back), _) => {
+            state.clear_status();
+            None
+        }
         (_, KeyCode::Char('d')) => {
             // Set as default account for this network
             if let Some(account) = state.accounts.get(state.selected_account) {
@@ -399,45 +728,77 @@ fn handle_accounts_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncComma
             }
             None
         }
-        (KeyModifiers::SHIFT, KeyCode::Char('D')) => {
-            // Delete account
-            if let Some(account) = state.accounts.get(state.selected_account) {
-                let account_id = account.id;
-                let handle = account.handle.clone();
-                if let Err(e) = state.db.delete_account(account_id) {
-                    state.set_status(format!("❌ Failed to delete: {e}"));
-                } else {
-                    // Remove credentials
-                    let _ =
-                        crate::auth::delete_credentials(&state.accounts[state.selected_account]);
-                    // Reload accounts
-                    if let Ok(accounts) = state.db.get_accounts() {
-                        state.accounts = accounts;
-                        if state.selected_account >= state.accounts.len()
-                            && !state.accounts.is_empty()
-                        {
-                            state.selected_account = state.accounts.len() - 1;
-                        }
-                    }
-                    state.set_status(format!("🗑 Deleted @{handle}"));
-                }
+        (_, KeyCode::Char('r')) => {
+            // Check the stored token/app password still works
+            let account = state.selected_account().cloned()?;
+            state.set_status(format!("Checking @{}...", account.handle));
+            Some(AsyncCommand::VerifyAccount { account })
+        }
+        _ => None,
+    }
+}
+
+fn handle_notifications_key(
+    state: &mut AppState,
+    key: KeyEvent,
+    action: Option<Action>,
+) -> Option<AsyncCommand> {
+    match (action, key.code) {
+        (Some(Action::NextPost), _) => {
+            state.select_next_notification();
+            None
+        }
+        (Some(Action::PrevPost), _) => {
+            state.select_prev_notification();
+            None
+        }
+        (Some(Action::Top), _) => {
+            state.selected_notification = 0;
+            None
+        }
+        (Some(Action::Bottom), _) => {
+            if !state.notifications.is_empty() {
+                state.selected_notification = state.notifications.len() - 1;
             }
             None
         }
-        (_, KeyCode::Enter) => {
-            // Switch to timeline view filtered by this account's network
-            if let Some(account) = state.accounts.get(state.selected_account) {
-                state.timeline_filter = match account.network {
-                    crate::models::Network::Mastodon => crate::app::state::TimelineFilter::Mastodon,
-                    crate::models::Network::Bluesky => crate::app::state::TimelineFilter::Bluesky,
-                };
-                state.view = crate::app::state::View::Timeline;
-                state.focused_panel = FocusedPanel::Timeline;
-                state.set_status(format!("Viewing {} timeline", account.network.emoji()));
+        (Some(Action::Refresh), _) | (_, KeyCode::Char('r')) => {
+            let cmd = state.refresh_notifications_command();
+            if cmd.is_some() {
+                state.set_status("Refreshing notifications...");
+            }
+            cmd
+        }
+        (Some(Action::Open | Action::Select), _) => {
+            // Open the post the notification is about in the browser
+            if let Some(url) = state
+                .selected_notification()
+                .and_then(|n| n.post.as_ref())
+                .and_then(|p| p.url.clone())
+            {
+                let _ = open::that(url);
+                state.set_status("✓ Opened in browser");
             }
             None
         }
-        (_, KeyCode::Esc) => {
+        (Some(Action::Profile), _) => {
+            // Show who the notification is from
+            let notification = state.selected_notification()?;
+            let handle = notification.actor_handle.clone();
+            let account = notification
+                .post
+                .as_ref()
+                .and_then(|p| p.acting_account(&state.accounts))
+                .or_else(|| {
+                    state
+                        .accounts
+                        .iter()
+                        .find(|a| a.network == notification.network)
+                })
+                .cloned()?;
+            Some(state.open_profile(handle, account))
+        }
+        (Some(Action::Back), _) => {
             state.clear_status();
             None
         }
@@ -445,42 +806,396 @@ fn handle_accounts_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncComma
     }
 }
 
-fn handle_compose_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncCommand> {
-    // Handle schedule input focus mode
-    if state.compose_schedule_focused {
-        match (key.modifiers, key.code) {
-            (_, KeyCode::Esc) => {
-                // Clear schedule and go back to text
-                state.compose_schedule_focused = false;
-                None
-            }
-            (_, KeyCode::Tab | KeyCode::Enter) => {
-                // Apply schedule and go back to text
-                if let Err(e) = state.apply_schedule_input() {
-                    state.set_status(format!("⚠ Invalid schedule: {}", e));
-                } else if state.compose_schedule.is_some() {
-                    state.set_status("📅 Post scheduled");
-                }
-                state.compose_schedule_focused = false;
-                None
+fn handle_thread_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncCommand> {
+    match state.keymap.action(key)? {
+        Action::Back | Action::Quit => {
+            // Back to the timeline; the selected post was never changed
+            state.mode = Mode::Normal;
+            None
+        }
+        Action::NextPost => {
+            state.select_next_thread_node();
+            None
+        }
+        Action::PrevPost => {
+            state.select_prev_thread_node();
+            None
+        }
+        Action::Top => {
+            state.thread_selected = 0;
+            None
+        }
+        Action::ToggleTimes => {
+            state.toggle_absolute_times();
+            None
+        }
+        Action::Bottom => {
+            state.thread_selected = state.thread_len().saturating_sub(1);
+            None
+        }
+        Action::Reply => {
+            // Reply to the focused node
+            if let Some(post) = state.thread_node(state.thread_selected).cloned() {
+                state.open_reply(post);
             }
-            (KeyModifiers::CONTROL, KeyCode::Char('u')) => {
-                // Clear schedule input
-                state.compose_schedule_input.clear();
-                state.compose_schedule = None;
-                None
+            None
+        }
+        Action::ToggleCw => {
+            if let Some(post) = state.thread_node(state.thread_selected).cloned() {
+                state.toggle_cw(&post);
             }
-            (_, KeyCode::Char(c)) => {
-                state.compose_schedule_input.push(c);
-                // Try to parse as we type
-                let _ = state.apply_schedule_input();
-                None
+            None
+        }
+        Action::Open => {
+            if let Some(url) = state
+                .thread_node(state.thread_selected)
+                .and_then(|p| p.url.clone())
+            {
+                let _ = open::that(url);
+                state.set_status("✓ Opened in browser");
             }
-            (_, KeyCode::Backspace) => {
-                state.compose_schedule_input.pop();
-                // Try to parse as we type
-                let _ = state.apply_schedule_input();
-                None
+            None
+        }
+        Action::Links => {
+            if let Some(post) = state.thread_node(state.thread_selected).cloned() {
+                open_links(state, &post);
+            }
+            None
+        }
+        action @ (Action::CopyText | Action::CopyUrl) => {
+            if let Some(post) = state.thread_node(state.thread_selected).cloned() {
+                copy_post(state, &post, action);
+            }
+            None
+        }
+        Action::Profile => {
+            let post = state.thread_node(state.thread_selected).cloned()?;
+            open_author_profile(state, &post)
+        }
+        _ => None,
+    }
+}
+
+fn handle_profile_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncCommand> {
+    let action = state.keymap.action(key)?;
+    match action {
+        Action::Back | Action::Quit => {
+            state.close_profile();
+            None
+        }
+        Action::ToggleTimes => {
+            state.toggle_absolute_times();
+            None
+        }
+        Action::NextPost => {
+            // Already at the last post: fetch the next page
+            if state.selected_profile_post + 1 >= state.profile_posts.len() {
+                return state.load_more_profile_command();
+            }
+            state.select_next_profile_post();
+            None
+        }
+        Action::PrevPost => {
+            state.select_prev_profile_post();
+            None
+        }
+        Action::Top => {
+            state.selected_profile_post = 0;
+            None
+        }
+        Action::Bottom => {
+            if state.selected_profile_post + 1 >= state.profile_posts.len() {
+                return state.load_more_profile_command();
+            }
+            state.selected_profile_post = state.profile_posts.len() - 1;
+            None
+        }
+        Action::Refresh => {
+            let account = state.profile_account.clone()?;
+            let handle = state.profile_handle.clone();
+            Some(state.open_profile(handle, account))
+        }
+        Action::Reply => {
+            if let Some(post) = state.selected_profile_post().cloned() {
+                state.open_reply(post);
+            }
+            None
+        }
+        Action::ToggleCw => {
+            if let Some(post) = state.selected_profile_post().cloned() {
+                state.toggle_cw(&post);
+            }
+            None
+        }
+        Action::Open | Action::Select => {
+            // The selected post, or the profile itself when there are no posts
+            let url = state
+                .selected_profile_post()
+                .and_then(|p| p.url.clone())
+                .or_else(|| state.profile.as_ref().and_then(|p| p.url.clone()));
+            if let Some(url) = url {
+                let _ = open::that(url);
+                state.set_status("✓ Opened in browser");
+            }
+            None
+        }
+        Action::Links => {
+            if let Some(post) = state.selected_profile_post().cloned() {
+                open_links(state, &post);
+            }
+            None
+        }
+        action @ (Action::CopyText | Action::CopyUrl) => {
+            if let Some(post) = state.selected_profile_post().cloned() {
+                copy_post(state, &post, action);
+            }
+            None
+        }
+        Action::Follow => state.toggle_follow_command(),
+        Action::Mute | Action::Block => {
+            let profile = state.profile.clone()?;
+            let account = state.profile_account.clone()?;
+            let (moderation, active) = if action == Action::Mute {
+                (Moderation::Mute, profile.muted)
+            } else {
+                (Moderation::Block, profile.blocked)
+            };
+            let undo =
+                active || state.is_moderated(profile.network, &profile.handle, Some(moderation));
+            state.ask_moderation(moderation, undo, profile.network, profile.handle, account);
+            None
+        }
+        Action::Profile => {
+            // A repost's original author
+            let post = state.selected_profile_post().cloned()?;
+            if post.author_handle == state.profile_handle {
+                return None;
+            }
+            open_author_profile(state, &post)
+        }
+        _ => None,
+    }
+}
+
+/// The post or reply that timeline actions on an author apply to
+fn focused_post(state: &AppState) -> Option<Post> {
+    if state.focused_panel == FocusedPanel::Detail {
+        state
+            .selected_reply
+            .and_then(|idx| state.current_replies.get(idx))
+            .map(|r| r.post.clone())
+            .or_else(|| state.selected_post().cloned())
+    } else {
+        state.selected_post().cloned()
+    }
+}
+
+/// Ask to confirm muting or blocking the author of `post`
+fn moderate_author(state: &mut AppState, post: &Post, moderation: Moderation) {
+    if state.own_account_for_post(post).is_some() {
+        state.set_status(format!("⚠ You can't {} yourself", moderation.verb(false)));
+        return;
+    }
+    let Some(account) = find_account_for_post(state, post) else {
+        state.set_status("⚠ No matching account for this network");
+        return;
+    };
+    state.ask_moderation(
+        moderation,
+        false,
+        post.network,
+        post.author_handle.clone(),
+        account,
+    );
+}
+
+/// Open the profile view for the author of `post`, looked up through the
+/// account the post was loaded with
+fn open_author_profile(state: &mut AppState, post: &Post) -> Option<AsyncCommand> {
+    let Some(account) = find_account_for_post(state, post) else {
+        state.set_status(format!(
+            "⚠ Add a {} account to view profiles",
+            post.network.name()
+        ));
+        return None;
+    };
+    Some(state.open_profile(post.author_handle.clone(), account))
+}
+
+/// Open the one link in `post`, or offer a picker when there are several
+fn open_links(state: &mut AppState, post: &Post) {
+    let links = post.links();
+    match links.as_slice() {
+        [] => state.set_status("No links in this post"),
+        [url] => {
+            let _ = open::that(url);
+            state.set_status("✓ Opened in browser");
+        }
+        _ => state.open_link_picker(links),
+    }
+}
+
+/// Copy a post's text (`CopyText`) or URL (`CopyUrl`), showing it in the
+/// status bar instead when there's no clipboard
+fn copy_post(state: &mut AppState, post: &Post, action: Action) {
+    let (text, what) = if action == Action::CopyUrl {
+        let Some(url) = post.url.clone() else {
+            state.set_status("⚠ This post has no URL");
+            return;
+        };
+        (url, "link")
+    } else {
+        (post.content.clone(), "post text")
+    };
+
+    match clipboard::copy(&text) {
+        Ok(()) => state.set_status(format!("✓ Copied {what}")),
+        Err(e) => state.set_status(format!("⚠ {e}, {what}: {}", text.replace('\n', " "))),
+    }
+}
+
+fn handle_link_picker_key(state: &mut AppState, key: KeyEvent) {
+    let picked = match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            state.close_link_picker();
+            return;
+        }
+        KeyCode::Enter | KeyCode::Char('o') => state.selected_link,
+        // Number keys pick a link directly
+        KeyCode::Char(c @ '1'..='9') => c as usize - '1' as usize,
+        KeyCode::Down | KeyCode::Char('j') if state.selected_link + 1 < state.links.len() => {
+            state.selected_link += 1;
+            return;
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            state.selected_link = state.selected_link.saturating_sub(1);
+            return;
+        }
+        KeyCode::Home | KeyCode::Char('g') => {
+            state.selected_link = 0;
+            return;
+        }
+        KeyCode::End | KeyCode::Char('G') => {
+            state.selected_link = state.links.len().saturating_sub(1);
+            return;
+        }
+        _ => return,
+    };
+
+    if let Some(url) = state.links.get(picked).cloned() {
+        state.close_link_picker();
+        let _ = open::that(&url);
+        state.set_status(format!("✓ Opened {url}"));
+    }
+}
+
+fn handle_compose_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncCommand> {
+    // Ctrl+C quits from compose too, asking first if there's text to lose
+    if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+        state.request_quit();
+        return None;
+    }
+
+    // Handle attachment path input
+    if state.compose_attach_focused {
+        match key.code {
+            KeyCode::Esc => {
+                state.compose_attach_input.clear();
+                state.compose_attach_focused = false;
+            }
+            KeyCode::Enter => match state.attach_media() {
+                Ok(()) => {
+                    state.compose_attach_focused = false;
+                    state.set_status(format!(
+                        "📎 {} image(s) attached",
+                        state.compose_media.len()
+                    ));
+                }
+                Err(e) => state.set_status(format!("⚠ {e}")),
+            },
+            KeyCode::Char(c) => state.compose_attach_input.push(c),
+            KeyCode::Backspace => {
+                state.compose_attach_input.pop();
+            }
+            _ => {}
+        }
+        return None;
+    }
+
+    // Handle the account picker
+    if state.compose_accounts_focused {
+        let count = state.accounts.len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Tab | KeyCode::F(7) => {
+                state.compose_accounts_focused = false;
+            }
+            KeyCode::Char('j' | 'l') | KeyCode::Down | KeyCode::Right if count > 0 => {
+                state.selected_compose_account = (state.selected_compose_account + 1) % count;
+            }
+            KeyCode::Char('k' | 'h') | KeyCode::Up | KeyCode::Left if count > 0 => {
+                state.selected_compose_account =
+                    (state.selected_compose_account + count - 1) % count;
+            }
+            KeyCode::Char(' ') => state.toggle_compose_account(state.selected_compose_account),
+            KeyCode::Char(c @ '1'..='9') => {
+                let index = c as usize - '1' as usize;
+                state.toggle_compose_account(index);
+            }
+            _ => {}
+        }
+        return None;
+    }
+
+    // Handle content warning input
+    if state.compose_cw_focused {
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Tab | KeyCode::F(6) => {
+                state.compose_cw_focused = false;
+            }
+            KeyCode::Char(c) => state.compose_cw.push(c),
+            KeyCode::Backspace => {
+                state.compose_cw.pop();
+            }
+            _ => {}
+        }
+        return None;
+    }
+
+    // Handle schedule input focus mode
+    if state.compose_schedule_focused {
+        match (key.modifiers, key.code) {
+            (_, KeyCode::Esc) => {
+                // Clear schedule and go back to text
+                state.compose_schedule_focused = false;
+                None
+            }
+            (_, KeyCode::Tab | KeyCode::Enter) => {
+                // Apply schedule and go back to text
+                if let Err(e) = state.apply_schedule_input() {
+                    state.set_status(format!("⚠ Invalid schedule: {}", e));
+                } else if state.compose_schedule.is_some() {
+                    state.set_status("📅 Post scheduled");
+                }
+                state.compose_schedule_focused = false;
+                None
+            }
+            (KeyModifiers::CONTROL, KeyCode::Char('u')) => {
+                // Clear schedule input
+                state.compose_schedule_input.clear();
+                state.compose_schedule = None;
+                None
+            }
+            (_, KeyCode::Char(c)) => {
+                state.compose_schedule_input.push(c);
+                // Try to parse as we type
+                let _ = state.apply_schedule_input();
+                None
+            }
+            (_, KeyCode::Backspace) => {
+                state.compose_schedule_input.pop();
+                // Try to parse as we type
+                let _ = state.apply_schedule_input();
+                None
             }
             _ => None,
         }
@@ -488,61 +1203,162 @@ fn handle_compose_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncComman
         // Normal compose mode (text input)
         match (key.modifiers, key.code) {
             (_, KeyCode::Esc) => {
+                if state.save_compose_draft() {
+                    state.set_status("💾 Saved as draft (w to reopen)");
+                }
                 state.close_compose();
                 None
             }
             // Enter for new line
             (KeyModifiers::NONE, KeyCode::Enter) => {
-                state.compose_text.push('\n');
+                state.compose_insert("\n");
                 None
             }
             // Ctrl+S to post
             (KeyModifiers::CONTROL, KeyCode::Char('s')) => {
+                let length = state.compose_text.chars().count();
+                let limit = state.compose_char_limit();
+                // A thread is split to fit each account
+                let threaded = state.compose_thread
+                    && state.compose_edit.is_none()
+                    && state.compose_scheduled.is_none();
+                if length > limit && !threaded {
+                    state.set_status(format!(
+                        "⚠ Post is {length} characters, the limit is {limit}"
+                    ));
+                    return None;
+                }
+                if let Some(post) = state.compose_edit.clone() {
+                    return submit_edit(state, post);
+                }
+                if let Some(post) = state.compose_scheduled.clone() {
+                    submit_scheduled_edit(state, post);
+                    return None;
+                }
                 // Post
-                if !state.compose_text.is_empty() && !state.compose_networks.is_empty() {
+                if !state.compose_text.is_empty() && !state.compose_accounts.is_empty() {
                     let content = state.compose_text.clone();
                     let reply_to = state.reply_to.clone();
+                    let quote = state.compose_quote.clone().map(Box::new);
                     let schedule = state.compose_schedule;
-                    // Find accounts matching selected networks
-                    let accounts: Vec<_> = state
-                        .accounts
+                    let media = state.compose_media.clone();
+                    let options = PostOptions {
+                        visibility: state.compose_visibility,
+                        spoiler_text: Some(state.compose_cw.trim().to_string())
+                            .filter(|cw| !cw.is_empty()),
+                        thread: state.compose_thread,
+                    };
+                    let accounts = state.compose_posting_accounts();
+
+                    if schedule.is_some() && !media.is_empty() {
+                        state.set_status("⚠ Scheduled posts can't include images yet");
+                        return None;
+                    }
+
+                    if schedule.is_some() && !state.compose_uses_default_accounts() {
+                        state.set_status(
+                            "⚠ Scheduled posts go out from each network's default account",
+                        );
+                        return None;
+                    }
+
+                    if schedule.is_some() && options.thread && length > limit {
+                        state.set_status("⚠ Scheduled posts can't be threads yet");
+                        return None;
+                    }
+
+                    if schedule.is_some() && quote.is_some() {
+                        state.set_status("⚠ Scheduled posts can't quote yet");
+                        return None;
+                    }
+
+                    // Only Mastodon has content warnings, and it keeps them when the
+                    // server schedules the post
+                    let server_account = accounts
                         .iter()
-                        .filter(|a| state.compose_networks.contains(&a.network))
-                        .cloned()
-                        .collect();
+                        .find(|a| a.network.capabilities().server_scheduling)
+                        .filter(|_| state.compose_server_side)
+                        .cloned();
+                    if schedule.is_some()
+                        && options.spoiler_text.is_some()
+                        && server_account.is_none()
+                    {
+                        state.set_status(
+                            "⚠ Scheduled posts can't include content warnings yet (F8 schedules on Mastodon)",
+                        );
+                        return None;
+                    }
 
-                    if accounts.is_empty() {
-                        state.set_status("⚠ No accounts for selected networks");
+                    if let Err(e) = state.check_alt_text() {
+                        state.set_status(format!("⚠ {e}"));
                         return None;
                     }
 
                     state.loading = true;
+                    state.sending_draft = state.compose_draft.as_ref().map(|d| d.id);
                     state.close_compose();
 
                     // If scheduled, save to database instead of posting
                     if let Some(scheduled_for) = schedule {
-                        let networks: Vec<_> = accounts.iter().map(|a| a.network).collect();
+                        let networks: Vec<_> = accounts
+                            .iter()
+                            .map(|a| a.network)
+                            .filter(|n| server_account.as_ref().is_none_or(|a| a.network != *n))
+                            .collect();
                         Some(AsyncCommand::SchedulePost {
                             content,
                             networks,
                             scheduled_for,
+                            server_account,
+                            options,
                         })
                     } else {
                         Some(AsyncCommand::Post {
                             content,
                             accounts,
                             reply_to,
+                            quote,
+                            media,
+                            options,
                         })
                     }
                 } else {
                     if state.compose_text.is_empty() {
                         state.set_status("⚠ Write something first!");
                     } else {
-                        state.set_status("⚠ Select at least one network");
+                        state.set_status("⚠ Select at least one account (F7)");
                     }
                     None
                 }
             }
+            // Ctrl+E to write the post in $EDITOR
+            (KeyModifiers::CONTROL, KeyCode::Char('e')) => {
+                state.pending_editor = true;
+                None
+            }
+            // Ctrl+A to attach an image
+            (KeyModifiers::CONTROL, KeyCode::Char('a')) => {
+                if state.reply_to.is_some() {
+                    state.set_status("⚠ Replies can't include images yet");
+                } else if state.compose_quote.is_some() {
+                    state.set_status("⚠ Quotes can't include images yet");
+                } else if state.compose_media.len() >= MAX_MEDIA_ATTACHMENTS {
+                    state.set_status(format!("⚠ At most {MAX_MEDIA_ATTACHMENTS} images per post"));
+                } else {
+                    state.compose_attach_focused = true;
+                }
+                None
+            }
+            // Ctrl+X to remove the last attached image
+            (KeyModifiers::CONTROL, KeyCode::Char('x')) => {
+                if state.compose_media.pop().is_some() {
+                    state.set_status(format!(
+                        "📎 {} image(s) attached",
+                        state.compose_media.len()
+                    ));
+                }
+                None
+            }
             // Tab to switch to schedule input
             (_, KeyCode::Tab) => {
                 state.compose_schedule_focused = true;
@@ -559,6 +1375,58 @@ fn handle_compose_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncComman
                 state.set_status("📅 Schedule cleared - will post immediately");
                 None
             }
+            // F5 to cycle Mastodon visibility
+            (_, KeyCode::F(5)) => {
+                if state.compose_visibility_enabled() {
+                    state.compose_visibility = state.compose_visibility.next();
+                } else {
+                    state.set_status("⚠ Visibility only applies to Mastodon");
+                }
+                None
+            }
+            // F6 to edit the content warning
+            (_, KeyCode::F(6)) => {
+                if state.compose_visibility_enabled() {
+                    state.compose_cw_focused = true;
+                } else {
+                    state.set_status("⚠ Content warnings only apply to Mastodon");
+                }
+                None
+            }
+            // F7 to pick which accounts to post from
+            (_, KeyCode::F(7)) => {
+                if state.accounts.is_empty() {
+                    state.set_status("⚠ No accounts configured");
+                } else {
+                    state.selected_compose_account = 0;
+                    state.compose_accounts_focused = true;
+                }
+                None
+            }
+            // F8 to have the Mastodon server publish a scheduled post
+            (_, KeyCode::F(8)) => {
+                if state.compose_visibility_enabled() {
+                    state.compose_server_side = !state.compose_server_side;
+                    state.set_status(if state.compose_server_side {
+                        "🐘 Mastodon will publish the scheduled post itself"
+                    } else {
+                        "📅 Perch will publish the scheduled post"
+                    });
+                } else {
+                    state.set_status("⚠ Server-side scheduling only applies to Mastodon");
+                }
+                None
+            }
+            // F9 to split long posts into threads
+            (_, KeyCode::F(9)) => {
+                state.compose_thread = !state.compose_thread;
+                state.set_status(if state.compose_thread {
+                    "🧵 Posts too long for an account go out as a thread"
+                } else {
+                    "Posts too long for an account won't be sent"
+                });
+                None
+            }
             (_, KeyCode::F(1)) => {
                 state.toggle_compose_network(Network::Mastodon);
                 None
@@ -568,11 +1436,48 @@ fn handle_compose_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncComman
                 None
             }
             (_, KeyCode::Char(c)) => {
-                state.compose_text.push(c);
+                state.compose_insert(c.encode_utf8(&mut [0; 4]));
                 None
             }
             (_, KeyCode::Backspace) => {
-                state.compose_text.pop();
+                state.compose_backspace();
+                None
+            }
+            (_, KeyCode::Delete) => {
+                state.compose_delete();
+                None
+            }
+            // Ctrl+Left/Right move by word
+            (KeyModifiers::CONTROL, KeyCode::Left) => {
+                state.move_compose_cursor(text_cursor::prev_word);
+                None
+            }
+            (KeyModifiers::CONTROL, KeyCode::Right) => {
+                state.move_compose_cursor(text_cursor::next_word);
+                None
+            }
+            (_, KeyCode::Left) => {
+                state.move_compose_cursor(text_cursor::prev_char);
+                None
+            }
+            (_, KeyCode::Right) => {
+                state.move_compose_cursor(text_cursor::next_char);
+                None
+            }
+            (_, KeyCode::Up) => {
+                state.move_compose_cursor(text_cursor::line_up);
+                None
+            }
+            (_, KeyCode::Down) => {
+                state.move_compose_cursor(text_cursor::line_down);
+                None
+            }
+            (_, KeyCode::Home) => {
+                state.move_compose_cursor(text_cursor::line_start);
+                None
+            }
+            (_, KeyCode::End) => {
+                state.move_compose_cursor(text_cursor::line_end);
                 None
             }
             _ => None,
@@ -586,30 +1491,32 @@ fn handle_search_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncCommand
             state.mode = Mode::Normal;
             state.search_query.clear();
         }
+        KeyCode::Tab => {
+            state.search_scope = state.search_scope.toggle();
+        }
+        KeyCode::Enter
+            if state.search_scope == SearchScope::Server
+                && !state.search_query.trim().is_empty() =>
+        {
+            let cmd = state.server_search_command();
+            if cmd.is_some() {
+                state.set_status(format!("Searching for '{}'...", state.last_search));
+            }
+            return cmd;
+        }
         KeyCode::Enter => {
-            if !state.search_query.is_empty() {
-                // Filter posts by search query (local search)
-                let query = state.search_query.to_lowercase();
-                let filtered: Vec<_> = state
-                    .posts
-                    .iter()
-                    .filter(|p| {
-                        p.content.to_lowercase().contains(&query)
-                            || p.author_handle.to_lowercase().contains(&query)
-                            || p.author_name.to_lowercase().contains(&query)
-                    })
-                    .cloned()
-                    .collect();
-
-                let count = filtered.len();
-                state.posts = filtered;
-                state.selected_post = 0;
-                state.mode = Mode::Normal;
-                state.set_status(format!(
-                    "✓ Found {} posts matching '{}'",
-                    count, state.search_query
-                ));
-                state.search_query.clear();
+            if !state.search_query.trim().is_empty() {
+                // Search everything cached, not just what's on screen
+                match state.run_search() {
+                    Ok(count) => state.set_status(format!(
+                        "🔍 {count} cached posts match '{}'",
+                        state.last_search
+                    )),
+                    Err(e) => {
+                        state.mode = Mode::Normal;
+                        state.set_status(format!("❌ Search failed: {e}"));
+                    }
+                }
             }
         }
         KeyCode::Char(c) => {
@@ -623,6 +1530,40 @@ fn handle_search_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncCommand
     None
 }
 
+fn handle_search_results_key(state: &mut AppState, key: KeyEvent) {
+    match (key.modifiers, key.code) {
+        (_, KeyCode::Esc | KeyCode::Char('q')) => {
+            // Back to the timeline, which the search never touched
+            state.mode = Mode::Normal;
+            state.clear_status();
+        }
+        (_, KeyCode::Char('j') | KeyCode::Down) => state.select_next_search_result(),
+        (_, KeyCode::Char('k') | KeyCode::Up) => state.select_prev_search_result(),
+        (_, KeyCode::Char('g')) => state.selected_search_result = 0,
+        (KeyModifiers::SHIFT, KeyCode::Char('G')) => {
+            state.selected_search_result = state.search_results.len().saturating_sub(1);
+        }
+        (_, KeyCode::Char('/')) => state.mode = Mode::Search,
+        (KeyModifiers::SHIFT, KeyCode::Char('R')) => {
+            if let Some(post) = state.selected_search_result().cloned() {
+                state.open_reply(post);
+            }
+        }
+        (_, KeyCode::Char('c')) => {
+            if let Some(post) = state.selected_search_result().cloned() {
+                state.toggle_cw(&post);
+            }
+        }
+        (_, KeyCode::Char('o') | KeyCode::Enter) => {
+            if let Some(url) = state.selected_search_result().and_then(|p| p.url.clone()) {
+                let _ = open::that(url);
+                state.set_status("✓ Opened in browser");
+            }
+        }
+        _ => {}
+    }
+}
+
 fn handle_theme_picker_key(state: &mut AppState, key: KeyEvent) {
     let themes = Theme::all();
     let len = themes.len();
@@ -634,7 +1575,7 @@ fn handle_theme_picker_key(state: &mut AppState, key: KeyEvent) {
         }
         KeyCode::Enter => {
             // Apply selected theme
-            let selected_theme = Theme::from(themes[state.theme_picker_index]);
+            let selected_theme = themes[state.theme_picker_index];
             state.theme = selected_theme;
             state.config.theme = selected_theme;
 
@@ -644,20 +1585,20 @@ fn handle_theme_picker_key(state: &mut AppState, key: KeyEvent) {
         KeyCode::Down | KeyCode::Char('j') => {
             state.theme_picker_index = (state.theme_picker_index + 1) % len;
             // Preview theme
-            state.theme = Theme::from(themes[state.theme_picker_index]);
+            state.theme = themes[state.theme_picker_index];
         }
         KeyCode::Up | KeyCode::Char('k') => {
             state.theme_picker_index = state.theme_picker_index.checked_sub(1).unwrap_or(len - 1);
             // Preview theme
-            state.theme = Theme::from(themes[state.theme_picker_index]);
+            state.theme = themes[state.theme_picker_index];
         }
         KeyCode::Home | KeyCode::Char('g') => {
             state.theme_picker_index = 0;
-            state.theme = Theme::from(themes[state.theme_picker_index]);
+            state.theme = themes[state.theme_picker_index];
         }
         KeyCode::End | KeyCode::Char('G') => {
             state.theme_picker_index = len - 1;
-            state.theme = Theme::from(themes[state.theme_picker_index]);
+            state.theme = themes[state.theme_picker_index];
         }
         _ => {}
     }
@@ -697,14 +1638,219 @@ fn handle_update_confirm_key(state: &mut AppState, key: KeyEvent) {
     }
 }
 
-/// Find an account that matches the network of a post
+/// Send the compose buffer as an edit of an existing post
+fn submit_edit(state: &mut AppState, post: crate::models::Post) -> Option<AsyncCommand> {
+    if state.compose_text.trim().is_empty() {
+        state.set_status("⚠ Write something first!");
+        return None;
+    }
+    if state.compose_schedule.is_some()
+        || !state.compose_media.is_empty()
+        || !state.compose_cw.trim().is_empty()
+    {
+        state.set_status("⚠ Edits can only change the post text");
+        return None;
+    }
+    let Some(account) = state.own_account_for_post(&post) else {
+        state.set_status("⚠ No matching account for this post");
+        return None;
+    };
+
+    let content = state.compose_text.clone();
+    state.loading = true;
+    state.close_compose();
+    state.set_status("Saving edit...");
+    Some(AsyncCommand::Edit {
+        post,
+        content,
+        account,
+    })
+}
+
+/// Save changes to a scheduled post reopened from the scheduled posts picker
+fn submit_scheduled_edit(state: &mut AppState, mut post: crate::ScheduledPost) {
+    let Some(scheduled_for) = state.compose_schedule else {
+        state.set_status("⚠ Set when to post (Tab), or cancel it from the list (S)");
+        return;
+    };
+    if !state.compose_media.is_empty() {
+        state.set_status("⚠ Scheduled posts can't include images yet");
+        return;
+    }
+    if !state.compose_cw.trim().is_empty() {
+        state.set_status("⚠ Scheduled posts can't include content warnings yet");
+        return;
+    }
+    if !state.compose_uses_default_accounts() {
+        state.set_status("⚠ Scheduled posts go out from each network's default account");
+        return;
+    }
+
+    let networks = state
+        .compose_posting_accounts()
+        .iter()
+        .map(|a| a.network)
+        .collect();
+    let saved = post
+        .edit(
+            Some(state.compose_text.clone()),
+            Some(networks),
+            Some(scheduled_for),
+        )
+        .and_then(|()| state.db.update_scheduled_post(&post));
+
+    match saved {
+        Ok(()) => {
+            state.close_compose();
+            state.set_status(format!(
+                "📅 Updated [{}] for {}",
+                &post.id.to_string()[..8],
+                post.scheduled_time_display(&state.config.timezone)
+            ));
+        }
+        Err(e) => state.set_status(format!("⚠ {e}")),
+    }
+}
+
+fn handle_scheduled_key(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            state.mode = Mode::Normal;
+        }
+        KeyCode::Enter => {
+            if let Some(post) = state.scheduled_posts.get(state.selected_scheduled).cloned() {
+                state.mode = Mode::Normal;
+                state.open_scheduled(post);
+            }
+        }
+        KeyCode::Down | KeyCode::Char('j')
+            if state.selected_scheduled + 1 < state.scheduled_posts.len() =>
+        {
+            state.selected_scheduled += 1;
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            state.selected_scheduled = state.selected_scheduled.saturating_sub(1);
+        }
+        KeyCode::Home | KeyCode::Char('g') => {
+            state.selected_scheduled = 0;
+        }
+        KeyCode::End | KeyCode::Char('G') => {
+            state.selected_scheduled = state.scheduled_posts.len().saturating_sub(1);
+        }
+        KeyCode::Delete | KeyCode::Char('d') => {
+            if let Some(post) = state.scheduled_posts.get(state.selected_scheduled) {
+                let _ = state.db.cancel_scheduled_post(post.id);
+                state.load_scheduled_posts();
+                state.set_status("🚫 Scheduled post cancelled");
+                if state.scheduled_posts.is_empty() {
+                    state.mode = Mode::Normal;
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn handle_drafts_key(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            state.mode = Mode::Normal;
+        }
+        KeyCode::Enter => {
+            if let Some(draft) = state.drafts.get(state.selected_draft).cloned() {
+                state.mode = Mode::Normal;
+                state.open_draft(draft);
+            }
+        }
+        KeyCode::Down | KeyCode::Char('j') if state.selected_draft + 1 < state.drafts.len() => {
+            state.selected_draft += 1;
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            state.selected_draft = state.selected_draft.saturating_sub(1);
+        }
+        KeyCode::Home | KeyCode::Char('g') => {
+            state.selected_draft = 0;
+        }
+        KeyCode::End | KeyCode::Char('G') => {
+            state.selected_draft = state.drafts.len().saturating_sub(1);
+        }
+        KeyCode::Delete | KeyCode::Char('d') => {
+            if let Some(draft) = state.drafts.get(state.selected_draft) {
+                let _ = state.db.delete_draft(draft.id);
+                state.load_drafts();
+                state.set_status("🗑 Draft deleted");
+                if state.drafts.is_empty() {
+                    state.mode = Mode::Normal;
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn handle_confirm_delete_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncCommand> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('n' | 'N') => {
+            state.pending_delete = None;
+            state.mode = Mode::Normal;
+            None
+        }
+        KeyCode::Enter | KeyCode::Char('y' | 'Y') => {
+            state.mode = Mode::Normal;
+            match state.pending_delete.take()? {
+                PendingDelete::Post(post) => {
+                    let account = state.own_account_for_post(&post)?;
+                    state.set_status("Deleting...");
+                    Some(AsyncCommand::Delete {
+                        post: *post,
+                        account,
+                    })
+                }
+                PendingDelete::Account(account) => {
+                    // Signed out on the server first, while the token is
+                    // still stored; removed here once that's done
+                    state.set_status(format!("Signing out @{}...", account.handle));
+                    Some(AsyncCommand::RevokeAccount { account: *account })
+                }
+            }
+        }
+        _ => None,
+    }
+}
+
+fn handle_confirm_quit_key(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('n' | 'N') => state.mode = Mode::Compose,
+        KeyCode::Char('y' | 'Y') => state.should_quit = true,
+        KeyCode::Char('s' | 'S') if state.compose_is_draftable() => {
+            if state.save_compose_draft() {
+                state.should_quit = true;
+            } else {
+                state.set_status("❌ Failed to save draft");
+                state.mode = Mode::Compose;
+            }
+        }
+        _ => {}
+    }
+}
+
+fn handle_confirm_moderation_key(state: &mut AppState, key: KeyEvent) -> Option<AsyncCommand> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('n' | 'N') => {
+            if let Some(pending) = state.pending_moderation.take() {
+                state.mode = pending.return_mode;
+            }
+            None
+        }
+        KeyCode::Enter | KeyCode::Char('y' | 'Y') => state.confirm_moderation(),
+        _ => None,
+    }
+}
+
+/// Find the account to act on a post with (see [`crate::models::Post::acting_account`])
 fn find_account_for_post(
     state: &AppState,
     post: &crate::models::Post,
 ) -> Option<crate::models::Account> {
-    state
-        .accounts
-        .iter()
-        .find(|a| a.network == post.network)
-        .cloned()
+    post.acting_account(&state.accounts).cloned()
 }
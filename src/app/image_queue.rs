@@ -0,0 +1,154 @@
+//! Loading images a few at a time, most important first
+//!
+//! The TUI asks for every image around the selection at once. Requests wait
+//! in a queue and at most [`MAX_CONCURRENT`] download together, the selected
+//! post's image ahead of everything else. Requests for images that are no
+//! longer wanted (the user scrolled on) are dropped before they start and
+//! reported back as [`AsyncResult::ImageCancelled`].
+
+use tokio::sync::mpsc;
+use tokio::task::{JoinHandle, JoinSet};
+
+use super::async_ops::{AsyncResult, handle_load_image};
+use crate::images::DiskCache;
+
+/// Most images downloading at once
+const MAX_CONCURRENT: usize = 3;
+
+/// How soon an image is needed; higher loads first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ImagePriority {
+    /// An author's avatar
+    Avatar,
+    /// Other images of the selected post, and those in its replies
+    Media,
+    /// The image shown in the post detail
+    Selected,
+}
+
+/// An image to load
+#[derive(Debug, Clone)]
+pub struct ImageRequest {
+    /// Image URL
+    pub url: String,
+    /// Decode every frame if it's an animated GIF
+    pub animate: bool,
+    /// How soon it's needed
+    pub priority: ImagePriority,
+}
+
+enum QueueCommand {
+    Load(ImageRequest),
+    Retain(Vec<String>),
+}
+
+/// The image loading task and the queue feeding it
+pub struct ImageQueue {
+    tx: mpsc::UnboundedSender<QueueCommand>,
+    task: JoinHandle<()>,
+}
+
+impl ImageQueue {
+    /// Start loading images, sending them to `result_tx` as they arrive
+    pub fn new(result_tx: mpsc::Sender<AsyncResult>, disk_cache: Option<DiskCache>) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let task = tokio::spawn(run(rx, result_tx, disk_cache));
+        Self { tx, task }
+    }
+
+    /// Queue an image
+    pub fn load(&self, request: ImageRequest) {
+        let _ = self.tx.send(QueueCommand::Load(request));
+    }
+
+    /// Drop queued images that aren't in `urls`
+    pub fn retain(&self, urls: Vec<String>) {
+        let _ = self.tx.send(QueueCommand::Retain(urls));
+    }
+}
+
+impl Drop for ImageQueue {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn run(
+    mut rx: mpsc::UnboundedReceiver<QueueCommand>,
+    result_tx: mpsc::Sender<AsyncResult>,
+    disk_cache: Option<DiskCache>,
+) {
+    let mut queue: Vec<ImageRequest> = Vec::new();
+    let mut running = JoinSet::new();
+
+    loop {
+        while running.len() < MAX_CONCURRENT
+            && let Some(request) = next_request(&mut queue)
+        {
+            let result_tx = result_tx.clone();
+            let disk_cache = disk_cache.clone();
+            running.spawn(async move {
+                let ImageRequest { url, animate, .. } = request;
+                handle_load_image(&result_tx, url, animate, disk_cache.as_ref()).await;
+            });
+        }
+
+        tokio::select! {
+            command = rx.recv() => match command {
+                Some(QueueCommand::Load(request)) => {
+                    if !queue.iter().any(|r| r.url == request.url) {
+                        queue.push(request);
+                    }
+                }
+                Some(QueueCommand::Retain(urls)) => {
+                    let (keep, dropped) = std::mem::take(&mut queue)
+                        .into_iter()
+                        .partition(|r| urls.contains(&r.url));
+                    queue = keep;
+                    for ImageRequest { url, .. } in dropped {
+                        let _ = result_tx.send(AsyncResult::ImageCancelled { url }).await;
+                    }
+                }
+                None => break,
+            },
+            Some(_) = running.join_next(), if !running.is_empty() => {}
+        }
+    }
+}
+
+/// Take the most urgent request, oldest first among equals
+fn next_request(queue: &mut Vec<ImageRequest>) -> Option<ImageRequest> {
+    let index = queue
+        .iter()
+        .enumerate()
+        .max_by(|(i, a), (j, b)| a.priority.cmp(&b.priority).then(j.cmp(i)))?
+        .0;
+    Some(queue.remove(index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(url: &str, priority: ImagePriority) -> ImageRequest {
+        ImageRequest {
+            url: url.to_string(),
+            animate: false,
+            priority,
+        }
+    }
+
+    #[test]
+    fn test_next_request_order() {
+        let mut queue = vec![
+            request("avatar1", ImagePriority::Avatar),
+            request("reply", ImagePriority::Media),
+            request("avatar2", ImagePriority::Avatar),
+            request("selected", ImagePriority::Selected),
+        ];
+        let order: Vec<_> = std::iter::from_fn(|| next_request(&mut queue))
+            .map(|r| r.url)
+            .collect();
+        assert_eq!(order, ["selected", "reply", "avatar1", "avatar2"]);
+    }
+}
@@ -0,0 +1,516 @@
+//! Key bindings for normal-mode actions
+//!
+//! Defaults can be replaced per action in the `[keybindings]` config table.
+//! Keys inside dialogs (compose, search, pickers) are fixed.
+
+use anyhow::{Context, Result, bail};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::config::KeyList;
+
+/// Something a key can be bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Quit Perch
+    Quit,
+    /// Show help
+    Help,
+    /// Next tab
+    NextView,
+    /// Previous tab
+    PrevView,
+    /// Go to the timeline tab
+    ViewTimeline,
+    /// Go to the accounts tab
+    ViewAccounts,
+    /// Go to the notifications tab
+    ViewNotifications,
+    /// Open the theme picker
+    Theme,
+    /// Open saved drafts
+    Drafts,
+    /// Open pending scheduled posts
+    Scheduled,
+    /// Show the about dialog
+    About,
+    /// Install an available update
+    Update,
+    /// Focus the panel to the left
+    PanelLeft,
+    /// Focus the panel to the right
+    PanelRight,
+    /// Move down (posts, replies, accounts, notifications)
+    NextPost,
+    /// Move up
+    PrevPost,
+    /// Scroll the detail panel down
+    ScrollDown,
+    /// Scroll the detail panel up
+    ScrollUp,
+    /// Scroll the detail panel down a page
+    PageDown,
+    /// Scroll the detail panel up a page
+    PageUp,
+    /// Jump to the first item
+    Top,
+    /// Jump to the last item (again to load older posts)
+    Bottom,
+    /// Jump to the newest post in the timeline
+    Newest,
+    /// Write a new post
+    Compose,
+    /// Search posts
+    Search,
+    /// Refresh the timeline or hashtag feed
+    Refresh,
+    /// Reply to the selected post or reply
+    Reply,
+    /// Quote the selected post
+    Quote,
+    /// Show/hide a content warning
+    ToggleCw,
+    /// Show the full alt text of the selected post's media
+    AltText,
+    /// Load the selected post's images again after they failed
+    RetryImages,
+    /// Switch between relative and exact times
+    ToggleTimes,
+    /// Cycle the timeline layout
+    Layout,
+    /// Open the selected post in the browser
+    Open,
+    /// Open a link from the selected post
+    Links,
+    /// Copy the selected post's text
+    CopyText,
+    /// Copy the selected post's URL
+    CopyUrl,
+    /// Like/unlike
+    Like,
+    /// Bookmark/unbookmark (Mastodon)
+    Bookmark,
+    /// Edit your own post (Mastodon)
+    Edit,
+    /// Delete your own post, or remove the selected account
+    Delete,
+    /// Repost/undo repost
+    Repost,
+    /// Cycle the network filter
+    Filter,
+    /// Cycle home, local and federated timelines
+    TimelineKind,
+    /// Highlight the next hashtag in the post
+    Hashtag,
+    /// Show the profile of the selected post's author
+    Profile,
+    /// Follow or unfollow the account in the profile view
+    Follow,
+    /// Mute the selected post's author (or unmute, in the profile view)
+    Mute,
+    /// Block the selected post's author (or unblock, in the profile view)
+    Block,
+    /// Open the thread, hashtag feed or account timeline
+    Select,
+    /// Go back or clear the highlight
+    Back,
+}
+
+impl Action {
+    /// Every action, in the order they are documented
+    pub const ALL: &[Self] = &[
+        Self::Quit,
+        Self::Help,
+        Self::NextView,
+        Self::PrevView,
+        Self::ViewTimeline,
+        Self::ViewAccounts,
+        Self::ViewNotifications,
+        Self::Theme,
+        Self::Drafts,
+        Self::Scheduled,
+        Self::About,
+        Self::Update,
+        Self::PanelLeft,
+        Self::PanelRight,
+        Self::NextPost,
+        Self::PrevPost,
+        Self::ScrollDown,
+        Self::ScrollUp,
+        Self::PageDown,
+        Self::PageUp,
+        Self::Top,
+        Self::Bottom,
+        Self::Newest,
+        Self::Compose,
+        Self::Search,
+        Self::Refresh,
+        Self::Reply,
+        Self::Quote,
+        Self::ToggleCw,
+        Self::AltText,
+        Self::RetryImages,
+        Self::ToggleTimes,
+        Self::Layout,
+        Self::Open,
+        Self::Links,
+        Self::CopyText,
+        Self::CopyUrl,
+        Self::Like,
+        Self::Bookmark,
+        Self::Edit,
+        Self::Delete,
+        Self::Repost,
+        Self::Filter,
+        Self::TimelineKind,
+        Self::Hashtag,
+        Self::Profile,
+        Self::Follow,
+        Self::Mute,
+        Self::Block,
+        Self::Select,
+        Self::Back,
+    ];
+
+    /// Name used in the config file
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::Help => "help",
+            Self::NextView => "next_view",
+            Self::PrevView => "prev_view",
+            Self::ViewTimeline => "view_timeline",
+            Self::ViewAccounts => "view_accounts",
+            Self::ViewNotifications => "view_notifications",
+            Self::Theme => "theme",
+            Self::Drafts => "drafts",
+            Self::Scheduled => "scheduled",
+            Self::About => "about",
+            Self::Update => "update",
+            Self::PanelLeft => "panel_left",
+            Self::PanelRight => "panel_right",
+            Self::NextPost => "next_post",
+            Self::PrevPost => "prev_post",
+            Self::ScrollDown => "scroll_down",
+            Self::ScrollUp => "scroll_up",
+            Self::PageDown => "page_down",
+            Self::PageUp => "page_up",
+            Self::Top => "top",
+            Self::Bottom => "bottom",
+            Self::Newest => "newest",
+            Self::Compose => "compose",
+            Self::Search => "search",
+            Self::Refresh => "refresh",
+            Self::Reply => "reply",
+            Self::Quote => "quote",
+            Self::ToggleCw => "toggle_cw",
+            Self::AltText => "alt_text",
+            Self::RetryImages => "retry_images",
+            Self::ToggleTimes => "toggle_times",
+            Self::Layout => "layout",
+            Self::Open => "open",
+            Self::Links => "links",
+            Self::CopyText => "copy_text",
+            Self::CopyUrl => "copy_url",
+            Self::Like => "like",
+            Self::Bookmark => "bookmark",
+            Self::Edit => "edit",
+            Self::Delete => "delete",
+            Self::Repost => "repost",
+            Self::Filter => "filter",
+            Self::TimelineKind => "timeline_kind",
+            Self::Hashtag => "hashtag",
+            Self::Profile => "profile",
+            Self::Follow => "follow",
+            Self::Mute => "mute",
+            Self::Block => "block",
+            Self::Select => "select",
+            Self::Back => "back",
+        }
+    }
+
+    /// Keys bound when the config doesn't say otherwise
+    const fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Self::Quit => &["q"],
+            Self::Help => &["?", "f1"],
+            Self::NextView => &["tab"],
+            Self::PrevView => &["backtab"],
+            Self::ViewTimeline => &["1"],
+            Self::ViewAccounts => &["2"],
+            Self::ViewNotifications => &["3"],
+            Self::Theme => &["t"],
+            Self::Drafts => &["w"],
+            Self::Scheduled => &["S"],
+            Self::About => &["A"],
+            Self::Update => &["u", "U"],
+            Self::PanelLeft => &["h", "left"],
+            Self::PanelRight => &["l", "right"],
+            Self::NextPost => &["j", "down"],
+            Self::PrevPost => &["k", "up"],
+            Self::ScrollDown => &["J"],
+            Self::ScrollUp => &["K"],
+            Self::PageDown => &["ctrl+d", "pagedown"],
+            Self::PageUp => &["ctrl+u", "pageup"],
+            Self::Top => &["g", "home"],
+            Self::Bottom => &["G", "end"],
+            Self::Newest => &["N"],
+            Self::Compose => &["n"],
+            Self::Search => &["/"],
+            Self::Refresh => &["b"],
+            Self::Reply => &["R"],
+            Self::Quote => &["Q"],
+            Self::ToggleCw => &["c"],
+            Self::AltText => &["a"],
+            Self::RetryImages => &["i"],
+            Self::ToggleTimes => &["T"],
+            Self::Layout => &["v"],
+            Self::Open => &["o"],
+            Self::Links => &["O"],
+            Self::CopyText => &["y"],
+            Self::CopyUrl => &["Y"],
+            Self::Like => &["L"],
+            Self::Bookmark => &["m"],
+            Self::Edit => &["e"],
+            Self::Delete => &["D"],
+            Self::Repost => &["r"],
+            Self::Filter => &["f"],
+            Self::TimelineKind => &["F"],
+            Self::Hashtag => &["#"],
+            Self::Profile => &["p"],
+            Self::Follow => &["+"],
+            Self::Mute => &["M"],
+            Self::Block => &["B"],
+            Self::Select => &["enter"],
+            Self::Back => &["esc"],
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|a| a.name() == name)
+    }
+}
+
+/// A key with its modifiers, normalized so that the case of a character
+/// carries Shift
+type Key = (KeyCode, KeyModifiers);
+
+/// Which action each key triggers
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    bindings: HashMap<Key, Action>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self::from_config(&BTreeMap::new()).expect("default keybindings are valid")
+    }
+}
+
+impl KeyMap {
+    /// Build the key map from the `[keybindings]` config table. Keys listed
+    /// for an action replace its defaults.
+    ///
+    /// Unknown action names, unparseable keys and keys bound to two actions
+    /// are errors.
+    pub fn from_config(overrides: &BTreeMap<String, KeyList>) -> Result<Self> {
+        for name in overrides.keys() {
+            if Action::from_name(name).is_none() {
+                bail!("Unknown action '{name}'");
+            }
+        }
+
+        let mut bindings = HashMap::new();
+        for &action in Action::ALL {
+            let specs: Vec<&str> = match overrides.get(action.name()) {
+                Some(keys) => keys.specs().iter().map(String::as_str).collect(),
+                None => action.default_keys().to_vec(),
+            };
+            for spec in specs {
+                let key = parse_key(spec).with_context(|| format!("In '{}'", action.name()))?;
+                if let Some(other) = bindings.insert(key, action)
+                    && other != action
+                {
+                    bail!(
+                        "'{spec}' is bound to both '{}' and '{}'",
+                        other.name(),
+                        action.name()
+                    );
+                }
+            }
+        }
+
+        Ok(Self { bindings })
+    }
+
+    /// The action bound to a key press, if any
+    pub fn action(&self, key: KeyEvent) -> Option<Action> {
+        self.bindings
+            .get(&normalize(key.code, key.modifiers))
+            .copied()
+    }
+}
+
+/// Drop Shift where it is already part of the key (uppercase letters,
+/// `BackTab`). Letters with Control are matched case-insensitively, since
+/// terminals can't tell Ctrl+Shift+D from Ctrl+D.
+fn normalize(code: KeyCode, modifiers: KeyModifiers) -> Key {
+    match code {
+        KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => (
+            KeyCode::Char(c.to_ascii_lowercase()),
+            modifiers - KeyModifiers::SHIFT,
+        ),
+        KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => (
+            KeyCode::Char(c.to_ascii_uppercase()),
+            modifiers - KeyModifiers::SHIFT,
+        ),
+        KeyCode::BackTab => (code, modifiers - KeyModifiers::SHIFT),
+        _ => (code, modifiers),
+    }
+}
+
+/// Parse a key spec like `q`, `G`, `ctrl+d`, `shift+tab`, `enter` or `f1`
+fn parse_key(spec: &str) -> Result<Key> {
+    // `+` on its own (or at the end, as in `ctrl++`) is the plus key
+    let (modifier_part, key_part) = match spec.strip_suffix("++") {
+        Some(rest) => (Some(rest), "+"),
+        None if spec == "+" => (None, "+"),
+        None => match spec.rsplit_once('+') {
+            Some((modifiers, key)) => (Some(modifiers), key),
+            None => (None, spec),
+        },
+    };
+
+    let mut modifiers = KeyModifiers::NONE;
+    for modifier in modifier_part.into_iter().flat_map(|m| m.split('+')) {
+        modifiers |= match modifier.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => bail!("Unknown modifier '{modifier}' in '{spec}'"),
+        };
+    }
+
+    let mut chars = key_part.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match key_part.to_ascii_lowercase().as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                _ => bail!("Unknown key '{key_part}'"),
+            },
+        },
+    };
+
+    Ok(normalize(code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(
+            parse_key("q").unwrap(),
+            (KeyCode::Char('q'), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            parse_key("shift+g").unwrap(),
+            (KeyCode::Char('G'), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            parse_key("Ctrl+D").unwrap(),
+            (KeyCode::Char('d'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            parse_key("ctrl++").unwrap(),
+            (KeyCode::Char('+'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            parse_key("shift+tab").unwrap(),
+            (KeyCode::BackTab, KeyModifiers::NONE)
+        );
+        assert_eq!(
+            parse_key("F5").unwrap(),
+            (KeyCode::F(5), KeyModifiers::NONE)
+        );
+        assert!(parse_key("hyper+x").is_err());
+        assert!(parse_key("f13").is_err());
+    }
+
+    #[test]
+    fn test_defaults_match_terminal_events() {
+        let keymap = KeyMap::default();
+        assert_eq!(
+            keymap.action(press(KeyCode::Char('G'), KeyModifiers::SHIFT)),
+            Some(Action::Bottom)
+        );
+        assert_eq!(
+            keymap.action(press(KeyCode::BackTab, KeyModifiers::SHIFT)),
+            Some(Action::PrevView)
+        );
+        assert_eq!(
+            keymap.action(press(KeyCode::Char('d'), KeyModifiers::CONTROL)),
+            Some(Action::PageDown)
+        );
+        assert_eq!(
+            keymap.action(press(KeyCode::Char('d'), KeyModifiers::NONE)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_overrides_replace_defaults() {
+        let overrides = BTreeMap::from([
+            ("quit".to_string(), KeyList::One("x".to_string())),
+            (
+                "next_post".to_string(),
+                KeyList::Many(vec!["n".to_string(), "down".to_string()]),
+            ),
+            ("compose".to_string(), KeyList::One("c".to_string())),
+            ("toggle_cw".to_string(), KeyList::One("z".to_string())),
+        ]);
+        let keymap = KeyMap::from_config(&overrides).unwrap();
+
+        let action = |c| keymap.action(press(KeyCode::Char(c), KeyModifiers::NONE));
+        assert_eq!(action('x'), Some(Action::Quit));
+        assert_eq!(action('q'), None);
+        assert_eq!(action('n'), Some(Action::NextPost));
+        assert_eq!(action('c'), Some(Action::Compose));
+        assert_eq!(action('j'), None);
+    }
+
+    #[test]
+    fn test_conflicts_and_unknown_actions_are_errors() {
+        let conflict = BTreeMap::from([("like".to_string(), KeyList::One("q".to_string()))]);
+        let error = KeyMap::from_config(&conflict).unwrap_err().to_string();
+        assert!(
+            error.contains("'quit'") && error.contains("'like'"),
+            "{error}"
+        );
+
+        let unknown = BTreeMap::from([("fly".to_string(), KeyList::One("y".to_string()))]);
+        assert!(KeyMap::from_config(&unknown).is_err());
+    }
+}
@@ -1,20 +1,26 @@
 //! TUI Application module
 
 mod async_ops;
+mod clipboard;
+mod config_watch;
+mod editor;
 mod events;
+mod image_queue;
+mod keymap;
+mod snapshot;
 mod state;
+mod streaming;
+mod terminal;
+mod text_cursor;
 mod ui;
 
 pub use state::AppState;
+use state::Feed;
 pub use state::FocusedPanel;
 pub use state::ReplyItem;
 
 use anyhow::Result;
-use crossterm::{
-    event::{self, Event},
-    execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
-};
+use crossterm::event::{self, Event};
 use ratatui::prelude::*;
 use std::io::stdout;
 use std::time::Duration;
@@ -23,8 +29,11 @@ use tokio::runtime::Runtime;
 use crate::config::Config;
 use crate::db::Database;
 use crate::demo;
+use crate::images::DiskCache;
+use crate::models::{FollowState, Moderation};
+use crate::theme::Theme;
 
-use async_ops::{AsyncCommand, AsyncHandle, AsyncResult, spawn_worker};
+use async_ops::{AsyncCommand, AsyncHandle, AsyncResult, Operation, spawn_worker};
 
 /// Run the TUI application
 pub fn run() -> Result<()> {
@@ -33,61 +42,113 @@ pub fn run() -> Result<()> {
 
     // Load config
     let config = Config::load()?;
+    crate::theme::set_monochrome(config.color.is_monochrome());
 
     // Open database
     let db = Database::open()?;
 
+    // Open the on-disk image cache (images still load without it)
+    let image_cache = crate::paths::image_cache_dir()
+        .and_then(|dir| DiskCache::open(dir, config.image_cache_max_mb))
+        .ok();
+
     // Spawn async worker
-    let async_handle = rt.block_on(async { spawn_worker() });
+    let async_handle = rt.block_on(async { spawn_worker(image_cache) });
+
+    // Query terminal graphics support before the TUI takes over stdio
+    if config.show_avatars {
+        crate::images::init_picker();
+    }
 
     // Initialize terminal
-    enable_raw_mode()?;
-    let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
+    let terminal_guard = terminal::TerminalGuard::enter(config.mouse)?;
+    let backend = CrosstermBackend::new(stdout());
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
 
     // Create app state
     let mut state = AppState::new(config, db)?;
 
+    // Custom keybindings; the defaults stay in place if they don't load
+    let keymap_error = match keymap::KeyMap::from_config(&state.config.keybindings) {
+        Ok(keymap) => {
+            state.keymap = keymap;
+            None
+        }
+        Err(e) => Some(format!("⚠ Keybindings ignored, using defaults: {e:#}")),
+    };
+    let filters_error = crate::filters::Filters::new(&state.config.filters.rules)
+        .err()
+        .map(|e| format!("⚠ Filters off: {e:#}"));
+    let theme_error = match crate::theme::theme_warnings() {
+        [] => None,
+        [warning] => Some(format!("⚠ {warning}")),
+        [warning, rest @ ..] => Some(format!("⚠ {warning} (and {} more)", rest.len())),
+    };
+
     // Trigger initial refresh if we have accounts
-    if !state.accounts.is_empty() {
-        let _ = async_handle
-            .cmd_tx
-            .blocking_send(AsyncCommand::RefreshTimeline {
-                accounts: state.accounts.clone(),
-            });
-        state.loading = true;
+    if let Some(cmd) = state.refresh_timeline_command() {
+        send(&async_handle, &mut state, cmd);
         state.set_status("Loading timeline...");
-    }
 
-    // Spawn background update check
-    std::thread::spawn(|| {
-        if let crate::VersionCheck::UpdateAvailable { latest, .. } =
-            crate::check_for_updates_crates_io()
-        {
-            // We can't easily send to main thread without channels,
-            // but we can use a static or file. For simplicity, we'll check in the main loop.
-            // This thread just warms up the check.
-            let _ = latest;
+        // Fetch notifications too so the unread badge is populated
+        if let Some(cmd) = state.refresh_notifications_command() {
+            send(&async_handle, &mut state, cmd);
         }
-    });
+    }
+
+    // Stream new posts as they arrive, if enabled
+    if let Some(cmd) = state.stream_command() {
+        send(&async_handle, &mut state, cmd);
+    }
+
+    // Learn the post length limits of servers added before they were stored
+    for cmd in state.post_limit_commands() {
+        send(&async_handle, &mut state, cmd);
+    }
+
+    // Keyword filters set up on Mastodon, if synced
+    if let Some(cmd) = state.server_filters_command() {
+        send(&async_handle, &mut state, cmd);
+    }
+
+    if let Some(message) = keymap_error.or(filters_error).or(theme_error) {
+        state.set_status(message);
+    }
 
     // Main loop
     let result = run_app(&mut terminal, &mut state, async_handle, &rt);
 
     // Restore terminal
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
+    drop(terminal_guard);
+
+    // Keep the offline cache from growing without bound
+    if state.config.cache_max_posts > 0
+        && let Err(e) = state.db.trim_cache(state.config.cache_max_posts)
+    {
+        tracing::warn!("Failed to trim the post cache: {e}");
+    }
 
     result
 }
 
+/// Hand a command to the worker, keeping track of it for the status bar
+fn send(async_handle: &AsyncHandle, state: &mut AppState, cmd: AsyncCommand) {
+    if let Some(operation) = cmd.operation() {
+        state.start_operation(operation);
+    }
+    let _ = async_handle.cmd_tx.blocking_send(cmd);
+}
+
 /// Background message types
 enum BackgroundMsg {
     UpdateAvailable(String),
+    /// Notes for the available update, if there are any
+    ReleaseNotes(Option<String>),
+    /// What a running update is doing now
+    UpdateProgress(String),
+    /// A running update finished
+    Updated(Result<(), String>),
 }
 
 fn run_app(
@@ -98,59 +159,136 @@ fn run_app(
 ) -> Result<()> {
     // Channel for background messages
     let (bg_tx, bg_rx) = std::sync::mpsc::channel::<BackgroundMsg>();
+    let update_tx = bg_tx.clone();
 
-    // Spawn background update check
-    std::thread::spawn(move || {
-        if let crate::VersionCheck::UpdateAvailable { latest, .. } =
-            crate::check_for_updates_crates_io()
-        {
-            let _ = bg_tx.send(BackgroundMsg::UpdateAvailable(latest));
-        }
-    });
+    // Spawn background update check, unless it's turned off
+    if state.config.update_check_enabled() {
+        std::thread::spawn(move || {
+            if let crate::VersionCheck::UpdateAvailable { latest, .. } =
+                crate::check_for_updates_crates_io()
+            {
+                let _ = bg_tx.send(BackgroundMsg::UpdateAvailable(latest.clone()));
+                let notes = crate::update::fetch_release_notes(&latest);
+                let _ = bg_tx.send(BackgroundMsg::ReleaseNotes(notes));
+            }
+        });
+    }
+
+    // Images the view wanted last time round
+    let mut wanted_images: Vec<String> = Vec::new();
+
+    let mut config_watch = config_watch::ConfigWatch::new();
 
     loop {
         // Check for background messages (non-blocking)
-        if let Ok(msg) = bg_rx.try_recv() {
+        while let Ok(msg) = bg_rx.try_recv() {
             match msg {
                 BackgroundMsg::UpdateAvailable(version) => {
                     state.set_update_available(version);
                 }
+                BackgroundMsg::ReleaseNotes(notes) => state.release_notes = notes,
+                BackgroundMsg::UpdateProgress(status) => state.update_status = Some(status),
+                BackgroundMsg::Updated(result) => {
+                    state.finish_operation(Operation::Update);
+                    events::finish_update(state, result);
+                }
             }
         }
 
         // Process any async results
         while let Ok(result) = async_handle.result_rx.try_recv() {
             if let Some(cmd) = handle_async_result(state, result) {
-                let _ = async_handle.cmd_tx.blocking_send(cmd);
+                send(&async_handle, state, cmd);
             }
         }
 
         // Draw UI
         terminal.draw(|frame| ui::render(frame, state))?;
 
-        // Process pending update after draw (so "Updating..." is visible)
+        // Run a confirmed update in the background, so the overlay can show
+        // its progress
         if state.pending_update {
-            events::process_pending_update(state);
-            // Redraw immediately after update completes
+            state.pending_update = false;
+            state.start_operation(Operation::Update);
+            let package_manager = state.package_manager.clone();
+            let version = state.update_available.clone().unwrap_or_default();
+            let tx = update_tx.clone();
+            std::thread::spawn(move || {
+                let result = crate::run_update(&package_manager, &version, |status| {
+                    let _ = tx.send(BackgroundMsg::UpdateProgress(status.to_string()));
+                });
+                let _ = tx.send(BackgroundMsg::Updated(result));
+            });
+        }
+
+        // Hand the compose buffer to an external editor
+        if state.pending_editor {
+            editor::edit_compose(terminal, state)?;
             terminal.draw(|frame| ui::render(frame, state))?;
         }
 
         // Handle events
-        if event::poll(Duration::from_millis(50))?
-            && let Event::Key(key) = event::read()?
-            && let Some(cmd) = events::handle_key(state, key)
+        if event::poll(Duration::from_millis(50))? {
+            let cmd = match event::read()? {
+                Event::Key(key) => events::handle_key(state, key),
+                Event::Mouse(mouse) => events::handle_mouse(state, mouse),
+                Event::Resize(width, height) => {
+                    resize(terminal, state, width, height)?;
+                    None
+                }
+                _ => None,
+            };
+            if let Some(cmd) = cmd {
+                send(&async_handle, state, cmd);
+            }
+        }
+
+        // Pick up edits to the config file and custom themes
+        if config_watch
+            .as_mut()
+            .is_some_and(config_watch::ConfigWatch::changed)
         {
-            let _ = async_handle.cmd_tx.blocking_send(cmd);
+            state.reload_config();
+        }
+
+        // Replies for a post the selection has settled on
+        if let Some(cmd) = state.context_command() {
+            send(&async_handle, state, cmd);
+        }
+
+        // Periodic timeline refresh
+        if let Some(cmd) = state.auto_refresh_command() {
+            send(&async_handle, state, cmd);
+        }
+
+        // Drop queued images the user has scrolled away from
+        let wanted: Vec<String> = state
+            .wanted_images()
+            .into_iter()
+            .map(|(url, _)| url)
+            .collect();
+        if wanted != wanted_images {
+            if state.loading_images.iter().any(|url| !wanted.contains(url)) {
+                let _ = async_handle
+                    .cmd_tx
+                    .blocking_send(AsyncCommand::RetainImages {
+                        urls: wanted.clone(),
+                    });
+            }
+            wanted_images = wanted;
         }
 
         // Queue image loading for current post
         let images_to_load = state.get_images_to_load();
         if !images_to_load.is_empty() {
             state.mark_images_loading(&images_to_load);
-            for url in images_to_load {
-                let _ = async_handle
-                    .cmd_tx
-                    .blocking_send(AsyncCommand::LoadImage { url });
+            for (url, priority) in images_to_load {
+                let animate = state.plays_animation(&url);
+                let _ = async_handle.cmd_tx.blocking_send(AsyncCommand::LoadImage {
+                    url,
+                    animate,
+                    priority,
+                });
             }
         }
 
@@ -172,77 +310,362 @@ fn run_app(
 
 fn handle_async_result(state: &mut AppState, result: AsyncResult) -> Option<AsyncCommand> {
     match result {
-        AsyncResult::TimelineRefreshed { posts } => {
+        AsyncResult::TimelineRefreshed {
+            posts,
+            cursors,
+            kind,
+            background,
+        } => {
+            // The user switched timelines while this one loaded
+            if kind != state.timeline_kind {
+                return None;
+            }
+
             // Cache posts to database
+            let key = state.timeline_cache_key();
             for post in &posts {
-                let _ = state.db.cache_post(post);
+                let _ = state.db.cache_feed_post(&key, post);
             }
-            state.posts = posts;
-            state.selected_post = 0;
+            state.mark_refreshed();
+            if std::mem::take(&mut state.offline) {
+                state.set_status("Back online");
+            }
+
+            // Periodic refreshes only add what's new above the selection
+            if background {
+                state.add_new_posts(posts);
+                return None;
+            }
+
+            state.new_posts = 0;
+            state.replace_posts(posts);
+
+            // A hashtag feed is open: update the home timeline underneath
+            if let Some(home) = state.feed_stack.first_mut() {
+                home.timeline_cursors = cursors;
+                state.loading = false;
+                return None;
+            }
+            state.timeline_cursors = cursors;
+            state.loading_more = false;
             state.loading = false;
             state.set_status(format!("Loaded {} posts", state.posts.len()));
 
-            // Fetch replies for the first post
-            if let Some(post) = state.selected_post().cloned()
-                && let Some(account) = state.accounts.iter().find(|a| a.network == post.network)
-            {
-                return Some(AsyncCommand::FetchContext {
-                    post,
-                    account: account.clone(),
-                });
+            // Fetch replies for the selected post
+            state.schedule_context_fetch();
+            None
+        }
+        AsyncResult::MoreLoaded {
+            posts,
+            cursors,
+            kind,
+        } => {
+            state.loading_more = false;
+            if kind != state.timeline_kind {
+                return None;
+            }
+
+            let key = state.timeline_cache_key();
+            for post in &posts {
+                let _ = state.db.cache_feed_post(&key, post);
+            }
+            let added = state.append_posts(posts);
+            if let Some(home) = state.feed_stack.first_mut() {
+                // Requested before the hashtag feed was opened
+                home.timeline_cursors.extend(cursors);
+                return None;
+            }
+            state.timeline_cursors.extend(cursors);
+            if added == 0 {
+                state.set_status("No older posts");
+            } else {
+                state.set_status(format!("Loaded {added} older posts"));
+            }
+            None
+        }
+        AsyncResult::NotificationsRefreshed { notifications } => {
+            state.notifications = notifications;
+            state.loading_notifications = false;
+            state.selected_notification = state
+                .selected_notification
+                .min(state.notifications.len().saturating_sub(1));
+            if state.view == state::View::Notifications {
+                state.set_status(format!(
+                    "Loaded {} notifications",
+                    state.notifications.len()
+                ));
             }
             None
         }
         AsyncResult::ContextFetched {
-            post_id: _,
+            post_id,
+            ancestors,
             replies,
         } => {
+            // The selection moved on while these were loading
+            if state
+                .selected_post()
+                .is_none_or(|p| p.network_id != post_id)
+            {
+                return None;
+            }
+            // Keep the thread view focused on the post it was opened for
+            state.thread_selected = ancestors.len();
+            state.current_ancestors = ancestors;
             state.current_replies = replies;
             state.loading_replies = false;
             None
         }
-        AsyncResult::Liked { post_id } => {
-            // Update the post in our local state
-            if let Some(post) = state.posts.iter_mut().find(|p| p.network_id == post_id) {
-                post.liked = true;
-                post.like_count += 1;
-            }
+        // Likes and reposts were shown when the key was pressed
+        AsyncResult::Liked { .. } => {
             state.set_status("❤️ Liked!");
             None
         }
-        AsyncResult::Unliked { post_id } => {
-            if let Some(post) = state.posts.iter_mut().find(|p| p.network_id == post_id) {
-                post.liked = false;
-                post.like_count = post.like_count.saturating_sub(1);
-            }
+        AsyncResult::Unliked { .. } => {
             state.set_status("💔 Unliked");
             None
         }
-        AsyncResult::Reposted { post_id } => {
-            if let Some(post) = state.posts.iter_mut().find(|p| p.network_id == post_id) {
-                post.reposted = true;
-                post.repost_count += 1;
-            }
+        AsyncResult::Reposted { .. } => {
             state.set_status("🔁 Reposted!");
             None
         }
-        AsyncResult::Unreposted { post_id } => {
-            if let Some(post) = state.posts.iter_mut().find(|p| p.network_id == post_id) {
-                post.reposted = false;
-                post.repost_count = post.repost_count.saturating_sub(1);
+        AsyncResult::Unreposted { post_id, removed } => {
+            // No repost record existed, so the count shouldn't have dropped
+            if !removed && let Some(post) = state.posts.iter_mut().find(|p| p.network_id == post_id)
+            {
+                post.repost_count += 1;
             }
             state.set_status("↩️ Unreposted");
             None
         }
+        AsyncResult::ActionFailed {
+            network,
+            post_id,
+            action,
+            message,
+        } => {
+            state.apply_post_action(network, &post_id, action.inverse());
+            state.set_status(format!("❌ {message}"));
+            None
+        }
+        AsyncResult::Bookmarked { post_id } => {
+            state.set_bookmarked(&post_id, true);
+            state.set_status("🔖 Bookmarked");
+            None
+        }
+        AsyncResult::Unbookmarked { post_id } => {
+            state.set_bookmarked(&post_id, false);
+            state.set_status("Bookmark removed");
+            None
+        }
+        AsyncResult::Edited { post } => {
+            state.loading = false;
+            state.replace_post(&post);
+            state.set_status("✏️ Post edited");
+            None
+        }
+        AsyncResult::Deleted { network, post_id } => {
+            state.remove_post(network, &post_id);
+            state.set_status("🗑 Post deleted");
+
+            None
+        }
+        AsyncResult::HashtagLoaded { tag, posts } => {
+            // The user left this feed before it loaded
+            let feed = Feed::Hashtag(tag);
+            if state.feed != feed {
+                return None;
+            }
+
+            let key = feed.cache_key();
+            for post in &posts {
+                let _ = state.db.cache_feed_post(&key, post);
+            }
+            state.show_posts(posts);
+            state.selected_post = 0;
+            state.loading = false;
+            state.set_status(format!("Loaded {} posts", state.posts.len()));
+            state.schedule_context_fetch();
+            None
+        }
+        AsyncResult::ProfileLoaded {
+            handle,
+            profile,
+            page,
+        } => {
+            // Another profile was opened in the meantime
+            if handle != state.profile_handle {
+                return None;
+            }
+            state.profile = Some(*profile);
+            state.profile_posts = page.posts;
+            state.profile_cursor = page.next_cursor;
+            state.selected_profile_post = 0;
+            state.loading_profile = false;
+            None
+        }
+        AsyncResult::ProfilePostsLoaded { handle, page } => {
+            if handle != state.profile_handle {
+                return None;
+            }
+            state.loading_profile = false;
+            // An empty page means there are no older posts
+            state.profile_cursor = page.next_cursor.filter(|_| !page.posts.is_empty());
+            if page.posts.is_empty() {
+                state.set_status("No older posts");
+            } else {
+                state.set_status(format!("Loaded {} older posts", page.posts.len()));
+                state.profile_posts.extend(page.posts);
+            }
+            None
+        }
+        AsyncResult::ProfileFailed { handle, message } => {
+            if handle == state.profile_handle {
+                state.loading_profile = false;
+            }
+            state.set_status(format!("❌ {message}"));
+            None
+        }
+        AsyncResult::FollowChanged { profile } => {
+            let message = match profile.follow_state {
+                FollowState::Following => format!("✓ Following @{}", profile.handle),
+                FollowState::Requested => {
+                    format!("⏳ Follow request sent to @{}", profile.handle)
+                }
+                FollowState::NotFollowing => format!("Unfollowed @{}", profile.handle),
+            };
+            state.set_status(message);
+            state.update_profile(*profile);
+            None
+        }
+        AsyncResult::FiltersLoaded { rules } => {
+            let before = state.filtered_count();
+            state.add_server_filters(rules);
+            let hidden = state.filtered_count() - before;
+            if hidden > 0 {
+                state.set_status(format!("Server filters hid {hidden} posts"));
+            }
+            None
+        }
+        AsyncResult::Moderated {
+            handle,
+            moderation,
+            undo,
+        } => {
+            if let Some(profile) = state
+                .profile
+                .as_mut()
+                .filter(|p| p.handle.eq_ignore_ascii_case(&handle))
+            {
+                match moderation {
+                    Moderation::Mute => profile.muted = !undo,
+                    Moderation::Block => profile.blocked = !undo,
+                }
+            }
+            state.set_status(format!("✓ {} @{handle}", moderation.done(undo)));
+            None
+        }
+        AsyncResult::SearchFinished {
+            query,
+            posts,
+            notices,
+        } => {
+            // A newer search replaced this one
+            if !state.searching || query != state.last_search {
+                return None;
+            }
+            state.searching = false;
+            state.search_results = posts;
+            state.selected_search_result = 0;
+
+            let mut message = format!("🔍 {} posts match '{query}'", state.search_results.len());
+            for notice in notices {
+                message.push_str(" · ");
+                message.push_str(&notice);
+            }
+            state.set_status(message);
+            None
+        }
+        AsyncResult::AccountRevoked { account, revoked } => {
+            // The account goes either way; a failed revocation only means
+            // the session stays open on the server
+            let note = match revoked {
+                Ok(true) => {
+                    tracing::info!("Revoked the token of @{}", account.handle);
+                    ", signed out on the server"
+                }
+                Ok(false) => "",
+                Err(e) => {
+                    tracing::warn!("Failed to revoke the token of @{}: {e}", account.handle);
+                    ", ⚠ couldn't sign out on the server"
+                }
+            };
+            match state.remove_account(&account) {
+                Ok(summary) => {
+                    state.set_status(format!("🗑 Removed @{} ({summary}){note}", account.handle));
+                }
+                Err(e) => state.set_status(format!("❌ Failed to remove account: {e}")),
+            }
+            // Stop streaming for the removed account
+            state.stream_command()
+        }
+        AsyncResult::AccountVerified {
+            account_id,
+            profile,
+        } => {
+            if let Err(e) = state.db.update_account_profile(
+                account_id,
+                &profile.display_name,
+                profile.avatar_url.as_deref(),
+            ) {
+                tracing::warn!("Failed to save account profile: {e}");
+            }
+            if let Err(e) = state.db.update_account_server_info(account_id, &profile) {
+                tracing::warn!("Failed to save server details: {e}");
+            }
+            if let Some(account) = state.accounts.iter_mut().find(|a| a.id == account_id) {
+                account.update_server_info(&profile);
+                account.display_name = profile.display_name;
+                account.avatar_url = profile.avatar_url;
+                let message = format!("✓ @{} credentials are valid", account.handle);
+                state.set_status(message);
+            }
+            None
+        }
+        AsyncResult::PostLimitFetched {
+            account_id,
+            max_chars,
+        } => {
+            state.set_post_char_limit(account_id, max_chars);
+            None
+        }
         AsyncResult::Posted { posts } => {
             let networks: Vec<_> = posts.iter().map(|p| p.network.emoji()).collect();
             state.set_status(format!("✅ Posted to {}", networks.join(" ")));
             state.loading = false;
+            state.clear_sent_draft();
             None
         }
-        AsyncResult::Scheduled { id, scheduled_for } => {
-            state.set_status(format!("📅 Scheduled [{}] for {}", id, scheduled_for));
+        AsyncResult::Scheduled {
+            id,
+            server_id,
+            scheduled_for,
+        } => {
+            let post = crate::ScheduledPost::new("", vec![], scheduled_for);
+            let ids = id
+                .map(|id| format!("[{id}]"))
+                .into_iter()
+                .chain(server_id.map(|id| format!("[🐘 {id}]")))
+                .collect::<Vec<_>>()
+                .join(" ");
+            state.set_status(format!(
+                "📅 Scheduled {} for {} (in {})",
+                ids,
+                post.scheduled_time_display(&state.config.timezone),
+                post.time_until()
+            ));
             state.loading = false;
+            state.clear_sent_draft();
             None
         }
         AsyncResult::Error { message } => {
@@ -250,52 +673,106 @@ fn handle_async_result(state: &mut AppState, result: AsyncResult) -> Option<Asyn
             state.loading = false;
             None
         }
+        AsyncResult::Offline { background } => {
+            state.loading = false;
+            // Say so once, not on every periodic refresh that fails
+            if !state.offline || !background {
+                state.set_status("Offline — showing cached timeline");
+            }
+            state.offline = true;
+            None
+        }
         AsyncResult::Status { message } => {
             state.set_status(message);
             None
         }
-        AsyncResult::ImageLoaded { url, image } => {
+        AsyncResult::Finished { operation } => {
+            state.finish_operation(operation);
+            None
+        }
+        AsyncResult::StreamedPosts { posts } => {
+            state.add_streamed_posts(posts);
+            None
+        }
+        AsyncResult::StreamFailed {
+            handle,
+            error,
+            retry,
+        } => {
+            state.set_status(match retry {
+                Some(after) => format!(
+                    "⚠ Stream for @{handle} failed: {error} (retrying in {}s)",
+                    after.as_secs()
+                ),
+                None => format!("⚠ Not streaming @{handle}: {error}"),
+            });
+            None
+        }
+        AsyncResult::ImageLoaded {
+            url,
+            image,
+            animation,
+        } => {
             state.loading_images.remove(&url);
+            // Loaded again after a retry
+            state.failed_images.remove(&url);
             state.image_cache.insert(&url, image);
+            if let Some(animation) = animation {
+                state.add_animation(url, animation);
+            }
             // No status message - images load quietly
             None
         }
         AsyncResult::ImageFailed { url, error } => {
             state.loading_images.remove(&url);
+            state.failed_images.insert(url.clone());
             tracing::warn!("Failed to load image {}: {}", url, error);
             // Don't show error in status bar - would be too noisy
             None
         }
+        AsyncResult::ImageCancelled { url } => {
+            // Queued again if it comes back into view
+            state.loading_images.remove(&url);
+            None
+        }
     }
 }
 
+/// Redraw at the new terminal size straight away, rather than on the next
+/// pass of the loop
+///
+/// Until then, the click areas from the last frame point at where things
+/// were drawn at the old size, so they're dropped.
+fn resize(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    state: &mut AppState,
+    width: u16,
+    height: u16,
+) -> Result<()> {
+    state.hit_regions = state::HitRegions::default();
+    terminal.resize(Rect::new(0, 0, width, height))?;
+    terminal.draw(|frame| ui::render(frame, state))?;
+    Ok(())
+}
+
 /// Run the TUI in demo mode with mock data (for screenshots)
-pub fn run_demo() -> Result<()> {
+pub fn run_demo(theme: Option<Theme>) -> Result<()> {
     // Load config
     let config = Config::load()?;
+    crate::theme::set_monochrome(config.color.is_monochrome());
 
     // Open database
     let db = Database::open()?;
 
     // Initialize terminal
-    enable_raw_mode()?;
-    let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
+    let terminal_guard = terminal::TerminalGuard::enter(false)?;
+    let backend = CrosstermBackend::new(stdout());
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
 
     // Create app state with demo data
     let mut state = AppState::new(config, db)?;
-    state.accounts = demo::demo_accounts();
-    state.posts = demo::demo_posts();
-    state.focused_panel = state::FocusedPanel::Timeline;
-    // Use Dracula theme for screenshots
-    state.theme = crate::theme::Theme(ratatui_themes::ThemeName::Dracula);
-    state.set_status(format!(
-        "Demo mode | {} posts | Press ? for help | q to quit",
-        state.posts.len()
-    ));
+    load_demo(&mut state, theme.unwrap_or(DEMO_THEME));
 
     // Main loop (simpler, no async)
     loop {
@@ -303,11 +780,15 @@ pub fn run_demo() -> Result<()> {
         terminal.draw(|frame| ui::render(frame, &mut state))?;
 
         // Handle events
-        if event::poll(Duration::from_millis(50))?
-            && let Event::Key(key) = event::read()?
-        {
-            // Simple event handling for demo
-            events::handle_key(&mut state, key);
+        if event::poll(Duration::from_millis(50))? {
+            match event::read()? {
+                // Simple event handling for demo
+                Event::Key(key) => {
+                    events::handle_key(&mut state, key);
+                }
+                Event::Resize(width, height) => resize(&mut terminal, &mut state, width, height)?,
+                _ => {}
+            }
         }
 
         // Tick for animations
@@ -319,12 +800,41 @@ pub fn run_demo() -> Result<()> {
     }
 
     // Restore terminal
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
+    drop(terminal_guard);
 
     // Save config on exit
     state.config.save()?;
 
     Ok(())
 }
+
+/// Theme the demo uses unless told otherwise, as in the screenshots
+const DEMO_THEME: Theme = Theme::Builtin(ratatui_themes::ThemeName::Dracula);
+
+/// Replace what's shown with the demo accounts and posts
+fn load_demo(state: &mut AppState, theme: Theme) {
+    state.accounts = demo::demo_accounts();
+    state.posts = demo::demo_posts();
+    state.focused_panel = state::FocusedPanel::Timeline;
+    state.theme = theme;
+    state.set_status(format!(
+        "Demo mode | {} posts | Press ? for help | q to quit",
+        state.posts.len()
+    ));
+}
+
+/// The demo on its own, without the user's config or database
+fn demo_state(theme: Theme) -> Result<AppState> {
+    let mut state = AppState::new(Config::default(), Database::open_in_memory()?)?;
+    load_demo(&mut state, theme);
+    Ok(state)
+}
+
+/// Write each demo screen to `dir` as ANSI text and SVG, drawn without a
+/// terminal, returning the files written
+pub fn write_demo_snapshots(
+    dir: &std::path::Path,
+    theme: Option<Theme>,
+) -> Result<Vec<std::path::PathBuf>> {
+    snapshot::write_snapshots(dir, theme.unwrap_or(DEMO_THEME))
+}
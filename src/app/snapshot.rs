@@ -0,0 +1,437 @@
+//! Drawing the demo without a terminal
+//!
+//! `perch demo --snapshot <dir>` renders a few screens of the demo into an
+//! off-screen buffer and writes each one out twice: as ANSI text, which
+//! `cat` replays in a terminal, and as SVG for the docs.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::style::{Color, Modifier};
+use unicode_width::UnicodeWidthStr;
+
+use super::state::{AppState, FocusedPanel, Mode, View};
+use super::ui;
+use crate::theme::Theme;
+
+/// Columns and rows every screen is drawn at
+pub const SNAPSHOT_SIZE: (u16, u16) = (120, 36);
+
+/// Size of one cell in the SVG, in pixels
+const CELL_WIDTH: u16 = 9;
+const CELL_HEIGHT: u16 = 18;
+
+/// Gets a screen of the demo on screen
+type Setup = fn(&mut AppState);
+
+/// Screens written, in order
+const SCREENS: &[(&str, Setup)] = &[
+    ("timeline", |_| {}),
+    ("detail", |state| state.focused_panel = FocusedPanel::Detail),
+    ("help", |state| state.mode = Mode::Help),
+    ("theme-picker", |state| state.mode = Mode::ThemePicker),
+    ("compose", |state| {
+        state.open_compose();
+        state.set_compose_text(
+            "Trying out Perch: one timeline for Mastodon and Bluesky, right in the terminal 🐦"
+                .to_string(),
+        );
+    }),
+    ("accounts", |state| state.view = View::Accounts),
+    ("about", |state| state.mode = Mode::About),
+];
+
+/// Render each demo screen in `theme` and write `NN-name.ans` and
+/// `NN-name.svg` files for it to `dir`, returning the paths written
+pub fn write_snapshots(dir: &Path, theme: Theme) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let mut written = Vec::new();
+    for (i, (name, setup)) in SCREENS.iter().enumerate() {
+        let mut state = super::demo_state(theme)?;
+        setup(&mut state);
+        let buffer = render(&mut state, SNAPSHOT_SIZE)?;
+
+        let stem = format!("{:02}-{name}", i + 1);
+        for (extension, content) in [("ans", to_ansi(&buffer)), ("svg", to_svg(&buffer, &state))] {
+            let path = dir.join(format!("{stem}.{extension}"));
+            std::fs::write(&path, content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            written.push(path);
+        }
+    }
+    Ok(written)
+}
+
+/// Draw `state` once into a buffer of `(width, height)` cells
+pub fn render(state: &mut AppState, (width, height): (u16, u16)) -> Result<Buffer> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    terminal.draw(|frame| ui::render(frame, state))?;
+    Ok(terminal.backend().buffer().clone())
+}
+
+/// The symbols of each row, skipping the cells wide characters cover
+fn rows(buffer: &Buffer) -> impl Iterator<Item = Vec<(u16, &Cell)>> {
+    let area = buffer.area;
+    (area.top()..area.bottom()).map(move |y| {
+        let mut cells = Vec::new();
+        let mut covered = 0;
+        for x in area.left()..area.right() {
+            let cell = &buffer[(x, y)];
+            if covered > 0 {
+                covered -= 1;
+                continue;
+            }
+            if cell.skip {
+                continue;
+            }
+            covered = cell.symbol().width().saturating_sub(1);
+            cells.push((x, cell));
+        }
+        cells
+    })
+}
+
+/// The buffer as text with ANSI escape codes for its colors and styles
+fn to_ansi(buffer: &Buffer) -> String {
+    let mut out = String::new();
+    for row in rows(buffer) {
+        let mut current = None;
+        for (_, cell) in row {
+            let style = (cell.fg, cell.bg, cell.modifier);
+            if current != Some(style) {
+                out.push_str("\x1b[0");
+                for (flag, code) in [
+                    (Modifier::BOLD, 1),
+                    (Modifier::DIM, 2),
+                    (Modifier::ITALIC, 3),
+                    (Modifier::UNDERLINED, 4),
+                    (Modifier::REVERSED, 7),
+                    (Modifier::CROSSED_OUT, 9),
+                ] {
+                    if cell.modifier.contains(flag) {
+                        let _ = write!(out, ";{code}");
+                    }
+                }
+                push_ansi_color(&mut out, cell.fg, 30);
+                push_ansi_color(&mut out, cell.bg, 40);
+                out.push('m');
+                current = Some(style);
+            }
+            out.push_str(cell.symbol());
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+/// Add the SGR parameters for `color`, `base` being 30 for the foreground
+/// and 40 for the background
+fn push_ansi_color(out: &mut String, color: Color, base: u8) {
+    let _ = match color {
+        Color::Reset => Ok(()),
+        Color::Rgb(r, g, b) => write!(out, ";{};2;{r};{g};{b}", base + 8),
+        Color::Indexed(i) => write!(out, ";{};5;{i}", base + 8),
+        named => match ansi_index(named) {
+            Some(i @ 0..8) => write!(out, ";{}", base + i),
+            Some(i) => write!(out, ";{}", base + 60 + i - 8),
+            None => Ok(()),
+        },
+    };
+}
+
+/// Position of a named color in the 16-color palette
+const fn ansi_index(color: Color) -> Option<u8> {
+    Some(match color {
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        Color::Indexed(i) => i,
+        Color::Reset | Color::Rgb(..) => return None,
+    })
+}
+
+/// What `color` looks like, as xterm draws it by default
+fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    const BASIC: [(u8, u8, u8); 16] = [
+        (0x00, 0x00, 0x00),
+        (0xcd, 0x00, 0x00),
+        (0x00, 0xcd, 0x00),
+        (0xcd, 0xcd, 0x00),
+        (0x00, 0x00, 0xee),
+        (0xcd, 0x00, 0xcd),
+        (0x00, 0xcd, 0xcd),
+        (0xe5, 0xe5, 0xe5),
+        (0x7f, 0x7f, 0x7f),
+        (0xff, 0x00, 0x00),
+        (0x00, 0xff, 0x00),
+        (0xff, 0xff, 0x00),
+        (0x5c, 0x5c, 0xff),
+        (0xff, 0x00, 0xff),
+        (0x00, 0xff, 0xff),
+        (0xff, 0xff, 0xff),
+    ];
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    if let Color::Rgb(r, g, b) = color {
+        return Some((r, g, b));
+    }
+    let i = ansi_index(color)?;
+    Some(match i {
+        0..16 => BASIC[usize::from(i)],
+        16..232 => {
+            let i = usize::from(i - 16);
+            (LEVELS[i / 36], LEVELS[i / 6 % 6], LEVELS[i % 6])
+        }
+        _ => {
+            let level = 8 + (i - 232) * 10;
+            (level, level, level)
+        }
+    })
+}
+
+fn hex(color: Color, default: &str) -> String {
+    rgb(color).map_or_else(
+        || default.to_string(),
+        |(r, g, b)| format!("#{r:02x}{g:02x}{b:02x}"),
+    )
+}
+
+/// Cells the SVG draws together: the column they start at, how many
+/// columns they take, their text and the cell their style comes from
+type Run<'a> = (u16, u16, String, &'a Cell);
+
+/// The buffer as an SVG image, with the theme's colors for cells that use
+/// the terminal's own
+fn to_svg(buffer: &Buffer, state: &AppState) -> String {
+    let colors = state.theme.colors();
+    let default_fg = hex(colors.fg, "#d0d0d0");
+    let default_bg = hex(colors.bg, "#000000");
+    let area = buffer.area;
+    let (width, height) = (area.width * CELL_WIDTH, area.height * CELL_HEIGHT);
+
+    let mut backgrounds = String::new();
+    let mut texts = String::new();
+    for (row, cells) in rows(buffer).enumerate() {
+        let y = u16::try_from(row).unwrap_or(u16::MAX) * CELL_HEIGHT;
+        // Runs of cells that look the same, drawn as one rect or text.
+        // Wide characters get their own text, since fonts disagree on how
+        // wide they are.
+        let mut runs: Vec<Run> = Vec::new();
+        for (x, cell) in cells {
+            let width = u16::try_from(cell.symbol().width().max(1)).unwrap_or(1);
+            match runs.last_mut() {
+                Some((_, run_width, text, first))
+                    if (first.fg, first.bg, first.modifier)
+                        == (cell.fg, cell.bg, cell.modifier)
+                        && text.width() == usize::from(*run_width)
+                        && cell.symbol().width() == 1 =>
+                {
+                    *run_width += width;
+                    text.push_str(cell.symbol());
+                }
+                _ => runs.push((x, width, cell.symbol().to_string(), cell)),
+            }
+        }
+
+        for (x, run_width, text, cell) in runs {
+            let (mut fg, mut bg) = (hex(cell.fg, &default_fg), hex(cell.bg, &default_bg));
+            if cell.modifier.contains(Modifier::REVERSED) {
+                std::mem::swap(&mut fg, &mut bg);
+            }
+            let (px, width) = (x * CELL_WIDTH, run_width * CELL_WIDTH);
+            if bg != default_bg {
+                let _ = writeln!(
+                    backgrounds,
+                    r#"<rect x="{px}" y="{y}" width="{width}" height="{CELL_HEIGHT}" fill="{bg}"/>"#
+                );
+            }
+            if text.trim().is_empty() {
+                continue;
+            }
+            let mut attributes = String::new();
+            for (flag, attribute) in [
+                (Modifier::BOLD, r#" font-weight="bold""#),
+                (Modifier::DIM, r#" opacity="0.6""#),
+                (Modifier::ITALIC, r#" font-style="italic""#),
+                (Modifier::UNDERLINED, r#" text-decoration="underline""#),
+                (Modifier::CROSSED_OUT, r#" text-decoration="line-through""#),
+            ] {
+                if cell.modifier.contains(flag) {
+                    attributes.push_str(attribute);
+                }
+            }
+            let _ = writeln!(
+                texts,
+                r#"<text x="{px}" y="{}" fill="{fg}"{attributes}>{}</text>"#,
+                y + CELL_HEIGHT - 5,
+                xml_escape(&text)
+            );
+        }
+    }
+
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="'JetBrains Mono', 'DejaVu Sans Mono', Menlo, Consolas, monospace" font-size="14" xml:space="preserve">
+<rect width="100%" height="100%" fill="{default_bg}"/>
+{backgrounds}{texts}</svg>
+"#
+    )
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Size the stored snapshots are drawn at, narrow enough for the
+    /// single-column layout, which leaves exact post times out
+    const TEST_SIZE: (u16, u16) = (88, 30);
+
+    /// The demo in Perch's own high-contrast theme, so the snapshots don't
+    /// change with the theme crate
+    fn test_state() -> AppState {
+        let mut state = super::super::demo_state(Theme::HighContrast).unwrap();
+        state.package_manager = crate::update::PackageManager::Cargo;
+        state
+    }
+
+    /// The text on screen, with trailing spaces trimmed
+    fn screen_text(buffer: &Buffer) -> String {
+        let mut text = String::new();
+        for cells in rows(buffer) {
+            let line: String = cells.iter().map(|(_, cell)| cell.symbol()).collect();
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Compare what `state` draws with `src/app/snapshots/<name>.txt`.
+    /// Run with `PERCH_UPDATE_SNAPSHOTS=1` to write the snapshots instead,
+    /// after a deliberate change.
+    fn assert_snapshot(name: &str, state: &mut AppState) {
+        let buffer = render(state, TEST_SIZE).unwrap();
+        let text = screen_text(&buffer).replace(crate::VERSION, "{version}");
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/app/snapshots")
+            .join(format!("{name}.txt"));
+        if std::env::var_os("PERCH_UPDATE_SNAPSHOTS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, &text).unwrap();
+            return;
+        }
+        let expected = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            text == expected,
+            "{name} doesn't match {}; it now draws:\n{text}\n\
+             (PERCH_UPDATE_SNAPSHOTS=1 cargo test writes it, if the change is intended)",
+            path.display()
+        );
+    }
+
+    #[test]
+    fn test_timeline_snapshot() {
+        assert_snapshot("timeline", &mut test_state());
+    }
+
+    #[test]
+    fn test_compose_snapshot() {
+        let mut state = test_state();
+        state.open_compose();
+        state.set_compose_text(
+            "Snapshot tests keep the compose popup in shape. Long lines wrap at words, \
+             and the cursor follows the text."
+                .to_string(),
+        );
+        assert_snapshot("compose", &mut state);
+    }
+
+    #[test]
+    fn test_help_snapshot() {
+        let mut state = test_state();
+        state.mode = Mode::Help;
+        assert_snapshot("help", &mut state);
+    }
+
+    #[test]
+    fn test_failed_images_are_flagged_until_retried() {
+        let mut state = test_state();
+        let post = state.selected_post().cloned().unwrap();
+        let preview = post.media[0].preview_url.clone().unwrap();
+        state.failed_images.insert(preview);
+        let text = screen_text(&render(&mut state, (120, 40)).unwrap());
+        assert!(text.contains("✗ failed to load (i to retry)"), "{text}");
+
+        assert_eq!(state.retry_failed_images(&post), 1);
+        assert!(state.failed_images.is_empty());
+        assert_eq!(state.retry_failed_images(&post), 0);
+        let text = screen_text(&render(&mut state, (120, 40)).unwrap());
+        assert!(!text.contains("failed to load"));
+    }
+
+    #[test]
+    fn test_profile_in_title() {
+        let mut state = test_state();
+        state.active_profile = Some("work".to_string());
+        let text = screen_text(&render(&mut state, TEST_SIZE).unwrap());
+        assert!(
+            text.lines().next().unwrap().contains("🐦 Perch · work"),
+            "{text}"
+        );
+    }
+
+    #[test]
+    fn test_update_banner_snapshot() {
+        let mut state = test_state();
+        state.update_available = Some("9.9.9".to_string());
+        assert_snapshot("update_banner", &mut state);
+    }
+
+    #[test]
+    fn test_writes_every_screen() {
+        let dir = tempfile::tempdir().unwrap();
+        let written = write_snapshots(dir.path(), Theme::default()).unwrap();
+        assert_eq!(written.len(), SCREENS.len() * 2);
+
+        let timeline = std::fs::read_to_string(dir.path().join("01-timeline.ans")).unwrap();
+        assert_eq!(timeline.lines().count(), usize::from(SNAPSHOT_SIZE.1));
+        assert!(timeline.contains("Timeline"));
+        let svg = std::fs::read_to_string(dir.path().join("05-compose.svg")).unwrap();
+        assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains("Trying out Perch"));
+    }
+
+    #[test]
+    fn test_ansi_colors() {
+        let mut out = String::new();
+        push_ansi_color(&mut out, Color::Rgb(1, 2, 3), 30);
+        push_ansi_color(&mut out, Color::LightBlue, 40);
+        push_ansi_color(&mut out, Color::Indexed(208), 30);
+        push_ansi_color(&mut out, Color::Reset, 40);
+        assert_eq!(out, ";38;2;1;2;3;104;38;5;208");
+        assert_eq!(rgb(Color::Indexed(196)), Some((255, 0, 0)));
+        assert_eq!(rgb(Color::Indexed(244)), Some((128, 128, 128)));
+    }
+}
@@ -2,13 +2,73 @@
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use ratatui::layout::{Position, Rect};
+use ratatui::style::Style;
 
-use crate::config::Config;
-use crate::db::Database;
+use crate::api::{MAX_MEDIA_ATTACHMENTS, MediaUpload, TimelineKind};
+use crate::config::{Config, MissingAltText, TimelineLayout};
+use crate::db::{Database, HOME_FEED};
+use crate::filters::{FilterRule, Filters};
 use crate::images::ImageCache;
-use crate::models::{Account, Network, Post};
+use crate::models::{
+    Account, Draft, FollowState, ModeratedAccount, Moderation, Network, Notification, Post,
+    Profile, Visibility, split_thread,
+};
 use crate::theme::Theme;
 
+use super::async_ops::{Operation, PostAction};
+use super::image_queue::ImagePriority;
+use super::keymap::KeyMap;
+use super::streaming::StreamCommand;
+use super::text_cursor;
+
+/// Number of posts on either side of the selection to fetch avatars for
+const AVATAR_PRELOAD: usize = 10;
+
+/// Most animations kept decoded at once; older ones are dropped and load
+/// again if their post is shown again
+const MAX_ANIMATIONS: usize = 3;
+
+/// Size blurhash placeholders are decoded at; they get scaled up to fit
+const PLACEHOLDER_SIZE: (u32, u32) = (32, 24);
+
+/// Most posts a cache search returns
+const SEARCH_LIMIT: usize = 200;
+
+/// How long the selection has to stay on a post before its replies are
+/// fetched, so scrolling past posts doesn't fetch each one's
+const CONTEXT_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(300);
+
+/// Where the post at `selected` in `old` is in `new`, and whether it is still
+/// there (if not, the index is clamped to `new`)
+fn reselect(old: &[Post], selected: usize, new: &[Post]) -> (usize, bool) {
+    old.get(selected)
+        .and_then(|post| {
+            new.iter()
+                .position(|p| p.network == post.network && p.network_id == post.network_id)
+        })
+        .map_or_else(
+            || (selected.min(new.len().saturating_sub(1)), false),
+            |i| (i, true),
+        )
+}
+
+/// Put `posts` in front of `list`, skipping ones already in it, and return
+/// how many were added
+fn prepend_posts(list: &mut Vec<Post>, posts: Vec<Post>) -> usize {
+    let mut seen: std::collections::HashSet<(Network, String)> =
+        list.iter().map(Post::dedup_key).collect();
+
+    let mut fresh: Vec<Post> = posts
+        .into_iter()
+        .filter(|p| seen.insert(p.dedup_key()))
+        .collect();
+    let added = fresh.len();
+    fresh.append(list);
+    *list = fresh;
+    added
+}
+
 /// A reply with its depth level for display
 #[derive(Debug, Clone)]
 pub struct ReplyItem {
@@ -18,6 +78,49 @@ pub struct ReplyItem {
     pub depth: usize,
 }
 
+/// Screen areas of the timeline view, recorded while rendering so mouse
+/// clicks can be mapped back to what was under them
+#[derive(Debug, Clone, Default)]
+pub struct HitRegions {
+    /// Timeline panel
+    pub timeline: Rect,
+    /// Detail panel
+    pub detail: Rect,
+    /// Visible posts, by index into `posts`
+    pub posts: Vec<(usize, Rect)>,
+    /// Visible replies, by index into `current_replies`
+    pub replies: Vec<(usize, Rect)>,
+    /// Links shown in the detail panel
+    pub links: Vec<(Rect, String)>,
+}
+
+impl HitRegions {
+    /// Index of the post drawn at `position`
+    pub fn post_at(&self, position: Position) -> Option<usize> {
+        find_region(&self.posts, position)
+    }
+
+    /// Index of the reply drawn at `position`
+    pub fn reply_at(&self, position: Position) -> Option<usize> {
+        find_region(&self.replies, position)
+    }
+
+    /// URL of the link drawn at `position`
+    pub fn link_at(&self, position: Position) -> Option<&str> {
+        self.links
+            .iter()
+            .find(|(area, _)| area.contains(position))
+            .map(|(_, url)| url.as_str())
+    }
+}
+
+fn find_region(regions: &[(usize, Rect)], position: Position) -> Option<usize> {
+    regions
+        .iter()
+        .find(|(_, area)| area.contains(position))
+        .map(|(index, _)| *index)
+}
+
 /// Which panel is currently focused
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum FocusedPanel {
@@ -56,6 +159,7 @@ pub enum View {
     #[default]
     Timeline,
     Accounts,
+    Notifications,
 }
 
 /// Modal mode for dialogs
@@ -78,6 +182,78 @@ pub enum Mode {
     UpdateConfirm,
     /// Update in progress
     Updating,
+    /// Full-screen conversation view for the selected post
+    Thread,
+    /// Confirm deleting one of your own posts or removing an account
+    ConfirmDelete,
+    /// Confirm muting or blocking an account (or lifting it)
+    ConfirmModeration,
+    /// Confirm quitting with an unsent post in compose
+    ConfirmQuit,
+    /// Drafts picker
+    Drafts,
+    /// Pending scheduled posts picker
+    Scheduled,
+    /// Full-screen results of the last search (the timeline is left as it was)
+    SearchResults,
+    /// Picker for the links in a post
+    LinkPicker,
+    /// Full-screen profile of a post's author, with their recent posts
+    Profile,
+}
+
+/// Something the user is being asked to confirm deleting
+#[derive(Debug, Clone)]
+pub enum PendingDelete {
+    /// One of your own posts (deleted on the server)
+    Post(Box<Post>),
+    /// A configured account (removed locally with its credentials)
+    Account(Box<Account>),
+}
+
+/// A mute or block the user is being asked to confirm
+#[derive(Debug, Clone)]
+pub struct PendingModeration {
+    /// Mute or block
+    pub moderation: Moderation,
+    /// Lift the mute or block instead
+    pub undo: bool,
+    /// Network of the account
+    pub network: Network,
+    /// Handle of the account, as shown on its posts
+    pub handle: String,
+    /// Our account to act from
+    pub account: Account,
+    /// Mode to go back to once answered
+    pub return_mode: Mode,
+}
+
+/// Where `/` searches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchScope {
+    /// Posts already in the local cache
+    #[default]
+    Local,
+    /// Each network's search API
+    Server,
+}
+
+impl SearchScope {
+    /// The other scope
+    pub const fn toggle(self) -> Self {
+        match self {
+            Self::Local => Self::Server,
+            Self::Server => Self::Local,
+        }
+    }
+
+    /// Short label for titles
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Local => "cache",
+            Self::Server => "servers",
+        }
+    }
 }
 
 /// Timeline filter
@@ -106,6 +282,13 @@ impl TimelineFilter {
         }
     }
 
+    pub const fn for_network(network: Network) -> Self {
+        match network {
+            Network::Mastodon => Self::Mastodon,
+            Network::Bluesky => Self::Bluesky,
+        }
+    }
+
     pub const fn to_network(&self) -> Option<Network> {
         match self {
             Self::All => None,
@@ -115,6 +298,42 @@ impl TimelineFilter {
     }
 }
 
+/// Which feed the timeline panel shows
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Feed {
+    /// The main timeline: home timelines of all accounts, or a Mastodon
+    /// account's local or federated timeline
+    #[default]
+    Home,
+    /// Recent posts with a hashtag (without the `#`)
+    Hashtag(String),
+}
+
+impl Feed {
+    /// Key this feed's posts are cached under
+    pub fn cache_key(&self) -> String {
+        match self {
+            Self::Home => HOME_FEED.to_string(),
+            Self::Hashtag(tag) => format!("#{}", tag.to_lowercase()),
+        }
+    }
+}
+
+/// A feed covered by opening another one, restored with Esc
+#[derive(Debug, Clone)]
+pub struct FeedSnapshot {
+    /// The covered feed
+    pub feed: Feed,
+    /// Its posts
+    pub posts: Vec<Post>,
+    /// Its selected post index
+    pub selected_post: usize,
+    /// Its next-page cursors
+    pub timeline_cursors: std::collections::HashMap<uuid::Uuid, String>,
+    /// Its posts hidden by content filters
+    pub filtered: std::collections::HashSet<(Network, String)>,
+}
+
 /// Application state
 pub struct AppState {
     /// Configuration
@@ -143,39 +362,157 @@ pub struct AppState {
     pub posts: Vec<Post>,
     /// Selected post index
     pub selected_post: usize,
+    /// Streamed posts above the selection that haven't been scrolled to yet
+    pub new_posts: usize,
     /// Scroll offset for timeline
     pub timeline_scroll: usize,
+    /// Posts the currently selected post replies to, thread root first
+    pub current_ancestors: Vec<Post>,
     /// Replies to currently selected post
     pub current_replies: Vec<ReplyItem>,
     /// Loading replies?
     pub loading_replies: bool,
+    /// When to fetch replies for the selected post, once the selection
+    /// has settled on it
+    context_due: Option<std::time::Instant>,
+    /// Replies weren't fetched because the post had none; Enter loads them
+    pub replies_skipped: bool,
     /// Scroll offset for detail panel
     pub detail_scroll: u16,
     /// Selected reply index (None = main post selected, Some(i) = reply i selected)
     pub selected_reply: Option<usize>,
+    /// Focused node in the thread view (ancestors, then the post, then replies)
+    pub thread_selected: usize,
+
+    /// Post or account waiting for delete confirmation
+    pub pending_delete: Option<PendingDelete>,
+    /// Mute or block waiting for confirmation
+    pub pending_moderation: Option<PendingModeration>,
+    /// Accounts muted or blocked from here, whose posts are never shown
+    pub moderated: Vec<ModeratedAccount>,
+    /// Content filters from the config (and the server, once fetched)
+    pub filters: Filters,
+    /// Posts of the current feed hidden by content filters
+    filtered: std::collections::HashSet<(Network, String)>,
 
+    /// Mode to go back to when compose closes
+    pub compose_return_mode: Mode,
     /// Compose text buffer
     pub compose_text: String,
+    /// Byte offset of the cursor in the compose text
+    pub compose_cursor: usize,
+    /// First wrapped row of the compose text shown, kept so the cursor
+    /// stays in view
+    pub compose_scroll: usize,
     /// Networks to post to (for cross-posting)
     pub compose_networks: Vec<Network>,
+    /// Accounts to post from, within the selected networks
+    pub compose_accounts: Vec<uuid::Uuid>,
+    /// Is the account picker focused?
+    pub compose_accounts_focused: bool,
+    /// Account under the cursor in the account picker
+    pub selected_compose_account: usize,
     /// Reply-to post (if replying)
     pub reply_to: Option<Post>,
+    /// Post being edited (compose submits an edit instead of a new post)
+    pub compose_edit: Option<Post>,
+    /// Post being quoted
+    pub compose_quote: Option<Post>,
+    /// Draft reopened into compose (updated instead of duplicated on save)
+    pub compose_draft: Option<Draft>,
+    /// Draft whose post is being sent (deleted once it goes through)
+    pub sending_draft: Option<uuid::Uuid>,
+    /// Scheduled post reopened into compose (updated when sent)
+    pub compose_scheduled: Option<crate::ScheduledPost>,
     /// Scheduled time (None = post immediately)
     pub compose_schedule: Option<DateTime<Utc>>,
     /// Schedule input text (for editing)
     pub compose_schedule_input: String,
     /// Is schedule input focused?
     pub compose_schedule_focused: bool,
+    /// Have the Mastodon server publish the scheduled post
+    pub compose_server_side: bool,
+    /// Split the post into a thread on accounts it's too long for
+    pub compose_thread: bool,
+    /// Images attached to the post
+    pub compose_media: Vec<MediaUpload>,
+    /// Attachment path input text
+    pub compose_attach_input: String,
+    /// Is attachment path input focused?
+    pub compose_attach_focused: bool,
+    /// Has sending been warned about images without alt text?
+    pub compose_alt_warned: bool,
+    /// Visibility for Mastodon posts
+    pub compose_visibility: Visibility,
+    /// Content warning text (Mastodon spoiler text)
+    pub compose_cw: String,
+    /// Is content warning input focused?
+    pub compose_cw_focused: bool,
+    /// Flag to open the compose buffer in `$EDITOR` on next tick
+    pub pending_editor: bool,
+
+    /// Saved drafts (most recent first)
+    pub drafts: Vec<Draft>,
+    /// Selected draft in the drafts picker
+    pub selected_draft: usize,
+
+    /// Pending scheduled posts (soonest first)
+    pub scheduled_posts: Vec<crate::ScheduledPost>,
+    /// Selected post in the scheduled posts picker
+    pub selected_scheduled: usize,
+
+    /// Links offered by the link picker
+    pub links: Vec<String>,
+    /// Selected link in the link picker
+    pub selected_link: usize,
+    /// Mode to go back to when the link picker closes
+    pub link_picker_return_mode: Mode,
 
     /// Search query
     pub search_query: String,
     /// Search results
     pub search_results: Vec<Post>,
+    /// Selected search result
+    pub selected_search_result: usize,
+    /// Query the current results are for
+    pub last_search: String,
+    /// Whether `/` searches the cache or the servers
+    pub search_scope: SearchScope,
+    /// Scope the current results came from
+    pub last_search_scope: SearchScope,
+    /// Waiting for server search results
+    pub searching: bool,
+
+    /// Profile shown in the profile view (`None` while it loads)
+    pub profile: Option<Profile>,
+    /// Handle the profile view was opened for
+    pub profile_handle: String,
+    /// Account the profile and its posts are fetched through
+    pub profile_account: Option<Account>,
+    /// Posts by the profile's account (newest first)
+    pub profile_posts: Vec<Post>,
+    /// Selected post in the profile view
+    pub selected_profile_post: usize,
+    /// Cursor for the profile's next (older) page of posts
+    pub profile_cursor: Option<String>,
+    /// Is the profile or a page of its posts being fetched?
+    pub loading_profile: bool,
+    /// Mode to go back to when the profile view closes
+    pub profile_return_mode: Mode,
+
+    /// Notifications across all accounts (newest first)
+    pub notifications: Vec<Notification>,
+    /// Selected notification index
+    pub selected_notification: usize,
+    /// Are notifications being fetched?
+    pub loading_notifications: bool,
 
     /// Status message (bottom bar)
     pub status: String,
     /// Is loading?
     pub loading: bool,
+    /// Operations sent to the worker and not finished yet, oldest first
+    in_flight: Vec<Operation>,
 
     /// Tick counter for animations
     tick: u64,
@@ -185,6 +522,10 @@ pub struct AppState {
 
     /// Update available (version string if newer version exists)
     pub update_available: Option<String>,
+    /// Profile in use (`--profile`), shown in the title
+    pub active_profile: Option<String>,
+    /// Release notes for the available update, once fetched
+    pub release_notes: Option<String>,
     /// Package manager for updates
     pub package_manager: crate::update::PackageManager,
     /// Update status message
@@ -196,27 +537,73 @@ pub struct AppState {
     pub image_cache: ImageCache,
     /// Whether to show images in detail view
     pub show_images: bool,
+    /// Show exact times instead of relative ones ("5m")
+    pub absolute_times: bool,
+    /// How the timeline view is laid out
+    pub layout: TimelineLayout,
+    /// Whether the timeline view showed one panel at a time on the last
+    /// frame, by choice or because the terminal was too narrow
+    pub single_column: bool,
     /// URLs of images currently being loaded
     pub loading_images: std::collections::HashSet<String>,
+    /// URLs of images that failed to load (only retried when asked)
+    pub failed_images: std::collections::HashSet<String>,
+    /// Next-page cursor per account (keyed by account ID)
+    pub timeline_cursors: std::collections::HashMap<uuid::Uuid, String>,
+    /// Is an older page of the timeline being fetched?
+    pub loading_more: bool,
+    /// Posts whose content warning has been expanded, keyed by network and ID
+    pub revealed_cw: std::collections::HashSet<(Network, String)>,
+    /// Posts whose media alt text is shown in full, keyed by network and ID
+    pub revealed_alt: std::collections::HashSet<(Network, String)>,
     /// Image protocol states for rendering (keyed by URL)
     pub image_protocols:
         std::collections::HashMap<String, ratatui_image::protocol::StatefulProtocol>,
+    /// Animated GIFs playing, oldest first (keyed by URL)
+    pub animated_images: Vec<(String, crate::images::AnimatedImage)>,
+
+    /// Home, local or federated timeline
+    pub timeline_kind: TimelineKind,
+    /// Mastodon account the local and federated timelines come from
+    pub timeline_account: Option<Account>,
+    /// Feed shown in the timeline panel
+    pub feed: Feed,
+    /// Feeds to return to with Esc, home timeline first
+    pub feed_stack: Vec<FeedSnapshot>,
+    /// Highlighted hashtag, as the post it belongs to and its index there
+    pub selected_hashtag: Option<(uuid::Uuid, usize)>,
+    /// When the main timeline was last refreshed
+    pub last_refreshed: Option<chrono::DateTime<chrono::Local>>,
+    /// The servers couldn't be reached on the last refresh, so the timeline
+    /// is what was cached
+    pub offline: bool,
+    /// When the periodic refresh is next due
+    next_auto_refresh: std::time::Instant,
+    /// Keys for normal-mode actions
+    pub keymap: KeyMap,
+    /// Where clickable things were drawn in the last frame
+    pub hit_regions: HitRegions,
 }
 
 impl AppState {
     /// Create a new app state
     pub fn new(config: Config, db: Database) -> Result<Self> {
         let theme = config.theme;
+        let absolute_times = config.absolute_times;
+        let layout = config.layout;
         let accounts = db.get_accounts()?;
         let posts = db.get_cached_posts(None, config.post_limit)?;
+        let moderated = db.get_moderated()?;
+        // A bad pattern turns filtering off rather than keeping perch from starting
+        let filters = Filters::new(&config.filters.rules).unwrap_or_default();
 
         // Find current theme index
-        let theme_picker_index = Theme::all()
-            .iter()
-            .position(|t| *t == theme.inner())
-            .unwrap_or(0);
+        let theme_picker_index = theme.index();
+
+        let next_auto_refresh = std::time::Instant::now()
+            + std::time::Duration::from_secs(config.refresh_interval_secs);
 
-        Ok(Self {
+        let mut state = Self {
             config,
             db,
             should_quit: false,
@@ -227,34 +614,112 @@ impl AppState {
             timeline_filter: TimelineFilter::All,
             accounts,
             selected_account: 0,
-            posts,
+            posts: Vec::new(),
             selected_post: 0,
+            new_posts: 0,
             timeline_scroll: 0,
+            current_ancestors: Vec::new(),
             current_replies: Vec::new(),
             loading_replies: false,
+            context_due: None,
+            replies_skipped: false,
             detail_scroll: 0,
             selected_reply: None,
+            thread_selected: 0,
+            pending_delete: None,
+            pending_moderation: None,
+            moderated,
+            filters,
+            filtered: std::collections::HashSet::new(),
+            compose_return_mode: Mode::Normal,
             compose_text: String::new(),
+            compose_cursor: 0,
+            compose_scroll: 0,
             compose_networks: vec![Network::Mastodon, Network::Bluesky],
+            compose_accounts: Vec::new(),
+            compose_accounts_focused: false,
+            selected_compose_account: 0,
             reply_to: None,
+            compose_edit: None,
+            compose_quote: None,
+            compose_draft: None,
+            sending_draft: None,
+            compose_scheduled: None,
             compose_schedule: None,
             compose_schedule_input: String::new(),
             compose_schedule_focused: false,
+            compose_server_side: false,
+            compose_thread: false,
+            compose_media: Vec::new(),
+            compose_attach_input: String::new(),
+            compose_attach_focused: false,
+            compose_alt_warned: false,
+            compose_visibility: Visibility::default(),
+            compose_cw: String::new(),
+            compose_cw_focused: false,
+            pending_editor: false,
+            drafts: Vec::new(),
+            selected_draft: 0,
+            scheduled_posts: Vec::new(),
+            selected_scheduled: 0,
+            links: Vec::new(),
+            selected_link: 0,
+            link_picker_return_mode: Mode::Normal,
             search_query: String::new(),
             search_results: Vec::new(),
+            selected_search_result: 0,
+            last_search: String::new(),
+            search_scope: SearchScope::default(),
+            last_search_scope: SearchScope::default(),
+            searching: false,
+            profile: None,
+            profile_handle: String::new(),
+            profile_account: None,
+            profile_posts: Vec::new(),
+            selected_profile_post: 0,
+            profile_cursor: None,
+            loading_profile: false,
+            profile_return_mode: Mode::Normal,
+            notifications: Vec::new(),
+            selected_notification: 0,
+            loading_notifications: false,
             status: String::new(),
             loading: false,
+            in_flight: Vec::new(),
             tick: 0,
             theme_picker_index,
             update_available: None,
+            active_profile: crate::paths::profile().map(str::to_string),
+            release_notes: None,
             package_manager: crate::update::detect_package_manager(),
             update_status: None,
             pending_update: false,
             image_cache: ImageCache::new(),
             show_images: true,
+            absolute_times,
+            layout,
+            single_column: false,
             loading_images: std::collections::HashSet::new(),
+            failed_images: std::collections::HashSet::new(),
+            revealed_cw: std::collections::HashSet::new(),
+            revealed_alt: std::collections::HashSet::new(),
+            timeline_cursors: std::collections::HashMap::new(),
+            loading_more: false,
             image_protocols: std::collections::HashMap::new(),
-        })
+            animated_images: Vec::new(),
+            feed: Feed::default(),
+            timeline_kind: TimelineKind::Home,
+            timeline_account: None,
+            feed_stack: Vec::new(),
+            selected_hashtag: None,
+            last_refreshed: None,
+            offline: false,
+            next_auto_refresh,
+            keymap: KeyMap::default(),
+            hit_regions: HitRegions::default(),
+        };
+        state.show_posts(posts);
+        Ok(state)
     }
 
     /// Tick for animations
@@ -267,6 +732,38 @@ impl AppState {
         self.tick
     }
 
+    /// Note that an operation was handed to the worker
+    pub fn start_operation(&mut self, operation: Operation) {
+        self.in_flight.push(operation);
+    }
+
+    /// Note that the worker finished an operation
+    pub fn finish_operation(&mut self, operation: Operation) {
+        if let Some(i) = self.in_flight.iter().position(|o| *o == operation) {
+            self.in_flight.remove(i);
+        }
+    }
+
+    /// Is anything the user might be waiting on still going?
+    pub fn is_busy(&self) -> bool {
+        self.loading || !self.in_flight.is_empty() || !self.loading_images.is_empty()
+    }
+
+    /// What's in flight, for the status bar: the latest operation started,
+    /// and how many more are going, e.g. "loading replies +1"
+    pub fn busy_label(&self) -> Option<String> {
+        let (latest, others) = match self.in_flight.split_last() {
+            Some((latest, others)) => (latest.label(), others.len()),
+            None if !self.loading_images.is_empty() => ("loading images", 0),
+            None => return None,
+        };
+        Some(if others == 0 {
+            latest.to_string()
+        } else {
+            format!("{latest} +{others}")
+        })
+    }
+
     /// Set status message
     pub fn set_status(&mut self, msg: impl Into<String>) {
         self.status = msg.into();
@@ -277,6 +774,41 @@ impl AppState {
         self.status.clear();
     }
 
+    /// Fetch replies for the selected post once the selection settles.
+    /// Posts without replies are skipped.
+    pub fn schedule_context_fetch(&mut self) {
+        let has_replies = self
+            .selected_post()
+            .is_some_and(|post| post.reply_count > 0);
+        self.context_due = has_replies.then(|| std::time::Instant::now() + CONTEXT_DEBOUNCE);
+        self.loading_replies = has_replies;
+        self.replies_skipped = !has_replies && self.selected_post().is_some();
+    }
+
+    /// The reply fetch for the selected post, once it's due
+    pub fn context_command(&mut self) -> Option<super::async_ops::AsyncCommand> {
+        if self
+            .context_due
+            .is_none_or(|due| std::time::Instant::now() < due)
+        {
+            return None;
+        }
+        self.load_replies_command()
+    }
+
+    /// Fetch replies for the selected post now
+    pub fn load_replies_command(&mut self) -> Option<super::async_ops::AsyncCommand> {
+        self.context_due = None;
+        self.replies_skipped = false;
+        let post = self.selected_post()?.clone();
+        let Some(account) = post.acting_account(&self.accounts).cloned() else {
+            self.loading_replies = false;
+            return None;
+        };
+        self.loading_replies = true;
+        Some(super::async_ops::AsyncCommand::FetchContext { post, account })
+    }
+
     /// Set update available (called from background task)
     pub fn set_update_available(&mut self, version: String) {
         self.update_available = Some(version.clone());
@@ -303,8 +835,9 @@ impl AppState {
             let old = self.selected_post;
             self.selected_post = (self.selected_post + 1).min(self.posts.len() - 1);
             if old != self.selected_post {
+                self.current_ancestors.clear();
                 self.current_replies.clear();
-                self.loading_replies = true;
+                self.schedule_context_fetch();
                 self.detail_scroll = 0;
                 self.selected_reply = None;
             }
@@ -316,216 +849,1939 @@ impl AppState {
         let old = self.selected_post;
         self.selected_post = self.selected_post.saturating_sub(1);
         if old != self.selected_post {
+            self.current_ancestors.clear();
             self.current_replies.clear();
-            self.loading_replies = true;
+            self.schedule_context_fetch();
             self.detail_scroll = 0;
             self.selected_reply = None;
         }
+        self.mark_new_posts_seen();
     }
 
-    /// Move selection down in accounts
-    pub fn select_next_account(&mut self) {
-        if !self.accounts.is_empty() {
-            self.selected_account = (self.selected_account + 1).min(self.accounts.len() - 1);
+    /// Select the post at `index`, as when it is clicked
+    pub fn select_post(&mut self, index: usize) {
+        if index < self.posts.len() && index != self.selected_post {
+            self.selected_post = index;
+            self.reset_post_context();
+            self.mark_new_posts_seen();
         }
     }
 
-    /// Move selection up in accounts
-    pub const fn select_prev_account(&mut self) {
-        self.selected_account = self.selected_account.saturating_sub(1);
-    }
-
-    /// Cycle through themes
-    pub fn next_theme(&mut self) {
-        self.theme = self.theme.next();
-        self.config.theme = self.theme;
+    /// Select the newest post in the timeline, counting every new post as seen
+    pub fn jump_to_newest(&mut self) {
+        let unseen = if self.feed == Feed::Home {
+            std::mem::take(&mut self.new_posts)
+        } else {
+            0
+        };
+        self.focused_panel = FocusedPanel::Timeline;
+        self.select_post(0);
+        if unseen > 0 {
+            self.set_status(format!(
+                "↑ {unseen} new post{}",
+                if unseen == 1 { "" } else { "s" }
+            ));
+        }
     }
 
-    /// Cycle through timeline filters
-    pub fn cycle_filter(&mut self) {
-        self.timeline_filter = self.timeline_filter.next();
-        // Reload posts with new filter
-        if let Ok(posts) = self
-            .db
-            .get_cached_posts(self.timeline_filter.to_network(), self.config.post_limit)
-        {
-            self.posts = posts;
-            self.selected_post = 0;
+    /// Count streamed posts the selection has moved up to as seen
+    pub fn mark_new_posts_seen(&mut self) {
+        if self.feed == Feed::Home {
+            self.new_posts = self.new_posts.min(self.selected_post);
         }
     }
 
-    /// Refresh data from database
-    pub fn refresh_data(&mut self) -> Result<()> {
-        self.accounts = self.db.get_accounts()?;
-        self.posts = self
-            .db
-            .get_cached_posts(self.timeline_filter.to_network(), self.config.post_limit)?;
-        Ok(())
+    /// Add posts from a stream to the top of the home timeline, keeping the
+    /// same post selected
+    pub fn add_streamed_posts(&mut self, posts: Vec<Post>) {
+        for post in &posts {
+            let _ = self.db.cache_post(post);
+        }
+        // Local and federated timelines aren't streamed; home picks these up
+        // from the cache when switched back to
+        if self.timeline_kind == TimelineKind::Home {
+            self.add_new_posts(posts);
+        }
     }
 
-    /// Open compose view
-    pub fn open_compose(&mut self) {
-        self.mode = Mode::Compose;
-        self.compose_text.clear();
-        self.reply_to = None;
-        self.compose_schedule = None;
-        self.compose_schedule_input.clear();
-        self.compose_schedule_focused = false;
-        // Pre-select networks based on configured accounts
-        self.compose_networks = self
-            .accounts
-            .iter()
-            .map(|a| a.network)
-            .collect::<std::collections::HashSet<_>>()
+    /// Add posts to the top of the main timeline, skipping ones already shown
+    /// and keeping the same post selected
+    pub fn add_new_posts(&mut self, posts: Vec<Post>) {
+        let network = self.timeline_filter.to_network();
+        let mut posts: Vec<Post> = posts
             .into_iter()
+            .filter(|p| network.is_none_or(|n| n == p.network))
             .collect();
+        let filtered = self.filter_posts(&mut posts);
+        posts.sort_by_key(|p| std::cmp::Reverse(p.created_at));
+
+        // A hashtag feed may be covering the home timeline
+        let (home, selected, home_filtered) = match self.feed_stack.first_mut() {
+            Some(covered) => (
+                &mut covered.posts,
+                &mut covered.selected_post,
+                &mut covered.filtered,
+            ),
+            None => (&mut self.posts, &mut self.selected_post, &mut self.filtered),
+        };
+        home_filtered.extend(filtered);
+        let was_empty = home.is_empty();
+        let added = prepend_posts(home, posts);
+        if !was_empty {
+            *selected += added;
+        }
+        self.new_posts = (self.new_posts + added).min(*selected);
     }
 
-    /// Open reply view for a specific post
-    pub fn open_reply(&mut self, post: Post) {
-        self.mode = Mode::Compose;
-        self.compose_text = format!("@{} ", post.author_handle);
-        self.reply_to = Some(post.clone());
-        self.compose_schedule = None;
-        self.compose_schedule_input.clear();
-        self.compose_schedule_focused = false;
-        // Only select the network of the post we're replying to
-        self.compose_networks = vec![post.network];
+    /// Show a like or repost (or undoing one) everywhere the post is shown
+    /// and in the cache, ahead of the server. The inverse action rolls it
+    /// back if the server refuses.
+    pub fn apply_post_action(&mut self, network: Network, network_id: &str, action: PostAction) {
+        let replies = self.current_replies.iter_mut().map(|r| &mut r.post);
+        for post in self
+            .posts
+            .iter_mut()
+            .chain(self.current_ancestors.iter_mut())
+            .chain(replies)
+            .filter(|p| p.network == network && p.network_id == network_id)
+        {
+            match action {
+                PostAction::Like => {
+                    post.liked = true;
+                    post.like_count += 1;
+                }
+                PostAction::Unlike => {
+                    post.liked = false;
+                    post.like_count = post.like_count.saturating_sub(1);
+                }
+                PostAction::Repost => {
+                    post.reposted = true;
+                    post.repost_count += 1;
+                }
+                PostAction::Unrepost => {
+                    post.reposted = false;
+                    post.repost_count = post.repost_count.saturating_sub(1);
+                }
+            }
+        }
+
+        if let Some(post) = self
+            .posts
+            .iter()
+            .find(|p| p.network == network && p.network_id == network_id)
+        {
+            self.cache_shown_post(post);
+        }
     }
 
-    /// Close compose view
-    pub fn close_compose(&mut self) {
-        self.mode = Mode::Normal;
-        self.reply_to = None;
-        self.compose_schedule = None;
-        self.compose_schedule_input.clear();
-        self.compose_schedule_focused = false;
+    /// Update a post's bookmark flag everywhere it is shown and in the cache
+    pub fn set_bookmarked(&mut self, network_id: &str, bookmarked: bool) {
+        let replies = self.current_replies.iter_mut().map(|r| &mut r.post);
+        for post in self
+            .posts
+            .iter_mut()
+            .chain(self.current_ancestors.iter_mut())
+            .chain(replies)
+            .filter(|p| p.network == Network::Mastodon && p.network_id == network_id)
+        {
+            post.bookmarked = bookmarked;
+        }
+
+        if let Some(post) = self
+            .posts
+            .iter()
+            .find(|p| p.network == Network::Mastodon && p.network_id == network_id)
+        {
+            self.cache_shown_post(post);
+        }
     }
 
-    /// Toggle schedule input focus
-    pub const fn toggle_schedule_focus(&mut self) {
-        self.compose_schedule_focused = !self.compose_schedule_focused;
+    /// Remove an account and its stored credentials, returning a summary
+    pub fn remove_account(&mut self, account: &Account) -> Result<String> {
+        self.db.delete_account(account.id)?;
+        self.accounts.retain(|a| a.id != account.id);
+        let removed = crate::auth::remove_account_credentials(account, &self.accounts)?;
+
+        if self.selected_account >= self.accounts.len() {
+            self.selected_account = self.accounts.len().saturating_sub(1);
+        }
+        Ok(removed.summary())
     }
 
-    /// Parse and set schedule from input
-    pub fn apply_schedule_input(&mut self) -> Result<(), String> {
-        if self.compose_schedule_input.trim().is_empty() {
-            self.compose_schedule = None;
-            return Ok(());
+    /// Find the configured account that authored a post, if any.
+    ///
+    /// Mastodon authors are `acct` values, which omit the domain for local
+    /// accounts, so both forms are accepted.
+    pub fn own_account_for_post(&self, post: &Post) -> Option<Account> {
+        if post.is_repost {
+            return None;
         }
+        let author = post.author_handle.trim_start_matches('@');
+        self.accounts
+            .iter()
+            .find(|a| {
+                if a.network != post.network {
+                    return false;
+                }
+                let handle = a.handle.trim_start_matches('@');
+                if author.eq_ignore_ascii_case(handle) {
+                    return true;
+                }
+                author.eq_ignore_ascii_case(&format!("{handle}@{}", a.server_host()))
+            })
+            .cloned()
+    }
 
-        match crate::schedule::parse_schedule_time(&self.compose_schedule_input) {
-            Ok(dt) => {
-                self.compose_schedule = Some(dt);
-                Ok(())
-            }
-            Err(e) => Err(e.to_string()),
+    /// Remove a deleted post from the timeline, the open thread and the cache
+    pub fn remove_post(&mut self, network: Network, network_id: &str) {
+        let selected_id = self.selected_post().map(|p| p.id);
+
+        self.posts
+            .retain(|p| !(p.network == network && p.network_id == network_id));
+        self.current_ancestors
+            .retain(|p| !(p.network == network && p.network_id == network_id));
+        self.current_replies
+            .retain(|r| !(r.post.network == network && r.post.network_id == network_id));
+        let _ = self.db.remove_cached_post(network, network_id);
+
+        if self.selected_post >= self.posts.len() {
+            self.selected_post = self.posts.len().saturating_sub(1);
+        }
+        if self.selected_post().map(|p| p.id) != selected_id {
+            self.current_ancestors.clear();
+            self.current_replies.clear();
+            self.schedule_context_fetch();
+            self.detail_scroll = 0;
+            self.selected_reply = None;
         }
     }
 
-    /// Clear schedule
-    pub fn clear_schedule(&mut self) {
-        self.compose_schedule = None;
-        self.compose_schedule_input.clear();
+    /// Enter the full-screen thread view for the selected post.
+    ///
+    /// The conversation is refetched, so the old context is cleared.
+    pub fn open_thread(&mut self) {
+        self.mode = Mode::Thread;
+        self.current_ancestors.clear();
+        self.current_replies.clear();
+        self.loading_replies = true;
+        self.context_due = None;
+        self.replies_skipped = false;
+        self.selected_reply = None;
+        self.thread_selected = 0;
     }
 
-    /// Get schedule display text
-    pub fn schedule_display(&self) -> String {
-        if let Some(dt) = &self.compose_schedule {
-            let scheduled_post = crate::ScheduledPost::new("", vec![], *dt);
-            format!(
-                "{} (in {})",
-                dt.format("%Y-%m-%d %H:%M UTC"),
-                scheduled_post.time_until()
-            )
-        } else if !self.compose_schedule_input.is_empty() {
-            format!("⚠️ {}", self.compose_schedule_input)
-        } else {
-            "Now".to_string()
+    /// Number of nodes in the thread view
+    pub fn thread_len(&self) -> usize {
+        if self.selected_post().is_none() {
+            return 0;
         }
+        self.current_ancestors.len() + 1 + self.current_replies.len()
     }
 
-    /// Toggle network in compose
-    pub fn toggle_compose_network(&mut self, network: Network) {
-        if let Some(idx) = self.compose_networks.iter().position(|n| *n == network) {
-            self.compose_networks.remove(idx);
-        } else {
-            self.compose_networks.push(network);
+    /// Get a node of the thread view by index
+    pub fn thread_node(&self, index: usize) -> Option<&Post> {
+        let ancestors = self.current_ancestors.len();
+        match index {
+            i if i < ancestors => self.current_ancestors.get(i),
+            i if i == ancestors => self.selected_post(),
+            i => self.current_replies.get(i - ancestors - 1).map(|r| &r.post),
         }
     }
 
-    /// Navigate to the next view
-    pub const fn next_view(&mut self) {
-        self.view = match self.view {
-            View::Timeline => View::Accounts,
-            View::Accounts => View::Timeline,
-        };
+    /// Move focus down the thread
+    pub fn select_next_thread_node(&mut self) {
+        let len = self.thread_len();
+        if len > 0 {
+            self.thread_selected = (self.thread_selected + 1).min(len - 1);
+        }
     }
 
-    /// Navigate to the previous view
-    pub const fn prev_view(&mut self) {
-        self.view = match self.view {
-            View::Timeline => View::Accounts,
-            View::Accounts => View::Timeline,
-        };
+    /// Move focus up the thread
+    pub const fn select_prev_thread_node(&mut self) {
+        self.thread_selected = self.thread_selected.saturating_sub(1);
     }
 
-    /// Get URLs of images that should be loaded for the current post.
-    /// Returns URLs that are not yet cached or loading.
-    pub fn get_images_to_load(&self) -> Vec<String> {
-        if !self.show_images {
-            return Vec::new();
+    /// Search the post cache for `search_query` and show the results,
+    /// returning how many were found
+    pub fn run_search(&mut self) -> Result<usize> {
+        let query = std::mem::take(&mut self.search_query);
+        self.search_results = self.db.search_cached(&query, SEARCH_LIMIT)?;
+        self.selected_search_result = 0;
+        self.last_search = query;
+        self.last_search_scope = SearchScope::Local;
+        self.searching = false;
+        self.mode = Mode::SearchResults;
+        Ok(self.search_results.len())
+    }
+
+    /// Start searching the servers for `search_query`, using one account per
+    /// network (the default one where set)
+    pub fn server_search_command(&mut self) -> Option<super::async_ops::AsyncCommand> {
+        let accounts = self.account_per_network();
+        if accounts.is_empty() {
+            self.set_status("⚠ Add an account to search servers");
+            return None;
         }
 
-        let mut urls = Vec::new();
+        let query = std::mem::take(&mut self.search_query);
+        self.search_results.clear();
+        self.selected_search_result = 0;
+        self.last_search.clone_from(&query);
+        self.last_search_scope = SearchScope::Server;
+        self.searching = true;
+        self.mode = Mode::SearchResults;
+        Some(super::async_ops::AsyncCommand::Search { query, accounts })
+    }
 
-        // Images from selected post
-        if let Some(post) = self.selected_post() {
-            for media in &post.media {
-                if media.media_type == crate::models::MediaType::Image {
-                    let url = media.preview_url.as_ref().unwrap_or(&media.url).clone();
-                    if !self.image_cache.contains(&url) && !self.loading_images.contains(&url) {
-                        urls.push(url);
-                    }
-                }
+    /// One account for each network, the default one where set
+    fn account_per_network(&self) -> Vec<Account> {
+        let mut accounts: Vec<Account> = Vec::new();
+        for account in self
+            .accounts
+            .iter()
+            .filter(|a| a.is_default)
+            .chain(&self.accounts)
+        {
+            if !accounts.iter().any(|a| a.network == account.network) {
+                accounts.push(account.clone());
             }
         }
+        accounts
+    }
 
-        // Images from replies (limit to first 5 to avoid loading too many)
-        for reply in self.current_replies.iter().take(5) {
-            for media in &reply.post.media {
-                if media.media_type == crate::models::MediaType::Image {
-                    let url = media.preview_url.as_ref().unwrap_or(&media.url).clone();
-                    if !self.image_cache.contains(&url) && !self.loading_images.contains(&url) {
-                        urls.push(url);
-                    }
-                }
-            }
+    /// Highlight the next hashtag in the selected post, wrapping around, and
+    /// return it (`None` if the post has no hashtags)
+    pub fn cycle_hashtag(&mut self) -> Option<String> {
+        let post = self.selected_post()?;
+        let post_id = post.id;
+        let mut tags = post.hashtags();
+        if tags.is_empty() {
+            self.selected_hashtag = None;
+            return None;
         }
 
-        urls
+        let next = match self.selected_hashtag {
+            Some((id, index)) if id == post_id => (index + 1) % tags.len(),
+            _ => 0,
+        };
+        self.selected_hashtag = Some((post_id, next));
+        Some(tags.swap_remove(next))
     }
 
-    /// Mark images as loading (to avoid duplicate requests).
-    pub fn mark_images_loading(&mut self, urls: &[String]) {
-        for url in urls {
-            self.loading_images.insert(url.clone());
+    /// The highlighted hashtag, if it belongs to the selected post
+    pub fn selected_hashtag(&self) -> Option<String> {
+        let post = self.selected_post()?;
+        let (id, index) = self.selected_hashtag?;
+        if id != post.id {
+            return None;
         }
+        post.hashtags().into_iter().nth(index)
     }
 
-    /// Get or create a `StatefulProtocol` for rendering an image.
-    /// Returns None if image is not cached or picker is not available.
-    pub fn get_image_protocol(
-        &mut self,
-        url: &str,
-    ) -> Option<&mut ratatui_image::protocol::StatefulProtocol> {
-        // If already have a protocol, return it
-        if self.image_protocols.contains_key(url) {
-            return self.image_protocols.get_mut(url);
-        }
+    /// Open a hashtag's feed on top of the current one, showing cached posts
+    /// until the fetch returns
+    pub fn open_hashtag(&mut self, tag: String) -> Option<super::async_ops::AsyncCommand> {
+        let covered = FeedSnapshot {
+            feed: std::mem::replace(&mut self.feed, Feed::Hashtag(tag.clone())),
+            posts: std::mem::take(&mut self.posts),
+            selected_post: self.selected_post,
+            timeline_cursors: std::mem::take(&mut self.timeline_cursors),
+            filtered: std::mem::take(&mut self.filtered),
+        };
+        self.feed_stack.push(covered);
 
-        // Try to create one from cached image
-        let image = self.image_cache.get(url)?;
+        let posts = self
+            .db
+            .get_feed_posts(
+                &self.feed_cache_key(),
+                self.timeline_filter.to_network(),
+                self.config.post_limit,
+            )
+            .unwrap_or_default();
+        self.show_posts(posts);
+        self.selected_post = 0;
+        self.reset_post_context();
+        self.hashtag_command(tag)
+    }
+
+    /// Return to the feed under the current one (`false` at the home timeline)
+    pub fn close_feed(&mut self) -> bool {
+        let Some(covered) = self.feed_stack.pop() else {
+            return false;
+        };
+        self.feed = covered.feed;
+        self.posts = covered.posts;
+        self.timeline_cursors = covered.timeline_cursors;
+        self.filtered = covered.filtered;
+        self.selected_post = covered
+            .selected_post
+            .min(self.posts.len().saturating_sub(1));
+        self.reset_post_context();
+        true
+    }
+
+    /// Build a command to fetch a hashtag feed, one account per network
+    pub fn hashtag_command(&mut self, tag: String) -> Option<super::async_ops::AsyncCommand> {
+        let accounts = self.account_per_network();
+        if accounts.is_empty() {
+            return None;
+        }
+        self.loading = true;
+        self.set_status(format!("Loading #{tag}..."));
+        Some(super::async_ops::AsyncCommand::HashtagTimeline { tag, accounts })
+    }
+
+    /// Forget the replies and highlights of the previously selected post
+    fn reset_post_context(&mut self) {
+        self.current_ancestors.clear();
+        self.current_replies.clear();
+        self.schedule_context_fetch();
+        self.selected_reply = None;
+        self.selected_hashtag = None;
+        self.detail_scroll = 0;
+    }
+
+    /// Key the main timeline's posts are cached under
+    pub fn timeline_cache_key(&self) -> String {
+        match (self.timeline_kind, &self.timeline_account) {
+            (TimelineKind::Home, _) | (_, None) => HOME_FEED.to_string(),
+            (kind, Some(account)) => {
+                format!("@{}:{}", kind.name().to_lowercase(), account.server_host())
+            }
+        }
+    }
+
+    /// Key the posts of the feed being shown are cached under
+    fn feed_cache_key(&self) -> String {
+        match self.feed {
+            Feed::Home => self.timeline_cache_key(),
+            Feed::Hashtag(_) => self.feed.cache_key(),
+        }
+    }
+
+    /// Timeline name for the panel title, e.g. "Local · mastodon.social"
+    pub fn timeline_label(&self) -> String {
+        match &self.timeline_account {
+            Some(account) if self.timeline_kind != TimelineKind::Home => {
+                format!("{} · {}", self.timeline_kind.name(), account.server_host())
+            }
+            _ => TimelineKind::Home.name().to_string(),
+        }
+    }
+
+    /// Mastodon account for the local and federated timelines: the one
+    /// selected in the accounts panel, else the default one
+    fn public_timeline_account(&self) -> Option<Account> {
+        let mut mastodon = self
+            .accounts
+            .iter()
+            .filter(|a| a.network.capabilities().public_timelines);
+        self.selected_account()
+            .filter(|a| a.network.capabilities().public_timelines)
+            .or_else(|| mastodon.clone().find(|a| a.is_default))
+            .or_else(|| mastodon.next())
+            .cloned()
+    }
+
+    /// Cycle the timeline Home → Local → Federated, showing cached posts
+    /// until the refresh returns
+    pub fn cycle_timeline_kind(&mut self) -> Option<super::async_ops::AsyncCommand> {
+        if !self.feed_stack.is_empty() {
+            self.set_status("⚠ Go back to the timeline (Esc) first");
+            return None;
+        }
+
+        let next = self.timeline_kind.next();
+        if next == TimelineKind::Home {
+            self.timeline_account = None;
+        } else if self.timeline_account.is_none() {
+            // Bluesky has no public timelines
+            let Some(account) = self.public_timeline_account() else {
+                self.set_status("⚠ Local and federated timelines need a Mastodon account");
+                return None;
+            };
+            self.timeline_account = Some(account);
+        }
+        self.timeline_kind = next;
+
+        let posts = self
+            .db
+            .get_feed_posts(
+                &self.timeline_cache_key(),
+                self.timeline_filter.to_network(),
+                self.config.post_limit,
+            )
+            .unwrap_or_default();
+        self.show_posts(posts);
+        self.selected_post = 0;
+        self.new_posts = 0;
+        self.timeline_cursors.clear();
+        self.reset_post_context();
+
+        let command = self.refresh_timeline_command();
+        self.set_status(format!("Timeline: {}", self.timeline_label()));
+        command
+    }
+
+    /// Build a command to refresh the main timeline
+    pub fn refresh_timeline_command(&mut self) -> Option<super::async_ops::AsyncCommand> {
+        let accounts = self.main_timeline_accounts();
+        if accounts.is_empty() {
+            return None;
+        }
+
+        self.loading = true;
+        self.set_status("Refreshing...");
+        Some(super::async_ops::AsyncCommand::RefreshTimeline {
+            accounts,
+            kind: self.timeline_kind,
+            background: false,
+        })
+    }
+
+    /// When refreshing can resume, if a Mastodon account's instance has used
+    /// up its rate limit
+    pub fn rate_limited_until(&self) -> Option<DateTime<Utc>> {
+        crate::api::mastodon::rate_limit::paused_until(
+            self.accounts
+                .iter()
+                .filter(|a| a.network == Network::Mastodon)
+                .map(|a| a.server.as_str()),
+        )
+    }
+
+    /// Build a command for the periodic refresh, if one is due.
+    ///
+    /// Nothing is sent while something is loading, while compose, search
+    /// or a confirmation is open, or until a Mastodon instance's rate limit
+    /// resets.
+    pub fn auto_refresh_command(&mut self) -> Option<super::async_ops::AsyncCommand> {
+        let interval = std::time::Duration::from_secs(self.config.refresh_interval_secs);
+        if interval.is_zero()
+            || self.loading
+            || std::time::Instant::now() < self.next_auto_refresh
+            || self.rate_limited_until().is_some()
+            || matches!(
+                self.mode,
+                Mode::Compose
+                    | Mode::Search
+                    | Mode::ConfirmDelete
+                    | Mode::ConfirmModeration
+                    | Mode::ConfirmQuit
+                    | Mode::Drafts
+                    | Mode::Scheduled
+                    | Mode::UpdateConfirm
+                    | Mode::Updating
+            )
+        {
+            return None;
+        }
+
+        self.next_auto_refresh = std::time::Instant::now() + interval;
+        let accounts = self.main_timeline_accounts();
+        if accounts.is_empty() {
+            return None;
+        }
+        Some(super::async_ops::AsyncCommand::RefreshTimeline {
+            accounts,
+            kind: self.timeline_kind,
+            background: true,
+        })
+    }
+
+    /// Accounts the main timeline is fetched from
+    fn main_timeline_accounts(&self) -> Vec<Account> {
+        match (self.timeline_kind, &self.timeline_account) {
+            (TimelineKind::Home, _) | (_, None) => self.accounts.clone(),
+            (_, Some(account)) => vec![account.clone()],
+        }
+    }
+
+    /// Note that the timeline was just refreshed, pushing back the next
+    /// periodic refresh
+    pub fn mark_refreshed(&mut self) {
+        self.last_refreshed = Some(chrono::Local::now());
+        self.next_auto_refresh = std::time::Instant::now()
+            + std::time::Duration::from_secs(self.config.refresh_interval_secs);
+    }
+
+    /// Cache a post under the feed being shown
+    fn cache_shown_post(&self, post: &Post) {
+        let _ = self.db.cache_feed_post(&self.feed_cache_key(), post);
+    }
+
+    /// Get the selected search result
+    pub fn selected_search_result(&self) -> Option<&Post> {
+        self.search_results.get(self.selected_search_result)
+    }
+
+    /// Move selection down in search results
+    pub fn select_next_search_result(&mut self) {
+        if !self.search_results.is_empty() {
+            self.selected_search_result =
+                (self.selected_search_result + 1).min(self.search_results.len() - 1);
+        }
+    }
+
+    /// Move selection up in search results
+    pub const fn select_prev_search_result(&mut self) {
+        self.selected_search_result = self.selected_search_result.saturating_sub(1);
+    }
+
+    /// Open the profile view for `handle`, fetched through `account`.
+    ///
+    /// Opening another profile from the profile view replaces it; Esc goes
+    /// back to where the first one was opened from.
+    pub fn open_profile(
+        &mut self,
+        handle: String,
+        account: Account,
+    ) -> super::async_ops::AsyncCommand {
+        if self.mode != Mode::Profile {
+            self.profile_return_mode = self.mode;
+        }
+        self.mode = Mode::Profile;
+        self.profile = None;
+        self.profile_posts.clear();
+        self.selected_profile_post = 0;
+        self.profile_cursor = None;
+        self.loading_profile = true;
+        self.profile_handle.clone_from(&handle);
+        self.profile_account = Some(account.clone());
+        self.clear_status();
+        super::async_ops::AsyncCommand::FetchProfile { handle, account }
+    }
+
+    /// Follow the profile's account, or unfollow it (also withdrawing a
+    /// pending request)
+    pub fn toggle_follow_command(&mut self) -> Option<super::async_ops::AsyncCommand> {
+        let profile = self.profile.clone()?;
+        let account = self.profile_account.clone()?;
+        let follow = profile.follow_state == FollowState::NotFollowing;
+        self.set_status(if follow {
+            format!("Following @{}...", profile.handle)
+        } else {
+            format!("Unfollowing @{}...", profile.handle)
+        });
+        Some(super::async_ops::AsyncCommand::Follow {
+            profile: Box::new(profile),
+            account,
+            follow,
+        })
+    }
+
+    /// Show a profile's new follow state, counting the follow in its
+    /// followers, if it is still the one in the profile view
+    pub fn update_profile(&mut self, updated: Profile) {
+        let Some(profile) = self
+            .profile
+            .as_mut()
+            .filter(|p| p.network == updated.network && p.network_id == updated.network_id)
+        else {
+            return;
+        };
+        let was_following = profile.follow_state == FollowState::Following;
+        let is_following = updated.follow_state == FollowState::Following;
+        let followers_count = match (was_following, is_following) {
+            (false, true) => profile.followers_count + 1,
+            (true, false) => profile.followers_count.saturating_sub(1),
+            _ => profile.followers_count,
+        };
+        *profile = Profile {
+            followers_count,
+            ..updated
+        };
+    }
+
+    /// Whether an account is muted or blocked from here (`moderation` of
+    /// `None` matches either)
+    pub fn is_moderated(
+        &self,
+        network: Network,
+        handle: &str,
+        moderation: Option<Moderation>,
+    ) -> bool {
+        self.moderated
+            .iter()
+            .any(|m| m.matches(network, handle) && moderation.is_none_or(|k| k == m.moderation))
+    }
+
+    /// Drop posts by muted and blocked accounts
+    pub fn hide_moderated(&self, posts: &mut Vec<Post>) {
+        if !self.moderated.is_empty() {
+            posts.retain(|p| !self.is_moderated(p.network, &p.author_handle, None));
+        }
+    }
+
+    /// Drop posts by muted and blocked accounts and posts a content filter
+    /// hides, returning the keys of the ones filters hid
+    pub fn filter_posts(
+        &self,
+        posts: &mut Vec<Post>,
+    ) -> std::collections::HashSet<(Network, String)> {
+        self.hide_moderated(posts);
+        let mut hidden = std::collections::HashSet::new();
+        if !self.filters.is_empty() {
+            posts.retain(|p| {
+                let hide = self.filters.hides(p);
+                if hide {
+                    hidden.insert((p.network, p.network_id.clone()));
+                }
+                !hide
+            });
+        }
+        hidden
+    }
+
+    /// Show `posts` as the current feed, minus filtered ones
+    pub fn show_posts(&mut self, mut posts: Vec<Post>) {
+        self.filtered = self.filter_posts(&mut posts);
+        self.posts = posts;
+    }
+
+    /// Number of posts content filters hide from the current feed
+    pub fn filtered_count(&self) -> usize {
+        self.filtered.len()
+    }
+
+    /// Build a command to fetch the filters set up on Mastodon accounts, if
+    /// `sync_mastodon` is on
+    pub fn server_filters_command(&self) -> Option<super::async_ops::AsyncCommand> {
+        let accounts: Vec<Account> = self
+            .accounts
+            .iter()
+            .filter(|a| a.network.capabilities().server_filters)
+            .cloned()
+            .collect();
+        if !self.config.filters.sync_mastodon || accounts.is_empty() {
+            return None;
+        }
+        Some(super::async_ops::AsyncCommand::FetchFilters { accounts })
+    }
+
+    /// Add the filters set up on the server to the configured ones, and apply
+    /// them to the posts already shown
+    pub fn add_server_filters(&mut self, rules: Vec<FilterRule>) {
+        let mut all = self.config.filters.rules.clone();
+        all.extend(rules);
+        match Filters::new(&all) {
+            Ok(filters) => self.filters = filters,
+            Err(e) => {
+                self.set_status(format!("⚠ Server filters not applied: {e}"));
+                return;
+            }
+        }
+
+        let mut posts = self.posts.clone();
+        let filtered = self.filter_posts(&mut posts);
+        let (selected, same_post) = reselect(&self.posts, self.selected_post, &posts);
+        self.posts = posts;
+        self.filtered.extend(filtered);
+        self.selected_post = selected;
+        if !same_post {
+            self.reset_post_context();
+        }
+    }
+
+    /// A post's content warning, or the pattern a filter marks it with
+    pub fn content_warning<'a>(&self, post: &'a Post) -> Option<std::borrow::Cow<'a, str>> {
+        if let Some(cw) = &post.spoiler_text {
+            return Some(std::borrow::Cow::Borrowed(cw));
+        }
+        self.filters
+            .warning(post)
+            .map(|pattern| std::borrow::Cow::Owned(format!("filtered ({pattern})")))
+    }
+
+    /// Ask to confirm muting or blocking an account (or with `undo`, lifting it)
+    pub fn ask_moderation(
+        &mut self,
+        moderation: Moderation,
+        undo: bool,
+        network: Network,
+        handle: String,
+        account: Account,
+    ) {
+        self.pending_moderation = Some(PendingModeration {
+            moderation,
+            undo,
+            network,
+            handle,
+            account,
+            return_mode: self.mode,
+        });
+        self.mode = Mode::ConfirmModeration;
+    }
+
+    /// Carry out the confirmed mute or block: hide the account's posts right
+    /// away and remember it, then build the command telling the server
+    pub fn confirm_moderation(&mut self) -> Option<super::async_ops::AsyncCommand> {
+        let pending = self.pending_moderation.take()?;
+        self.mode = pending.return_mode;
+        let PendingModeration {
+            moderation,
+            undo,
+            network,
+            handle,
+            account,
+            ..
+        } = pending;
+
+        if undo {
+            if let Err(e) = self.db.remove_moderated(network, &handle, moderation) {
+                tracing::warn!("Failed to forget {}: {e}", moderation.as_str());
+            }
+            self.moderated
+                .retain(|m| !(m.matches(network, &handle) && m.moderation == moderation));
+        } else {
+            let entry = ModeratedAccount::new(network, &handle, moderation);
+            if let Err(e) = self.db.add_moderated(&entry) {
+                tracing::warn!("Failed to remember {}: {e}", moderation.as_str());
+            }
+            self.moderated.retain(|m| m != &entry);
+            self.moderated.insert(0, entry);
+
+            // Including feeds a hashtag feed is covering
+            let mut posts = std::mem::take(&mut self.posts);
+            let mut feed_stack = std::mem::take(&mut self.feed_stack);
+            self.hide_moderated(&mut posts);
+            for covered in &mut feed_stack {
+                self.hide_moderated(&mut covered.posts);
+                covered.selected_post = covered
+                    .selected_post
+                    .min(covered.posts.len().saturating_sub(1));
+            }
+            self.posts = posts;
+            self.feed_stack = feed_stack;
+            self.selected_post = self.selected_post.min(self.posts.len().saturating_sub(1));
+            self.reset_post_context();
+        }
+
+        self.set_status(format!(
+            "{} {} @{handle}",
+            moderation.emoji(),
+            moderation.done(undo)
+        ));
+        Some(super::async_ops::AsyncCommand::Moderate {
+            handle,
+            account,
+            moderation,
+            undo,
+        })
+    }
+
+    /// Close the profile view, back to where it was opened from
+    pub fn close_profile(&mut self) {
+        self.mode = self.profile_return_mode;
+        self.clear_status();
+    }
+
+    /// Get the selected post in the profile view
+    pub fn selected_profile_post(&self) -> Option<&Post> {
+        self.profile_posts.get(self.selected_profile_post)
+    }
+
+    /// Move selection down in the profile view
+    pub fn select_next_profile_post(&mut self) {
+        if !self.profile_posts.is_empty() {
+            self.selected_profile_post =
+                (self.selected_profile_post + 1).min(self.profile_posts.len() - 1);
+        }
+    }
+
+    /// Move selection up in the profile view
+    pub const fn select_prev_profile_post(&mut self) {
+        self.selected_profile_post = self.selected_profile_post.saturating_sub(1);
+    }
+
+    /// Fetch the profile's next page of posts, unless one is already loading
+    /// or there are no more
+    pub fn load_more_profile_command(&mut self) -> Option<super::async_ops::AsyncCommand> {
+        if self.loading_profile {
+            return None;
+        }
+        let profile = self.profile.clone()?;
+        let account = self.profile_account.clone()?;
+        let cursor = self.profile_cursor.clone()?;
+        self.loading_profile = true;
+        self.set_status("Loading older posts...");
+        Some(super::async_ops::AsyncCommand::FetchProfilePosts {
+            handle: self.profile_handle.clone(),
+            profile: Box::new(profile),
+            account,
+            cursor,
+        })
+    }
+
+    /// Move selection down in accounts
+    pub fn select_next_account(&mut self) {
+        if !self.accounts.is_empty() {
+            self.selected_account = (self.selected_account + 1).min(self.accounts.len() - 1);
+        }
+    }
+
+    /// Move selection up in accounts
+    pub const fn select_prev_account(&mut self) {
+        self.selected_account = self.selected_account.saturating_sub(1);
+    }
+
+    /// Move selection down in notifications
+    pub fn select_next_notification(&mut self) {
+        if !self.notifications.is_empty() {
+            self.selected_notification =
+                (self.selected_notification + 1).min(self.notifications.len() - 1);
+        }
+    }
+
+    /// Move selection up in notifications
+    pub const fn select_prev_notification(&mut self) {
+        self.selected_notification = self.selected_notification.saturating_sub(1);
+    }
+
+    /// Get the currently selected notification
+    pub fn selected_notification(&self) -> Option<&Notification> {
+        self.notifications.get(self.selected_notification)
+    }
+
+    /// Number of notifications the server reports as unread
+    pub fn unread_notifications(&self) -> usize {
+        self.notifications.iter().filter(|n| !n.is_read).count()
+    }
+
+    /// Build a command to refresh notifications, unless one is already in flight
+    pub fn refresh_notifications_command(&mut self) -> Option<super::async_ops::AsyncCommand> {
+        if self.loading_notifications || self.accounts.is_empty() {
+            return None;
+        }
+        self.loading_notifications = true;
+        Some(super::async_ops::AsyncCommand::RefreshNotifications {
+            accounts: self.accounts.clone(),
+        })
+    }
+
+    /// Mark unread notifications as read locally and build the command that
+    /// does the same on the server for the networks involved
+    pub fn mark_notifications_read_command(&mut self) -> Option<super::async_ops::AsyncCommand> {
+        let mut networks = Vec::new();
+        for notification in self.notifications.iter_mut().filter(|n| !n.is_read) {
+            notification.is_read = true;
+            if !networks.contains(&notification.network) {
+                networks.push(notification.network);
+            }
+        }
+
+        let accounts: Vec<_> = self
+            .accounts
+            .iter()
+            .filter(|a| networks.contains(&a.network))
+            .cloned()
+            .collect();
+        if accounts.is_empty() {
+            return None;
+        }
+
+        Some(super::async_ops::AsyncCommand::MarkNotificationsRead { accounts })
+    }
+
+    /// Cycle through themes
+    pub fn next_theme(&mut self) {
+        self.theme = self.theme.next();
+        self.config.theme = self.theme;
+    }
+
+    /// Re-read the config file and custom themes after they were edited,
+    /// applying the theme, colors, keybindings, refresh interval, layout and
+    /// time format. A file that doesn't parse (it may have been caught halfway
+    /// through being written) leaves everything as it was.
+    pub fn reload_config(&mut self) {
+        crate::theme::reload_themes();
+        let config = match Config::load() {
+            Ok(config) => config,
+            Err(e) => {
+                // The parse error quotes the file over several lines
+                let error = format!("{e:#}");
+                let error = error.lines().next().unwrap_or_default();
+                self.set_status(format!("⚠ Config not reloaded: {error}"));
+                return;
+            }
+        };
+
+        let keymap = KeyMap::from_config(&config.keybindings);
+        if let Ok(keymap) = &keymap {
+            self.keymap = keymap.clone();
+        }
+        if config.refresh_interval_secs != self.config.refresh_interval_secs {
+            self.next_auto_refresh = std::time::Instant::now()
+                + std::time::Duration::from_secs(config.refresh_interval_secs);
+        }
+        // Switched while running: only a change in the file overrides them
+        if config.layout != self.config.layout {
+            self.layout = config.layout;
+        }
+        if config.absolute_times != self.config.absolute_times {
+            self.absolute_times = config.absolute_times;
+        }
+        crate::theme::set_monochrome(config.color.is_monochrome());
+        self.theme = config.theme;
+        self.theme_picker_index = self.theme.index();
+        self.config = config;
+
+        let warning = match (keymap, crate::theme::theme_warnings().first()) {
+            (Err(e), _) => Some(format!("⚠ Keybindings unchanged: {e:#}")),
+            (Ok(_), Some(warning)) => Some(format!("⚠ {warning}")),
+            (Ok(_), None) => None,
+        };
+        self.set_status(warning.unwrap_or_else(|| "✓ Config reloaded".to_string()));
+    }
+
+    /// Cycle through timeline filters
+    pub fn cycle_filter(&mut self) {
+        self.timeline_filter = self.timeline_filter.next();
+        // Reload posts with new filter
+        if let Ok(posts) = self.db.get_feed_posts(
+            &self.feed_cache_key(),
+            self.timeline_filter.to_network(),
+            self.config.post_limit,
+        ) {
+            self.show_posts(posts);
+            self.selected_post = 0;
+            self.mark_new_posts_seen();
+        }
+    }
+
+    /// Refresh data from database
+    pub fn refresh_data(&mut self) -> Result<()> {
+        self.accounts = self.db.get_accounts()?;
+        let posts = self
+            .db
+            .get_cached_posts(self.timeline_filter.to_network(), self.config.post_limit)?;
+        self.show_posts(posts);
+        Ok(())
+    }
+
+    /// Switch to compose, remembering whether to return to the thread view or
+    /// search results
+    const fn enter_compose_mode(&mut self) {
+        self.compose_return_mode = if matches!(
+            self.mode,
+            Mode::Thread | Mode::SearchResults | Mode::Profile
+        ) {
+            self.mode
+        } else {
+            Mode::Normal
+        };
+        self.mode = Mode::Compose;
+    }
+
+    /// Open compose view
+    pub fn open_compose(&mut self) {
+        self.enter_compose_mode();
+        self.compose_text.clear();
+        self.compose_cursor = 0;
+        self.compose_scroll = 0;
+        self.reply_to = None;
+        self.compose_edit = None;
+        self.compose_quote = None;
+        self.compose_draft = None;
+        self.compose_scheduled = None;
+        self.compose_schedule = None;
+        self.compose_schedule_input.clear();
+        self.compose_schedule_focused = false;
+        self.compose_server_side = false;
+        self.compose_thread = false;
+        self.compose_media.clear();
+        self.compose_attach_input.clear();
+        self.compose_attach_focused = false;
+        self.compose_alt_warned = false;
+        self.compose_cw.clear();
+        self.compose_cw_focused = false;
+        self.compose_visibility = Visibility::default();
+        self.compose_accounts_focused = false;
+        self.select_compose_networks(self.default_compose_networks());
+    }
+
+    /// Networks compose starts out posting to: `default_post_networks`
+    /// where they have an account, or else every network that has one
+    fn default_compose_networks(&self) -> Vec<Network> {
+        let with_accounts: Vec<Network> = self
+            .account_per_network()
+            .iter()
+            .map(|a| a.network)
+            .collect();
+        let networks: Vec<Network> = self
+            .config
+            .default_networks(&with_accounts)
+            .into_iter()
+            .filter(|n| with_accounts.contains(n))
+            .collect();
+        if networks.is_empty() {
+            with_accounts
+        } else {
+            networks
+        }
+    }
+
+    /// Replace the compose text, with the cursor at its end
+    pub fn set_compose_text(&mut self, text: String) {
+        self.compose_cursor = text.len();
+        self.compose_text = text;
+    }
+
+    /// Type `text` at the compose cursor
+    pub fn compose_insert(&mut self, text: &str) {
+        text_cursor::insert(&mut self.compose_text, &mut self.compose_cursor, text);
+    }
+
+    /// Remove the character before the compose cursor
+    pub fn compose_backspace(&mut self) {
+        text_cursor::backspace(&mut self.compose_text, &mut self.compose_cursor);
+    }
+
+    /// Remove the character after the compose cursor
+    pub fn compose_delete(&mut self) {
+        text_cursor::delete(&mut self.compose_text, &mut self.compose_cursor);
+    }
+
+    /// Move the compose cursor with one of the [`text_cursor`] movements
+    pub fn move_compose_cursor(&mut self, movement: fn(&str, usize) -> usize) {
+        let cursor = text_cursor::clamp(&self.compose_text, self.compose_cursor);
+        self.compose_cursor = movement(&self.compose_text, cursor);
+    }
+
+    /// Open reply view for a specific post
+    pub fn open_reply(&mut self, post: Post) {
+        self.enter_compose_mode();
+        self.set_compose_text(format!("@{} ", post.author_handle));
+        self.reply_to = Some(post.clone());
+        self.compose_edit = None;
+        self.compose_quote = None;
+        self.compose_draft = None;
+        self.compose_scheduled = None;
+        self.compose_schedule = None;
+        self.compose_schedule_input.clear();
+        self.compose_schedule_focused = false;
+        self.compose_server_side = false;
+        self.compose_thread = false;
+        self.compose_media.clear();
+        self.compose_attach_input.clear();
+        self.compose_attach_focused = false;
+        self.compose_alt_warned = false;
+        self.compose_cw.clear();
+        self.compose_cw_focused = false;
+        self.compose_visibility = Visibility::default();
+        self.compose_accounts_focused = false;
+        // Only reply from the account the post was loaded through
+        self.select_compose_account_for(&post);
+    }
+
+    /// Open compose to quote a post, on the post's network only
+    pub fn open_quote(&mut self, post: Post) {
+        self.open_compose();
+        self.select_compose_account_for(&post);
+        self.compose_quote = Some(post);
+    }
+
+    /// Open compose pre-filled with one of your own posts to edit it
+    pub fn open_edit(&mut self, post: Post) {
+        self.open_compose();
+        self.set_compose_text(post.content.clone());
+        self.select_compose_networks(vec![post.network]);
+        self.compose_edit = Some(post);
+    }
+
+    /// Reopen a saved draft in compose
+    pub fn open_draft(&mut self, draft: Draft) {
+        self.open_compose();
+        self.set_compose_text(draft.content.clone());
+        self.select_compose_networks(draft.networks.clone());
+        self.reply_to.clone_from(&draft.reply_to);
+        self.compose_draft = Some(draft);
+    }
+
+    /// Reopen a pending scheduled post in compose to change it
+    pub fn open_scheduled(&mut self, post: crate::ScheduledPost) {
+        self.open_compose();
+        self.set_compose_text(post.content.clone());
+        self.select_compose_networks(post.networks.clone());
+        // Written the way it's parsed back, in the schedule timezone
+        self.compose_schedule_input = post
+            .scheduled_for
+            .with_timezone(&self.config.timezone)
+            .format("%Y-%m-%d %H:%M")
+            .to_string();
+        let _ = self.apply_schedule_input();
+        self.compose_scheduled = Some(post);
+    }
+
+    /// Quit, unless a post is being written: then ask first
+    pub fn request_quit(&mut self) {
+        if self.mode == Mode::Compose && !self.compose_text.trim().is_empty() {
+            self.mode = Mode::ConfirmQuit;
+        } else {
+            self.should_quit = true;
+        }
+    }
+
+    /// Whether the post being written would be kept as a draft (edits of
+    /// posts and scheduled posts aren't)
+    pub const fn compose_is_draftable(&self) -> bool {
+        self.compose_edit.is_none() && self.compose_scheduled.is_none()
+    }
+
+    /// Save the compose buffer as a draft, if there is anything to keep.
+    ///
+    /// Returns `true` if a draft was written. Edits of posts and scheduled
+    /// posts are never saved as drafts.
+    pub fn save_compose_draft(&mut self) -> bool {
+        if !self.compose_is_draftable() {
+            return false;
+        }
+
+        if self.compose_text.trim().is_empty() {
+            // Clearing a reopened draft discards it
+            if let Some(draft) = self.compose_draft.take() {
+                let _ = self.db.delete_draft(draft.id);
+            }
+            return false;
+        }
+
+        let draft = match self.compose_draft.take() {
+            Some(existing) => Draft {
+                content: self.compose_text.clone(),
+                networks: self.compose_networks.clone(),
+                reply_to: self.reply_to.clone(),
+                updated_at: Utc::now(),
+                ..existing
+            },
+            None => Draft::new(
+                self.compose_text.clone(),
+                self.compose_networks.clone(),
+                self.reply_to.clone(),
+            ),
+        };
+        self.db.save_draft(&draft).is_ok()
+    }
+
+    /// Reload drafts from the database
+    pub fn load_drafts(&mut self) {
+        self.drafts = self.db.get_drafts().unwrap_or_default();
+        self.selected_draft = self.selected_draft.min(self.drafts.len().saturating_sub(1));
+    }
+
+    /// Reload pending scheduled posts from the database
+    pub fn load_scheduled_posts(&mut self) {
+        self.scheduled_posts = self.db.get_pending_scheduled_posts().unwrap_or_default();
+        self.selected_scheduled = self
+            .selected_scheduled
+            .min(self.scheduled_posts.len().saturating_sub(1));
+    }
+
+    /// Let the user pick one of `links` to open
+    pub fn open_link_picker(&mut self, links: Vec<String>) {
+        self.link_picker_return_mode = self.mode;
+        self.links = links;
+        self.selected_link = 0;
+        self.mode = Mode::LinkPicker;
+    }
+
+    /// Close the link picker, back to where it was opened from
+    pub fn close_link_picker(&mut self) {
+        self.mode = self.link_picker_return_mode;
+        self.links.clear();
+    }
+
+    /// Delete the draft that was just sent, if the post came from one
+    pub fn clear_sent_draft(&mut self) {
+        if let Some(id) = self.sending_draft.take() {
+            let _ = self.db.delete_draft(id);
+        }
+    }
+
+    /// Swap in the server's copy of an edited post, keeping its local identity
+    pub fn replace_post(&mut self, edited: &Post) {
+        let replies = self.current_replies.iter_mut().map(|r| &mut r.post);
+        for post in self
+            .posts
+            .iter_mut()
+            .chain(self.current_ancestors.iter_mut())
+            .chain(replies)
+            .filter(|p| p.network == edited.network && p.network_id == edited.network_id)
+        {
+            *post = Post {
+                id: post.id,
+                ..edited.clone()
+            };
+        }
+
+        if let Some(post) = self
+            .posts
+            .iter()
+            .find(|p| p.network == edited.network && p.network_id == edited.network_id)
+        {
+            self.cache_shown_post(post);
+        }
+    }
+
+    /// Close compose view
+    pub fn close_compose(&mut self) {
+        self.mode = self.compose_return_mode;
+        self.reply_to = None;
+        self.compose_edit = None;
+        self.compose_quote = None;
+        self.compose_draft = None;
+        self.compose_scheduled = None;
+        self.compose_schedule = None;
+        self.compose_schedule_input.clear();
+        self.compose_schedule_focused = false;
+        self.compose_server_side = false;
+        self.compose_thread = false;
+        self.compose_media.clear();
+        self.compose_attach_input.clear();
+        self.compose_attach_focused = false;
+        self.compose_alt_warned = false;
+        self.compose_cw.clear();
+        self.compose_cw_focused = false;
+        self.compose_accounts_focused = false;
+    }
+
+    /// Toggle schedule input focus
+    pub const fn toggle_schedule_focus(&mut self) {
+        self.compose_schedule_focused = !self.compose_schedule_focused;
+    }
+
+    /// Parse and set schedule from input
+    pub fn apply_schedule_input(&mut self) -> Result<(), String> {
+        if self.compose_schedule_input.trim().is_empty() {
+            self.compose_schedule = None;
+            return Ok(());
+        }
+
+        match crate::schedule::parse_schedule_time(
+            &self.compose_schedule_input,
+            &self.config.timezone,
+        ) {
+            Ok(dt) => {
+                self.compose_schedule = Some(dt);
+                Ok(())
+            }
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// Clear schedule
+    pub fn clear_schedule(&mut self) {
+        self.compose_schedule = None;
+        self.compose_schedule_input.clear();
+    }
+
+    /// Get schedule display text
+    pub fn schedule_display(&self) -> String {
+        if let Some(dt) = &self.compose_schedule {
+            let scheduled_post = crate::ScheduledPost::new("", vec![], *dt);
+            format!(
+                "{} (in {})",
+                scheduled_post.scheduled_time_display(&self.config.timezone),
+                scheduled_post.time_until()
+            )
+        } else if !self.compose_schedule_input.is_empty() {
+            format!("⚠️ {}", self.compose_schedule_input)
+        } else {
+            "Now".to_string()
+        }
+    }
+
+    /// Attach the image at the path in the attachment input.
+    ///
+    /// The input may carry alt text after a `|`, e.g. `~/cat.png | A sleeping cat`.
+    pub fn attach_media(&mut self) -> Result<(), String> {
+        if self.compose_media.len() >= MAX_MEDIA_ATTACHMENTS {
+            return Err(format!("At most {MAX_MEDIA_ATTACHMENTS} images per post"));
+        }
+
+        let input = self.compose_attach_input.trim();
+        let (path, alt_text) = match input.split_once('|') {
+            Some((path, alt)) => (path.trim(), Some(alt.trim().to_string())),
+            None => (input, None),
+        };
+        if path.is_empty() {
+            return Err("No file path given".to_string());
+        }
+
+        let path = crate::paths::expand_tilde(path);
+        let media = MediaUpload::from_path(&path, alt_text).map_err(|e| e.to_string())?;
+        self.compose_media.push(media);
+        self.compose_attach_input.clear();
+        self.compose_alt_warned = false;
+        Ok(())
+    }
+
+    /// Check attached images have alt text, as the `missing_alt_text`
+    /// setting asks. When it only warns, trying again sends anyway.
+    pub fn check_alt_text(&mut self) -> Result<(), String> {
+        let missing = self
+            .compose_media
+            .iter()
+            .filter(|m| m.alt_text.is_none())
+            .count();
+        if missing == 0 {
+            return Ok(());
+        }
+        let images = if missing == 1 {
+            "An image has".to_string()
+        } else {
+            format!("{missing} images have")
+        };
+        match self.config.missing_alt_text {
+            MissingAltText::Off => Ok(()),
+            MissingAltText::Warn if self.compose_alt_warned => Ok(()),
+            MissingAltText::Warn => {
+                self.compose_alt_warned = true;
+                Err(format!(
+                    "{images} no description (path | alt text), Ctrl+S again to send anyway"
+                ))
+            }
+            MissingAltText::Require => Err(format!(
+                "{images} no description, add one with path | alt text"
+            )),
+        }
+    }
+
+    /// Whether the visibility selector applies (only Mastodon has visibility levels)
+    pub fn compose_visibility_enabled(&self) -> bool {
+        self.compose_networks
+            .iter()
+            .any(|n| n.capabilities().visibility)
+    }
+
+    /// Build a command to stream new posts for every account, if streaming
+    /// is enabled
+    pub fn stream_command(&self) -> Option<super::async_ops::AsyncCommand> {
+        if !self.config.streaming {
+            return None;
+        }
+        let command = if self.accounts.is_empty() {
+            StreamCommand::Stop
+        } else {
+            StreamCommand::Start {
+                accounts: self.accounts.clone(),
+            }
+        };
+        Some(super::async_ops::AsyncCommand::Stream(command))
+    }
+
+    /// Commands to look up the post length limit of Mastodon accounts that
+    /// don't have one stored
+    pub fn post_limit_commands(&self) -> Vec<super::async_ops::AsyncCommand> {
+        self.accounts
+            .iter()
+            .filter(|a| a.network.capabilities().server_char_limit && a.max_post_chars.is_none())
+            .map(|account| super::async_ops::AsyncCommand::FetchPostLimit {
+                account: account.clone(),
+            })
+            .collect()
+    }
+
+    /// Store the post length limit an account's server reported
+    pub fn set_post_char_limit(&mut self, account_id: uuid::Uuid, max_chars: usize) {
+        if let Err(e) = self.db.update_account_max_post_chars(account_id, max_chars) {
+            tracing::warn!("Failed to save post length limit: {e}");
+        }
+        if let Some(account) = self.accounts.iter_mut().find(|a| a.id == account_id) {
+            account.max_post_chars = Some(max_chars);
+        }
+    }
+
+    /// Longest post compose can send: the tightest limit of the accounts it
+    /// will post from
+    pub fn compose_char_limit(&self) -> usize {
+        self.compose_posting_accounts()
+            .iter()
+            .map(Account::post_char_limit)
+            .min()
+            .unwrap_or_else(|| Network::Mastodon.default_char_limit())
+    }
+
+    /// Most posts a thread needs on any one account, when compose splits
+    /// long posts into threads
+    pub fn compose_thread_len(&self) -> usize {
+        self.compose_posting_accounts()
+            .iter()
+            .map(|a| split_thread(&self.compose_text, a.post_char_limit()).len())
+            .max()
+            .unwrap_or(1)
+    }
+
+    /// Replace the main timeline's posts after a refresh, keeping the same
+    /// post selected (and its detail scrolled as it was) if it's still there
+    pub fn replace_posts(&mut self, mut posts: Vec<Post>) {
+        let filtered = self.filter_posts(&mut posts);
+        // A hashtag feed may be covering the home timeline
+        if let Some(covered) = self.feed_stack.first_mut() {
+            covered.selected_post = reselect(&covered.posts, covered.selected_post, &posts).0;
+            covered.posts = posts;
+            covered.filtered = filtered;
+            return;
+        }
+
+        let (selected, same_post) = reselect(&self.posts, self.selected_post, &posts);
+        self.posts = posts;
+        self.filtered = filtered;
+        self.selected_post = selected;
+        if !same_post {
+            self.reset_post_context();
+        }
+    }
+
+    /// Append older posts to the timeline, skipping ones already shown
+    pub fn append_posts(&mut self, mut posts: Vec<Post>) -> usize {
+        let filtered = self.filter_posts(&mut posts);
+        // A hashtag feed may be covering the home timeline
+        let (shown, hidden) = match self.feed_stack.first_mut() {
+            Some(covered) => (&mut covered.posts, &mut covered.filtered),
+            None => (&mut self.posts, &mut self.filtered),
+        };
+        hidden.extend(filtered);
+        let mut seen: std::collections::HashSet<(Network, String)> =
+            shown.iter().map(Post::dedup_key).collect();
+
+        let before = shown.len();
+        shown.extend(posts.into_iter().filter(|p| seen.insert(p.dedup_key())));
+        shown.len() - before
+    }
+
+    /// Build a command to fetch the next page, if there is one and none is in flight
+    pub fn load_more_command(&mut self) -> Option<super::async_ops::AsyncCommand> {
+        if self.loading_more || self.timeline_cursors.is_empty() {
+            return None;
+        }
+
+        let accounts: Vec<_> = self
+            .accounts
+            .iter()
+            .filter(|a| self.timeline_cursors.contains_key(&a.id))
+            .filter(|a| {
+                self.timeline_filter
+                    .to_network()
+                    .is_none_or(|n| n == a.network)
+            })
+            .cloned()
+            .collect();
+        if accounts.is_empty() {
+            return None;
+        }
+
+        // Take the cursors being used; the result hands back the next ones
+        let cursors = accounts
+            .iter()
+            .filter_map(|a| {
+                self.timeline_cursors
+                    .remove(&a.id)
+                    .map(|cursor| (a.id, cursor))
+            })
+            .collect();
+
+        self.loading_more = true;
+        Some(super::async_ops::AsyncCommand::LoadMore {
+            accounts,
+            cursors,
+            kind: self.timeline_kind,
+        })
+    }
+
+    /// Whether a post's body is hidden behind its content warning
+    pub fn is_cw_collapsed(&self, post: &Post) -> bool {
+        self.content_warning(post).is_some()
+            && !self
+                .revealed_cw
+                .contains(&(post.network, post.network_id.clone()))
+    }
+
+    /// When a post or notification was made, as lists show it: relative
+    /// ("5m"), or the time in the configured timezone once switched
+    pub fn display_time(&self, at: DateTime<Utc>) -> String {
+        if self.absolute_times {
+            self.config.timezone.format_short(at)
+        } else {
+            crate::models::format_relative_time(at)
+        }
+    }
+
+    /// Switch between relative and exact times
+    pub fn toggle_absolute_times(&mut self) {
+        self.absolute_times = !self.absolute_times;
+        self.set_status(if self.absolute_times {
+            "🕒 Showing exact times"
+        } else {
+            "🕒 Showing relative times"
+        });
+    }
+
+    /// Switch to the next timeline layout
+    pub fn cycle_layout(&mut self) {
+        self.layout = self.layout.next();
+        self.set_status(match self.layout {
+            TimelineLayout::Split => "▥ List and post side by side",
+            TimelineLayout::Single => "▯ One panel at a time — Enter opens a post",
+        });
+    }
+
+    /// Whether a post's media alt text is shown in full
+    pub fn is_alt_text_expanded(&self, post: &Post) -> bool {
+        self.revealed_alt
+            .contains(&(post.network, post.network_id.clone()))
+    }
+
+    /// Show a post's media alt text in full, or shortened again
+    pub fn toggle_alt_text(&mut self, post: &Post) {
+        let key = (post.network, post.network_id.clone());
+        if !self.revealed_alt.remove(&key) {
+            self.revealed_alt.insert(key);
+        }
+    }
+
+    /// Expand or collapse a post's content warning
+    pub fn toggle_cw(&mut self, post: &Post) {
+        let key = (post.network, post.network_id.clone());
+        if !self.revealed_cw.remove(&key) {
+            self.revealed_cw.insert(key);
+        }
+    }
+
+    /// Accounts compose will post from
+    pub fn compose_posting_accounts(&self) -> Vec<Account> {
+        self.accounts
+            .iter()
+            .filter(|a| self.compose_accounts.contains(&a.id))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether compose posts from exactly one account per network, the
+    /// default one (the only way scheduled posts can be sent)
+    pub fn compose_uses_default_accounts(&self) -> bool {
+        let defaults: Vec<_> = self
+            .account_per_network()
+            .iter()
+            .filter(|a| self.compose_networks.contains(&a.network))
+            .map(|a| a.id)
+            .collect();
+        defaults.len() == self.compose_accounts.len()
+            && defaults.iter().all(|id| self.compose_accounts.contains(id))
+    }
+
+    /// Post only from the account that acts on `post`, on its network
+    fn select_compose_account_for(&mut self, post: &Post) {
+        self.compose_networks = vec![post.network];
+        self.compose_accounts = post
+            .acting_account(&self.accounts)
+            .map(|a| a.id)
+            .into_iter()
+            .collect();
+    }
+
+    /// Post to these networks from the default account of each
+    fn select_compose_networks(&mut self, networks: Vec<Network>) {
+        self.compose_accounts = self
+            .account_per_network()
+            .iter()
+            .filter(|a| networks.contains(&a.network))
+            .map(|a| a.id)
+            .collect();
+        self.compose_networks = networks;
+    }
+
+    /// Toggle network in compose
+    pub fn toggle_compose_network(&mut self, network: Network) {
+        if let Some(idx) = self.compose_networks.iter().position(|n| *n == network) {
+            self.compose_networks.remove(idx);
+            self.compose_accounts.retain(|id| {
+                !self
+                    .accounts
+                    .iter()
+                    .any(|a| a.id == *id && a.network == network)
+            });
+        } else {
+            self.compose_networks.push(network);
+            if let Some(account) = self
+                .account_per_network()
+                .into_iter()
+                .find(|a| a.network == network)
+            {
+                self.compose_accounts.push(account.id);
+            }
+        }
+    }
+
+    /// Toggle posting from an account in compose, selecting its network when
+    /// it's the first account picked there
+    pub fn toggle_compose_account(&mut self, index: usize) {
+        let Some(account) = self.accounts.get(index) else {
+            return;
+        };
+        let (id, network) = (account.id, account.network);
+
+        if let Some(idx) = self.compose_accounts.iter().position(|a| *a == id) {
+            self.compose_accounts.remove(idx);
+        } else {
+            self.compose_accounts.push(id);
+        }
+
+        let network_used = self
+            .accounts
+            .iter()
+            .any(|a| a.network == network && self.compose_accounts.contains(&a.id));
+        if network_used && !self.compose_networks.contains(&network) {
+            self.compose_networks.push(network);
+        } else if !network_used {
+            self.compose_networks.retain(|n| *n != network);
+        }
+    }
+
+    /// Navigate to the next view
+    pub const fn next_view(&mut self) {
+        self.view = match self.view {
+            View::Timeline => View::Accounts,
+            View::Accounts => View::Notifications,
+            View::Notifications => View::Timeline,
+        };
+    }
+
+    /// Navigate to the previous view
+    pub const fn prev_view(&mut self) {
+        self.view = match self.view {
+            View::Timeline => View::Notifications,
+            View::Accounts => View::Timeline,
+            View::Notifications => View::Accounts,
+        };
+    }
+
+    /// Whether avatars should be drawn next to authors.
+    ///
+    /// Avatars are only worth it with a native graphics protocol; on
+    /// halfblocks terminals a 2-row image is an unreadable smudge.
+    pub fn avatars_enabled(&self) -> bool {
+        self.config.show_avatars && crate::images::has_native_graphics()
+    }
+
+    /// Style of the marker showing which account a post was loaded
+    /// through, while several accounts' posts mix and markers are on. They
+    /// can't be told apart without colors, so there are none then either.
+    pub fn account_marker(&self, account_id: Option<uuid::Uuid>) -> Option<Style> {
+        let colors = self.theme.colors();
+        if !self.config.account_colors || self.accounts.len() < 2 || colors.monochrome {
+            return None;
+        }
+        let index = self
+            .accounts
+            .iter()
+            .position(|a| Some(a.id) == account_id)?;
+        let color = self.config.account_marker_colors(&self.accounts)[index];
+        Some(colors.account_marker(color))
+    }
+
+    /// Whether animated GIFs should play. Like avatars, they need a native
+    /// graphics protocol.
+    pub fn animations_enabled(&self) -> bool {
+        self.config.animate_images && crate::images::has_native_graphics()
+    }
+
+    /// URLs of a post's images to show, in order. The first one is the full
+    /// GIF when it should play; the rest are previews.
+    pub fn image_urls<'a>(&self, post: &'a Post) -> Vec<&'a str> {
+        let animate = self.animations_enabled();
+        post.media
+            .iter()
+            .filter(|m| m.media_type == crate::models::MediaType::Image)
+            .enumerate()
+            .map(|(i, media)| media.display_url(animate && i == 0))
+            .collect()
+    }
+
+    /// Whether `url` is the selected post's GIF, which should load with all
+    /// its frames
+    pub fn plays_animation(&self, url: &str) -> bool {
+        self.animations_enabled()
+            && self
+                .selected_post()
+                .and_then(|post| {
+                    post.media
+                        .iter()
+                        .find(|m| m.media_type == crate::models::MediaType::Image)
+                })
+                .is_some_and(|media| media.is_gif() && media.url == url)
+    }
+
+    /// Whether an animation is ready to play for `url`
+    pub fn is_playing(&self, url: &str) -> bool {
+        self.animations_enabled() && self.animated_images.iter().any(|(u, _)| u == url)
+    }
+
+    /// Start playing an animation that finished loading
+    pub fn add_animation(&mut self, url: String, animation: crate::images::Animation) {
+        self.animated_images.retain(|(u, _)| *u != url);
+        if self.animated_images.len() >= MAX_ANIMATIONS {
+            let (old_url, _) = self.animated_images.remove(0);
+            // Forget the still too, so the animation loads again when needed
+            self.image_cache.remove(&old_url);
+            self.image_protocols.remove(&old_url);
+        }
+        self.animated_images
+            .push((url, crate::images::AnimatedImage::new(animation)));
+    }
+
+    /// Get URLs of images the current view shows, with how soon each is
+    /// needed. Includes images already cached.
+    pub fn wanted_images(&self) -> Vec<(String, ImagePriority)> {
+        let mut urls: Vec<(String, ImagePriority)> = Vec::new();
+        let mut queue = |url: &str, priority| {
+            if !urls.iter().any(|(u, _)| u == url) {
+                urls.push((url.to_string(), priority));
+            }
+        };
+
+        if self.show_images {
+            // Images from selected post, the one in the detail panel first
+            if let Some(post) = self.selected_post() {
+                for (i, url) in self.image_urls(post).into_iter().enumerate() {
+                    let priority = if i == 0 {
+                        ImagePriority::Selected
+                    } else {
+                        ImagePriority::Media
+                    };
+                    queue(url, priority);
+                }
+            }
+
+            // Images from replies (limit to first 5 to avoid loading too many)
+            for reply in self.current_replies.iter().take(5) {
+                for media in &reply.post.media {
+                    if media.media_type == crate::models::MediaType::Image {
+                        queue(media.display_url(false), ImagePriority::Media);
+                    }
+                }
+            }
+        }
+
+        if self.avatars_enabled() {
+            match self.view {
+                View::Timeline => {
+                    // Posts around the selection, which covers what is on screen
+                    let start = self.selected_post.saturating_sub(AVATAR_PRELOAD);
+                    for post in self.posts.iter().skip(start).take(AVATAR_PRELOAD * 2) {
+                        if let Some(url) = &post.author_avatar {
+                            queue(url, ImagePriority::Avatar);
+                        }
+                    }
+                }
+                View::Accounts => {
+                    for account in &self.accounts {
+                        if let Some(url) = &account.avatar_url {
+                            queue(url, ImagePriority::Avatar);
+                        }
+                    }
+                }
+                View::Notifications => {
+                    let start = self.selected_notification.saturating_sub(AVATAR_PRELOAD);
+                    for notification in self
+                        .notifications
+                        .iter()
+                        .skip(start)
+                        .take(AVATAR_PRELOAD * 2)
+                    {
+                        if let Some(url) = &notification.actor_avatar {
+                            queue(url, ImagePriority::Avatar);
+                        }
+                    }
+                }
+            }
+        }
+
+        urls
+    }
+
+    /// Get images that should be loaded for the current view.
+    /// Returns those that are not yet cached, loading or known to fail.
+    pub fn get_images_to_load(&self) -> Vec<(String, ImagePriority)> {
+        self.wanted_images()
+            .into_iter()
+            .filter(|(url, _)| {
+                !self.image_cache.contains(url)
+                    && !self.loading_images.contains(url)
+                    && !self.failed_images.contains(url)
+            })
+            .collect()
+    }
+
+    /// Whether loading an attachment's image failed
+    pub fn media_failed(&self, media: &crate::models::MediaAttachment) -> bool {
+        self.failed_images.contains(&media.url)
+            || media
+                .preview_url
+                .as_ref()
+                .is_some_and(|url| self.failed_images.contains(url))
+    }
+
+    /// Forget that a post's images failed, so they're loaded again while
+    /// they're in view. Returns how many there were.
+    pub fn retry_failed_images(&mut self, post: &Post) -> usize {
+        post.media
+            .iter()
+            .filter(|media| {
+                let full = self.failed_images.remove(&media.url);
+                let preview = media
+                    .preview_url
+                    .as_ref()
+                    .is_some_and(|url| self.failed_images.remove(url));
+                full || preview
+            })
+            .count()
+    }
+
+    /// Mark images as loading (to avoid duplicate requests).
+    pub fn mark_images_loading(&mut self, images: &[(String, ImagePriority)]) {
+        for (url, _) in images {
+            self.loading_images.insert(url.clone());
+        }
+    }
+
+    /// Get or create a `StatefulProtocol` for the blurry placeholder of an
+    /// image that is still loading.
+    /// Returns None if the blurhash is invalid or picker is not available.
+    pub fn get_placeholder_protocol(
+        &mut self,
+        blurhash: &str,
+    ) -> Option<&mut ratatui_image::protocol::StatefulProtocol> {
+        let key = format!("blurhash:{blurhash}");
+        if !self.image_protocols.contains_key(&key) {
+            let (width, height) = PLACEHOLDER_SIZE;
+            let image = crate::images::blurhash::decode(blurhash, width, height)?;
+            let picker = crate::images::picker()?;
+            self.image_protocols
+                .insert(key.clone(), picker.new_resize_protocol(image));
+        }
+        self.image_protocols.get_mut(&key)
+    }
+
+    /// Get or create a `StatefulProtocol` for rendering an image, showing
+    /// the current frame if it's a playing animation.
+    /// Returns None if image is not cached or picker is not available.
+    pub fn get_image_protocol(
+        &mut self,
+        url: &str,
+    ) -> Option<&mut ratatui_image::protocol::StatefulProtocol> {
+        if self.is_playing(url)
+            && let Some((_, animated)) = self.animated_images.iter_mut().find(|(u, _)| u == url)
+        {
+            return animated.current_protocol();
+        }
+
+        // If already have a protocol, return it
+        if self.image_protocols.contains_key(url) {
+            return self.image_protocols.get_mut(url);
+        }
+
+        // Try to create one from cached image
+        let image = self.image_cache.get(url)?;
         let picker = crate::images::picker()?;
 
         // Create the protocol state
@@ -534,3 +2790,51 @@ impl AppState {
         self.image_protocols.get_mut(url)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(id: &str, author: &str, content: &str) -> Post {
+        let mut post = Post::new(Network::Mastodon, id);
+        post.author_handle = author.to_string();
+        post.content = content.to_string();
+        post
+    }
+
+    #[test]
+    fn test_older_posts_for_covered_home_are_filtered() {
+        let mut state =
+            AppState::new(Config::default(), Database::open_in_memory().unwrap()).unwrap();
+        state.moderated.push(ModeratedAccount::new(
+            Network::Mastodon,
+            "troll",
+            Moderation::Mute,
+        ));
+        state.filters = Filters::new(&[FilterRule {
+            pattern: "spoiler".to_string(),
+            action: crate::filters::FilterAction::Hide,
+        }])
+        .unwrap();
+        state.show_posts(vec![post("1", "ana", "First")]);
+        let _ = state.open_hashtag("rust".to_string());
+        let tagged = state.posts.len();
+
+        let added = state.append_posts(vec![
+            post("1", "ana", "First"),
+            post("2", "troll", "Muted"),
+            post("3", "ana", "A spoiler"),
+            post("4", "ana", "Older"),
+        ]);
+
+        assert_eq!(added, 1);
+        assert_eq!(state.posts.len(), tagged);
+        let home = &state.feed_stack[0];
+        let ids: Vec<_> = home.posts.iter().map(|p| p.network_id.as_str()).collect();
+        assert_eq!(ids, ["1", "4"]);
+        assert!(
+            home.filtered
+                .contains(&(Network::Mastodon, "3".to_string()))
+        );
+    }
+}
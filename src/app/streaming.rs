@@ -0,0 +1,142 @@
+//! Background streams of new home timeline posts
+//!
+//! Each account gets a task that keeps a streaming connection open,
+//! reconnecting with exponential backoff, and forwards new posts to the TUI
+//! as [`AsyncResult::StreamedPosts`]. Failures go back as
+//! [`AsyncResult::StreamFailed`], for the status bar, as anything printed
+//! would land on top of the TUI.
+
+use anyhow::{Context, Result};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use super::async_ops::AsyncResult;
+use crate::api::get_client;
+use crate::auth;
+use crate::models::{Account, Post};
+
+/// Wait before the first reconnect
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Longest wait between reconnects
+const MAX_BACKOFF: Duration = Duration::from_mins(2);
+
+/// A connection that stayed up this long resets the backoff
+const STABLE_CONNECTION: Duration = Duration::from_mins(1);
+
+/// Commands for the streaming tasks
+#[derive(Debug, Clone)]
+pub enum StreamCommand {
+    /// Stream posts for these accounts, replacing any running streams
+    Start { accounts: Vec<Account> },
+    /// Stop all streams
+    Stop,
+}
+
+/// Running stream tasks, one per account
+#[derive(Default)]
+pub struct Streams {
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Streams {
+    /// Apply a command, sending streamed posts to `result_tx`
+    pub fn handle(&mut self, command: StreamCommand, result_tx: &mpsc::Sender<AsyncResult>) {
+        self.stop();
+        if let StreamCommand::Start { accounts } = command {
+            self.tasks = accounts
+                .into_iter()
+                .map(|account| tokio::spawn(stream_account(account, result_tx.clone())))
+                .collect();
+        }
+    }
+
+    fn stop(&mut self) {
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
+    }
+}
+
+impl Drop for Streams {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Keep one account's stream running until the TUI goes away
+async fn stream_account(account: Account, result_tx: mpsc::Sender<AsyncResult>) {
+    let failed = |error: String, retry| AsyncResult::StreamFailed {
+        handle: account.handle.clone(),
+        error,
+        retry,
+    };
+    let token = match auth::get_credentials(&account) {
+        Ok(Some(token)) => token,
+        Ok(None) => {
+            tracing::debug!("Not streaming @{}: no credentials", account.handle);
+            let _ = result_tx
+                .send(failed("no credentials".to_string(), None))
+                .await;
+            return;
+        }
+        Err(e) => {
+            tracing::debug!("Not streaming @{}: {e}", account.handle);
+            let _ = result_tx.send(failed(e.to_string(), None)).await;
+            return;
+        }
+    };
+
+    let mut backoff = INITIAL_BACKOFF;
+    while !result_tx.is_closed() {
+        let connected_at = Instant::now();
+        let result = stream_once(&account, &token, &result_tx).await;
+        if connected_at.elapsed() >= STABLE_CONNECTION {
+            backoff = INITIAL_BACKOFF;
+        }
+        match result {
+            Ok(()) => tracing::debug!("Stream for @{} closed", account.handle),
+            Err(e) => {
+                tracing::debug!("Stream for @{} failed: {e:#}", account.handle);
+                let _ = result_tx
+                    .send(failed(format!("{e:#}"), Some(backoff)))
+                    .await;
+            }
+        }
+
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+/// Connect once and forward posts until the stream ends
+async fn stream_once(
+    account: &Account,
+    token: &str,
+    result_tx: &mpsc::Sender<AsyncResult>,
+) -> Result<()> {
+    let client = get_client(account, token)
+        .await
+        .context("Failed to create client")?;
+    let (post_tx, mut post_rx) = mpsc::channel(16);
+    let owner = account.clone();
+
+    let stream = async move { client.stream_home(&post_tx).await };
+    let forward = async move {
+        while let Some(mut posts) = post_rx.recv().await {
+            Post::set_owner(&mut posts, &owner);
+            if result_tx
+                .send(AsyncResult::StreamedPosts { posts })
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    };
+
+    let (result, ()) = tokio::join!(stream, forward);
+    result
+}
@@ -0,0 +1,134 @@
+//! Taking over the terminal and handing it back
+//!
+//! [`TerminalGuard`] puts the terminal in raw mode on the alternate screen
+//! and restores it when dropped, so an early `?` return can't leave it
+//! garbled. A panic hook restores it too, before the panic message prints,
+//! so the message shows up on the normal screen rather than being wiped
+//! with the alternate one.
+
+use std::io::stdout;
+use std::sync::{Mutex, Once};
+use std::thread::ThreadId;
+
+use anyhow::Result;
+use crossterm::{
+    cursor::Show,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+
+/// Thread running the TUI while the terminal is taken over. Only its panics
+/// end the TUI; worker threads panicking shouldn't touch the terminal.
+static OWNER: Mutex<Option<ThreadId>> = Mutex::new(None);
+
+/// Restores the terminal when dropped
+pub struct TerminalGuard {
+    mouse: bool,
+}
+
+impl TerminalGuard {
+    /// Switch to raw mode on the alternate screen, capturing the mouse if
+    /// `mouse` is set
+    pub fn enter(mouse: bool) -> Result<Self> {
+        install_panic_hook();
+        // Created first, so a step failing below still undoes the others
+        let guard = Self::take_over(mouse);
+        enable_raw_mode()?;
+        execute!(stdout(), EnterAlternateScreen)?;
+        // Capturing the mouse takes over the terminal's own text selection
+        if mouse {
+            execute!(stdout(), EnableMouseCapture)?;
+        }
+        Ok(guard)
+    }
+
+    fn take_over(mouse: bool) -> Self {
+        *owner() = Some(std::thread::current().id());
+        Self { mouse }
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        if owner().take().is_some() {
+            restore(self.mouse);
+        }
+    }
+}
+
+fn owner() -> std::sync::MutexGuard<'static, Option<ThreadId>> {
+    OWNER
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Undo everything [`TerminalGuard::enter`] did, ignoring failures: this
+/// runs on the way out, when there's nothing better to do about them
+fn restore(mouse: bool) {
+    let _ = disable_raw_mode();
+    let mut stdout = stdout();
+    if mouse {
+        let _ = execute!(stdout, DisableMouseCapture);
+    }
+    let _ = execute!(stdout, LeaveAlternateScreen, Show);
+}
+
+/// Restore the terminal before a panic on the TUI thread is reported
+fn install_panic_hook() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let report = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let current = std::thread::current().id();
+            let mut owner = owner();
+            if *owner == Some(current) {
+                *owner = None;
+                drop(owner);
+                // Whether the mouse was captured isn't known here, and
+                // releasing it when it wasn't does no harm
+                restore(true);
+            } else {
+                drop(owner);
+            }
+            report(info);
+        }));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn restored() -> bool {
+        owner().is_none()
+    }
+
+    // One test, since the owner is shared by the whole process
+    #[test]
+    fn test_restores_on_panic_and_early_return() {
+        install_panic_hook();
+
+        // A forced panic restores it from the hook, while the guard is
+        // still alive
+        let guard = TerminalGuard::take_over(false);
+        let result = std::panic::catch_unwind(|| panic!("forced panic"));
+        assert!(result.is_err());
+        assert!(restored());
+        drop(guard);
+
+        // A panic on another thread leaves the terminal to the TUI
+        let guard = TerminalGuard::take_over(false);
+        let worker = std::thread::spawn(|| panic!("worker panic"));
+        assert!(worker.join().is_err());
+        assert!(!restored());
+
+        // Dropping the guard on an early return restores it
+        let setup = || -> Result<()> {
+            let _guard = guard;
+            anyhow::bail!("setup failed");
+        };
+        assert!(setup().is_err());
+        assert!(restored());
+    }
+}
@@ -0,0 +1,254 @@
+//! Editing text at a cursor
+//!
+//! Cursors are byte offsets into the text that always sit on a character
+//! boundary, so they can be used to slice and insert directly. The movement
+//! functions take the text and a cursor and return where the cursor moves to.
+
+use std::ops::Range;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// The cursor moved back onto a character boundary (and into the text) if
+/// it isn't on one
+pub fn clamp(text: &str, cursor: usize) -> usize {
+    let mut cursor = cursor.min(text.len());
+    while !text.is_char_boundary(cursor) {
+        cursor -= 1;
+    }
+    cursor
+}
+
+/// Insert `s` at the cursor and move the cursor past it
+pub fn insert(text: &mut String, cursor: &mut usize, s: &str) {
+    *cursor = clamp(text, *cursor);
+    text.insert_str(*cursor, s);
+    *cursor += s.len();
+}
+
+/// Remove the character before the cursor
+pub fn backspace(text: &mut String, cursor: &mut usize) {
+    let end = clamp(text, *cursor);
+    *cursor = prev_char(text, end);
+    text.replace_range(*cursor..end, "");
+}
+
+/// Remove the character after the cursor
+pub fn delete(text: &mut String, cursor: &mut usize) {
+    *cursor = clamp(text, *cursor);
+    let end = next_char(text, *cursor);
+    text.replace_range(*cursor..end, "");
+}
+
+/// One character back
+pub fn prev_char(text: &str, cursor: usize) -> usize {
+    text[..cursor]
+        .char_indices()
+        .next_back()
+        .map_or(0, |(i, _)| i)
+}
+
+/// One character forward
+pub fn next_char(text: &str, cursor: usize) -> usize {
+    text[cursor..]
+        .chars()
+        .next()
+        .map_or(cursor, |c| cursor + c.len_utf8())
+}
+
+/// Start of the cursor's line
+pub fn line_start(text: &str, cursor: usize) -> usize {
+    text[..cursor].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// End of the cursor's line, before its newline
+pub fn line_end(text: &str, cursor: usize) -> usize {
+    text[cursor..].find('\n').map_or(text.len(), |i| cursor + i)
+}
+
+/// Start of the word before the cursor
+pub fn prev_word(text: &str, cursor: usize) -> usize {
+    let before = text[..cursor].trim_end();
+    before
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_whitespace())
+        .map_or(0, |(i, c)| i + c.len_utf8())
+}
+
+/// End of the word after the cursor
+pub fn next_word(text: &str, cursor: usize) -> usize {
+    let after = &text[cursor..];
+    let word = after.len() - after.trim_start().len();
+    after[word..]
+        .find(char::is_whitespace)
+        .map_or(text.len(), |i| cursor + word + i)
+}
+
+/// Same column on the line above, or the start of the text on the first line
+pub fn line_up(text: &str, cursor: usize) -> usize {
+    let start = line_start(text, cursor);
+    if start == 0 {
+        return 0;
+    }
+    let column = text[start..cursor].chars().count();
+    nth_char_or_end(text, line_start(text, start - 1), column)
+}
+
+/// Same column on the line below, or the end of the text on the last line
+pub fn line_down(text: &str, cursor: usize) -> usize {
+    let end = line_end(text, cursor);
+    if end == text.len() {
+        return end;
+    }
+    let column = text[line_start(text, cursor)..cursor].chars().count();
+    nth_char_or_end(text, end + 1, column)
+}
+
+/// Word-wrap the text into rows at most `width` columns wide, as byte ranges
+///
+/// Rows break after the last whitespace that fits, or mid-word when a word
+/// is wider than a row. Newlines end a row and aren't part of any.
+pub fn wrap(text: &str, width: usize) -> Vec<Range<usize>> {
+    let width = width.max(1);
+    let mut rows = Vec::new();
+    let mut line_start = 0;
+    for line in text.split('\n') {
+        let mut start = line_start;
+        let mut used = 0;
+        // Where the row can break, after whitespace
+        let mut break_at = None;
+        for (i, c) in line.char_indices() {
+            let i = line_start + i;
+            let c_width = c.width().unwrap_or(0);
+            if used + c_width > width && i > start {
+                let end = break_at.filter(|&b| b > start).unwrap_or(i);
+                rows.push(start..end);
+                start = end;
+                used = text[start..i].width();
+                break_at = None;
+            }
+            used += c_width;
+            if c.is_whitespace() {
+                break_at = Some(i + c.len_utf8());
+            }
+        }
+        rows.push(start..line_start + line.len());
+        line_start += line.len() + 1;
+    }
+    rows
+}
+
+/// Row and column of the cursor among rows from [`wrap`]
+///
+/// A cursor where a row wraps is shown at the start of the next row.
+pub fn locate(text: &str, rows: &[Range<usize>], cursor: usize) -> (usize, usize) {
+    let row = rows
+        .iter()
+        .rposition(|r| r.start <= cursor)
+        .unwrap_or_default();
+    let start = rows.get(row).map_or(0, |r| r.start);
+    (row, text[start..cursor.max(start)].width())
+}
+
+/// `column` characters into the line starting at `start`, or its end
+fn nth_char_or_end(text: &str, start: usize, column: usize) -> usize {
+    let end = line_end(text, start);
+    text[start..end]
+        .char_indices()
+        .nth(column)
+        .map_or(end, |(i, _)| start + i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edits_multibyte_text() {
+        let mut text = String::from("naïve 🐘");
+        let mut cursor = text.len();
+
+        backspace(&mut text, &mut cursor);
+        assert_eq!(text, "naïve ");
+        cursor = prev_char(&text, prev_char(&text, prev_char(&text, cursor)));
+        assert_eq!(&text[cursor..], "ve ");
+        backspace(&mut text, &mut cursor);
+        assert_eq!(text, "nave ");
+        insert(&mut text, &mut cursor, "ï");
+        insert(&mut text, &mut cursor, "🦋");
+        assert_eq!(text, "naï🦋ve ");
+        delete(&mut text, &mut cursor);
+        assert_eq!(text, "naï🦋e ");
+        assert_eq!(&text[cursor..], "e ");
+
+        // Nothing to remove at either end
+        let mut start = 0;
+        backspace(&mut text, &mut start);
+        let mut end = text.len();
+        delete(&mut text, &mut end);
+        assert_eq!(text, "naï🦋e ");
+    }
+
+    #[test]
+    fn test_moves_by_character() {
+        let text = "añ🐘b";
+        let mut cursor = text.len();
+        let mut stops = vec![cursor];
+        while cursor > 0 {
+            cursor = prev_char(text, cursor);
+            stops.push(cursor);
+        }
+        assert_eq!(stops, [8, 7, 3, 1, 0]);
+        assert_eq!(next_char(text, 1), 3);
+        assert_eq!(next_char(text, 8), 8);
+        assert_eq!(clamp(text, 5), 3);
+        assert_eq!(clamp(text, 99), 8);
+    }
+
+    #[test]
+    fn test_moves_by_word() {
+        let text = "héllo  wörld\nagain";
+        assert_eq!(prev_word(text, text.len()), 15);
+        assert_eq!(prev_word(text, 15), 8);
+        assert_eq!(prev_word(text, 8), 0);
+        assert_eq!(next_word(text, 0), 6);
+        assert_eq!(next_word(text, 6), 14);
+        assert_eq!(next_word(text, 14), text.len());
+    }
+
+    #[test]
+    fn test_wraps_at_words() {
+        let text = "the 🐘 walks\n\nsupercalifragilistic";
+        let rows = wrap(text, 8);
+        let shown: Vec<&str> = rows.iter().map(|r| &text[r.clone()]).collect();
+        // The elephant is two columns wide
+        assert_eq!(
+            shown,
+            ["the 🐘 ", "walks", "", "supercal", "ifragili", "stic"]
+        );
+
+        assert_eq!(locate(text, &rows, 0), (0, 0));
+        assert_eq!(locate(text, &rows, 4), (0, 4));
+        // Where "walks" wraps, the cursor goes to the next row
+        assert_eq!(locate(text, &rows, 9), (1, 0));
+        assert_eq!(locate(text, &rows, 14), (1, 5));
+        assert_eq!(locate(text, &rows, 15), (2, 0));
+        assert_eq!(locate(text, &rows, text.len()), (5, 4));
+        assert_eq!(wrap("", 8), vec![0..0]);
+    }
+
+    #[test]
+    fn test_moves_by_line() {
+        let text = "ab\nçdéf\ng";
+        assert_eq!(line_start(text, 6), 3);
+        assert_eq!(line_end(text, 3), 9);
+        // From after "ç" up to after "a", and back down
+        assert_eq!(line_up(text, 5), 1);
+        assert_eq!(line_down(text, 1), 5);
+        // Shorter lines keep the cursor at their end
+        assert_eq!(line_down(text, 9), 11);
+        assert_eq!(line_up(text, 11), 5);
+        assert_eq!(line_up(text, 1), 0);
+        assert_eq!(line_down(text, 11), text.len());
+    }
+}
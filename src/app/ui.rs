@@ -2,15 +2,27 @@
 
 use ratatui::{
     Frame,
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph, Tabs, Wrap},
+    widgets::{
+        Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Tabs, Wrap,
+    },
 };
 use ratatui_image::StatefulImage;
+use std::fmt::Write as _;
+use std::ops::Range;
 
-use super::state::{AppState, FocusedPanel, Mode, TimelineFilter, View};
-use crate::theme::Theme;
+use super::state::{
+    AppState, Feed, FocusedPanel, HitRegions, Mode, PendingDelete, SearchScope, TimelineFilter,
+    View,
+};
+use super::text_cursor;
+use crate::api::TimelineKind;
+use crate::config::TimelineLayout;
+use crate::models::{FollowState, Moderation, Network, Visibility, find_links, split_hashtags};
+use crate::theme::{Theme, ThemeColors};
 
 /// ASCII art logo for Perch
 #[allow(dead_code)]
@@ -26,6 +38,32 @@ const LOGO: &str = r"
 /// Perch icon
 const ICON: &str = "🐦";
 
+/// Avatar width in cells (4x2 cells is roughly square on most fonts)
+const AVATAR_WIDTH: u16 = 4;
+
+/// Avatar height in rows
+const AVATAR_HEIGHT: u16 = 2;
+
+/// Bar beside the posts of an account, in its color
+const ACCOUNT_MARKER: &str = "▎";
+
+/// Rows per entry in the accounts list
+const ACCOUNT_ITEM_HEIGHT: u16 = 5;
+
+/// Most lines of a bio shown in the profile view
+const PROFILE_BIO_LINES: usize = 4;
+
+/// Narrowest terminal that shows the timeline and post detail side by side
+const MIN_SPLIT_WIDTH: u16 = 90;
+
+/// What fills the image area of the post detail
+enum DetailImage {
+    /// A downloaded image, by URL
+    Loaded(String),
+    /// The blurhash of an image still loading
+    Placeholder(String),
+}
+
 /// Main render function
 pub fn render(frame: &mut Frame, state: &mut AppState) {
     let colors = state.theme.colors();
@@ -35,6 +73,9 @@ pub fn render(frame: &mut Frame, state: &mut AppState) {
     let bg_block = Block::default().style(Style::default().bg(colors.bg));
     frame.render_widget(bg_block, area);
 
+    // Only the timeline view records anything clickable
+    state.hit_regions = HitRegions::default();
+
     // Check if we need to show update banner
     let has_update = state.update_available.is_some();
 
@@ -64,7 +105,23 @@ pub fn render(frame: &mut Frame, state: &mut AppState) {
     };
 
     render_tabs(frame, state, tabs_area);
-    render_main(frame, state, main_area);
+    // The thread and search results stay visible behind compose when replying
+    // from them, and behind the link picker
+    let screen = match state.mode {
+        Mode::Compose | Mode::ConfirmQuit => state.compose_return_mode,
+        Mode::LinkPicker => state.link_picker_return_mode,
+        Mode::ConfirmModeration => state
+            .pending_moderation
+            .as_ref()
+            .map_or(Mode::Normal, |p| p.return_mode),
+        mode => mode,
+    };
+    match screen {
+        Mode::Thread => render_thread_view(frame, state, main_area),
+        Mode::SearchResults => render_search_results(frame, state, main_area),
+        Mode::Profile => render_profile_view(frame, state, main_area),
+        _ => render_main(frame, state, main_area),
+    }
     render_status_bar(frame, state, status_area);
 
     // Render modal dialogs
@@ -75,14 +132,30 @@ pub fn render(frame: &mut Frame, state: &mut AppState) {
         Mode::Compose => render_compose_popup(frame, state),
         Mode::Search => render_search_popup(frame, state),
         Mode::UpdateConfirm => render_update_confirm_dialog(frame, state),
+        Mode::ConfirmDelete => render_confirm_delete_dialog(frame, state),
+        Mode::ConfirmModeration => render_confirm_moderation_dialog(frame, state),
+        Mode::ConfirmQuit => {
+            render_compose_popup(frame, state);
+            render_confirm_quit_dialog(frame, state);
+        }
+        Mode::Drafts => render_drafts_picker(frame, state),
+        Mode::Scheduled => render_scheduled_picker(frame, state),
+        Mode::LinkPicker => render_link_picker(frame, state),
         Mode::Updating => render_updating_overlay(frame, state),
-        Mode::Normal => {}
+        Mode::Normal | Mode::Thread | Mode::SearchResults | Mode::Profile => {}
     }
 
     // Render update status banner if present
     if state.mode == Mode::Normal && state.update_status.is_some() {
         render_update_status(frame, state);
     }
+
+    // Without colors, also drop the few that don't come from the theme
+    if colors.monochrome {
+        for cell in &mut frame.buffer_mut().content {
+            cell.set_fg(Color::Reset).set_bg(Color::Reset);
+        }
+    }
 }
 
 fn render_tabs(frame: &mut Frame, state: &AppState, area: Rect) {
@@ -105,6 +178,18 @@ fn render_tabs(frame: &mut Frame, state: &AppState, area: Rect) {
                 "○"
             }
         ),
+        format!(
+            "{}  Notifications{}",
+            if state.view == View::Notifications {
+                "●"
+            } else {
+                "○"
+            },
+            match state.unread_notifications() {
+                0 => String::new(),
+                n => format!(" ({n})"),
+            }
+        ),
     ]
     .into_iter()
     .map(Line::from)
@@ -113,6 +198,7 @@ fn render_tabs(frame: &mut Frame, state: &AppState, area: Rect) {
     let selected = match state.view {
         View::Timeline => 0,
         View::Accounts => 1,
+        View::Notifications => 2,
     };
 
     let tabs = Tabs::new(titles)
@@ -121,7 +207,10 @@ fn render_tabs(frame: &mut Frame, state: &AppState, area: Rect) {
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
                 .border_style(colors.block())
-                .title(format!(" {ICON} Perch "))
+                .title(match &state.active_profile {
+                    Some(profile) => format!(" {ICON} Perch · {profile} "),
+                    None => format!(" {ICON} Perch "),
+                })
                 .title_style(colors.logo_style_primary()),
         )
         .select(selected)
@@ -136,27 +225,66 @@ fn render_main(frame: &mut Frame, state: &mut AppState, area: Rect) {
     match state.view {
         View::Timeline => render_timeline_view(frame, state, area),
         View::Accounts => render_accounts_view(frame, state, area),
+        View::Notifications => render_notifications_view(frame, state, area),
     }
 }
 
 fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
-    let colors = state.theme.colors();
+    // Side by side, or one panel at a time when there's no room for both
+    state.single_column = state.layout == TimelineLayout::Single || area.width < MIN_SPLIT_WIDTH;
+    if state.single_column {
+        if state.focused_panel == FocusedPanel::Detail {
+            state.hit_regions.detail = area;
+            render_post_detail(frame, state, area);
+        } else {
+            state.hit_regions.timeline = area;
+            render_timeline_list(frame, state, area);
+        }
+        return;
+    }
 
-    // Layout: [Timeline 50%] [Detail 50%]
+    let width = state.config.timeline_width();
     let horizontal = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .constraints([
+            Constraint::Percentage(width),
+            Constraint::Percentage(100 - width),
+        ])
         .split(area);
+    state.hit_regions.timeline = horizontal[0];
+    state.hit_regions.detail = horizontal[1];
+    render_timeline_list(frame, state, horizontal[0]);
+    render_post_detail(frame, state, horizontal[1]);
+}
+
+fn render_timeline_list(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    let colors = state.theme.colors();
+    let show_avatars = state.avatars_enabled();
+    let gutter = avatar_gutter(show_avatars);
 
-    // Timeline panel
     let filter_label = match state.timeline_filter {
         TimelineFilter::All => "🌐 All",
         TimelineFilter::Mastodon => "🐘 Mastodon",
         TimelineFilter::Bluesky => "🦋 Bluesky",
     };
 
+    let new_posts = match state.new_posts {
+        0 => String::new(),
+        1 => " · ↑ 1 new post".to_string(),
+        n => format!(" · ↑ {n} new posts"),
+    };
+    let timeline_title = match &state.feed {
+        Feed::Home if state.timeline_kind == TimelineKind::Home => {
+            format!(" 📰 Timeline ({filter_label}){new_posts} ")
+        }
+        Feed::Home => format!(
+            " 📰 {} ({filter_label}){new_posts} ",
+            state.timeline_label()
+        ),
+        Feed::Hashtag(tag) => format!(" # {tag} ({filter_label}) · Esc to go back "),
+    };
     let timeline_block = Block::default()
-        .title(format!(" 📰 Timeline ({filter_label}) "))
+        .title(timeline_title)
         .title_style(colors.text_primary())
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
@@ -193,7 +321,7 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
             .enumerate()
             .map(|(i, post)| {
                 let icon = post.network.emoji();
-                let time = post.relative_time();
+                let time = state.display_time(post.created_at);
 
                 // Status indicators
                 let mut indicators = String::new();
@@ -203,9 +331,12 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
                 if post.reposted {
                     indicators.push_str(" 🔁");
                 }
+                if post.bookmarked {
+                    indicators.push_str(" 🔖");
+                }
 
                 let is_selected = i == state.selected_post;
-                let width = horizontal[0].width.saturating_sub(3) as usize;
+                let width = area.width.saturating_sub(3) as usize;
 
                 // Full-width background for selected item
                 let base_style = if is_selected {
@@ -214,8 +345,10 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
                     Style::default()
                 };
 
-                let author_text =
-                    format!(" {} @{} · {}{}", icon, post.author_handle, time, indicators);
+                let author_text = format!(
+                    "{gutter} {} @{} · {}{}",
+                    icon, post.author_handle, time, indicators
+                );
 
                 // Pad author line to full width for selection highlight
                 let author_padded = format!("{author_text:<width$}");
@@ -229,7 +362,13 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
 
                 // Show full content, wrapping lines to fit width
                 let content_width = width.saturating_sub(4); // 3 spaces indent + margin
-                for line in post.content.lines() {
+                let body = if state.is_cw_collapsed(post) {
+                    let cw = state.content_warning(post).unwrap_or_default();
+                    format!("⚠ CW: {cw} (c to show)")
+                } else {
+                    post.content.clone()
+                };
+                for line in body.lines() {
                     // Wrap long lines
                     let chars: Vec<char> = line.chars().collect();
                     if chars.is_empty() {
@@ -248,6 +387,10 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
                     }
                 }
 
+                if let Some(marker) = state.account_marker(post.owner_account_id) {
+                    lines = lines.into_iter().map(|l| mark_line(l, marker)).collect();
+                }
+
                 // Add spacer between posts
                 lines.push(Line::styled(spacer, Style::default()));
 
@@ -256,6 +399,8 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
             .collect()
     };
 
+    let item_heights: Vec<usize> = post_items.iter().map(ListItem::height).collect();
+
     let timeline_list = List::new(post_items)
         .block(timeline_block)
         .highlight_style(colors.selected());
@@ -264,11 +409,55 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
     let mut list_state = ratatui::widgets::ListState::default();
     list_state.select(Some(state.selected_post));
 
-    frame.render_stateful_widget(timeline_list, horizontal[0], &mut list_state);
+    frame.render_stateful_widget(timeline_list, area, &mut list_state);
+
+    // Remember where each visible post landed, for mouse clicks
+    if !state.posts.is_empty() {
+        let inner = area.inner(Margin::new(1, 1));
+        let mut y = inner.y;
+        for (i, height) in item_heights.iter().enumerate().skip(list_state.offset()) {
+            if y >= inner.bottom() {
+                break;
+            }
+            let height = u16::try_from(*height)
+                .unwrap_or(u16::MAX)
+                .min(inner.bottom() - y);
+            state
+                .hit_regions
+                .posts
+                .push((i, Rect::new(inner.x, y, inner.width, height)));
+            y += height;
+        }
+    }
+
+    // Draw avatars over the gutter of each visible post
+    if show_avatars && !state.posts.is_empty() {
+        let inner = area.inner(Margin::new(1, 1));
+        let mut y: u16 = 0;
+        for (i, height) in item_heights.iter().enumerate().skip(list_state.offset()) {
+            if y + AVATAR_HEIGHT > inner.height {
+                break;
+            }
+            let url = state.posts[i].author_avatar.clone();
+            let avatar_area = Rect::new(inner.x + 1, inner.y + y, AVATAR_WIDTH, AVATAR_HEIGHT);
+            render_avatar(frame, state, url.as_deref(), avatar_area);
+            y = y.saturating_add(u16::try_from(*height).unwrap_or(u16::MAX));
+        }
+    }
+}
+
+/// The selected post, what it replies to and its replies
+fn render_post_detail(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    let colors = state.theme.colors();
+    let show_avatars = state.avatars_enabled();
+    let gutter = avatar_gutter(show_avatars);
 
-    // Detail panel
     let detail_block = Block::default()
-        .title(" 📝 Post Detail ")
+        .title(if state.single_column {
+            " 📝 Post Detail · Esc to go back "
+        } else {
+            " 📝 Post Detail "
+        })
         .title_style(colors.text_primary())
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
@@ -282,10 +471,54 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
         let like_icon = if post.liked { "❤️" } else { "♡" };
         let repost_icon = if post.reposted { "🔁" } else { "↻" };
 
-        let mut detail_content = vec![
-            Line::from(""),
+        let mut detail_content = vec![Line::from("")];
+
+        // What this post replies to, oldest first, one line each so that
+        // wrapping can't push the author line away from its avatar
+        if !state.current_ancestors.is_empty() {
+            let snippet_width = area.width.saturating_sub(8) as usize;
+            detail_content.push(Line::styled(
+                "  ── In reply to ──────────────────",
+                colors.text_dim(),
+            ));
+            for ancestor in &state.current_ancestors {
+                let header = format!(
+                    "@{} · {}",
+                    ancestor.author_handle,
+                    state.display_time(ancestor.created_at)
+                );
+                let snippet: String = if state.is_cw_collapsed(ancestor) {
+                    format!(
+                        "⚠ CW: {}",
+                        state.content_warning(ancestor).unwrap_or_default()
+                    )
+                } else {
+                    ancestor
+                        .content
+                        .lines()
+                        .next()
+                        .unwrap_or_default()
+                        .to_string()
+                }
+                .chars()
+                .take(snippet_width)
+                .collect();
+                detail_content.push(Line::from(vec![
+                    Span::styled("  ", Style::default()),
+                    Span::styled(header, colors.text_muted()),
+                ]));
+                detail_content.push(Line::from(vec![
+                    Span::styled("  │ ", colors.text_dim()),
+                    Span::styled(snippet, colors.text_dim()),
+                ]));
+            }
+            detail_content.push(Line::styled("  │", colors.text_dim()));
+        }
+
+        let author_line = u16::try_from(detail_content.len()).unwrap_or(u16::MAX);
+        detail_content.extend([
             Line::from(vec![
-                Span::styled("  ", Style::default()),
+                Span::styled(format!("  {gutter}"), Style::default()),
                 Span::styled(post.network.emoji(), Style::default()),
                 Span::styled(
                     format!(" {} ", post.author_name),
@@ -294,23 +527,129 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
                 Span::styled(format!("@{}", post.author_handle), colors.text_muted()),
             ]),
             Line::from(vec![Span::styled(
-                format!("     {}", post.relative_time()),
+                // The exact time, whichever way lists show it
+                format!(
+                    "{gutter}     {} · {}",
+                    state.config.timezone.format(post.created_at),
+                    post.relative_time()
+                ),
                 colors.text_muted(),
             )]),
             Line::from(""),
-        ];
+        ]);
+        if let Some(marker) = state.account_marker(post.owner_account_id) {
+            let header = usize::from(author_line);
+            for line in &mut detail_content[header..header + 2] {
+                *line = mark_line(std::mem::take(line), marker);
+            }
+        }
 
-        // Add content lines, preserving line breaks
-        for line in post.content.lines() {
+        // Show content warning, with the body only once expanded
+        if let Some(cw) = state.content_warning(&post) {
             detail_content.push(Line::from(vec![
-                Span::styled("  ", Style::default()),
-                Span::styled(line, colors.text()),
+                Span::styled("  ⚠ CW: ", colors.text_error()),
+                Span::styled(cw.clone(), colors.text_primary()),
+                Span::styled(
+                    if state.is_cw_collapsed(&post) {
+                        "  (c to show)"
+                    } else {
+                        "  (c to hide)"
+                    },
+                    colors.text_muted(),
+                ),
             ]));
+            detail_content.push(Line::from(""));
+        }
+
+        // Add content lines, preserving line breaks, with hashtags marked
+        // (and the one picked with `#` highlighted)
+        if !state.is_cw_collapsed(&post) {
+            let selected_tag = state.selected_hashtag();
+            for line in post.content.lines() {
+                let mut spans = vec![Span::styled("  ", Style::default())];
+                for (segment, is_tag) in split_hashtags(line) {
+                    let style = if !is_tag {
+                        colors.text()
+                    } else if selected_tag
+                        .as_deref()
+                        .is_some_and(|t| t.eq_ignore_ascii_case(&segment[1..]))
+                    {
+                        colors.key_hint().add_modifier(Modifier::REVERSED)
+                    } else {
+                        colors.key_hint()
+                    };
+                    spans.push(Span::styled(segment, style));
+                }
+                detail_content.push(Line::from(spans));
+            }
+
+            // Link card
+            if let Some(card) = &post.link_card {
+                let host = card
+                    .uri
+                    .split("://")
+                    .nth(1)
+                    .and_then(|rest| rest.split('/').next())
+                    .unwrap_or(&card.uri);
+                detail_content.push(Line::from(""));
+                detail_content.push(Line::from(vec![
+                    Span::styled("  ┃ 🔗 ", colors.text_muted()),
+                    Span::styled(
+                        if card.title.is_empty() {
+                            card.uri.clone()
+                        } else {
+                            card.title.clone()
+                        },
+                        colors.text_primary().add_modifier(Modifier::BOLD),
+                    ),
+                ]));
+                for line in card.description.lines().filter(|l| !l.trim().is_empty()) {
+                    detail_content.push(Line::from(vec![
+                        Span::styled("  ┃ ", colors.text_muted()),
+                        Span::styled(line.to_string(), colors.text_dim()),
+                    ]));
+                }
+                detail_content.push(Line::from(vec![
+                    Span::styled("  ┃ ", colors.text_muted()),
+                    Span::styled(host.to_string(), colors.text_muted()),
+                ]));
+            }
+
+            // Quoted post, set off by a bar
+            if let Some(quote) = &post.quote {
+                detail_content.push(Line::from(""));
+                detail_content.push(Line::from(vec![
+                    Span::styled("  ┃ ", colors.text_muted()),
+                    Span::styled(
+                        if quote.author_name.is_empty() {
+                            quote.author_handle.clone()
+                        } else {
+                            quote.author_name.clone()
+                        },
+                        colors.text_primary().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        format!(
+                            " @{} · {}",
+                            quote.author_handle,
+                            state.display_time(quote.created_at)
+                        ),
+                        colors.text_muted(),
+                    ),
+                ]));
+                for line in quote.content.lines() {
+                    detail_content.push(Line::from(vec![
+                        Span::styled("  ┃ ", colors.text_muted()),
+                        Span::styled(line.to_string(), colors.text_dim()),
+                    ]));
+                }
+            }
         }
 
         // Show media attachments indicator
         if !post.media.is_empty() {
             detail_content.push(Line::from(""));
+            let full_alt = state.is_alt_text_expanded(&post);
             for (i, media) in post.media.iter().enumerate() {
                 let media_type = match media.media_type {
                     crate::models::MediaType::Image => "🖼️ Image",
@@ -319,7 +658,6 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
                     crate::models::MediaType::Audio => "🎵 Audio",
                     crate::models::MediaType::Unknown => "📎 Attachment",
                 };
-                let alt = media.alt_text.as_deref().unwrap_or("no description");
                 let loading = if state.loading_images.contains(&media.url) {
                     " ⏳"
                 } else if state.image_cache.contains(&media.url) {
@@ -327,14 +665,32 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
                 } else {
                     ""
                 };
+                let alt = match (&media.alt_text, full_alt) {
+                    (None, _) => Span::styled(" ⚠ no description", colors.text_warning()),
+                    (Some(_), true) => Span::raw(""),
+                    (Some(_), false) => Span::styled(
+                        format!(" {}", media.alt_preview(60).unwrap_or_default()),
+                        colors.text_dim(),
+                    ),
+                };
                 detail_content.push(Line::from(vec![
                     Span::styled("  ", Style::default()),
                     Span::styled(
                         format!("[{} {}{}]", media_type, i + 1, loading),
                         colors.text_secondary(),
                     ),
-                    Span::styled(format!(" {}", alt), colors.text_dim()),
+                    failed_marker(state, media, &colors),
+                    alt,
                 ]));
+                // The full description, below the attachment (a toggles it)
+                if let Some(alt) = media.alt_text.as_deref().filter(|_| full_alt) {
+                    for line in alt.lines() {
+                        detail_content.push(Line::from(vec![
+                            Span::styled("    ", Style::default()),
+                            Span::styled(line.to_string(), colors.text()),
+                        ]));
+                    }
+                }
             }
         }
 
@@ -362,9 +718,14 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
             ),
             Span::styled("   ", Style::default()),
             Span::styled(format!("💬 {}", post.reply_count), colors.text_muted()),
+            Span::styled(
+                if post.bookmarked { "   🔖" } else { "" },
+                colors.text_warning(),
+            ),
         ]));
 
-        // Add replies section
+        // Add replies section, noting which lines each reply takes up
+        let mut reply_lines = Vec::new();
         if !state.current_replies.is_empty() {
             detail_content.push(Line::from(""));
             detail_content.push(Line::from(vec![Span::styled(
@@ -374,6 +735,7 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
 
             // Show all replies (no limit since we show nested structure)
             for (idx, reply_item) in state.current_replies.iter().enumerate() {
+                let first_line = detail_content.len() + 1;
                 let is_selected = state.selected_reply == Some(idx)
                     && state.focused_panel == FocusedPanel::Detail;
                 let indent = "  ".repeat(reply_item.depth + 1);
@@ -402,7 +764,7 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
                     ),
                     Span::styled(format!("@{}", reply_item.post.author_handle), handle_style),
                     Span::styled(
-                        format!(" · {}", reply_item.post.relative_time()),
+                        format!(" · {}", state.display_time(reply_item.post.created_at)),
                         colors.text_muted(),
                     ),
                 ]));
@@ -410,8 +772,15 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
                 // Content indent (depth + 2 for alignment after handle)
                 let content_indent = "  ".repeat(reply_item.depth + 2);
 
-                // Show full content
-                if reply_item.post.content.contains('\n') {
+                // Show full content (or just the content warning while collapsed)
+                if state.is_cw_collapsed(&reply_item.post) {
+                    let cw = state.content_warning(&reply_item.post).unwrap_or_default();
+                    detail_content.push(Line::from(vec![
+                        Span::styled(content_indent.clone(), Style::default()),
+                        Span::styled(format!("⚠ CW: {cw}"), colors.text_error()),
+                        Span::styled("  (c to show)", colors.text_muted()),
+                    ]));
+                } else if reply_item.post.content.contains('\n') {
                     // Multi-line content
                     for line in reply_item.post.content.lines() {
                         detail_content.push(Line::from(vec![
@@ -439,6 +808,7 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
 
                 // Show media attachments for replies
                 if !reply_item.post.media.is_empty() {
+                    let full_alt = state.is_alt_text_expanded(&reply_item.post);
                     for (i, media) in reply_item.post.media.iter().enumerate() {
                         let media_icon = match media.media_type {
                             crate::models::MediaType::Image => "🖼️",
@@ -455,23 +825,27 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
                         } else {
                             ""
                         };
-                        let alt = media.alt_text.as_deref().unwrap_or("");
-                        let alt_display = if alt.is_empty() {
-                            String::new()
-                        } else if alt.len() > 40 {
-                            format!(" {:.40}...", alt)
+                        let alt = if media.alt_text.is_none() {
+                            Span::styled(" ⚠ no description", colors.text_warning())
                         } else {
-                            format!(" {}", alt)
+                            let max_len = if full_alt { usize::MAX } else { 40 };
+                            Span::styled(
+                                format!(" {}", media.alt_preview(max_len).unwrap_or_default()),
+                                colors.text_secondary(),
+                            )
                         };
                         detail_content.push(Line::from(vec![
                             Span::styled(content_indent.clone(), Style::default()),
                             Span::styled(
-                                format!("[{} {}{}]{}", media_icon, i + 1, status, alt_display),
+                                format!("[{} {}{}]", media_icon, i + 1, status),
                                 colors.text_secondary(),
                             ),
+                            failed_marker(state, media, &colors),
+                            alt,
                         ]));
                     }
                 }
+                reply_lines.push((idx, first_line..detail_content.len()));
             }
         } else if state.loading_replies {
             detail_content.push(Line::from(""));
@@ -479,25 +853,37 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
                 "  ⏳ Loading replies...",
                 colors.text_muted(),
             )]));
+        } else if state.replies_skipped {
+            detail_content.push(Line::from(""));
+            detail_content.push(Line::from(vec![Span::styled(
+                "  No replies yet — Enter to load replies",
+                colors.text_dim(),
+            )]));
         }
 
         // Check if we have images ready to render (in cache with protocol)
-        let image_to_render: Option<String> = if state.show_images {
-            post.media
-                .iter()
-                .filter(|m| m.media_type == crate::models::MediaType::Image)
-                .find_map(|m| {
-                    let url = m.preview_url.as_ref().unwrap_or(&m.url);
-                    // Only include if we can actually render it
-                    if state.image_cache.contains(url) && state.image_protocols.contains_key(url) {
-                        Some(url.clone())
-                    } else {
-                        None
-                    }
-                })
-        } else {
-            None
-        };
+        // The first image that has loaded, or while the first one is still
+        // loading, its blurhash
+        let image_to_render: Option<DetailImage> =
+            if state.show_images && crate::images::picker().is_some() {
+                let urls = state.image_urls(&post);
+                urls.iter()
+                    .find(|url| state.image_cache.contains(url))
+                    .map(|url| DetailImage::Loaded((*url).to_string()))
+                    .or_else(|| {
+                        let first = urls.first()?;
+                        let media = post
+                            .media
+                            .iter()
+                            .find(|m| m.media_type == crate::models::MediaType::Image)?;
+                        if state.failed_images.contains(*first) {
+                            return None;
+                        }
+                        media.blurhash.clone().map(DetailImage::Placeholder)
+                    })
+            } else {
+                None
+            };
 
         // Split detail area only if we have an image ready to render
         let (text_area, image_area) = if image_to_render.is_some() {
@@ -507,20 +893,34 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
                     Constraint::Min(10),    // Text content
                     Constraint::Length(12), // Image area (12 rows)
                 ])
-                .split(horizontal[1]);
+                .split(area);
             (areas[0], Some(areas[1]))
         } else {
-            (horizontal[1], None)
+            (area, None)
         };
 
+        let inner = text_area.inner(Margin::new(1, 1));
+        state.hit_regions.replies =
+            reply_regions(&detail_content, &reply_lines, inner, state.detail_scroll);
+
         let detail = Paragraph::new(detail_content)
             .block(detail_block)
             .wrap(Wrap { trim: false })
             .scroll((state.detail_scroll, 0));
         frame.render_widget(detail, text_area);
+        state.hit_regions.links = link_regions(frame.buffer_mut(), inner);
+
+        // Avatar sits beside the author and time lines, so only while they're on screen
+        if show_avatars
+            && let Some(row) = author_line.checked_sub(state.detail_scroll)
+            && row + AVATAR_HEIGHT <= inner.height
+        {
+            let avatar_area = Rect::new(inner.x + 2, inner.y + row, AVATAR_WIDTH, AVATAR_HEIGHT);
+            render_avatar(frame, state, post.author_avatar.as_deref(), avatar_area);
+        }
 
         // Render image if available
-        if let (Some(image_url), Some(img_area)) = (image_to_render, image_area) {
+        if let (Some(image), Some(img_area)) = (image_to_render, image_area) {
             // Add some padding
             let inner_area = Rect {
                 x: img_area.x + 2,
@@ -529,7 +929,11 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
                 height: img_area.height,
             };
 
-            if let Some(protocol) = state.get_image_protocol(&image_url) {
+            let protocol = match &image {
+                DetailImage::Loaded(url) => state.get_image_protocol(url),
+                DetailImage::Placeholder(blurhash) => state.get_placeholder_protocol(blurhash),
+            };
+            if let Some(protocol) = protocol {
                 let image_widget = StatefulImage::new();
                 frame.render_stateful_widget(image_widget, inner_area, protocol);
             }
@@ -541,12 +945,88 @@ fn render_timeline_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
             Line::styled("  Select a post", colors.text_muted()),
         ])
         .block(detail_block);
-        frame.render_widget(empty, horizontal[1]);
+        frame.render_widget(empty, area);
+    }
+}
+
+/// Flag on an attachment whose image couldn't be loaded, or nothing
+fn failed_marker(
+    state: &AppState,
+    media: &crate::models::MediaAttachment,
+    colors: &ThemeColors,
+) -> Span<'static> {
+    if state.failed_images.is_empty() || !state.media_failed(media) {
+        return Span::raw("");
+    }
+    Span::styled(" ✗ failed to load (i to retry)", colors.text_error())
+}
+
+/// Where each reply ended up in the (wrapped, scrolled) detail panel, given
+/// the lines it spans in `lines`
+fn reply_regions(
+    lines: &[Line],
+    reply_lines: &[(usize, Range<usize>)],
+    inner: Rect,
+    scroll: u16,
+) -> Vec<(usize, Rect)> {
+    // Row each line starts on once wrapped, plus the row after the last
+    let mut rows = Vec::with_capacity(lines.len() + 1);
+    let mut row = 0usize;
+    for line in lines {
+        rows.push(row);
+        row += Paragraph::new(line.clone())
+            .wrap(Wrap { trim: false })
+            .line_count(inner.width);
+    }
+    rows.push(row);
+
+    let scroll = usize::from(scroll);
+    let height = usize::from(inner.height);
+    reply_lines
+        .iter()
+        .filter_map(|(index, range)| {
+            let top = rows[range.start].max(scroll) - scroll;
+            let bottom = (rows[range.end].saturating_sub(scroll)).min(height);
+            if top >= bottom {
+                return None;
+            }
+            // Both fit, being within the panel's height
+            let y = inner.y + u16::try_from(top).ok()?;
+            let rows = u16::try_from(bottom - top).ok()?;
+            Some((*index, Rect::new(inner.x, y, inner.width, rows)))
+        })
+        .collect()
+}
+
+/// Find the links drawn in `area`, one region per row a link is on
+fn link_regions(buffer: &Buffer, area: Rect) -> Vec<(Rect, String)> {
+    let mut links = Vec::new();
+    for y in area.top()..area.bottom() {
+        // Text of the row, with the column each byte of it was drawn in
+        let mut text = String::new();
+        let mut columns = Vec::new();
+        for x in area.left()..area.right() {
+            let symbol = buffer[(x, y)].symbol();
+            columns.extend(std::iter::repeat_n(x, symbol.len()));
+            text.push_str(symbol);
+        }
+
+        for range in find_links(&text) {
+            let start = columns[range.start];
+            let end = columns[range.end - 1];
+            links.push((
+                Rect::new(start, y, end - start + 1, 1),
+                text[range].to_string(),
+            ));
+        }
     }
+    links
 }
 
-fn render_accounts_view(frame: &mut Frame, state: &AppState, area: Rect) {
+fn render_accounts_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
     let colors = state.theme.colors();
+    let show_avatars = state.avatars_enabled();
+    let gutter = avatar_gutter(show_avatars);
 
     // Split into main area and bottom bar for account actions
     let layout = Layout::default()
@@ -634,7 +1114,7 @@ fn render_accounts_view(frame: &mut Frame, state: &AppState, area: Rect) {
 
             // Build lines with full-width background
             let line1 = format!(
-                " {} {} {} {}",
+                " {} {gutter}{} {} {}",
                 cursor,
                 account.network.emoji(),
                 account.display_name,
@@ -642,18 +1122,34 @@ fn render_accounts_view(frame: &mut Frame, state: &AppState, area: Rect) {
             );
             let line1_padded = format!("{line1:content_width$}");
 
-            let line2 = format!("     @{}", account.handle);
+            let line2 = format!("{gutter}     @{}", account.handle);
             let line2_padded = format!("{line2:content_width$}");
 
-            let line3 = format!("     Server: {}", account.server);
+            let mut line3 = format!("{gutter}     Server: {}", account.server);
+            if let Some(name) = &account.server_name {
+                let _ = write!(line3, " ({name})");
+            }
+            if let Some(software) = account.software_label() {
+                let _ = write!(line3, " · {software}");
+            }
             let line3_padded = format!("{line3:content_width$}");
 
-            ListItem::new(vec![
+            let line4 = format!("{gutter}     Limits: {}", server_limits(account));
+            let line4_padded = format!("{line4:content_width$}");
+
+            let mut lines = vec![
                 Line::from(Span::styled(line1_padded, text_style.patch(bg_style))),
                 Line::from(Span::styled(line2_padded, muted_style.patch(bg_style))),
                 Line::from(Span::styled(line3_padded, dim_style.patch(bg_style))),
-                Line::from(""),
-            ])
+                Line::from(Span::styled(line4_padded, dim_style.patch(bg_style))),
+            ];
+            // The color this account's posts are marked with
+            if let Some(marker) = state.account_marker(Some(account.id)) {
+                let marker = bg_style.patch(marker);
+                lines = lines.into_iter().map(|l| mark_line(l, marker)).collect();
+            }
+            lines.push(Line::from(""));
+            ListItem::new(lines)
         })
         .collect();
 
@@ -671,6 +1167,21 @@ fn render_accounts_view(frame: &mut Frame, state: &AppState, area: Rect) {
 
     frame.render_widget(list, main_area);
 
+    // Draw avatars between the cursor and the account name
+    if show_avatars {
+        let inner = main_area.inner(Margin::new(1, 1));
+        let mut y: u16 = 0;
+        for i in 0..state.accounts.len() {
+            if y + AVATAR_HEIGHT > inner.height {
+                break;
+            }
+            let url = state.accounts[i].avatar_url.clone();
+            let avatar_area = Rect::new(inner.x + 3, inner.y + y, AVATAR_WIDTH, AVATAR_HEIGHT);
+            render_avatar(frame, state, url.as_deref(), avatar_area);
+            y += ACCOUNT_ITEM_HEIGHT;
+        }
+    }
+
     // Render action bar with account-specific keys
     let action_bar = Line::from(vec![
         Span::styled(" ", Style::default().bg(colors.bg_secondary)),
@@ -679,7 +1190,7 @@ fn render_accounts_view(frame: &mut Frame, state: &AppState, area: Rect) {
         Span::styled("[D]", colors.key_hint()),
         Span::styled(" Delete  ", colors.text_muted()),
         Span::styled("[r]", colors.key_hint()),
-        Span::styled(" Refresh token  ", colors.text_muted()),
+        Span::styled(" Refresh  ", colors.text_muted()),
         Span::styled("[Enter]", colors.key_hint()),
         Span::styled(" View timeline", colors.text_muted()),
     ]);
@@ -688,86 +1199,806 @@ fn render_accounts_view(frame: &mut Frame, state: &AppState, area: Rect) {
     frame.render_widget(action_bar_bg, action_bar_area);
 }
 
-fn render_status_bar(frame: &mut Frame, state: &AppState, area: Rect) {
+/// Post length and attachment limits of an account's server, e.g.
+/// `500 characters · 4 images of up to 16 MB`
+fn server_limits(account: &crate::models::Account) -> String {
+    let images = account
+        .max_media_attachments
+        .unwrap_or(crate::api::MAX_MEDIA_ATTACHMENTS);
+    let mut limits = format!("{} characters · {images} images", account.post_char_limit());
+    if let Some(bytes) = account.max_image_bytes {
+        let _ = write!(limits, " of up to {} MB", bytes / 1_000_000);
+    }
+    limits
+}
+
+fn render_thread_view(frame: &mut Frame, state: &AppState, area: Rect) {
     let colors = state.theme.colors();
 
-    // Spinner animation frames
-    const SPINNER: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+    // Split into main area and bottom bar for thread actions
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(area);
 
-    let loading_indicator = if state.loading {
-        let frame_idx = (state.current_tick() / 2) as usize % SPINNER.len();
-        format!("{} ", SPINNER[frame_idx])
-    } else {
-        String::new()
-    };
+    let main_area = layout[0];
+    let action_bar_area = layout[1];
+    let width = main_area.width.saturating_sub(2) as usize; // -2 for borders
 
-    let content = if !state.status.is_empty() {
-        vec![
-            Span::styled(" ", Style::default()),
-            Span::styled(&loading_indicator, colors.text_secondary()),
-            Span::styled(&state.status, colors.text_secondary()),
-        ]
-    } else if let Some(ref version) = state.update_available {
-        // Show update notification
-        vec![
-            Span::styled(" ⬆ ", Style::default().fg(Color::Yellow)),
-            Span::styled(
-                format!("Update available: v{version}"),
-                Style::default().fg(Color::Yellow),
-            ),
-            Span::styled("  Press ", colors.text_muted()),
-            Span::styled("A", colors.key_hint()),
-            Span::styled(" then ", colors.text_muted()),
-            Span::styled("U", colors.key_hint()),
-            Span::styled(" to update", colors.text_muted()),
-        ]
-    } else {
-        vec![
-            Span::styled(" ", Style::default()),
-            Span::styled(&loading_indicator, colors.text_secondary()),
-            Span::styled("Tab", colors.key_hint()),
-            Span::styled(": views  ", colors.text_muted()),
-            Span::styled("?", colors.key_hint()),
-            Span::styled(": help  ", colors.text_muted()),
-            Span::styled("t", colors.key_hint()),
-            Span::styled(": theme  ", colors.text_muted()),
-            Span::styled("A", colors.key_hint()),
-            Span::styled(": about  ", colors.text_muted()),
-            Span::styled("q", colors.key_hint()),
-            Span::styled(": quit", colors.text_muted()),
-        ]
-    };
+    let ancestors = state.current_ancestors.len();
+    let mut lines: Vec<Line> = Vec::new();
+    let mut selected_line = 0;
 
-    let status =
-        Paragraph::new(Line::from(content)).style(Style::default().bg(colors.bg_secondary));
-    frame.render_widget(status, area);
-}
+    for index in 0..state.thread_len() {
+        let Some(post) = state.thread_node(index) else {
+            continue;
+        };
+        let is_focus_post = index == ancestors;
+        let is_selected = index == state.thread_selected;
+        let depth = index
+            .checked_sub(ancestors + 1)
+            .and_then(|i| state.current_replies.get(i))
+            .map_or(0, |r| r.depth + 1);
+
+        if is_focus_post && ancestors > 0 {
+            lines.push(Line::from(""));
+        }
+        if index == ancestors + 1 {
+            lines.push(Line::from(""));
+            lines.push(Line::styled(
+                "  ── Replies ──────────────────────",
+                colors.text_dim(),
+            ));
+            lines.push(Line::from(""));
+        }
 
-fn render_help_popup(frame: &mut Frame, state: &AppState) {
-    let colors = state.theme.colors();
-    let area = frame.area();
+        if is_selected {
+            selected_line = lines.len();
+        }
 
-    // Same dimensions as Hazelnut: 50% width, 70% height
-    let popup_area = centered_rect(50, 70, area);
+        let indent = "  ".repeat(depth + 1);
+        let tree_char = if depth > 0 { "└ " } else { "" };
+        let marker = if is_selected { "▶ " } else { "  " };
+        let name_style = if is_focus_post {
+            colors.text_primary().add_modifier(Modifier::BOLD)
+        } else {
+            colors.text_primary()
+        };
+        let name_style = if is_selected {
+            name_style.add_modifier(Modifier::REVERSED)
+        } else {
+            name_style
+        };
 
-    // First render a solid background block to cover everything underneath
-    let bg_block = Block::default().style(Style::default().bg(colors.bg_secondary));
-    frame.render_widget(Clear, popup_area);
-    frame.render_widget(bg_block, popup_area);
+        lines.push(Line::from(vec![
+            Span::styled(format!("{indent}{tree_char}"), colors.text_dim()),
+            Span::styled(marker, colors.text_primary()),
+            Span::styled(format!("{} ", post.network.emoji()), Style::default()),
+            Span::styled(post.author_name.clone(), name_style),
+            Span::styled(
+                format!(
+                    " @{} · {}",
+                    post.author_handle,
+                    state.display_time(post.created_at)
+                ),
+                colors.text_muted(),
+            ),
+        ]));
 
-    let help_content = vec![
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "  Navigation",
-            colors.text_primary().add_modifier(Modifier::BOLD),
-        )]),
+        // Body, wrapped by hand so line counts match what is drawn
+        let body_indent = "  ".repeat(depth + 3);
+        let body_width = width.saturating_sub(body_indent.len()).max(10);
+        if state.is_cw_collapsed(post) {
+            let cw = state.content_warning(post).unwrap_or_default();
+            lines.push(Line::from(vec![
+                Span::styled(body_indent.clone(), Style::default()),
+                Span::styled(format!("⚠ CW: {cw}"), colors.text_error()),
+                Span::styled("  (c to show)", colors.text_muted()),
+            ]));
+        } else {
+            for line in post.content.lines() {
+                let chars: Vec<char> = line.chars().collect();
+                if chars.is_empty() {
+                    lines.push(Line::from(""));
+                }
+                for chunk in chars.chunks(body_width) {
+                    lines.push(Line::from(vec![
+                        Span::styled(body_indent.clone(), Style::default()),
+                        Span::styled(chunk.iter().collect::<String>(), colors.text()),
+                    ]));
+                }
+            }
+        }
+
+        lines.push(Line::styled(
+            format!(
+                "{body_indent}♡ {}  ↻ {}  💬 {}",
+                post.like_count, post.repost_count, post.reply_count
+            ),
+            if is_focus_post {
+                colors.text_muted()
+            } else {
+                colors.text_dim()
+            },
+        ));
+
+        // Connect ancestors down to the post they lead to
+        if index < ancestors {
+            lines.push(Line::styled(format!("{indent}  │"), colors.text_dim()));
+        } else {
+            lines.push(Line::from(""));
+        }
+    }
+
+    if state.loading_replies {
+        lines.push(Line::styled(
+            "  ⏳ Loading conversation...",
+            colors.text_muted(),
+        ));
+    }
+
+    // Keep the focused node in the upper third of the screen
+    let visible = main_area.height.saturating_sub(2) as usize;
+    let scroll = u16::try_from(selected_line.saturating_sub(visible / 3)).unwrap_or(u16::MAX);
+
+    let thread = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(colors.block_focus())
+                .title(format!(
+                    " 🧵 Thread ({}/{}) ",
+                    state.thread_selected + 1,
+                    state.thread_len()
+                ))
+                .title_style(colors.text_primary()),
+        )
+        .scroll((scroll, 0));
+    frame.render_widget(thread, main_area);
+
+    let action_bar = Line::from(vec![
+        Span::styled(" ", Style::default().bg(colors.bg_secondary)),
+        Span::styled("[j/k]", colors.key_hint()),
+        Span::styled(" Move  ", colors.text_muted()),
+        Span::styled("[R]", colors.key_hint()),
+        Span::styled(" Reply  ", colors.text_muted()),
+        Span::styled("[c]", colors.key_hint()),
+        Span::styled(" Show CW  ", colors.text_muted()),
+        Span::styled("[o]", colors.key_hint()),
+        Span::styled(" Open  ", colors.text_muted()),
+        Span::styled("[Esc]", colors.key_hint()),
+        Span::styled(" Back", colors.text_muted()),
+    ]);
+
+    let action_bar_bg = Paragraph::new(action_bar).style(Style::default().bg(colors.bg_secondary));
+    frame.render_widget(action_bar_bg, action_bar_area);
+}
+
+fn render_search_results(frame: &mut Frame, state: &AppState, area: Rect) {
+    let colors = state.theme.colors();
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(area);
+
+    let main_area = layout[0];
+    let action_bar_area = layout[1];
+    let width = main_area.width.saturating_sub(2) as usize; // -2 for borders
+    let body_indent = "      ";
+    let body_width = width.saturating_sub(body_indent.len()).max(10);
+
+    let mut lines: Vec<Line> = Vec::new();
+    let mut selected_line = 0;
+
+    if state.searching {
+        lines.push(Line::from(""));
+        lines.push(Line::styled("  ⏳ Searching...", colors.text_muted()));
+    } else if state.search_results.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::styled(
+            match state.last_search_scope {
+                SearchScope::Local => {
+                    "  No cached posts match. Try fewer words, or Tab to search the servers."
+                }
+                SearchScope::Server => "  No posts found.",
+            },
+            colors.text_muted(),
+        ));
+    }
+
+    for (index, post) in state.search_results.iter().enumerate() {
+        let is_selected = index == state.selected_search_result;
+        if is_selected {
+            selected_line = lines.len();
+        }
+
+        let marker = if is_selected { "▶ " } else { "  " };
+        let name_style = if is_selected {
+            colors.text_primary().add_modifier(Modifier::REVERSED)
+        } else {
+            colors.text_primary()
+        };
+
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {marker}"), colors.text_primary()),
+            Span::styled(format!("{} ", post.network.emoji()), Style::default()),
+            Span::styled(post.author_name.clone(), name_style),
+            Span::styled(
+                format!(
+                    " @{} · {}",
+                    post.author_handle,
+                    state.display_time(post.created_at)
+                ),
+                colors.text_muted(),
+            ),
+        ]));
+
+        if state.is_cw_collapsed(post) {
+            let cw = state.content_warning(post).unwrap_or_default();
+            lines.push(Line::from(vec![
+                Span::styled(body_indent, Style::default()),
+                Span::styled(format!("⚠ CW: {cw}"), colors.text_error()),
+                Span::styled("  (c to show)", colors.text_muted()),
+            ]));
+        } else {
+            for line in post.content.lines().filter(|l| !l.trim().is_empty()) {
+                let chars: Vec<char> = line.chars().collect();
+                for chunk in chars.chunks(body_width) {
+                    lines.push(Line::from(vec![
+                        Span::styled(body_indent, Style::default()),
+                        Span::styled(chunk.iter().collect::<String>(), colors.text()),
+                    ]));
+                }
+            }
+        }
+        lines.push(Line::from(""));
+    }
+
+    // Keep the selected result in the upper third of the screen
+    let visible = main_area.height.saturating_sub(2) as usize;
+    let scroll = u16::try_from(selected_line.saturating_sub(visible / 3)).unwrap_or(u16::MAX);
+
+    let results = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(colors.block_focus())
+                .title(format!(
+                    " 🔍 '{}' in {} ({} results) ",
+                    state.last_search,
+                    state.last_search_scope.label(),
+                    state.search_results.len()
+                ))
+                .title_style(colors.text_primary()),
+        )
+        .scroll((scroll, 0));
+    frame.render_widget(results, main_area);
+
+    let action_bar = Line::from(vec![
+        Span::styled(" ", Style::default().bg(colors.bg_secondary)),
+        Span::styled("[j/k]", colors.key_hint()),
+        Span::styled(" Move  ", colors.text_muted()),
+        Span::styled("[R]", colors.key_hint()),
+        Span::styled(" Reply  ", colors.text_muted()),
+        Span::styled("[c]", colors.key_hint()),
+        Span::styled(" Show CW  ", colors.text_muted()),
+        Span::styled("[o]", colors.key_hint()),
+        Span::styled(" Open  ", colors.text_muted()),
+        Span::styled("[/]", colors.key_hint()),
+        Span::styled(" New search  ", colors.text_muted()),
+        Span::styled("[Esc]", colors.key_hint()),
+        Span::styled(" Back", colors.text_muted()),
+    ]);
+
+    let action_bar_bg = Paragraph::new(action_bar).style(Style::default().bg(colors.bg_secondary));
+    frame.render_widget(action_bar_bg, action_bar_area);
+}
+
+fn render_profile_view(frame: &mut Frame, state: &AppState, area: Rect) {
+    let colors = state.theme.colors();
+    let width = area.width.saturating_sub(2) as usize; // -2 for borders
+
+    // Header: name, counts and bio (a few lines of it at most)
+    let mut header: Vec<Line> = Vec::new();
+    if let Some(profile) = &state.profile {
+        header.push(Line::from(vec![
+            Span::styled(format!(" {} ", profile.network.emoji()), Style::default()),
+            Span::styled(
+                profile.name().to_string(),
+                colors.text_primary().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(format!(" @{}", profile.handle), colors.text_muted()),
+            Span::styled(if profile.locked { " 🔒" } else { "" }, colors.text_muted()),
+            Span::styled("  ", Style::default()),
+            Span::styled(
+                format!("[{}]", profile.follow_state.label()),
+                if profile.follow_state == FollowState::NotFollowing {
+                    colors.key_hint()
+                } else {
+                    colors.text_success()
+                },
+            ),
+            Span::styled(
+                match (profile.muted, profile.blocked) {
+                    (_, true) => " [🚫 Blocked]",
+                    (true, false) => " [🔇 Muted]",
+                    (false, false) => "",
+                },
+                colors.text_error(),
+            ),
+        ]));
+        header.push(Line::from(vec![
+            Span::styled(format!("   {}", profile.posts_count), colors.text()),
+            Span::styled(" posts · ", colors.text_muted()),
+            Span::styled(profile.followers_count.to_string(), colors.text()),
+            Span::styled(" followers · ", colors.text_muted()),
+            Span::styled(profile.following_count.to_string(), colors.text()),
+            Span::styled(" following", colors.text_muted()),
+        ]));
+        let bio_width = width.saturating_sub(3).max(10);
+        let mut bio_lines = Vec::new();
+        for line in profile.bio.lines().filter(|l| !l.trim().is_empty()) {
+            let chars: Vec<char> = line.chars().collect();
+            for chunk in chars.chunks(bio_width) {
+                bio_lines.push(Line::styled(
+                    format!("   {}", chunk.iter().collect::<String>()),
+                    colors.text_dim(),
+                ));
+            }
+        }
+        if !bio_lines.is_empty() {
+            header.push(Line::from(""));
+            header.extend(bio_lines.into_iter().take(PROFILE_BIO_LINES));
+        }
+    } else if state.loading_profile {
+        header.push(Line::styled(
+            format!("  ⏳ Loading @{}...", state.profile_handle),
+            colors.text_muted(),
+        ));
+    } else {
+        header.push(Line::styled(
+            format!("  Couldn't load @{} (b to retry)", state.profile_handle),
+            colors.text_muted(),
+        ));
+    }
+
+    let header_height = u16::try_from(header.len()).unwrap_or(u16::MAX) + 2;
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(header_height),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(area);
+
+    let header = Paragraph::new(header).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(colors.block())
+            .title(" 👤 Profile ")
+            .title_style(colors.text_primary()),
+    );
+    frame.render_widget(header, layout[0]);
+
+    let posts_area = layout[1];
+    let body_indent = "      ";
+    let body_width = width.saturating_sub(body_indent.len()).max(10);
+    let mut lines: Vec<Line> = Vec::new();
+    let mut selected_line = 0;
+
+    for (index, post) in state.profile_posts.iter().enumerate() {
+        let is_selected = index == state.selected_profile_post;
+        if is_selected {
+            selected_line = lines.len();
+        }
+
+        let marker = if is_selected { "▶ " } else { "  " };
+        let name_style = if is_selected {
+            colors.text_primary().add_modifier(Modifier::REVERSED)
+        } else {
+            colors.text_primary()
+        };
+        let mut header_spans = vec![
+            Span::styled(format!("  {marker}"), colors.text_primary()),
+            Span::styled(format!("{} ", post.network.emoji()), Style::default()),
+        ];
+        if post.is_repost {
+            header_spans.push(Span::styled("🔁 ", colors.text_muted()));
+        }
+        header_spans.push(Span::styled(post.author_name.clone(), name_style));
+        header_spans.push(Span::styled(
+            format!(
+                " @{} · {}",
+                post.author_handle,
+                state.display_time(post.created_at)
+            ),
+            colors.text_muted(),
+        ));
+        lines.push(Line::from(header_spans));
+
+        if state.is_cw_collapsed(post) {
+            let cw = state.content_warning(post).unwrap_or_default();
+            lines.push(Line::from(vec![
+                Span::styled(body_indent, Style::default()),
+                Span::styled(format!("⚠ CW: {cw}"), colors.text_error()),
+                Span::styled("  (c to show)", colors.text_muted()),
+            ]));
+        } else {
+            for line in post.content.lines().filter(|l| !l.trim().is_empty()) {
+                let chars: Vec<char> = line.chars().collect();
+                for chunk in chars.chunks(body_width) {
+                    lines.push(Line::from(vec![
+                        Span::styled(body_indent, Style::default()),
+                        Span::styled(chunk.iter().collect::<String>(), colors.text()),
+                    ]));
+                }
+            }
+        }
+        lines.push(Line::styled(
+            format!(
+                "{body_indent}♡ {}  ↻ {}  💬 {}",
+                post.like_count, post.repost_count, post.reply_count
+            ),
+            colors.text_dim(),
+        ));
+        lines.push(Line::from(""));
+    }
+
+    if state.loading_profile && state.profile.is_some() {
+        lines.push(Line::styled("  ⏳ Loading posts...", colors.text_muted()));
+    } else if state.profile.is_some() && state.profile_posts.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::styled("  No posts yet.", colors.text_muted()));
+    }
+
+    // Keep the selected post in the upper third of the screen
+    let visible = posts_area.height.saturating_sub(2) as usize;
+    let scroll = u16::try_from(selected_line.saturating_sub(visible / 3)).unwrap_or(u16::MAX);
+
+    let title = if state.profile_posts.is_empty() {
+        " Posts ".to_string()
+    } else {
+        format!(
+            " Posts ({}/{}{}) ",
+            state.selected_profile_post + 1,
+            state.profile_posts.len(),
+            if state.profile_cursor.is_some() {
+                "+"
+            } else {
+                ""
+            }
+        )
+    };
+    let posts = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(colors.block_focus())
+                .title(title)
+                .title_style(colors.text_primary()),
+        )
+        .scroll((scroll, 0));
+    frame.render_widget(posts, posts_area);
+
+    let action_bar = Line::from(vec![
+        Span::styled(" ", Style::default().bg(colors.bg_secondary)),
+        Span::styled("[j/k]", colors.key_hint()),
+        Span::styled(" Move (older at the end)  ", colors.text_muted()),
+        Span::styled("[R]", colors.key_hint()),
+        Span::styled(" Reply  ", colors.text_muted()),
+        Span::styled("[+]", colors.key_hint()),
+        Span::styled(" Follow/unfollow  ", colors.text_muted()),
+        Span::styled("[M/B]", colors.key_hint()),
+        Span::styled(" Mute/block  ", colors.text_muted()),
+        Span::styled("[c]", colors.key_hint()),
+        Span::styled(" Show CW  ", colors.text_muted()),
+        Span::styled("[o]", colors.key_hint()),
+        Span::styled(" Open  ", colors.text_muted()),
+        Span::styled("[b]", colors.key_hint()),
+        Span::styled(" Refresh  ", colors.text_muted()),
+        Span::styled("[Esc]", colors.key_hint()),
+        Span::styled(" Back", colors.text_muted()),
+    ]);
+
+    let action_bar_bg = Paragraph::new(action_bar).style(Style::default().bg(colors.bg_secondary));
+    frame.render_widget(action_bar_bg, layout[2]);
+}
+
+fn render_notifications_view(frame: &mut Frame, state: &mut AppState, area: Rect) {
+    let colors = state.theme.colors();
+    let show_avatars = state.avatars_enabled();
+    let gutter = avatar_gutter(show_avatars);
+
+    // Split into main area and bottom bar for notification actions
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(area);
+
+    let main_area = layout[0];
+    let action_bar_area = layout[1];
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(colors.block_focus())
+        .title(match state.unread_notifications() {
+            0 => " 🔔 Notifications ".to_string(),
+            n => format!(" 🔔 Notifications ({n} unread) "),
+        })
+        .title_style(colors.text_primary());
+
+    if state.notifications.is_empty() {
+        let message = if state.loading_notifications {
+            "  ⏳ Loading..."
+        } else {
+            "  No notifications"
+        };
+        let empty = Paragraph::new(vec![
+            Line::from(""),
+            Line::styled(message, colors.text_muted()),
+        ])
+        .block(block);
+        frame.render_widget(empty, main_area);
+    } else {
+        let width = main_area.width.saturating_sub(2) as usize; // -2 for borders
+
+        let items: Vec<ListItem> = state
+            .notifications
+            .iter()
+            .enumerate()
+            .map(|(i, notification)| {
+                let base_style = if i == state.selected_notification {
+                    colors.selected()
+                } else {
+                    Style::default()
+                };
+                let unread = if notification.is_read { " " } else { "●" };
+
+                let title = format!(
+                    "{unread}{gutter}{} {} {} {} · {}",
+                    notification.kind.emoji(),
+                    notification.network.emoji(),
+                    notification.actor_name,
+                    notification.kind.action(),
+                    state.display_time(notification.created_at)
+                );
+                let handle = format!(" {gutter}   @{}", notification.actor_handle);
+
+                let mut lines = vec![
+                    Line::styled(
+                        format!("{title:<width$}"),
+                        base_style.patch(colors.text_primary()),
+                    ),
+                    Line::styled(
+                        format!("{handle:<width$}"),
+                        base_style.patch(colors.text_muted()),
+                    ),
+                ];
+
+                // First line of the post, as a reminder of what it was about
+                if let Some(post) = &notification.post {
+                    let snippet = if state.is_cw_collapsed(post) {
+                        format!("⚠ CW: {}", state.content_warning(post).unwrap_or_default())
+                    } else {
+                        post.content.lines().next().unwrap_or_default().to_string()
+                    };
+                    let snippet: String = snippet
+                        .chars()
+                        .take(width.saturating_sub(gutter.len() + 4))
+                        .collect();
+                    let text = format!(" {gutter}   {snippet}");
+                    lines.push(Line::styled(
+                        format!("{text:<width$}"),
+                        base_style.patch(colors.text()),
+                    ));
+                }
+
+                lines.push(Line::from(""));
+                ListItem::new(lines)
+            })
+            .collect();
+
+        let item_heights: Vec<usize> = items.iter().map(ListItem::height).collect();
+
+        let list = List::new(items).block(block);
+        let mut list_state = ratatui::widgets::ListState::default();
+        list_state.select(Some(state.selected_notification));
+        frame.render_stateful_widget(list, main_area, &mut list_state);
+
+        // Draw actor avatars after the unread marker
+        if show_avatars {
+            let inner = main_area.inner(Margin::new(1, 1));
+            let mut y: u16 = 0;
+            for (i, height) in item_heights.iter().enumerate().skip(list_state.offset()) {
+                if y + AVATAR_HEIGHT > inner.height {
+                    break;
+                }
+                let url = state.notifications[i].actor_avatar.clone();
+                let avatar_area = Rect::new(inner.x + 1, inner.y + y, AVATAR_WIDTH, AVATAR_HEIGHT);
+                render_avatar(frame, state, url.as_deref(), avatar_area);
+                y = y.saturating_add(u16::try_from(*height).unwrap_or(u16::MAX));
+            }
+        }
+    }
+
+    let action_bar = Line::from(vec![
+        Span::styled(" ", Style::default().bg(colors.bg_secondary)),
+        Span::styled("[r]", colors.key_hint()),
+        Span::styled(" Refresh  ", colors.text_muted()),
+        Span::styled("[o]", colors.key_hint()),
+        Span::styled(" Open post  ", colors.text_muted()),
+        Span::styled("[j/k]", colors.key_hint()),
+        Span::styled(" Navigate", colors.text_muted()),
+    ]);
+
+    let action_bar_bg = Paragraph::new(action_bar).style(Style::default().bg(colors.bg_secondary));
+    frame.render_widget(action_bar_bg, action_bar_area);
+}
+
+/// Blank space reserved for an avatar in front of an author line
+/// Put an account's marker in place of the blank first column of `line`
+fn mark_line(mut line: Line<'_>, marker: Style) -> Line<'_> {
+    if let Some(first) = line.spans.first_mut() {
+        first.content = first.content.chars().skip(1).collect::<String>().into();
+    }
+    line.spans.insert(0, Span::styled(ACCOUNT_MARKER, marker));
+    line
+}
+
+fn avatar_gutter(show_avatars: bool) -> String {
+    if show_avatars {
+        " ".repeat(usize::from(AVATAR_WIDTH) + 1)
+    } else {
+        String::new()
+    }
+}
+
+/// Draw an avatar once it has loaded; until then the gutter stays blank
+fn render_avatar(frame: &mut Frame, state: &mut AppState, url: Option<&str>, area: Rect) {
+    let Some(url) = url else {
+        return;
+    };
+    if let Some(protocol) = state.get_image_protocol(url) {
+        frame.render_stateful_widget(StatefulImage::new(), area, protocol);
+    }
+}
+
+fn render_status_bar(frame: &mut Frame, state: &AppState, area: Rect) {
+    let colors = state.theme.colors();
+
+    // Spinner animation frames
+    const SPINNER: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+    let loading_indicator = if state.is_busy() {
+        let frame_idx = (state.current_tick() / 2) as usize % SPINNER.len();
+        format!("{} ", SPINNER[frame_idx])
+    } else {
+        String::new()
+    };
+
+    let content = if !state.status.is_empty() {
+        vec![
+            Span::styled(" ", Style::default()),
+            Span::styled(&loading_indicator, colors.text_secondary()),
+            Span::styled(&state.status, colors.text_secondary()),
+        ]
+    } else if let Some(ref version) = state.update_available {
+        // Show update notification
+        vec![
+            Span::styled(" ⬆ ", Style::default().fg(Color::Yellow)),
+            Span::styled(
+                format!("Update available: v{version}"),
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::styled("  Press ", colors.text_muted()),
+            Span::styled("A", colors.key_hint()),
+            Span::styled(" then ", colors.text_muted()),
+            Span::styled("U", colors.key_hint()),
+            Span::styled(" to update", colors.text_muted()),
+        ]
+    } else {
+        vec![
+            Span::styled(" ", Style::default()),
+            Span::styled(&loading_indicator, colors.text_secondary()),
+            Span::styled("Tab", colors.key_hint()),
+            Span::styled(": views  ", colors.text_muted()),
+            Span::styled("?", colors.key_hint()),
+            Span::styled(": help  ", colors.text_muted()),
+            Span::styled("t", colors.key_hint()),
+            Span::styled(": theme  ", colors.text_muted()),
+            Span::styled("A", colors.key_hint()),
+            Span::styled(": about  ", colors.text_muted()),
+            Span::styled("q", colors.key_hint()),
+            Span::styled(": quit", colors.text_muted()),
+        ]
+    };
+
+    let status =
+        Paragraph::new(Line::from(content)).style(Style::default().bg(colors.bg_secondary));
+
+    // What's in flight, rate limiting, being offline, posts hidden by
+    // filters and the last refresh time on the right
+    let mut right = Vec::new();
+    if let Some(label) = state.busy_label() {
+        right.push(Span::styled(format!("{label}  "), colors.text_muted()));
+    }
+    if let Some(reset) = state.rate_limited_until() {
+        let secs = (reset - chrono::Utc::now()).num_seconds().max(1);
+        right.push(Span::styled(
+            format!("⏳ rate limited, retry in {secs}s  "),
+            colors.text_warning(),
+        ));
+    }
+    if state.offline {
+        right.push(Span::styled(
+            "⚠ offline, showing cache  ",
+            colors.text_warning(),
+        ));
+    }
+    let filtered = state.filtered_count();
+    if filtered > 0 {
+        right.push(Span::styled(
+            format!("{filtered} filtered  "),
+            colors.text_dim(),
+        ));
+    }
+    if let Some(refreshed) = state.last_refreshed {
+        right.push(Span::styled(
+            format!("↻ {} ", refreshed.format("%H:%M")),
+            colors.text_dim(),
+        ));
+    }
+    if right.is_empty() {
+        frame.render_widget(status, area);
+        return;
+    }
+    let right = Line::from(right);
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(u16::try_from(right.width()).unwrap_or(u16::MAX)),
+        ])
+        .split(area);
+    frame.render_widget(status, chunks[0]);
+    frame.render_widget(
+        Paragraph::new(right).style(Style::default().bg(colors.bg_secondary)),
+        chunks[1],
+    );
+}
+
+fn render_help_popup(frame: &mut Frame, state: &AppState) {
+    let colors = state.theme.colors();
+    let area = frame.area();
+
+    // Same dimensions as Hazelnut: 50% width, 70% height
+    let popup_area = centered_rect(50, 70, area);
+
+    // First render a solid background block to cover everything underneath
+    let bg_block = Block::default().style(Style::default().bg(colors.bg_secondary));
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(bg_block, popup_area);
+
+    let help_content = vec![
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Navigation",
+            colors.text_primary().add_modifier(Modifier::BOLD),
+        )]),
         Line::from(vec![
             Span::styled("  Tab              ", colors.key_hint()),
             Span::styled("Switch between views", colors.text()),
         ]),
         Line::from(vec![
-            Span::styled("  1/2              ", colors.key_hint()),
-            Span::styled("Jump to Timeline/Accounts", colors.text()),
+            Span::styled("  1/2/3            ", colors.key_hint()),
+            Span::styled("Jump to Timeline/Accounts/Notifications", colors.text()),
         ]),
         Line::from(vec![
             Span::styled("  h/l or ←/→       ", colors.key_hint()),
@@ -781,6 +2012,10 @@ fn render_help_popup(frame: &mut Frame, state: &AppState) {
             Span::styled("  g/G              ", colors.key_hint()),
             Span::styled("Go to first/last item", colors.text()),
         ]),
+        Line::from(vec![
+            Span::styled("  N                ", colors.key_hint()),
+            Span::styled("Jump to the newest post", colors.text()),
+        ]),
         Line::from(""),
         Line::from(vec![Span::styled(
             "  Timeline Actions",
@@ -794,6 +2029,10 @@ fn render_help_popup(frame: &mut Frame, state: &AppState) {
             Span::styled("  R                ", colors.key_hint()),
             Span::styled("Reply to post/reply", colors.text()),
         ]),
+        Line::from(vec![
+            Span::styled("  Q                ", colors.key_hint()),
+            Span::styled("Quote post", colors.text()),
+        ]),
         Line::from(vec![
             Span::styled("  r                ", colors.key_hint()),
             Span::styled("Repost/unrepost (toggle)", colors.text()),
@@ -806,17 +2045,81 @@ fn render_help_popup(frame: &mut Frame, state: &AppState) {
             Span::styled("  b                ", colors.key_hint()),
             Span::styled("Refresh timeline", colors.text()),
         ]),
+        Line::from(vec![
+            Span::styled("  Enter            ", colors.key_hint()),
+            Span::styled("Open full thread (Esc to return)", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  #                ", colors.key_hint()),
+            Span::styled("Pick a hashtag (Enter opens, Esc back)", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  p                ", colors.key_hint()),
+            Span::styled("Show the author's profile (Esc back)", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  +                ", colors.key_hint()),
+            Span::styled("Follow/unfollow (in a profile)", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  M / B            ", colors.key_hint()),
+            Span::styled("Mute / block the author (asks first)", colors.text()),
+        ]),
         Line::from(vec![
             Span::styled("  o                ", colors.key_hint()),
             Span::styled("Open in browser", colors.text()),
         ]),
+        Line::from(vec![
+            Span::styled("  O                ", colors.key_hint()),
+            Span::styled("Open a link from the post", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  y / Y            ", colors.key_hint()),
+            Span::styled("Copy post text / link", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  c                ", colors.key_hint()),
+            Span::styled("Show/hide content warning", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  a                ", colors.key_hint()),
+            Span::styled("Show full image descriptions", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  i                ", colors.key_hint()),
+            Span::styled("Retry images that failed to load", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  T                ", colors.key_hint()),
+            Span::styled("Relative / exact times", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  v                ", colors.key_hint()),
+            Span::styled("Side by side / one panel", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  m                ", colors.key_hint()),
+            Span::styled("Bookmark (Mastodon)", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  e                ", colors.key_hint()),
+            Span::styled("Edit your own post (Mastodon)", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  D                ", colors.key_hint()),
+            Span::styled("Delete your own post", colors.text()),
+        ]),
         Line::from(vec![
             Span::styled("  f                ", colors.key_hint()),
             Span::styled("Cycle filter (All/Mastodon/Bluesky)", colors.text()),
         ]),
+        Line::from(vec![
+            Span::styled("  F                ", colors.key_hint()),
+            Span::styled("Home/local/federated timeline", colors.text()),
+        ]),
         Line::from(vec![
             Span::styled("  /                ", colors.key_hint()),
-            Span::styled("Search posts", colors.text()),
+            Span::styled("Search cached posts", colors.text()),
         ]),
         Line::from(""),
         Line::from(vec![Span::styled(
@@ -864,6 +2167,38 @@ fn render_help_popup(frame: &mut Frame, state: &AppState) {
             Span::styled("  F4               ", colors.key_hint()),
             Span::styled("Clear schedule", colors.text()),
         ]),
+        Line::from(vec![
+            Span::styled("  F5               ", colors.key_hint()),
+            Span::styled("Cycle Mastodon visibility", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  F6               ", colors.key_hint()),
+            Span::styled("Edit content warning", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  F7               ", colors.key_hint()),
+            Span::styled("Pick accounts to post from", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  F8               ", colors.key_hint()),
+            Span::styled("Schedule on the Mastodon server", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  F9               ", colors.key_hint()),
+            Span::styled("Split long posts into a thread", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  Ctrl+E           ", colors.key_hint()),
+            Span::styled("Write post in $EDITOR", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  Ctrl+A           ", colors.key_hint()),
+            Span::styled("Attach image (path | alt text)", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  Ctrl+X           ", colors.key_hint()),
+            Span::styled("Remove last image", colors.text()),
+        ]),
         Line::from(vec![
             Span::styled("  Ctrl+S           ", colors.key_hint()),
             Span::styled("Send/schedule post", colors.text()),
@@ -889,6 +2224,14 @@ fn render_help_popup(frame: &mut Frame, state: &AppState) {
             Span::styled("  Enter            ", colors.key_hint()),
             Span::styled("Execute search", colors.text()),
         ]),
+        Line::from(vec![
+            Span::styled("  Tab              ", colors.key_hint()),
+            Span::styled("Switch between cache and server search", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  \"...\" author:   ", colors.key_hint()),
+            Span::styled("Exact phrase, limit to an author", colors.text()),
+        ]),
         Line::from(vec![
             Span::styled("  Esc              ", colors.key_hint()),
             Span::styled("Cancel", colors.text()),
@@ -906,6 +2249,10 @@ fn render_help_popup(frame: &mut Frame, state: &AppState) {
             Span::styled("  D                ", colors.key_hint()),
             Span::styled("Delete account", colors.text()),
         ]),
+        Line::from(vec![
+            Span::styled("  r                ", colors.key_hint()),
+            Span::styled("Check credentials, refresh server details", colors.text()),
+        ]),
         Line::from(vec![
             Span::styled("  Enter            ", colors.key_hint()),
             Span::styled("View account's timeline", colors.text()),
@@ -919,6 +2266,14 @@ fn render_help_popup(frame: &mut Frame, state: &AppState) {
             Span::styled("  t                ", colors.key_hint()),
             Span::styled("Open theme selector", colors.text()),
         ]),
+        Line::from(vec![
+            Span::styled("  w                ", colors.key_hint()),
+            Span::styled("Open saved drafts", colors.text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  S                ", colors.key_hint()),
+            Span::styled("Open scheduled posts", colors.text()),
+        ]),
         Line::from(vec![
             Span::styled("  A                ", colors.key_hint()),
             Span::styled("About Perch", colors.text()),
@@ -972,16 +2327,12 @@ fn render_theme_picker(frame: &mut Frame, state: &AppState) {
     let items: Vec<ListItem> = themes
         .iter()
         .enumerate()
-        .map(|(i, theme_name)| {
-            let palette = theme_name.palette();
+        .map(|(i, theme)| {
+            let palette = theme.palette();
             let selected = i == state.theme_picker_index;
 
             // Create color preview squares - exactly like Hazelnut
-            let preview = format!(
-                "  {} {} ",
-                if selected { "▸" } else { " " },
-                theme_name.display_name()
-            );
+            let preview = format!("  {} {} ", if selected { "▸" } else { " " }, theme.name());
 
             let style = if selected {
                 Style::default()
@@ -1021,6 +2372,191 @@ fn render_theme_picker(frame: &mut Frame, state: &AppState) {
     frame.render_widget(theme_list, popup_area);
 }
 
+fn render_drafts_picker(frame: &mut Frame, state: &AppState) {
+    let colors = state.theme.colors();
+    let area = frame.area();
+
+    let popup_area = centered_rect(60, 60, area);
+
+    let bg_block = Block::default().style(Style::default().bg(colors.bg));
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(bg_block, popup_area);
+
+    let items: Vec<ListItem> = state
+        .drafts
+        .iter()
+        .enumerate()
+        .map(|(i, draft)| {
+            let selected = i == state.selected_draft;
+            let networks: String = draft.networks.iter().map(Network::emoji).collect();
+            let reply = draft
+                .reply_to
+                .as_ref()
+                .map_or_else(String::new, |p| format!(" ↩ @{}", p.author_handle));
+
+            let style = if selected {
+                colors.selected()
+            } else {
+                colors.text()
+            };
+
+            ListItem::new(vec![
+                Line::from(vec![
+                    Span::styled(if selected { "  ▸ " } else { "    " }, style),
+                    Span::styled(draft.preview().to_string(), style),
+                ]),
+                Line::from(vec![
+                    Span::raw("    "),
+                    Span::styled(
+                        format!(
+                            "{networks}{reply}  {}",
+                            draft
+                                .updated_at
+                                .with_timezone(&chrono::Local)
+                                .format("%Y-%m-%d %H:%M")
+                        ),
+                        colors.text_dim(),
+                    ),
+                ]),
+            ])
+        })
+        .collect();
+
+    let mut list_state = ListState::default().with_selected(Some(state.selected_draft));
+    let list = List::new(items)
+        .style(Style::default().bg(colors.bg))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(colors.primary))
+                .border_type(BorderType::Rounded)
+                .style(Style::default().bg(colors.bg))
+                .title(format!(" 💾 Drafts ({}) ", state.drafts.len()))
+                .title_bottom(
+                    Line::from(" ↑↓ navigate │ ↵ open │ d delete │ Esc close ").centered(),
+                ),
+        );
+
+    frame.render_stateful_widget(list, popup_area, &mut list_state);
+}
+
+fn render_scheduled_picker(frame: &mut Frame, state: &AppState) {
+    let colors = state.theme.colors();
+    let area = frame.area();
+
+    let popup_area = centered_rect(60, 60, area);
+
+    let bg_block = Block::default().style(Style::default().bg(colors.bg));
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(bg_block, popup_area);
+
+    let items: Vec<ListItem> = state
+        .scheduled_posts
+        .iter()
+        .enumerate()
+        .map(|(i, post)| {
+            let selected = i == state.selected_scheduled;
+            let networks: String = post.networks.iter().map(Network::emoji).collect();
+            let repeats = post
+                .recurrence
+                .as_ref()
+                .map_or_else(String::new, |r| format!("  🔁 {r}"));
+
+            let style = if selected {
+                colors.selected()
+            } else {
+                colors.text()
+            };
+
+            ListItem::new(vec![
+                Line::from(vec![
+                    Span::styled(if selected { "  ▸ " } else { "    " }, style),
+                    Span::styled(post.preview().to_string(), style),
+                ]),
+                Line::from(vec![
+                    Span::raw("    "),
+                    Span::styled(
+                        format!(
+                            "{networks}  {} (in {}){repeats}",
+                            post.scheduled_time_display(&state.config.timezone),
+                            post.time_until()
+                        ),
+                        colors.text_dim(),
+                    ),
+                ]),
+            ])
+        })
+        .collect();
+
+    let mut list_state = ListState::default().with_selected(Some(state.selected_scheduled));
+    let list = List::new(items)
+        .style(Style::default().bg(colors.bg))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(colors.primary))
+                .border_type(BorderType::Rounded)
+                .style(Style::default().bg(colors.bg))
+                .title(format!(" 📅 Scheduled ({}) ", state.scheduled_posts.len()))
+                .title_bottom(
+                    Line::from(" ↑↓ navigate │ ↵ edit │ d cancel │ Esc close ").centered(),
+                ),
+        );
+
+    frame.render_stateful_widget(list, popup_area, &mut list_state);
+}
+
+fn render_link_picker(frame: &mut Frame, state: &AppState) {
+    let colors = state.theme.colors();
+    let area = frame.area();
+
+    let popup_area = centered_rect(70, 50, area);
+
+    let bg_block = Block::default().style(Style::default().bg(colors.bg));
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(bg_block, popup_area);
+
+    let items: Vec<ListItem> = state
+        .links
+        .iter()
+        .enumerate()
+        .map(|(i, url)| {
+            let selected = i == state.selected_link;
+            let style = if selected {
+                colors.selected()
+            } else {
+                colors.text()
+            };
+            let number = if i < 9 {
+                format!("{}", i + 1)
+            } else {
+                " ".to_string()
+            };
+
+            ListItem::new(Line::from(vec![
+                Span::styled(if selected { "  ▸ " } else { "    " }, style),
+                Span::styled(format!("{number} "), colors.key_hint()),
+                Span::styled(url.clone(), style),
+            ]))
+        })
+        .collect();
+
+    let mut list_state = ListState::default().with_selected(Some(state.selected_link));
+    let list = List::new(items)
+        .style(Style::default().bg(colors.bg))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(colors.primary))
+                .border_type(BorderType::Rounded)
+                .style(Style::default().bg(colors.bg))
+                .title(format!(" 🔗 Links ({}) ", state.links.len()))
+                .title_bottom(Line::from(" ↑↓ navigate │ ↵/1-9 open │ Esc close ").centered()),
+        );
+
+    frame.render_stateful_widget(list, popup_area, &mut list_state);
+}
+
 fn render_about_dialog(frame: &mut Frame, state: &AppState) {
     let colors = state.theme.colors();
     let area = frame.area();
@@ -1138,7 +2674,7 @@ fn render_about_dialog(frame: &mut Frame, state: &AppState) {
     frame.render_widget(paragraph, popup_area);
 }
 
-fn render_compose_popup(frame: &mut Frame, state: &AppState) {
+fn render_compose_popup(frame: &mut Frame, state: &mut AppState) {
     let colors = state.theme.colors();
     let area = frame.area();
 
@@ -1150,9 +2686,6 @@ fn render_compose_popup(frame: &mut Frame, state: &AppState) {
     frame.render_widget(Clear, popup_area);
     frame.render_widget(bg_block, popup_area);
 
-    let char_count = state.compose_text.len();
-    let max_chars = 500; // Mastodon default, Bluesky is 300
-
     let mut content = vec![Line::from("")];
 
     // Show reply context if replying
@@ -1167,43 +2700,42 @@ fn render_compose_popup(frame: &mut Frame, state: &AppState) {
         content.push(Line::from(""));
     }
 
-    // Show network selection toggles - cleaner pill-style
-    let has_mastodon = state
-        .accounts
-        .iter()
-        .any(|a| a.network == crate::models::Network::Mastodon);
-    let has_bluesky = state
-        .accounts
-        .iter()
-        .any(|a| a.network == crate::models::Network::Bluesky);
-    let mastodon_selected = state
-        .compose_networks
-        .contains(&crate::models::Network::Mastodon);
-    let bluesky_selected = state
-        .compose_networks
-        .contains(&crate::models::Network::Bluesky);
+    // Show the quoted post if quoting
+    if let Some(ref quote) = state.compose_quote {
+        content.push(Line::from(vec![
+            Span::styled("  ❝ Quoting ", colors.text_dim()),
+            Span::styled(format!("@{}", quote.author_handle), colors.text_primary()),
+        ]));
+        content.push(Line::from(""));
+    }
 
+    // Show account selection toggles - cleaner pill-style
     let mut network_spans = vec![Span::styled("  ", Style::default())];
 
-    if has_mastodon {
-        if mastodon_selected {
-            network_spans.push(Span::styled(" 🐘 Mastodon ✓ ", colors.selected()));
+    for (i, account) in state.accounts.iter().enumerate() {
+        let selected = state.compose_accounts.contains(&account.id);
+        let label = format!(
+            " {} @{}{} ",
+            account.network.emoji(),
+            account.handle,
+            if selected { " ✓" } else { "" }
+        );
+        let mut style = if selected {
+            colors.selected()
         } else {
-            network_spans.push(Span::styled(" 🐘 Mastodon ", colors.text_dim()));
+            colors.text_dim()
+        };
+        if state.compose_accounts_focused && i == state.selected_compose_account {
+            style = style.add_modifier(Modifier::UNDERLINED | Modifier::BOLD);
         }
+        network_spans.push(Span::styled(label, style));
         network_spans.push(Span::styled(" ", Style::default()));
     }
 
-    if has_bluesky {
-        if bluesky_selected {
-            network_spans.push(Span::styled(" 🦋 Bluesky ✓ ", colors.selected()));
-        } else {
-            network_spans.push(Span::styled(" 🦋 Bluesky ", colors.text_dim()));
-        }
-    }
-
-    if !has_mastodon && !has_bluesky {
+    if state.accounts.is_empty() {
         network_spans.push(Span::styled("No accounts configured!", colors.text_error()));
+    } else if state.compose_accounts_focused {
+        network_spans.push(Span::styled("(Space toggles)", colors.text_muted()));
     }
 
     content.push(Line::from(network_spans));
@@ -1221,7 +2753,7 @@ fn render_compose_popup(frame: &mut Frame, state: &AppState) {
         "🕐"
     };
 
-    content.push(Line::from(vec![
+    let mut schedule_spans = vec![
         Span::styled("  ", Style::default()),
         Span::styled(format!("{} Schedule: ", schedule_icon), schedule_style),
         if state.compose_schedule_focused {
@@ -1249,115 +2781,303 @@ fn render_compose_popup(frame: &mut Frame, state: &AppState) {
                 },
             )
         },
-    ]));
-
-    // Key hints
-    content.push(Line::from(vec![
-        Span::styled("  ", Style::default()),
-        Span::styled("F1", colors.key_hint()),
-        Span::styled("/", colors.text_dim()),
-        Span::styled("F2", colors.key_hint()),
-        Span::styled(" network  ", colors.text_dim()),
-        Span::styled("Tab", colors.key_hint()),
-        Span::styled(" schedule  ", colors.text_dim()),
-        Span::styled("Ctrl+S", colors.key_hint()),
-        Span::styled(" send  ", colors.text_dim()),
-        Span::styled("Esc", colors.key_hint()),
-        Span::styled(" cancel", colors.text_dim()),
-    ]));
-    content.push(Line::from(""));
+    ];
+    if state.compose_visibility_enabled() && state.compose_schedule.is_some() {
+        schedule_spans.push(if state.compose_server_side {
+            Span::styled("  🐘 published by Mastodon", colors.text_primary())
+        } else {
+            Span::styled("  (F8 server-side)", colors.text_muted())
+        });
+    }
+    content.push(Line::from(schedule_spans));
 
-    // Display compose text - handle multiple lines
-    if state.compose_text.is_empty() && !state.compose_schedule_focused {
+    // Visibility row (Mastodon only)
+    if state.compose_visibility_enabled() {
         content.push(Line::from(vec![
             Span::styled("  ", Style::default()),
             Span::styled(
-                if state.reply_to.is_some() {
-                    "Write your reply..."
+                format!("{} Visibility: ", state.compose_visibility.emoji()),
+                colors.text_dim(),
+            ),
+            Span::styled(
+                state.compose_visibility.name(),
+                if state.compose_visibility == Visibility::Public {
+                    colors.text_dim()
                 } else {
-                    "What's on your mind?"
+                    colors.text_primary()
                 },
-                colors.text_muted(),
             ),
+            Span::styled("  (F5)", colors.text_muted()),
         ]));
-    } else {
-        // Split text by newlines and render each line
-        let text_style = if state.compose_schedule_focused {
-            colors.text_dim()
+    }
+
+    // Content warning row (Mastodon only)
+    if state.compose_visibility_enabled() {
+        let cw_style = if state.compose_cw_focused {
+            colors.selected()
         } else {
-            colors.text()
+            colors.text_dim()
         };
-        for line in state.compose_text.split('\n') {
-            content.push(Line::from(vec![
-                Span::styled("  ", Style::default()),
-                Span::styled(line.to_string(), text_style),
-            ]));
-        }
+        content.push(Line::from(vec![
+            Span::styled("  ", Style::default()),
+            Span::styled("⚠ CW: ", cw_style),
+            if state.compose_cw.is_empty() {
+                Span::styled(
+                    if state.compose_cw_focused {
+                        "content warning..."
+                    } else {
+                        "none  (F6)"
+                    },
+                    colors.text_muted(),
+                )
+            } else {
+                Span::styled(state.compose_cw.clone(), colors.text_primary())
+            },
+        ]));
     }
 
+    // Attachments row
+    let attach_style = if state.compose_attach_focused {
+        colors.selected()
+    } else {
+        colors.text_dim()
+    };
+    content.push(Line::from(vec![
+        Span::styled("  ", Style::default()),
+        Span::styled("📎 Images: ", attach_style),
+        if state.compose_attach_focused {
+            if state.compose_attach_input.is_empty() {
+                Span::styled("path/to/image.png | alt text", colors.text_muted())
+            } else {
+                Span::styled(state.compose_attach_input.clone(), colors.text_primary())
+            }
+        } else if state.compose_media.is_empty() {
+            Span::styled("none", colors.text_dim())
+        } else {
+            Span::styled(
+                state
+                    .compose_media
+                    .iter()
+                    .map(|m| m.file_name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                colors.text_primary(),
+            )
+        },
+    ]));
+
+    // Key hints
+    content.push(Line::from(vec![
+        Span::styled("  ", Style::default()),
+        Span::styled("F1", colors.key_hint()),
+        Span::styled("/", colors.text_dim()),
+        Span::styled("F2", colors.key_hint()),
+        Span::styled(" network  ", colors.text_dim()),
+        Span::styled("F7", colors.key_hint()),
+        Span::styled(" accounts  ", colors.text_dim()),
+        Span::styled("Tab", colors.key_hint()),
+        Span::styled(" schedule  ", colors.text_dim()),
+        Span::styled("Ctrl+E", colors.key_hint()),
+        Span::styled(" editor  ", colors.text_dim()),
+        Span::styled("Ctrl+A", colors.key_hint()),
+        Span::styled(" image  ", colors.text_dim()),
+        Span::styled("Ctrl+S", colors.key_hint()),
+        Span::styled(" send  ", colors.text_dim()),
+        Span::styled("Esc", colors.key_hint()),
+        Span::styled(" cancel", colors.text_dim()),
+    ]));
     content.push(Line::from(""));
-    content.push(Line::from(vec![Span::styled(
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(colors.block_focus())
+        .style(Style::default().bg(colors.bg))
+        .title(compose_title(state))
+        .title_style(colors.text_primary());
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    // Everything above the text, the text, then a blank line and the count
+    let [header_area, text_area, count_area] = Layout::vertical([
+        Constraint::Length(content.len() as u16),
+        Constraint::Min(1),
+        Constraint::Length(2),
+    ])
+    .areas(inner);
+    frame.render_widget(Paragraph::new(content), header_area);
+    let text_area = text_area.inner(Margin::new(2, 0));
+    let (more_above, more_below) = render_compose_text(frame, state, text_area);
+
+    let char_count = state.compose_text.chars().count();
+    let max_chars = state.compose_char_limit();
+    let too_long = char_count > max_chars;
+    let mut count_spans = vec![Span::styled(
         format!("  {char_count}/{max_chars}"),
-        if char_count > max_chars {
+        if too_long && !state.compose_thread {
             colors.text_error()
         } else {
             colors.text_dim()
         },
-    )]));
+    )];
+    if state.compose_thread && too_long {
+        count_spans.push(Span::styled(
+            format!("  🧵 thread of {}", state.compose_thread_len()),
+            colors.text_primary(),
+        ));
+    } else if too_long {
+        count_spans.push(Span::styled("  (F9 thread)", colors.text_muted()));
+    }
+    if more_above || more_below {
+        count_spans.push(Span::styled(
+            format!(
+                "  {}{} more",
+                if more_above { "▲" } else { "" },
+                if more_below { "▼" } else { "" }
+            ),
+            colors.text_muted(),
+        ));
+    }
+    frame.render_widget(
+        Paragraph::new(vec![Line::from(""), Line::from(count_spans)]),
+        count_area,
+    );
 
-    let title = if state.reply_to.is_some() {
-        " ↩ Reply ".to_string()
-    } else if state.compose_schedule.is_some() {
-        " 📅 Schedule Post ".to_string()
+    // Show cursor position
+    let reply_offset = if state.reply_to.is_some() || state.compose_quote.is_some() {
+        2u16
     } else {
-        " 📝 Compose ".to_string()
+        0
+    };
+    // Visibility and CW rows are only shown when Mastodon is selected
+    let visibility_offset = if state.compose_visibility_enabled() {
+        2u16
+    } else {
+        0
     };
 
-    let compose = Paragraph::new(content)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .border_style(colors.block_focus())
-                .style(Style::default().bg(colors.bg))
-                .title(title)
-                .title_style(colors.text_primary()),
-        )
-        .wrap(Wrap { trim: false });
-
-    frame.render_widget(compose, popup_area);
-
-    // Show cursor position
-    let reply_offset = if state.reply_to.is_some() { 2u16 } else { 0 };
-    let network_offset = 4u16; // network pills + schedule + hint line + empty line
-
-    if state.compose_schedule_focused {
+    if state.compose_cw_focused {
+        // Cursor in content warning input
+        let cw_prefix = "  ⚠ CW: ".len() as u16;
+        let input_len = state.compose_cw.len() as u16;
+        let cursor_x = popup_area.x + cw_prefix + input_len;
+        let cursor_y = popup_area.y + 2 + reply_offset + 3; // After visibility row
+        if cursor_x < popup_area.right().saturating_sub(1) {
+            frame.set_cursor_position((cursor_x, cursor_y));
+        }
+    } else if state.compose_attach_focused {
+        // Cursor in attachment path input
+        let attach_prefix = "  📎 Images: ".len() as u16;
+        let input_len = state.compose_attach_input.len() as u16;
+        let cursor_x = popup_area.x + attach_prefix + input_len;
+        let cursor_y = popup_area.y + 2 + reply_offset + 2 + visibility_offset; // After schedule row
+        if cursor_x < popup_area.right().saturating_sub(1) {
+            frame.set_cursor_position((cursor_x, cursor_y));
+        }
+    } else if state.compose_schedule_focused {
         // Cursor in schedule input field
         let schedule_prefix = "  📅 Schedule: ".len() as u16;
         let input_len = state.compose_schedule_input.len() as u16;
         let cursor_x = popup_area.x + schedule_prefix + input_len;
         let cursor_y = popup_area.y + 2 + reply_offset + 1; // After network row
-        if cursor_x < popup_area.x + popup_area.width - 1 {
+        if cursor_x < popup_area.right().saturating_sub(1) {
             frame.set_cursor_position((cursor_x, cursor_y));
         }
+    } else if let Some((x, y)) = compose_cursor_position(state, text_area) {
+        frame.set_cursor_position((x, y));
+    }
+}
+
+const fn compose_title(state: &AppState) -> &'static str {
+    if state.compose_edit.is_some() {
+        " ✏️ Edit Post "
+    } else if state.compose_scheduled.is_some() {
+        " ✏️ Edit Scheduled Post "
+    } else if state.reply_to.is_some() {
+        " ↩ Reply "
+    } else if state.compose_quote.is_some() {
+        " ❝ Quote "
+    } else if state.compose_schedule.is_some() {
+        " 📅 Schedule Post "
     } else {
-        // Cursor in text area
-        let text_lines: Vec<&str> = state.compose_text.split('\n').collect();
-        let line_count = text_lines.len();
-        let last_line_len = text_lines.last().map_or(0, |l| l.len());
-
-        let cursor_x = popup_area.x + 3 + last_line_len as u16;
-        let cursor_y = popup_area.y
-            + 2
-            + reply_offset
-            + network_offset
-            + (line_count.saturating_sub(1)) as u16;
-        if cursor_x < popup_area.x + popup_area.width - 1
-            && cursor_y < popup_area.y + popup_area.height - 2
-        {
-            frame.set_cursor_position((cursor_x, cursor_y));
+        " 📝 Compose "
+    }
+}
+
+/// Width the compose text wraps at, leaving a column for the cursor after
+/// the last character of a full row
+const fn compose_wrap_width(area: Rect) -> usize {
+    area.width.saturating_sub(1) as usize
+}
+
+/// Render the compose text word-wrapped into `area`, scrolled so the cursor
+/// is in view. Returns whether rows are hidden above and below.
+fn render_compose_text(frame: &mut Frame, state: &mut AppState, area: Rect) -> (bool, bool) {
+    let colors = state.theme.colors();
+    let input_focused = state.compose_schedule_focused
+        || state.compose_attach_focused
+        || state.compose_cw_focused
+        || state.compose_accounts_focused;
+    if state.compose_text.is_empty() {
+        state.compose_scroll = 0;
+        if !input_focused {
+            let placeholder = if state.reply_to.is_some() {
+                "Write your reply..."
+            } else if state.compose_quote.is_some() {
+                "Add a comment..."
+            } else {
+                "What's on your mind?"
+            };
+            frame.render_widget(
+                Paragraph::new(Span::styled(placeholder, colors.text_muted())),
+                area,
+            );
         }
+        return (false, false);
     }
+
+    let text = &state.compose_text;
+    let rows = text_cursor::wrap(text, compose_wrap_width(area));
+    let cursor = text_cursor::clamp(text, state.compose_cursor);
+    let (cursor_row, _) = text_cursor::locate(text, &rows, cursor);
+    let height = usize::from(area.height).max(1);
+    // Scroll just enough to show the cursor's row
+    state.compose_scroll = state
+        .compose_scroll
+        .clamp(cursor_row.saturating_sub(height - 1), cursor_row)
+        .min(rows.len().saturating_sub(height));
+
+    let text_style = if input_focused {
+        colors.text_dim()
+    } else {
+        colors.text()
+    };
+    let lines: Vec<Line> = rows
+        .iter()
+        .skip(state.compose_scroll)
+        .take(height)
+        .map(|row| Line::from(Span::styled(&text[row.clone()], text_style)))
+        .collect();
+    frame.render_widget(Paragraph::new(lines), area);
+
+    (
+        state.compose_scroll > 0,
+        rows.len() > state.compose_scroll + height,
+    )
+}
+
+/// Where the terminal cursor goes for the compose text cursor, if it's in
+/// view of `area`
+fn compose_cursor_position(state: &AppState, area: Rect) -> Option<(u16, u16)> {
+    let text = &state.compose_text;
+    let rows = text_cursor::wrap(text, compose_wrap_width(area));
+    let cursor = text_cursor::clamp(text, state.compose_cursor);
+    let (row, column) = text_cursor::locate(text, &rows, cursor);
+    let row = u16::try_from(row.checked_sub(state.compose_scroll)?).ok()?;
+    let column = u16::try_from(column)
+        .ok()?
+        .min(area.width.saturating_sub(1));
+    (row < area.height).then_some((area.x + column, area.y + row))
 }
 
 fn render_search_popup(frame: &mut Frame, state: &AppState) {
@@ -1389,9 +3109,17 @@ fn render_search_popup(frame: &mut Frame, state: &AppState) {
             Span::styled("  ", Style::default()),
             Span::styled("Enter", colors.key_hint()),
             Span::styled(" search  ", colors.text_muted()),
+            Span::styled("Tab", colors.key_hint()),
+            Span::styled(" cache/servers  ", colors.text_muted()),
             Span::styled("Esc", colors.key_hint()),
             Span::styled(" cancel", colors.text_muted()),
         ]),
+        Line::from(vec![
+            Span::styled("  ", Style::default()),
+            Span::styled("\"exact phrase\"", colors.key_hint()),
+            Span::styled("  ", Style::default()),
+            Span::styled("author:name", colors.key_hint()),
+        ]),
     ];
 
     let search = Paragraph::new(content).block(
@@ -1400,7 +3128,7 @@ fn render_search_popup(frame: &mut Frame, state: &AppState) {
             .border_type(BorderType::Rounded)
             .border_style(colors.block_focus())
             .style(Style::default().bg(colors.bg))
-            .title(" 🔍 Search ")
+            .title(format!(" 🔍 Search {} ", state.search_scope.label()))
             .title_style(colors.text_primary()),
     );
 
@@ -1457,12 +3185,86 @@ fn render_update_banner(frame: &mut Frame, state: &AppState, area: Rect) {
     }
 }
 
-fn render_update_confirm_dialog(frame: &mut Frame, state: &AppState) {
+fn render_confirm_delete_dialog(frame: &mut Frame, state: &AppState) {
+    let colors = state.theme.colors();
+    let area = frame.area();
+
+    // Center popup
+    let popup_width = 60u16;
+    let popup_height = 9u16;
+    let popup_area = Rect {
+        x: area.width.saturating_sub(popup_width) / 2,
+        y: area.height.saturating_sub(popup_height) / 2,
+        width: popup_width.min(area.width),
+        height: popup_height.min(area.height),
+    };
+
+    frame.render_widget(Clear, popup_area);
+
+    let (title, question, preview) = match &state.pending_delete {
+        Some(PendingDelete::Account(account)) => (
+            " 🗑 Remove Account ",
+            "Remove this account and its stored credentials?",
+            format!("{} @{}", account.network.emoji(), account.handle),
+        ),
+        Some(PendingDelete::Post(post)) => {
+            let text = post.content.replace('\n', " ");
+            let max = usize::from(popup_width.saturating_sub(8));
+            let preview = if text.chars().count() > max {
+                format!("{}…", text.chars().take(max - 1).collect::<String>())
+            } else {
+                text
+            };
+            (
+                " 🗑 Delete Post ",
+                "Delete this post? This cannot be undone.",
+                preview,
+            )
+        }
+        None => (" 🗑 Delete ", "", String::new()),
+    };
+
+    let lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(question, colors.text())),
+        Line::from(""),
+        Line::from(Span::styled(preview, colors.text_muted())),
+        Line::from(""),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                " [Y] ",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Yes, delete"),
+            Span::raw("    "),
+            Span::styled(" [N/Esc] ", colors.text_muted()),
+            Span::raw("Cancel"),
+        ]),
+    ];
+
+    let paragraph = Paragraph::new(lines).alignment(Alignment::Center).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Red))
+            .style(Style::default().bg(colors.bg))
+            .title(title)
+            .title_style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+    );
+
+    frame.render_widget(paragraph, popup_area);
+}
+
+fn render_confirm_moderation_dialog(frame: &mut Frame, state: &AppState) {
     let colors = state.theme.colors();
     let area = frame.area();
+    let Some(pending) = &state.pending_moderation else {
+        return;
+    };
 
     // Center popup
-    let popup_width = 50u16;
+    let popup_width = 60u16;
     let popup_height = 9u16;
     let popup_area = Rect {
         x: area.width.saturating_sub(popup_width) / 2,
@@ -1473,10 +3275,163 @@ fn render_update_confirm_dialog(frame: &mut Frame, state: &AppState) {
 
     frame.render_widget(Clear, popup_area);
 
+    let verb = pending.moderation.verb(pending.undo);
+    let question = match (pending.moderation, pending.undo) {
+        (Moderation::Mute, false) => "Mute this account? Their posts stop showing up.",
+        (Moderation::Block, false) => "Block this account? They can't follow or reply to you.",
+        (Moderation::Mute, true) => "Unmute this account? Their posts return on refresh.",
+        (Moderation::Block, true) => "Unblock this account? Their posts return on refresh.",
+    };
+    let title = format!(
+        " {} {}{} ",
+        pending.moderation.emoji(),
+        verb[..1].to_uppercase(),
+        &verb[1..]
+    );
+
+    let lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(question, colors.text())),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("{} @{}", pending.network.emoji(), pending.handle),
+            colors.text_muted(),
+        )),
+        Line::from(""),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                " [Y] ",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!("Yes, {verb}")),
+            Span::raw("    "),
+            Span::styled(" [N/Esc] ", colors.text_muted()),
+            Span::raw("Cancel"),
+        ]),
+    ];
+
+    let paragraph = Paragraph::new(lines).alignment(Alignment::Center).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Red))
+            .style(Style::default().bg(colors.bg))
+            .title(title)
+            .title_style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+    );
+
+    frame.render_widget(paragraph, popup_area);
+}
+
+fn render_confirm_quit_dialog(frame: &mut Frame, state: &AppState) {
+    let colors = state.theme.colors();
+    let area = frame.area();
+
+    // Center popup
+    let popup_width = 60u16;
+    let popup_height = 8u16;
+    let popup_area = Rect {
+        x: area.width.saturating_sub(popup_width) / 2,
+        y: area.height.saturating_sub(popup_height) / 2,
+        width: popup_width.min(area.width),
+        height: popup_height.min(area.height),
+    };
+
+    frame.render_widget(Clear, popup_area);
+
+    let mut choices = vec![
+        Span::styled(
+            " [Y] ",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ),
+        Span::raw("Quit"),
+        Span::raw("   "),
+    ];
+    if state.compose_is_draftable() {
+        choices.extend([
+            Span::styled(" [S] ", colors.key_hint()),
+            Span::raw("Save draft and quit"),
+            Span::raw("   "),
+        ]);
+    }
+    choices.extend([
+        Span::styled(" [N/Esc] ", colors.text_muted()),
+        Span::raw("Keep writing"),
+    ]);
+
+    let lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "You have an unsent post. Quit anyway?",
+            colors.text(),
+        )),
+        Line::from(""),
+        Line::from(""),
+        Line::from(choices),
+    ];
+
+    let paragraph = Paragraph::new(lines).alignment(Alignment::Center).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Red))
+            .style(Style::default().bg(colors.bg))
+            .title(" ⚠ Quit ")
+            .title_style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+    );
+
+    frame.render_widget(paragraph, popup_area);
+}
+
+fn render_update_confirm_dialog(frame: &mut Frame, state: &AppState) {
+    let colors = state.theme.colors();
+    let area = frame.area();
+
+    let notes = state
+        .release_notes
+        .as_deref()
+        .map(|notes| release_notes_lines(notes, &colors))
+        .unwrap_or_default();
+
+    // Center popup, taller when there are notes to show
+    let notes_height = u16::try_from(notes.len()).unwrap_or(u16::MAX).clamp(1, 14);
+    let popup_width = if notes.is_empty() { 50u16 } else { 70 };
+    let popup_height = 10 + notes_height;
+    let popup_area = Rect {
+        x: area.width.saturating_sub(popup_width) / 2,
+        y: area.height.saturating_sub(popup_height) / 2,
+        width: popup_width.min(area.width),
+        height: popup_height.min(area.height),
+    };
+
+    frame.render_widget(Clear, popup_area);
+
     let latest = state.update_available.as_deref().unwrap_or("unknown");
     let pm = &state.package_manager;
 
-    let lines = vec![
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Yellow))
+        .style(Style::default().bg(colors.bg))
+        .title(" ⬆️ Update Perch ")
+        .title_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        );
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let [header_area, notes_area, buttons_area] = Layout::vertical([
+        Constraint::Length(5),
+        Constraint::Min(1),
+        Constraint::Length(3),
+    ])
+    .areas(inner);
+
+    let header = vec![
         Line::from(""),
         Line::from(vec![
             Span::styled("Update to ", colors.text()),
@@ -1494,6 +3449,31 @@ fn render_update_confirm_dialog(frame: &mut Frame, state: &AppState) {
             Span::styled(pm.update_command(), Style::default().fg(colors.primary)),
         ]),
         Line::from(""),
+    ];
+    frame.render_widget(
+        Paragraph::new(header).alignment(Alignment::Center),
+        header_area,
+    );
+
+    if notes.is_empty() {
+        frame.render_widget(
+            Paragraph::new(Line::styled(
+                "No release notes available",
+                colors.text_muted(),
+            ))
+            .alignment(Alignment::Center),
+            notes_area,
+        );
+    } else {
+        frame.render_widget(
+            Paragraph::new(notes)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().padding(ratatui::widgets::Padding::horizontal(2))),
+            notes_area,
+        );
+    }
+
+    let buttons = vec![
         Line::from(""),
         Line::from(vec![
             Span::styled(
@@ -1508,22 +3488,48 @@ fn render_update_confirm_dialog(frame: &mut Frame, state: &AppState) {
             Span::raw("Cancel"),
         ]),
     ];
-
-    let paragraph = Paragraph::new(lines).alignment(Alignment::Center).block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(Color::Yellow))
-            .style(Style::default().bg(colors.bg))
-            .title(" ⬆️ Update Perch ")
-            .title_style(
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            ),
+    frame.render_widget(
+        Paragraph::new(buttons).alignment(Alignment::Center),
+        buttons_area,
     );
+}
 
-    frame.render_widget(paragraph, popup_area);
+/// Release notes as lines to show, with Markdown headings in bold and list
+/// items as bullets
+fn release_notes_lines(notes: &str, colors: &ThemeColors) -> Vec<Line<'static>> {
+    let mut lines: Vec<Line<'static>> = Vec::new();
+    let mut last_blank = true;
+    for line in notes.lines().map(str::trim_end) {
+        if line.trim().is_empty() {
+            if !last_blank {
+                lines.push(Line::from(""));
+            }
+            last_blank = true;
+            continue;
+        }
+        last_blank = false;
+
+        let line = if let Some(heading) = line.strip_prefix('#') {
+            Line::styled(
+                heading.trim_start_matches('#').trim().to_string(),
+                colors.text().add_modifier(Modifier::BOLD),
+            )
+        } else if let Some(item) = line
+            .trim_start()
+            .strip_prefix("- ")
+            .or_else(|| line.trim_start().strip_prefix("* "))
+        {
+            let indent = line.len() - line.trim_start().len();
+            Line::styled(format!("{}• {item}", " ".repeat(indent)), colors.text())
+        } else {
+            Line::styled(line.to_string(), colors.text())
+        };
+        lines.push(line);
+    }
+    if last_blank {
+        lines.pop();
+    }
+    lines
 }
 
 fn render_updating_overlay(frame: &mut Frame, state: &AppState) {
@@ -1534,8 +3540,13 @@ fn render_updating_overlay(frame: &mut Frame, state: &AppState) {
     let overlay = Block::default().style(Style::default().bg(Color::Black));
     frame.render_widget(overlay, area);
 
-    // Centered modal
-    let popup_width = 40u16;
+    let msg = state
+        .update_status
+        .as_deref()
+        .unwrap_or("Updating... please wait");
+
+    // Centered modal, wide enough for the current step
+    let popup_width = u16::try_from(msg.chars().count() + 8).map_or(40, |w| w.max(40));
     let popup_height = 5u16;
 
     // Calculate centered position
@@ -1552,11 +3563,6 @@ fn render_updating_overlay(frame: &mut Frame, state: &AppState) {
     // Clear the popup area first to ensure clean rendering
     frame.render_widget(Clear, popup_area);
 
-    let msg = state
-        .update_status
-        .as_deref()
-        .unwrap_or("Updating... please wait");
-
     let text = vec![
         Line::from(""),
         Line::from(Span::styled(
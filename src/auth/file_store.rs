@@ -0,0 +1,324 @@
+//! Encrypted file credential store
+//!
+//! Stores credentials encrypted with AES-256-GCM in ~/.config/perch/credentials.enc
+//! The encryption key is derived from machine-specific identifiers.
+//!
+//! File layout: `PRCH`, a format version byte, an 8-byte fingerprint of the
+//! key, the nonce, then the ciphertext. Files written before the header was
+//! added are just nonce + ciphertext and are still read.
+
+use aes_gcm::{
+    Aes256Gcm, Nonce,
+    aead::{Aead, KeyInit},
+};
+use anyhow::{Context, Result};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::CredentialStore;
+
+const NONCE_SIZE: usize = 12;
+
+/// Marks files that carry a version and key fingerprint
+const MAGIC: &[u8; 4] = b"PRCH";
+/// Current file format version
+const FORMAT_VERSION: u8 = 1;
+/// Bytes of the key fingerprint stored in the header
+const KEY_CHECK_SIZE: usize = 8;
+const HEADER_SIZE: usize = MAGIC.len() + 1 + KEY_CHECK_SIZE;
+
+/// The credentials file exists but can't be decrypted with this machine's key
+/// (e.g. the machine ID changed, the home directory moved, or the file is corrupt)
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "credentials could not be decrypted on this machine; run `perch auth <network>` to log in again or `perch logout --all` to reset"
+)]
+pub struct UndecryptableCredentials;
+
+/// Credentials kept in a single encrypted file
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    /// Create a store backed by the file at `path`
+    pub const fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Whether the credentials file exists
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    /// Load every stored entry
+    pub fn entries(&self) -> Result<HashMap<String, String>> {
+        load_credentials(&self.path)
+    }
+
+    /// Remove the credentials file
+    pub fn remove(&self) -> Result<()> {
+        fs::remove_file(&self.path).context("Failed to remove credentials file")
+    }
+}
+
+impl CredentialStore for FileStore {
+    fn store(&self, key: &str, secret: &str) -> Result<()> {
+        let mut creds = load_credentials(&self.path).unwrap_or_default();
+        creds.insert(key.to_string(), secret.to_string());
+        save_credentials(&self.path, &creds)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(load_credentials(&self.path)?.get(key).cloned())
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        let mut creds = load_credentials(&self.path).unwrap_or_default();
+        if creds.remove(key).is_some() {
+            save_credentials(&self.path, &creds)?;
+        }
+        Ok(())
+    }
+
+    fn keys(&self) -> Vec<String> {
+        load_credentials(&self.path)
+            .map(|c| c.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Get machine ID for key derivation (cross-platform)
+fn get_machine_id() -> String {
+    // Try platform-specific machine IDs first
+
+    // Linux: /etc/machine-id or /var/lib/dbus/machine-id
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(id) = fs::read_to_string("/etc/machine-id") {
+            return id.trim().to_string();
+        }
+        if let Ok(id) = fs::read_to_string("/var/lib/dbus/machine-id") {
+            return id.trim().to_string();
+        }
+    }
+
+    // macOS: IOPlatformUUID via ioreg
+    #[cfg(target_os = "macos")]
+    {
+        if let Ok(output) = std::process::Command::new("ioreg")
+            .args(["-rd1", "-c", "IOPlatformExpertDevice"])
+            .output()
+        {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            for line in stdout.lines() {
+                if line.contains("IOPlatformUUID") {
+                    if let Some(uuid) = line.split('"').nth(3) {
+                        return uuid.to_string();
+                    }
+                }
+            }
+        }
+    }
+
+    // Windows: MachineGuid from registry
+    #[cfg(target_os = "windows")]
+    {
+        if let Ok(output) = std::process::Command::new("reg")
+            .args([
+                "query",
+                r"HKLM\SOFTWARE\Microsoft\Cryptography",
+                "/v",
+                "MachineGuid",
+            ])
+            .output()
+        {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            for line in stdout.lines() {
+                if line.contains("MachineGuid") {
+                    if let Some(guid) = line.split_whitespace().last() {
+                        return guid.to_string();
+                    }
+                }
+            }
+        }
+    }
+
+    // Fallback: use home directory path (always available via dirs crate)
+    dirs::home_dir().map_or_else(
+        || "perch-fallback-key".to_string(),
+        |p| p.to_string_lossy().to_string(),
+    )
+}
+
+/// Derive encryption key from machine-specific data
+fn derive_key() -> [u8; 32] {
+    let mut hasher = Sha256::new();
+
+    // Primary: machine-specific ID
+    hasher.update(get_machine_id().as_bytes());
+
+    // Secondary: home directory path (cross-platform via dirs crate)
+    if let Some(home) = dirs::home_dir() {
+        hasher.update(home.to_string_lossy().as_bytes());
+    }
+
+    // Tertiary: data directory path
+    if let Some(data) = dirs::data_dir() {
+        hasher.update(data.to_string_lossy().as_bytes());
+    }
+
+    // Fixed salt for this app
+    hasher.update(b"perch-social-client-v1");
+
+    hasher.finalize().into()
+}
+
+/// Short fingerprint of a key, so a key mismatch can be told apart from corruption
+fn key_check(key: &[u8; 32]) -> [u8; KEY_CHECK_SIZE] {
+    let digest = Sha256::new()
+        .chain_update(key)
+        .chain_update(b"perch-key-check")
+        .finalize();
+    let mut check = [0u8; KEY_CHECK_SIZE];
+    check.copy_from_slice(&digest[..KEY_CHECK_SIZE]);
+    check
+}
+
+/// Load all credentials from encrypted file
+fn load_credentials(path: &Path) -> Result<HashMap<String, String>> {
+    load_with_key(path, &derive_key())
+}
+
+fn load_with_key(path: &Path, key: &[u8; 32]) -> Result<HashMap<String, String>> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let encrypted = fs::read(path).context("Failed to read credentials file")?;
+
+    let body = match encrypted.strip_prefix(MAGIC) {
+        Some(rest) if rest.len() >= HEADER_SIZE - MAGIC.len() => {
+            let (version, rest) = (rest[0], &rest[1..]);
+            if version > FORMAT_VERSION {
+                anyhow::bail!(
+                    "Credentials file was written by a newer version of perch (format {version})"
+                );
+            }
+            let (check, body) = rest.split_at(KEY_CHECK_SIZE);
+            if check != key_check(key) {
+                tracing::warn!("Credentials file was encrypted with a different machine key");
+                return Err(UndecryptableCredentials.into());
+            }
+            body
+        }
+        _ => &encrypted[..],
+    };
+
+    if body.len() < NONCE_SIZE {
+        return Ok(HashMap::new());
+    }
+
+    let (nonce_bytes, ciphertext) = body.split_at(NONCE_SIZE);
+    let nonce = Nonce::from_slice(nonce_bytes);
+
+    let cipher = Aes256Gcm::new_from_slice(key).expect("Invalid key length");
+
+    let plaintext = cipher
+        .decrypt(nonce, ciphertext)
+        .map_err(|_| UndecryptableCredentials)?;
+
+    let json = String::from_utf8(plaintext).context("Invalid UTF-8 in credentials")?;
+    let creds: HashMap<String, String> = serde_json::from_str(&json)?;
+
+    Ok(creds)
+}
+
+/// Save all credentials to encrypted file
+fn save_credentials(path: &Path, creds: &HashMap<String, String>) -> Result<()> {
+    save_with_key(path, creds, &derive_key())
+}
+
+fn save_with_key(path: &Path, creds: &HashMap<String, String>, key: &[u8; 32]) -> Result<()> {
+    let json = serde_json::to_string(creds)?;
+
+    let cipher = Aes256Gcm::new_from_slice(key).expect("Invalid key length");
+
+    let mut rng = rand::rng();
+    let mut nonce_bytes = [0u8; NONCE_SIZE];
+    rng.fill(&mut nonce_bytes);
+    let nonce = Nonce::from_slice(&nonce_bytes);
+
+    let ciphertext = cipher
+        .encrypt(nonce, json.as_bytes())
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt credentials"))?;
+
+    let mut output = Vec::with_capacity(HEADER_SIZE + NONCE_SIZE + ciphertext.len());
+    output.extend_from_slice(MAGIC);
+    output.push(FORMAT_VERSION);
+    output.extend_from_slice(&key_check(key));
+    output.extend_from_slice(&nonce_bytes);
+    output.extend(ciphertext);
+
+    fs::write(path, output).context("Failed to write credentials file")?;
+
+    // Set restrictive permissions on Unix
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(path)?.permissions();
+        perms.set_mode(0o600);
+        fs::set_permissions(path, perms)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> HashMap<String, String> {
+        HashMap::from([("perch:mastodon:1".to_string(), "token".to_string())])
+    }
+
+    #[test]
+    fn test_roundtrip_with_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("credentials.enc");
+        let key = [7u8; 32];
+
+        save_with_key(&path, &sample(), &key).unwrap();
+        assert!(fs::read(&path).unwrap().starts_with(MAGIC));
+        assert_eq!(load_with_key(&path, &key).unwrap(), sample());
+    }
+
+    #[test]
+    fn test_wrong_key_gives_friendly_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("credentials.enc");
+        save_with_key(&path, &sample(), &[7u8; 32]).unwrap();
+
+        let err = load_with_key(&path, &[8u8; 32]).unwrap_err();
+        assert!(err.downcast_ref::<UndecryptableCredentials>().is_some());
+        assert!(err.to_string().contains("perch logout --all"));
+    }
+
+    #[test]
+    fn test_legacy_file_with_wrong_key_gives_friendly_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("credentials.enc");
+        save_with_key(&path, &sample(), &[7u8; 32]).unwrap();
+
+        // Strip the header to get the pre-header layout
+        let legacy = fs::read(&path).unwrap()[HEADER_SIZE..].to_vec();
+        fs::write(&path, legacy).unwrap();
+
+        assert_eq!(load_with_key(&path, &[7u8; 32]).unwrap(), sample());
+        let err = load_with_key(&path, &[8u8; 32]).unwrap_err();
+        assert!(err.downcast_ref::<UndecryptableCredentials>().is_some());
+    }
+}
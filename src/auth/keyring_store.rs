@@ -0,0 +1,56 @@
+//! System keyring credential store
+//!
+//! Uses the macOS Keychain, Windows Credential Manager, or the Secret Service
+//! (GNOME Keyring, `KWallet`) on Linux.
+
+use anyhow::{Context, Result};
+use keyring::Entry;
+
+use super::CredentialStore;
+
+/// Service name entries are stored under
+const SERVICE: &str = "perch";
+
+/// Credentials kept in the system keyring, one entry per key
+pub struct KeyringStore;
+
+impl KeyringStore {
+    /// Check that a keyring service is reachable
+    pub fn is_available() -> bool {
+        match Entry::new(SERVICE, "perch:probe").and_then(|e| e.get_password()) {
+            Ok(_) | Err(keyring::Error::NoEntry) => true,
+            Err(e) => {
+                tracing::debug!("System keyring unavailable: {e}");
+                false
+            }
+        }
+    }
+}
+
+impl CredentialStore for KeyringStore {
+    fn store(&self, key: &str, secret: &str) -> Result<()> {
+        Entry::new(SERVICE, key)
+            .and_then(|e| e.set_password(secret))
+            .context("Failed to store credentials in the system keyring")
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        match Entry::new(SERVICE, key).and_then(|e| e.get_password()) {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e).context("Failed to read credentials from the system keyring"),
+        }
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        match Entry::new(SERVICE, key).and_then(|e| e.delete_credential()) {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e).context("Failed to delete credentials from the system keyring"),
+        }
+    }
+
+    fn keys(&self) -> Vec<String> {
+        // Keyrings can't be enumerated by service
+        Vec::new()
+    }
+}
@@ -1,225 +1,317 @@
-//! Authentication module (encrypted file-based credential storage)
+//! Authentication module (credential storage)
 //!
-//! Stores credentials encrypted with AES-256-GCM in ~/.config/perch/credentials.enc
-//! The encryption key is derived from machine-specific identifiers.
-
-use aes_gcm::{
-    Aes256Gcm, Nonce,
-    aead::{Aead, KeyInit},
-};
-use anyhow::{Context, Result};
-use rand::Rng;
-use sha2::{Digest, Sha256};
+//! Credentials live in an encrypted file by default, or in the system keyring
+//! when `credential_backend = "keyring"` is set in the config.
+//!
+//! Each profile has its own credentials file. The keyring is shared, so with
+//! a profile its keys get the profile's name in front, keeping profiles from
+//! reading or removing each other's tokens and OAuth clients.
+
+mod file_store;
+mod keyring_store;
+mod transfer;
+
+pub use file_store::{FileStore, UndecryptableCredentials};
+pub use keyring_store::KeyringStore;
+pub use transfer::{AccountsExport, ImportedAccount};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
+use std::sync::OnceLock;
 
 use crate::models::Account;
 use crate::paths;
 
-const NONCE_SIZE: usize = 12;
+/// Where credentials are stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CredentialBackend {
+    /// AES-256-GCM encrypted file keyed to this machine
+    #[default]
+    File,
+    /// System keyring (Keychain, Credential Manager, Secret Service)
+    Keyring,
+}
+
+/// A place to keep secrets by key
+pub trait CredentialStore: Send + Sync {
+    /// Store a secret, replacing any existing value
+    fn store(&self, key: &str, secret: &str) -> Result<()>;
+
+    /// Get a secret, if one is stored
+    fn get(&self, key: &str) -> Result<Option<String>>;
 
-/// Get the credentials file path
-fn credentials_path() -> Result<PathBuf> {
-    paths::credentials_path()
+    /// Delete a secret (missing keys are not an error)
+    fn delete(&self, key: &str) -> Result<()>;
+
+    /// List stored keys, where the backend supports it
+    fn keys(&self) -> Vec<String>;
 }
 
-/// Get machine ID for key derivation (cross-platform)
-fn get_machine_id() -> String {
-    // Try platform-specific machine IDs first
+/// Keys of one profile, in a store other profiles share
+struct ProfileStore<S> {
+    prefix: String,
+    inner: S,
+}
 
-    // Linux: /etc/machine-id or /var/lib/dbus/machine-id
-    #[cfg(target_os = "linux")]
-    {
-        if let Ok(id) = fs::read_to_string("/etc/machine-id") {
-            return id.trim().to_string();
-        }
-        if let Ok(id) = fs::read_to_string("/var/lib/dbus/machine-id") {
-            return id.trim().to_string();
+impl<S: CredentialStore> ProfileStore<S> {
+    fn new(profile: &str, inner: S) -> Self {
+        Self {
+            prefix: format!("profile:{profile}:"),
+            inner,
         }
     }
 
-    // macOS: IOPlatformUUID via ioreg
-    #[cfg(target_os = "macos")]
-    {
-        if let Ok(output) = std::process::Command::new("ioreg")
-            .args(["-rd1", "-c", "IOPlatformExpertDevice"])
-            .output()
-        {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            for line in stdout.lines() {
-                if line.contains("IOPlatformUUID") {
-                    if let Some(uuid) = line.split('"').nth(3) {
-                        return uuid.to_string();
-                    }
-                }
-            }
-        }
+    fn key(&self, key: &str) -> String {
+        format!("{}{key}", self.prefix)
     }
+}
 
-    // Windows: MachineGuid from registry
-    #[cfg(target_os = "windows")]
-    {
-        if let Ok(output) = std::process::Command::new("reg")
-            .args([
-                "query",
-                r"HKLM\SOFTWARE\Microsoft\Cryptography",
-                "/v",
-                "MachineGuid",
-            ])
-            .output()
-        {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            for line in stdout.lines() {
-                if line.contains("MachineGuid") {
-                    if let Some(guid) = line.split_whitespace().last() {
-                        return guid.to_string();
-                    }
-                }
-            }
-        }
+impl<S: CredentialStore> CredentialStore for ProfileStore<S> {
+    fn store(&self, key: &str, secret: &str) -> Result<()> {
+        self.inner.store(&self.key(key), secret)
     }
 
-    // Fallback: use home directory path (always available via dirs crate)
-    dirs::home_dir().map_or_else(
-        || "perch-fallback-key".to_string(),
-        |p| p.to_string_lossy().to_string(),
-    )
-}
-
-/// Derive encryption key from machine-specific data
-fn derive_key() -> [u8; 32] {
-    let mut hasher = Sha256::new();
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        self.inner.get(&self.key(key))
+    }
 
-    // Primary: machine-specific ID
-    hasher.update(get_machine_id().as_bytes());
+    fn delete(&self, key: &str) -> Result<()> {
+        self.inner.delete(&self.key(key))
+    }
 
-    // Secondary: home directory path (cross-platform via dirs crate)
-    if let Some(home) = dirs::home_dir() {
-        hasher.update(home.to_string_lossy().as_bytes());
+    fn keys(&self) -> Vec<String> {
+        self.inner
+            .keys()
+            .into_iter()
+            .filter_map(|key| key.strip_prefix(&self.prefix).map(str::to_string))
+            .collect()
     }
+}
 
-    // Tertiary: data directory path
-    if let Some(data) = dirs::data_dir() {
-        hasher.update(data.to_string_lossy().as_bytes());
+/// The system keyring, scoped to the active profile if there is one
+fn keyring_store() -> Box<dyn CredentialStore> {
+    match paths::profile() {
+        Some(profile) => Box::new(ProfileStore::new(profile, KeyringStore)),
+        None => Box::new(KeyringStore),
     }
+}
 
-    // Fixed salt for this app
-    hasher.update(b"perch-social-client-v1");
+/// Active credential store (set once at startup)
+static STORE: OnceLock<Box<dyn CredentialStore>> = OnceLock::new();
 
-    hasher.finalize().into()
+fn file_store() -> Result<FileStore> {
+    Ok(FileStore::new(paths::credentials_path()?))
 }
 
-/// Load all credentials from encrypted file
-fn load_credentials() -> Result<HashMap<String, String>> {
-    let path = credentials_path()?;
+/// Select the credential backend.
+///
+/// This should be called once at startup, before any credentials are read.
+/// Choosing the keyring moves existing file credentials into it; if no
+/// keyring service is available the encrypted file is used instead.
+pub fn init(backend: CredentialBackend) {
+    STORE.get_or_init(|| match backend {
+        CredentialBackend::Keyring if KeyringStore::is_available() => {
+            let keyring = keyring_store();
+            if let Err(e) = migrate_file_credentials(keyring.as_ref()) {
+                tracing::warn!("Failed to move credentials into the keyring: {e}");
+            }
+            keyring
+        }
+        CredentialBackend::Keyring => {
+            tracing::warn!("No system keyring available, using the encrypted credentials file");
+            default_store()
+        }
+        CredentialBackend::File => default_store(),
+    });
+}
 
-    if !path.exists() {
-        return Ok(HashMap::new());
+fn default_store() -> Box<dyn CredentialStore> {
+    match file_store() {
+        Ok(store) => Box::new(store),
+        Err(e) => {
+            tracing::warn!("Credentials file unavailable: {e}");
+            keyring_store()
+        }
     }
+}
 
-    let encrypted = fs::read(&path).context("Failed to read credentials file")?;
+/// Get the active store (the encrypted file unless `init` chose otherwise)
+fn store() -> &'static dyn CredentialStore {
+    STORE.get_or_init(default_store).as_ref()
+}
 
-    if encrypted.len() < NONCE_SIZE {
-        return Ok(HashMap::new());
+/// Move credentials from the encrypted file into another store, removing the
+/// file once every entry has been copied
+fn migrate_file_credentials(to: &dyn CredentialStore) -> Result<()> {
+    let file = file_store()?;
+    if !file.exists() {
+        return Ok(());
     }
 
-    let (nonce_bytes, ciphertext) = encrypted.split_at(NONCE_SIZE);
-    let nonce = Nonce::from_slice(nonce_bytes);
+    let count = copy_entries(file.entries()?, to)?;
+    file.remove()?;
+    tracing::info!("Moved {count} credential(s) into the system keyring");
+    Ok(())
+}
 
-    let key = derive_key();
-    let cipher = Aes256Gcm::new_from_slice(&key).expect("Invalid key length");
+/// Copy entries into a store, stopping at the first failure
+fn copy_entries(entries: HashMap<String, String>, to: &dyn CredentialStore) -> Result<usize> {
+    let count = entries.len();
+    for (key, secret) in entries {
+        to.store(&key, &secret)?;
+    }
+    Ok(count)
+}
 
-    let plaintext = cipher
-        .decrypt(nonce, ciphertext)
-        .map_err(|_| anyhow::anyhow!("Failed to decrypt credentials"))?;
+/// Remove the credentials file, including entries (like OAuth clients) that
+/// belong to no account and files that can no longer be decrypted
+pub fn clear_credentials() -> Result<()> {
+    let file = file_store()?;
+    if file.exists() {
+        file.remove()?;
+    }
+    Ok(())
+}
 
-    let json = String::from_utf8(plaintext).context("Invalid UTF-8 in credentials")?;
-    let creds: HashMap<String, String> = serde_json::from_str(&json)?;
+/// Store credentials for an account
+pub fn store_credentials(account: &Account, secret: &str) -> Result<()> {
+    store().store(&account.keyring_key(), secret)
+}
 
-    Ok(creds)
+/// Get credentials for an account
+pub fn get_credentials(account: &Account) -> Result<Option<String>> {
+    store().get(&account.keyring_key())
 }
 
-/// Save all credentials to encrypted file
-fn save_credentials(creds: &HashMap<String, String>) -> Result<()> {
-    let path = credentials_path()?;
+/// Delete credentials for an account
+pub fn delete_credentials(account: &Account) -> Result<()> {
+    delete_account_entries(store(), account)
+}
 
-    let json = serde_json::to_string(creds)?;
+fn delete_account_entries(store: &dyn CredentialStore, account: &Account) -> Result<()> {
+    store.delete(&account.keyring_key())?;
+    store.delete(&session_key(account))
+}
 
-    let key = derive_key();
-    let cipher = Aes256Gcm::new_from_slice(&key).expect("Invalid key length");
+/// Which credentials were removed along with an account
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RemovedCredentials {
+    /// Access token or app password
+    pub token: bool,
+    /// Cached session tokens (Bluesky)
+    pub session: bool,
+    /// OAuth client registration for the account's Mastodon instance
+    pub oauth_client: bool,
+}
 
-    let mut rng = rand::rng();
-    let mut nonce_bytes = [0u8; NONCE_SIZE];
-    rng.fill(&mut nonce_bytes);
-    let nonce = Nonce::from_slice(&nonce_bytes);
+impl RemovedCredentials {
+    /// Human-readable list, e.g. "token, OAuth client"
+    pub fn summary(&self) -> String {
+        let parts: Vec<&str> = [
+            (self.token, "token"),
+            (self.session, "session"),
+            (self.oauth_client, "OAuth client"),
+        ]
+        .into_iter()
+        .filter_map(|(removed, name)| removed.then_some(name))
+        .collect();
+
+        if parts.is_empty() {
+            "no stored credentials".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
 
-    let ciphertext = cipher
-        .encrypt(nonce, json.as_bytes())
-        .map_err(|_| anyhow::anyhow!("Failed to encrypt credentials"))?;
+/// Delete everything stored for an account that is being removed.
+///
+/// The Mastodon OAuth client is shared per instance, so it is only removed
+/// when none of the `remaining` accounts use the same instance.
+pub fn remove_account_credentials(
+    account: &Account,
+    remaining: &[Account],
+) -> Result<RemovedCredentials> {
+    remove_account_entries(store(), account, remaining)
+}
 
-    let mut output = nonce_bytes.to_vec();
-    output.extend(ciphertext);
+fn remove_account_entries(
+    store: &dyn CredentialStore,
+    account: &Account,
+    remaining: &[Account],
+) -> Result<RemovedCredentials> {
+    let mut removed = RemovedCredentials::default();
+
+    let token_key = account.keyring_key();
+    if store.get(&token_key).unwrap_or_default().is_some() {
+        store.delete(&token_key)?;
+        removed.token = true;
+    }
 
-    fs::write(&path, output).context("Failed to write credentials file")?;
+    let session = session_key(account);
+    if store.get(&session).unwrap_or_default().is_some() {
+        store.delete(&session)?;
+        removed.session = true;
+    }
 
-    // Set restrictive permissions on Unix
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(&path)?.permissions();
-        perms.set_mode(0o600);
-        fs::set_permissions(&path, perms)?;
+    let instance_in_use = remaining
+        .iter()
+        .any(|a| a.id != account.id && a.network == account.network && a.server == account.server);
+    if account.network.capabilities().oauth && !instance_in_use {
+        for client_key in [
+            oauth_client_key(&account.server),
+            legacy_oauth_client_key(&account.server),
+        ] {
+            if store.get(&client_key).unwrap_or_default().is_some() {
+                store.delete(&client_key)?;
+                removed.oauth_client = true;
+            }
+        }
     }
 
-    Ok(())
+    Ok(removed)
 }
 
-/// Store credentials for an account
-pub fn store_credentials(account: &Account, secret: &str) -> Result<()> {
-    let mut creds = load_credentials().unwrap_or_default();
-    creds.insert(account.keyring_key(), secret.to_string());
-    save_credentials(&creds)
+fn session_key(account: &Account) -> String {
+    format!("session:{}", account.keyring_key())
 }
 
-/// Get credentials for an account
-pub fn get_credentials(account: &Account) -> Result<Option<String>> {
-    let creds = load_credentials()?;
-    Ok(creds.get(&account.keyring_key()).cloned())
+/// Store serialized session tokens for an account (Bluesky)
+pub fn store_session(account: &Account, session: &str) -> Result<()> {
+    store().store(&session_key(account), session)
 }
 
-/// Delete credentials for an account
-pub fn delete_credentials(account: &Account) -> Result<()> {
-    let mut creds = load_credentials().unwrap_or_default();
-    creds.remove(&account.keyring_key());
-    save_credentials(&creds)
+/// Get serialized session tokens for an account
+pub fn get_session(account: &Account) -> Result<Option<String>> {
+    store().get(&session_key(account))
+}
+
+fn oauth_client_key(instance: &str) -> String {
+    format!("oauth:{instance}:app")
+}
+
+/// Where registrations were kept before they allowed the localhost redirect.
+/// They only work with a pasted code, so they're left for a new registration
+/// to replace and just cleaned up.
+fn legacy_oauth_client_key(instance: &str) -> String {
+    format!("oauth:{instance}:client")
 }
 
 /// Store OAuth client credentials (for Mastodon instances)
 pub fn store_oauth_client(instance: &str, client_id: &str, client_secret: &str) -> Result<()> {
-    let mut creds = load_credentials().unwrap_or_default();
-    let key = format!("oauth:{instance}:client");
     let value = format!("{client_id}:{client_secret}");
-    creds.insert(key, value);
-    save_credentials(&creds)
+    store().store(&oauth_client_key(instance), &value)
 }
 
 /// Get OAuth client credentials for a Mastodon instance
 pub fn get_oauth_client(instance: &str) -> Result<Option<(String, String)>> {
-    let creds = load_credentials()?;
-    let key = format!("oauth:{instance}:client");
-
-    match creds.get(&key) {
-        Some(value) => {
-            let parts: Vec<&str> = value.splitn(2, ':').collect();
-            if parts.len() == 2 {
-                Ok(Some((parts[0].to_string(), parts[1].to_string())))
-            } else {
-                Ok(None)
-            }
-        }
-        None => Ok(None),
-    }
+    Ok(store().get(&oauth_client_key(instance))?.and_then(|value| {
+        value
+            .split_once(':')
+            .map(|(id, secret)| (id.to_string(), secret.to_string()))
+    }))
 }
 
 /// Check if credentials exist for an account
@@ -229,9 +321,170 @@ pub fn has_credentials(account: &Account) -> bool {
         .unwrap_or(false)
 }
 
-/// Get all stored credential keys (for debugging)
+/// Get all stored credential keys (for debugging; empty for the keyring)
 pub fn list_credential_keys() -> Vec<String> {
-    load_credentials()
-        .map(|c| c.keys().cloned().collect())
-        .unwrap_or_default()
+    store().keys()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// In-memory store that can be told to fail writes
+    #[derive(Default)]
+    pub(super) struct MockStore {
+        entries: Mutex<HashMap<String, String>>,
+        fail_writes: bool,
+    }
+
+    impl CredentialStore for MockStore {
+        fn store(&self, key: &str, secret: &str) -> Result<()> {
+            if self.fail_writes {
+                anyhow::bail!("store unavailable");
+            }
+            self.entries
+                .lock()
+                .unwrap()
+                .insert(key.to_string(), secret.to_string());
+            Ok(())
+        }
+
+        fn get(&self, key: &str) -> Result<Option<String>> {
+            Ok(self.entries.lock().unwrap().get(key).cloned())
+        }
+
+        fn delete(&self, key: &str) -> Result<()> {
+            self.entries.lock().unwrap().remove(key);
+            Ok(())
+        }
+
+        fn keys(&self) -> Vec<String> {
+            self.entries.lock().unwrap().keys().cloned().collect()
+        }
+    }
+
+    /// So profiles can share one store
+    impl CredentialStore for &MockStore {
+        fn store(&self, key: &str, secret: &str) -> Result<()> {
+            (*self).store(key, secret)
+        }
+
+        fn get(&self, key: &str) -> Result<Option<String>> {
+            (*self).get(key)
+        }
+
+        fn delete(&self, key: &str) -> Result<()> {
+            (*self).delete(key)
+        }
+
+        fn keys(&self) -> Vec<String> {
+            (*self).keys()
+        }
+    }
+
+    #[test]
+    fn test_copy_entries_moves_everything() {
+        let store = MockStore::default();
+        let entries = HashMap::from([
+            ("mastodon:a".to_string(), "token".to_string()),
+            ("oauth:x:client".to_string(), "id:secret".to_string()),
+        ]);
+
+        assert_eq!(copy_entries(entries, &store).unwrap(), 2);
+        assert_eq!(store.get("mastodon:a").unwrap().as_deref(), Some("token"));
+        assert_eq!(store.keys().len(), 2);
+    }
+
+    #[test]
+    fn test_copy_entries_reports_failure() {
+        let store = MockStore {
+            fail_writes: true,
+            ..MockStore::default()
+        };
+        let entries = HashMap::from([("k".to_string(), "v".to_string())]);
+        assert!(copy_entries(entries, &store).is_err());
+    }
+
+    #[test]
+    fn test_delete_account_entries_removes_session() {
+        let store = MockStore::default();
+        let account = Account::new_bluesky("alice.bsky.social", "Alice");
+        store.store(&account.keyring_key(), "password").unwrap();
+        store.store(&session_key(&account), "{}").unwrap();
+        store.store("other", "kept").unwrap();
+
+        delete_account_entries(&store, &account).unwrap();
+        assert_eq!(store.keys(), vec!["other".to_string()]);
+    }
+
+    #[test]
+    fn test_remove_account_keeps_shared_oauth_client() {
+        let store = MockStore::default();
+        let alice = Account::new_mastodon("alice", "https://mastodon.social", "Alice");
+        let bob = Account::new_mastodon("bob", "https://mastodon.social", "Bob");
+        let client_key = oauth_client_key("https://mastodon.social");
+        store.store(&alice.keyring_key(), "a").unwrap();
+        store.store(&bob.keyring_key(), "b").unwrap();
+        store.store(&client_key, "id:secret").unwrap();
+
+        // Bob still uses the instance, so the client stays
+        let removed = remove_account_entries(&store, &alice, std::slice::from_ref(&bob)).unwrap();
+        assert_eq!(removed.summary(), "token");
+        assert!(store.get(&client_key).unwrap().is_some());
+
+        // Last account on the instance takes the client with it, along with
+        // an older registration
+        store
+            .store(
+                &legacy_oauth_client_key("https://mastodon.social"),
+                "old:secret",
+            )
+            .unwrap();
+        let removed = remove_account_entries(&store, &bob, &[]).unwrap();
+        assert_eq!(removed.summary(), "token, OAuth client");
+        assert!(store.keys().is_empty());
+    }
+
+    #[test]
+    fn test_profiles_keep_their_own_oauth_client() {
+        let keyring = MockStore::default();
+        let personal = ProfileStore::new("personal", &keyring);
+        let work = ProfileStore::new("work", &keyring);
+        let instance = "https://mastodon.social";
+        let alice = Account::new_mastodon("alice", instance, "Alice");
+        let bob = Account::new_mastodon("bob", instance, "Bob");
+        personal.store(&alice.keyring_key(), "a").unwrap();
+        personal
+            .store(&oauth_client_key(instance), "id1:secret1")
+            .unwrap();
+        work.store(&bob.keyring_key(), "b").unwrap();
+        work.store(&oauth_client_key(instance), "id2:secret2")
+            .unwrap();
+        assert_eq!(work.keys().len(), 2);
+
+        // Alice was the last account on the instance in her profile
+        let removed = remove_account_entries(&personal, &alice, &[]).unwrap();
+        assert_eq!(removed.summary(), "token, OAuth client");
+        assert!(personal.keys().is_empty());
+        assert_eq!(
+            work.get(&oauth_client_key(instance)).unwrap().as_deref(),
+            Some("id2:secret2")
+        );
+        assert_eq!(work.get(&bob.keyring_key()).unwrap().as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn test_file_store_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::new(dir.path().join("credentials.enc"));
+
+        store.store("a", "1").unwrap();
+        store.store("b", "2").unwrap();
+        assert_eq!(store.get("a").unwrap().as_deref(), Some("1"));
+
+        store.delete("a").unwrap();
+        assert_eq!(store.get("a").unwrap(), None);
+        assert_eq!(store.keys(), vec!["b".to_string()]);
+    }
 }
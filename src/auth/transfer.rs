@@ -0,0 +1,389 @@
+//! Moving accounts to another machine
+//!
+//! `perch accounts export` writes account details as JSON. Credentials are
+//! left out unless asked for, and then sealed with AES-256-GCM under a key
+//! derived from a passphrase (PBKDF2-HMAC-SHA256), since the credentials
+//! file's own key only works on the machine that wrote it.
+
+use std::collections::HashMap;
+
+use aes_gcm::{
+    Aes256Gcm, Nonce,
+    aead::{Aead, KeyInit},
+};
+use anyhow::{Context, Result, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use super::{CredentialStore, oauth_client_key, session_key};
+use crate::models::Account;
+
+/// Current export format version
+const FORMAT_VERSION: u32 = 1;
+
+/// PBKDF2 rounds for new exports (OWASP's recommendation for HMAC-SHA256)
+const KDF_ITERATIONS: u32 = 600_000;
+
+/// PBKDF2 rounds an import accepts. Fewer would make the passphrase easy
+/// to guess; many more would only be there to keep the import busy.
+const KDF_ITERATIONS_RANGE: std::ops::RangeInclusive<u32> = 100_000..=5_000_000;
+
+const SALT_SIZE: usize = 16;
+const NONCE_SIZE: usize = 12;
+
+/// An accounts export file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountsExport {
+    version: u32,
+    accounts: Vec<Account>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    credentials: Option<SealedCredentials>,
+}
+
+/// Credentials encrypted under a passphrase
+#[derive(Debug, Serialize, Deserialize)]
+struct SealedCredentials {
+    iterations: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// The secrets of one account, as sealed in an export
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AccountSecrets {
+    token: Option<String>,
+    session: Option<String>,
+    oauth_client: Option<String>,
+}
+
+/// An account read from an export, with its credentials if they were included
+#[derive(Debug)]
+pub struct ImportedAccount {
+    /// The account
+    pub account: Account,
+    /// Access token or app password
+    pub token: Option<String>,
+    /// Cached session tokens (Bluesky)
+    pub session: Option<String>,
+    /// OAuth client registration for the account's Mastodon instance
+    pub oauth_client: Option<(String, String)>,
+}
+
+impl AccountsExport {
+    /// Export `accounts`, with their credentials sealed under `passphrase`
+    /// when one is given
+    pub fn new(accounts: Vec<Account>, passphrase: Option<&str>) -> Result<Self> {
+        Self::from_store(super::store(), accounts, passphrase, KDF_ITERATIONS)
+    }
+
+    fn from_store(
+        store: &dyn CredentialStore,
+        accounts: Vec<Account>,
+        passphrase: Option<&str>,
+        iterations: u32,
+    ) -> Result<Self> {
+        let credentials = passphrase
+            .map(|passphrase| {
+                let mut secrets = HashMap::new();
+                for account in &accounts {
+                    let oauth_client = if account.network.capabilities().oauth {
+                        store.get(&oauth_client_key(&account.server))?
+                    } else {
+                        None
+                    };
+                    let entry = AccountSecrets {
+                        token: store.get(&account.keyring_key())?,
+                        session: store.get(&session_key(account))?,
+                        oauth_client,
+                    };
+                    secrets.insert(account.id, entry);
+                }
+                seal(&serde_json::to_vec(&secrets)?, passphrase, iterations)
+            })
+            .transpose()?;
+
+        Ok(Self {
+            version: FORMAT_VERSION,
+            accounts,
+            credentials,
+        })
+    }
+
+    /// Parse an export file, checking that every account in it is usable
+    pub fn parse(json: &str) -> Result<Self> {
+        let export: Self = serde_json::from_str(json).context("Not a Perch accounts export")?;
+        if export.version > FORMAT_VERSION {
+            bail!(
+                "This export was written by a newer version of perch (format {})",
+                export.version
+            );
+        }
+        for account in &export.accounts {
+            if account.handle.trim().is_empty() {
+                bail!("An account in the export has no handle");
+            }
+            if !account.server.starts_with("https://") && !account.server.starts_with("http://") {
+                bail!(
+                    "@{} has an invalid server: {}",
+                    account.handle,
+                    account.server
+                );
+            }
+        }
+        Ok(export)
+    }
+
+    /// Write the export as JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Number of accounts in the export
+    pub const fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Whether the export has no accounts
+    pub const fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Whether credentials were included (and a passphrase is needed)
+    pub const fn has_credentials(&self) -> bool {
+        self.credentials.is_some()
+    }
+
+    /// The accounts to add next to `existing` ones, with their credentials
+    /// unsealed with `passphrase`.
+    ///
+    /// Accounts that are already set up, or appear twice, are left out. An
+    /// account whose ID is taken gets a new one, and none of them become the
+    /// default when there already are accounts.
+    pub fn accounts_to_import(
+        self,
+        existing: &[Account],
+        passphrase: Option<&str>,
+    ) -> Result<Vec<ImportedAccount>> {
+        let mut secrets: HashMap<Uuid, AccountSecrets> = match (&self.credentials, passphrase) {
+            (Some(sealed), Some(passphrase)) => {
+                serde_json::from_slice(&unseal(sealed, passphrase)?)
+                    .context("The export's credentials are malformed")?
+            }
+            _ => HashMap::new(),
+        };
+
+        let mut imported: Vec<ImportedAccount> = Vec::new();
+        for mut account in self.accounts {
+            let duplicate = existing
+                .iter()
+                .chain(imported.iter().map(|i| &i.account))
+                .any(|other| same_account(other, &account));
+            if duplicate {
+                continue;
+            }
+
+            let entry = secrets.remove(&account.id).unwrap_or_default();
+            let taken = existing
+                .iter()
+                .chain(imported.iter().map(|i| &i.account))
+                .any(|other| other.id == account.id);
+            if taken {
+                account.id = Uuid::new_v4();
+            }
+            if !existing.is_empty() {
+                account.is_default = false;
+            }
+
+            imported.push(ImportedAccount {
+                account,
+                token: entry.token,
+                session: entry.session,
+                oauth_client: entry.oauth_client.and_then(|value| {
+                    value
+                        .split_once(':')
+                        .map(|(id, secret)| (id.to_string(), secret.to_string()))
+                }),
+            });
+        }
+        Ok(imported)
+    }
+}
+
+/// Whether two accounts are the same login: one network, server and handle
+fn same_account(a: &Account, b: &Account) -> bool {
+    let handle = |account: &Account| account.handle.trim_start_matches('@').to_lowercase();
+    a.network == b.network
+        && a.server_host().eq_ignore_ascii_case(b.server_host())
+        && handle(a) == handle(b)
+}
+
+/// Derive an encryption key from a passphrase
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> [u8; 32] {
+    pbkdf2::pbkdf2_hmac_array::<Sha256, 32>(passphrase.as_bytes(), salt, iterations)
+}
+
+fn seal(plaintext: &[u8], passphrase: &str, iterations: u32) -> Result<SealedCredentials> {
+    let mut rng = rand::rng();
+    let mut salt = [0u8; SALT_SIZE];
+    let mut nonce = [0u8; NONCE_SIZE];
+    rng.fill(&mut salt);
+    rng.fill(&mut nonce);
+
+    let key = derive_key(passphrase, &salt, iterations);
+    let cipher = Aes256Gcm::new_from_slice(&key).expect("Invalid key length");
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt credentials"))?;
+
+    Ok(SealedCredentials {
+        iterations,
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+}
+
+fn unseal(sealed: &SealedCredentials, passphrase: &str) -> Result<Vec<u8>> {
+    let decode = |field: &str| {
+        BASE64
+            .decode(field)
+            .context("The export's credentials are malformed")
+    };
+    let salt = decode(&sealed.salt)?;
+    let nonce = decode(&sealed.nonce)?;
+    if nonce.len() != NONCE_SIZE {
+        bail!("The export's credentials are malformed");
+    }
+    if !KDF_ITERATIONS_RANGE.contains(&sealed.iterations) {
+        bail!(
+            "The export's credentials use {} key derivation rounds; Perch accepts {} to {}",
+            sealed.iterations,
+            KDF_ITERATIONS_RANGE.start(),
+            KDF_ITERATIONS_RANGE.end()
+        );
+    }
+
+    let key = derive_key(passphrase, &salt, sealed.iterations);
+    let cipher = Aes256Gcm::new_from_slice(&key).expect("Invalid key length");
+    cipher
+        .decrypt(
+            Nonce::from_slice(&nonce),
+            decode(&sealed.ciphertext)?.as_slice(),
+        )
+        .map_err(|_| anyhow::anyhow!("Wrong passphrase, or the export was changed"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockStore;
+    use super::*;
+
+    #[test]
+    fn test_derive_key_matches_pbkdf2() {
+        // RFC 7914 section 11, PBKDF2-HMAC-SHA256 with P="passwd", S="salt", c=1
+        let key = derive_key("passwd", b"salt", 1);
+        assert_eq!(key[..8], [0x55, 0xac, 0x04, 0x6e, 0x56, 0xe3, 0x08, 0x9f]);
+
+        // And with P="Password", S="NaCl", c=80000
+        let key = derive_key("Password", b"NaCl", 80_000);
+        assert_eq!(key[..8], [0x4d, 0xdc, 0xd8, 0xf6, 0x0b, 0x98, 0xbe, 0x21]);
+    }
+
+    #[test]
+    fn test_unseal_rejects_odd_iteration_counts() {
+        let sealed = seal(b"{}", "hunter2", *KDF_ITERATIONS_RANGE.start()).unwrap();
+        assert!(unseal(&sealed, "hunter2").is_ok());
+
+        for iterations in [1, u32::MAX] {
+            let crafted = SealedCredentials {
+                iterations,
+                ..seal(b"{}", "hunter2", 1).unwrap()
+            };
+            assert!(unseal(&crafted, "hunter2").is_err(), "{iterations}");
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_with_credentials() {
+        let store = MockStore::default();
+        let alice = Account::new_mastodon("alice", "https://mastodon.social", "Alice");
+        store.store(&alice.keyring_key(), "token").unwrap();
+        store
+            .store(&oauth_client_key(&alice.server), "id:secret")
+            .unwrap();
+
+        let export = AccountsExport::from_store(
+            &store,
+            vec![alice.clone()],
+            Some("hunter2"),
+            *KDF_ITERATIONS_RANGE.start(),
+        )
+        .unwrap();
+        let json = export.to_json().unwrap();
+        assert!(!json.contains("token\""));
+        assert!(!json.contains("secret"));
+
+        let wrong = AccountsExport::parse(&json).unwrap();
+        assert!(wrong.accounts_to_import(&[], Some("nope")).is_err());
+
+        let imported = AccountsExport::parse(&json)
+            .unwrap()
+            .accounts_to_import(&[], Some("hunter2"))
+            .unwrap();
+        assert_eq!(imported[0].account.id, alice.id);
+        assert_eq!(imported[0].token.as_deref(), Some("token"));
+        assert_eq!(
+            imported[0].oauth_client,
+            Some(("id".to_string(), "secret".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_import_skips_duplicates() {
+        let mut alice = Account::new_mastodon("alice", "https://mastodon.social", "Alice");
+        alice.is_default = true;
+        let mut again = Account::new_mastodon("@Alice", "https://Mastodon.social/", "Alice");
+        again.id = alice.id;
+        let bob = Account::new_bluesky("bob.bsky.social", "Bob");
+        let mut existing = bob.clone();
+        existing.handle = "carol.bsky.social".to_string();
+
+        let json =
+            AccountsExport::from_store(&MockStore::default(), vec![alice, again, bob], None, 1)
+                .unwrap()
+                .to_json()
+                .unwrap();
+        let imported = AccountsExport::parse(&json)
+            .unwrap()
+            .accounts_to_import(std::slice::from_ref(&existing), None)
+            .unwrap();
+
+        assert_eq!(imported.len(), 2);
+        assert_eq!(imported[0].account.handle, "alice");
+        assert!(!imported[0].account.is_default);
+        // Bob's ID is already Carol's
+        assert_ne!(imported[1].account.id, existing.id);
+        assert!(imported[1].token.is_none());
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_exports() {
+        assert!(AccountsExport::parse("{}").is_err());
+        assert!(AccountsExport::parse(r#"{"version": 99, "accounts": []}"#).is_err());
+
+        let mut account = Account::new_bluesky("bob.bsky.social", "Bob");
+        account.server = "bsky.social".to_string();
+        let json = serde_json::to_string(&AccountsExport {
+            version: FORMAT_VERSION,
+            accounts: vec![account],
+            credentials: None,
+        })
+        .unwrap();
+        assert!(AccountsExport::parse(&json).is_err());
+    }
+}
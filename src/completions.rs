@@ -0,0 +1,92 @@
+//! Shell completion scripts for the `perch` command line
+//!
+//! The scripts are written by hand to match the hand-rolled argument parsing
+//! in `main.rs`, so they need updating along with it.
+
+use anyhow::{Result, bail};
+
+/// A shell Perch has completions for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    /// Bash, with or without the bash-completion package
+    Bash,
+    /// Zsh
+    Zsh,
+    /// Fish
+    Fish,
+    /// `PowerShell` (Windows `PowerShell` and `pwsh`)
+    PowerShell,
+}
+
+impl Shell {
+    /// Every supported shell
+    pub const ALL: &[Self] = &[Self::Bash, Self::Zsh, Self::Fish, Self::PowerShell];
+
+    /// Parse a shell name (`bash`, `zsh`, `fish` or `powershell`)
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "bash" => Ok(Self::Bash),
+            "zsh" => Ok(Self::Zsh),
+            "fish" => Ok(Self::Fish),
+            "powershell" | "pwsh" => Ok(Self::PowerShell),
+            other => bail!("Unknown shell: {other}\nSupported: bash, zsh, fish, powershell"),
+        }
+    }
+
+    /// The completion script to print
+    pub const fn script(self) -> &'static str {
+        match self {
+            Self::Bash => include_str!("../completions/perch.bash"),
+            Self::Zsh => include_str!("../completions/perch.zsh"),
+            Self::Fish => include_str!("../completions/perch.fish"),
+            Self::PowerShell => include_str!("../completions/perch.ps1"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripts_cover_every_command() {
+        let words = [
+            "auth",
+            "post",
+            "schedule",
+            "timeline",
+            "accounts",
+            "export",
+            "config",
+            "follow",
+            "mute",
+            "block",
+            "post_limit",
+            "logout",
+            "completions",
+            // Fish spells flags without dashes
+            "server-side",
+            "thread",
+            "repeat",
+            "daemon",
+            "with-credentials",
+            "oob",
+            "once",
+            "snapshot",
+            "config-dir",
+            "profile",
+            "mastodon",
+            "bluesky",
+        ];
+        for shell in Shell::ALL {
+            for word in words {
+                assert!(
+                    shell.script().contains(word),
+                    "{shell:?} completions miss {word}"
+                );
+            }
+        }
+        assert_eq!(Shell::parse("PWSH").unwrap(), Shell::PowerShell);
+        assert!(Shell::parse("tcsh").is_err());
+    }
+}
@@ -1,11 +1,17 @@
 //! Configuration module for Perch
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow, bail};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+use crate::api::NetworkOptions;
+use crate::auth::CredentialBackend;
+use crate::filters::FilterConfig;
+use crate::models::{Account, Network};
 use crate::paths;
-use crate::theme::Theme;
+use crate::schedule::Timezone;
+use crate::theme::{AccountColor, Theme};
 
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +24,11 @@ pub struct Config {
     #[serde(default = "default_timeline")]
     pub default_timeline: String,
 
+    /// Whether to draw in color: "auto" (unless `NO_COLOR` is set),
+    /// "always" or "never"
+    #[serde(default)]
+    pub color: ColorMode,
+
     /// Enable vim-like keybindings
     #[serde(default = "default_vim_mode")]
     pub vim_mode: bool,
@@ -26,6 +37,11 @@ pub struct Config {
     #[serde(default = "default_refresh_interval")]
     pub refresh_interval_secs: u64,
 
+    /// Stream new posts into the home timeline as they arrive (off by
+    /// default, since not every instance allows streaming connections)
+    #[serde(default)]
+    pub streaming: bool,
+
     /// Number of posts to fetch per request
     #[serde(default = "default_post_limit")]
     pub post_limit: usize,
@@ -34,9 +50,240 @@ pub struct Config {
     #[serde(default = "default_show_media")]
     pub show_media: bool,
 
-    /// Default networks to post to (for cross-posting)
+    /// Whether to show author avatars (needs a terminal with native graphics)
+    #[serde(default = "default_show_avatars")]
+    pub show_avatars: bool,
+
+    /// How the timeline view is laid out: "split" (list and post detail side
+    /// by side) or "single" (one at a time, Enter opens the detail). Narrow
+    /// terminals always get "single"; `v` switches while running.
+    #[serde(default)]
+    pub layout: TimelineLayout,
+
+    /// Share of the width the timeline list takes in the split layout, in
+    /// percent
+    #[serde(default = "default_timeline_width")]
+    pub timeline_width: u16,
+
+    /// Whether the mouse can select posts and open links (turn off to keep
+    /// the terminal's own text selection)
+    #[serde(default = "default_mouse")]
+    pub mouse: bool,
+
+    /// Maximum size of the on-disk image cache in megabytes (0 = disabled)
+    #[serde(default = "default_image_cache_max_mb")]
+    pub image_cache_max_mb: u64,
+
+    /// Most posts kept in the offline cache for each network; older ones
+    /// are dropped on exit (0 = no limit)
+    #[serde(default = "default_cache_max_posts")]
+    pub cache_max_posts: usize,
+
+    /// Default networks to post to (for cross-posting); when empty, every
+    /// network with an account
     #[serde(default)]
     pub default_post_networks: Vec<String>,
+
+    /// Where credentials are stored ("file" or "keyring")
+    #[serde(default)]
+    pub credential_backend: CredentialBackend,
+
+    /// Timezone for entering and showing schedule times, and for exact post
+    /// times ("local", "UTC", an offset like "+02:00" or a zone like
+    /// "Europe/Berlin")
+    #[serde(default)]
+    pub timezone: Timezone,
+
+    /// Keys for normal-mode actions, replacing the defaults of the actions
+    /// listed (e.g. `quit = "x"` or `next_post = ["j", "down"]`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub keybindings: BTreeMap<String, KeyList>,
+
+    /// Show when posts were made as exact times instead of "5m" ago (`T`
+    /// switches while running)
+    #[serde(default)]
+    pub absolute_times: bool,
+
+    /// Play animated GIFs in the post detail (needs a terminal with native
+    /// graphics; turn off to save memory and CPU)
+    #[serde(default = "default_animate_images")]
+    pub animate_images: bool,
+
+    /// Whether to mark each post with a color for the account it was loaded
+    /// through, when there's more than one account
+    #[serde(default = "default_account_colors")]
+    pub account_colors: bool,
+
+    /// Colors for particular accounts' markers, by handle (e.g.
+    /// `"alice.bsky.social" = "green"`); the rest take the next free one
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub account_color: BTreeMap<String, AccountColor>,
+
+    /// What to do when sending images without a description ("off",
+    /// "warn" or "require")
+    #[serde(default)]
+    pub missing_alt_text: MissingAltText,
+
+    /// Keyword and regex filters that hide or mark posts
+    #[serde(default)]
+    pub filters: FilterConfig,
+
+    /// Proxy for every connection, e.g. `http://proxy.example.com:3128`
+    /// (unset: `HTTPS_PROXY` and friends apply)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+
+    /// PEM file with extra root certificates to trust, for instances
+    /// signed by a private certificate authority
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root_certificate: Option<PathBuf>,
+
+    /// Ask crates.io for a newer version at startup (`PERCH_NO_UPDATE_CHECK`
+    /// turns it off too)
+    #[serde(default = "default_check_for_updates")]
+    pub check_for_updates: bool,
+
+    /// Write debug logs to `perch.log` in the platform cache directory
+    /// (`PERCH_LOG_FILE` overrides it)
+    #[serde(default)]
+    pub log_file: bool,
+}
+
+/// How compose treats images that have no alt text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MissingAltText {
+    /// Send them without asking
+    Off,
+    /// Ask once, then send on the next try
+    #[default]
+    Warn,
+    /// Refuse to send until every image has a description
+    Require,
+}
+
+impl MissingAltText {
+    /// Get the value used in the config file
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Warn => "warn",
+            Self::Require => "require",
+        }
+    }
+
+    /// Parse from the config value
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "off" => Some(Self::Off),
+            "warn" => Some(Self::Warn),
+            "require" => Some(Self::Require),
+            _ => None,
+        }
+    }
+}
+
+/// When to draw in color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// In color, unless the `NO_COLOR` environment variable is set
+    #[default]
+    Auto,
+    /// In color, even with `NO_COLOR`
+    Always,
+    /// Without color: bold, dim and reverse video only
+    Never,
+}
+
+impl ColorMode {
+    /// Get the value used in the config file
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Always => "always",
+            Self::Never => "never",
+        }
+    }
+
+    /// Parse from the config value
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "auto" => Some(Self::Auto),
+            "always" => Some(Self::Always),
+            "never" => Some(Self::Never),
+            _ => None,
+        }
+    }
+
+    /// Whether to draw without color. `NO_COLOR` counts when it's set to
+    /// anything but an empty string (<https://no-color.org>).
+    pub fn is_monochrome(self) -> bool {
+        match self {
+            Self::Auto => std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()),
+            Self::Always => false,
+            Self::Never => true,
+        }
+    }
+}
+
+/// How the timeline view arranges the post list and detail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimelineLayout {
+    /// List and detail side by side
+    #[default]
+    Split,
+    /// One panel at a time
+    Single,
+}
+
+impl TimelineLayout {
+    /// Get the value used in the config file
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Split => "split",
+            Self::Single => "single",
+        }
+    }
+
+    /// Parse from the config value
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "split" => Some(Self::Split),
+            "single" => Some(Self::Single),
+            _ => None,
+        }
+    }
+
+    /// The layout after this one
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Split => Self::Single,
+            Self::Single => Self::Split,
+        }
+    }
+}
+
+/// One key or several, as written in the `[keybindings]` table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    /// A single key, e.g. `"ctrl+d"`
+    One(String),
+    /// Several keys for the same action
+    Many(Vec<String>),
+}
+
+impl KeyList {
+    /// The key specs in this list
+    pub fn specs(&self) -> &[String] {
+        match self {
+            Self::One(spec) => std::slice::from_ref(spec),
+            Self::Many(specs) => specs,
+        }
+    }
 }
 
 fn default_timeline() -> String {
@@ -59,16 +306,69 @@ const fn default_show_media() -> bool {
     true
 }
 
+const fn default_show_avatars() -> bool {
+    true
+}
+
+const fn default_timeline_width() -> u16 {
+    50
+}
+
+const fn default_mouse() -> bool {
+    true
+}
+
+const fn default_animate_images() -> bool {
+    true
+}
+
+const fn default_account_colors() -> bool {
+    true
+}
+
+const fn default_image_cache_max_mb() -> u64 {
+    200
+}
+
+const fn default_cache_max_posts() -> usize {
+    5000
+}
+
+const fn default_check_for_updates() -> bool {
+    true
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             theme: Theme::default(),
+            color: ColorMode::default(),
             default_timeline: default_timeline(),
             vim_mode: default_vim_mode(),
             refresh_interval_secs: default_refresh_interval(),
+            streaming: false,
             post_limit: default_post_limit(),
             show_media: default_show_media(),
+            show_avatars: default_show_avatars(),
+            layout: TimelineLayout::default(),
+            timeline_width: default_timeline_width(),
+            mouse: default_mouse(),
+            image_cache_max_mb: default_image_cache_max_mb(),
+            cache_max_posts: default_cache_max_posts(),
             default_post_networks: Vec::new(),
+            credential_backend: CredentialBackend::default(),
+            timezone: Timezone::default(),
+            absolute_times: false,
+            animate_images: default_animate_images(),
+            account_colors: default_account_colors(),
+            account_color: BTreeMap::new(),
+            keybindings: BTreeMap::new(),
+            missing_alt_text: MissingAltText::default(),
+            filters: FilterConfig::default(),
+            proxy: None,
+            root_certificate: None,
+            check_for_updates: default_check_for_updates(),
+            log_file: false,
         }
     }
 }
@@ -89,12 +389,101 @@ impl Config {
     pub fn load_from(path: &PathBuf) -> Result<Self> {
         if path.exists() {
             let content = std::fs::read_to_string(path).context("Failed to read config file")?;
-            toml::from_str(&content).context("Failed to parse config file")
+            let config: Self = toml::from_str(&content).context("Failed to parse config file")?;
+            if let Some(name) = config
+                .default_post_networks
+                .iter()
+                .find(|name| Network::from_str(name).is_none())
+            {
+                bail!(
+                    "Unknown network in default_post_networks: {name}\nSupported: mastodon, bluesky"
+                );
+            }
+            Ok(config)
         } else {
             Ok(Self::default())
         }
     }
 
+    /// Networks to post to when none are picked: `default_post_networks`,
+    /// or when that's empty, every network in `with_accounts`
+    pub fn default_networks(&self, with_accounts: &[Network]) -> Vec<Network> {
+        let configured: Vec<Network> = self
+            .default_post_networks
+            .iter()
+            .filter_map(|name| Network::from_str(name))
+            .collect();
+        if configured.is_empty() {
+            with_accounts.to_vec()
+        } else {
+            configured
+        }
+    }
+
+    /// Check the proxy and load the root certificate, so a mistake in either
+    /// stops Perch at startup instead of failing every request
+    pub fn network_options(&self) -> Result<NetworkOptions> {
+        let root_certificate = self
+            .root_certificate
+            .as_ref()
+            .map(|path| paths::expand_tilde(&path.to_string_lossy()));
+        NetworkOptions::new(self.proxy.as_deref(), root_certificate.as_deref())
+            .context("Check the proxy and root_certificate settings")
+    }
+
+    /// Whether to look for a newer version, unless `PERCH_NO_UPDATE_CHECK`
+    /// is set (to anything but "0" or "false")
+    pub fn update_check_enabled(&self) -> bool {
+        let disabled_by_env = std::env::var("PERCH_NO_UPDATE_CHECK")
+            .is_ok_and(|v| !matches!(v.trim(), "" | "0" | "false"));
+        self.check_for_updates && !disabled_by_env
+    }
+
+    /// Whether to write logs to a file: `PERCH_LOG_FILE` turns it on, or
+    /// off when it's "0" or "false", and otherwise the setting decides
+    pub fn file_logging_enabled(&self) -> bool {
+        match std::env::var("PERCH_LOG_FILE") {
+            Ok(v) if !v.trim().is_empty() => !matches!(v.trim(), "0" | "false"),
+            _ => self.log_file,
+        }
+    }
+
+    /// The marker color of each account, in order: the one configured for
+    /// it, else the next color no account is configured with
+    pub fn account_marker_colors(&self, accounts: &[Account]) -> Vec<AccountColor> {
+        let configured = |account: &Account| {
+            let handle = account.handle.trim_start_matches('@');
+            self.account_color
+                .iter()
+                .find(|(key, _)| key.trim_start_matches('@').eq_ignore_ascii_case(handle))
+                .map(|(_, color)| *color)
+        };
+        let taken: Vec<_> = accounts.iter().filter_map(configured).collect();
+        let mut free: Vec<_> = AccountColor::ALL
+            .into_iter()
+            .filter(|color| !taken.contains(color))
+            .collect();
+        if free.is_empty() {
+            free = AccountColor::ALL.to_vec();
+        }
+        let mut next = free.iter().copied().cycle();
+        accounts
+            .iter()
+            .map(|account| {
+                configured(account)
+                    .or_else(|| next.next())
+                    .unwrap_or(AccountColor::Accent)
+            })
+            .collect()
+    }
+
+    /// The timeline list's share of the width, kept in range even if the
+    /// config file was edited by hand
+    pub fn timeline_width(&self) -> u16 {
+        self.timeline_width
+            .clamp(*TIMELINE_WIDTHS.start(), *TIMELINE_WIDTHS.end())
+    }
+
     /// Save config to the default path
     pub fn save(&self) -> Result<()> {
         let path = Self::default_path()?;
@@ -108,9 +497,346 @@ impl Config {
             std::fs::create_dir_all(parent).context("Failed to create config directory")?;
         }
 
+        // Write next to the real file and rename over it, so an interrupted
+        // save never leaves a half-written config behind
         let content = toml::to_string_pretty(self).context("Failed to serialize config")?;
-        std::fs::write(path, content).context("Failed to write config file")?;
+        let tmp_path = path.with_extension("toml.tmp");
+        std::fs::write(&tmp_path, content).context("Failed to write config file")?;
+        std::fs::rename(&tmp_path, path).context("Failed to replace config file")?;
 
         Ok(())
     }
+
+    /// Read a setting by name, formatted the way [`Config::set`] accepts it
+    pub fn get(&self, key: &str) -> Result<String> {
+        Ok(match key {
+            "theme" => self.theme.slug().to_string(),
+            "color" => self.color.as_str().to_string(),
+            "post_limit" => self.post_limit.to_string(),
+            "refresh_interval_secs" => self.refresh_interval_secs.to_string(),
+            "default_post_networks" => self.default_post_networks.join(","),
+            "streaming" => self.streaming.to_string(),
+            "show_avatars" => self.show_avatars.to_string(),
+            "layout" => self.layout.as_str().to_string(),
+            "timeline_width" => self.timeline_width.to_string(),
+            "mouse" => self.mouse.to_string(),
+            "image_cache_max_mb" => self.image_cache_max_mb.to_string(),
+            "cache_max_posts" => self.cache_max_posts.to_string(),
+            "timezone" => self.timezone.to_string(),
+            "absolute_times" => self.absolute_times.to_string(),
+            "animate_images" => self.animate_images.to_string(),
+            "account_colors" => self.account_colors.to_string(),
+            "missing_alt_text" => self.missing_alt_text.as_str().to_string(),
+            "check_for_updates" => self.check_for_updates.to_string(),
+            "log_file" => self.log_file.to_string(),
+            "proxy" => self.proxy.clone().unwrap_or_default(),
+            "root_certificate" => self
+                .root_certificate
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            _ => return Err(unknown_setting(key)),
+        })
+    }
+
+    /// Change a setting by name, rejecting values Perch can't use
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let value = value.trim();
+        match key {
+            "theme" => {
+                self.theme = Theme::from_slug(value).ok_or_else(|| {
+                    let slugs: Vec<_> = Theme::all().iter().map(Theme::slug).collect();
+                    anyhow!("Unknown theme: {value}\nAvailable: {}", slugs.join(", "))
+                })?;
+            }
+            "color" => {
+                self.color = ColorMode::from_str(value).ok_or_else(|| {
+                    anyhow!("Invalid value for {key}: {value}\nAvailable: auto, always, never")
+                })?;
+            }
+            "post_limit" => {
+                let limit = parse_setting(key, value)?;
+                if !(1..=MAX_POST_LIMIT).contains(&limit) {
+                    bail!("post_limit must be between 1 and {MAX_POST_LIMIT}");
+                }
+                self.post_limit = limit;
+            }
+            "refresh_interval_secs" => self.refresh_interval_secs = parse_setting(key, value)?,
+            "default_post_networks" => {
+                let mut networks = Vec::new();
+                for name in value.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                    let network = Network::from_str(name).ok_or_else(|| {
+                        anyhow!("Unknown network: {name}\nSupported: mastodon, bluesky")
+                    })?;
+                    let name = network.name().to_lowercase();
+                    if !networks.contains(&name) {
+                        networks.push(name);
+                    }
+                }
+                self.default_post_networks = networks;
+            }
+            "streaming" => self.streaming = parse_setting(key, value)?,
+            "show_avatars" => self.show_avatars = parse_setting(key, value)?,
+            "layout" => {
+                self.layout = TimelineLayout::from_str(value).ok_or_else(|| {
+                    anyhow!("Invalid value for {key}: {value}\nAvailable: split, single")
+                })?;
+            }
+            "timeline_width" => {
+                let width = parse_setting(key, value)?;
+                if !TIMELINE_WIDTHS.contains(&width) {
+                    bail!(
+                        "timeline_width must be between {} and {}",
+                        TIMELINE_WIDTHS.start(),
+                        TIMELINE_WIDTHS.end()
+                    );
+                }
+                self.timeline_width = width;
+            }
+            "mouse" => self.mouse = parse_setting(key, value)?,
+            "image_cache_max_mb" => self.image_cache_max_mb = parse_setting(key, value)?,
+            "cache_max_posts" => self.cache_max_posts = parse_setting(key, value)?,
+            "timezone" => self.timezone = Timezone::parse(value)?,
+            "absolute_times" => self.absolute_times = parse_setting(key, value)?,
+            "animate_images" => self.animate_images = parse_setting(key, value)?,
+            "account_colors" => self.account_colors = parse_setting(key, value)?,
+            "missing_alt_text" => {
+                self.missing_alt_text = MissingAltText::from_str(value).ok_or_else(|| {
+                    anyhow!("Invalid value for {key}: {value}\nAvailable: off, warn, require")
+                })?;
+            }
+            "check_for_updates" => self.check_for_updates = parse_setting(key, value)?,
+            "log_file" => self.log_file = parse_setting(key, value)?,
+            // An empty value goes back to the default
+            "proxy" => {
+                let proxy = Some(value).filter(|v| !v.is_empty());
+                NetworkOptions::new(proxy, None)?;
+                self.proxy = proxy.map(str::to_string);
+            }
+            "root_certificate" => {
+                let path = Some(value)
+                    .filter(|v| !v.is_empty())
+                    .map(paths::expand_tilde);
+                NetworkOptions::new(None, path.as_deref())?;
+                self.root_certificate = path;
+            }
+            _ => return Err(unknown_setting(key)),
+        }
+        Ok(())
+    }
+}
+
+/// Settings `perch config get` and `perch config set` know about
+pub const SETTINGS: &[&str] = &[
+    "theme",
+    "color",
+    "post_limit",
+    "refresh_interval_secs",
+    "default_post_networks",
+    "streaming",
+    "show_avatars",
+    "layout",
+    "timeline_width",
+    "mouse",
+    "image_cache_max_mb",
+    "cache_max_posts",
+    "timezone",
+    "absolute_times",
+    "animate_images",
+    "account_colors",
+    "missing_alt_text",
+    "check_for_updates",
+    "log_file",
+    "proxy",
+    "root_certificate",
+];
+
+/// Most posts the networks return per request
+const MAX_POST_LIMIT: usize = 100;
+
+/// Shares of the width, in percent, the timeline list can take
+const TIMELINE_WIDTHS: std::ops::RangeInclusive<u16> = 20..=80;
+
+fn unknown_setting(key: &str) -> anyhow::Error {
+    anyhow!("Unknown setting: {key}\nAvailable: {}", SETTINGS.join(", "))
+}
+
+fn parse_setting<T: std::str::FromStr>(key: &str, value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| anyhow!("Invalid value for {key}: {value}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::{FilterAction, FilterRule};
+
+    #[test]
+    fn test_set_validates_values() {
+        let mut config = Config::default();
+
+        config.set("post_limit", "20").unwrap();
+        assert_eq!(config.get("post_limit").unwrap(), "20");
+        assert!(config.set("post_limit", "0").is_err());
+        assert!(config.set("post_limit", "lots").is_err());
+
+        config
+            .set("default_post_networks", "Bluesky, mastodon,bsky")
+            .unwrap();
+        assert_eq!(config.default_post_networks, ["bluesky", "mastodon"]);
+        assert!(config.set("default_post_networks", "myspace").is_err());
+        config.set("default_post_networks", "").unwrap();
+        assert!(config.default_post_networks.is_empty());
+
+        config.set("mouse", "false").unwrap();
+        assert!(!config.mouse);
+        assert!(config.set("mouse", "nope").is_err());
+
+        config.set("timezone", "+02:00").unwrap();
+        assert_eq!(config.get("timezone").unwrap(), "+02:00");
+
+        config.set("account_colors", "false").unwrap();
+        assert!(!config.account_colors);
+        assert!(config.set("account_colors", "sometimes").is_err());
+
+        config.set("missing_alt_text", "require").unwrap();
+        assert_eq!(config.missing_alt_text, MissingAltText::Require);
+        assert!(config.set("missing_alt_text", "always").is_err());
+
+        config.set("check_for_updates", "false").unwrap();
+        assert!(!config.check_for_updates);
+        config.set("log_file", "true").unwrap();
+        assert!(config.log_file);
+        assert!(config.set("log_file", "sometimes").is_err());
+        assert!(!config.update_check_enabled());
+
+        config.set("color", "never").unwrap();
+        assert!(config.color.is_monochrome());
+        assert!(config.set("color", "mono").is_err());
+
+        config.set("layout", "single").unwrap();
+        assert_eq!(config.layout, TimelineLayout::Single);
+        assert!(config.set("layout", "grid").is_err());
+        config.set("timeline_width", "65").unwrap();
+        assert_eq!(config.get("timeline_width").unwrap(), "65");
+        assert!(config.set("timeline_width", "95").is_err());
+
+        config.set("cache_max_posts", "100").unwrap();
+        assert_eq!(config.cache_max_posts, 100);
+        assert!(config.set("cache_max_posts", "lots").is_err());
+
+        config
+            .set("proxy", "http://proxy.example.com:3128")
+            .unwrap();
+        assert_eq!(
+            config.get("proxy").unwrap(),
+            "http://proxy.example.com:3128"
+        );
+        config.set("proxy", "").unwrap();
+        assert_eq!(config.proxy, None);
+        assert!(config.set("proxy", "not a url").is_err());
+        assert!(config.set("proxy", "ftp://proxy.example.com").is_err());
+        assert!(config.set("root_certificate", "/no/such/cert.pem").is_err());
+        assert_eq!(config.root_certificate, None);
+
+        assert!(config.set("theme", "not-a-theme").is_err());
+        assert!(config.set("vim", "true").is_err());
+        assert!(config.get("vim").is_err());
+
+        // Every listed setting can be read back
+        for key in SETTINGS {
+            config.get(key).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_parses_filters() {
+        let config: Config = toml::from_str(
+            r#"
+            [filters]
+            sync_mastodon = true
+
+            [[filters.rules]]
+            pattern = "spoiler"
+
+            [[filters.rules]]
+            pattern = "/elections?/"
+            action = "warn"
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.filters.sync_mastodon);
+        assert_eq!(
+            config.filters.rules,
+            [
+                FilterRule::keyword("spoiler", FilterAction::Hide),
+                FilterRule::keyword("/elections?/", FilterAction::Warn),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_account_marker_colors() {
+        let config: Config = toml::from_str(
+            r#"
+            [account_color]
+            "@Alice@mastodon.social" = "accent"
+            "bob.bsky.social" = "red"
+            "#,
+        )
+        .unwrap();
+        let accounts = [
+            Account::new_mastodon("carol@hachyderm.io", "https://hachyderm.io", "Carol"),
+            Account::new_bluesky("bob.bsky.social", "Bob"),
+            Account::new_mastodon("alice@mastodon.social", "https://mastodon.social", "Alice"),
+            Account::new_bluesky("dave.bsky.social", "Dave"),
+        ];
+
+        // Configured accounts keep their color; the rest skip those
+        assert_eq!(
+            config.account_marker_colors(&accounts),
+            [
+                AccountColor::Secondary,
+                AccountColor::Red,
+                AccountColor::Accent,
+                AccountColor::Green,
+            ]
+        );
+        assert!(toml::from_str::<Config>("[account_color]\na = \"mauve\"").is_err());
+    }
+
+    #[test]
+    fn test_save_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut config = Config::default();
+        config.set("refresh_interval_secs", "90").unwrap();
+        config.save_to(&path).unwrap();
+
+        let loaded = Config::load_from(&path).unwrap();
+        assert_eq!(loaded.refresh_interval_secs, 90);
+        assert!(!path.with_extension("toml.tmp").exists());
+
+        // A network set by hand has to exist
+        std::fs::write(&path, "default_post_networks = [\"myspace\"]").unwrap();
+        let error = Config::load_from(&path).unwrap_err();
+        assert!(error.to_string().contains("myspace"));
+    }
+
+    #[test]
+    fn test_default_networks() {
+        let mut config = Config::default();
+        // Without a setting, every network with an account
+        assert_eq!(
+            config.default_networks(&[Network::Bluesky]),
+            [Network::Bluesky]
+        );
+        config.set("default_post_networks", "mastodon").unwrap();
+        assert_eq!(
+            config.default_networks(&[Network::Mastodon, Network::Bluesky]),
+            [Network::Mastodon]
+        );
+    }
 }
@@ -6,9 +6,199 @@ use rusqlite::{Connection, params};
 use std::path::PathBuf;
 use uuid::Uuid;
 
-use crate::models::{Account, Network, Post, ScheduledPost, ScheduledPostStatus};
+use crate::models::{
+    Account, Draft, ModeratedAccount, Moderation, Network, Post, Recurrence, ScheduledPost,
+    ScheduledPostStatus, normalize_handle,
+};
 use crate::paths;
 
+/// Schema migrations, applied in order.
+///
+/// `PRAGMA user_version` records how many have run, so a step at index `n`
+/// upgrades a database from version `n` to `n + 1`. Only ever append here.
+const MIGRATIONS: &[fn(&Database) -> Result<()>] = &[
+    Database::migrate_v1,
+    Database::migrate_v2,
+    Database::migrate_v3,
+    Database::migrate_v4,
+    Database::migrate_v5,
+    Database::migrate_v6,
+    Database::migrate_v7,
+    Database::migrate_v8,
+    Database::migrate_v9,
+    Database::migrate_v10,
+    Database::migrate_v11,
+    Database::migrate_v12,
+    Database::migrate_v13,
+];
+
+/// Feed key for the home timeline in the post cache
+pub const HOME_FEED: &str = "";
+
+/// Schema version this build expects
+pub const SCHEMA_VERSION: usize = MIGRATIONS.len();
+
+/// Columns read by `post_from_row`, in order
+const POST_COLUMNS: &str = "id, network_id, network, author_handle, author_name, author_avatar,
+    content, content_raw, created_at, url, is_repost, repost_author,
+    like_count, repost_count, reply_count, liked, reposted, reply_to_id,
+    cid, uri, media_json, spoiler_text, bookmarked, quote_json, link_card_json,
+    owner_account_id";
+
+/// Condition on `post_cache` leaving out posts by muted and blocked accounts
+const NOT_MODERATED: &str = "NOT EXISTS (SELECT 1 FROM moderated_accounts m
+    WHERE m.network = post_cache.network AND m.handle = lower(post_cache.author_handle))";
+
+/// Cache trims removing at least this many posts also `VACUUM` the database
+const VACUUM_THRESHOLD: usize = 1000;
+
+/// Columns read by `account_from_row`, in order
+const ACCOUNT_COLUMNS: &str = "id, network, display_name, handle, server, is_default, avatar_url,
+    created_at, last_used_at, max_post_chars, server_version, server_name,
+    max_media_attachments, max_image_bytes";
+
+/// Columns read by `row_to_scheduled_post`, in order
+const SCHEDULED_POST_COLUMNS: &str = "id, content, networks, scheduled_for, status, error,
+    created_at, recurrence, retry_count, next_retry_at, posted_networks, recurrence_anchor";
+
+/// Build an account from a row selected with `ACCOUNT_COLUMNS`
+fn account_from_row(row: &rusqlite::Row) -> rusqlite::Result<Account> {
+    let network_str: String = row.get(1)?;
+
+    Ok(Account {
+        id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
+        network: Network::from_str(&network_str).unwrap_or_default(),
+        display_name: row.get(2)?,
+        handle: row.get(3)?,
+        server: row.get(4)?,
+        is_default: row.get::<_, i32>(5)? != 0,
+        avatar_url: row.get(6)?,
+        created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?)
+            .unwrap()
+            .with_timezone(&Utc),
+        last_used_at: row
+            .get::<_, Option<String>>(8)?
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc)),
+        max_post_chars: row
+            .get::<_, Option<i64>>(9)?
+            .and_then(|n| usize::try_from(n).ok()),
+        server_version: row.get(10)?,
+        server_name: row.get(11)?,
+        max_media_attachments: row
+            .get::<_, Option<i64>>(12)?
+            .and_then(|n| usize::try_from(n).ok()),
+        max_image_bytes: row
+            .get::<_, Option<i64>>(13)?
+            .and_then(|n| usize::try_from(n).ok()),
+    })
+}
+
+/// Build a post from a row selected with `POST_COLUMNS`
+fn post_from_row(row: &rusqlite::Row) -> rusqlite::Result<Post> {
+    let network_str: String = row.get(2)?;
+    let network = Network::from_str(&network_str).unwrap_or_default();
+
+    // Deserialize media from JSON
+    let media_json: String = row
+        .get::<_, Option<String>>(20)?
+        .unwrap_or_else(|| "[]".to_string());
+    let media: Vec<crate::models::MediaAttachment> =
+        serde_json::from_str(&media_json).unwrap_or_default();
+
+    Ok(Post {
+        id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
+        network_id: row.get(1)?,
+        network,
+        author_handle: row.get(3)?,
+        author_name: row.get(4)?,
+        author_avatar: row.get(5)?,
+        content: row.get(6)?,
+        content_raw: row.get(7)?,
+        spoiler_text: row.get(21)?,
+        created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(8)?)
+            .unwrap()
+            .with_timezone(&Utc),
+        url: row.get(9)?,
+        is_repost: row.get::<_, i32>(10)? != 0,
+        repost_author: row.get(11)?,
+        like_count: row.get(12)?,
+        repost_count: row.get(13)?,
+        reply_count: row.get(14)?,
+        liked: row.get::<_, i32>(15)? != 0,
+        reposted: row.get::<_, i32>(16)? != 0,
+        bookmarked: row.get::<_, i32>(22)? != 0,
+        reply_to_id: row.get(17)?,
+        media,
+        cid: row.get(18)?,
+        uri: row.get(19)?,
+        quote: row
+            .get::<_, Option<String>>(23)?
+            .and_then(|json| serde_json::from_str(&json).ok()),
+        link_card: row
+            .get::<_, Option<String>>(24)?
+            .and_then(|json| serde_json::from_str(&json).ok()),
+        owner_account_id: row
+            .get::<_, Option<String>>(25)?
+            .and_then(|id| Uuid::parse_str(&id).ok()),
+    })
+}
+
+/// Turn a search box query into an FTS5 expression.
+///
+/// Every term is quoted so punctuation can't be read as FTS syntax. Bare
+/// words become prefix matches, `"..."` stays a phrase, and `author:` limits
+/// a term to the author columns. Returns `None` if there is nothing to match.
+fn fts_query(input: &str) -> Option<String> {
+    let mut terms = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let mut token = String::new();
+        let mut quoted = false;
+        while let Some(&c) = chars.peek() {
+            if c == '"' {
+                quoted = !quoted;
+                chars.next();
+                continue;
+            }
+            if c.is_whitespace() && !quoted {
+                break;
+            }
+            token.push(c);
+            chars.next();
+        }
+
+        let (author, text) = match token.strip_prefix("author:") {
+            Some(rest) => (true, rest.trim_start_matches('@')),
+            None => (false, token.as_str()),
+        };
+        if text.trim().is_empty() {
+            continue;
+        }
+
+        let phrase = format!("\"{}\"", text.replace('"', "\"\""));
+        // Phrases with spaces match exactly, single words by prefix
+        let term = if text.contains(char::is_whitespace) {
+            phrase
+        } else {
+            format!("{phrase}*")
+        };
+        terms.push(if author {
+            format!("{{author_handle author_name}} : {term}")
+        } else {
+            term
+        });
+    }
+
+    (!terms.is_empty()).then(|| terms.join(" AND "))
+}
+
 /// Database connection wrapper
 pub struct Database {
     conn: Connection,
@@ -29,9 +219,23 @@ impl Database {
         }
 
         let conn = Connection::open(path).context("Failed to open database")?;
+        Self::from_connection(conn)
+    }
+
+    /// Open an empty database that only lives as long as this value, for
+    /// running without touching the user's data
+    pub fn open_in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory().context("Failed to open database")?;
+        Self::from_connection(conn)
+    }
+
+    fn from_connection(conn: Connection) -> Result<Self> {
+        // `INSERT OR REPLACE` only fires delete triggers with this on, and the
+        // search index relies on them
+        conn.pragma_update(None, "recursive_triggers", true)?;
 
         let db = Self { conn };
-        db.init()?;
+        db.migrate()?;
 
         Ok(db)
     }
@@ -41,8 +245,44 @@ impl Database {
         paths::database_path()
     }
 
-    /// Initialize the database schema
-    fn init(&self) -> Result<()> {
+    /// Bring the schema up to date, running each pending migration in its own
+    /// transaction
+    fn migrate(&self) -> Result<()> {
+        let version = self.schema_version()?;
+        if version > SCHEMA_VERSION {
+            tracing::warn!(
+                "Database schema version {version} is newer than this build supports ({SCHEMA_VERSION})"
+            );
+            return Ok(());
+        }
+
+        for (index, step) in MIGRATIONS.iter().enumerate().skip(version) {
+            let target = index + 1;
+            let tx = self.conn.unchecked_transaction()?;
+            step(self)
+                .with_context(|| format!("Failed to migrate database to version {target}"))?;
+            tx.pragma_update(None, "user_version", i64::try_from(target)?)?;
+            tx.commit()?;
+            tracing::info!("Migrated database to schema version {target}");
+        }
+
+        Ok(())
+    }
+
+    /// Current schema version (0 for databases created before migrations)
+    pub fn schema_version(&self) -> Result<usize> {
+        let version: i64 = self
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+        Ok(usize::try_from(version).unwrap_or_default())
+    }
+
+    /// Version 1: the schema as it stood before migrations existed.
+    ///
+    /// Databases from those releases may be missing columns that were added
+    /// later, so they are filled in here. Later steps can use plain
+    /// `ALTER TABLE` statements.
+    fn migrate_v1(&self) -> Result<()> {
         self.conn.execute_batch(
             r"
             -- Accounts table
@@ -65,6 +305,7 @@ impl Database {
                 networks TEXT NOT NULL,
                 reply_to_id TEXT,
                 reply_to_network TEXT,
+                reply_to_json TEXT,
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL
             );
@@ -103,6 +344,8 @@ impl Database {
                 cid TEXT,
                 uri TEXT,
                 media_json TEXT DEFAULT '[]',
+                spoiler_text TEXT,
+                bookmarked INTEGER NOT NULL DEFAULT 0,
                 cached_at TEXT NOT NULL,
                 UNIQUE(network, network_id)
             );
@@ -116,6 +359,220 @@ impl Database {
             ",
         )?;
 
+        // Columns added to earlier releases without a version bump
+        self.ensure_column("post_cache", "spoiler_text", "TEXT")?;
+        self.ensure_column("post_cache", "bookmarked", "INTEGER NOT NULL DEFAULT 0")?;
+        self.ensure_column("drafts", "reply_to_json", "TEXT")?;
+
+        Ok(())
+    }
+
+    /// Version 2: full-text search index over cached posts, kept in sync by
+    /// triggers
+    fn migrate_v2(&self) -> Result<()> {
+        self.conn.execute_batch(
+            r"
+            CREATE VIRTUAL TABLE post_search USING fts5(
+                post_id UNINDEXED,
+                content,
+                author_handle,
+                author_name
+            );
+
+            CREATE TRIGGER post_cache_search_insert AFTER INSERT ON post_cache BEGIN
+                INSERT INTO post_search (post_id, content, author_handle, author_name)
+                VALUES (new.id, new.content, new.author_handle, new.author_name);
+            END;
+
+            CREATE TRIGGER post_cache_search_delete AFTER DELETE ON post_cache BEGIN
+                DELETE FROM post_search WHERE post_id = old.id;
+            END;
+
+            CREATE TRIGGER post_cache_search_update AFTER UPDATE ON post_cache BEGIN
+                DELETE FROM post_search WHERE post_id = old.id;
+                INSERT INTO post_search (post_id, content, author_handle, author_name)
+                VALUES (new.id, new.content, new.author_handle, new.author_name);
+            END;
+
+            INSERT INTO post_search (post_id, content, author_handle, author_name)
+            SELECT id, content, author_handle, author_name FROM post_cache;
+            ",
+        )?;
+        Ok(())
+    }
+
+    /// Version 3: cache posts per feed, so hashtag timelines don't mix with
+    /// the home timeline.
+    ///
+    /// The same post can now appear in several feeds, which needs a new
+    /// unique key, so the table is rebuilt (dropping it also drops its
+    /// triggers, which are recreated).
+    fn migrate_v3(&self) -> Result<()> {
+        self.conn.execute_batch(
+            r"
+            CREATE TABLE post_cache_v3 (
+                id TEXT PRIMARY KEY,
+                network_id TEXT NOT NULL,
+                network TEXT NOT NULL,
+                author_handle TEXT NOT NULL,
+                author_name TEXT NOT NULL,
+                author_avatar TEXT,
+                content TEXT NOT NULL,
+                content_raw TEXT,
+                created_at TEXT NOT NULL,
+                url TEXT,
+                is_repost INTEGER NOT NULL DEFAULT 0,
+                repost_author TEXT,
+                like_count INTEGER NOT NULL DEFAULT 0,
+                repost_count INTEGER NOT NULL DEFAULT 0,
+                reply_count INTEGER NOT NULL DEFAULT 0,
+                liked INTEGER NOT NULL DEFAULT 0,
+                reposted INTEGER NOT NULL DEFAULT 0,
+                reply_to_id TEXT,
+                cid TEXT,
+                uri TEXT,
+                media_json TEXT DEFAULT '[]',
+                spoiler_text TEXT,
+                bookmarked INTEGER NOT NULL DEFAULT 0,
+                cached_at TEXT NOT NULL,
+                feed TEXT NOT NULL DEFAULT '',
+                UNIQUE(feed, network, network_id)
+            );
+
+            INSERT INTO post_cache_v3
+                (id, network_id, network, author_handle, author_name, author_avatar,
+                 content, content_raw, created_at, url, is_repost, repost_author,
+                 like_count, repost_count, reply_count, liked, reposted, reply_to_id,
+                 cid, uri, media_json, spoiler_text, bookmarked, cached_at)
+            SELECT id, network_id, network, author_handle, author_name, author_avatar,
+                   content, content_raw, created_at, url, is_repost, repost_author,
+                   like_count, repost_count, reply_count, liked, reposted, reply_to_id,
+                   cid, uri, media_json, spoiler_text, bookmarked, cached_at
+            FROM post_cache;
+
+            DROP TABLE post_cache;
+            ALTER TABLE post_cache_v3 RENAME TO post_cache;
+
+            CREATE INDEX idx_post_cache_network ON post_cache(network);
+            CREATE INDEX idx_post_cache_cached_at ON post_cache(cached_at);
+            CREATE INDEX idx_post_cache_feed ON post_cache(feed);
+
+            CREATE TRIGGER post_cache_search_insert AFTER INSERT ON post_cache BEGIN
+                INSERT INTO post_search (post_id, content, author_handle, author_name)
+                VALUES (new.id, new.content, new.author_handle, new.author_name);
+            END;
+
+            CREATE TRIGGER post_cache_search_delete AFTER DELETE ON post_cache BEGIN
+                DELETE FROM post_search WHERE post_id = old.id;
+            END;
+
+            CREATE TRIGGER post_cache_search_update AFTER UPDATE ON post_cache BEGIN
+                DELETE FROM post_search WHERE post_id = old.id;
+                INSERT INTO post_search (post_id, content, author_handle, author_name)
+                VALUES (new.id, new.content, new.author_handle, new.author_name);
+            END;
+            ",
+        )?;
+        Ok(())
+    }
+
+    /// Version 4: quoted posts, stored as JSON
+    fn migrate_v4(&self) -> Result<()> {
+        self.conn
+            .execute_batch("ALTER TABLE post_cache ADD COLUMN quote_json TEXT;")?;
+        Ok(())
+    }
+
+    /// Version 5: link preview cards, stored as JSON
+    fn migrate_v5(&self) -> Result<()> {
+        self.conn
+            .execute_batch("ALTER TABLE post_cache ADD COLUMN link_card_json TEXT;")?;
+        Ok(())
+    }
+
+    /// Version 6: each account's post length limit (Mastodon instances vary)
+    fn migrate_v6(&self) -> Result<()> {
+        self.conn
+            .execute_batch("ALTER TABLE accounts ADD COLUMN max_post_chars INTEGER;")?;
+        Ok(())
+    }
+
+    /// Version 7: the account each cached post was loaded through
+    fn migrate_v7(&self) -> Result<()> {
+        self.conn
+            .execute_batch("ALTER TABLE post_cache ADD COLUMN owner_account_id TEXT;")?;
+        Ok(())
+    }
+
+    /// Version 8: repeating scheduled posts
+    fn migrate_v8(&self) -> Result<()> {
+        self.conn
+            .execute_batch("ALTER TABLE scheduled_posts ADD COLUMN recurrence TEXT;")?;
+        Ok(())
+    }
+
+    /// Version 9: retrying failed scheduled posts
+    fn migrate_v9(&self) -> Result<()> {
+        self.conn.execute_batch(
+            "ALTER TABLE scheduled_posts ADD COLUMN retry_count INTEGER NOT NULL DEFAULT 0;
+             ALTER TABLE scheduled_posts ADD COLUMN next_retry_at TEXT;
+             ALTER TABLE scheduled_posts ADD COLUMN posted_networks TEXT NOT NULL DEFAULT '';",
+        )?;
+        Ok(())
+    }
+
+    /// Version 10: accounts muted or blocked from perch
+    fn migrate_v10(&self) -> Result<()> {
+        self.conn.execute_batch(
+            "CREATE TABLE moderated_accounts (
+                network TEXT NOT NULL,
+                handle TEXT NOT NULL,
+                moderation TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (network, handle, moderation)
+            );",
+        )?;
+        Ok(())
+    }
+
+    /// Version 11: the software version each account's server reports
+    fn migrate_v11(&self) -> Result<()> {
+        self.conn
+            .execute_batch("ALTER TABLE accounts ADD COLUMN server_version TEXT;")?;
+        Ok(())
+    }
+
+    /// Version 12: more of what each account's server says about itself
+    fn migrate_v12(&self) -> Result<()> {
+        self.conn.execute_batch(
+            "ALTER TABLE accounts ADD COLUMN server_name TEXT;
+             ALTER TABLE accounts ADD COLUMN max_media_attachments INTEGER;
+             ALTER TABLE accounts ADD COLUMN max_image_bytes INTEGER;",
+        )?;
+        Ok(())
+    }
+
+    /// Version 13: the first occurrence of each repeating scheduled post
+    fn migrate_v13(&self) -> Result<()> {
+        self.conn
+            .execute_batch("ALTER TABLE scheduled_posts ADD COLUMN recurrence_anchor TEXT;")?;
+        Ok(())
+    }
+
+    /// Add a column to an existing table if it is missing
+    fn ensure_column(&self, table: &str, column: &str, definition: &str) -> Result<()> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({table})"))?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(Result::ok)
+            .any(|name| name == column);
+
+        if !exists {
+            self.conn.execute(
+                &format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"),
+                [],
+            )?;
+        }
         Ok(())
     }
 
@@ -124,8 +581,8 @@ impl Database {
     /// Insert a new account
     pub fn insert_account(&self, account: &Account) -> Result<()> {
         self.conn.execute(
-            r"INSERT INTO accounts (id, network, display_name, handle, server, is_default, avatar_url, created_at, last_used_at)
-               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            r"INSERT INTO accounts (id, network, display_name, handle, server, is_default, avatar_url, created_at, last_used_at, max_post_chars, server_version, server_name, max_media_attachments, max_image_bytes)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
             params![
                 account.id.to_string(),
                 format!("{:?}", account.network).to_lowercase(),
@@ -136,6 +593,11 @@ impl Database {
                 account.avatar_url,
                 account.created_at.to_rfc3339(),
                 account.last_used_at.map(|dt| dt.to_rfc3339()),
+                account.max_post_chars.and_then(|n| i64::try_from(n).ok()),
+                account.server_version,
+                account.server_name,
+                account.max_media_attachments.and_then(|n| i64::try_from(n).ok()),
+                account.max_image_bytes.and_then(|n| i64::try_from(n).ok()),
             ],
         )?;
         Ok(())
@@ -143,100 +605,35 @@ impl Database {
 
     /// Get all accounts
     pub fn get_accounts(&self) -> Result<Vec<Account>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, network, display_name, handle, server, is_default, avatar_url, created_at, last_used_at 
-             FROM accounts ORDER BY network, display_name"
-        )?;
-
-        let accounts = stmt.query_map([], |row| {
-            let network_str: String = row.get(1)?;
-            let network = Network::from_str(&network_str).unwrap_or_default();
-
-            Ok(Account {
-                id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
-                network,
-                display_name: row.get(2)?,
-                handle: row.get(3)?,
-                server: row.get(4)?,
-                is_default: row.get::<_, i32>(5)? != 0,
-                avatar_url: row.get(6)?,
-                created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?)
-                    .unwrap()
-                    .with_timezone(&Utc),
-                last_used_at: row
-                    .get::<_, Option<String>>(8)?
-                    .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                    .map(|dt| dt.with_timezone(&Utc)),
-            })
-        })?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {ACCOUNT_COLUMNS} FROM accounts ORDER BY network, display_name"
+        ))?;
 
+        let accounts = stmt.query_map([], account_from_row)?;
         accounts.collect::<Result<Vec<_>, _>>().map_err(Into::into)
     }
 
     /// Get accounts for a specific network
     pub fn get_accounts_for_network(&self, network: Network) -> Result<Vec<Account>> {
         let network_str = format!("{network:?}").to_lowercase();
-        let mut stmt = self.conn.prepare(
-            "SELECT id, network, display_name, handle, server, is_default, avatar_url, created_at, last_used_at 
-             FROM accounts WHERE network = ?1 ORDER BY display_name"
-        )?;
-
-        let accounts = stmt.query_map(params![network_str], |row| {
-            let net_str: String = row.get(1)?;
-            let net = Network::from_str(&net_str).unwrap_or_default();
-
-            Ok(Account {
-                id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
-                network: net,
-                display_name: row.get(2)?,
-                handle: row.get(3)?,
-                server: row.get(4)?,
-                is_default: row.get::<_, i32>(5)? != 0,
-                avatar_url: row.get(6)?,
-                created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?)
-                    .unwrap()
-                    .with_timezone(&Utc),
-                last_used_at: row
-                    .get::<_, Option<String>>(8)?
-                    .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                    .map(|dt| dt.with_timezone(&Utc)),
-            })
-        })?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {ACCOUNT_COLUMNS} FROM accounts WHERE network = ?1 ORDER BY display_name"
+        ))?;
 
+        let accounts = stmt.query_map(params![network_str], account_from_row)?;
         accounts.collect::<Result<Vec<_>, _>>().map_err(Into::into)
     }
 
-    /// Get the default account for a network
+    /// Get the default account for a network, or its oldest account when
+    /// none is marked default
     pub fn get_default_account(&self, network: Network) -> Result<Option<Account>> {
         let network_str = format!("{network:?}").to_lowercase();
-        let mut stmt = self.conn.prepare(
-            "SELECT id, network, display_name, handle, server, is_default, avatar_url, created_at, last_used_at 
-             FROM accounts WHERE network = ?1 AND is_default = 1"
-        )?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {ACCOUNT_COLUMNS} FROM accounts WHERE network = ?1
+             ORDER BY is_default DESC, created_at LIMIT 1"
+        ))?;
 
-        let result = stmt.query_row(params![network_str], |row| {
-            let net_str: String = row.get(1)?;
-            let net = Network::from_str(&net_str).unwrap_or_default();
-
-            Ok(Account {
-                id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
-                network: net,
-                display_name: row.get(2)?,
-                handle: row.get(3)?,
-                server: row.get(4)?,
-                is_default: row.get::<_, i32>(5)? != 0,
-                avatar_url: row.get(6)?,
-                created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?)
-                    .unwrap()
-                    .with_timezone(&Utc),
-                last_used_at: row
-                    .get::<_, Option<String>>(8)?
-                    .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                    .map(|dt| dt.with_timezone(&Utc)),
-            })
-        });
-
-        match result {
+        match stmt.query_row(params![network_str], account_from_row) {
             Ok(account) => Ok(Some(account)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(e.into()),
@@ -267,6 +664,53 @@ impl Database {
         Ok(())
     }
 
+    /// Update the profile details shown for an account
+    pub fn update_account_profile(
+        &self,
+        id: Uuid,
+        display_name: &str,
+        avatar_url: Option<&str>,
+    ) -> Result<()> {
+        self.conn.execute(
+            "UPDATE accounts SET display_name = ?2, avatar_url = ?3 WHERE id = ?1",
+            params![id.to_string(), display_name, avatar_url],
+        )?;
+        Ok(())
+    }
+
+    /// Save the post length limit reported by an account's server
+    pub fn update_account_max_post_chars(&self, id: Uuid, max_post_chars: usize) -> Result<()> {
+        self.conn.execute(
+            "UPDATE accounts SET max_post_chars = ?2 WHERE id = ?1",
+            params![id.to_string(), i64::try_from(max_post_chars)?],
+        )?;
+        Ok(())
+    }
+
+    /// Save what an account's server reported about itself in `info`,
+    /// keeping the stored values of anything it didn't say
+    pub fn update_account_server_info(&self, id: Uuid, info: &Account) -> Result<()> {
+        let count = |n: Option<usize>| n.and_then(|n| i64::try_from(n).ok());
+        self.conn.execute(
+            "UPDATE accounts SET
+                server_version = COALESCE(?2, server_version),
+                server_name = COALESCE(?3, server_name),
+                max_post_chars = COALESCE(?4, max_post_chars),
+                max_media_attachments = COALESCE(?5, max_media_attachments),
+                max_image_bytes = COALESCE(?6, max_image_bytes)
+             WHERE id = ?1",
+            params![
+                id.to_string(),
+                info.server_version,
+                info.server_name,
+                count(info.max_post_chars),
+                count(info.max_media_attachments),
+                count(info.max_image_bytes),
+            ],
+        )?;
+        Ok(())
+    }
+
     /// Update last used timestamp
     pub fn update_account_last_used(&self, id: Uuid) -> Result<()> {
         self.conn.execute(
@@ -276,20 +720,161 @@ impl Database {
         Ok(())
     }
 
+    // ==================== Drafts ====================
+
+    /// Save a draft (replacing any earlier version with the same id)
+    pub fn save_draft(&self, draft: &Draft) -> Result<()> {
+        let networks = draft
+            .networks
+            .iter()
+            .map(|n| format!("{n:?}").to_lowercase())
+            .collect::<Vec<_>>()
+            .join(",");
+        let reply_to_json = draft
+            .reply_to
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+
+        self.conn.execute(
+            r"INSERT OR REPLACE INTO drafts
+               (id, content, networks, reply_to_id, reply_to_network, reply_to_json,
+                created_at, updated_at)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                draft.id.to_string(),
+                draft.content,
+                networks,
+                draft.reply_to.as_ref().map(|p| p.network_id.clone()),
+                draft
+                    .reply_to
+                    .as_ref()
+                    .map(|p| format!("{:?}", p.network).to_lowercase()),
+                reply_to_json,
+                draft.created_at.to_rfc3339(),
+                draft.updated_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get all drafts (most recently saved first)
+    pub fn get_drafts(&self) -> Result<Vec<Draft>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, content, networks, reply_to_json, created_at, updated_at
+             FROM drafts ORDER BY updated_at DESC",
+        )?;
+
+        let drafts = stmt.query_map([], |row| {
+            let parse_time = |s: String| {
+                DateTime::parse_from_rfc3339(&s)
+                    .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc))
+            };
+            Ok(Draft {
+                id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap_or_default(),
+                content: row.get(1)?,
+                networks: ScheduledPost::networks_from_str(&row.get::<_, String>(2)?),
+                reply_to: row
+                    .get::<_, Option<String>>(3)?
+                    .and_then(|json| serde_json::from_str(&json).ok()),
+                created_at: parse_time(row.get(4)?),
+                updated_at: parse_time(row.get(5)?),
+            })
+        })?;
+        drafts.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// Delete a draft
+    pub fn delete_draft(&self, id: Uuid) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM drafts WHERE id = ?1", params![id.to_string()])?;
+        Ok(())
+    }
+
+    // ==================== Muted and Blocked ====================
+
+    /// Remember a muted or blocked account, so its posts stay hidden
+    pub fn add_moderated(&self, account: &ModeratedAccount) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO moderated_accounts (network, handle, moderation, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                format!("{:?}", account.network).to_lowercase(),
+                account.handle,
+                account.moderation.as_str(),
+                account.created_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Forget a mute or block (`true` if there was one)
+    pub fn remove_moderated(
+        &self,
+        network: Network,
+        handle: &str,
+        moderation: Moderation,
+    ) -> Result<bool> {
+        let removed = self.conn.execute(
+            "DELETE FROM moderated_accounts WHERE network = ?1 AND handle = ?2 AND moderation = ?3",
+            params![
+                format!("{network:?}").to_lowercase(),
+                normalize_handle(handle),
+                moderation.as_str(),
+            ],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// Get every muted and blocked account, most recent first
+    pub fn get_moderated(&self) -> Result<Vec<ModeratedAccount>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT network, handle, moderation, created_at
+             FROM moderated_accounts ORDER BY created_at DESC",
+        )?;
+
+        let accounts = stmt.query_map([], |row| {
+            Ok(ModeratedAccount {
+                network: Network::from_str(&row.get::<_, String>(0)?).unwrap_or_default(),
+                handle: row.get(1)?,
+                moderation: Moderation::from_str(&row.get::<_, String>(2)?)
+                    .unwrap_or(Moderation::Mute),
+                created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
+                    .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
+            })
+        })?;
+
+        accounts.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
     // ==================== Post Cache ====================
 
-    /// Cache a post
+    /// Cache a home timeline post
     pub fn cache_post(&self, post: &Post) -> Result<()> {
+        self.cache_feed_post(HOME_FEED, post)
+    }
+
+    /// Cache a post under a feed (`HOME_FEED` or a key like `#rust`)
+    pub fn cache_feed_post(&self, feed: &str, post: &Post) -> Result<()> {
         let network_str = format!("{:?}", post.network).to_lowercase();
         let media_json = serde_json::to_string(&post.media).unwrap_or_else(|_| "[]".to_string());
+        let quote_json = post
+            .quote
+            .as_ref()
+            .and_then(|quote| serde_json::to_string(quote).ok());
+        let link_card_json = post
+            .link_card
+            .as_ref()
+            .and_then(|card| serde_json::to_string(card).ok());
 
         self.conn.execute(
             r"INSERT OR REPLACE INTO post_cache 
                (id, network_id, network, author_handle, author_name, author_avatar, 
                 content, content_raw, created_at, url, is_repost, repost_author,
                 like_count, repost_count, reply_count, liked, reposted, reply_to_id,
-                cid, uri, media_json, cached_at)
-               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22)",
+                cid, uri, media_json, spoiler_text, bookmarked, cached_at, feed, quote_json,
+                link_card_json, owner_account_id)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28)",
             params![
                 post.id.to_string(),
                 post.network_id,
@@ -312,76 +897,113 @@ impl Database {
                 post.cid,
                 post.uri,
                 media_json,
+                post.spoiler_text,
+                i32::from(post.bookmarked),
                 Utc::now().to_rfc3339(),
+                feed,
+                quote_json,
+                link_card_json,
+                post.owner_account_id.map(|id| id.to_string()),
             ],
         )?;
         Ok(())
     }
 
-    /// Get cached posts for a network (most recent first)
+    /// Remove a post from the cache
+    pub fn remove_cached_post(&self, network: Network, network_id: &str) -> Result<()> {
+        let network_str = format!("{network:?}").to_lowercase();
+        self.conn.execute(
+            "DELETE FROM post_cache WHERE network = ?1 AND network_id = ?2",
+            params![network_str, network_id],
+        )?;
+        Ok(())
+    }
+
+    /// Get cached home timeline posts for a network (most recent first)
     pub fn get_cached_posts(&self, network: Option<Network>, limit: usize) -> Result<Vec<Post>> {
+        self.get_feed_posts(HOME_FEED, network, limit)
+    }
+
+    /// Get cached posts of a feed for a network (most recent first)
+    pub fn get_feed_posts(
+        &self,
+        feed: &str,
+        network: Option<Network>,
+        limit: usize,
+    ) -> Result<Vec<Post>> {
         let sql = if let Some(net) = network {
             let network_str = format!("{net:?}").to_lowercase();
             format!(
-                "SELECT id, network_id, network, author_handle, author_name, author_avatar,
-                        content, content_raw, created_at, url, is_repost, repost_author,
-                        like_count, repost_count, reply_count, liked, reposted, reply_to_id,
-                        cid, uri, media_json
-                 FROM post_cache WHERE network = '{network_str}' ORDER BY created_at DESC LIMIT {limit}"
+                "SELECT {POST_COLUMNS} FROM post_cache WHERE feed = ?1 AND network = '{network_str}' AND {NOT_MODERATED} ORDER BY created_at DESC LIMIT {limit}"
             )
         } else {
             format!(
-                "SELECT id, network_id, network, author_handle, author_name, author_avatar,
-                        content, content_raw, created_at, url, is_repost, repost_author,
-                        like_count, repost_count, reply_count, liked, reposted, reply_to_id,
-                        cid, uri, media_json
-                 FROM post_cache ORDER BY created_at DESC LIMIT {limit}"
+                "SELECT {POST_COLUMNS} FROM post_cache WHERE feed = ?1 AND {NOT_MODERATED} ORDER BY created_at DESC LIMIT {limit}"
             )
         };
 
         let mut stmt = self.conn.prepare(&sql)?;
+        let posts = stmt.query_map([feed], post_from_row)?;
+        posts.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
 
-        let posts = stmt.query_map([], |row| {
-            let network_str: String = row.get(2)?;
-            let network = Network::from_str(&network_str).unwrap_or_default();
-
-            // Deserialize media from JSON
-            let media_json: String = row
-                .get::<_, Option<String>>(20)?
-                .unwrap_or_else(|| "[]".to_string());
-            let media: Vec<crate::models::MediaAttachment> =
-                serde_json::from_str(&media_json).unwrap_or_default();
-
-            Ok(Post {
-                id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
-                network_id: row.get(1)?,
-                network,
-                author_handle: row.get(3)?,
-                author_name: row.get(4)?,
-                author_avatar: row.get(5)?,
-                content: row.get(6)?,
-                content_raw: row.get(7)?,
-                created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(8)?)
-                    .unwrap()
-                    .with_timezone(&Utc),
-                url: row.get(9)?,
-                is_repost: row.get::<_, i32>(10)? != 0,
-                repost_author: row.get(11)?,
-                like_count: row.get(12)?,
-                repost_count: row.get(13)?,
-                reply_count: row.get(14)?,
-                liked: row.get::<_, i32>(15)? != 0,
-                reposted: row.get::<_, i32>(16)? != 0,
-                reply_to_id: row.get(17)?,
-                media,
-                cid: row.get(18)?,
-                uri: row.get(19)?,
-            })
-        })?;
+    /// Every cached post, optionally only one network's, most recent first.
+    ///
+    /// A post cached in several feeds is only returned once.
+    pub fn export_posts(&self, network: Option<Network>) -> Result<Vec<Post>> {
+        let network = network.map(|net| format!("{net:?}").to_lowercase());
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {POST_COLUMNS} FROM post_cache
+             WHERE ?1 IS NULL OR network = ?1
+             GROUP BY network, network_id
+             ORDER BY created_at DESC"
+        ))?;
+        let posts = stmt.query_map(params![network], post_from_row)?;
+        posts.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// Full-text search over cached posts (most recent first).
+    ///
+    /// Words match by prefix, `"quoted phrases"` match exactly, and
+    /// `author:name` only matches the author's handle or display name.
+    pub fn search_cached(&self, query: &str, limit: usize) -> Result<Vec<Post>> {
+        let Some(expr) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
 
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {POST_COLUMNS} FROM post_cache
+             WHERE id IN (SELECT post_id FROM post_search WHERE post_search MATCH ?1)
+                 AND {NOT_MODERATED}
+             GROUP BY network, network_id
+             ORDER BY created_at DESC LIMIT ?2"
+        ))?;
+        let posts = stmt.query_map(params![expr, i64::try_from(limit)?], post_from_row)?;
         posts.collect::<Result<Vec<_>, _>>().map_err(Into::into)
     }
 
+    /// Keep only the newest `max_posts` cached posts of each network,
+    /// returning how many were removed.
+    ///
+    /// Large trims are followed by a `VACUUM` so the file actually shrinks.
+    pub fn trim_cache(&self, max_posts: usize) -> Result<usize> {
+        let removed = self.conn.execute(
+            "DELETE FROM post_cache WHERE id IN (
+                 SELECT id FROM (
+                     SELECT id, ROW_NUMBER() OVER (
+                         PARTITION BY network ORDER BY created_at DESC, cached_at DESC
+                     ) AS position
+                     FROM post_cache
+                 ) WHERE position > ?1
+             )",
+            params![i64::try_from(max_posts)?],
+        )?;
+        if removed >= VACUUM_THRESHOLD {
+            self.conn.execute_batch("VACUUM")?;
+        }
+        Ok(removed)
+    }
+
     /// Clear old cache entries
     pub fn clear_old_cache(&self, max_age_hours: u64) -> Result<usize> {
         let cutoff = Utc::now() - chrono::Duration::hours(max_age_hours as i64);
@@ -397,8 +1019,10 @@ impl Database {
     /// Save a scheduled post
     pub fn save_scheduled_post(&self, post: &ScheduledPost) -> Result<()> {
         self.conn.execute(
-            r"INSERT INTO scheduled_posts (id, content, networks, scheduled_for, status, error, created_at)
-               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            &format!(
+                "INSERT INTO scheduled_posts ({SCHEDULED_POST_COLUMNS})
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)"
+            ),
             params![
                 post.id.to_string(),
                 post.content,
@@ -407,6 +1031,11 @@ impl Database {
                 post.status.as_str(),
                 post.error,
                 post.created_at.to_rfc3339(),
+                post.recurrence.as_ref().map(ToString::to_string),
+                post.retry_count,
+                post.next_retry_at.map(|at| at.to_rfc3339()),
+                post.posted_networks_str(),
+                post.recurrence_anchor.map(|at| at.to_rfc3339()),
             ],
         )?;
         Ok(())
@@ -414,24 +1043,24 @@ impl Database {
 
     /// Get all scheduled posts (sorted by scheduled time)
     pub fn get_scheduled_posts(&self) -> Result<Vec<ScheduledPost>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, content, networks, scheduled_for, status, error, created_at
-             FROM scheduled_posts ORDER BY scheduled_for ASC",
-        )?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {SCHEDULED_POST_COLUMNS} FROM scheduled_posts ORDER BY scheduled_for ASC"
+        ))?;
 
         let posts = stmt.query_map([], Self::row_to_scheduled_post)?;
         posts.collect::<Result<Vec<_>, _>>().map_err(Into::into)
     }
 
-    /// Get pending scheduled posts that are due
+    /// Get pending scheduled posts that are due, skipping any still waiting
+    /// to retry
     pub fn get_due_scheduled_posts(&self) -> Result<Vec<ScheduledPost>> {
         let now = Utc::now().to_rfc3339();
-        let mut stmt = self.conn.prepare(
-            "SELECT id, content, networks, scheduled_for, status, error, created_at
-             FROM scheduled_posts 
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {SCHEDULED_POST_COLUMNS} FROM scheduled_posts
              WHERE status = 'pending' AND scheduled_for <= ?1
-             ORDER BY scheduled_for ASC",
-        )?;
+               AND (next_retry_at IS NULL OR next_retry_at <= ?1)
+             ORDER BY scheduled_for ASC"
+        ))?;
 
         let posts = stmt.query_map(params![now], Self::row_to_scheduled_post)?;
         posts.collect::<Result<Vec<_>, _>>().map_err(Into::into)
@@ -439,12 +1068,11 @@ impl Database {
 
     /// Get pending scheduled posts (not yet posted)
     pub fn get_pending_scheduled_posts(&self) -> Result<Vec<ScheduledPost>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, content, networks, scheduled_for, status, error, created_at
-             FROM scheduled_posts 
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {SCHEDULED_POST_COLUMNS} FROM scheduled_posts
              WHERE status = 'pending'
-             ORDER BY scheduled_for ASC",
-        )?;
+             ORDER BY scheduled_for ASC"
+        ))?;
 
         let posts = stmt.query_map([], Self::row_to_scheduled_post)?;
         posts.collect::<Result<Vec<_>, _>>().map_err(Into::into)
@@ -464,6 +1092,42 @@ impl Database {
         Ok(())
     }
 
+    /// Move a repeating post on to its next occurrence, pending again
+    pub fn reschedule_post(&self, id: Uuid, scheduled_for: DateTime<Utc>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE scheduled_posts SET status = 'pending', error = NULL, scheduled_for = ?2,
+                retry_count = 0, next_retry_at = NULL, posted_networks = '',
+                recurrence_anchor = COALESCE(recurrence_anchor, scheduled_for)
+             WHERE id = ?1",
+            params![id.to_string(), scheduled_for.to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Save changes to a scheduled post: its content, networks and time
+    /// as well as its status and retry state
+    pub fn update_scheduled_post(&self, post: &ScheduledPost) -> Result<()> {
+        self.conn.execute(
+            "UPDATE scheduled_posts SET status = ?2, error = ?3, scheduled_for = ?4,
+                retry_count = ?5, next_retry_at = ?6, posted_networks = ?7,
+                content = ?8, networks = ?9, recurrence_anchor = ?10
+             WHERE id = ?1",
+            params![
+                post.id.to_string(),
+                post.status.as_str(),
+                post.error,
+                post.scheduled_for.to_rfc3339(),
+                post.retry_count,
+                post.next_retry_at.map(|at| at.to_rfc3339()),
+                post.posted_networks_str(),
+                post.content,
+                post.networks_str(),
+                post.recurrence_anchor.map(|at| at.to_rfc3339()),
+            ],
+        )?;
+        Ok(())
+    }
+
     /// Delete a scheduled post
     pub fn delete_scheduled_post(&self, id: Uuid) -> Result<()> {
         self.conn.execute(
@@ -478,10 +1142,11 @@ impl Database {
         self.update_scheduled_post_status(id, ScheduledPostStatus::Cancelled, None)
     }
 
-    /// Helper to convert a row to `ScheduledPost`
+    /// Helper to convert a row selected with `SCHEDULED_POST_COLUMNS`
     fn row_to_scheduled_post(row: &rusqlite::Row<'_>) -> rusqlite::Result<ScheduledPost> {
         let networks_str: String = row.get(2)?;
         let status_str: String = row.get(4)?;
+        let posted_str: String = row.get(10)?;
 
         Ok(ScheduledPost {
             id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
@@ -490,11 +1155,24 @@ impl Database {
             scheduled_for: DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
                 .unwrap()
                 .with_timezone(&Utc),
+            recurrence: row
+                .get::<_, Option<String>>(7)?
+                .and_then(|r| Recurrence::parse(&r).ok()),
             status: ScheduledPostStatus::from_str(&status_str).unwrap_or_default(),
             error: row.get(5)?,
             created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
                 .unwrap()
                 .with_timezone(&Utc),
+            retry_count: row.get(8)?,
+            next_retry_at: row
+                .get::<_, Option<String>>(9)?
+                .and_then(|at| DateTime::parse_from_rfc3339(&at).ok())
+                .map(|at| at.with_timezone(&Utc)),
+            posted_networks: ScheduledPost::networks_from_str(&posted_str),
+            recurrence_anchor: row
+                .get::<_, Option<String>>(11)?
+                .and_then(|at| DateTime::parse_from_rfc3339(&at).ok())
+                .map(|at| at.with_timezone(&Utc)),
         })
     }
 
@@ -512,6 +1190,7 @@ impl Database {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::ServerSoftware;
     use tempfile::tempdir;
 
     #[test]
@@ -522,6 +1201,77 @@ mod tests {
         // Should create without error
     }
 
+    #[test]
+    fn test_migrates_unversioned_database() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+
+        // A database from before migrations: no user_version, missing columns
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                r"
+                CREATE TABLE accounts (
+                    id TEXT PRIMARY KEY, network TEXT NOT NULL, display_name TEXT NOT NULL,
+                    handle TEXT NOT NULL, server TEXT NOT NULL,
+                    is_default INTEGER NOT NULL DEFAULT 0, avatar_url TEXT,
+                    created_at TEXT NOT NULL, last_used_at TEXT
+                );
+                CREATE TABLE drafts (
+                    id TEXT PRIMARY KEY, content TEXT NOT NULL, networks TEXT NOT NULL,
+                    reply_to_id TEXT, reply_to_network TEXT,
+                    created_at TEXT NOT NULL, updated_at TEXT NOT NULL
+                );
+                CREATE TABLE post_cache (
+                    id TEXT PRIMARY KEY, network_id TEXT NOT NULL, network TEXT NOT NULL,
+                    author_handle TEXT NOT NULL, author_name TEXT NOT NULL, author_avatar TEXT,
+                    content TEXT NOT NULL, content_raw TEXT, created_at TEXT NOT NULL, url TEXT,
+                    is_repost INTEGER NOT NULL DEFAULT 0, repost_author TEXT,
+                    like_count INTEGER NOT NULL DEFAULT 0, repost_count INTEGER NOT NULL DEFAULT 0,
+                    reply_count INTEGER NOT NULL DEFAULT 0, liked INTEGER NOT NULL DEFAULT 0,
+                    reposted INTEGER NOT NULL DEFAULT 0, reply_to_id TEXT, cid TEXT, uri TEXT,
+                    media_json TEXT DEFAULT '[]', cached_at TEXT NOT NULL,
+                    UNIQUE(network, network_id)
+                );
+                ",
+            )
+            .unwrap();
+
+            let account = Account::new_bluesky("old.bsky.social", "Old");
+            conn.execute(
+                "INSERT INTO accounts (id, network, display_name, handle, server, created_at)
+                 VALUES (?1, 'bluesky', 'Old', 'old.bsky.social', '', ?2)",
+                params![account.id.to_string(), Utc::now().to_rfc3339()],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO post_cache (id, network_id, network, author_handle, author_name,
+                                         content, created_at, cached_at)
+                 VALUES (?1, 'p1', 'bluesky', 'old.bsky.social', 'Old', 'hello', ?2, ?2)",
+                params![Uuid::new_v4().to_string(), Utc::now().to_rfc3339()],
+            )
+            .unwrap();
+        }
+
+        let db = Database::open_path(&path).unwrap();
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+
+        // Existing rows survive and the new columns are usable
+        assert_eq!(db.get_accounts().unwrap()[0].handle, "old.bsky.social");
+        let posts = db.get_cached_posts(None, 10).unwrap();
+        assert_eq!(posts.len(), 1);
+        assert!(!posts[0].bookmarked);
+        assert_eq!(db.search_cached("hello", 10).unwrap().len(), 1);
+        db.save_draft(&Draft::new("draft", vec![Network::Bluesky], None))
+            .unwrap();
+
+        // Reopening is a no-op
+        drop(db);
+        let db = Database::open_path(&path).unwrap();
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+        assert_eq!(db.get_drafts().unwrap().len(), 1);
+    }
+
     #[test]
     fn test_account_crud() {
         let dir = tempdir().unwrap();
@@ -542,4 +1292,339 @@ mod tests {
         let accounts = db.get_accounts().unwrap();
         assert!(accounts.is_empty());
     }
+
+    #[test]
+    fn test_default_account_fallback() {
+        let db = Database::open_in_memory().unwrap();
+        let account = Account::new_mastodon("test", "https://mastodon.social", "Test User");
+        db.insert_account(&account).unwrap();
+
+        // With none marked default, the network's only account stands in
+        let default = db.get_default_account(Network::Mastodon).unwrap();
+        assert_eq!(default.map(|a| a.id), Some(account.id));
+        assert!(db.get_default_account(Network::Bluesky).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_account_post_char_limit() {
+        let db = Database::open_in_memory().unwrap();
+        let account = Account::new_mastodon("test", "https://mastodon.social", "Test User");
+        db.insert_account(&account).unwrap();
+        assert_eq!(db.get_accounts().unwrap()[0].post_char_limit(), 500);
+
+        // Store the server's limit
+        db.update_account_max_post_chars(account.id, 5000).unwrap();
+        let accounts = db.get_accounts().unwrap();
+        assert_eq!(accounts[0].max_post_chars, Some(5000));
+        assert_eq!(accounts[0].post_char_limit(), 5000);
+    }
+
+    #[test]
+    fn test_account_server_info_roundtrip() {
+        let db = Database::open_in_memory().unwrap();
+        let account = Account::new_mastodon("test", "https://mastodon.social", "Test User");
+        db.insert_account(&account).unwrap();
+        db.update_account_max_post_chars(account.id, 5000).unwrap();
+
+        let mut info = Account::new_mastodon("test", "https://mastodon.social", "Test User");
+        info.server_version = Some("2.7.2 (compatible; Akkoma 3.13.2)".to_string());
+        info.max_image_bytes = Some(16_000_000);
+        db.update_account_server_info(account.id, &info).unwrap();
+        let accounts = db.get_accounts().unwrap();
+        assert_eq!(accounts[0].server_software(), Some(ServerSoftware::Akkoma));
+        assert_eq!(
+            accounts[0].software_label().as_deref(),
+            Some("Akkoma 3.13.2")
+        );
+        assert_eq!(accounts[0].max_image_bytes, Some(16_000_000));
+        // What the server didn't report this time is kept
+        assert_eq!(accounts[0].max_post_chars, Some(5000));
+    }
+
+    #[test]
+    fn test_recurring_scheduled_post() {
+        let dir = tempdir().unwrap();
+        let db = Database::open_path(&dir.path().join("test.sqlite")).unwrap();
+
+        let mut post = ScheduledPost::new("Standup", vec![Network::Mastodon], Utc::now());
+        post.recurrence = Some(Recurrence::parse("0 9 * * 1-5").unwrap());
+        db.save_scheduled_post(&post).unwrap();
+        db.update_scheduled_post_status(post.id, ScheduledPostStatus::Posting, None)
+            .unwrap();
+
+        let next = Utc::now() + chrono::Duration::days(1);
+        db.reschedule_post(post.id, next).unwrap();
+
+        let pending = db.get_pending_scheduled_posts().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].recurrence, post.recurrence);
+        assert_eq!(pending[0].scheduled_for.timestamp(), next.timestamp());
+        // The first occurrence is kept to take the wall-clock time from
+        let anchor = pending[0].recurrence_anchor.unwrap();
+        assert_eq!(anchor.timestamp(), post.scheduled_for.timestamp());
+
+        db.reschedule_post(post.id, next + chrono::Duration::days(1))
+            .unwrap();
+        let pending = db.get_pending_scheduled_posts().unwrap();
+        assert_eq!(pending[0].recurrence_anchor, Some(anchor));
+    }
+
+    #[test]
+    fn test_scheduled_post_waits_to_retry() {
+        let dir = tempdir().unwrap();
+        let db = Database::open_path(&dir.path().join("test.sqlite")).unwrap();
+
+        let mut post = ScheduledPost::new(
+            "Hello",
+            vec![Network::Mastodon, Network::Bluesky],
+            Utc::now() - chrono::Duration::minutes(1),
+        );
+        db.save_scheduled_post(&post).unwrap();
+        assert_eq!(db.get_due_scheduled_posts().unwrap().len(), 1);
+
+        post.posted_networks.push(Network::Mastodon);
+        post.record_failure("timed out".to_string(), Utc::now());
+        db.update_scheduled_post(&post).unwrap();
+        assert!(db.get_due_scheduled_posts().unwrap().is_empty());
+
+        let pending = db.get_pending_scheduled_posts().unwrap();
+        assert_eq!(pending[0].retry_count, 1);
+        assert_eq!(pending[0].posted_networks, vec![Network::Mastodon]);
+        assert_eq!(pending[0].error.as_deref(), Some("timed out"));
+        assert!(pending[0].next_retry_at.is_some());
+
+        let mut edited = pending[0].clone();
+        edited
+            .edit(
+                Some("Hello again".to_string()),
+                Some(vec![Network::Bluesky]),
+                None,
+            )
+            .unwrap();
+        db.update_scheduled_post(&edited).unwrap();
+        let pending = db.get_pending_scheduled_posts().unwrap();
+        assert_eq!(pending[0].content, "Hello again");
+        assert_eq!(pending[0].networks, vec![Network::Bluesky]);
+        assert!(pending[0].posted_networks.is_empty());
+    }
+
+    #[test]
+    fn test_cache_post_keeps_bookmark() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let mut post = Post::new(Network::Mastodon, "123");
+        post.bookmarked = true;
+        post.owner_account_id = Some(Uuid::new_v4());
+        db.cache_post(&post).unwrap();
+
+        let cached = db.get_cached_posts(None, 10).unwrap();
+        assert_eq!(cached.len(), 1);
+        assert!(cached[0].bookmarked);
+        assert_eq!(cached[0].owner_account_id, post.owner_account_id);
+    }
+
+    #[test]
+    fn test_cache_post_keeps_quote() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let mut quoted = Post::new(Network::Bluesky, "quoted");
+        quoted.content = "Original".to_string();
+        let mut post = Post::new(Network::Bluesky, "quoting");
+        post.quote = Some(Box::new(quoted));
+        db.cache_post(&post).unwrap();
+
+        let cached = db.get_cached_posts(None, 10).unwrap();
+        let quote = cached[0].quote.as_ref().unwrap();
+        assert_eq!(quote.network_id, "quoted");
+        assert_eq!(quote.content, "Original");
+    }
+
+    #[test]
+    fn test_moderated_posts_are_hidden() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let mut post = Post::new(Network::Mastodon, "1");
+        post.author_handle = "Troll@example.social".to_string();
+        db.cache_post(&post).unwrap();
+        assert_eq!(db.get_cached_posts(None, 10).unwrap().len(), 1);
+
+        let muted =
+            ModeratedAccount::new(Network::Mastodon, "@troll@example.social", Moderation::Mute);
+        db.add_moderated(&muted).unwrap();
+        assert_eq!(db.get_moderated().unwrap(), [muted]);
+        assert!(db.get_cached_posts(None, 10).unwrap().is_empty());
+
+        // Unmuting one network doesn't touch the other
+        assert!(
+            !db.remove_moderated(Network::Bluesky, "troll@example.social", Moderation::Mute)
+                .unwrap()
+        );
+        assert!(
+            db.remove_moderated(Network::Mastodon, "troll@example.social", Moderation::Mute)
+                .unwrap()
+        );
+        assert_eq!(db.get_cached_posts(None, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_draft_crud() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let reply_to = Post::new(Network::Bluesky, "abc");
+        let mut draft = Draft::new("Hello", vec![Network::Bluesky], Some(reply_to));
+        db.save_draft(&draft).unwrap();
+
+        // Saving again updates the same draft
+        draft.content = "Hello, world".to_string();
+        db.save_draft(&draft).unwrap();
+
+        let drafts = db.get_drafts().unwrap();
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(drafts[0].content, "Hello, world");
+        assert_eq!(drafts[0].networks, vec![Network::Bluesky]);
+        assert_eq!(drafts[0].reply_to.as_ref().unwrap().network_id, "abc");
+
+        db.delete_draft(draft.id).unwrap();
+        assert!(db.get_drafts().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_cached() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let mut rust = Post::new(Network::Mastodon, "1");
+        rust.content = "Learning Rust with ratatui today".to_string();
+        rust.author_handle = "alice@mastodon.social".to_string();
+        rust.author_name = "Alice".to_string();
+        let mut other = Post::new(Network::Bluesky, "2");
+        other.content = "Rust is a kind of fungus".to_string();
+        other.author_handle = "bob.bsky.social".to_string();
+        other.author_name = "Bob".to_string();
+        db.cache_post(&rust).unwrap();
+        db.cache_post(&other).unwrap();
+
+        assert_eq!(db.search_cached("rus", 10).unwrap().len(), 2);
+        assert_eq!(db.search_cached("\"rust with\"", 10).unwrap().len(), 1);
+        assert_eq!(db.search_cached("\"rust is fungus\"", 10).unwrap().len(), 0);
+
+        let by_bob = db.search_cached("author:@bob rust", 10).unwrap();
+        assert_eq!(by_bob.len(), 1);
+        assert_eq!(by_bob[0].network_id, "2");
+
+        // Re-caching replaces the indexed text; removing drops it
+        rust.id = Uuid::new_v4();
+        rust.content = "Now about gardening".to_string();
+        db.cache_post(&rust).unwrap();
+        assert_eq!(db.search_cached("rust", 10).unwrap().len(), 1);
+        assert_eq!(db.search_cached("gardening", 10).unwrap().len(), 1);
+
+        // Muted and blocked authors are left out, as in the feeds
+        let blocked = ModeratedAccount::new(Network::Bluesky, "bob.bsky.social", Moderation::Block);
+        db.add_moderated(&blocked).unwrap();
+        assert!(db.search_cached("fungus", 10).unwrap().is_empty());
+        db.remove_moderated(Network::Bluesky, "bob.bsky.social", Moderation::Block)
+            .unwrap();
+        assert_eq!(db.search_cached("fungus", 10).unwrap().len(), 1);
+
+        db.remove_cached_post(Network::Bluesky, "2").unwrap();
+        assert!(db.search_cached("fungus", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_trim_cache_keeps_newest() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let now = Utc::now();
+        for i in 0..5 {
+            let mut post = Post::new(Network::Mastodon, &i.to_string());
+            post.created_at = now - chrono::Duration::minutes(i);
+            db.cache_post(&post).unwrap();
+        }
+        db.cache_post(&Post::new(Network::Bluesky, "old")).unwrap();
+
+        assert_eq!(db.trim_cache(3).unwrap(), 2);
+        let kept: Vec<_> = db
+            .get_cached_posts(Some(Network::Mastodon), 10)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.network_id)
+            .collect();
+        assert_eq!(kept, ["0", "1", "2"]);
+        // Each network has its own allowance
+        assert_eq!(
+            db.get_cached_posts(Some(Network::Bluesky), 10)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(db.trim_cache(3).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_export_posts() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let mut tagged = Post::new(Network::Mastodon, "1");
+        db.cache_post(&tagged).unwrap();
+        tagged.id = Uuid::new_v4();
+        db.cache_feed_post("#rust", &tagged).unwrap();
+        db.cache_post(&Post::new(Network::Bluesky, "2")).unwrap();
+
+        // Once per post, however many feeds it's in
+        assert_eq!(db.export_posts(None).unwrap().len(), 2);
+        let mastodon = db.export_posts(Some(Network::Mastodon)).unwrap();
+        assert_eq!(mastodon.len(), 1);
+        assert_eq!(mastodon[0].network_id, "1");
+    }
+
+    #[test]
+    fn test_feeds_are_cached_separately() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let mut post = Post::new(Network::Mastodon, "1");
+        post.content = "Hello #rust".to_string();
+        db.cache_post(&post).unwrap();
+
+        // The same post fetched again for a hashtag feed
+        let tagged = Post {
+            id: Uuid::new_v4(),
+            ..post.clone()
+        };
+        db.cache_feed_post("#rust", &tagged).unwrap();
+
+        assert_eq!(db.get_cached_posts(None, 10).unwrap()[0].id, post.id);
+        let feed = db.get_feed_posts("#rust", None, 10).unwrap();
+        assert_eq!(feed.len(), 1);
+        assert_eq!(feed[0].id, tagged.id);
+        assert_eq!(db.search_cached("hello", 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_fts_query() {
+        assert_eq!(fts_query("  "), None);
+        assert_eq!(fts_query("rust tui").unwrap(), "\"rust\"* AND \"tui\"*");
+        assert_eq!(fts_query("\"hello world\"").unwrap(), "\"hello world\"");
+        assert_eq!(
+            fts_query("author:@alice").unwrap(),
+            "{author_handle author_name} : \"alice\"*"
+        );
+        // FTS syntax in the input is matched literally
+        assert_eq!(fts_query("a*b:c").unwrap(), "\"a*b:c\"*");
+    }
 }
@@ -18,6 +18,11 @@ pub fn demo_accounts() -> Vec<Account> {
             avatar_url: None,
             created_at: Utc::now(),
             last_used_at: Some(Utc::now()),
+            max_post_chars: Some(500),
+            server_version: Some("4.3.1".to_string()),
+            server_name: Some("Fosstodon".to_string()),
+            max_media_attachments: Some(4),
+            max_image_bytes: Some(16_777_216),
         },
         Account {
             id: Uuid::new_v4(),
@@ -29,6 +34,11 @@ pub fn demo_accounts() -> Vec<Account> {
             avatar_url: None,
             created_at: Utc::now(),
             last_used_at: Some(Utc::now()),
+            max_post_chars: None,
+            server_version: None,
+            server_name: Some("did:web:bsky.social".to_string()),
+            max_media_attachments: Some(4),
+            max_image_bytes: Some(1_000_000),
         },
     ]
 }
@@ -47,6 +57,7 @@ pub fn demo_posts() -> Vec<Post> {
             author_avatar: None,
             content: "Mastodon 4.3 is here! 🎉 This release brings improved quote posts, better search, and a refreshed web interface. Thank you to all contributors!".to_string(),
             content_raw: None,
+            spoiler_text: None,
             created_at: now - Duration::minutes(5),
             url: Some("https://mastodon.social/@gargron/123".to_string()),
             is_repost: false,
@@ -56,6 +67,7 @@ pub fn demo_posts() -> Vec<Post> {
             reply_count: 342,
             liked: true,
             reposted: false,
+            bookmarked: false,
             reply_to_id: None,
             media: vec![
                 MediaAttachment {
@@ -63,10 +75,14 @@ pub fn demo_posts() -> Vec<Post> {
                     preview_url: Some("https://files.mastodon.social/media_attachments/files/123/456/789/small/mastodon43.png".to_string()),
                     media_type: MediaType::Image,
                     alt_text: Some("Mastodon 4.3 release banner showing new features".to_string()),
+                    blurhash: Some("UBL_:rOpGG-oBUNG,qRj2so|=eE1w^n4S5NH".to_string()),
                 },
             ],
             cid: None,
             uri: None,
+            quote: None,
+            link_card: None,
+            owner_account_id: None,
         },
         Post {
             id: Uuid::new_v4(),
@@ -77,6 +93,7 @@ pub fn demo_posts() -> Vec<Post> {
             author_avatar: None,
             content: "The AT Protocol is designed from the ground up to enable a more open and decentralized social web. Excited to see what builders create with it! 🦋".to_string(),
             content_raw: None,
+            spoiler_text: None,
             created_at: now - Duration::minutes(15),
             url: Some("https://bsky.app/profile/jay.bsky.team/post/abc".to_string()),
             is_repost: false,
@@ -86,10 +103,14 @@ pub fn demo_posts() -> Vec<Post> {
             reply_count: 89,
             liked: false,
             reposted: true,
+            bookmarked: false,
             reply_to_id: None,
             media: Vec::new(),
             cid: Some("bafyrei...".to_string()),
             uri: Some("at://did:plc:abc/app.bsky.feed.post/123".to_string()),
+            quote: None,
+            link_card: None,
+            owner_account_id: None,
         },
         Post {
             id: Uuid::new_v4(),
@@ -100,6 +121,7 @@ pub fn demo_posts() -> Vec<Post> {
             author_avatar: None,
             content: "TIL: You can use `tail -f` with multiple files:\n\n$ tail -f /var/log/*.log\n\nWatches all log files simultaneously. The output shows which file each line comes from. 🐧".to_string(),
             content_raw: None,
+            spoiler_text: None,
             created_at: now - Duration::minutes(32),
             url: Some("https://mastodon.social/@nixCraft/456".to_string()),
             is_repost: false,
@@ -109,10 +131,14 @@ pub fn demo_posts() -> Vec<Post> {
             reply_count: 34,
             liked: false,
             reposted: false,
+            bookmarked: false,
             reply_to_id: None,
             media: Vec::new(),
             cid: None,
             uri: None,
+            quote: None,
+            link_card: None,
+            owner_account_id: None,
         },
         Post {
             id: Uuid::new_v4(),
@@ -123,6 +149,7 @@ pub fn demo_posts() -> Vec<Post> {
             author_avatar: None,
             content: "Just published my new Rust crate for building TUI applications! Check it out: https://crates.io/crates/awesome-tui 🦀✨".to_string(),
             content_raw: None,
+            spoiler_text: None,
             created_at: now - Duration::hours(1),
             url: Some("https://bsky.app/profile/samuel.bsky.social/post/def".to_string()),
             is_repost: false,
@@ -132,10 +159,14 @@ pub fn demo_posts() -> Vec<Post> {
             reply_count: 18,
             liked: true,
             reposted: false,
+            bookmarked: false,
             reply_to_id: None,
             media: Vec::new(),
             cid: Some("bafyrei...".to_string()),
             uri: Some("at://did:plc:def/app.bsky.feed.post/456".to_string()),
+            quote: None,
+            link_card: None,
+            owner_account_id: None,
         },
         Post {
             id: Uuid::new_v4(),
@@ -146,6 +177,7 @@ pub fn demo_posts() -> Vec<Post> {
             author_avatar: None,
             content: "What's your favorite terminal emulator and why? 💻\n\nI'll go first: Alacritty for its speed, though Kitty's image support is tempting...".to_string(),
             content_raw: None,
+            spoiler_text: None,
             created_at: now - Duration::hours(2),
             url: Some("https://mastodon.cloud/@ThePracticalDev/789".to_string()),
             is_repost: false,
@@ -155,10 +187,14 @@ pub fn demo_posts() -> Vec<Post> {
             reply_count: 156,
             liked: false,
             reposted: false,
+            bookmarked: false,
             reply_to_id: None,
             media: Vec::new(),
             cid: None,
             uri: None,
+            quote: None,
+            link_card: None,
+            owner_account_id: None,
         },
         Post {
             id: Uuid::new_v4(),
@@ -169,6 +205,7 @@ pub fn demo_posts() -> Vec<Post> {
             author_avatar: None,
             content: "Hot take: The best code is the code you don't write.\n\nThe second best code is the code that's boring and obvious.\n\n🌶️".to_string(),
             content_raw: None,
+            spoiler_text: None,
             created_at: now - Duration::hours(3),
             url: Some("https://bsky.app/profile/cassidoo.bsky.social/post/ghi".to_string()),
             is_repost: false,
@@ -178,10 +215,14 @@ pub fn demo_posts() -> Vec<Post> {
             reply_count: 123,
             liked: false,
             reposted: false,
+            bookmarked: false,
             reply_to_id: None,
             media: Vec::new(),
             cid: Some("bafyrei...".to_string()),
             uri: Some("at://did:plc:ghi/app.bsky.feed.post/789".to_string()),
+            quote: None,
+            link_card: None,
+            owner_account_id: None,
         },
         Post {
             id: Uuid::new_v4(),
@@ -192,6 +233,7 @@ pub fn demo_posts() -> Vec<Post> {
             author_avatar: None,
             content: "New blog post: \"Why async Rust is actually good, actually\"\n\nIn which I argue that Pin is not that scary and lifetimes make sense if you squint hard enough. 🦀📝".to_string(),
             content_raw: None,
+            spoiler_text: None,
             created_at: now - Duration::hours(5),
             url: Some("https://hachyderm.io/@fasterthanlime/101".to_string()),
             is_repost: false,
@@ -201,10 +243,14 @@ pub fn demo_posts() -> Vec<Post> {
             reply_count: 78,
             liked: true,
             reposted: true,
+            bookmarked: false,
             reply_to_id: None,
             media: Vec::new(),
             cid: None,
             uri: None,
+            quote: None,
+            link_card: None,
+            owner_account_id: None,
         },
     ]
 }
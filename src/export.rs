@@ -0,0 +1,121 @@
+//! Exporting cached posts to JSON or CSV
+//!
+//! JSON keeps whole posts, media and quotes included, in the same shape the
+//! cache stores them. CSV flattens each post to one row of its key fields
+//! for spreadsheets and scripts.
+
+use std::io::Write;
+
+use anyhow::{Result, bail};
+
+use crate::models::Post;
+
+/// Columns of a CSV export
+const CSV_HEADER: [&str; 10] = [
+    "network",
+    "handle",
+    "name",
+    "content",
+    "created_at",
+    "likes",
+    "reposts",
+    "replies",
+    "media",
+    "url",
+];
+
+/// File format of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A JSON array of full posts
+    Json,
+    /// One row of key fields per post
+    Csv,
+}
+
+impl ExportFormat {
+    /// Parse a format name (`json` or `csv`)
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            other => bail!("Unknown export format: {other}\nSupported: json, csv"),
+        }
+    }
+
+    /// Write `posts` to `out` in this format
+    pub fn write(self, posts: &[Post], mut out: impl Write) -> Result<()> {
+        match self {
+            Self::Json => {
+                serde_json::to_writer_pretty(&mut out, posts)?;
+                writeln!(out)?;
+            }
+            Self::Csv => {
+                writeln!(out, "{}", CSV_HEADER.join(","))?;
+                for post in posts {
+                    let row = [
+                        post.network.name().to_string(),
+                        post.author_handle.clone(),
+                        post.author_name.clone(),
+                        post.content.clone(),
+                        post.created_at.to_rfc3339(),
+                        post.like_count.to_string(),
+                        post.repost_count.to_string(),
+                        post.reply_count.to_string(),
+                        post.media.len().to_string(),
+                        post.url.clone().unwrap_or_default(),
+                    ];
+                    let row: Vec<_> = row.iter().map(|field| csv_field(field)).collect();
+                    writeln!(out, "{}", row.join(","))?;
+                }
+            }
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// Quote a CSV field if it needs it (RFC 4180)
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Network;
+
+    #[test]
+    fn test_csv_export() {
+        let mut post = Post::new(Network::Mastodon, "1");
+        post.author_handle = "alice@mastodon.social".to_string();
+        post.content = "Hello, \"world\"\nsecond line".to_string();
+        post.like_count = 3;
+
+        let mut out = Vec::new();
+        ExportFormat::Csv.write(&[post], &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let (header, row) = csv.split_once('\n').unwrap();
+
+        assert_eq!(header, CSV_HEADER.join(","));
+        assert!(row.starts_with("Mastodon,alice@mastodon.social,"));
+        assert!(row.contains(",\"Hello, \"\"world\"\"\nsecond line\","));
+        assert!(row.contains(",3,0,0,0,"));
+    }
+
+    #[test]
+    fn test_json_export_round_trips() {
+        let post = Post::new(Network::Bluesky, "2");
+        let mut out = Vec::new();
+        ExportFormat::Json.write(&[post], &mut out).unwrap();
+
+        let posts: Vec<Post> = serde_json::from_slice(&out).unwrap();
+        assert_eq!(posts[0].network_id, "2");
+        assert!(ExportFormat::parse("xml").is_err());
+        assert_eq!(ExportFormat::parse("CSV").unwrap(), ExportFormat::Csv);
+    }
+}
@@ -0,0 +1,174 @@
+//! Client-side content filters: hide or mark posts matching keywords or
+//! regexes (configured under `[filters]`)
+
+use anyhow::{Context, Result};
+use regex_lite::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::models::Post;
+
+/// The `[filters]` config section
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterConfig {
+    /// Also apply the filters set up on Mastodon accounts
+    #[serde(default)]
+    pub sync_mastodon: bool,
+    /// Patterns to check posts against, in `[[filters.rules]]` tables
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<FilterRule>,
+}
+
+/// One filter pattern and what to do with posts matching it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterRule {
+    /// A word or phrase (matched ignoring case), or a regex written
+    /// between slashes: `/spoilers?/`
+    pub pattern: String,
+    /// Hide matching posts, or just mark them
+    #[serde(default)]
+    pub action: FilterAction,
+}
+
+impl FilterRule {
+    /// Create a rule for a plain keyword
+    pub fn keyword(pattern: impl Into<String>, action: FilterAction) -> Self {
+        Self {
+            pattern: pattern.into(),
+            action,
+        }
+    }
+
+    /// Compile the pattern into a case-insensitive regex
+    fn compile(&self) -> Result<Regex> {
+        let source = match self
+            .pattern
+            .strip_prefix('/')
+            .and_then(|p| p.strip_suffix('/'))
+        {
+            Some(regex) if !regex.is_empty() => regex.to_string(),
+            _ => regex_lite::escape(&self.pattern),
+        };
+        RegexBuilder::new(&source)
+            .case_insensitive(true)
+            .build()
+            .with_context(|| format!("Invalid filter pattern {}", self.pattern))
+    }
+}
+
+/// What happens to a post a filter matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterAction {
+    /// Leave it out of the timeline
+    #[default]
+    Hide,
+    /// Show it collapsed behind a warning, like a content warning
+    Warn,
+}
+
+/// Compiled filters, ready to check posts against
+#[derive(Debug, Clone, Default)]
+pub struct Filters {
+    rules: Vec<(Regex, FilterAction, String)>,
+}
+
+impl Filters {
+    /// Compile `rules`, failing on the first invalid pattern
+    pub fn new(rules: &[FilterRule]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| Ok((rule.compile()?, rule.action, rule.pattern.clone())))
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Whether there is nothing to filter
+    pub const fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The strongest filter matching a post (hiding beats marking), with the
+    /// pattern that matched
+    pub fn check(&self, post: &Post) -> Option<(FilterAction, &str)> {
+        let texts = [
+            Some(post.content.as_str()),
+            post.spoiler_text.as_deref(),
+            post.quote.as_ref().map(|q| q.content.as_str()),
+        ];
+        let mut found = None;
+        for (regex, action, pattern) in &self.rules {
+            if texts.iter().flatten().any(|text| regex.is_match(text)) {
+                if *action == FilterAction::Hide {
+                    return Some((FilterAction::Hide, pattern));
+                }
+                found.get_or_insert((*action, pattern.as_str()));
+            }
+        }
+        found
+    }
+
+    /// Whether a post should be left out
+    pub fn hides(&self, post: &Post) -> bool {
+        matches!(self.check(post), Some((FilterAction::Hide, _)))
+    }
+
+    /// The pattern a post is marked with, if a filter only marks it
+    pub fn warning(&self, post: &Post) -> Option<&str> {
+        match self.check(post) {
+            Some((FilterAction::Warn, pattern)) => Some(pattern),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Network;
+
+    fn post(content: &str) -> Post {
+        let mut post = Post::new(Network::Mastodon, "1");
+        post.content = content.to_string();
+        post
+    }
+
+    #[test]
+    fn test_keywords_ignore_case() {
+        let filters = Filters::new(&[FilterRule::keyword("Finale", FilterAction::Hide)]).unwrap();
+        assert!(filters.hides(&post("no FINALE spoilers please")));
+        assert!(!filters.hides(&post("nothing to see")));
+
+        // Keywords are literal, not regexes
+        let filters = Filters::new(&[FilterRule::keyword("c++", FilterAction::Hide)]).unwrap();
+        assert!(filters.hides(&post("Learning C++ today")));
+        assert!(!filters.hides(&post("Learning C today")));
+    }
+
+    #[test]
+    fn test_regex_patterns() {
+        let filters = Filters::new(&[
+            FilterRule::keyword(r"/\belections?\b/", FilterAction::Warn),
+            FilterRule::keyword("/crypto|nft/", FilterAction::Hide),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            filters.warning(&post("Election night")),
+            Some(r"/\belections?\b/")
+        );
+        assert_eq!(filters.warning(&post("preelection")), None);
+        assert!(filters.hides(&post("New NFT drop")));
+        // Hiding wins when both match
+        assert!(filters.hides(&post("Elections and crypto")));
+
+        assert!(Filters::new(&[FilterRule::keyword("/(/", FilterAction::Hide)]).is_err());
+    }
+
+    #[test]
+    fn test_content_warnings_are_checked() {
+        let filters = Filters::new(&[FilterRule::keyword("spoiler", FilterAction::Hide)]).unwrap();
+        let mut post = post("It was him all along");
+        post.spoiler_text = Some("Spoilers for the movie".to_string());
+        assert!(filters.hides(&post));
+    }
+}
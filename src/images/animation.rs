@@ -0,0 +1,177 @@
+//! Animated GIFs, decoded into frames that play in the detail panel.
+//!
+//! Frames are shrunk and their number capped so a long GIF can't use up
+//! memory, and frames shorter than [`MIN_FRAME_DELAY`] are slowed down to
+//! it, which keeps redrawing (and re-encoding for the terminal) in check.
+
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+use image::codecs::gif::GifDecoder;
+use image::{AnimationDecoder, DynamicImage, ImageFormat};
+use ratatui_image::protocol::StatefulProtocol;
+
+/// Most frames kept from one animation
+const MAX_FRAMES: usize = 100;
+
+/// Most memory the decoded frames of one animation may take, in bytes
+const MAX_BYTES: usize = 32 * 1024 * 1024;
+
+/// Largest width or height of a frame; animations show smaller than stills
+const MAX_DIMENSION: u32 = 400;
+
+/// Shortest time a frame is shown (at most 10 frames per second)
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// The frames of an animated GIF and how long each one shows
+#[derive(Debug, Clone)]
+pub struct Animation {
+    frames: Vec<(DynamicImage, Duration)>,
+    duration: Duration,
+}
+
+impl Animation {
+    /// Decode an animated GIF. `None` for anything else, including GIFs
+    /// with a single frame.
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        if image::guess_format(bytes).ok()? != ImageFormat::Gif {
+            return None;
+        }
+
+        let mut frames = Vec::new();
+        let mut size = 0;
+        for frame in GifDecoder::new(Cursor::new(bytes)).ok()?.into_frames() {
+            let frame = frame.ok()?;
+            let delay = Duration::from(frame.delay()).max(MIN_FRAME_DELAY);
+            let image = DynamicImage::ImageRgba8(frame.into_buffer());
+            let image = if image.width() > MAX_DIMENSION || image.height() > MAX_DIMENSION {
+                image.thumbnail(MAX_DIMENSION, MAX_DIMENSION)
+            } else {
+                image
+            };
+
+            size += image.as_bytes().len();
+            if frames.len() == MAX_FRAMES || size > MAX_BYTES {
+                break;
+            }
+            frames.push((image, delay));
+        }
+        if frames.len() < 2 {
+            return None;
+        }
+
+        let duration = frames.iter().map(|(_, delay)| *delay).sum();
+        Some(Self { frames, duration })
+    }
+
+    /// Number of frames
+    pub const fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether there are no frames (never true for a decoded animation)
+    pub const fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The first frame, shown where animations are turned off
+    pub fn first_frame(&self) -> &DynamicImage {
+        &self.frames[0].0
+    }
+
+    /// Which frame shows `elapsed` after the animation started, looping
+    pub fn frame_at(&self, elapsed: Duration) -> usize {
+        if self.duration.is_zero() {
+            return 0;
+        }
+        let mut offset = Duration::from_nanos(
+            u64::try_from(elapsed.as_nanos() % self.duration.as_nanos()).unwrap_or_default(),
+        );
+        for (index, (_, delay)) in self.frames.iter().enumerate() {
+            if offset < *delay {
+                return index;
+            }
+            offset -= *delay;
+        }
+        self.frames.len() - 1
+    }
+}
+
+/// An animation being played, with the terminal encoding of each frame
+/// made the first time it shows
+pub struct AnimatedImage {
+    animation: Animation,
+    started: Instant,
+    protocols: Vec<Option<StatefulProtocol>>,
+}
+
+impl AnimatedImage {
+    /// Start playing an animation from its first frame
+    pub fn new(animation: Animation) -> Self {
+        let protocols = std::iter::repeat_with(|| None)
+            .take(animation.len())
+            .collect();
+        Self {
+            animation,
+            started: Instant::now(),
+            protocols,
+        }
+    }
+
+    /// The frame to draw now, ready for rendering
+    pub fn current_protocol(&mut self) -> Option<&mut StatefulProtocol> {
+        let index = self.animation.frame_at(self.started.elapsed());
+        let picker = super::picker()?;
+        let (image, _) = self.animation.frames.get(index)?;
+        let protocol = self.protocols.get_mut(index)?;
+        Some(protocol.get_or_insert_with(|| picker.new_resize_protocol(image.clone())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::gif::GifEncoder;
+    use image::{Delay, Frame, RgbaImage};
+
+    fn gif(delays_ms: &[u32]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut bytes);
+            for (i, ms) in delays_ms.iter().enumerate() {
+                let pixel = image::Rgba([u8::try_from(i * 60).unwrap(), 0, 0, 255]);
+                let frame = Frame::from_parts(
+                    RgbaImage::from_pixel(4, 4, pixel),
+                    0,
+                    0,
+                    Delay::from_numer_denom_ms(*ms, 1),
+                );
+                encoder.encode_frame(frame).unwrap();
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_decode_and_timing() {
+        let animation = Animation::decode(&gif(&[200, 300, 0])).unwrap();
+        assert_eq!(animation.len(), 3);
+        assert_eq!(animation.first_frame().width(), 4);
+
+        let ms = Duration::from_millis;
+        assert_eq!(animation.frame_at(ms(0)), 0);
+        assert_eq!(animation.frame_at(ms(199)), 0);
+        assert_eq!(animation.frame_at(ms(200)), 1);
+        // A zero delay still shows for the minimum time
+        assert_eq!(animation.frame_at(ms(550)), 2);
+        // and then it loops
+        assert_eq!(animation.frame_at(ms(600)), 0);
+        assert_eq!(animation.frame_at(ms(850)), 1);
+    }
+
+    #[test]
+    fn test_stills_are_not_animations() {
+        assert!(Animation::decode(&gif(&[100])).is_none());
+        assert!(Animation::decode(b"not an image").is_none());
+    }
+}
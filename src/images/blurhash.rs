@@ -0,0 +1,107 @@
+//! Decoding [BlurHash](https://blurha.sh) strings, the blurry previews
+//! Mastodon sends with each image, into placeholder images.
+
+use std::f32::consts::PI;
+
+use image::{DynamicImage, Rgb, RgbImage};
+
+const BASE83: &[u8] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+/// Decode a blurhash into a `width` x `height` image, or `None` if it isn't
+/// a valid one
+pub fn decode(hash: &str, width: u32, height: u32) -> Option<DynamicImage> {
+    let hash = hash.as_bytes();
+    let size = decode83(hash.get(..1)?)?;
+    let (components_x, components_y) = (size % 9 + 1, size / 9 + 1);
+    if hash.len() != 4 + 2 * (components_x * components_y) as usize {
+        return None;
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let max_value = (decode83(&hash[1..2])? + 1) as f32 / 166.0;
+    let mut colors = vec![decode_dc(decode83(&hash[2..6])?)];
+    for chunk in hash[6..].chunks(2) {
+        colors.push(decode_ac(decode83(chunk)?, max_value));
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let image = RgbImage::from_fn(width, height, |x, y| {
+        let mut pixel = [0.0; 3];
+        for j in 0..components_y {
+            for i in 0..components_x {
+                let basis = (PI * x as f32 * i as f32 / width as f32).cos()
+                    * (PI * y as f32 * j as f32 / height as f32).cos();
+                let color = colors[(i + j * components_x) as usize];
+                for (channel, value) in pixel.iter_mut().zip(color) {
+                    *channel += value * basis;
+                }
+            }
+        }
+        Rgb(pixel.map(linear_to_srgb))
+    });
+    Some(DynamicImage::ImageRgb8(image))
+}
+
+fn decode83(chars: &[u8]) -> Option<u32> {
+    chars.iter().try_fold(0u32, |value, c| {
+        let digit = BASE83.iter().position(|b| b == c)?;
+        Some(value * 83 + u32::try_from(digit).ok()?)
+    })
+}
+
+/// The average color
+fn decode_dc(value: u32) -> [f32; 3] {
+    [value >> 16, (value >> 8) & 255, value & 255].map(srgb_to_linear)
+}
+
+/// One of the cosine components
+#[allow(clippy::cast_precision_loss)]
+fn decode_ac(value: u32, max_value: f32) -> [f32; 3] {
+    [value / (19 * 19), (value / 19) % 19, value % 19].map(|quantised| {
+        let v = (quantised as f32 - 9.0) / 9.0;
+        v.signum() * v * v * max_value
+    })
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn srgb_to_linear(value: u32) -> f32 {
+    let v = value as f32 / 255.0;
+    if v <= 0.040_45 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn linear_to_srgb(value: f32) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    let srgb = if v <= 0.003_130_8 {
+        v * 12.92
+    } else {
+        1.055f32.mul_add(v.powf(1.0 / 2.4), -0.055)
+    };
+    (srgb * 255.0 + 0.5) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        // One component: the whole image is the average color, pure red
+        let image = decode("00TI:j", 4, 3).unwrap().to_rgb8();
+        assert_eq!(image.dimensions(), (4, 3));
+        assert!(image.pixels().all(|p| p.0 == [255, 0, 0]));
+
+        let image = decode("LEHV6nWB2yk8pyo0adR*.7kCMdnj", 32, 20).unwrap();
+        assert_eq!((image.width(), image.height()), (32, 20));
+
+        // Wrong length for its component count, or not base 83
+        assert!(decode("LEHV6nWB2yk8pyo0adR*.7kCMdn", 32, 20).is_none());
+        assert!(decode("00TI:\"", 4, 3).is_none());
+        assert!(decode("", 4, 3).is_none());
+    }
+}
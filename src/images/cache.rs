@@ -1,9 +1,15 @@
-//! LRU cache for loaded images.
+//! LRU caches for loaded images (in memory and on disk).
 
 use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
+use anyhow::{Context, Result};
 use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 /// Maximum number of images to keep in cache
 const MAX_CACHE_SIZE: usize = 50;
@@ -73,6 +79,11 @@ impl ImageCache {
         self.images.lock().unwrap().contains_key(url)
     }
 
+    /// Drop one image from the cache.
+    pub fn remove(&self, url: &str) {
+        self.images.lock().unwrap().remove(url);
+    }
+
     /// Evict the oldest entry from the cache.
     fn evict_oldest(cache: &mut HashMap<String, CachedImage>) {
         if let Some((oldest_key, _)) = cache
@@ -99,3 +110,172 @@ impl ImageCache {
         self.images.lock().unwrap().is_empty()
     }
 }
+
+/// Validators stored alongside a cached file for HTTP revalidation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheValidators {
+    /// `ETag` response header
+    pub etag: Option<String>,
+    /// `Last-Modified` response header
+    pub last_modified: Option<String>,
+}
+
+impl CacheValidators {
+    /// Whether the server gave us anything to revalidate with
+    pub const fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Image bytes persisted on disk, keyed by a hash of the URL.
+///
+/// Each entry is a `<hash>.img` file with the raw response body and a
+/// `<hash>.meta` file with its validators. File modification times track
+/// recency: hits touch the file, and the least recently used entries are
+/// removed once the directory grows past `max_bytes`.
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl DiskCache {
+    /// Open (and create) a disk cache in `dir` capped at `max_mb` megabytes
+    pub fn open(dir: PathBuf, max_mb: u64) -> Result<Self> {
+        fs::create_dir_all(&dir).context("Failed to create image cache directory")?;
+        Ok(Self {
+            dir,
+            max_bytes: max_mb * 1024 * 1024,
+        })
+    }
+
+    fn key(url: &str) -> String {
+        use std::fmt::Write;
+
+        let digest = Sha256::digest(url.as_bytes());
+        digest.iter().fold(String::with_capacity(64), |mut key, b| {
+            let _ = write!(key, "{b:02x}");
+            key
+        })
+    }
+
+    fn data_path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{}.img", Self::key(url)))
+    }
+
+    fn meta_path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{}.meta", Self::key(url)))
+    }
+
+    /// Read a cached entry, marking it as recently used
+    pub fn get(&self, url: &str) -> Option<(Vec<u8>, CacheValidators)> {
+        let path = self.data_path(url);
+        let bytes = fs::read(&path).ok()?;
+        let validators = fs::read_to_string(self.meta_path(url))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+
+        // Touch for LRU ordering
+        if let Ok(file) = fs::File::options().append(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+
+        Some((bytes, validators))
+    }
+
+    /// Store an entry and evict old ones if the cache is over its size limit
+    pub fn put(&self, url: &str, bytes: &[u8], validators: &CacheValidators) -> Result<()> {
+        if self.max_bytes == 0 {
+            return Ok(());
+        }
+
+        fs::write(self.data_path(url), bytes).context("Failed to write cached image")?;
+        fs::write(self.meta_path(url), serde_json::to_string(validators)?)
+            .context("Failed to write cache metadata")?;
+
+        self.evict()
+    }
+
+    /// Remove least recently used entries until the cache fits in `max_bytes`
+    fn evict(&self) -> Result<()> {
+        let mut entries: Vec<(PathBuf, u64, SystemTime)> = fs::read_dir(&self.dir)?
+            .filter_map(Result::ok)
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "img"))
+            .filter_map(|e| {
+                let meta = e.metadata().ok()?;
+                Some((e.path(), meta.len(), meta.modified().ok()?))
+            })
+            .collect();
+
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        if total <= self.max_bytes {
+            return Ok(());
+        }
+
+        // Oldest first
+        entries.sort_by_key(|(_, _, modified)| *modified);
+
+        for (path, size, _) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            let _ = fs::remove_file(path.with_extension("meta"));
+            if fs::remove_file(&path).is_ok() {
+                total = total.saturating_sub(size);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_disk_cache_roundtrip() {
+        let dir = tempdir().unwrap();
+        let cache = DiskCache::open(dir.path().to_path_buf(), 1).unwrap();
+
+        let validators = CacheValidators {
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+        };
+        cache
+            .put("https://example.com/a.png", b"image", &validators)
+            .unwrap();
+
+        let (bytes, cached) = cache.get("https://example.com/a.png").unwrap();
+        assert_eq!(bytes, b"image");
+        assert_eq!(cached.etag.as_deref(), Some("\"abc\""));
+        assert!(cache.get("https://example.com/missing.png").is_none());
+    }
+
+    #[test]
+    fn test_disk_cache_evicts_least_recently_used() {
+        let dir = tempdir().unwrap();
+        let cache = DiskCache::open(dir.path().to_path_buf(), 1).unwrap();
+        let chunk = vec![0u8; 400 * 1024];
+        let none = CacheValidators::default();
+
+        cache
+            .put("https://example.com/old.png", &chunk, &none)
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        cache
+            .put("https://example.com/mid.png", &chunk, &none)
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        // Third entry pushes the cache over 1 MB, so the oldest goes
+        cache
+            .put("https://example.com/new.png", &chunk, &none)
+            .unwrap();
+
+        assert!(cache.get("https://example.com/old.png").is_none());
+        assert!(cache.get("https://example.com/mid.png").is_some());
+        assert!(cache.get("https://example.com/new.png").is_some());
+    }
+}
@@ -76,11 +76,8 @@ async fn loader_task(
     result_tx: mpsc::UnboundedSender<LoadResult>,
     cache: ImageCache,
 ) {
-    // Create a reqwest client for downloading images
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-        .unwrap_or_default();
+    // Reuse the shared client (and its connection pool) for downloads
+    let client = crate::api::http_client();
 
     while let Some(msg) = rx.recv().await {
         match msg {
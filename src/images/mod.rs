@@ -4,10 +4,13 @@
 //! rendering in the terminal using various graphics protocols (Sixel,
 //! Kitty, iTerm2) with fallback to Unicode halfblocks.
 
+mod animation;
+pub mod blurhash;
 mod cache;
 mod loader;
 
-pub use cache::ImageCache;
+pub use animation::{AnimatedImage, Animation};
+pub use cache::{CacheValidators, DiskCache, ImageCache};
 pub use loader::ImageLoader;
 
 use ratatui_image::picker::Picker;
@@ -42,11 +42,13 @@
 //!
 //! - [`api`] — Network API clients (Mastodon, Bluesky)
 //! - [`app`] — TUI application state and event loop
-//! - [`auth`] — Credential storage via system keyring
+//! - [`auth`] — Credential storage (encrypted file or system keyring)
 //! - [`config`] — Configuration management
 //! - [`db`] — `SQLite` database for accounts, cache, drafts
+//! - [`export`] — Exporting cached posts to JSON or CSV
+//! - [`filters`] — Keyword and regex filters for the timeline
 //! - [`models`] — Data models (Post, Account, Network)
-//! - [`theme`] — Theme support via ratatui-themes
+//! - [`theme`] — Theme support via ratatui-themes, plus custom palettes
 //!
 //! ## Example
 //!
@@ -64,7 +66,7 @@
 //! - **Cross-Post** — Write once, post to multiple networks
 //! - **Beautiful TUI** — Three-panel interface with 15 themes
 //! - **Offline Cache** — Read your timeline without internet
-//! - **Secure** — Credentials stored in system keyring
+//! - **Secure** — Credentials encrypted on disk or kept in the system keyring
 //! - **Fast** — Async networking with Tokio
 
 #![doc(html_root_url = "https://docs.rs/perch/0.1.0")]
@@ -100,9 +102,12 @@
 pub mod api;
 pub mod app;
 pub mod auth;
+pub mod completions;
 pub mod config;
 pub mod db;
 pub mod demo;
+pub mod export;
+pub mod filters;
 pub mod images;
 pub mod models;
 pub mod paths;
@@ -115,7 +120,10 @@ pub mod update;
 pub use app::AppState;
 pub use config::Config;
 pub use db::Database;
-pub use models::{Account, Network, Post, ScheduledPost, ScheduledPostStatus};
+pub use models::{
+    Account, Draft, FollowState, ModeratedAccount, Moderation, Network, Notification,
+    NotificationKind, Post, Recurrence, ScheduledPost, ScheduledPostStatus, Visibility,
+};
 pub use theme::{Theme, ThemeColors};
 pub use update::{
     PackageManager, VersionCheck, check_for_updates_crates_io, detect_package_manager, run_update,
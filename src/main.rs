@@ -2,32 +2,101 @@
 #![allow(clippy::uninlined_format_args)]
 
 use anyhow::Result;
-use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{EnvFilter, Layer, layer::SubscriberExt, util::SubscriberInitExt};
 
 use perch::api::SocialApi;
 
-fn main() -> Result<()> {
-    // Initialize logging (RUST_LOG=debug for verbose output)
+/// Log warnings to stderr, unless the TUI is going to own the terminal
+/// (anything printed would land on top of it), and with `to_file`
+/// everything Perch logs at debug level to the log file too. `RUST_LOG`
+/// picks what's logged to both.
+fn init_logging(to_stderr: bool, to_file: bool) {
+    let filter = |default: &str| {
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default))
+    };
+    let file_layer = to_file
+        .then(|| {
+            let path = perch::paths::log_path().ok()?;
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .ok()?;
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(std::sync::Mutex::new(file))
+                    .with_filter(filter("warn,perch=debug")),
+            )
+        })
+        .flatten();
+
+    let stderr_layer = to_stderr.then(|| {
+        tracing_subscriber::fmt::layer()
+            .with_writer(std::io::stderr)
+            .with_filter(filter("warn"))
+    });
+
     tracing_subscriber::registry()
-        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")))
-        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(stderr_layer)
+        .with(file_layer)
         .init();
+}
+
+fn main() -> Result<()> {
+    // Where everything lives has to be settled before the config is read
+    let mut args: Vec<String> = std::env::args().collect();
+    if let Some(dir) = take_global_flag(&mut args, "--config-dir")? {
+        perch::paths::set_config_dir(perch::paths::expand_tilde(&dir));
+    }
+    if let Some(name) = take_global_flag(&mut args, "--profile")? {
+        perch::paths::set_profile(&name)?;
+    }
 
     // Parse CLI arguments
-    match parse_args()? {
+    let command = parse_args(&args)?;
+
+    let config = perch::Config::load();
+    init_logging(
+        !command.runs_tui(),
+        config
+            .as_ref()
+            .is_ok_and(perch::Config::file_logging_enabled),
+    );
+
+    // Pick the credential backend before anything reads credentials
+    if let Ok(config) = config {
+        perch::auth::init(config.credential_backend);
+
+        // Set up the proxy and root certificate before the first request.
+        // `perch config` still runs with broken ones, so they can be fixed.
+        match config.network_options() {
+            Ok(options) => perch::api::init_network(options),
+            Err(e) if !matches!(command, Command::Config { .. }) => return Err(e),
+            Err(_) => {}
+        }
+    }
+
+    match command {
         Command::Run => run_tui(),
-        Command::Demo => run_demo(),
-        Command::Auth { network, instance } => {
-            tokio::runtime::Runtime::new()?.block_on(auth_flow(&network, instance.as_deref()))
+        Command::Demo { theme, snapshot } => run_demo(theme, snapshot.as_deref()),
+        Command::Auth {
+            network,
+            instance,
+            oob,
+        } => {
+            tokio::runtime::Runtime::new()?.block_on(auth_flow(&network, instance.as_deref(), oob))
         }
         Command::Post {
             content,
             networks,
             schedule,
+            images,
+            cw,
+            handles,
+            thread,
         } => tokio::runtime::Runtime::new()?.block_on(post_cli(
-            &content,
-            &networks,
-            schedule.as_deref(),
+            &content, &networks, &schedule, &images, cw, &handles, thread,
         )),
         Command::Schedule { subcommand } => {
             tokio::runtime::Runtime::new()?.block_on(schedule_cli(subcommand))
@@ -35,7 +104,39 @@ fn main() -> Result<()> {
         Command::Timeline { network, limit } => {
             tokio::runtime::Runtime::new()?.block_on(timeline_cli(network.as_deref(), limit))
         }
-        Command::Accounts => list_accounts(),
+        Command::Accounts { subcommand } => accounts_cli(subcommand),
+        Command::Export {
+            format,
+            network,
+            out,
+        } => export_cli(format, network.as_deref(), out.as_deref()),
+        Command::Follow {
+            handle,
+            account,
+            follow,
+        } => tokio::runtime::Runtime::new()?.block_on(follow_cli(
+            &handle,
+            account.as_deref(),
+            follow,
+        )),
+        Command::Moderate {
+            handle,
+            account,
+            moderation,
+            undo,
+        } => tokio::runtime::Runtime::new()?.block_on(moderate_cli(
+            &handle,
+            account.as_deref(),
+            moderation,
+            undo,
+        )),
+        Command::Config { subcommand } => config_cli(subcommand),
+        Command::Completions { shell } => {
+            print!("{}", shell.script());
+            Ok(())
+        }
+        Command::Logout { network, handle } => tokio::runtime::Runtime::new()?
+            .block_on(logout_cli(network.as_deref(), handle.as_deref())),
         Command::Help => {
             print_help();
             Ok(())
@@ -50,15 +151,30 @@ fn main() -> Result<()> {
 /// CLI commands
 enum Command {
     Run,
-    Demo,
+    Demo {
+        theme: Option<perch::theme::Theme>,
+        /// Write the screens to this directory instead of running the TUI
+        snapshot: Option<std::path::PathBuf>,
+    },
     Auth {
         network: String,
         instance: Option<String>,
+        /// Paste the Mastodon authorization code instead of catching the
+        /// redirect on localhost
+        oob: bool,
     },
     Post {
         content: String,
         networks: Vec<String>,
-        schedule: Option<String>,
+        schedule: ScheduleFlags,
+        /// Image paths with optional alt text
+        images: Vec<(String, Option<String>)>,
+        /// Content warning (Mastodon)
+        cw: Option<String>,
+        /// Handles of the accounts to post from (default accounts if empty)
+        handles: Vec<String>,
+        /// Post as a thread on accounts it's too long for
+        thread: bool,
     },
     Schedule {
         subcommand: ScheduleSubcommand,
@@ -67,22 +183,131 @@ enum Command {
         network: Option<String>,
         limit: usize,
     },
-    Accounts,
+    Accounts {
+        subcommand: AccountsSubcommand,
+    },
+    /// Write cached posts to a file, or stdout when `out` is `None`
+    Export {
+        format: perch::export::ExportFormat,
+        network: Option<String>,
+        out: Option<std::path::PathBuf>,
+    },
+    /// Follow (or unfollow) an account, optionally from one of ours
+    Follow {
+        handle: String,
+        account: Option<String>,
+        follow: bool,
+    },
+    /// Mute or block an account (or with `undo`, lift it)
+    Moderate {
+        handle: String,
+        account: Option<String>,
+        moderation: perch::Moderation,
+        undo: bool,
+    },
+    /// View or change settings
+    Config {
+        subcommand: ConfigSubcommand,
+    },
+    /// Print a shell completion script
+    Completions {
+        shell: perch::completions::Shell,
+    },
+    /// Log out accounts on a network (optionally one handle), or all when `network` is `None`
+    Logout {
+        network: Option<String>,
+        handle: Option<String>,
+    },
     Help,
     Version,
 }
 
+impl Command {
+    /// Whether the TUI takes over the terminal
+    const fn runs_tui(&self) -> bool {
+        matches!(self, Self::Run | Self::Demo { snapshot: None, .. })
+    }
+}
+
+/// How `perch post` schedules a post
+#[derive(Default)]
+struct ScheduleFlags {
+    /// When to post (posts right away if unset)
+    time: Option<String>,
+    /// How often the post repeats
+    repeat: Option<String>,
+    /// Let the Mastodon server publish it
+    server_side: bool,
+}
+
 /// Schedule subcommands
 enum ScheduleSubcommand {
     List,
-    Cancel { id: String },
+    Cancel {
+        id: String,
+    },
+    Retry {
+        id: String,
+    },
+    Edit {
+        id: String,
+        content: Option<String>,
+        networks: Option<String>,
+        time: Option<String>,
+    },
     Run,
-    Daemon { interval: u64 },
+    Daemon {
+        interval: u64,
+        once: bool,
+    },
+}
+
+/// Accounts subcommands
+enum AccountsSubcommand {
+    List,
+    /// Write accounts to a file, with credentials sealed under a passphrase
+    Export {
+        path: std::path::PathBuf,
+        with_credentials: bool,
+    },
+    /// Add the accounts in an export file
+    Import {
+        path: std::path::PathBuf,
+    },
 }
 
-fn parse_args() -> Result<Command> {
-    let args: Vec<String> = std::env::args().collect();
+/// Config subcommands
+enum ConfigSubcommand {
+    Path,
+    Show,
+    Get { key: String },
+    Set { key: String, value: String },
+}
+
+/// Take a flag that applies to every command, with its value, out of
+/// `args`, wherever it is (`--flag <value>` or `--flag=<value>`)
+fn take_global_flag(args: &mut Vec<String>, name: &str) -> Result<Option<String>> {
+    let prefix = format!("{name}=");
+    let Some(i) = args
+        .iter()
+        .position(|a| a == name || a.starts_with(&prefix))
+    else {
+        return Ok(None);
+    };
+    let flag = args.remove(i);
+    let value = match flag.strip_prefix(&prefix) {
+        Some(value) => value.to_string(),
+        None if i < args.len() => args.remove(i),
+        None => anyhow::bail!("Missing value for {name}"),
+    };
+    if value.is_empty() {
+        anyhow::bail!("Missing value for {name}");
+    }
+    Ok(Some(value))
+}
 
+#[allow(clippy::too_many_lines)]
+fn parse_args(args: &[String]) -> Result<Command> {
     if args.len() == 1 {
         return Ok(Command::Run);
     }
@@ -90,100 +315,179 @@ fn parse_args() -> Result<Command> {
     match args[1].as_str() {
         "-h" | "--help" | "help" => Ok(Command::Help),
         "-v" | "--version" | "version" => Ok(Command::Version),
-        "--demo" | "demo" => Ok(Command::Demo),
+        "--demo" | "demo" => {
+            let flag = |name: &str| {
+                args.iter()
+                    .position(|a| a == name)
+                    .map(|i| {
+                        args.get(i + 1)
+                            .ok_or_else(|| anyhow::anyhow!("Missing value for {name}"))
+                    })
+                    .transpose()
+            };
+            let theme = flag("--theme")?
+                .map(|slug| {
+                    perch::theme::Theme::from_slug(slug).ok_or_else(|| {
+                        let slugs: Vec<_> = perch::theme::Theme::all()
+                            .iter()
+                            .map(perch::theme::Theme::slug)
+                            .collect();
+                        anyhow::anyhow!("Unknown theme: {slug}\nAvailable: {}", slugs.join(", "))
+                    })
+                })
+                .transpose()?;
+            let snapshot = flag("--snapshot")?.map(std::path::PathBuf::from);
+            Ok(Command::Demo { theme, snapshot })
+        }
 
         "auth" => {
             let network = args
                 .get(2)
                 .ok_or_else(|| anyhow::anyhow!("Missing network (mastodon or bluesky)"))?
                 .clone();
-            let instance = args.get(3).cloned();
-            Ok(Command::Auth { network, instance })
+            let instance = args.iter().skip(3).find(|a| !a.starts_with("--")).cloned();
+            let oob = args.iter().any(|a| a == "--oob");
+            Ok(Command::Auth {
+                network,
+                instance,
+                oob,
+            })
         }
 
-        "post" => {
-            let content = args
-                .get(2)
-                .ok_or_else(|| anyhow::anyhow!("Missing post content"))?
-                .clone();
+        "post" => parse_post_args(args),
 
-            // Parse flags
-            let mut networks = Vec::new();
-            let mut schedule = None;
-            let mut i = 3;
-            while i < args.len() {
-                match args[i].as_str() {
-                    "--to" | "-t" => {
-                        if let Some(nets) = args.get(i + 1) {
-                            networks.extend(nets.split(',').map(String::from));
-                        }
-                        i += 2;
-                    }
-                    "--schedule" | "-s" | "--at" => {
-                        if let Some(time) = args.get(i + 1) {
-                            schedule = Some(time.clone());
-                        }
-                        i += 2;
-                    }
-                    _ => i += 1,
+        "schedule" | "scheduled" => parse_schedule_args(args),
+
+        "timeline" | "tl" => {
+            let network = args.get(2).cloned();
+            let limit = args
+                .iter()
+                .position(|a| a == "--limit" || a == "-l")
+                .and_then(|i| args.get(i + 1))
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(20);
+            Ok(Command::Timeline { network, limit })
+        }
+
+        "accounts" => {
+            let path = || {
+                args.iter()
+                    .skip(3)
+                    .find(|a| !a.starts_with('-'))
+                    .map(std::path::PathBuf::from)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Missing file\nExample: perch accounts export accounts.json"
+                        )
+                    })
+            };
+            let subcommand = match args.get(2).map(String::as_str) {
+                Some("list" | "ls") | None => AccountsSubcommand::List,
+                Some("export") => AccountsSubcommand::Export {
+                    path: path()?,
+                    with_credentials: args.iter().any(|a| a == "--with-credentials"),
+                },
+                Some("import") => AccountsSubcommand::Import { path: path()? },
+                Some(other) => {
+                    return Err(anyhow::anyhow!(
+                        "Unknown accounts subcommand: {other}\nTry: list, export, import"
+                    ));
                 }
-            }
+            };
+            Ok(Command::Accounts { subcommand })
+        }
 
-            // Default to all configured networks
-            if networks.is_empty() {
-                networks = vec!["mastodon".to_string(), "bluesky".to_string()];
-            }
+        "export" => {
+            let flag = |names: &[&str]| {
+                args.iter()
+                    .position(|a| names.contains(&a.as_str()))
+                    .and_then(|i| args.get(i + 1))
+            };
+            let format = flag(&["--format", "-f"]).map_or("json", String::as_str);
+            Ok(Command::Export {
+                format: perch::export::ExportFormat::parse(format)?,
+                network: flag(&["--network", "-n"]).cloned(),
+                out: flag(&["--out", "-o"]).map(std::path::PathBuf::from),
+            })
+        }
 
-            Ok(Command::Post {
-                content,
-                networks,
-                schedule,
+        command @ ("follow" | "unfollow" | "mute" | "unmute" | "block" | "unblock") => {
+            let handle = args
+                .get(2)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Missing handle\nExample: perch {command} alice@mastodon.social"
+                    )
+                })?
+                .clone();
+            let account = args
+                .iter()
+                .position(|a| a == "--as")
+                .and_then(|i| args.get(i + 1))
+                .cloned();
+            let undo = command.starts_with("un");
+            Ok(match command.trim_start_matches("un") {
+                "follow" => Command::Follow {
+                    handle,
+                    account,
+                    follow: !undo,
+                },
+                moderation => Command::Moderate {
+                    handle,
+                    account,
+                    moderation: if moderation == "mute" {
+                        perch::Moderation::Mute
+                    } else {
+                        perch::Moderation::Block
+                    },
+                    undo,
+                },
             })
         }
 
-        "schedule" | "scheduled" => {
+        "config" => {
+            let missing = |what: &str| anyhow::anyhow!("Missing setting {what}");
             let subcommand = match args.get(2).map(String::as_str) {
-                Some("list" | "ls") | None => ScheduleSubcommand::List,
-                Some("cancel" | "rm" | "delete") => {
-                    let id = args
-                        .get(3)
-                        .ok_or_else(|| anyhow::anyhow!("Missing post ID to cancel"))?
-                        .clone();
-                    ScheduleSubcommand::Cancel { id }
-                }
-                Some("run" | "process") => ScheduleSubcommand::Run,
-                Some("daemon" | "watch") => {
-                    // Parse --interval flag (default 60 seconds)
-                    let interval = args
-                        .iter()
-                        .position(|a| a == "--interval" || a == "-i")
-                        .and_then(|i| args.get(i + 1))
-                        .and_then(|s| s.parse().ok())
-                        .unwrap_or(60);
-                    ScheduleSubcommand::Daemon { interval }
-                }
+                Some("show") | None => ConfigSubcommand::Show,
+                Some("path") => ConfigSubcommand::Path,
+                Some("get") => ConfigSubcommand::Get {
+                    key: args.get(3).ok_or_else(|| missing("name"))?.clone(),
+                },
+                Some("set") => ConfigSubcommand::Set {
+                    key: args.get(3).ok_or_else(|| missing("name"))?.clone(),
+                    value: args.get(4).ok_or_else(|| missing("value"))?.clone(),
+                },
                 Some(other) => {
                     return Err(anyhow::anyhow!(
-                        "Unknown schedule subcommand: {}\nTry: list, cancel, run, daemon",
-                        other
+                        "Unknown config subcommand: {other}\nTry: path, show, get, set"
                     ));
                 }
             };
-            Ok(Command::Schedule { subcommand })
+            Ok(Command::Config { subcommand })
         }
 
-        "timeline" | "tl" => {
-            let network = args.get(2).cloned();
-            let limit = args
-                .iter()
-                .position(|a| a == "--limit" || a == "-l")
-                .and_then(|i| args.get(i + 1))
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(20);
-            Ok(Command::Timeline { network, limit })
+        "completions" => {
+            let shell = args.get(2).ok_or_else(|| {
+                anyhow::anyhow!("Missing shell\nSupported: bash, zsh, fish, powershell")
+            })?;
+            Ok(Command::Completions {
+                shell: perch::completions::Shell::parse(shell)?,
+            })
         }
 
-        "accounts" => Ok(Command::Accounts),
+        "logout" => match args.get(2).map(String::as_str) {
+            Some("--all" | "-a") => Ok(Command::Logout {
+                network: None,
+                handle: None,
+            }),
+            Some(network) => Ok(Command::Logout {
+                network: Some(network.to_string()),
+                handle: args.get(3).map(|h| h.trim_start_matches('@').to_string()),
+            }),
+            None => Err(anyhow::anyhow!(
+                "Specify a network or --all\nExample: perch logout bluesky you.bsky.social"
+            )),
+        },
 
         other => Err(anyhow::anyhow!(
             "Unknown command: {other}\nRun 'perch --help' for usage"
@@ -191,6 +495,192 @@ fn parse_args() -> Result<Command> {
     }
 }
 
+/// Parse `perch schedule [SUBCOMMAND]`
+fn parse_schedule_args(args: &[String]) -> Result<Command> {
+    let subcommand = match args.get(2).map(String::as_str) {
+        Some("list" | "ls") | None => ScheduleSubcommand::List,
+        Some("cancel" | "rm" | "delete") => {
+            let id = args
+                .get(3)
+                .ok_or_else(|| anyhow::anyhow!("Missing post ID to cancel"))?
+                .clone();
+            ScheduleSubcommand::Cancel { id }
+        }
+        Some("retry") => {
+            let id = args
+                .get(3)
+                .ok_or_else(|| anyhow::anyhow!("Missing post ID to retry"))?
+                .clone();
+            ScheduleSubcommand::Retry { id }
+        }
+        Some("edit") => {
+            let id = args
+                .get(3)
+                .ok_or_else(|| anyhow::anyhow!("Missing post ID to edit"))?
+                .clone();
+            let flag = |names: &[&str]| {
+                args.iter()
+                    .position(|a| names.contains(&a.as_str()))
+                    .and_then(|i| args.get(i + 1))
+                    .cloned()
+            };
+            ScheduleSubcommand::Edit {
+                id,
+                content: flag(&["--text"]),
+                networks: flag(&["--to", "-t"]),
+                time: flag(&["--schedule", "-s", "--at"]),
+            }
+        }
+        Some("run" | "process") => ScheduleSubcommand::Run,
+        Some("daemon" | "watch") => {
+            // Parse --interval flag (default 60 seconds)
+            let interval = args
+                .iter()
+                .position(|a| a == "--interval" || a == "-i")
+                .and_then(|i| args.get(i + 1))
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(60);
+            let once = args.iter().any(|a| a == "--once");
+            ScheduleSubcommand::Daemon { interval, once }
+        }
+        Some(other) => {
+            return Err(anyhow::anyhow!(
+                "Unknown schedule subcommand: {}\nTry: list, edit, cancel, retry, run, daemon",
+                other
+            ));
+        }
+    };
+    Ok(Command::Schedule { subcommand })
+}
+
+/// Flags accepted by `perch post`
+const POST_FLAGS: &[&str] = &[
+    "--to",
+    "-t",
+    "--schedule",
+    "-s",
+    "--at",
+    "--media",
+    "-m",
+    "--image",
+    "--cw",
+    "--alt",
+    "--as",
+    "--repeat",
+    "-r",
+    "--server-side",
+    "--thread",
+];
+
+/// Read post content from stdin, dropping the single trailing newline that
+/// `echo` and most editors add
+fn read_stdin_content() -> Result<String> {
+    use std::io::Read;
+
+    let mut content = String::new();
+    std::io::stdin()
+        .read_to_string(&mut content)
+        .map_err(|e| anyhow::anyhow!("Failed to read post content from stdin: {e}"))?;
+
+    let content = content
+        .strip_suffix("\r\n")
+        .or_else(|| content.strip_suffix('\n'))
+        .unwrap_or(&content);
+    if content.trim().is_empty() {
+        anyhow::bail!("No post content on stdin");
+    }
+    Ok(content.to_string())
+}
+
+/// Parse `perch post <content|-> [OPTIONS]`
+fn parse_post_args(args: &[String]) -> Result<Command> {
+    use std::io::IsTerminal;
+
+    // `-` reads stdin; so does a missing content argument when input is piped
+    let (content, flags_start) = match args.get(2).map(String::as_str) {
+        Some("-") => (read_stdin_content()?, 3),
+        Some(arg) if !POST_FLAGS.contains(&arg) => (arg.to_string(), 3),
+        _ if !std::io::stdin().is_terminal() => (read_stdin_content()?, 2),
+        _ => anyhow::bail!("Missing post content"),
+    };
+
+    // Parse flags
+    let mut networks = Vec::new();
+    let mut schedule = ScheduleFlags::default();
+    let mut images: Vec<(String, Option<String>)> = Vec::new();
+    let mut cw = None;
+    let mut handles = Vec::new();
+    let mut thread = false;
+    let mut i = flags_start;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--to" | "-t" => {
+                if let Some(nets) = args.get(i + 1) {
+                    networks.extend(nets.split(',').map(String::from));
+                }
+                i += 2;
+            }
+            "--schedule" | "-s" | "--at" => {
+                if let Some(time) = args.get(i + 1) {
+                    schedule.time = Some(time.clone());
+                }
+                i += 2;
+            }
+            "--server-side" => {
+                schedule.server_side = true;
+                i += 1;
+            }
+            "--thread" => {
+                thread = true;
+                i += 1;
+            }
+            "--media" | "-m" | "--image" => {
+                if let Some(path) = args.get(i + 1) {
+                    images.push((path.clone(), None));
+                }
+                i += 2;
+            }
+            "--cw" => {
+                if let Some(text) = args.get(i + 1) {
+                    cw = Some(text.clone());
+                }
+                i += 2;
+            }
+            "--repeat" | "-r" => {
+                if let Some(rule) = args.get(i + 1) {
+                    schedule.repeat = Some(rule.clone());
+                }
+                i += 2;
+            }
+            "--as" => {
+                if let Some(handle) = args.get(i + 1) {
+                    handles.push(handle.trim_start_matches('@').to_string());
+                }
+                i += 2;
+            }
+            "--alt" => {
+                // Alt text applies to the most recently added image
+                if let (Some(alt), Some(image)) = (args.get(i + 1), images.last_mut()) {
+                    image.1 = Some(alt.clone());
+                }
+                i += 2;
+            }
+            _ => i += 1,
+        }
+    }
+
+    Ok(Command::Post {
+        content,
+        networks,
+        schedule,
+        images,
+        cw,
+        handles,
+        thread,
+    })
+}
+
+#[allow(clippy::too_many_lines)]
 fn print_help() {
     let config_path = perch::Config::default_path()
         .map_or_else(|_| "Unknown".to_string(), |p| p.display().to_string());
@@ -200,39 +690,75 @@ fn print_help() {
 
 USAGE:
     perch                              Launch TUI
-    perch [COMMAND]
+    perch [--config-dir <dir>] [--profile <name>] [COMMAND]
 
 COMMANDS:
     auth <network> [instance]          Authenticate with a network
+      Options:
+        --oob                          Paste the Mastodon authorization code
+                                       instead of catching the browser's
+                                       redirect on localhost
       Examples:
         perch auth mastodon mastodon.social
         perch auth bluesky
 
-    post <content> [OPTIONS]           Post to networks
+    post <content|-> [OPTIONS]         Post to networks (`-` reads stdin)
       Options:
-        -t, --to <networks>            Comma-separated networks (default: all)
+        -t, --to <networks>            Comma-separated networks (default:
+                                       default_post_networks, else every
+                                       network with an account)
         -s, --schedule <time>          Schedule post for later
+        -m, --media <path>             Attach an image (up to 4)
+        --alt <text>                   Alt text for the preceding image
+        --cw <text>                    Content warning (Mastodon)
+        -r, --repeat <rule>            Repeat a scheduled post: daily, weekly
+                                       or a cron expression ("0 9 * * 1-5")
+        --server-side                  Have the Mastodon server publish a
+                                       scheduled post (Bluesky stays local)
+        --as <handle>                  Post from this account (repeatable;
+                                       default: each network's default account)
+        --thread                       Post as a numbered thread where it's
+                                       too long for an account
       Examples:
         perch post "Hello world!"
         perch post "Hello Fediverse!" --to mastodon
         perch post "Hello!" --to mastodon,bluesky
+        perch post "Hi!" --as alice@mastodon.social --as alice.bsky.social
         perch post "Good morning!" --schedule "in 2h"
+        perch post "Standup in 5!" --schedule "09:55" --repeat "55 9 * * 1-5"
+        perch post "Tonight!" --schedule "tonight" --server-side
+        perch post "Look!" --media cat.png --alt "A sleeping cat"
         perch post "Scheduled!" --schedule "YYYY-MM-DD HH:MM"
+        echo "Hello from a pipe" | perch post - --to mastodon
 
     schedule [SUBCOMMAND]              Manage scheduled posts
       Subcommands:
-        list                           List pending scheduled posts
-        cancel <id>                    Cancel a scheduled post
+        list                           List pending and failed scheduled posts
+        cancel <id>                    Cancel a scheduled post (or one
+                                       scheduled on Mastodon, by its ID)
+        edit <id> [OPTIONS]            Change a pending scheduled post
+        retry <id>                     Try a failed scheduled post again
         run                            Process due scheduled posts
-        daemon [OPTIONS]               Run continuously, processing posts
+        daemon [OPTIONS]               Run continuously, posting as posts come
+                                       due (logs to stderr; stops on
+                                       SIGINT/SIGTERM)
+      Options (edit):
+        --text <content>               New content
+        -t, --to <networks>            New comma-separated networks
+        -s, --schedule <time>          New time
       Options (daemon):
-        -i, --interval <secs>          Check interval in seconds (default: 60)
+        -i, --interval <secs>          Longest wait between checks for new
+                                       posts, in seconds (default: 60)
+        --once                         Post what's due now, then exit
       Examples:
         perch schedule list
+        perch schedule edit abc123 --schedule "tomorrow 10am"
         perch schedule cancel abc123
+        perch schedule retry abc123
         perch schedule run
         perch schedule daemon
         perch schedule daemon --interval 30
+        perch schedule daemon --once
 
     timeline [network] [OPTIONS]       Show timeline
       Options:
@@ -241,16 +767,112 @@ COMMANDS:
         perch timeline
         perch timeline mastodon --limit 50
 
-    accounts                           List configured accounts
+    accounts [SUBCOMMAND]              Manage configured accounts
+      Subcommands:
+        list                           List configured accounts (default)
+        export <file> [OPTIONS]        Write accounts to a file for another
+                                       machine
+        import <file>                  Add the accounts in an export file,
+                                       skipping ones already set up
+      Options (export):
+        --with-credentials             Include logins, encrypted with a
+                                       passphrase you choose
+      Examples:
+        perch accounts export accounts.json --with-credentials
+        perch accounts import accounts.json
+
+    export [OPTIONS]                   Export cached posts
+      Options:
+        -f, --format <json|csv>        JSON keeps whole posts with media;
+                                       CSV has one row per post (default: json)
+        -n, --network <network>        Only this network's posts
+        -o, --out <path>               Write to a file (default: stdout)
+      Examples:
+        perch export --format csv --out timeline.csv
+        perch export --network bluesky > bluesky.json
+
+    follow <handle> [OPTIONS]          Follow an account (Mastodon handles
+                                       have a server, Bluesky ones don't)
+    unfollow <handle> [OPTIONS]        Unfollow an account (or withdraw a
+                                       pending follow request)
+      Options:
+        --as <handle>                  Follow from this account (default:
+                                       the network's default account)
+      Examples:
+        perch follow alice@mastodon.social
+        perch follow bob.bsky.social
+        perch unfollow alice@mastodon.social --as me@hachyderm.io
+
+    mute <handle> [OPTIONS]            Mute an account: hide its posts
+    block <handle> [OPTIONS]           Block an account: hide its posts and
+                                       stop it following or replying
+    unmute, unblock <handle>           Lift a mute or block
+      Options:
+        --as <handle>                  Act from this account (default: the
+                                       network's default account)
+      Examples:
+        perch mute loud@mastodon.social
+        perch block spam.bsky.social
+        perch unmute loud@mastodon.social
+
+    config [SUBCOMMAND]                View or change settings
+      Subcommands:
+        path                           Print the config file location
+        show                           Print the current settings
+        get <key>                      Print one setting
+        set <key> <value>              Change one setting
+      Keys:
+        theme, color, post_limit, refresh_interval_secs,
+        default_post_networks, streaming, show_avatars, layout,
+        timeline_width, mouse, image_cache_max_mb, cache_max_posts, timezone
+      Examples:
+        perch config set theme dracula
+        perch config set default_post_networks mastodon,bluesky
+        perch config get post_limit
+
+    logout <network> [handle]          Remove accounts and their credentials
+    logout --all                       Reset all logins (e.g. after a machine change)
+      Examples:
+        perch logout bluesky alice.bsky.social
+        perch logout mastodon
+        perch logout --all
+
+    completions <shell>                Print a completion script for bash,
+                                       zsh, fish or powershell
+      Examples:
+        perch completions bash > ~/.local/share/bash-completion/completions/perch
+        perch completions fish > ~/.config/fish/completions/perch.fish
+
+    demo [OPTIONS]                     Launch the TUI with sample data
+      Options:
+        --theme <theme>                Show it in this theme (default: dracula)
+        --snapshot <dir>               Write each screen to <dir> as ANSI text
+                                       and SVG instead, without a terminal
+      Examples:
+        perch demo --theme nord
+        perch demo --snapshot docs/screens --theme catppuccin-mocha
 
 SCHEDULE TIME FORMATS:
     Relative:    "in 5m", "in 2h", "in 1d", "in 30 minutes"
     Time today:  "15:00", "3pm" (schedules for tomorrow if past)
+    Day:         "tomorrow 9am", "today 17:00", "tonight"
+    Weekday:     "friday", "next monday 14:00"
     Date+time:   "YYYY-MM-DD HH:MM", "YYYY-MM-DDTHH:MM"
 
 OPTIONS:
     -h, --help                         Show this help message
     -v, --version                      Show version information
+    --config-dir <dir>                 Keep config, accounts, database and
+                                       caches in <dir> (or set
+                                       PERCH_CONFIG_DIR); XDG_CONFIG_HOME,
+                                       XDG_DATA_HOME and XDG_CACHE_HOME are
+                                       respected otherwise
+    --profile <name>                   Use a separate setup (accounts,
+                                       settings, caches) kept under
+                                       profiles/<name>
+      Examples:
+        perch --profile work auth mastodon hachyderm.io
+        perch --profile work
 
 KEYBINDINGS (TUI):
     Navigation
@@ -289,11 +911,138 @@ fn run_tui() -> Result<()> {
     perch::app::run()
 }
 
-fn run_demo() -> Result<()> {
-    perch::app::run_demo()
+fn run_demo(theme: Option<perch::theme::Theme>, snapshot: Option<&std::path::Path>) -> Result<()> {
+    let Some(dir) = snapshot else {
+        return perch::app::run_demo(theme);
+    };
+    let written = perch::app::write_demo_snapshots(dir, theme)?;
+    println!("Wrote {} files to {}", written.len(), dir.display());
+    Ok(())
+}
+
+/// The PDS hosting a Bluesky handle, asking for it only if the handle can't
+/// be resolved
+async fn bluesky_pds(handle: &str) -> Result<String> {
+    match perch::api::bluesky::resolve_pds(handle).await {
+        Ok(pds_url) => {
+            println!("\nFound your PDS: {pds_url}");
+            Ok(pds_url)
+        }
+        Err(e) => {
+            println!("\nCouldn't find your PDS ({e}).");
+            println!("Enter your PDS URL (press Enter for default bsky.social):");
+            let mut pds_input = String::new();
+            std::io::stdin().read_line(&mut pds_input)?;
+            let pds_url = pds_input.trim();
+            Ok(if pds_url.is_empty() {
+                perch::api::bluesky::DEFAULT_PDS_URL.to_string()
+            } else if pds_url.starts_with("http") {
+                pds_url.to_string()
+            } else {
+                format!("https://{pds_url}")
+            })
+        }
+    }
+}
+
+/// Log in to Bluesky, asking for the emailed sign-in code if the account
+/// has two-factor authentication
+async fn bluesky_login(
+    handle: &str,
+    password: &str,
+    pds_url: &str,
+) -> Result<perch::api::bluesky::BlueskyClient> {
+    use perch::api::bluesky::{BlueskyClient, LoginError};
+
+    match BlueskyClient::login_with_pds(handle, password, pds_url).await {
+        Err(e) if e.downcast_ref() == Some(&LoginError::AuthFactorRequired) => {
+            println!("\nBluesky emailed you a sign-in code. Enter it:");
+            println!("(An app password logs in without one)");
+            let mut code = String::new();
+            std::io::stdin().read_line(&mut code)?;
+            BlueskyClient::login_with_auth_factor(handle, password, pds_url, Some(code.trim()))
+                .await
+        }
+        result => result,
+    }
+}
+
+/// The app registration to log in to a Mastodon instance with: the one stored
+/// from an earlier login if the instance still knows it, or a new one
+async fn mastodon_app(instance: &str) -> Result<perch::api::mastodon::oauth::OAuthApp> {
+    use perch::api::mastodon::oauth;
+
+    if let Some((client_id, client_secret)) = perch::auth::get_oauth_client(instance)? {
+        let app = oauth::OAuthApp {
+            client_id,
+            client_secret,
+        };
+        if oauth::app_is_registered(instance, &app, None).await? {
+            println!("✓ Using existing app registration");
+            return Ok(app);
+        }
+        println!("⚠ The stored app registration was revoked, registering again");
+    }
+
+    let app = oauth::register_app(instance, None).await?;
+    println!("✓ App registered");
+    perch::auth::store_oauth_client(instance, &app.client_id, &app.client_secret)?;
+    Ok(app)
+}
+
+/// Have the user authorize Perch in the browser and return the code that
+/// comes back, with the redirect URI it was requested for
+///
+/// The code is caught by a listener on localhost, or pasted by the user when
+/// `oob` is set or the listener can't start.
+async fn mastodon_authorize(
+    instance: &str,
+    app: &perch::api::mastodon::oauth::OAuthApp,
+    oob: bool,
+) -> Result<(String, &'static str)> {
+    use perch::api::mastodon::oauth;
+
+    let server = if oob {
+        None
+    } else {
+        match oauth::CallbackServer::bind().await {
+            Ok(server) => Some(server),
+            Err(e) => {
+                println!("⚠ {e:#}, you'll need to paste the code instead");
+                None
+            }
+        }
+    };
+    let redirect_uri = if server.is_some() {
+        oauth::LOOPBACK_REDIRECT_URI
+    } else {
+        oauth::OOB_REDIRECT_URI
+    };
+
+    let auth_url = oauth::get_auth_url(
+        instance,
+        &app.client_id,
+        redirect_uri,
+        server.as_ref().map(oauth::CallbackServer::state),
+    );
+    println!("\n📋 Open this URL in your browser:\n\n  {}\n", auth_url);
+
+    // Try to open browser
+    let _ = open::that(&auth_url);
+
+    let code = if let Some(server) = server {
+        println!("Waiting for you to authorize Perch in the browser...");
+        server.wait_for_code().await?
+    } else {
+        println!("Paste the authorization code here:");
+        let mut code = String::new();
+        std::io::stdin().read_line(&mut code)?;
+        code.trim().to_string()
+    };
+    Ok((code, redirect_uri))
 }
 
-async fn auth_flow(network: &str, instance: Option<&str>) -> Result<()> {
+async fn auth_flow(network: &str, instance: Option<&str>, oob: bool) -> Result<()> {
     match network.to_lowercase().as_str() {
         "mastodon" | "masto" => {
             let instance = instance.ok_or_else(|| {
@@ -308,31 +1057,18 @@ async fn auth_flow(network: &str, instance: Option<&str>) -> Result<()> {
 
             println!("🐘 Authenticating with Mastodon ({})...", instance);
 
-            // Register app
-            let app = perch::api::mastodon::oauth::register_app(&instance).await?;
-            println!("✓ App registered");
+            let app = mastodon_app(&instance).await?;
 
-            // Store client credentials
-            perch::auth::store_oauth_client(&instance, &app.client_id, &app.client_secret)?;
-
-            // Get auth URL
-            let auth_url = perch::api::mastodon::oauth::get_auth_url(&instance, &app.client_id);
-            println!("\n📋 Open this URL in your browser:\n\n  {}\n", auth_url);
-
-            // Try to open browser
-            let _ = open::that(&auth_url);
-
-            println!("Paste the authorization code here:");
-            let mut code = String::new();
-            std::io::stdin().read_line(&mut code)?;
-            let code = code.trim();
+            let (code, redirect_uri) = mastodon_authorize(&instance, &app, oob).await?;
 
             // Exchange for token
             let token = perch::api::mastodon::oauth::get_token(
                 &instance,
                 &app.client_id,
                 &app.client_secret,
-                code,
+                &code,
+                redirect_uri,
+                None,
             )
             .await?;
 
@@ -346,7 +1082,8 @@ async fn auth_flow(network: &str, instance: Option<&str>) -> Result<()> {
                 &instance,
                 &account_info.display_name,
             );
-            account.avatar_url = account_info.avatar_url;
+            account.avatar_url.clone_from(&account_info.avatar_url);
+            account.update_server_info(&account_info);
 
             let db = perch::Database::open()?;
             db.insert_account(&account)?;
@@ -365,18 +1102,7 @@ async fn auth_flow(network: &str, instance: Option<&str>) -> Result<()> {
             std::io::stdin().read_line(&mut handle)?;
             let handle = handle.trim();
 
-            // Ask for PDS URL (optional)
-            println!("\nEnter your PDS URL (press Enter for default bsky.social):");
-            let mut pds_input = String::new();
-            std::io::stdin().read_line(&mut pds_input)?;
-            let pds_url = pds_input.trim();
-            let pds_url = if pds_url.is_empty() {
-                perch::api::bluesky::DEFAULT_PDS_URL
-            } else if pds_url.starts_with("http") {
-                pds_url
-            } else {
-                &format!("https://{}", pds_url)
-            };
+            let pds_url = &bluesky_pds(handle).await?;
 
             println!("\nEnter your app password:");
             println!("(Create one at https://bsky.app/settings/app-passwords)");
@@ -385,9 +1111,7 @@ async fn auth_flow(network: &str, instance: Option<&str>) -> Result<()> {
             let password = password.trim();
 
             // Login and verify
-            let client =
-                perch::api::bluesky::BlueskyClient::login_with_pds(handle, password, pds_url)
-                    .await?;
+            let client = bluesky_login(handle, password, pds_url).await?;
             let account_info = client.verify_credentials().await?;
 
             // Create and store account with the actual PDS URL
@@ -396,7 +1120,8 @@ async fn auth_flow(network: &str, instance: Option<&str>) -> Result<()> {
                 &account_info.display_name,
                 pds_url,
             );
-            account.avatar_url = account_info.avatar_url;
+            account.avatar_url.clone_from(&account_info.avatar_url);
+            account.update_server_info(&account_info);
 
             let db = perch::Database::open()?;
             db.insert_account(&account)?;
@@ -419,71 +1144,477 @@ async fn auth_flow(network: &str, instance: Option<&str>) -> Result<()> {
     Ok(())
 }
 
-async fn post_cli(content: &str, networks: &[String], schedule: Option<&str>) -> Result<()> {
+#[allow(clippy::future_not_send)]
+async fn post_cli(
+    content: &str,
+    networks: &[String],
+    schedule: &ScheduleFlags,
+    images: &[(String, Option<String>)],
+    cw: Option<String>,
+    handles: &[String],
+    thread: bool,
+) -> Result<()> {
     let db = perch::Database::open()?;
 
-    // Parse networks
-    let parsed_networks: Vec<perch::Network> = networks
+    let media = images
         .iter()
-        .filter_map(|n| perch::Network::from_str(n))
-        .collect();
-
-    if parsed_networks.is_empty() {
-        return Err(anyhow::anyhow!("No valid networks specified"));
+        .map(|(path, alt)| {
+            perch::api::MediaUpload::from_path(&perch::paths::expand_tilde(path), alt.clone())
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if media.len() > perch::api::MAX_MEDIA_ATTACHMENTS {
+        anyhow::bail!(
+            "At most {} images can be attached",
+            perch::api::MAX_MEDIA_ATTACHMENTS
+        );
     }
 
-    // If scheduling, save to database instead of posting
-    if let Some(schedule_time) = schedule {
-        let scheduled_for = perch::schedule::parse_schedule_time(schedule_time)?;
-        let scheduled_post =
-            perch::ScheduledPost::new(content, parsed_networks.clone(), scheduled_for);
-
-        db.save_scheduled_post(&scheduled_post)?;
-
-        let networks_str = parsed_networks
-            .iter()
-            .map(perch::Network::name)
-            .collect::<Vec<_>>()
-            .join(", ");
+    let missing_alt = media.iter().filter(|m| m.alt_text.is_none()).count();
+    if missing_alt > 0 {
+        let policy = perch::Config::load()
+            .map(|config| config.missing_alt_text)
+            .unwrap_or_default();
+        match policy {
+            perch::config::MissingAltText::Require => anyhow::bail!(
+                "{missing_alt} image(s) have no description; add --alt after each --media"
+            ),
+            perch::config::MissingAltText::Warn => eprintln!(
+                "⚠️  {missing_alt} image(s) have no description; add --alt after each --media"
+            ),
+            perch::config::MissingAltText::Off => {}
+        }
+    }
 
-        println!("📅 Post scheduled!");
-        println!("   Networks: {}", networks_str);
-        println!("   Time: {}", scheduled_post.scheduled_time_display());
-        println!("   In: {}", scheduled_post.time_until());
-        println!("\n   ID: {}", &scheduled_post.id.to_string()[..8]);
-        println!("\n   Run 'perch schedule list' to see pending posts");
-        println!("   Run 'perch schedule run' to process due posts");
+    // Named accounts decide the networks themselves
+    let parsed_networks = if handles.is_empty() {
+        post_networks(&db, networks)?
+    } else {
+        Vec::new()
+    };
 
-        return Ok(());
+    if schedule.time.is_none() {
+        if schedule.repeat.is_some() {
+            anyhow::bail!("--repeat needs --schedule for the first post");
+        }
+        if schedule.server_side {
+            anyhow::bail!("--server-side needs --schedule");
+        }
     }
 
-    // Post immediately
-    for network in &parsed_networks {
-        let account = db.get_default_account(*network)?.ok_or_else(|| {
-            anyhow::anyhow!(
-                "No {} account configured. Run: perch auth {}",
-                network.name(),
-                format!("{:?}", network).to_lowercase()
-            )
-        })?;
-
-        let token = perch::auth::get_credentials(&account)?
-            .ok_or_else(|| anyhow::anyhow!("No credentials found for {}", account.handle))?;
+    // If scheduling, save for later instead of posting
+    if let Some(schedule_time) = &schedule.time {
+        if !handles.is_empty() {
+            anyhow::bail!("Scheduled posts go out from each network's default account");
+        }
+        if !media.is_empty() {
+            anyhow::bail!("Scheduled posts can't include images yet");
+        }
+        if thread {
+            anyhow::bail!("Scheduled posts can't be threads yet");
+        }
 
-        let client = perch::api::get_client(&account, &token).await?;
+        return schedule_post(&db, content, &parsed_networks, schedule_time, schedule, cw).await;
+    }
 
-        println!("{} Posting to {}...", network.emoji(), network.name());
-        let post = client.post(content).await?;
+    // Post immediately, after checking the post fits everywhere
+    let accounts = if handles.is_empty() {
+        default_accounts(&db, &parsed_networks)?
+    } else {
+        accounts_by_handle(&db.get_accounts()?, handles)?
+    };
 
-        if let Some(url) = &post.url {
-            println!("✓ Posted: {}", url);
-        } else {
-            println!("✓ Posted successfully");
+    // A thread is split to fit each account's limit instead
+    if !thread {
+        let length = content.chars().count();
+        for account in &accounts {
+            let limit = account.post_char_limit();
+            if length > limit {
+                anyhow::bail!(
+                    "Post is {length} characters, but @{} allows {limit}",
+                    account.handle
+                );
+            }
         }
     }
 
-    Ok(())
-}
+    let options = perch::api::PostOptions {
+        spoiler_text: cw,
+        thread,
+        ..Default::default()
+    };
+    for account in accounts {
+        post_as(&account, content, &media, &options).await?;
+    }
+
+    Ok(())
+}
+
+/// Networks to post to: those named with `--to`, else the configured
+/// `default_post_networks`, else every network with an account. Networks
+/// without an account are left out with a warning.
+fn post_networks(db: &perch::Database, names: &[String]) -> Result<Vec<perch::Network>> {
+    let mut with_accounts = Vec::new();
+    for network in perch::Network::all() {
+        if db.get_default_account(*network)?.is_some() {
+            with_accounts.push(*network);
+        }
+    }
+
+    let requested = if names.is_empty() {
+        perch::Config::load()?.default_networks(&with_accounts)
+    } else {
+        let parsed: Vec<_> = names
+            .iter()
+            .filter_map(|n| perch::Network::from_str(n))
+            .collect();
+        if parsed.is_empty() {
+            anyhow::bail!("No valid networks specified");
+        }
+        parsed
+    };
+    if requested.is_empty() {
+        anyhow::bail!("No accounts configured. Run: perch auth mastodon, or perch auth bluesky");
+    }
+
+    let (usable, missing): (Vec<_>, Vec<_>) = requested
+        .into_iter()
+        .partition(|n| with_accounts.contains(n));
+    for network in &missing {
+        let message = format!(
+            "No {} account configured. Run: perch auth {}",
+            network.name(),
+            network.name().to_lowercase()
+        );
+        if usable.is_empty() {
+            anyhow::bail!(message);
+        }
+        eprintln!("⚠️  {message} (posting without it)");
+    }
+    Ok(usable)
+}
+
+/// Post from one account, as a thread if `options` asks and it's too long
+#[allow(clippy::future_not_send)]
+async fn post_as(
+    account: &perch::Account,
+    content: &str,
+    media: &[perch::api::MediaUpload],
+    options: &perch::api::PostOptions,
+) -> Result<()> {
+    let network = account.network;
+
+    let token = perch::auth::get_credentials(account)?
+        .ok_or_else(|| anyhow::anyhow!("No credentials found for {}", account.handle))?;
+
+    let client = perch::api::get_client(account, &token).await?;
+
+    println!(
+        "{} Posting to {} as @{}...",
+        network.emoji(),
+        network.name(),
+        account.handle
+    );
+    let parts = if options.thread {
+        perch::models::split_thread(content, account.post_char_limit())
+    } else {
+        vec![content.to_string()]
+    };
+    let post = if media.is_empty() {
+        client.post(&parts[0], options).await?
+    } else {
+        client
+            .post_with_media(&parts[0], media.to_vec(), options)
+            .await?
+    };
+    let rest = client.continue_thread(&post, &parts[1..], options).await?;
+
+    for post in std::iter::once(&post).chain(&rest) {
+        if let Some(url) = &post.url {
+            println!("✓ Posted: {}", url);
+        } else {
+            println!("✓ Posted successfully");
+        }
+    }
+    Ok(())
+}
+
+/// Schedule a post: on the Mastodon server with `--server-side`, and in the
+/// local database for everything else
+#[allow(clippy::future_not_send)]
+async fn schedule_post(
+    db: &perch::Database,
+    content: &str,
+    networks: &[perch::Network],
+    time: &str,
+    schedule: &ScheduleFlags,
+    cw: Option<String>,
+) -> Result<()> {
+    let timezone = configured_timezone();
+    let scheduled_for = perch::schedule::parse_schedule_time(time, &timezone)?;
+    let recurrence = schedule
+        .repeat
+        .as_deref()
+        .map(perch::Recurrence::parse)
+        .transpose()?;
+
+    let mut local_networks = networks.to_vec();
+    let server_network = if schedule.server_side {
+        let Some(network) = networks
+            .iter()
+            .copied()
+            .find(|n| n.capabilities().server_scheduling)
+        else {
+            anyhow::bail!("--server-side only applies to Mastodon");
+        };
+        if recurrence.is_some() {
+            anyhow::bail!("Repeating posts can't be scheduled on the server");
+        }
+        local_networks.retain(|n| *n != network);
+        Some(network)
+    } else {
+        None
+    };
+    // Only a local post on a network with content warnings minds
+    if cw.is_some()
+        && local_networks
+            .iter()
+            .any(|n| n.capabilities().content_warnings)
+    {
+        anyhow::bail!("Scheduled posts can't include content warnings yet (try --server-side)");
+    }
+
+    if let Some(network) = server_network {
+        let account = default_accounts(db, &[network])?.remove(0);
+        let token = perch::auth::get_credentials(&account)?
+            .ok_or_else(|| anyhow::anyhow!("No credentials found for {}", account.handle))?;
+        let client = perch::api::get_client(&account, &token).await?;
+        let options = perch::api::PostOptions {
+            spoiler_text: cw,
+            ..Default::default()
+        };
+        let id = client
+            .schedule_post(content, &options, scheduled_for)
+            .await?;
+
+        println!("🐘 Scheduled on {} as @{}", account.server, account.handle);
+        println!("   Time: {}", timezone.format(scheduled_for));
+        println!("   Mastodon ID: {id}");
+        println!("   Cancel with 'perch schedule cancel {id}'");
+    }
+
+    if !local_networks.is_empty() {
+        if server_network.is_some() {
+            println!();
+        }
+        let mut scheduled_post = perch::ScheduledPost::new(content, local_networks, scheduled_for);
+        scheduled_post.recurrence = recurrence;
+
+        db.save_scheduled_post(&scheduled_post)?;
+        print_scheduled(&scheduled_post, timezone);
+    }
+
+    Ok(())
+}
+
+/// Cancel a post scheduled on the default Mastodon account's server
+#[allow(clippy::future_not_send)]
+async fn cancel_server_scheduled(db: &perch::Database, id: &str) -> Result<()> {
+    let networks: Vec<_> = perch::Network::supporting(|c| c.server_scheduling).collect();
+    let account = default_accounts(db, &networks)?.remove(0);
+    let token = perch::auth::get_credentials(&account)?
+        .ok_or_else(|| anyhow::anyhow!("No credentials found for {}", account.handle))?;
+    let client = perch::api::get_client(&account, &token).await?;
+
+    client.cancel_scheduled_post(id).await?;
+    println!("🚫 Cancelled post {id} scheduled on {}", account.server);
+    Ok(())
+}
+
+/// Timezone schedule times are entered and shown in
+fn configured_timezone() -> perch::schedule::Timezone {
+    perch::Config::load()
+        .map(|config| config.timezone)
+        .unwrap_or_default()
+}
+
+/// Confirm a post was scheduled
+fn print_scheduled(post: &perch::ScheduledPost, timezone: perch::schedule::Timezone) {
+    let networks_str = post
+        .networks
+        .iter()
+        .map(perch::Network::name)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    println!("📅 Post scheduled!");
+    println!("   Networks: {}", networks_str);
+    println!("   Time: {}", post.scheduled_time_display(&timezone));
+    println!("   In: {}", post.time_until());
+    if let Some(recurrence) = &post.recurrence {
+        println!("   Repeats: {recurrence}");
+    }
+    println!("\n   ID: {}", &post.id.to_string()[..8]);
+    println!("\n   Run 'perch schedule list' to see pending posts");
+    println!("   Run 'perch schedule run' to process due posts");
+}
+
+/// The default account of each network
+fn default_accounts(
+    db: &perch::Database,
+    networks: &[perch::Network],
+) -> Result<Vec<perch::Account>> {
+    networks
+        .iter()
+        .map(|network| {
+            db.get_default_account(*network)?.ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No {} account configured. Run: perch auth {}",
+                    network.name(),
+                    format!("{:?}", network).to_lowercase()
+                )
+            })
+        })
+        .collect()
+}
+
+/// Look up the accounts to post from by handle. A handle matches with or
+/// without its server (`alice` or `alice@mastodon.social`).
+fn accounts_by_handle(
+    accounts: &[perch::Account],
+    handles: &[String],
+) -> Result<Vec<perch::Account>> {
+    let mut found: Vec<perch::Account> = Vec::new();
+    for handle in handles {
+        let matches: Vec<_> = accounts
+            .iter()
+            .filter(|a| a.matches_handle(handle))
+            .collect();
+        match matches[..] {
+            [account] => {
+                if !found.iter().any(|a| a.id == account.id) {
+                    found.push(account.clone());
+                }
+            }
+            [] => anyhow::bail!("No account @{handle}. Run 'perch accounts' to list them"),
+            _ => anyhow::bail!("@{handle} matches several accounts, add the server to pick one"),
+        }
+    }
+    Ok(found)
+}
+
+/// The network a handle to follow lives on: Mastodon handles carry their
+/// server (`alice@mastodon.social`), Bluesky handles are domains.
+fn network_for_handle(handle: &str) -> perch::Network {
+    if handle.trim_start_matches('@').contains('@') {
+        perch::Network::Mastodon
+    } else {
+        perch::Network::Bluesky
+    }
+}
+
+/// Client for acting on `handle`: from our account `ours`, else the
+/// default account of the handle's network
+#[allow(clippy::future_not_send)]
+async fn client_for_handle(
+    db: &perch::Database,
+    handle: &str,
+    ours: Option<&str>,
+) -> Result<(perch::Account, perch::api::Client)> {
+    let account = match ours {
+        Some(ours) => accounts_by_handle(&db.get_accounts()?, &[ours.to_string()])?,
+        None => default_accounts(db, &[network_for_handle(handle)])?,
+    }
+    .remove(0);
+    let token = perch::auth::get_credentials(&account)?.ok_or_else(|| {
+        anyhow::anyhow!(
+            "No credentials for @{}. Run: perch auth {}",
+            account.handle,
+            format!("{:?}", account.network).to_lowercase()
+        )
+    })?;
+    let client = perch::api::get_client(&account, &token).await?;
+    Ok((account, client))
+}
+
+/// Mute or block an account (or with `undo`, lift it), here and on the server
+#[allow(clippy::future_not_send)]
+async fn moderate_cli(
+    handle: &str,
+    account: Option<&str>,
+    moderation: perch::Moderation,
+    undo: bool,
+) -> Result<()> {
+    let db = perch::Database::open()?;
+    let handle = handle.trim_start_matches('@');
+    let (account, client) = client_for_handle(&db, handle, account).await?;
+
+    let profile = client.profile(handle).await?;
+    client.moderate(&profile, moderation, undo).await?;
+
+    // Posts show the handle the way the server knows it
+    if undo {
+        db.remove_moderated(profile.network, &profile.handle, moderation)?;
+    } else {
+        db.add_moderated(&perch::ModeratedAccount::new(
+            profile.network,
+            &profile.handle,
+            moderation,
+        ))?;
+    }
+    println!(
+        "{} {} @{} (as @{})",
+        moderation.emoji(),
+        moderation.done(undo),
+        profile.handle,
+        account.handle
+    );
+    Ok(())
+}
+
+/// Follow or unfollow an account, resolving its handle first
+#[allow(clippy::future_not_send)]
+async fn follow_cli(handle: &str, account: Option<&str>, follow: bool) -> Result<()> {
+    let db = perch::Database::open()?;
+    let handle = handle.trim_start_matches('@');
+    let (account, client) = client_for_handle(&db, handle, account).await?;
+
+    let profile = client.profile(handle).await?;
+    let following = profile.follow_state != perch::FollowState::NotFollowing;
+    if follow && following {
+        println!(
+            "Already {} @{}",
+            profile.follow_state.label().to_lowercase(),
+            profile.handle
+        );
+        return Ok(());
+    }
+    if !follow && !following {
+        println!("Not following @{}", profile.handle);
+        return Ok(());
+    }
+
+    let profile = if follow {
+        client.follow(&profile).await?
+    } else {
+        client.unfollow(&profile).await?
+    };
+    match profile.follow_state {
+        perch::FollowState::Following => {
+            println!("✓ Following @{} as @{}", profile.handle, account.handle);
+        }
+        perch::FollowState::Requested => {
+            println!(
+                "⏳ Follow request sent to @{}; they approve followers, so it's pending until they do",
+                profile.handle
+            );
+        }
+        perch::FollowState::NotFollowing => {
+            println!("✓ Unfollowed @{}", profile.handle);
+        }
+    }
+    Ok(())
+}
 
 /// Handle schedule subcommands
 #[allow(clippy::too_many_lines, clippy::future_not_send)]
@@ -492,7 +1623,17 @@ async fn schedule_cli(subcommand: ScheduleSubcommand) -> Result<()> {
 
     match subcommand {
         ScheduleSubcommand::List => {
-            let posts = db.get_pending_scheduled_posts()?;
+            let posts: Vec<_> = db
+                .get_scheduled_posts()?
+                .into_iter()
+                .filter(|p| {
+                    matches!(
+                        p.status,
+                        perch::ScheduledPostStatus::Pending | perch::ScheduledPostStatus::Failed
+                    )
+                })
+                .collect();
+            let timezone = configured_timezone();
 
             if posts.is_empty() {
                 println!("No scheduled posts.");
@@ -518,9 +1659,13 @@ async fn schedule_cli(subcommand: ScheduleSubcommand) -> Result<()> {
                 println!("   To: {}", networks_str);
                 println!(
                     "   At: {} (in {})",
-                    post.scheduled_time_display(),
+                    post.scheduled_time_display(&timezone),
                     post.time_until()
                 );
+                if let Some(recurrence) = &post.recurrence {
+                    println!("   Repeats: {recurrence}");
+                }
+                print_retry_state(&post, timezone);
                 println!();
             }
         }
@@ -534,6 +1679,10 @@ async fn schedule_cli(subcommand: ScheduleSubcommand) -> Result<()> {
                 .collect();
 
             match matching.len() {
+                // Mastodon IDs are numeric; local ones are UUIDs
+                0 if id.chars().all(|c| c.is_ascii_digit()) => {
+                    cancel_server_scheduled(&db, &id).await?;
+                }
                 0 => {
                     return Err(anyhow::anyhow!(
                         "No scheduled post found with ID starting with '{}'",
@@ -559,6 +1708,23 @@ async fn schedule_cli(subcommand: ScheduleSubcommand) -> Result<()> {
             }
         }
 
+        ScheduleSubcommand::Retry { id } => {
+            let mut post = find_scheduled_post(&db, &id, |p| {
+                p.status == perch::ScheduledPostStatus::Failed || p.retry_count > 0
+            })?;
+            post.retry_now();
+            db.update_scheduled_post(&post)?;
+            println!("🔄 Retrying on the next run: {}", &post.id.to_string()[..8]);
+            println!("   \"{}\"", truncate_content(&post.content, 50));
+        }
+
+        ScheduleSubcommand::Edit {
+            id,
+            content,
+            networks,
+            time,
+        } => edit_scheduled(&db, &id, content, networks.as_deref(), time.as_deref())?,
+
         ScheduleSubcommand::Run => {
             let due_posts = db.get_due_scheduled_posts()?;
 
@@ -570,125 +1736,277 @@ async fn schedule_cli(subcommand: ScheduleSubcommand) -> Result<()> {
             println!("📤 Processing {} scheduled post(s)...\n", due_posts.len());
 
             for post in due_posts {
-                process_scheduled_post(&db, &post).await?;
+                process_scheduled_post(&db, post, |line| println!("{line}")).await?;
                 println!();
             }
         }
 
-        ScheduleSubcommand::Daemon { interval } => {
-            println!(
-                "🕐 Starting scheduler daemon (checking every {}s)",
-                interval
-            );
-            println!("   Press Ctrl+C to stop\n");
+        ScheduleSubcommand::Daemon { interval, once } => {
+            schedule_daemon(&db, interval, once).await;
+        }
+    }
 
-            // Set up Ctrl+C handler
-            let running = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(true));
-            let r = running.clone();
-            ctrlc::set_handler(move || {
-                r.store(false, std::sync::atomic::Ordering::SeqCst);
-            })?;
+    Ok(())
+}
 
-            while running.load(std::sync::atomic::Ordering::SeqCst) {
-                let due_posts = db.get_due_scheduled_posts()?;
+/// Post scheduled posts as they come due, until stopped by SIGINT or
+/// SIGTERM. With `once`, exits after posting whatever is due now.
+#[allow(clippy::future_not_send)]
+async fn schedule_daemon(db: &perch::Database, interval: u64, once: bool) {
+    let stop = std::sync::Arc::new(tokio::sync::Notify::new());
+    if !once {
+        let notify = stop.clone();
+        // Catches SIGTERM too, so `systemctl stop` shuts down cleanly
+        if let Err(e) = ctrlc::set_handler(move || notify.notify_one()) {
+            daemon_log(&format!(
+                "⚠️  Can't handle Ctrl+C, stopping may interrupt a post: {e}"
+            ));
+        }
+        daemon_log(&format!(
+            "🕐 Scheduler daemon started (checking at least every {interval}s)"
+        ));
+    }
 
+    loop {
+        match db.get_due_scheduled_posts() {
+            Ok(due_posts) => {
                 if !due_posts.is_empty() {
-                    println!(
-                        "[{}] Processing {} due post(s)...",
-                        chrono::Local::now().format("%H:%M:%S"),
-                        due_posts.len()
-                    );
-
-                    for post in due_posts {
-                        process_scheduled_post(&db, &post).await?;
-                    }
+                    daemon_log(&format!("📤 Processing {} due post(s)", due_posts.len()));
                 }
-
-                // Sleep in small increments to allow Ctrl+C to work
-                for _ in 0..interval {
-                    if !running.load(std::sync::atomic::Ordering::SeqCst) {
-                        break;
+                for post in due_posts {
+                    if let Err(e) = process_scheduled_post(db, post, daemon_log).await {
+                        daemon_log(&format!("⚠️  {e}"));
                     }
-                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
                 }
             }
+            Err(e) => daemon_log(&format!("⚠️  Failed to read scheduled posts: {e}")),
+        }
+
+        if once {
+            return;
+        }
 
-            println!("\n👋 Daemon stopped");
+        // A signal that arrived while posting is kept, so this returns at once
+        tokio::select! {
+            () = tokio::time::sleep(daemon_sleep(db, interval)) => {}
+            () = stop.notified() => break,
         }
     }
 
+    daemon_log("👋 Scheduler daemon stopped");
+}
+
+/// How long the daemon sleeps: until the next post is due, but at most
+/// `interval` seconds so posts scheduled in the meantime are noticed
+fn daemon_sleep(db: &perch::Database, interval: u64) -> std::time::Duration {
+    let cap = std::time::Duration::from_secs(interval);
+    let next_due = db
+        .get_pending_scheduled_posts()
+        .ok()
+        .and_then(|posts| posts.iter().map(perch::ScheduledPost::due_at).min());
+
+    next_due
+        .map_or(cap, |at| {
+            (at - chrono::Utc::now())
+                .to_std()
+                .unwrap_or_default()
+                .min(cap)
+        })
+        // Don't spin if a due post can't be processed
+        .max(std::time::Duration::from_secs(1))
+}
+
+/// Write a timestamped daemon log line to stderr
+fn daemon_log(message: &str) {
+    eprintln!(
+        "[{}] {message}",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+    );
+}
+
+/// Change the content, networks or time of a pending scheduled post
+fn edit_scheduled(
+    db: &perch::Database,
+    id: &str,
+    content: Option<String>,
+    networks: Option<&str>,
+    time: Option<&str>,
+) -> Result<()> {
+    if content.is_none() && networks.is_none() && time.is_none() {
+        anyhow::bail!("Nothing to change. Pass --text, --to and/or --schedule");
+    }
+
+    let timezone = configured_timezone();
+    let networks = networks
+        .map(|list| {
+            list.split(',')
+                .map(|n| {
+                    perch::Network::from_str(n.trim())
+                        .ok_or_else(|| anyhow::anyhow!("Unknown network: {n}"))
+                })
+                .collect::<Result<Vec<_>>>()
+        })
+        .transpose()?;
+    let scheduled_for = time
+        .map(|t| perch::schedule::parse_schedule_time(t, &timezone))
+        .transpose()?;
+
+    let mut post = find_scheduled_post(db, id, |_| true)?;
+    post.edit(content, networks, scheduled_for)?;
+    db.update_scheduled_post(&post)?;
+
+    println!("✏️  Updated scheduled post: {}", &post.id.to_string()[..8]);
+    println!("   \"{}\"", truncate_content(&post.content, 60));
+    println!("   To: {}", post.networks_str());
+    println!(
+        "   At: {} (in {})",
+        post.scheduled_time_display(&timezone),
+        post.time_until()
+    );
     Ok(())
 }
 
-/// Process a single scheduled post
+/// Show how a post's failed attempts stand
+fn print_retry_state(post: &perch::ScheduledPost, timezone: perch::schedule::Timezone) {
+    if !post.posted_networks.is_empty() {
+        let posted = post
+            .posted_networks
+            .iter()
+            .map(perch::Network::name)
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("   Already posted to: {posted}");
+    }
+    if let Some(at) = post.next_retry_at {
+        println!(
+            "   Retry {}/{} at {}",
+            post.retry_count,
+            perch::ScheduledPost::MAX_RETRIES,
+            timezone.format(at)
+        );
+    } else if post.status == perch::ScheduledPostStatus::Failed {
+        println!("   Gave up, run 'perch schedule retry' to try again");
+    }
+    if let Some(error) = &post.error {
+        println!("   Last error: {error}");
+    }
+}
+
+/// Find the one scheduled post whose ID starts with `id` among those
+/// matching `filter`
+fn find_scheduled_post(
+    db: &perch::Database,
+    id: &str,
+    filter: impl Fn(&perch::ScheduledPost) -> bool,
+) -> Result<perch::ScheduledPost> {
+    let mut matching: Vec<_> = db
+        .get_scheduled_posts()?
+        .into_iter()
+        .filter(|p| filter(p) && p.id.to_string().starts_with(id))
+        .collect();
+
+    match matching.len() {
+        0 => anyhow::bail!("No scheduled post found with ID starting with '{id}'"),
+        1 => Ok(matching.remove(0)),
+        _ => {
+            let ids = matching
+                .iter()
+                .map(|p| p.id.to_string()[..8].to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            anyhow::bail!("Multiple posts match '{id}' ({ids}). Please be more specific")
+        }
+    }
+}
+
+/// Post a scheduled post's content from a network's default account,
+/// returning the new post's URL
 #[allow(clippy::future_not_send)]
-async fn process_scheduled_post(db: &perch::Database, post: &perch::ScheduledPost) -> Result<()> {
+async fn post_scheduled_to(
+    db: &perch::Database,
+    network: perch::Network,
+    content: &str,
+) -> Result<Option<String>> {
+    let account = db
+        .get_default_account(network)?
+        .ok_or_else(|| anyhow::anyhow!("No {} account configured", network.name()))?;
+    let token = perch::auth::get_credentials(&account)?
+        .ok_or_else(|| anyhow::anyhow!("No credentials for {}", account.handle))?;
+
+    let client = perch::api::get_client(&account, &token)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to connect to {}: {}", network.name(), e))?;
+    let posted = client
+        .post(content, &perch::api::PostOptions::default())
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to post to {}: {}", network.name(), e))?;
+    Ok(posted.url)
+}
+
+/// Process a single scheduled post.
+///
+/// Networks it already went out on are skipped. If any network fails, the
+/// post waits and is retried with backoff, until it runs out of retries.
+#[allow(clippy::future_not_send)]
+async fn process_scheduled_post(
+    db: &perch::Database,
+    mut post: perch::ScheduledPost,
+    log: fn(&str),
+) -> Result<()> {
     let id_short = &post.id.to_string()[..8];
-    println!(
+    log(&format!(
         "  [{}]: \"{}\"",
         id_short,
         truncate_content(&post.content, 40)
-    );
+    ));
 
     // Mark as posting
     db.update_scheduled_post_status(post.id, perch::ScheduledPostStatus::Posting, None)?;
 
-    let mut success = true;
-    let mut error_msg = String::new();
-
-    for network in &post.networks {
-        let Some(account) = db.get_default_account(*network)? else {
-            let msg = format!("No {} account configured", network.name());
-            println!("    ⚠️  {}", msg);
-            error_msg = msg;
-            success = false;
-            continue;
-        };
-
-        let Some(token) = perch::auth::get_credentials(&account)? else {
-            let msg = format!("No credentials for {}", account.handle);
-            println!("    ⚠️  {}", msg);
-            error_msg = msg;
-            success = false;
-            continue;
-        };
-
-        match perch::api::get_client(&account, &token).await {
-            Ok(client) => match client.post(&post.content).await {
-                Ok(posted) => {
-                    if let Some(url) = &posted.url {
-                        println!("    {} ✓ {}: {}", network.emoji(), network.name(), url);
-                    } else {
-                        println!("    {} ✓ {}", network.emoji(), network.name());
-                    }
-                }
-                Err(e) => {
-                    let msg = format!("Failed to post to {}: {}", network.name(), e);
-                    println!("    {} ✗ {}", network.emoji(), msg);
-                    error_msg = msg;
-                    success = false;
+    let mut error_msg = None;
+    for network in post.remaining_networks() {
+        match post_scheduled_to(db, network, &post.content).await {
+            Ok(url) => {
+                if let Some(url) = url {
+                    log(&format!(
+                        "    {} ✓ {}: {}",
+                        network.emoji(),
+                        network.name(),
+                        url
+                    ));
+                } else {
+                    log(&format!("    {} ✓ {}", network.emoji(), network.name()));
                 }
-            },
+                post.posted_networks.push(network);
+            }
             Err(e) => {
-                let msg = format!("Failed to connect to {}: {}", network.name(), e);
-                println!("    {} ✗ {}", network.emoji(), msg);
-                error_msg = msg;
-                success = false;
+                log(&format!("    {} ✗ {}", network.emoji(), e));
+                error_msg = Some(e.to_string());
             }
         }
     }
 
-    // Update status
-    if success {
-        db.update_scheduled_post_status(post.id, perch::ScheduledPostStatus::Posted, None)?;
-        println!("    ✅ Done");
+    // Update status, moving repeating posts on to their next time
+    let timezone = configured_timezone();
+    let now = chrono::Utc::now();
+    if let Some(error) = error_msg {
+        post.record_failure(error, now);
+        db.update_scheduled_post(&post)?;
+        match post.next_retry_at {
+            Some(at) => log(&format!(
+                "    ⏳ Failed, retry {}/{} at {}",
+                post.retry_count,
+                perch::ScheduledPost::MAX_RETRIES,
+                timezone.format(at)
+            )),
+            None => log("    ❌ Failed, giving up"),
+        }
+    } else if let Some(next) = post.next_occurrence(now, &timezone) {
+        db.reschedule_post(post.id, next)?;
+        log(&format!("    🔁 Done, next at {}", timezone.format(next)));
     } else {
-        db.update_scheduled_post_status(
-            post.id,
-            perch::ScheduledPostStatus::Failed,
-            Some(&error_msg),
-        )?;
-        println!("    ❌ Failed");
+        db.update_scheduled_post_status(post.id, perch::ScheduledPostStatus::Posted, None)?;
+        log("    ✅ Done");
     }
 
     Ok(())
@@ -750,6 +2068,264 @@ async fn timeline_cli(network: Option<&str>, limit: usize) -> Result<()> {
     Ok(())
 }
 
+fn export_cli(
+    format: perch::export::ExportFormat,
+    network: Option<&str>,
+    out: Option<&std::path::Path>,
+) -> Result<()> {
+    let network = network
+        .map(|name| {
+            perch::Network::from_str(name).ok_or_else(|| {
+                anyhow::anyhow!("Unknown network: {name}\nSupported: mastodon, bluesky")
+            })
+        })
+        .transpose()?;
+    let posts = perch::Database::open()?.export_posts(network)?;
+
+    match out {
+        Some(path) => {
+            let file = std::fs::File::create(path)
+                .map_err(|e| anyhow::anyhow!("Failed to create {}: {e}", path.display()))?;
+            format.write(&posts, std::io::BufWriter::new(file))?;
+            eprintln!("✓ Exported {} posts to {}", posts.len(), path.display());
+        }
+        None => format.write(&posts, std::io::stdout().lock())?,
+    }
+    Ok(())
+}
+
+async fn logout_cli(network: Option<&str>, handle: Option<&str>) -> Result<()> {
+    let db = perch::Database::open()?;
+    let accounts = db.get_accounts()?;
+
+    let (removing, remaining): (Vec<_>, Vec<_>) = match network {
+        None => (accounts, Vec::new()),
+        Some(name) => {
+            let network = perch::Network::from_str(name).ok_or_else(|| {
+                anyhow::anyhow!("Unknown network: {name}\nSupported: mastodon, bluesky")
+            })?;
+            accounts
+                .into_iter()
+                .partition(|a| a.network == network && handle.is_none_or(|h| a.matches_handle(h)))
+        }
+    };
+
+    if removing.is_empty() && network.is_some() {
+        return Err(anyhow::anyhow!("No matching accounts to log out"));
+    }
+
+    for account in &removing {
+        // Local state goes even if the server can't be reached
+        match perch::api::revoke_credentials(account).await {
+            Ok(true) => println!("✓ Signed out @{} on {}", account.handle, account.server),
+            Ok(false) => {}
+            Err(e) => eprintln!(
+                "⚠ Couldn't sign out @{} on the server: {e:#}",
+                account.handle
+            ),
+        }
+        db.delete_account(account.id)?;
+        let removed = perch::auth::remove_account_credentials(account, &remaining)?;
+        println!(
+            "✓ Logged out {} @{} (removed {})",
+            account.network.emoji(),
+            account.handle,
+            removed.summary()
+        );
+    }
+
+    if network.is_none() {
+        perch::auth::clear_credentials()?;
+        println!("✓ Stored credentials cleared");
+    }
+
+    Ok(())
+}
+
+fn config_cli(subcommand: ConfigSubcommand) -> Result<()> {
+    let path = perch::Config::default_path()?;
+    match subcommand {
+        ConfigSubcommand::Path => println!("{}", path.display()),
+        ConfigSubcommand::Show => {
+            let config = perch::Config::load()?;
+            println!("# {}", path.display());
+            print!("{}", toml::to_string_pretty(&config)?);
+        }
+        ConfigSubcommand::Get { key } => println!("{}", perch::Config::load()?.get(&key)?),
+        ConfigSubcommand::Set { key, value } => {
+            let mut config = perch::Config::load()?;
+            config.set(&key, &value)?;
+            config.save()?;
+            println!("✓ {key} = {}", config.get(&key)?);
+        }
+    }
+    Ok(())
+}
+
+fn accounts_cli(subcommand: AccountsSubcommand) -> Result<()> {
+    match subcommand {
+        AccountsSubcommand::List => list_accounts(),
+        AccountsSubcommand::Export {
+            path,
+            with_credentials,
+        } => export_accounts(&path, with_credentials),
+        AccountsSubcommand::Import { path } => import_accounts(&path),
+    }
+}
+
+/// Shortest passphrase accepted for sealing exported credentials
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// Ask for a passphrase on stdin, without echoing it when stdin is a
+/// terminal
+fn read_passphrase(prompt: &str) -> Result<String> {
+    use std::io::IsTerminal;
+
+    println!("{prompt}");
+    if !std::io::stdin().is_terminal() {
+        let mut passphrase = String::new();
+        std::io::stdin().read_line(&mut passphrase)?;
+        return Ok(passphrase.trim_end_matches(['\r', '\n']).to_string());
+    }
+
+    crossterm::terminal::enable_raw_mode()?;
+    let passphrase = read_hidden_line();
+    crossterm::terminal::disable_raw_mode()?;
+    println!();
+    passphrase
+}
+
+/// Read keys up to Enter in raw mode, so nothing typed shows up
+fn read_hidden_line() -> Result<String> {
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+
+    let mut line = String::new();
+    loop {
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Enter => return Ok(line),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                anyhow::bail!("Cancelled")
+            }
+            KeyCode::Esc => anyhow::bail!("Cancelled"),
+            KeyCode::Backspace => {
+                line.pop();
+            }
+            KeyCode::Char(c) => line.push(c),
+            _ => {}
+        }
+    }
+}
+
+fn export_accounts(path: &std::path::Path, with_credentials: bool) -> Result<()> {
+    let accounts = perch::Database::open()?.get_accounts()?;
+    if accounts.is_empty() {
+        anyhow::bail!("No accounts to export");
+    }
+
+    let passphrase = if with_credentials {
+        println!("⚠️  Anyone with the file and the passphrase can use these accounts.");
+        let passphrase = read_passphrase("Choose a passphrase to encrypt the credentials:")?;
+        if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+            anyhow::bail!("The passphrase must be at least {MIN_PASSPHRASE_LEN} characters");
+        }
+        if read_passphrase("Repeat the passphrase:")? != passphrase {
+            anyhow::bail!("The passphrases don't match");
+        }
+        Some(passphrase)
+    } else {
+        None
+    };
+
+    let export = perch::auth::AccountsExport::new(accounts, passphrase.as_deref())?;
+    std::fs::write(path, export.to_json()?)
+        .map_err(|e| anyhow::anyhow!("Failed to write {}: {e}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+
+    println!(
+        "✓ Exported {} account(s) to {}{}",
+        export.len(),
+        path.display(),
+        if with_credentials {
+            " with their credentials"
+        } else {
+            "; log in to each again after importing"
+        }
+    );
+    Ok(())
+}
+
+fn import_accounts(path: &std::path::Path) -> Result<()> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+    let export = perch::auth::AccountsExport::parse(&json)?;
+    let passphrase = if export.has_credentials() {
+        Some(read_passphrase("Passphrase for the exported credentials:")?)
+    } else {
+        None
+    };
+
+    let db = perch::Database::open()?;
+    let total = export.len();
+    let imported = export.accounts_to_import(&db.get_accounts()?, passphrase.as_deref())?;
+
+    let mut need_login = Vec::new();
+    for item in &imported {
+        let account = &item.account;
+        db.insert_account(account)?;
+        match &item.token {
+            Some(token) => perch::auth::store_credentials(account, token)?,
+            None => need_login.push(account),
+        }
+        if let Some(session) = &item.session {
+            perch::auth::store_session(account, session)?;
+        }
+        if let Some((client_id, client_secret)) = &item.oauth_client
+            && perch::auth::get_oauth_client(&account.server)?.is_none()
+        {
+            perch::auth::store_oauth_client(&account.server, client_id, client_secret)?;
+        }
+        println!(
+            "✓ {} {} {}",
+            account.network.emoji(),
+            account.display_name,
+            account.full_handle()
+        );
+    }
+
+    let skipped = total - imported.len();
+    println!(
+        "\nImported {} account(s){}",
+        imported.len(),
+        if skipped > 0 {
+            format!(", skipped {skipped} already set up")
+        } else {
+            String::new()
+        }
+    );
+    if !need_login.is_empty() {
+        println!("Log in again to use:");
+        for account in need_login {
+            match account.network {
+                perch::Network::Mastodon => {
+                    println!("  perch auth mastodon {}", account.server_host());
+                }
+                perch::Network::Bluesky => println!("  perch auth bluesky"),
+            }
+        }
+    }
+    Ok(())
+}
+
 fn list_accounts() -> Result<()> {
     let db = perch::Database::open()?;
     let accounts = db.get_accounts()?;
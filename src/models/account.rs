@@ -27,6 +27,53 @@ pub struct Account {
     pub created_at: DateTime<Utc>,
     /// Last used timestamp
     pub last_used_at: Option<DateTime<Utc>>,
+    /// Longest post the server accepts, in characters, once it has told us
+    #[serde(default)]
+    pub max_post_chars: Option<usize>,
+    /// Version the server reports about itself, which names the software
+    /// of Mastodon-compatible servers (see [`ServerSoftware`])
+    #[serde(default)]
+    pub server_version: Option<String>,
+    /// What the server calls itself: a Mastodon instance's title, or a
+    /// Bluesky PDS's DID
+    #[serde(default)]
+    pub server_name: Option<String>,
+    /// Most attachments a post can carry, once the server has told us
+    #[serde(default)]
+    pub max_media_attachments: Option<usize>,
+    /// Largest image the server accepts, in bytes, once it has told us
+    #[serde(default)]
+    pub max_image_bytes: Option<usize>,
+}
+
+/// Software a Mastodon-compatible server runs, for working around the ways
+/// they differ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerSoftware {
+    /// Mastodon itself, or a server that doesn't say otherwise
+    Mastodon,
+    /// Pleroma
+    Pleroma,
+    /// Akkoma (a Pleroma fork)
+    Akkoma,
+}
+
+impl ServerSoftware {
+    /// Identify the software from the version in `/api/v1/instance`.
+    /// Compatible servers report one like `2.7.2 (compatible; Pleroma 2.5.0)`.
+    pub fn from_version(version: &str) -> Self {
+        let compatible = version
+            .split_once("(compatible;")
+            .map(|(_, rest)| rest.to_lowercase())
+            .unwrap_or_default();
+        if compatible.contains("akkoma") {
+            Self::Akkoma
+        } else if compatible.contains("pleroma") {
+            Self::Pleroma
+        } else {
+            Self::Mastodon
+        }
+    }
 }
 
 impl Account {
@@ -42,6 +89,11 @@ impl Account {
             avatar_url: None,
             created_at: Utc::now(),
             last_used_at: None,
+            max_post_chars: None,
+            server_version: None,
+            server_name: None,
+            max_media_attachments: None,
+            max_image_bytes: None,
         }
     }
 
@@ -62,7 +114,65 @@ impl Account {
             avatar_url: None,
             created_at: Utc::now(),
             last_used_at: None,
+            max_post_chars: None,
+            server_version: None,
+            server_name: None,
+            max_media_attachments: None,
+            max_image_bytes: None,
+        }
+    }
+
+    /// Longest post this account can publish, in characters
+    pub fn post_char_limit(&self) -> usize {
+        self.max_post_chars
+            .unwrap_or_else(|| self.network.default_char_limit())
+    }
+
+    /// Software the account's server runs, once it has told us
+    pub fn server_software(&self) -> Option<ServerSoftware> {
+        self.server_version
+            .as_deref()
+            .map(ServerSoftware::from_version)
+    }
+
+    /// Software and version of the account's server, e.g. `Mastodon 4.3.1`
+    /// or `Akkoma 3.13.2`, once it has told us
+    pub fn software_label(&self) -> Option<String> {
+        let version = self.server_version.as_deref();
+        match self.network {
+            Network::Bluesky => Some(
+                version.map_or_else(|| "Bluesky PDS".to_string(), |v| format!("Bluesky PDS {v}")),
+            ),
+            Network::Mastodon => {
+                let version = version?;
+                Some(version.split_once("(compatible;").map_or_else(
+                    || format!("Mastodon {version}"),
+                    |(_, software)| software.trim_end_matches(')').trim().to_string(),
+                ))
+            }
+        }
+    }
+
+    /// Keep what the server reported about itself in `info`, leaving alone
+    /// what it didn't say
+    pub fn update_server_info(&mut self, info: &Self) {
+        if let Some(version) = &info.server_version {
+            self.server_version = Some(version.clone());
         }
+        if let Some(name) = &info.server_name {
+            self.server_name = Some(name.clone());
+        }
+        self.max_post_chars = info.max_post_chars.or(self.max_post_chars);
+        self.max_media_attachments = info.max_media_attachments.or(self.max_media_attachments);
+        self.max_image_bytes = info.max_image_bytes.or(self.max_image_bytes);
+    }
+
+    /// Server host without the scheme, e.g. `mastodon.social`
+    pub fn server_host(&self) -> &str {
+        self.server
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_end_matches('/')
     }
 
     /// Get the full handle with instance (for Mastodon)
@@ -72,13 +182,7 @@ impl Account {
                 if self.handle.contains('@') {
                     self.handle.clone()
                 } else {
-                    // Extract domain from server URL
-                    let domain = self
-                        .server
-                        .trim_start_matches("https://")
-                        .trim_start_matches("http://")
-                        .trim_end_matches('/');
-                    format!("@{}@{}", self.handle, domain)
+                    format!("@{}@{}", self.handle, self.server_host())
                 }
             }
             Network::Bluesky => {
@@ -91,8 +195,44 @@ impl Account {
         }
     }
 
+    /// Whether `handle` names this account: its handle or its full handle,
+    /// with or without a leading `@`, ignoring case
+    pub fn matches_handle(&self, handle: &str) -> bool {
+        let handle = handle.trim_start_matches('@');
+        self.handle
+            .trim_start_matches('@')
+            .eq_ignore_ascii_case(handle)
+            || self
+                .full_handle()
+                .trim_start_matches('@')
+                .eq_ignore_ascii_case(handle)
+    }
+
     /// Get the keyring key for storing credentials
     pub fn keyring_key(&self) -> String {
         format!("perch:{}:{}", self.network.name().to_lowercase(), self.id)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_handle() {
+        let alice = Account::new_mastodon("alice", "https://mastodon.social", "Alice");
+        for handle in [
+            "alice",
+            "@alice",
+            "alice@mastodon.social",
+            "@Alice@Mastodon.Social",
+        ] {
+            assert!(alice.matches_handle(handle), "{handle}");
+        }
+        assert!(!alice.matches_handle("alice@hachyderm.io"));
+
+        let bob = Account::new_bluesky("bob.bsky.social", "Bob");
+        assert!(bob.matches_handle("@bob.bsky.social"));
+        assert!(!bob.matches_handle("bob"));
+    }
+}
@@ -0,0 +1,48 @@
+//! Draft model (unsent compose buffers)
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Network, Post};
+
+/// A saved compose buffer that can be reopened later
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Draft {
+    /// Unique identifier
+    pub id: Uuid,
+    /// Post content
+    pub content: String,
+    /// Networks selected in compose
+    pub networks: Vec<Network>,
+    /// Post being replied to (if this is a reply)
+    pub reply_to: Option<Post>,
+    /// When this was first saved
+    pub created_at: DateTime<Utc>,
+    /// When this was last saved
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Draft {
+    /// Create a new draft
+    pub fn new(content: impl Into<String>, networks: Vec<Network>, reply_to: Option<Post>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            content: content.into(),
+            networks,
+            reply_to,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// First line of the content, for the drafts picker
+    pub fn preview(&self) -> &str {
+        self.content
+            .lines()
+            .find(|l| !l.trim().is_empty())
+            .unwrap_or("")
+            .trim()
+    }
+}
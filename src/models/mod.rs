@@ -1,11 +1,27 @@
 //! Data models for Perch
 
 mod account;
+mod draft;
+mod moderation;
 mod network;
+mod notification;
 mod post;
+mod profile;
+mod recurrence;
 mod scheduled_post;
+mod thread;
+mod visibility;
 
-pub use account::Account;
-pub use network::Network;
-pub use post::{MediaAttachment, MediaType, Post};
+pub use account::{Account, ServerSoftware};
+pub use draft::Draft;
+pub use moderation::{ModeratedAccount, Moderation, normalize_handle};
+pub use network::{Capabilities, Network, NetworkInfo};
+pub use notification::{Notification, NotificationKind};
+pub use post::{
+    LinkCard, MediaAttachment, MediaType, Post, find_links, format_relative_time, split_hashtags,
+};
+pub use profile::{FollowState, Profile};
+pub use recurrence::{CronSchedule, Recurrence};
 pub use scheduled_post::{ScheduledPost, ScheduledPostStatus};
+pub use thread::split_thread;
+pub use visibility::Visibility;
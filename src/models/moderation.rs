@@ -0,0 +1,97 @@
+//! Muted and blocked accounts
+
+use chrono::{DateTime, Utc};
+
+use super::Network;
+
+/// How an account is kept out of the timeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Moderation {
+    /// Hide their posts; they can still follow and reply
+    Mute,
+    /// Hide their posts and stop them from following or interacting
+    Block,
+}
+
+impl Moderation {
+    /// Get the value stored in the database
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Mute => "mute",
+            Self::Block => "block",
+        }
+    }
+
+    /// Parse from the stored value
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "mute" => Some(Self::Mute),
+            "block" => Some(Self::Block),
+            _ => None,
+        }
+    }
+
+    /// Verb for prompts and errors ("mute", "unblock")
+    pub const fn verb(self, undo: bool) -> &'static str {
+        match (self, undo) {
+            (Self::Mute, false) => "mute",
+            (Self::Mute, true) => "unmute",
+            (Self::Block, false) => "block",
+            (Self::Block, true) => "unblock",
+        }
+    }
+
+    /// Past tense for status messages ("Muted", "Unblocked")
+    pub const fn done(self, undo: bool) -> &'static str {
+        match (self, undo) {
+            (Self::Mute, false) => "Muted",
+            (Self::Mute, true) => "Unmuted",
+            (Self::Block, false) => "Blocked",
+            (Self::Block, true) => "Unblocked",
+        }
+    }
+
+    /// Get the emoji icon
+    pub const fn emoji(self) -> &'static str {
+        match self {
+            Self::Mute => "🔇",
+            Self::Block => "🚫",
+        }
+    }
+}
+
+/// An account muted or blocked from perch, whose posts are hidden locally
+/// whatever the server has caught up with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModeratedAccount {
+    /// Which network the account is on
+    pub network: Network,
+    /// Handle, as it appears on posts (lowercase)
+    pub handle: String,
+    /// Muted or blocked
+    pub moderation: Moderation,
+    /// When it was muted or blocked
+    pub created_at: DateTime<Utc>,
+}
+
+impl ModeratedAccount {
+    /// Create a new entry for `handle` (a leading `@` and case are ignored)
+    pub fn new(network: Network, handle: &str, moderation: Moderation) -> Self {
+        Self {
+            network,
+            handle: normalize_handle(handle),
+            moderation,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Whether this entry covers `handle` on `network`
+    pub fn matches(&self, network: Network, handle: &str) -> bool {
+        self.network == network && self.handle == normalize_handle(handle)
+    }
+}
+
+/// Handles are compared without a leading `@` and case-insensitively
+pub fn normalize_handle(handle: &str) -> String {
+    handle.trim_start_matches('@').to_lowercase()
+}
@@ -1,4 +1,10 @@
 //! Network type definitions
+//!
+//! Everything Perch needs to know about a network, other than how to talk
+//! to it, lives in its [`NetworkInfo`] entry in the registry. Code that
+//! depends on what a network can do asks [`Network::capabilities`] rather
+//! than matching on the network, so a new one is mostly a new entry (and a
+//! client in [`crate::api`]).
 
 use serde::{Deserialize, Serialize};
 
@@ -13,43 +19,146 @@ pub enum Network {
     Bluesky,
 }
 
+/// What a network supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Capabilities {
+    /// Posts can quote other posts (embedded, or linked above the text)
+    pub quotes: bool,
+    /// Your own posts can be edited after posting
+    pub edit: bool,
+    /// Posts can be bookmarked
+    pub bookmarks: bool,
+    /// Posts have a visibility (public, unlisted, followers, direct)
+    pub visibility: bool,
+    /// Posts can carry a content warning
+    pub content_warnings: bool,
+    /// The server can hold a post and publish it later
+    pub server_scheduling: bool,
+    /// Accounts have keyword filters stored on the server
+    pub server_filters: bool,
+    /// The server reports its own post length limit
+    pub server_char_limit: bool,
+    /// Servers have local and federated timelines
+    pub public_timelines: bool,
+    /// Logging in registers an OAuth app with the server
+    pub oauth: bool,
+}
+
+/// A network in the registry
+#[derive(Debug)]
+pub struct NetworkInfo {
+    /// The network
+    pub network: Network,
+    /// Display name
+    pub name: &'static str,
+    /// Emoji icon
+    pub emoji: &'static str,
+    /// Color for theming (hex)
+    pub color: &'static str,
+    /// Names it can be given on the command line and in the config, lowercase
+    pub aliases: &'static [&'static str],
+    /// Post length limit in characters, unless the server says otherwise
+    pub default_char_limit: usize,
+    /// What it supports
+    pub capabilities: Capabilities,
+}
+
+/// Every supported network, in the order of [`Network`]'s variants
+static REGISTRY: [NetworkInfo; 2] = [
+    NetworkInfo {
+        network: Network::Mastodon,
+        name: "Mastodon",
+        emoji: "🐘",
+        color: "#6364FF", // Mastodon purple
+        aliases: &["mastodon", "masto"],
+        default_char_limit: 500,
+        capabilities: Capabilities {
+            quotes: true,
+            edit: true,
+            bookmarks: true,
+            visibility: true,
+            content_warnings: true,
+            server_scheduling: true,
+            server_filters: true,
+            server_char_limit: true,
+            public_timelines: true,
+            oauth: true,
+        },
+    },
+    NetworkInfo {
+        network: Network::Bluesky,
+        name: "Bluesky",
+        emoji: "🦋",
+        color: "#0085FF", // Bluesky blue
+        aliases: &["bluesky", "bsky"],
+        default_char_limit: 300,
+        capabilities: Capabilities {
+            quotes: true,
+            edit: false,
+            bookmarks: false,
+            visibility: false,
+            content_warnings: false,
+            server_scheduling: false,
+            server_filters: false,
+            server_char_limit: false,
+            public_timelines: false,
+            oauth: false,
+        },
+    },
+];
+
 impl Network {
     /// Get all supported networks
     pub const fn all() -> &'static [Self] {
         &[Self::Mastodon, Self::Bluesky]
     }
 
+    /// The network's registry entry
+    pub const fn info(&self) -> &'static NetworkInfo {
+        &REGISTRY[*self as usize]
+    }
+
+    /// What the network supports
+    pub const fn capabilities(&self) -> Capabilities {
+        self.info().capabilities
+    }
+
     /// Get the display name
     pub const fn name(&self) -> &'static str {
-        match self {
-            Self::Mastodon => "Mastodon",
-            Self::Bluesky => "Bluesky",
-        }
+        self.info().name
     }
 
     /// Get the emoji icon
     pub const fn emoji(&self) -> &'static str {
-        match self {
-            Self::Mastodon => "🐘",
-            Self::Bluesky => "🦋",
-        }
+        self.info().emoji
     }
 
     /// Get the color for theming (hex)
     pub const fn color(&self) -> &'static str {
-        match self {
-            Self::Mastodon => "#6364FF", // Mastodon purple
-            Self::Bluesky => "#0085FF",  // Bluesky blue
-        }
+        self.info().color
+    }
+
+    /// Post length limit in characters, unless the server says otherwise
+    pub const fn default_char_limit(&self) -> usize {
+        self.info().default_char_limit
     }
 
     /// Parse from string
     pub fn from_str(s: &str) -> Option<Self> {
-        match s.to_lowercase().as_str() {
-            "mastodon" | "masto" => Some(Self::Mastodon),
-            "bluesky" | "bsky" => Some(Self::Bluesky),
-            _ => None,
-        }
+        let s = s.to_lowercase();
+        REGISTRY
+            .iter()
+            .find(|info| info.aliases.contains(&s.as_str()))
+            .map(|info| info.network)
+    }
+
+    /// Networks that support something
+    pub fn supporting(supports: impl Fn(Capabilities) -> bool) -> impl Iterator<Item = Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .filter(move |network| supports(network.capabilities()))
     }
 }
 
@@ -58,3 +167,19 @@ impl std::fmt::Display for Network {
         write!(f, "{}", self.name())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_matches_variants() {
+        assert_eq!(REGISTRY.len(), Network::all().len());
+        for network in Network::all() {
+            assert_eq!(network.info().network, *network);
+            assert_eq!(Network::from_str(network.name()), Some(*network));
+        }
+        assert_eq!(Network::from_str("BSKY"), Some(Network::Bluesky));
+        assert_eq!(Network::from_str("twitter"), None);
+    }
+}
@@ -0,0 +1,85 @@
+//! Notification model (unified across networks)
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::post::format_relative_time;
+use super::{Network, Post};
+
+/// What a notification is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationKind {
+    /// Someone mentioned you
+    Mention,
+    /// Someone replied to your post
+    Reply,
+    /// Someone liked/favourited your post
+    Like,
+    /// Someone reposted/boosted your post
+    Repost,
+    /// Someone followed you (or requested to)
+    Follow,
+    /// Someone quoted your post
+    Quote,
+    /// Anything else (polls, edits, new posts from followed accounts, ...)
+    Other,
+}
+
+impl NotificationKind {
+    /// Get the emoji shown next to this kind of notification
+    pub const fn emoji(&self) -> &'static str {
+        match self {
+            Self::Mention => "💬",
+            Self::Reply => "↩️",
+            Self::Like => "❤️",
+            Self::Repost => "🔁",
+            Self::Follow => "👤",
+            Self::Quote => "🗨️",
+            Self::Other => "🔔",
+        }
+    }
+
+    /// Describe what the actor did, e.g. "liked your post"
+    pub const fn action(&self) -> &'static str {
+        match self {
+            Self::Mention => "mentioned you",
+            Self::Reply => "replied to you",
+            Self::Like => "liked your post",
+            Self::Repost => "reposted your post",
+            Self::Follow => "followed you",
+            Self::Quote => "quoted your post",
+            Self::Other => "sent a notification",
+        }
+    }
+}
+
+/// A notification (unified model for all networks)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    /// Network-specific ID
+    pub id: String,
+    /// Which network this notification is from
+    pub network: Network,
+    /// What happened
+    pub kind: NotificationKind,
+    /// Handle of the account that triggered it
+    pub actor_handle: String,
+    /// Display name of the account that triggered it
+    pub actor_name: String,
+    /// Avatar URL of the account that triggered it
+    pub actor_avatar: Option<String>,
+    /// The post involved (the mention/reply itself, or your liked/reposted post)
+    pub post: Option<Post>,
+    /// When it happened
+    pub created_at: DateTime<Utc>,
+    /// Whether it has been seen (per the server's read marker)
+    pub is_read: bool,
+}
+
+impl Notification {
+    /// Get relative time string (e.g., "5m", "2h", "3d")
+    pub fn relative_time(&self) -> String {
+        format_relative_time(self.created_at)
+    }
+}
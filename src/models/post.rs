@@ -1,10 +1,15 @@
 //! Post/Status model (unified across networks)
 
 use chrono::{DateTime, Utc};
+use regex_lite::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::ops::Range;
+use std::sync::OnceLock;
 use uuid::Uuid;
 
-use super::Network;
+use super::{Account, Network};
 
 /// A post/status (unified model for all networks)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +30,9 @@ pub struct Post {
     pub content: String,
     /// Original content (HTML for Mastodon, facets for Bluesky)
     pub content_raw: Option<String>,
+    /// Content warning shown in place of the body until expanded (Mastodon)
+    #[serde(default)]
+    pub spoiler_text: Option<String>,
     /// When the post was created
     pub created_at: DateTime<Utc>,
     /// URL to the post on the web
@@ -43,6 +51,9 @@ pub struct Post {
     pub liked: bool,
     /// Whether the current user has reposted this post
     pub reposted: bool,
+    /// Whether the current user has bookmarked this post (Mastodon only)
+    #[serde(default)]
+    pub bookmarked: bool,
     /// Reply-to post ID (if this is a reply)
     pub reply_to_id: Option<String>,
     /// Media attachments (URLs)
@@ -51,6 +62,29 @@ pub struct Post {
     pub cid: Option<String>,
     /// URI for Bluesky (at:// URI)
     pub uri: Option<String>,
+    /// Post quoted by this one (Bluesky record embeds)
+    #[serde(default)]
+    pub quote: Option<Box<Post>>,
+    /// Link preview card (Bluesky external embeds)
+    #[serde(default)]
+    pub link_card: Option<LinkCard>,
+    /// Account whose timeline, search or thread the post was loaded through,
+    /// which likes, reposts and replies act as
+    #[serde(default)]
+    pub owner_account_id: Option<Uuid>,
+}
+
+/// Preview of a linked page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkCard {
+    /// Linked URL
+    pub uri: String,
+    /// Page title
+    pub title: String,
+    /// Page description
+    pub description: String,
+    /// Thumbnail image URL
+    pub thumb: Option<String>,
 }
 
 /// Media attachment
@@ -64,6 +98,9 @@ pub struct MediaAttachment {
     pub media_type: MediaType,
     /// Alt text description
     pub alt_text: Option<String>,
+    /// Blurry preview to show while the image loads (Mastodon)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blurhash: Option<String>,
 }
 
 /// Media type
@@ -82,6 +119,40 @@ pub enum MediaType {
     Unknown,
 }
 
+impl MediaAttachment {
+    /// Whether the full-size file is a GIF, which may be animated
+    pub fn is_gif(&self) -> bool {
+        let path = self.url.split(['?', '#']).next().unwrap_or_default();
+        path.to_ascii_lowercase().ends_with(".gif")
+    }
+
+    /// URL of the image to show: the preview, or the full file for a GIF
+    /// that should play
+    pub fn display_url(&self, animate: bool) -> &str {
+        if animate && self.is_gif() {
+            &self.url
+        } else {
+            self.preview_url.as_deref().unwrap_or(&self.url)
+        }
+    }
+
+    /// Get the alt text on one line, shortened to `max_len` characters
+    pub fn alt_preview(&self, max_len: usize) -> Option<String> {
+        let alt = self
+            .alt_text
+            .as_deref()?
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if alt.chars().count() <= max_len {
+            Some(alt)
+        } else {
+            let truncated: String = alt.chars().take(max_len.saturating_sub(3)).collect();
+            Some(format!("{truncated}..."))
+        }
+    }
+}
+
 impl Post {
     /// Create a new post from network data
     pub fn new(network: Network, network_id: &str) -> Self {
@@ -94,6 +165,7 @@ impl Post {
             author_avatar: None,
             content: String::new(),
             content_raw: None,
+            spoiler_text: None,
             created_at: Utc::now(),
             url: None,
             is_repost: false,
@@ -103,10 +175,14 @@ impl Post {
             reply_count: 0,
             liked: false,
             reposted: false,
+            bookmarked: false,
             reply_to_id: None,
             media: Vec::new(),
             cid: None,
             uri: None,
+            quote: None,
+            link_card: None,
+            owner_account_id: None,
         }
     }
 
@@ -125,19 +201,368 @@ impl Post {
 
     /// Get relative time string (e.g., "5m", "2h", "3d")
     pub fn relative_time(&self) -> String {
-        let now = Utc::now();
-        let duration = now.signed_duration_since(self.created_at);
-
-        if duration.num_seconds() < 60 {
-            format!("{}s", duration.num_seconds())
-        } else if duration.num_minutes() < 60 {
-            format!("{}m", duration.num_minutes())
-        } else if duration.num_hours() < 24 {
-            format!("{}h", duration.num_hours())
-        } else if duration.num_days() < 7 {
-            format!("{}d", duration.num_days())
-        } else {
-            self.created_at.format("%b %d").to_string()
+        format_relative_time(self.created_at)
+    }
+
+    /// Hashtags in the content, in order of appearance, without the `#`.
+    ///
+    /// Each tag is listed once, whatever its capitalization.
+    pub fn hashtags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+        for line in self.content.lines() {
+            for (segment, _) in split_hashtags(line)
+                .into_iter()
+                .filter(|(_, is_tag)| *is_tag)
+            {
+                let tag = &segment[1..];
+                if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                    tags.push(tag.to_string());
+                }
+            }
+        }
+        tags
+    }
+
+    /// The account to like, repost or reply with: the one the post was
+    /// loaded through, else the first account on the post's network
+    pub fn acting_account<'a>(&self, accounts: &'a [Account]) -> Option<&'a Account> {
+        self.owner_account_id
+            .and_then(|id| accounts.iter().find(|a| a.id == id))
+            .or_else(|| accounts.iter().find(|a| a.network == self.network))
+    }
+
+    /// What identifies the post across accounts, boosts included.
+    ///
+    /// Boosts already carry the boosted post's own ID. On Bluesky that's the
+    /// `at://` URI, since the record key alone is only unique per author. Each
+    /// Mastodon instance numbers statuses itself, so there it's the original's
+    /// web URL, which every instance reports the same.
+    pub fn dedup_key(&self) -> (Network, String) {
+        let id = match self.network {
+            Network::Bluesky => self.uri.as_ref(),
+            Network::Mastodon => self.url.as_ref(),
+        };
+        (self.network, id.unwrap_or(&self.network_id).clone())
+    }
+
+    /// Drop posts shown more than once, such as one post seen by two
+    /// accounts or boosted by several people, keeping each where it first
+    /// appears. The author's own post is kept over a boost of it.
+    pub fn dedup(posts: &mut Vec<Self>) {
+        let mut first: HashMap<(Network, String), usize> = HashMap::new();
+        let mut kept: Vec<Self> = Vec::with_capacity(posts.len());
+        for post in std::mem::take(posts) {
+            match first.entry(post.dedup_key()) {
+                Entry::Occupied(entry) => {
+                    let earlier = &mut kept[*entry.get()];
+                    if earlier.is_repost && !post.is_repost {
+                        *earlier = post;
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(kept.len());
+                    kept.push(post);
+                }
+            }
+        }
+        *posts = kept;
+    }
+
+    /// Mark every post (and quoted post) as loaded through `account`
+    pub fn set_owner(posts: &mut [Self], account: &Account) {
+        for post in posts {
+            post.owner_account_id = Some(account.id);
+            if let Some(quote) = post.quote.as_deref_mut() {
+                quote.owner_account_id = Some(account.id);
+            }
+        }
+    }
+
+    /// URLs linked from the post, in order of appearance and each listed once.
+    ///
+    /// Mastodon anchors and Bluesky link facets (kept in `content_raw`) give
+    /// full URLs even where the text shows them shortened; mentions and
+    /// hashtag links are left out. The link card's URL comes last.
+    pub fn links(&self) -> Vec<String> {
+        let mut links: Vec<String> = Vec::new();
+        let mut add = |url: String| {
+            if !links.contains(&url) {
+                links.push(url);
+            }
+        };
+
+        if let Some(raw) = &self.content_raw {
+            match self.network {
+                Network::Mastodon => anchor_links(raw).into_iter().for_each(&mut add),
+                Network::Bluesky => facet_links(raw).into_iter().for_each(&mut add),
+            }
+        }
+        for range in find_links(&self.content) {
+            add(self.content[range].to_string());
+        }
+        if let Some(card) = &self.link_card {
+            add(card.uri.clone());
+        }
+        links
+    }
+}
+
+/// `href`s of the anchors in Mastodon HTML, skipping mentions and hashtags
+fn anchor_links(html: &str) -> Vec<String> {
+    static ANCHOR: OnceLock<Regex> = OnceLock::new();
+    static HREF: OnceLock<Regex> = OnceLock::new();
+    let anchor = ANCHOR.get_or_init(|| Regex::new(r"<a\s[^>]*>").expect("valid regex"));
+    let href = HREF.get_or_init(|| Regex::new(r#"href="([^"]*)""#).expect("valid regex"));
+
+    anchor
+        .find_iter(html)
+        .map(|m| m.as_str())
+        .filter(|tag| !tag.contains("mention") && !tag.contains(r#"rel="tag""#))
+        .filter_map(|tag| href.captures(tag)?.get(1))
+        .map(|m| html_escape::decode_html_entities(m.as_str()).into_owned())
+        .filter(|url| url.starts_with("https://") || url.starts_with("http://"))
+        .collect()
+}
+
+/// URIs of the link features in Bluesky facets (as JSON)
+fn facet_links(facets: &str) -> Vec<String> {
+    let Ok(serde_json::Value::Array(facets)) = serde_json::from_str(facets) else {
+        return Vec::new();
+    };
+
+    facets
+        .iter()
+        .filter_map(|facet| facet.get("features")?.as_array())
+        .flatten()
+        .filter(|feature| {
+            feature.get("$type").and_then(serde_json::Value::as_str)
+                == Some("app.bsky.richtext.facet#link")
+        })
+        .filter_map(|feature| feature.get("uri")?.as_str().map(String::from))
+        .collect()
+}
+
+/// Split a line of text into runs of plain text and `#hashtags`.
+///
+/// A hashtag starts at a `#` that doesn't follow a word character or `/`
+/// (so URL fragments don't count) and runs over letters, digits and `_`.
+/// All-digit tags like `#1` are treated as text.
+pub fn split_hashtags(line: &str) -> Vec<(&str, bool)> {
+    let mut segments = Vec::new();
+    let mut text_start = 0;
+    let mut prev: Option<char> = None;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let at_boundary =
+            prev.is_none_or(|p| !(p.is_alphanumeric() || matches!(p, '_' | '/' | '&')));
+        prev = Some(c);
+        if c != '#' || !at_boundary {
+            continue;
+        }
+
+        let mut end = start + 1;
+        while let Some(&(i, next)) = chars.peek() {
+            if !(next.is_alphanumeric() || next == '_') {
+                break;
+            }
+            end = i + next.len_utf8();
+            prev = Some(next);
+            chars.next();
+        }
+
+        let tag = &line[start..end];
+        if tag[1..].chars().all(|c| c.is_ascii_digit()) {
+            continue;
         }
+        if text_start < start {
+            segments.push((&line[text_start..start], false));
+        }
+        segments.push((tag, true));
+        text_start = end;
+    }
+
+    if text_start < line.len() {
+        segments.push((&line[text_start..], false));
+    }
+    segments
+}
+
+/// Byte ranges of the `http(s)://` links in `text`.
+///
+/// Sentence punctuation after a link isn't part of it, nor is a closing
+/// paren when the link has no opening one.
+pub fn find_links(text: &str) -> Vec<Range<usize>> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| Regex::new(r"(?:^|[\s(])(https?://\S+)").expect("valid regex"));
+
+    re.captures_iter(text)
+        .filter_map(|c| c.get(1))
+        .map(|m| {
+            let mut link = m
+                .as_str()
+                .trim_end_matches(['.', ',', ';', ':', '!', '?', '"', '\'']);
+            if !link.contains('(') {
+                link = link.trim_end_matches(')');
+            }
+            m.start()..m.start() + link.len()
+        })
+        .collect()
+}
+
+/// Format a timestamp relative to now (e.g., "5m", "2h", "3d", "Jan 05")
+pub fn format_relative_time(created_at: DateTime<Utc>) -> String {
+    let duration = Utc::now().signed_duration_since(created_at);
+
+    if duration.num_seconds() < 60 {
+        format!("{}s", duration.num_seconds())
+    } else if duration.num_minutes() < 60 {
+        format!("{}m", duration.num_minutes())
+    } else if duration.num_hours() < 24 {
+        format!("{}h", duration.num_hours())
+    } else if duration.num_days() < 7 {
+        format!("{}d", duration.num_days())
+    } else {
+        created_at.format("%b %d").to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_hashtags() {
+        assert_eq!(
+            split_hashtags("Hello #Rust and #tui_dev!"),
+            vec![
+                ("Hello ", false),
+                ("#Rust", true),
+                (" and ", false),
+                ("#tui_dev", true),
+                ("!", false),
+            ]
+        );
+        // URL fragments, numbers and mid-word hashes are text
+        assert_eq!(
+            split_hashtags("see example.com/#intro, issue #12, C#"),
+            vec![("see example.com/#intro, issue #12, C#", false)]
+        );
+        assert_eq!(split_hashtags("#café"), vec![("#café", true)]);
+    }
+
+    #[test]
+    fn test_alt_preview() {
+        let mut media = MediaAttachment {
+            url: "https://example.com/a.png".to_string(),
+            preview_url: None,
+            media_type: MediaType::Image,
+            alt_text: None,
+            blurhash: None,
+        };
+        assert_eq!(media.alt_preview(20), None);
+
+        media.alt_text = Some("A cat\nasleep".to_string());
+        assert_eq!(media.alt_preview(20).as_deref(), Some("A cat asleep"));
+
+        media.alt_text = Some("A very sleepy café cat on a sofa".to_string());
+        assert_eq!(
+            media.alt_preview(20).as_deref(),
+            Some("A very sleepy caf...")
+        );
+    }
+
+    #[test]
+    fn test_hashtags_dedupes() {
+        let mut post = Post::new(Network::Mastodon, "1");
+        post.content = "#Rust is fun\nmore #rust and #ratatui".to_string();
+        assert_eq!(post.hashtags(), vec!["Rust", "ratatui"]);
+    }
+
+    #[test]
+    fn test_links_from_mastodon_html() {
+        let mut post = Post::new(Network::Mastodon, "1");
+        post.content_raw = Some(
+            r#"<p>Hi <span class="h-card"><a href="https://m.example/@bob" class="u-url mention">@<span>bob</span></a></span>, see <a href="https://example.com/a?x=1&amp;y=2" rel="nofollow noopener" target="_blank"><span class="invisible">https://</span>example.com/a?x…</a> <a href="https://m.example/tags/rust" class="mention hashtag" rel="tag">#rust</a></p>"#
+                .to_string(),
+        );
+        post.content = "Hi @bob, see example.com/a?x… #rust or https://other.example.".to_string();
+
+        assert_eq!(
+            post.links(),
+            vec!["https://example.com/a?x=1&y=2", "https://other.example"]
+        );
+    }
+
+    #[test]
+    fn test_links_from_bluesky_facets() {
+        let mut post = Post::new(Network::Bluesky, "1");
+        post.content = "read example.com/long-arti... @alice.test".to_string();
+        post.content_raw = Some(
+            r#"[{"index":{"byteStart":5,"byteEnd":26},"features":[{"$type":"app.bsky.richtext.facet#link","uri":"https://example.com/long-article"}]},{"index":{"byteStart":27,"byteEnd":38},"features":[{"$type":"app.bsky.richtext.facet#mention","did":"did:plc:alice"}]}]"#
+                .to_string(),
+        );
+
+        assert_eq!(post.links(), vec!["https://example.com/long-article"]);
+    }
+
+    #[test]
+    fn test_acting_account_is_the_owner() {
+        let first = Account::new_bluesky("a.bsky.social", "A");
+        let second = Account::new_bluesky("b.bsky.social", "B");
+        let accounts = vec![first.clone(), second.clone()];
+
+        let mut post = Post::new(Network::Bluesky, "at://did:plc:x/app.bsky.feed.post/1");
+        assert_eq!(post.acting_account(&accounts).map(|a| a.id), Some(first.id));
+
+        Post::set_owner(std::slice::from_mut(&mut post), &second);
+        assert_eq!(
+            post.acting_account(&accounts).map(|a| a.id),
+            Some(second.id)
+        );
+
+        // An owner that has since been removed falls back to the network
+        assert_eq!(
+            post.acting_account(&accounts[..1]).map(|a| a.id),
+            Some(first.id)
+        );
+    }
+
+    #[test]
+    fn test_dedup_collapses_boosts_and_cross_posts() {
+        let mastodon = |id: &str, url: &str, boosted_by: Option<&str>| {
+            let mut post = Post::new(Network::Mastodon, id);
+            post.url = Some(url.to_string());
+            post.is_repost = boosted_by.is_some();
+            post.repost_author = boosted_by.map(str::to_string);
+            post
+        };
+        let bluesky = |rkey: &str, uri: &str| {
+            let mut post = Post::new(Network::Bluesky, rkey);
+            post.uri = Some(uri.to_string());
+            post
+        };
+
+        let original = "https://mastodon.social/@alice/1";
+        let mut posts = vec![
+            // Boosted by two people, then seen by a second account on another
+            // instance, which numbers it differently
+            mastodon("1", original, Some("Bob")),
+            mastodon("1", original, Some("Carol")),
+            mastodon("900", original, None),
+            // Same record key, different authors
+            bluesky("3k", "at://did:plc:a/app.bsky.feed.post/3k"),
+            bluesky("3k", "at://did:plc:b/app.bsky.feed.post/3k"),
+            bluesky("3k", "at://did:plc:a/app.bsky.feed.post/3k"),
+            // Distinct statuses on two instances that happen to share an ID
+            mastodon("2", "https://mastodon.social/@alice/2", None),
+            mastodon("2", "https://fosstodon.org/@dave/2", None),
+        ];
+        Post::dedup(&mut posts);
+
+        let ids: Vec<_> = posts.iter().map(|p| p.network_id.as_str()).collect();
+        assert_eq!(ids, ["900", "3k", "3k", "2", "2"]);
+        // The author's own post is kept over the boosts of it
+        assert!(!posts[0].is_repost);
     }
 }
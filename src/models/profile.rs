@@ -0,0 +1,74 @@
+//! Profile model (unified across networks)
+
+use super::Network;
+
+/// An account's public profile, as shown in the profile view
+#[derive(Debug, Clone)]
+pub struct Profile {
+    /// Which network the account is on
+    pub network: Network,
+    /// Network-specific ID (Mastodon account ID, Bluesky DID)
+    pub network_id: String,
+    /// Handle (`user` or `user@instance` on Mastodon, `user.bsky.social` on Bluesky)
+    pub handle: String,
+    /// Display name (may be empty)
+    pub display_name: String,
+    /// Bio (plain text, HTML stripped)
+    pub bio: String,
+    /// Avatar URL
+    pub avatar_url: Option<String>,
+    /// URL to the profile on the web
+    pub url: Option<String>,
+    /// Number of followers
+    pub followers_count: u64,
+    /// Number of accounts followed
+    pub following_count: u64,
+    /// Number of posts
+    pub posts_count: u64,
+    /// Whether the account approves followers by hand (Mastodon)
+    pub locked: bool,
+    /// Whether the signed-in account follows this one
+    pub follow_state: FollowState,
+    /// URI of the signed-in account's follow record, needed to unfollow (Bluesky)
+    pub follow_uri: Option<String>,
+    /// Whether the signed-in account mutes this one
+    pub muted: bool,
+    /// Whether the signed-in account blocks this one
+    pub blocked: bool,
+    /// URI of the signed-in account's block record, needed to unblock (Bluesky)
+    pub block_uri: Option<String>,
+}
+
+/// Whether the signed-in account follows a profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FollowState {
+    /// Not following
+    #[default]
+    NotFollowing,
+    /// Follow request waiting for a locked account to approve it
+    Requested,
+    /// Following
+    Following,
+}
+
+impl FollowState {
+    /// Label for the follow button
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::NotFollowing => "Follow",
+            Self::Requested => "Requested",
+            Self::Following => "Following",
+        }
+    }
+}
+
+impl Profile {
+    /// Display name, or the handle when there is none
+    pub fn name(&self) -> &str {
+        if self.display_name.trim().is_empty() {
+            &self.handle
+        } else {
+            &self.display_name
+        }
+    }
+}
@@ -0,0 +1,385 @@
+//! Repeat rules for scheduled posts
+
+use std::fmt;
+
+use anyhow::{Result, anyhow, bail};
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How far ahead to look for the next match of a cron expression (covers
+/// February 29th)
+const CRON_SEARCH_DAYS: i64 = 366 * 5;
+
+/// How often a scheduled post repeats
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Recurrence {
+    /// Every day at the same local time
+    Daily,
+    /// Every week on the same weekday, at the same local time
+    Weekly,
+    /// A cron expression
+    Cron(CronSchedule),
+}
+
+impl Recurrence {
+    /// Parse `daily`, `weekly` or a five-field cron expression
+    /// (`minute hour day-of-month month day-of-week`)
+    pub fn parse(input: &str) -> Result<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "daily" => Ok(Self::Daily),
+            "weekly" => Ok(Self::Weekly),
+            _ => CronSchedule::parse(input).map(Self::Cron),
+        }
+    }
+
+    /// The first occurrence after `after`, given the previous one at `last`
+    /// and the first one at `anchor`.
+    ///
+    /// Daily and weekly posts keep the anchor's wall-clock time in `tz`
+    /// across daylight saving changes, so an occurrence moved an hour by one
+    /// doesn't move the ones after it. Occurrences missed while nothing was
+    /// running are skipped rather than posted late.
+    pub fn next_after<Tz: TimeZone>(
+        &self,
+        last: DateTime<Utc>,
+        after: DateTime<Utc>,
+        anchor: DateTime<Utc>,
+        tz: &Tz,
+    ) -> Option<DateTime<Utc>> {
+        let step = match self {
+            Self::Daily => Duration::days(1),
+            Self::Weekly => Duration::weeks(1),
+            Self::Cron(cron) => return cron.next_after(after, tz),
+        };
+
+        let time = anchor.with_timezone(tz).time();
+        let mut date = last.with_timezone(tz).date_naive();
+        loop {
+            date += step;
+            if let Some(next) = resolve_local(tz, date.and_time(time))
+                && next > after
+            {
+                return Some(next);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Daily => f.write_str("daily"),
+            Self::Weekly => f.write_str("weekly"),
+            Self::Cron(cron) => f.write_str(&cron.expr),
+        }
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        Self::parse(&value)
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(value: Recurrence) -> Self {
+        value.to_string()
+    }
+}
+
+/// A parsed cron expression. Each field is a bit set of the values it
+/// matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expr: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    /// Sunday is 0 (7 is accepted too)
+    weekdays: u64,
+    /// Whether day-of-month or day-of-week is `*`. As in cron, when both
+    /// are restricted a day matching either one counts.
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    /// Parse `minute hour day-of-month month day-of-week`. Fields take `*`,
+    /// numbers, ranges (`1-5`), lists (`1,15`) and steps (`*/15`).
+    pub fn parse(input: &str) -> Result<Self> {
+        let fields: Vec<&str> = input.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            bail!(
+                "Unknown repeat: '{}'\n\
+                 Use 'daily', 'weekly' or a cron expression like '0 9 * * 1-5'",
+                input.trim()
+            );
+        };
+
+        let mut weekdays = parse_field(weekday, 0, 7)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            expr: fields.join(" "),
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days: parse_field(day, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    /// The first matching minute after `after`, in `tz`'s local time.
+    /// Times skipped by a daylight saving change never match.
+    pub fn next_after<Tz: TimeZone>(&self, after: DateTime<Utc>, tz: &Tz) -> Option<DateTime<Utc>> {
+        let first_day = after.with_timezone(tz).date_naive();
+
+        for offset in 0..CRON_SEARCH_DAYS {
+            let date = first_day + Duration::days(offset);
+            if !self.matches_day(
+                date.day(),
+                date.month(),
+                date.weekday().num_days_from_sunday(),
+            ) {
+                continue;
+            }
+            for hour in bits(self.hours) {
+                for minute in bits(self.minutes) {
+                    let Some(local) = date.and_hms_opt(hour, minute, 0) else {
+                        continue;
+                    };
+                    if let Some(next) = tz.from_local_datetime(&local).earliest()
+                        && next.with_timezone(&Utc) > after
+                    {
+                        return Some(next.with_timezone(&Utc));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    const fn matches_day(&self, day: u32, month: u32, weekday: u32) -> bool {
+        if self.months & (1 << month) == 0 {
+            return false;
+        }
+        let day_matches = self.days & (1 << day) != 0;
+        let weekday_matches = self.weekdays & (1 << weekday) != 0;
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day_matches || weekday_matches,
+            _ => day_matches && weekday_matches,
+        }
+    }
+}
+
+/// Parse one cron field into a bit set of the values between `min` and `max`
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64> {
+    let value = |s: &str| {
+        s.parse::<u32>()
+            .ok()
+            .filter(|v| (min..=max).contains(v))
+            .ok_or_else(|| anyhow!("'{s}' is out of range in cron field '{field}' ({min}-{max})"))
+    };
+
+    let mut set = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<usize>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| anyhow!("Invalid step in cron field '{field}'"))?,
+            ),
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (value(start)?, value(end)?)
+        } else {
+            let start = value(range)?;
+            // `5/10` means every 10 starting at 5
+            (start, if step > 1 { max } else { start })
+        };
+        if start > end {
+            bail!("Backwards range in cron field '{field}'");
+        }
+
+        for v in (start..=end).step_by(step) {
+            set |= 1 << v;
+        }
+    }
+    Ok(set)
+}
+
+/// The values in a bit set, smallest first
+fn bits(set: u64) -> impl Iterator<Item = u32> {
+    (0..64).filter(move |i| set & (1 << i) != 0)
+}
+
+/// A local time in `tz`, moved an hour later if a daylight saving change
+/// skips it
+fn resolve_local<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(local + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, MappedLocalTime, NaiveDate};
+
+    /// Central European time with 2026's daylight saving dates: clocks go
+    /// forward at 02:00 on March 29th and back at 03:00 on October 25th
+    #[derive(Debug, Clone, Copy)]
+    struct Berlin;
+
+    impl Berlin {
+        fn winter() -> FixedOffset {
+            FixedOffset::east_opt(3600).unwrap()
+        }
+
+        fn summer() -> FixedOffset {
+            FixedOffset::east_opt(7200).unwrap()
+        }
+    }
+
+    impl TimeZone for Berlin {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            Self
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(12, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(
+            &self,
+            local: &NaiveDateTime,
+        ) -> MappedLocalTime<FixedOffset> {
+            let valid: Vec<_> = [Self::summer(), Self::winter()]
+                .into_iter()
+                .filter(|offset| {
+                    let utc = *local - Duration::seconds(i64::from(offset.local_minus_utc()));
+                    self.offset_from_utc_datetime(&utc) == *offset
+                })
+                .collect();
+            match valid[..] {
+                [offset] => MappedLocalTime::Single(offset),
+                [earlier, later] => MappedLocalTime::Ambiguous(earlier, later),
+                _ => MappedLocalTime::None,
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            let start = NaiveDate::from_ymd_opt(2026, 3, 29)
+                .unwrap()
+                .and_hms_opt(1, 0, 0);
+            let end = NaiveDate::from_ymd_opt(2026, 10, 25)
+                .unwrap()
+                .and_hms_opt(1, 0, 0);
+            if (start.unwrap()..end.unwrap()).contains(utc) {
+                Self::summer()
+            } else {
+                Self::winter()
+            }
+        }
+    }
+
+    fn berlin(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Berlin
+            .with_ymd_and_hms(y, m, d, h, min, 0)
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_daily_keeps_local_time_across_dst() {
+        let daily = Recurrence::Daily;
+
+        // Spring forward: 09:00 stays 09:00 although the day is 23 hours long
+        let last = berlin(2026, 3, 28, 9, 0);
+        let next = daily.next_after(last, last, last, &Berlin).unwrap();
+        assert_eq!(next, berlin(2026, 3, 29, 9, 0));
+        assert_eq!(next - last, Duration::hours(23));
+
+        // Fall back: a 25 hour day
+        let last = berlin(2026, 10, 24, 9, 0);
+        let next = daily.next_after(last, last, last, &Berlin).unwrap();
+        assert_eq!(next - last, Duration::hours(25));
+    }
+
+    #[test]
+    fn test_daily_time_skipped_by_dst_moves_an_hour() {
+        let anchor = berlin(2026, 3, 28, 2, 30);
+        let next = Recurrence::Daily
+            .next_after(anchor, anchor, anchor, &Berlin)
+            .unwrap();
+        assert_eq!(next, berlin(2026, 3, 29, 3, 30));
+
+        // Stepping on from the moved one goes back to 02:30 the day after
+        let after = Recurrence::Daily
+            .next_after(next, next, anchor, &Berlin)
+            .unwrap();
+        assert_eq!(after, berlin(2026, 3, 30, 2, 30));
+    }
+
+    #[test]
+    fn test_weekly_skips_missed_occurrences() {
+        let last = berlin(2026, 3, 2, 9, 0);
+        let now = berlin(2026, 3, 20, 12, 0);
+        let next = Recurrence::Weekly
+            .next_after(last, now, last, &Berlin)
+            .unwrap();
+        assert_eq!(next, berlin(2026, 3, 23, 9, 0));
+    }
+
+    #[test]
+    fn test_cron_weekdays_across_dst() {
+        let cron = Recurrence::parse("0 9 * * 1-5").unwrap();
+
+        // Friday 27th, then Monday 30th, the first day of summer time
+        let friday = berlin(2026, 3, 27, 9, 0);
+        let next = cron.next_after(friday, friday, friday, &Berlin).unwrap();
+        assert_eq!(next, berlin(2026, 3, 30, 9, 0));
+        assert_eq!(next - friday, Duration::hours(71));
+    }
+
+    #[test]
+    fn test_cron_parsing() {
+        let cron = CronSchedule::parse("*/15 9-17 1,15 * 7").unwrap();
+        assert_eq!(bits(cron.minutes).collect::<Vec<_>>(), vec![0, 15, 30, 45]);
+        assert_eq!(bits(cron.hours).count(), 9);
+        assert_eq!(bits(cron.weekdays).collect::<Vec<_>>(), vec![0]);
+
+        assert!(Recurrence::parse("60 * * * *").is_err());
+        assert!(Recurrence::parse("hourly").is_err());
+        assert_eq!(Recurrence::parse("Daily").unwrap(), Recurrence::Daily);
+        assert_eq!(
+            Recurrence::parse("0  9 * * 1-5").unwrap().to_string(),
+            "0 9 * * 1-5"
+        );
+    }
+}
@@ -1,10 +1,12 @@
 //! Scheduled post model
 
-use chrono::{DateTime, Utc};
+use anyhow::{Result, bail};
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::Network;
+use super::{Network, Recurrence};
+use crate::schedule::Timezone;
 
 /// Status of a scheduled post
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -60,6 +62,9 @@ impl ScheduledPostStatus {
     }
 }
 
+/// Wait before the first retry; each later one waits twice as long
+const RETRY_BACKOFF_MINUTES: i64 = 2;
+
 /// A scheduled post
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScheduledPost {
@@ -69,17 +74,38 @@ pub struct ScheduledPost {
     pub content: String,
     /// Target networks
     pub networks: Vec<Network>,
-    /// When to post
+    /// When to post (the next occurrence, for repeating posts)
     pub scheduled_for: DateTime<Utc>,
+    /// How often the post repeats, if it does
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    /// The first occurrence of a repeating post, whose wall-clock time the
+    /// later ones keep. `None` until the post first repeats, while it's
+    /// still `scheduled_for`.
+    #[serde(default)]
+    pub recurrence_anchor: Option<DateTime<Utc>>,
     /// Current status
     pub status: ScheduledPostStatus,
     /// Error message if failed
     pub error: Option<String>,
     /// When this was created
     pub created_at: DateTime<Utc>,
+    /// Failed attempts so far at the current occurrence
+    #[serde(default)]
+    pub retry_count: u32,
+    /// When to try again after a failure
+    #[serde(default)]
+    pub next_retry_at: Option<DateTime<Utc>>,
+    /// Networks the current occurrence already went out on, so retries
+    /// don't post twice
+    #[serde(default)]
+    pub posted_networks: Vec<Network>,
 }
 
 impl ScheduledPost {
+    /// Attempts at posting after the first failure, before giving up
+    pub const MAX_RETRIES: u32 = 5;
+
     /// Create a new scheduled post
     pub fn new(
         content: impl Into<String>,
@@ -91,24 +117,139 @@ impl ScheduledPost {
             content: content.into(),
             networks,
             scheduled_for,
+            recurrence: None,
+            recurrence_anchor: None,
             status: ScheduledPostStatus::Pending,
             error: None,
             created_at: Utc::now(),
+            retry_count: 0,
+            next_retry_at: None,
+            posted_networks: Vec::new(),
         }
     }
 
-    /// Check if this post is due (scheduled time has passed)
+    /// Check if this post is due (scheduled time has passed, and any retry
+    /// wait is over)
     pub fn is_due(&self) -> bool {
-        self.status == ScheduledPostStatus::Pending && Utc::now() >= self.scheduled_for
+        self.status == ScheduledPostStatus::Pending && Utc::now() >= self.due_at()
     }
 
-    /// Get networks as comma-separated string
-    pub fn networks_str(&self) -> String {
+    /// When this post should next be attempted: its scheduled time, or later
+    /// while waiting to retry
+    pub fn due_at(&self) -> DateTime<Utc> {
+        self.next_retry_at
+            .map_or(self.scheduled_for, |at| at.max(self.scheduled_for))
+    }
+
+    /// When a repeating post goes out next after `after`, in `tz`'s local
+    /// time, or `None` if it doesn't repeat
+    pub fn next_occurrence<Tz: TimeZone>(
+        &self,
+        after: DateTime<Utc>,
+        tz: &Tz,
+    ) -> Option<DateTime<Utc>> {
+        let anchor = self.recurrence_anchor.unwrap_or(self.scheduled_for);
+        self.recurrence
+            .as_ref()?
+            .next_after(self.scheduled_for, after, anchor, tz)
+    }
+
+    /// Networks still to post to
+    pub fn remaining_networks(&self) -> Vec<Network> {
         self.networks
             .iter()
-            .map(|n| format!("{:?}", n).to_lowercase())
-            .collect::<Vec<_>>()
-            .join(",")
+            .filter(|n| !self.posted_networks.contains(n))
+            .copied()
+            .collect()
+    }
+
+    /// Record a failed attempt: wait and try again with exponential
+    /// backoff, or fail for good after [`Self::MAX_RETRIES`] retries
+    pub fn record_failure(&mut self, error: String, now: DateTime<Utc>) {
+        self.error = Some(error);
+        if self.retry_count >= Self::MAX_RETRIES {
+            self.status = ScheduledPostStatus::Failed;
+            self.next_retry_at = None;
+        } else {
+            self.status = ScheduledPostStatus::Pending;
+            self.next_retry_at =
+                Some(now + Duration::minutes(RETRY_BACKOFF_MINUTES << self.retry_count));
+            self.retry_count += 1;
+        }
+    }
+
+    /// Try again on the next run, with a fresh set of retries. Networks
+    /// already posted to are still skipped.
+    pub const fn retry_now(&mut self) {
+        self.status = ScheduledPostStatus::Pending;
+        self.retry_count = 0;
+        self.next_retry_at = None;
+    }
+
+    /// Change what a pending post says, where it goes and when. A new time
+    /// must be in the future, and starts the retries over.
+    pub fn edit(
+        &mut self,
+        content: Option<String>,
+        networks: Option<Vec<Network>>,
+        scheduled_for: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        if self.status != ScheduledPostStatus::Pending {
+            bail!(
+                "Only pending posts can be edited, this one is {}",
+                self.status.as_str()
+            );
+        }
+        if let Some(content) = &content
+            && content.trim().is_empty()
+        {
+            bail!("A scheduled post needs some content");
+        }
+        if let Some(networks) = &networks
+            && networks.is_empty()
+        {
+            bail!("A scheduled post needs at least one network");
+        }
+        let scheduled_for = scheduled_for.filter(|at| *at != self.scheduled_for);
+        if let Some(at) = scheduled_for
+            && at <= Utc::now()
+        {
+            bail!("The new time has already passed");
+        }
+
+        if let Some(content) = content {
+            self.content = content;
+        }
+        if let Some(networks) = networks {
+            self.posted_networks.retain(|n| networks.contains(n));
+            self.networks = networks;
+        }
+        if let Some(at) = scheduled_for {
+            self.scheduled_for = at;
+            self.recurrence_anchor = None;
+            self.retry_count = 0;
+            self.next_retry_at = None;
+        }
+        Ok(())
+    }
+
+    /// First non-empty line of the content
+    pub fn preview(&self) -> &str {
+        self.content
+            .lines()
+            .find(|l| !l.trim().is_empty())
+            .unwrap_or("")
+            .trim()
+    }
+
+    /// Get networks as comma-separated string
+    pub fn networks_str(&self) -> String {
+        networks_to_str(&self.networks)
+    }
+
+    /// Get the networks already posted to as a comma-separated string
+    pub fn posted_networks_str(&self) -> String {
+        networks_to_str(&self.posted_networks)
     }
 
     /// Parse networks from comma-separated string
@@ -151,8 +292,78 @@ impl ScheduledPost {
         }
     }
 
-    /// Format scheduled time for display
-    pub fn scheduled_time_display(&self) -> String {
-        self.scheduled_for.format("%Y-%m-%d %H:%M UTC").to_string()
+    /// Format scheduled time for display in `tz`
+    pub fn scheduled_time_display(&self, tz: &Timezone) -> String {
+        tz.format(self.scheduled_for)
+    }
+}
+
+fn networks_to_str(networks: &[Network]) -> String {
+    networks
+        .iter()
+        .map(|n| format!("{:?}", n).to_lowercase())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failures_back_off_then_give_up() {
+        let now = Utc::now();
+        let mut post = ScheduledPost::new("Hi", vec![Network::Mastodon], now);
+
+        let mut waits = Vec::new();
+        for _ in 0..ScheduledPost::MAX_RETRIES {
+            post.record_failure("offline".to_string(), now);
+            assert_eq!(post.status, ScheduledPostStatus::Pending);
+            waits.push((post.next_retry_at.unwrap() - now).num_minutes());
+        }
+        assert_eq!(waits, vec![2, 4, 8, 16, 32]);
+        assert!(!post.is_due());
+        assert_eq!(post.due_at(), post.next_retry_at.unwrap());
+
+        post.record_failure("offline".to_string(), now);
+        assert_eq!(post.status, ScheduledPostStatus::Failed);
+        assert_eq!(post.error.as_deref(), Some("offline"));
+
+        post.retry_now();
+        assert!(post.is_due());
+    }
+
+    #[test]
+    fn test_retries_skip_posted_networks() {
+        let mut post =
+            ScheduledPost::new("Hi", vec![Network::Mastodon, Network::Bluesky], Utc::now());
+        post.posted_networks.push(Network::Mastodon);
+        assert_eq!(post.remaining_networks(), vec![Network::Bluesky]);
+    }
+
+    #[test]
+    fn test_edit_pending_only() {
+        let later = Utc::now() + Duration::hours(1);
+        let mut post = ScheduledPost::new("Hi", vec![Network::Mastodon], later);
+        post.record_failure("offline".to_string(), Utc::now());
+
+        assert!(
+            post.edit(None, None, Some(Utc::now() - Duration::hours(1)))
+                .is_err()
+        );
+        assert!(post.edit(None, Some(Vec::new()), None).is_err());
+
+        let new_time = later + Duration::hours(1);
+        post.edit(Some("Hello".to_string()), None, Some(new_time))
+            .unwrap();
+        assert_eq!(post.content, "Hello");
+        assert_eq!(post.scheduled_for, new_time);
+        assert_eq!(post.retry_count, 0);
+
+        post.status = ScheduledPostStatus::Posted;
+        let err = post
+            .edit(Some("Again".to_string()), None, None)
+            .unwrap_err();
+        assert!(err.to_string().contains("posted"));
     }
 }
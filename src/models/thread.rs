@@ -0,0 +1,155 @@
+//! Splitting long posts into numbered threads
+//!
+//! A post too long for a network is cut into parts that each fit, numbered
+//! `1/3`, `2/3` and so on, to be posted as a chain of replies. Parts end at
+//! sentences where that doesn't leave them too short, and otherwise between
+//! words, so links stay whole. Only a word longer than a whole post is cut,
+//! and then between characters rather than inside one.
+
+/// Split `text` into parts of at most `limit` characters, each ending with
+/// its number in the thread. Text that already fits comes back whole.
+pub fn split_thread(text: &str, limit: usize) -> Vec<String> {
+    if text.chars().count() <= limit {
+        return vec![text.to_string()];
+    }
+    let text = text.trim();
+    if text.chars().count() <= limit {
+        return vec![text.to_string()];
+    }
+
+    // Room for the number depends on how many parts there are
+    let mut digits = 1;
+    loop {
+        // " 12/34"
+        let reserved = 2 * digits + 2;
+        let parts = split_parts(text, limit.saturating_sub(reserved).max(1));
+        let total = parts.len();
+        if total.to_string().len() <= digits {
+            return parts
+                .into_iter()
+                .enumerate()
+                .map(|(i, part)| format!("{part} {}/{total}", i + 1))
+                .collect();
+        }
+        digits += 1;
+    }
+}
+
+/// Cut `text` into parts of at most `budget` characters
+fn split_parts(text: &str, budget: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while rest.chars().count() > budget {
+        let end = break_point(rest, budget);
+        parts.push(rest[..end].trim_end().to_string());
+        rest = rest[end..].trim_start();
+    }
+    if !rest.is_empty() {
+        parts.push(rest.to_string());
+    }
+    parts
+}
+
+/// Byte index to end the next part of `text` at, for a part of at most
+/// `budget` characters
+fn break_point(text: &str, budget: usize) -> usize {
+    let mut last_space = None;
+    let mut last_sentence = None;
+    let mut previous = None;
+    // `chars` is how long the part would be, ending before `c`
+    for (chars, (i, c)) in text.char_indices().enumerate().take(budget + 1) {
+        if c.is_whitespace() && chars > 0 {
+            last_space = Some(i);
+            // Only worth it if the part isn't left much shorter
+            if (c == '\n' || matches!(previous, Some('.' | '!' | '?'))) && chars * 2 >= budget {
+                last_sentence = Some(i);
+            }
+        }
+        previous = Some(c);
+    }
+
+    last_sentence.or(last_space).unwrap_or_else(|| {
+        // A single word longer than a part: cut it after the last character
+        // that fits
+        text.char_indices()
+            .nth(budget)
+            .map_or(text.len(), |(i, _)| i)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fits(parts: &[String], limit: usize) -> bool {
+        parts.iter().all(|p| p.chars().count() <= limit)
+    }
+
+    #[test]
+    fn test_short_text_is_one_post() {
+        assert_eq!(split_thread("Hello there", 300), ["Hello there"]);
+        // Only surrounding whitespace made it too long
+        assert_eq!(split_thread("  Hello  ", 5), ["Hello"]);
+    }
+
+    #[test]
+    fn test_splits_at_sentences_and_words() {
+        let text = "The first sentence is here. The second one runs a bit longer than that. \
+                    And a third closes it";
+        let parts = split_thread(text, 50);
+        assert_eq!(
+            parts,
+            [
+                "The first sentence is here. 1/3",
+                "The second one runs a bit longer than that. 2/3",
+                "And a third closes it 3/3",
+            ]
+        );
+
+        // Without a sentence end far enough in, the last space will do
+        let parts = split_thread(&"word ".repeat(30), 40);
+        assert!(fits(&parts, 40));
+        assert!(
+            parts
+                .iter()
+                .all(|p| !p.contains("wor ") && !p.starts_with("ord"))
+        );
+        assert_eq!(parts.last().unwrap(), "word word 5/5");
+    }
+
+    #[test]
+    fn test_keeps_links_whole() {
+        let link = "https://example.com/a/rather/long/path?with=query";
+        let text = format!("Have a look at this page, it's worth it: {link} and tell me");
+        let parts = split_thread(&text, 60);
+        assert!(fits(&parts, 60));
+        assert_eq!(parts.iter().filter(|p| p.contains(link)).count(), 1);
+    }
+
+    #[test]
+    fn test_multibyte_text() {
+        // No spaces at all, so it's cut between characters
+        let text = "日本語のテキスト🐦".repeat(10);
+        let parts = split_thread(&text, 30);
+        assert!(fits(&parts, 30));
+        let joined: String = parts
+            .iter()
+            .map(|p| p.rsplit_once(' ').unwrap().0)
+            .collect();
+        assert_eq!(joined, text);
+
+        let text = "Café déjà vu à Paris. ".repeat(20);
+        let parts = split_thread(&text, 50);
+        assert!(fits(&parts, 50));
+        assert!(parts.iter().all(|p| p.starts_with("Café")));
+    }
+
+    #[test]
+    fn test_numbers_fit_with_many_parts() {
+        let text = "Lorem ipsum dolor sit amet. ".repeat(40);
+        let parts = split_thread(&text, 30);
+        assert!(parts.len() >= 10);
+        assert!(fits(&parts, 30));
+        assert!(parts[9].ends_with(&format!(" 10/{}", parts.len())));
+    }
+}
@@ -0,0 +1,66 @@
+//! Post visibility levels
+
+use serde::{Deserialize, Serialize};
+
+/// Who can see a post (Mastodon only; Bluesky posts are always public)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    /// Visible to everyone and shown in public timelines
+    #[default]
+    Public,
+    /// Visible to everyone but hidden from public timelines
+    Unlisted,
+    /// Followers only
+    Private,
+    /// Mentioned users only
+    Direct,
+}
+
+impl Visibility {
+    /// Get the value used by the Mastodon API
+    pub const fn as_api_str(&self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Unlisted => "unlisted",
+            Self::Private => "private",
+            Self::Direct => "direct",
+        }
+    }
+
+    /// Get the display name
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Public => "Public",
+            Self::Unlisted => "Unlisted",
+            Self::Private => "Followers only",
+            Self::Direct => "Direct",
+        }
+    }
+
+    /// Get the emoji icon
+    pub const fn emoji(&self) -> &'static str {
+        match self {
+            Self::Public => "🌐",
+            Self::Unlisted => "🔓",
+            Self::Private => "🔒",
+            Self::Direct => "✉️",
+        }
+    }
+
+    /// Cycle to the next visibility level
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Public => Self::Unlisted,
+            Self::Unlisted => Self::Private,
+            Self::Private => Self::Direct,
+            Self::Direct => Self::Public,
+        }
+    }
+}
+
+impl std::fmt::Display for Visibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -1,22 +1,185 @@
 //! Common paths for Perch data storage
 //!
-//! All Perch data is stored under ~/.config/perch/ on all platforms:
+//! By default all Perch data is stored under ~/.config/perch/ on all
+//! platforms:
 //! - config.toml - User configuration
 //! - credentials.enc - Encrypted credentials
 //! - perch.sqlite - Database
+//! - cache/images/ - Downloaded images
+//! - cache/release-notes/ - Notes for releases Perch offered to update to
+//! - themes/ - Custom theme palettes
+//!
+//! The log file, when logging to one is on, goes in the platform cache
+//! directory instead (e.g. ~/.cache/perch/perch.log), as it's safe to delete.
+//!
+//! `XDG_CONFIG_HOME`, `XDG_DATA_HOME` and `XDG_CACHE_HOME` move the config,
+//! the data (database and credentials) and the caches when they're set. A
+//! database or credentials file already in the config directory stays in
+//! use, so setting `XDG_DATA_HOME` later doesn't lose them.
+//!
+//! `--config-dir` or `PERCH_CONFIG_DIR` puts everything under one directory
+//! instead, laid out as above, for isolated test setups and portable
+//! installs.
+//!
+//! `--profile <name>` keeps a separate setup (accounts, settings, caches) in
+//! a `profiles/<name>/` subdirectory of each of these directories. Without
+//! it, Perch uses the directories themselves, as it always has.
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
+use std::ffi::OsString;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Directory given with `--config-dir`, which wins over `PERCH_CONFIG_DIR`
+static CONFIG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Keep everything under `dir` for the rest of the run (`--config-dir`).
+/// Call it before anything reads a path; only the first call counts.
+pub fn set_config_dir(dir: PathBuf) {
+    let _ = CONFIG_DIR.set(dir);
+}
+
+/// Profile given with `--profile`
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Use the profile called `name` for the rest of the run (`--profile`).
+/// Call it before anything reads a path; only the first call counts.
+pub fn set_profile(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        bail!("Invalid profile name: {name}\nUse letters, digits, '-' and '_'");
+    }
+    let _ = PROFILE.set(name.to_string());
+    Ok(())
+}
+
+/// The profile in use, if one was picked with `--profile`
+pub fn profile() -> Option<&'static str> {
+    PROFILE.get().map(String::as_str)
+}
 
-/// Get the Perch data directory (~/.config/perch/)
-///
-/// This is consistent across all platforms for simplicity.
+/// Where each kind of file goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Dirs {
+    /// Config and custom themes
+    config: PathBuf,
+    /// Database and credentials
+    data: PathBuf,
+    /// Downloaded images and release notes
+    cache: PathBuf,
+    /// The log file
+    log: PathBuf,
+}
+
+impl Dirs {
+    /// The directories for this run
+    fn current() -> Result<Self> {
+        let dirs = Self::resolve(
+            CONFIG_DIR.get().cloned(),
+            |name| std::env::var_os(name),
+            dirs::home_dir(),
+            dirs::cache_dir(),
+        )?;
+        Ok(dirs.in_profile(profile()))
+    }
+
+    /// The directories of profile `name`, or these ones without a profile
+    fn in_profile(self, name: Option<&str>) -> Self {
+        let Some(name) = name else {
+            return self;
+        };
+        let dir = |base: PathBuf| base.join("profiles").join(name);
+        Self {
+            config: dir(self.config),
+            data: dir(self.data),
+            cache: dir(self.cache),
+            log: dir(self.log),
+        }
+    }
+
+    /// Work out the directories from an explicit base directory, the
+    /// environment (looked up with `env`), the home directory and the
+    /// platform cache directory
+    fn resolve(
+        config_dir: Option<PathBuf>,
+        env: impl Fn(&str) -> Option<OsString>,
+        home: Option<PathBuf>,
+        platform_cache: Option<PathBuf>,
+    ) -> Result<Self> {
+        let base = config_dir.or_else(|| {
+            env("PERCH_CONFIG_DIR")
+                .filter(|dir| !dir.is_empty())
+                .map(|dir| expand_tilde(&dir.to_string_lossy()))
+        });
+        if let Some(base) = base {
+            return Ok(Self {
+                config: base.clone(),
+                data: base.clone(),
+                cache: base.join("cache"),
+                log: base.join("cache"),
+            });
+        }
+
+        // Relative XDG paths are invalid and ignored, as the spec says
+        let xdg = |name| {
+            env(name)
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_absolute())
+                .map(|dir| dir.join("perch"))
+        };
+        let config = match xdg("XDG_CONFIG_HOME") {
+            Some(dir) => dir,
+            None => home
+                .context("Could not determine home directory")?
+                .join(".config")
+                .join("perch"),
+        };
+        let cache = xdg("XDG_CACHE_HOME");
+        let log = cache
+            .clone()
+            .or_else(|| platform_cache.map(|dir| dir.join("perch")))
+            .unwrap_or_else(|| config.join("cache"));
+        Ok(Self {
+            data: xdg("XDG_DATA_HOME").unwrap_or_else(|| config.clone()),
+            cache: cache.unwrap_or_else(|| config.join("cache")),
+            log,
+            config,
+        })
+    }
+
+    /// Path of a data file: in the data directory, unless an older copy is
+    /// already in the config directory
+    fn data_file(&self, name: &str) -> PathBuf {
+        let legacy = self.config.join(name);
+        if self.data == self.config || legacy.exists() {
+            legacy
+        } else {
+            self.data.join(name)
+        }
+    }
+}
+
+/// Create `dir` if it's missing and return it
+fn ensure_dir(dir: PathBuf, what: &str) -> Result<PathBuf> {
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {what} directory"))?;
+    Ok(dir)
+}
+
+/// Create the directory a file goes in, and return the file's path
+fn ensure_parent(path: PathBuf) -> Result<PathBuf> {
+    if let Some(parent) = path.parent() {
+        ensure_dir(parent.to_path_buf(), "perch")?;
+    }
+    Ok(path)
+}
+
+/// Get the Perch config directory (~/.config/perch/ unless overridden)
 pub fn perch_dir() -> Result<PathBuf> {
-    let home = dirs::home_dir().context("Could not determine home directory")?;
-    let perch_dir = home.join(".config").join("perch");
-    fs::create_dir_all(&perch_dir).context("Failed to create perch directory")?;
-    Ok(perch_dir)
+    ensure_dir(Dirs::current()?.config, "perch")
 }
 
 /// Get the config file path (~/.config/perch/config.toml)
@@ -26,10 +189,166 @@ pub fn config_path() -> Result<PathBuf> {
 
 /// Get the database file path (~/.config/perch/perch.sqlite)
 pub fn database_path() -> Result<PathBuf> {
-    Ok(perch_dir()?.join("perch.sqlite"))
+    ensure_parent(Dirs::current()?.data_file("perch.sqlite"))
 }
 
 /// Get the credentials file path (~/.config/perch/credentials.enc)
 pub fn credentials_path() -> Result<PathBuf> {
-    Ok(perch_dir()?.join("credentials.enc"))
+    ensure_parent(Dirs::current()?.data_file("credentials.enc"))
+}
+
+/// Get the image cache directory (~/.config/perch/cache/images/)
+pub fn image_cache_dir() -> Result<PathBuf> {
+    ensure_dir(Dirs::current()?.cache.join("images"), "image cache")
+}
+
+/// Get the release notes cache directory (~/.config/perch/cache/release-notes/)
+pub fn release_notes_dir() -> Result<PathBuf> {
+    ensure_dir(
+        Dirs::current()?.cache.join("release-notes"),
+        "release notes",
+    )
+}
+
+/// Get the log file path (perch.log in the platform cache directory)
+pub fn log_path() -> Result<PathBuf> {
+    Ok(ensure_dir(Dirs::current()?.log, "log")?.join("perch.log"))
+}
+
+/// Get the custom themes directory (~/.config/perch/themes/), which may not
+/// exist
+pub fn themes_dir() -> Result<PathBuf> {
+    Ok(perch_dir()?.join("themes"))
+}
+
+/// Expand a leading `~` in a user-supplied path to the home directory
+pub fn expand_tilde(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map_or_else(|| PathBuf::from(path), |home| home.join(rest)),
+        None => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(config_dir: Option<&str>, vars: &[(&str, &str)]) -> Dirs {
+        Dirs::resolve(
+            config_dir.map(PathBuf::from),
+            |name| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| OsString::from(value))
+            },
+            Some(PathBuf::from("/home/ana")),
+            Some(PathBuf::from("/home/ana/.cache")),
+        )
+        .unwrap()
+    }
+
+    fn dirs(config: &str, data: &str, cache: &str, log: &str) -> Dirs {
+        Dirs {
+            config: PathBuf::from(config),
+            data: PathBuf::from(data),
+            cache: PathBuf::from(cache),
+            log: PathBuf::from(log),
+        }
+    }
+
+    #[test]
+    fn test_default_dirs() {
+        assert_eq!(
+            resolve(None, &[]),
+            dirs(
+                "/home/ana/.config/perch",
+                "/home/ana/.config/perch",
+                "/home/ana/.config/perch/cache",
+                "/home/ana/.cache/perch",
+            )
+        );
+    }
+
+    #[test]
+    fn test_config_dir_override() {
+        let expected = dirs("/tmp/p", "/tmp/p", "/tmp/p/cache", "/tmp/p/cache");
+        let vars = [("PERCH_CONFIG_DIR", "/tmp/p"), ("XDG_CONFIG_HOME", "/xdg")];
+        assert_eq!(resolve(None, &vars), expected);
+        // The flag wins over the environment
+        assert_eq!(
+            resolve(Some("/tmp/p"), &[("PERCH_CONFIG_DIR", "/other")]),
+            expected
+        );
+        // An empty variable is as good as unset
+        assert_eq!(
+            resolve(None, &[("PERCH_CONFIG_DIR", "")]),
+            resolve(None, &[])
+        );
+    }
+
+    #[test]
+    fn test_xdg_dirs() {
+        let vars = [
+            ("XDG_CONFIG_HOME", "/xdg/config"),
+            ("XDG_DATA_HOME", "/xdg/data"),
+            ("XDG_CACHE_HOME", "/xdg/cache"),
+        ];
+        assert_eq!(
+            resolve(None, &vars),
+            dirs(
+                "/xdg/config/perch",
+                "/xdg/data/perch",
+                "/xdg/cache/perch",
+                "/xdg/cache/perch",
+            )
+        );
+        // Relative ones are ignored
+        assert_eq!(
+            resolve(None, &[("XDG_CONFIG_HOME", "config")]),
+            resolve(None, &[])
+        );
+    }
+
+    #[test]
+    fn test_profiles() {
+        let base = resolve(None, &[("XDG_DATA_HOME", "/xdg/data")]);
+        assert_eq!(base.clone().in_profile(None), base);
+        assert_eq!(
+            base.in_profile(Some("work")),
+            dirs(
+                "/home/ana/.config/perch/profiles/work",
+                "/xdg/data/perch/profiles/work",
+                "/home/ana/.config/perch/cache/profiles/work",
+                "/home/ana/.cache/perch/profiles/work",
+            )
+        );
+        assert_eq!(
+            resolve(Some("/tmp/p"), &[]).in_profile(Some("work")).config,
+            PathBuf::from("/tmp/p/profiles/work")
+        );
+
+        for name in ["", "../work", "my work", ".hidden"] {
+            assert!(set_profile(name).is_err(), "{name:?}");
+        }
+    }
+
+    #[test]
+    fn test_data_file_keeps_existing_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("config");
+        let dirs = Dirs {
+            config: config.clone(),
+            data: dir.path().join("data"),
+            cache: dir.path().join("cache"),
+            log: dir.path().join("cache"),
+        };
+        assert_eq!(
+            dirs.data_file("perch.sqlite"),
+            dir.path().join("data").join("perch.sqlite")
+        );
+
+        fs::create_dir_all(&config).unwrap();
+        fs::write(config.join("perch.sqlite"), "").unwrap();
+        assert_eq!(dirs.data_file("perch.sqlite"), config.join("perch.sqlite"));
+    }
 }
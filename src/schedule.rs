@@ -1,21 +1,199 @@
 //! Time parsing utilities for scheduled posts
 
+use std::fmt;
+
 use anyhow::{Result, anyhow};
-use chrono::{DateTime, Duration, Local, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, MappedLocalTime, NaiveDate, NaiveDateTime,
+    NaiveTime, Offset, TimeZone, Utc, Weekday,
+};
+use serde::{Deserialize, Serialize};
+
+/// Timezone that schedule times are entered and shown in. Times are always
+/// stored in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Timezone {
+    /// The system's local timezone, following its daylight saving rules
+    #[default]
+    Local,
+    /// A fixed offset from UTC
+    Fixed(FixedOffset),
+    /// A named zone like `Europe/Berlin`, following its daylight saving rules
+    Named(chrono_tz::Tz),
+}
+
+impl Timezone {
+    /// Parse `local`, `UTC`, an offset like `+02:00`, `-0330` or `UTC+5`, or
+    /// a zone name like `Europe/Berlin`
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        if input.eq_ignore_ascii_case("local") {
+            return Ok(Self::Local);
+        }
+        Self::parse_offset(input).or_else(|e| {
+            chrono_tz::Tz::from_str_insensitive(input)
+                .map(Self::Named)
+                .map_err(|_| e)
+        })
+    }
+
+    fn parse_offset(input: &str) -> Result<Self> {
+        let lower = input.to_lowercase();
+
+        let offset = lower
+            .strip_prefix("utc")
+            .or_else(|| lower.strip_prefix("gmt"))
+            .unwrap_or(&lower);
+        if offset.is_empty() || offset == "z" {
+            return Ok(Self::Fixed(
+                FixedOffset::east_opt(0).expect("zero offset is valid"),
+            ));
+        }
+
+        let (sign, rest) = match offset.split_at(1) {
+            ("+", rest) => (1, rest),
+            ("-", rest) => (-1, rest),
+            _ => return Err(timezone_error(input)),
+        };
+        let (hours, minutes) = match rest.split_once(':') {
+            Some((h, m)) => (h, m),
+            None if rest.len() > 2 => rest.split_at(rest.len() - 2),
+            None => (rest, "0"),
+        };
+        let (Ok(hours), Ok(minutes)) = (hours.parse::<i32>(), minutes.parse::<i32>()) else {
+            return Err(timezone_error(input));
+        };
+        if minutes >= 60 {
+            return Err(timezone_error(input));
+        }
+
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(Self::Fixed)
+            .ok_or_else(|| timezone_error(input))
+    }
+
+    /// Format a time in this timezone, e.g. `2030-01-15 14:30 +05:30`
+    pub fn format(&self, dt: DateTime<Utc>) -> String {
+        let local = dt.with_timezone(self);
+        if local.offset().local_minus_utc() == 0 && *self != Self::Local {
+            local.format("%Y-%m-%d %H:%M UTC").to_string()
+        } else {
+            local.format("%Y-%m-%d %H:%M %:z").to_string()
+        }
+    }
+
+    /// Format a time compactly for lists: the time alone today, the day and
+    /// time this year, and the date before that
+    pub fn format_short(&self, dt: DateTime<Utc>) -> String {
+        self.format_short_at(dt, Utc::now())
+    }
+
+    fn format_short_at(&self, dt: DateTime<Utc>, now: DateTime<Utc>) -> String {
+        let local = dt.with_timezone(self);
+        let today = now.with_timezone(self).date_naive();
+        if local.date_naive() == today {
+            local.format("%H:%M").to_string()
+        } else if local.year() == today.year() {
+            local.format("%b %d %H:%M").to_string()
+        } else {
+            local.format("%Y-%m-%d").to_string()
+        }
+    }
+}
+
+fn timezone_error(input: &str) -> anyhow::Error {
+    anyhow!(
+        "Unknown timezone: '{input}' (use 'local', 'UTC', an offset like '+02:00' or a zone like 'Europe/Berlin')"
+    )
+}
+
+impl fmt::Display for Timezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local => f.write_str("local"),
+            Self::Fixed(offset) if offset.local_minus_utc() == 0 => f.write_str("UTC"),
+            Self::Fixed(offset) => write!(f, "{offset}"),
+            Self::Named(tz) => f.write_str(tz.name()),
+        }
+    }
+}
+
+impl TryFrom<String> for Timezone {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        Self::parse(&value)
+    }
+}
+
+impl From<Timezone> for String {
+    fn from(value: Timezone) -> Self {
+        value.to_string()
+    }
+}
+
+impl TimeZone for Timezone {
+    type Offset = FixedOffset;
+
+    fn from_offset(offset: &FixedOffset) -> Self {
+        Self::Fixed(*offset)
+    }
+
+    fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<FixedOffset> {
+        match self {
+            Self::Local => Local.offset_from_local_date(local),
+            Self::Fixed(offset) => MappedLocalTime::Single(*offset),
+            Self::Named(tz) => tz.offset_from_local_date(local).map(|o| o.fix()),
+        }
+    }
+
+    fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> MappedLocalTime<FixedOffset> {
+        match self {
+            Self::Local => Local.offset_from_local_datetime(local),
+            Self::Fixed(offset) => MappedLocalTime::Single(*offset),
+            Self::Named(tz) => tz.offset_from_local_datetime(local).map(|o| o.fix()),
+        }
+    }
+
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+        match self {
+            Self::Local => Local.offset_from_utc_date(utc),
+            Self::Fixed(offset) => *offset,
+            Self::Named(tz) => tz.offset_from_utc_date(utc).fix(),
+        }
+    }
+
+    fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+        match self {
+            Self::Local => Local.offset_from_utc_datetime(utc),
+            Self::Fixed(offset) => *offset,
+            Self::Named(tz) => tz.offset_from_utc_datetime(utc).fix(),
+        }
+    }
+}
 
 /// Parse a schedule time string into a `DateTime`<Utc>
 ///
 /// Supports formats:
 /// - Relative: "in 5m", "in 2h", "in 1d", "in 30 minutes", "in 2 hours"
 /// - Absolute time today: "15:00", "3pm", "15:30"
+/// - Day phrases: "tomorrow 9am", "tonight", "next monday 14:00"
 /// - Absolute datetime: "YYYY-MM-DD 15:00", "YYYY-MM-DDT15:00:00"
 /// - ISO 8601: "YYYY-MM-DDT15:00:00Z", "YYYY-MM-DDT15:00:00+01:00"
-pub fn parse_schedule_time(input: &str) -> Result<DateTime<Utc>> {
+///
+/// Times and dates without an offset are read in `tz`.
+pub fn parse_schedule_time(input: &str, tz: &Timezone) -> Result<DateTime<Utc>> {
+    parse_schedule_time_at(input, tz, Utc::now())
+}
+
+/// [`parse_schedule_time`] as if it were `now`
+fn parse_schedule_time_at(input: &str, tz: &Timezone, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
     let input = input.trim().to_lowercase();
 
     // Try relative time first
     if let Some(rest) = input.strip_prefix("in ") {
-        return parse_relative_time(rest);
+        return parse_relative_time(rest, now);
     }
 
     // Try ISO 8601 with timezone
@@ -28,37 +206,33 @@ pub fn parse_schedule_time(input: &str) -> Result<DateTime<Utc>> {
         return Ok(dt.with_timezone(&Utc));
     }
 
-    // Try datetime without timezone (assume local)
-    if let Ok(naive) = NaiveDateTime::parse_from_str(&input, "%Y-%m-%d %H:%M:%S") {
-        return local_to_utc(naive);
-    }
-    if let Ok(naive) = NaiveDateTime::parse_from_str(&input, "%Y-%m-%d %H:%M") {
-        return local_to_utc(naive);
-    }
-    if let Ok(naive) = NaiveDateTime::parse_from_str(&input, "%Y-%m-%dT%H:%M:%S") {
-        return local_to_utc(naive);
+    // Try datetime without timezone (in the configured one)
+    for format in [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+    ] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(&input, format) {
+            return local_to_utc(naive, tz);
+        }
     }
-    if let Ok(naive) = NaiveDateTime::parse_from_str(&input, "%Y-%m-%dT%H:%M") {
-        return local_to_utc(naive);
+
+    // Try a day phrase: "tomorrow 9am", "tonight", "next monday 14:00"
+    if let Some(result) = parse_day_phrase(&input, tz, now) {
+        return result;
     }
 
     // Try time only (assume today, or tomorrow if time has passed)
     if let Some(time) = parse_time_only(&input) {
-        let today = Local::now().date_naive();
-        let naive_dt = today.and_time(time);
-        let local_dt = Local.from_local_datetime(&naive_dt).single();
-
-        if let Some(dt) = local_dt {
-            // If time has passed, schedule for tomorrow
-            if dt <= Local::now() {
-                let tomorrow = today + Duration::days(1);
-                let naive_dt = tomorrow.and_time(time);
-                if let Some(dt) = Local.from_local_datetime(&naive_dt).single() {
-                    return Ok(dt.with_timezone(&Utc));
-                }
-            }
-            return Ok(dt.with_timezone(&Utc));
+        let today = now.with_timezone(tz).date_naive();
+        if let Ok(dt) = local_to_utc(today.and_time(time), tz)
+            && dt > now
+        {
+            return Ok(dt);
         }
+        // If time has passed, schedule for tomorrow
+        return local_to_utc((today + Duration::days(1)).and_time(time), tz);
     }
 
     Err(anyhow!(
@@ -66,18 +240,20 @@ pub fn parse_schedule_time(input: &str) -> Result<DateTime<Utc>> {
          Supported formats:\n  \
          - Relative: 'in 5m', 'in 2h', 'in 1d', 'in 30 minutes'\n  \
          - Time today: '15:00', '3pm', '15:30'\n  \
+         - Day: 'tomorrow 9am', 'tonight', 'today 17:00'\n  \
+         - Weekday: 'monday', 'next friday 14:00'\n  \
          - Date+time: 'YYYY-MM-DD 15:00'",
         input
     ))
 }
 
 /// Parse relative time like "5m", "2h", "1d", "30 minutes", "2 hours"
-fn parse_relative_time(input: &str) -> Result<DateTime<Utc>> {
+fn parse_relative_time(input: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
     let input = input.trim();
 
     // Try short format: 5m, 2h, 1d
     if let Some(duration) = parse_short_duration(input) {
-        return Ok(Utc::now() + duration);
+        return Ok(now + duration);
     }
 
     // Try long format: "30 minutes", "2 hours", "1 day"
@@ -94,7 +270,7 @@ fn parse_relative_time(input: &str) -> Result<DateTime<Utc>> {
             "week" => Duration::weeks(amount),
             _ => return Err(anyhow!("Unknown time unit: {}", parts[1])),
         };
-        return Ok(Utc::now() + duration);
+        return Ok(now + duration);
     }
 
     Err(anyhow!(
@@ -104,6 +280,90 @@ fn parse_relative_time(input: &str) -> Result<DateTime<Utc>> {
     ))
 }
 
+/// Time used for a day phrase without one ("tomorrow", "next monday")
+const DEFAULT_DAY_TIME: (u32, u32) = (9, 0);
+
+/// Time "tonight" means without one
+const TONIGHT_TIME: (u32, u32) = (20, 0);
+
+/// Parse "today", "tonight", "tomorrow" or a weekday (optionally after
+/// "next"), followed by an optional time (`at` is allowed before it).
+///
+/// Returns `None` if the input doesn't start with a day word. A weekday is
+/// always in the future, so "monday" on a Monday means next week's. "next"
+/// picks the weekday in the following week (weeks start on Monday), so
+/// "next friday" on a Monday is 11 days away rather than 4.
+fn parse_day_phrase(
+    input: &str,
+    tz: &Timezone,
+    now: DateTime<Utc>,
+) -> Option<Result<DateTime<Utc>>> {
+    let today = now.with_timezone(tz).date_naive();
+    let (next_week, input) = match input.strip_prefix("next ") {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, input),
+    };
+    let (day, rest) = input.split_once(' ').unwrap_or((input, ""));
+
+    let (date, default_time) = match day {
+        "today" | "tonight" | "tomorrow" if next_week => {
+            return Some(Err(anyhow!("'next' goes with a weekday, not '{day}'")));
+        }
+        "today" => (today, DEFAULT_DAY_TIME),
+        "tonight" => (today, TONIGHT_TIME),
+        "tomorrow" => (today + Duration::days(1), DEFAULT_DAY_TIME),
+        _ => {
+            let weekday = parse_weekday(day)?;
+            let from_monday = today.weekday().num_days_from_monday();
+            let days_ahead = if next_week {
+                7 - from_monday + weekday.num_days_from_monday()
+            } else {
+                match (7 + weekday.num_days_from_monday() - from_monday) % 7 {
+                    0 => 7,
+                    days => days,
+                }
+            };
+            (
+                today + Duration::days(i64::from(days_ahead)),
+                DEFAULT_DAY_TIME,
+            )
+        }
+    };
+
+    let rest = rest.trim();
+    let rest = rest.strip_prefix("at ").unwrap_or(rest).trim();
+    let time = if rest.is_empty() {
+        NaiveTime::from_hms_opt(default_time.0, default_time.1, 0)?
+    } else {
+        match parse_time_only(rest) {
+            Some(time) => time,
+            None => return Some(Err(anyhow!("Could not parse time: '{rest}'"))),
+        }
+    };
+
+    Some(local_to_utc(date.and_time(time), tz).and_then(|dt| {
+        if dt > now {
+            Ok(dt)
+        } else {
+            Err(anyhow!("'{input}' has already passed"))
+        }
+    }))
+}
+
+/// Parse a weekday name, full or abbreviated
+fn parse_weekday(input: &str) -> Option<Weekday> {
+    match input {
+        "monday" | "mon" => Some(Weekday::Mon),
+        "tuesday" | "tue" | "tues" => Some(Weekday::Tue),
+        "wednesday" | "wed" => Some(Weekday::Wed),
+        "thursday" | "thu" | "thurs" => Some(Weekday::Thu),
+        "friday" | "fri" => Some(Weekday::Fri),
+        "saturday" | "sat" => Some(Weekday::Sat),
+        "sunday" | "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
 /// Parse short duration format: 5m, 2h, 1d, 30s
 fn parse_short_duration(input: &str) -> Option<Duration> {
     let input = input.trim();
@@ -159,10 +419,9 @@ fn parse_time_only(input: &str) -> Option<NaiveTime> {
     None
 }
 
-/// Convert naive local datetime to UTC
-fn local_to_utc(naive: NaiveDateTime) -> Result<DateTime<Utc>> {
-    Local
-        .from_local_datetime(&naive)
+/// Convert a naive datetime in `tz` to UTC
+fn local_to_utc(naive: NaiveDateTime, tz: &Timezone) -> Result<DateTime<Utc>> {
+    tz.from_local_datetime(&naive)
         .single()
         .map(|dt| dt.with_timezone(&Utc))
         .ok_or_else(|| anyhow!("Ambiguous or invalid local time"))
@@ -171,12 +430,12 @@ fn local_to_utc(naive: NaiveDateTime) -> Result<DateTime<Utc>> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Datelike;
+    use chrono::Timelike;
 
     #[test]
     fn test_relative_short() {
         let now = Utc::now();
-        let result = parse_schedule_time("in 5m").unwrap();
+        let result = parse_schedule_time("in 5m", &Timezone::Local).unwrap();
         let diff = result - now;
         assert!(diff.num_minutes() >= 4 && diff.num_minutes() <= 6);
     }
@@ -184,16 +443,135 @@ mod tests {
     #[test]
     fn test_relative_long() {
         let now = Utc::now();
-        let result = parse_schedule_time("in 2 hours").unwrap();
+        let result = parse_schedule_time("in 2 hours", &Timezone::Local).unwrap();
         let diff = result - now;
         assert!(diff.num_hours() >= 1 && diff.num_hours() <= 3);
     }
 
     #[test]
     fn test_datetime() {
-        let result = parse_schedule_time("2030-01-15 14:30").unwrap();
+        let result = parse_schedule_time("2030-01-15 14:30", &Timezone::Local).unwrap();
         assert_eq!(result.year(), 2030);
         assert_eq!(result.month(), 1);
         assert_eq!(result.day(), 15);
     }
+
+    #[test]
+    fn test_time_in_configured_timezone() {
+        let tz = Timezone::parse("UTC+5:30").unwrap();
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().to_utc();
+
+        // 08:00 UTC is 13:30 there, so 15:00 is later the same day
+        let result = parse_schedule_time_at("15:00", &tz, at("2030-01-15T08:00:00Z")).unwrap();
+        assert_eq!(result, at("2030-01-15T09:30:00Z"));
+
+        // 10:00 UTC is 15:30 there, so 15:00 is tomorrow
+        let result = parse_schedule_time_at("15:00", &tz, at("2030-01-15T10:00:00Z")).unwrap();
+        assert_eq!(result, at("2030-01-16T09:30:00Z"));
+    }
+
+    #[test]
+    fn test_format_short() {
+        let tz = Timezone::parse("+02:00").unwrap();
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().to_utc();
+        let now = at("2030-06-15T12:00:00Z");
+
+        assert_eq!(tz.format_short_at(at("2030-06-15T08:05:00Z"), now), "10:05");
+        // Already the same day there
+        assert_eq!(tz.format_short_at(at("2030-06-14T22:30:00Z"), now), "00:30");
+        assert_eq!(
+            tz.format_short_at(at("2030-01-02T09:00:00Z"), now),
+            "Jan 02 11:00"
+        );
+        assert_eq!(
+            tz.format_short_at(at("2029-12-31T09:00:00Z"), now),
+            "2029-12-31"
+        );
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let tz = Timezone::parse("-03:00").unwrap();
+        let result = parse_schedule_time("2030-01-15 14:30", &tz).unwrap();
+        assert_eq!(result.hour(), 17);
+        assert_eq!(tz.format(result), "2030-01-15 14:30 -03:00");
+        assert_eq!(
+            parse_schedule_time(&tz.format(result)[..16], &tz).unwrap(),
+            result
+        );
+
+        let utc = Timezone::parse("utc").unwrap();
+        assert_eq!(utc.format(result), "2030-01-15 17:30 UTC");
+        assert_eq!(Timezone::parse(&tz.to_string()).unwrap(), tz);
+        assert!(Timezone::parse("Europe/Atlantis").is_err());
+    }
+
+    #[test]
+    fn test_next_weekday_is_in_the_following_week() {
+        let tz = Timezone::parse("+01:00").unwrap();
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().to_utc();
+        // Monday
+        let monday = at("2030-01-14T08:00:00+01:00");
+
+        let friday = parse_schedule_time_at("friday", &tz, monday).unwrap();
+        assert_eq!(friday, at("2030-01-18T09:00:00+01:00"));
+        let next_friday = parse_schedule_time_at("next friday", &tz, monday).unwrap();
+        assert_eq!(next_friday, at("2030-01-25T09:00:00+01:00"));
+        let next_fri = parse_schedule_time_at("next fri at 14:00", &tz, monday).unwrap();
+        assert_eq!(next_fri, at("2030-01-25T14:00:00+01:00"));
+
+        // From a Saturday, next week's Monday is the coming one
+        let saturday = at("2030-01-19T08:00:00+01:00");
+        let result = parse_schedule_time_at("next monday", &tz, saturday).unwrap();
+        assert_eq!(result, at("2030-01-21T09:00:00+01:00"));
+        let result = parse_schedule_time_at("next sunday", &tz, saturday).unwrap();
+        assert_eq!(result, at("2030-01-27T09:00:00+01:00"));
+    }
+
+    #[test]
+    fn test_named_zone_follows_dst() {
+        let tz = Timezone::parse("europe/berlin").unwrap();
+        assert_eq!(tz.to_string(), "Europe/Berlin");
+        assert_eq!(Timezone::parse(&tz.to_string()).unwrap(), tz);
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().to_utc();
+
+        // 9:00 in Berlin is 8:00 UTC in winter and 7:00 UTC in summer
+        let result = parse_schedule_time_at("2030-03-30 09:00", &tz, at("2030-03-01T00:00:00Z"));
+        assert_eq!(result.unwrap(), at("2030-03-30T08:00:00Z"));
+        let result = parse_schedule_time_at("2030-03-31 09:00", &tz, at("2030-03-01T00:00:00Z"));
+        assert_eq!(result.unwrap(), at("2030-03-31T07:00:00Z"));
+
+        // "tomorrow" across the switch keeps the wall-clock time
+        let result = parse_schedule_time_at("tomorrow 9am", &tz, at("2030-03-30T12:00:00Z"));
+        assert_eq!(result.unwrap(), at("2030-03-31T07:00:00Z"));
+        assert_eq!(
+            tz.format(at("2030-03-31T07:00:00Z")),
+            "2030-03-31 09:00 +02:00"
+        );
+    }
+
+    #[test]
+    fn test_day_phrases() {
+        let tz = Timezone::parse("+01:00").unwrap();
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().to_utc();
+        // Monday evening
+        let now = at("2030-01-14T18:00:00+01:00");
+
+        let result = parse_schedule_time_at("tomorrow 9am", &tz, now).unwrap();
+        assert_eq!(result, at("2030-01-15T09:00:00+01:00"));
+
+        let result = parse_schedule_time_at("Tonight", &tz, now).unwrap();
+        assert_eq!(result, at("2030-01-14T20:00:00+01:00"));
+
+        // Today is Monday, so next Monday is a week away
+        let result = parse_schedule_time_at("next monday", &tz, now).unwrap();
+        assert_eq!(result, at("2030-01-21T09:00:00+01:00"));
+
+        let result = parse_schedule_time_at("fri at 14:00", &tz, now).unwrap();
+        assert_eq!(result, at("2030-01-18T14:00:00+01:00"));
+
+        assert!(parse_schedule_time_at("today 8am", &tz, now).is_err());
+        assert!(parse_schedule_time_at("next tomorrow", &tz, now).is_err());
+        assert!(parse_schedule_time_at("tomorrow noonish", &tz, now).is_err());
+    }
 }
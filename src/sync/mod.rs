@@ -5,7 +5,7 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::time::{Duration, interval};
 
-use crate::api::get_client;
+use crate::api::{PostOptions, get_client};
 use crate::db::Database;
 use crate::models::{Account, Post};
 
@@ -97,7 +97,7 @@ pub async fn cross_post(
     for (account, token) in accounts.iter().zip(tokens.iter()) {
         let result = async {
             let client = get_client(account, token).await?;
-            client.post(content).await
+            client.post(content, &PostOptions::default()).await
         }
         .await;
 
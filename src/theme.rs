@@ -2,69 +2,171 @@
 //!
 //! Perch supports popular terminal color schemes out of the box.
 //! Theme palettes are provided by the `ratatui-themes` crate.
-
+//!
+//! More themes can be added as TOML or JSON files in
+//! `~/.config/perch/themes/`, named after the file:
+//!
+//! ```toml
+//! name = "Solarized Light"  # optional display name
+//! bg = "#fdf6e3"
+//! fg = "#657b83"
+//! muted = "#93a1a1"
+//! accent = "#268bd2"
+//! secondary = "#2aa198"
+//! success = "#859900"
+//! warning = "#b58900"
+//! error = "#dc322f"
+//! info = "#268bd2"
+//! selection = "#eee8d5"
+//! ```
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{PoisonError, RwLock};
+
+use anyhow::{Context, Result, anyhow, bail};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui_themes::{ThemeName, ThemePalette};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::paths;
+
+/// Themes found in the themes directory, read on first use and again by
+/// [`reload_themes`]
+static CUSTOM_THEMES: RwLock<Option<&'static CustomThemes>> = RwLock::new(None);
+
+/// Whether to draw without colors, as `NO_COLOR` asks
+static MONOCHROME: AtomicBool = AtomicBool::new(false);
+
+/// Black, white and bright accents for low vision
+const HIGH_CONTRAST: Palette = Palette {
+    bg: Color::Rgb(0, 0, 0),
+    fg: Color::Rgb(255, 255, 255),
+    muted: Color::Rgb(200, 200, 200),
+    accent: Color::Rgb(255, 255, 0),
+    secondary: Color::Rgb(0, 255, 255),
+    success: Color::Rgb(0, 255, 0),
+    warning: Color::Rgb(255, 170, 0),
+    error: Color::Rgb(255, 85, 85),
+    info: Color::Rgb(85, 170, 255),
+    selection: Color::Rgb(0, 0, 192),
+};
+
+/// Draw every theme without colors (or with them again)
+pub fn set_monochrome(monochrome: bool) {
+    MONOCHROME.store(monochrome, Ordering::Relaxed);
+}
+
+/// Whether themes are drawn without colors
+pub fn is_monochrome() -> bool {
+    MONOCHROME.load(Ordering::Relaxed)
+}
+
+/// A built-in theme, or one loaded from the themes directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    /// One of the `ratatui-themes` palettes
+    Builtin(ThemeName),
+    /// Perch's own high-contrast palette
+    HighContrast,
+    /// A palette from a file in the themes directory
+    Custom(&'static CustomTheme),
+}
 
-/// Theme wrapper around `ThemeName` from ratatui-themes.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(transparent)]
-pub struct Theme(pub ThemeName);
+impl Default for Theme {
+    fn default() -> Self {
+        Self::Builtin(ThemeName::default())
+    }
+}
 
 impl Theme {
-    /// Get all available theme names.
+    /// Every theme: the built-in ones, then those from the themes directory
     #[must_use]
-    pub const fn all() -> &'static [ThemeName] {
+    pub fn all() -> Vec<Self> {
         ThemeName::all()
+            .iter()
+            .copied()
+            .map(Self::Builtin)
+            .chain([Self::HighContrast])
+            .chain(custom_themes().themes.iter().map(Self::Custom))
+            .collect()
+    }
+
+    /// Find a theme by its slug
+    #[must_use]
+    pub fn from_slug(slug: &str) -> Option<Self> {
+        Self::all().into_iter().find(|theme| theme.slug() == slug)
+    }
+
+    /// Where this theme is in [`Theme::all`]
+    #[must_use]
+    pub fn index(&self) -> usize {
+        Self::all()
+            .iter()
+            .position(|theme| theme == self)
+            .unwrap_or(0)
     }
 
     /// Get the next theme in rotation
     #[must_use]
     pub fn next(&self) -> Self {
-        Self(self.0.next())
+        let all = Self::all();
+        all[(self.index() + 1) % all.len()]
     }
 
     /// Get the previous theme in rotation
     #[must_use]
     pub fn prev(&self) -> Self {
-        Self(self.0.prev())
+        let all = Self::all();
+        all[self.index().checked_sub(1).unwrap_or(all.len() - 1)]
     }
 
     /// Get the display name for the theme.
     #[must_use]
     pub const fn name(&self) -> &'static str {
-        self.0.display_name()
+        match self {
+            Self::Builtin(name) => name.display_name(),
+            Self::HighContrast => "High Contrast",
+            Self::Custom(theme) => theme.name.as_str(),
+        }
     }
 
-    /// Get the color palette for this theme
+    /// Get the color palette for this theme, or no colors at all when
+    /// they're turned off
     #[must_use]
     pub fn colors(&self) -> ThemeColors {
-        ThemeColors::from_palette(self.0.palette())
-    }
-
-    /// Get the raw color palette for this theme.
-    #[must_use]
-    pub const fn palette(&self) -> ThemePalette {
-        self.0.palette()
+        if is_monochrome() {
+            ThemeColors::monochrome()
+        } else {
+            ThemeColors::from_palette(self.palette())
+        }
     }
 
-    /// Get the inner `ThemeName`
+    /// Get the base colors of this theme.
     #[must_use]
-    pub const fn inner(&self) -> ThemeName {
-        self.0
+    pub fn palette(&self) -> Palette {
+        match self {
+            Self::Builtin(name) => name.palette().into(),
+            Self::HighContrast => HIGH_CONTRAST,
+            Self::Custom(theme) => theme.palette,
+        }
     }
 
     /// Get the kebab-case slug for config files
     #[must_use]
     pub const fn slug(&self) -> &'static str {
-        self.0.slug()
+        match self {
+            Self::Builtin(name) => name.slug(),
+            Self::HighContrast => "high-contrast",
+            Self::Custom(theme) => theme.slug.as_str(),
+        }
     }
 }
 
 impl From<ThemeName> for Theme {
     fn from(name: ThemeName) -> Self {
-        Self(name)
+        Self::Builtin(name)
     }
 }
 
@@ -74,6 +176,275 @@ impl std::fmt::Display for Theme {
     }
 }
 
+impl Serialize for Theme {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.slug())
+    }
+}
+
+impl<'de> Deserialize<'de> for Theme {
+    /// A theme that's no longer there (its file was removed) falls back to
+    /// the default rather than keeping Perch from starting
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let slug = String::deserialize(deserializer)?;
+        Ok(Self::from_slug(&slug).unwrap_or_else(|| {
+            tracing::warn!("Unknown theme {slug}, using the default");
+            Self::default()
+        }))
+    }
+}
+
+/// The base colors of a theme, which the rest are derived from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    /// Background
+    pub bg: Color,
+    /// Text
+    pub fg: Color,
+    /// Dimmed text and borders
+    pub muted: Color,
+    /// Main accent, focused borders and key hints
+    pub accent: Color,
+    /// Second accent
+    pub secondary: Color,
+    /// Success messages
+    pub success: Color,
+    /// Warnings
+    pub warning: Color,
+    /// Errors
+    pub error: Color,
+    /// Informational messages
+    pub info: Color,
+    /// Selected item background
+    pub selection: Color,
+}
+
+impl From<ThemePalette> for Palette {
+    fn from(p: ThemePalette) -> Self {
+        Self {
+            bg: p.bg,
+            fg: p.fg,
+            muted: p.muted,
+            accent: p.accent,
+            secondary: p.secondary,
+            success: p.success,
+            warning: p.warning,
+            error: p.error,
+            info: p.info,
+            selection: p.selection,
+        }
+    }
+}
+
+/// A palette color for marking the posts of one account, as written in the
+/// `[account_color]` table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountColor {
+    /// The theme's main accent
+    Accent,
+    /// The theme's second accent
+    Secondary,
+    /// The success color
+    Green,
+    /// The warning color
+    Yellow,
+    /// The informational color
+    Blue,
+    /// The error color
+    Red,
+}
+
+impl AccountColor {
+    /// Colors in the order accounts get them when not configured. Red
+    /// comes last, as it otherwise reads as an error.
+    pub const ALL: [Self; 6] = [
+        Self::Accent,
+        Self::Secondary,
+        Self::Green,
+        Self::Yellow,
+        Self::Blue,
+        Self::Red,
+    ];
+}
+
+/// A theme loaded from the themes directory
+#[derive(Debug, PartialEq, Eq)]
+pub struct CustomTheme {
+    slug: String,
+    name: String,
+    palette: Palette,
+}
+
+/// A theme file as written: a display name and hex colors
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    name: Option<String>,
+    bg: String,
+    fg: String,
+    muted: String,
+    accent: String,
+    secondary: String,
+    success: String,
+    warning: String,
+    error: String,
+    info: String,
+    selection: String,
+}
+
+impl ThemeFile {
+    fn into_theme(self, slug: String) -> Result<CustomTheme> {
+        let color = |key: &str, value: &str| {
+            parse_hex(value).with_context(|| format!("{key} = \"{value}\""))
+        };
+        Ok(CustomTheme {
+            name: self.name.unwrap_or_else(|| slug.clone()),
+            slug,
+            palette: Palette {
+                bg: color("bg", &self.bg)?,
+                fg: color("fg", &self.fg)?,
+                muted: color("muted", &self.muted)?,
+                accent: color("accent", &self.accent)?,
+                secondary: color("secondary", &self.secondary)?,
+                success: color("success", &self.success)?,
+                warning: color("warning", &self.warning)?,
+                error: color("error", &self.error)?,
+                info: color("info", &self.info)?,
+                selection: color("selection", &self.selection)?,
+            },
+        })
+    }
+}
+
+/// Parse a `#rrggbb` or `#rgb` color
+fn parse_hex(value: &str) -> Result<Color> {
+    let hex = value.trim().trim_start_matches('#');
+    let digits: Vec<u8> = hex
+        .chars()
+        .map(|c| c.to_digit(16).and_then(|d| u8::try_from(d).ok()))
+        .collect::<Option<_>>()
+        .ok_or_else(|| anyhow!("Not a hex color"))?;
+    let [r, g, b] = match digits[..] {
+        [r, g, b] => [r * 17, g * 17, b * 17],
+        [r1, r2, g1, g2, b1, b2] => [r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2],
+        _ => bail!("Not a hex color"),
+    };
+    Ok(Color::Rgb(r, g, b))
+}
+
+/// Custom themes, and why any files were skipped
+#[derive(Debug, Default)]
+struct CustomThemes {
+    themes: Vec<CustomTheme>,
+    warnings: Vec<String>,
+}
+
+fn custom_themes() -> &'static CustomThemes {
+    if let Some(themes) = *CUSTOM_THEMES.read().unwrap_or_else(PoisonError::into_inner) {
+        return themes;
+    }
+    read_themes()
+}
+
+/// Read the themes directory again, after its files changed
+pub fn reload_themes() {
+    read_themes();
+}
+
+/// Read the themes directory, replacing the themes read before.
+///
+/// Those are leaked rather than freed, since a [`Theme`] handed out earlier
+/// may still point at one of them. They are small, and this only happens
+/// when a file is edited.
+fn read_themes() -> &'static CustomThemes {
+    let loaded = match paths::themes_dir() {
+        Ok(dir) => load_themes(&dir),
+        Err(e) => CustomThemes {
+            themes: Vec::new(),
+            warnings: vec![format!("{e:#}")],
+        },
+    };
+    let loaded: &'static CustomThemes = Box::leak(Box::new(loaded));
+    *CUSTOM_THEMES
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(loaded);
+    loaded
+}
+
+/// Why theme files were skipped, one message per file
+pub fn theme_warnings() -> &'static [String] {
+    &custom_themes().warnings
+}
+
+/// Read every `.toml` and `.json` theme in `dir`, in name order. Files that
+/// don't parse, or whose name is already taken, are skipped with a warning.
+fn load_themes(dir: &Path) -> CustomThemes {
+    let mut loaded = CustomThemes::default();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return loaded;
+    };
+    let mut files: Vec<_> = entries.filter_map(|e| Some(e.ok()?.path())).collect();
+    files.sort();
+
+    let builtin: Vec<&str> = ThemeName::all()
+        .iter()
+        .map(ThemeName::slug)
+        .chain([Theme::HighContrast.slug()])
+        .collect();
+    let mut seen: HashMap<String, String> = HashMap::new();
+    for path in files {
+        let (Some(stem), Some(extension)) = (
+            path.file_stem().and_then(|s| s.to_str()),
+            path.extension().and_then(|e| e.to_str()),
+        ) else {
+            continue;
+        };
+        if !matches!(extension, "toml" | "json") {
+            continue;
+        }
+        let file_name = format!("{stem}.{extension}");
+        let slug = stem.to_lowercase();
+        if builtin.contains(&slug.as_str()) {
+            loaded.warnings.push(format!(
+                "Theme {file_name} skipped: {slug} is a built-in theme"
+            ));
+            continue;
+        }
+        if let Some(other) = seen.get(&slug) {
+            loaded.warnings.push(format!(
+                "Theme {file_name} skipped: {other} has the same name"
+            ));
+            continue;
+        }
+
+        match read_theme(&path, extension, slug.clone()) {
+            Ok(theme) => {
+                seen.insert(slug, file_name);
+                loaded.themes.push(theme);
+            }
+            Err(e) => loaded
+                .warnings
+                .push(format!("Theme {file_name} skipped: {e:#}")),
+        }
+    }
+    for warning in &loaded.warnings {
+        tracing::warn!("{warning}");
+    }
+    loaded
+}
+
+fn read_theme(path: &Path, extension: &str, slug: String) -> Result<CustomTheme> {
+    let content = std::fs::read_to_string(path)?;
+    let file: ThemeFile = if extension == "json" {
+        serde_json::from_str(&content)?
+    } else {
+        // Just the message: the full error quotes the file over several lines
+        toml::from_str(&content).map_err(|e| anyhow!("{}", e.message()))?
+    };
+    file.into_theme(slug)
+}
+
 /// Extended color palette for UI elements.
 #[derive(Debug, Clone)]
 pub struct ThemeColors {
@@ -128,12 +499,15 @@ pub struct ThemeColors {
     pub logo_primary: Color,
     /// Logo secondary color
     pub logo_secondary: Color,
+
+    /// No colors: emphasis comes from bold, dim and reverse video alone
+    pub monochrome: bool,
 }
 
 impl ThemeColors {
-    /// Create `ThemeColors` from a `ThemePalette`
+    /// Create `ThemeColors` from a theme's base colors
     #[must_use]
-    pub fn from_palette(p: ThemePalette) -> Self {
+    pub fn from_palette(p: Palette) -> Self {
         let bg_secondary = Self::adjust_brightness(p.bg, 10);
         let bg_highlight = Self::adjust_brightness(p.bg, 20);
 
@@ -165,6 +539,37 @@ impl ThemeColors {
             // Logo colors
             logo_primary: p.accent,
             logo_secondary: p.secondary,
+
+            monochrome: false,
+        }
+    }
+
+    /// The terminal's own colors everywhere, for `NO_COLOR`
+    #[must_use]
+    pub const fn monochrome() -> Self {
+        let none = Color::Reset;
+        Self {
+            bg: none,
+            bg_secondary: none,
+            bg_highlight: none,
+            fg: none,
+            fg_dim: none,
+            fg_muted: none,
+            primary: none,
+            secondary: none,
+            accent: none,
+            success: none,
+            warning: none,
+            error: none,
+            info: none,
+            border: none,
+            border_focus: none,
+            selection: none,
+            mastodon: none,
+            bluesky: none,
+            logo_primary: none,
+            logo_secondary: none,
+            monochrome: true,
         }
     }
 
@@ -195,13 +600,22 @@ impl ThemeColors {
     /// Dimmed text style
     #[must_use]
     pub fn text_dim(&self) -> Style {
-        Style::default().fg(self.fg_dim)
+        self.dim(Style::default().fg(self.fg_dim))
     }
 
     /// Muted text style
     #[must_use]
     pub fn text_muted(&self) -> Style {
-        Style::default().fg(self.fg_muted)
+        self.dim(Style::default().fg(self.fg_muted))
+    }
+
+    /// Without colors, lower contrast can only come from dim text
+    const fn dim(&self, style: Style) -> Style {
+        if self.monochrome {
+            style.add_modifier(Modifier::DIM)
+        } else {
+            style
+        }
     }
 
     /// Primary accent style
@@ -249,16 +663,27 @@ impl ThemeColors {
     /// Focused block border style
     #[must_use]
     pub fn block_focus(&self) -> Style {
-        Style::default().fg(self.border_focus)
+        let style = Style::default().fg(self.border_focus);
+        if self.monochrome {
+            style.add_modifier(Modifier::BOLD)
+        } else {
+            style
+        }
     }
 
-    /// Selected item style
+    /// Selected item style; reverse video when there's no background color
+    /// to fill it with
     #[must_use]
     pub fn selected(&self) -> Style {
-        Style::default()
+        let style = Style::default()
             .bg(self.selection)
             .fg(self.fg)
-            .add_modifier(Modifier::BOLD)
+            .add_modifier(Modifier::BOLD);
+        if self.monochrome {
+            style.add_modifier(Modifier::REVERSED)
+        } else {
+            style
+        }
     }
 
     /// Tab style
@@ -270,9 +695,14 @@ impl ThemeColors {
     /// Active tab style
     #[must_use]
     pub fn tab_active(&self) -> Style {
-        Style::default()
+        let style = Style::default()
             .fg(self.primary)
-            .add_modifier(Modifier::BOLD)
+            .add_modifier(Modifier::BOLD);
+        if self.monochrome {
+            style.add_modifier(Modifier::REVERSED)
+        } else {
+            style
+        }
     }
 
     /// Key hint style (for shortcuts)
@@ -295,6 +725,19 @@ impl ThemeColors {
         Style::default().fg(self.bluesky)
     }
 
+    /// Style of the marker beside an account's posts
+    #[must_use]
+    pub fn account_marker(&self, color: AccountColor) -> Style {
+        Style::default().fg(match color {
+            AccountColor::Accent => self.primary,
+            AccountColor::Secondary => self.secondary,
+            AccountColor::Green => self.success,
+            AccountColor::Yellow => self.warning,
+            AccountColor::Blue => self.info,
+            AccountColor::Red => self.error,
+        })
+    }
+
     /// Logo primary style
     #[must_use]
     pub fn logo_style_primary(&self) -> Style {
@@ -311,3 +754,71 @@ impl ThemeColors {
             .add_modifier(Modifier::BOLD)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monochrome_selection_is_reversed() {
+        let colors = ThemeColors::monochrome();
+        let selected = colors.selected();
+        assert_eq!(selected.bg, Some(Color::Reset));
+        assert!(selected.add_modifier.contains(Modifier::REVERSED));
+
+        let colors = Theme::HighContrast.colors();
+        assert!(!colors.selected().add_modifier.contains(Modifier::REVERSED));
+        assert_eq!(colors.selection, HIGH_CONTRAST.selection);
+    }
+
+    #[test]
+    fn test_load_themes() {
+        let dir = tempfile::tempdir().unwrap();
+        let colors = [
+            "bg",
+            "fg",
+            "muted",
+            "accent",
+            "secondary",
+            "success",
+            "warning",
+            "error",
+            "info",
+            "selection",
+        ];
+        let toml = colors
+            .iter()
+            .map(|c| format!("{c} = \"#1e1e2e\""))
+            .collect::<Vec<_>>()
+            .join("\n");
+        std::fs::write(
+            dir.path().join("Mocha.toml"),
+            format!("name = \"Mocha\"\n{toml}"),
+        )
+        .unwrap();
+        let json = colors
+            .iter()
+            .map(|c| format!("\"{c}\": \"#abc\""))
+            .collect::<Vec<_>>()
+            .join(",");
+        std::fs::write(dir.path().join("light.json"), format!("{{{json}}}")).unwrap();
+        std::fs::write(
+            dir.path().join("broken.toml"),
+            toml.replace("#1e1e2e", "#12345g"),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("mocha.json"), format!("{{{json}}}")).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a theme").unwrap();
+
+        let loaded = load_themes(dir.path());
+        let slugs: Vec<_> = loaded.themes.iter().map(|t| t.slug.as_str()).collect();
+        assert_eq!(slugs, ["mocha", "light"]);
+        assert_eq!(loaded.themes[0].name, "Mocha");
+        assert_eq!(loaded.themes[0].palette.bg, Color::Rgb(0x1e, 0x1e, 0x2e));
+        assert_eq!(loaded.themes[1].name, "light");
+        assert_eq!(loaded.themes[1].palette.fg, Color::Rgb(0xaa, 0xbb, 0xcc));
+        // The bad color, and the second "mocha"
+        assert_eq!(loaded.warnings.len(), 2);
+        assert!(loaded.warnings[0].contains("broken.toml"));
+    }
+}
@@ -1,5 +1,8 @@
 //! Auto-update functionality for Perch
 
+#[cfg(feature = "self-update")]
+mod release;
+
 use std::time::Duration;
 
 /// Current version from Cargo.toml
@@ -30,9 +33,7 @@ pub fn check_for_updates_crates_io() -> VersionCheck {
 pub fn check_for_updates_crates_io_timeout(timeout: Duration) -> VersionCheck {
     let url = "https://crates.io/api/v1/crates/perch";
 
-    let agent = ureq::AgentBuilder::new().timeout(timeout).build();
-
-    let result = agent
+    let result = agent(timeout)
         .get(url)
         .set("User-Agent", &format!("perch/{VERSION}"))
         .call();
@@ -63,6 +64,53 @@ pub fn check_for_updates_crates_io_timeout(timeout: Duration) -> VersionCheck {
     }
 }
 
+/// Release notes for `version` from its GitHub release, cached on disk so
+/// they're only fetched once. `None` if there are none or they can't be
+/// fetched.
+pub fn fetch_release_notes(version: &str) -> Option<String> {
+    let cache = crate::paths::release_notes_dir()
+        .ok()
+        .map(|dir| dir.join(format!("v{version}.md")));
+    if let Some(notes) = cache
+        .as_ref()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+    {
+        return Some(notes);
+    }
+
+    let repo = crate::REPO_URL.strip_prefix("https://github.com/")?;
+    let url = format!("https://api.github.com/repos/{repo}/releases/tags/v{version}");
+    let json: serde_json::Value = agent(Duration::from_secs(10))
+        .get(&url)
+        .set("User-Agent", &format!("perch/{VERSION}"))
+        .set("Accept", "application/vnd.github+json")
+        .call()
+        .ok()?
+        .into_json()
+        .ok()?;
+    let notes = json.get("body")?.as_str()?.trim().replace("\r\n", "\n");
+    if notes.is_empty() {
+        return None;
+    }
+
+    if let Some(path) = cache {
+        let _ = std::fs::write(path, &notes);
+    }
+    Some(notes)
+}
+
+/// An HTTP agent for update requests, going through the configured proxy
+fn agent(timeout: Duration) -> ureq::Agent {
+    let mut agent = ureq::AgentBuilder::new().timeout(timeout);
+    if let Some(proxy) = crate::api::network_options()
+        .and_then(crate::api::NetworkOptions::proxy_url)
+        .and_then(|url| ureq::Proxy::new(url).ok())
+    {
+        agent = agent.proxy(proxy);
+    }
+    agent.build()
+}
+
 /// Compare semver versions, returns true if `latest` is newer than `current`
 fn version_is_newer(latest: &str, current: &str) -> bool {
     let parse = |v: &str| -> Vec<u32> { v.split('.').filter_map(|s| s.parse().ok()).collect() };
@@ -93,6 +141,12 @@ pub enum PackageManager {
         /// The formula name (e.g., "perch" or "ricardodantas/tap/perch")
         formula: String,
     },
+    /// A prebuilt binary from GitHub releases, replaced in place
+    #[cfg(feature = "self-update")]
+    Binary {
+        /// Where the running binary lives
+        path: std::path::PathBuf,
+    },
 }
 
 impl PackageManager {
@@ -101,6 +155,8 @@ impl PackageManager {
         match self {
             Self::Cargo => "cargo",
             Self::Homebrew { .. } => "brew",
+            #[cfg(feature = "self-update")]
+            Self::Binary { .. } => "GitHub release",
         }
     }
 
@@ -109,6 +165,8 @@ impl PackageManager {
         match self {
             Self::Cargo => "cargo install perch".to_string(),
             Self::Homebrew { formula } => format!("brew upgrade {formula}"),
+            #[cfg(feature = "self-update")]
+            Self::Binary { path } => format!("download into {}", path.display()),
         }
     }
 }
@@ -136,17 +194,29 @@ pub fn detect_package_manager() -> PackageManager {
                 formula: "perch".to_string(),
             };
         }
+
+        // Only binaries no package manager owns replace themselves
+        #[cfg(feature = "self-update")]
+        if release::is_standalone(&exe_path) {
+            return PackageManager::Binary { path: exe_path };
+        }
     }
 
     PackageManager::Cargo
 }
 
-/// Run the update command and return the result
-pub fn run_update(pm: &PackageManager) -> Result<(), String> {
+/// Update to `version` and return the result, reporting each step to
+/// `progress`
+pub fn run_update(
+    pm: &PackageManager,
+    version: &str,
+    mut progress: impl FnMut(&str),
+) -> Result<(), String> {
     use std::process::Stdio;
 
     match pm {
         PackageManager::Cargo => {
+            progress(&format!("Installing v{version} with cargo..."));
             match std::process::Command::new("cargo")
                 .args(["install", "perch"])
                 .stdout(Stdio::null())
@@ -159,12 +229,14 @@ pub fn run_update(pm: &PackageManager) -> Result<(), String> {
             }
         }
         PackageManager::Homebrew { formula } => {
+            progress("Running brew update...");
             let _ = std::process::Command::new("brew")
                 .args(["update"])
                 .stdout(Stdio::null())
                 .stderr(Stdio::null())
                 .status();
 
+            progress(&format!("Running brew upgrade {formula}..."));
             match std::process::Command::new("brew")
                 .args(["upgrade", formula])
                 .stdout(Stdio::null())
@@ -187,5 +259,7 @@ pub fn run_update(pm: &PackageManager) -> Result<(), String> {
                 Err(e) => Err(format!("Failed to run brew: {e}")),
             }
         }
+        #[cfg(feature = "self-update")]
+        PackageManager::Binary { path } => release::update(path, version, &mut progress),
     }
 }
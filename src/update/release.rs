@@ -0,0 +1,291 @@
+//! Updating a prebuilt binary from GitHub releases (the `self-update` feature)
+//!
+//! Each release has a `perch-<version>-<target>.tar.gz` archive per platform
+//! and a `SHA256SUMS.txt` listing their checksums. The archive is checked
+//! against that list before anything is touched, and the new binary is
+//! written next to the old one and renamed over it, so a failed update
+//! leaves the running binary as it was.
+
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+
+/// Timeout for downloading the release archive
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_mins(5);
+
+/// Name of the binary inside the release archive
+const BINARY_NAME: &str = "perch";
+
+/// Install locations that belong to a package manager, which must never
+/// be replaced behind its back
+const MANAGED_PATHS: &[&str] = &[
+    "/.cargo/",
+    "/Cellar/",
+    "/homebrew/",
+    "/linuxbrew/",
+    "/nix/",
+    "/snap/",
+    "/flatpak/",
+];
+
+/// The release target this binary was built for, if releases include it
+const fn target() -> Option<&'static str> {
+    if cfg!(all(
+        target_os = "linux",
+        target_arch = "x86_64",
+        target_env = "musl"
+    )) {
+        Some("x86_64-unknown-linux-musl")
+    } else if cfg!(all(
+        target_os = "linux",
+        target_arch = "x86_64",
+        target_env = "gnu"
+    )) {
+        Some("x86_64-unknown-linux-gnu")
+    } else if cfg!(all(
+        target_os = "linux",
+        target_arch = "aarch64",
+        target_env = "gnu"
+    )) {
+        Some("aarch64-unknown-linux-gnu")
+    } else if cfg!(all(target_os = "macos", target_arch = "x86_64")) {
+        Some("x86_64-apple-darwin")
+    } else if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+        Some("aarch64-apple-darwin")
+    } else {
+        None
+    }
+}
+
+/// Whether `exe` was unpacked from a release by hand rather than installed
+/// by a package manager (including the system's, under `/usr`)
+pub(super) fn is_standalone(exe: &Path) -> bool {
+    let path = exe.to_string_lossy();
+    let cargo_home = std::env::var_os("CARGO_HOME").map(std::path::PathBuf::from);
+
+    target().is_some()
+        && !MANAGED_PATHS.iter().any(|managed| path.contains(managed))
+        && (!path.starts_with("/usr/") || path.starts_with("/usr/local/"))
+        && !cargo_home.is_some_and(|home| exe.starts_with(home))
+}
+
+/// Download `version`, check it and put it in place of the binary at `exe`
+pub(super) fn update(
+    exe: &Path,
+    version: &str,
+    progress: &mut impl FnMut(&str),
+) -> Result<(), String> {
+    let target = target().ok_or("There is no release build for this platform")?;
+    let asset = format!("perch-{version}-{target}.tar.gz");
+    let base = format!("{}/releases/download/v{version}", crate::REPO_URL);
+    let agent = super::agent(DOWNLOAD_TIMEOUT);
+
+    progress("Fetching checksums...");
+    let sums = get(&agent, &format!("{base}/SHA256SUMS.txt"))?
+        .into_string()
+        .map_err(|e| format!("Failed to read checksums: {e}"))?;
+    let expected =
+        checksum_for(&sums, &asset).ok_or_else(|| format!("{asset} isn't in the checksums"))?;
+
+    let archive = download(&agent, &format!("{base}/{asset}"), progress)?;
+
+    progress("Verifying checksum...");
+    if sha256_hex(&archive) != expected.to_ascii_lowercase() {
+        return Err(format!(
+            "Checksum mismatch for {asset}, nothing was changed"
+        ));
+    }
+    let mut tar = Vec::new();
+    flate2::read::GzDecoder::new(archive.as_slice())
+        .read_to_end(&mut tar)
+        .map_err(|e| format!("Failed to unpack {asset}: {e}"))?;
+    let binary =
+        tar_entry(&tar, BINARY_NAME).ok_or_else(|| format!("{asset} has no {BINARY_NAME}"))?;
+
+    progress("Installing...");
+    replace(exe, binary)
+}
+
+fn get(agent: &ureq::Agent, url: &str) -> Result<ureq::Response, String> {
+    agent
+        .get(url)
+        .set("User-Agent", &format!("perch/{}", super::VERSION))
+        .call()
+        .map_err(|e| format!("Request failed: {e}"))
+}
+
+/// Download a file, reporting how much has arrived
+fn download(
+    agent: &ureq::Agent,
+    url: &str,
+    progress: &mut impl FnMut(&str),
+) -> Result<Vec<u8>, String> {
+    progress("Downloading...");
+    let response = get(agent, url)?;
+    let total: Option<usize> = response
+        .header("Content-Length")
+        .and_then(|len| len.parse().ok());
+
+    let mut reader = response.into_reader();
+    let mut data = Vec::with_capacity(total.unwrap_or_default());
+    let mut chunk = vec![0; 64 * 1024];
+    let mut last_percent = None;
+    loop {
+        let read = reader
+            .read(&mut chunk)
+            .map_err(|e| format!("Download failed: {e}"))?;
+        if read == 0 {
+            break;
+        }
+        data.extend_from_slice(&chunk[..read]);
+
+        if let Some(total) = total.filter(|&t| t > 0) {
+            let percent = (data.len() * 100 / total).min(100);
+            if last_percent != Some(percent) {
+                last_percent = Some(percent);
+                progress(&format!("Downloading... {percent}%"));
+            }
+        }
+    }
+    Ok(data)
+}
+
+/// The checksum `SHA256SUMS.txt` lists for `asset`
+fn checksum_for<'a>(sums: &'a str, asset: &str) -> Option<&'a str> {
+    sums.lines().find_map(|line| {
+        let (hash, name) = line.split_once(char::is_whitespace)?;
+        // `sha256sum` marks binary-mode entries with `*`
+        let name = name.trim().trim_start_matches('*');
+        (name == asset).then_some(hash)
+    })
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    use std::fmt::Write;
+
+    Sha256::digest(data)
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, b| {
+            let _ = write!(hex, "{b:02x}");
+            hex
+        })
+}
+
+/// The contents of the regular file called `name` in a tar archive
+fn tar_entry<'a>(tar: &'a [u8], name: &str) -> Option<&'a [u8]> {
+    const BLOCK: usize = 512;
+
+    let mut offset = 0;
+    while let Some(header) = tar.get(offset..offset + BLOCK) {
+        // Two zero blocks end the archive
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+        let entry_name = tar_str(&header[..100]);
+        let size = usize::from_str_radix(tar_str(&header[124..136]).trim(), 8).ok()?;
+        let is_file = matches!(header[156], b'0' | 0);
+        let start = offset + BLOCK;
+
+        if is_file && entry_name.trim_start_matches("./") == name {
+            return tar.get(start..start + size);
+        }
+        offset = start + size.div_ceil(BLOCK) * BLOCK;
+    }
+    None
+}
+
+/// A NUL-padded header field
+fn tar_str(field: &[u8]) -> &str {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    std::str::from_utf8(&field[..end]).unwrap_or_default()
+}
+
+/// Write the new binary beside `exe` and rename it over the old one
+fn replace(exe: &Path, binary: &[u8]) -> Result<(), String> {
+    let tmp = exe.with_file_name(format!(".{BINARY_NAME}.update"));
+    let write = || -> std::io::Result<()> {
+        fs::write(&tmp, binary)?;
+        fs::set_permissions(&tmp, fs::metadata(exe)?.permissions())?;
+        fs::File::open(&tmp)?.sync_all()?;
+        fs::rename(&tmp, exe)
+    };
+
+    write().map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("Failed to replace {}: {e}", exe.display())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tar archive with one regular file per entry
+    fn tar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut tar = Vec::new();
+        for (name, data) in entries {
+            let mut header = [0u8; 512];
+            header[..name.len()].copy_from_slice(name.as_bytes());
+            let size = format!("{:011o}", data.len());
+            header[124..135].copy_from_slice(size.as_bytes());
+            header[156] = b'0';
+            tar.extend_from_slice(&header);
+            tar.extend_from_slice(data);
+            tar.resize(tar.len().div_ceil(512) * 512, 0);
+        }
+        tar.resize(tar.len() + 1024, 0);
+        tar
+    }
+
+    #[test]
+    fn test_tar_entry() {
+        let archive = tar(&[("README.md", &[b'x'; 600]), ("./perch", b"binary")]);
+        assert_eq!(tar_entry(&archive, "perch"), Some(&b"binary"[..]));
+        assert_eq!(tar_entry(&archive, "README.md").map(<[u8]>::len), Some(600));
+        assert_eq!(tar_entry(&archive, "missing"), None);
+        assert_eq!(tar_entry(&[], "perch"), None);
+    }
+
+    #[test]
+    fn test_checksum_for() {
+        let sums = "aaa  perch-1.0.0-x86_64-apple-darwin.tar.gz\n\
+                    bbb *perch-1.0.0-x86_64-unknown-linux-gnu.tar.gz\n";
+        assert_eq!(
+            checksum_for(sums, "perch-1.0.0-x86_64-unknown-linux-gnu.tar.gz"),
+            Some("bbb")
+        );
+        assert_eq!(checksum_for(sums, "perch-1.0.0-x86_64.tar.gz"), None);
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_package_manager_installs_are_not_standalone() {
+        assert!(!is_standalone(Path::new("/home/me/.cargo/bin/perch")));
+        assert!(!is_standalone(Path::new(
+            "/opt/homebrew/Cellar/perch/1.0/bin/perch"
+        )));
+        assert!(!is_standalone(Path::new("/usr/bin/perch")));
+        assert!(!is_standalone(Path::new("/nix/store/abc-perch/bin/perch")));
+        if target().is_some() {
+            assert!(is_standalone(Path::new("/home/me/.local/bin/perch")));
+            assert!(is_standalone(Path::new("/usr/local/bin/perch")));
+        }
+    }
+
+    #[test]
+    fn test_replace() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = dir.path().join("perch");
+        fs::write(&exe, b"old").unwrap();
+
+        replace(&exe, b"new").unwrap();
+        assert_eq!(fs::read(&exe).unwrap(), b"new");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}